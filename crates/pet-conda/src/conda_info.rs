@@ -2,8 +2,11 @@
 // Licensed under the MIT License.
 
 use log::{error, trace, warn};
-use pet_fs::path::resolve_symlink;
-use pet_python_utils::executable::new_silent_command;
+use pet_fs::{
+    file_system::{FileSystem, OsFileSystem},
+    path::resolve_symlink,
+};
+use pet_python_utils::{env::is_spawn_allowed, executable::new_silent_command};
 use std::path::PathBuf;
 
 #[derive(Debug, serde::Deserialize)]
@@ -37,6 +40,14 @@ pub struct CondaInfoJson {
 
 impl CondaInfo {
     pub fn from(executable: Option<PathBuf>) -> Option<CondaInfo> {
+        CondaInfo::from_in(&OsFileSystem, executable)
+    }
+    /// Same as `from`, conda is not spawned when the file system is that of an alternate root directory.
+    pub fn from_in(file_system: &dyn FileSystem, executable: Option<PathBuf>) -> Option<CondaInfo> {
+        if !is_spawn_allowed(file_system) {
+            trace!("Not spawning conda, spawning is disabled");
+            return None;
+        }
         // let using_default = executable.is_none() || executable == Some("conda".into());
         // Possible we got a symlink to the conda exe, first try to resolve that.
         let executable = if cfg!(windows) {
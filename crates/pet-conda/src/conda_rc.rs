@@ -1,12 +1,15 @@
 // Copyright (c) Microsoft Corporation. All rights reserved.
 // Licensed under the MIT License.
 
-use crate::env_variables::EnvVariables;
+use crate::{env_variables::EnvVariables, utils::change_root_of_path};
 use log::trace;
-use pet_fs::path::expand_path;
+use pet_fs::{
+    file_system::{FileSystem, OsFileSystem},
+    path::expand_path,
+};
 use std::{
     collections::HashSet,
-    env, fs,
+    env,
     path::{Path, PathBuf},
 };
 use yaml_rust2::YamlLoader;
@@ -19,10 +22,18 @@ pub struct Condarc {
 
 impl Condarc {
     pub fn from(env_vars: &EnvVariables) -> Option<Condarc> {
-        get_conda_conda_rc(env_vars)
+        Self::from_in(&OsFileSystem, env_vars)
+    }
+    /// Same as `from`, looking at the files in the given file system.
+    pub fn from_in(file_system: &dyn FileSystem, env_vars: &EnvVariables) -> Option<Condarc> {
+        get_conda_conda_rc(file_system, env_vars)
     }
     pub fn from_path(path: &Path) -> Option<Condarc> {
-        get_conda_conda_rc_from_path(&path.to_path_buf())
+        Self::from_path_in(&OsFileSystem, path)
+    }
+    /// Same as `from_path`, looking at the files in the given file system.
+    pub fn from_path_in(file_system: &dyn FileSystem, path: &Path) -> Option<Condarc> {
+        get_conda_conda_rc_from_path(file_system, path)
     }
 }
 
@@ -30,8 +41,6 @@ impl Condarc {
 // https://conda.io/projects/conda/en/latest/user-guide/configuration/use-condarc.html#searching-for-condarc
 // https://github.com/conda/conda/blob/3ae5d7cf6cbe2b0ff9532359456b7244ae1ea5ef/conda/base/constants.py#L28
 pub fn get_conda_rc_search_paths(env_vars: &EnvVariables) -> Vec<PathBuf> {
-    use crate::utils::change_root_of_path;
-
     let mut search_paths: Vec<PathBuf> = vec![];
 
     if std::env::consts::OS == "windows" {
@@ -127,7 +136,7 @@ pub fn get_conda_rc_search_paths(env_vars: &EnvVariables) -> Vec<PathBuf> {
             ]
             .iter()
             .map(PathBuf::from)
-            // This is done only for testing purposes, hacky, but we need some tests with different paths.
+            // The alternate root directory, tests use this to create these files in a test folder.
             .map(|p| change_root_of_path(&p, &env_vars.root))
             .collect(),
         );
@@ -203,12 +212,17 @@ static SUPPORTED_EXTENSIONS: &[&str] = &["yaml", "yml"];
  * https://conda.io/projects/conda/en/latest/user-guide/configuration/use-condarc.html#searching-for-condarc
  * https://github.com/conda/conda/blob/3ae5d7cf6cbe2b0ff9532359456b7244ae1ea5ef/conda/base/constants.py#L28
  */
-fn get_conda_conda_rc(env_vars: &EnvVariables) -> Option<Condarc> {
+fn get_conda_conda_rc(file_system: &dyn FileSystem, env_vars: &EnvVariables) -> Option<Condarc> {
     let mut env_dirs = vec![];
     let mut files = vec![];
     for conda_rc in get_conda_rc_search_paths(env_vars).into_iter() {
-        if let Some(ref mut cfg) = get_conda_conda_rc_from_path(&conda_rc) {
-            env_dirs.append(&mut cfg.env_dirs);
+        if let Some(ref mut cfg) = get_conda_conda_rc_from_path(file_system, &conda_rc) {
+            // The directories are those of the image when searching an alternate root.
+            env_dirs.extend(
+                cfg.env_dirs
+                    .drain(..)
+                    .map(|dir| file_system.to_host_path(&dir)),
+            );
             files.append(&mut cfg.files);
         }
     }
@@ -220,25 +234,21 @@ fn get_conda_conda_rc(env_vars: &EnvVariables) -> Option<Condarc> {
     }
 }
 
-fn get_conda_conda_rc_from_path(conda_rc: &PathBuf) -> Option<Condarc> {
+fn get_conda_conda_rc_from_path(file_system: &dyn FileSystem, conda_rc: &Path) -> Option<Condarc> {
     let mut env_dirs = vec![];
     let mut files = vec![];
-    if conda_rc.is_file() {
-        if let Some(ref mut cfg) = parse_conda_rc(conda_rc) {
+    if file_system.is_file(conda_rc) {
+        if let Some(ref mut cfg) = parse_conda_rc(file_system, conda_rc) {
             env_dirs.append(&mut cfg.env_dirs);
-            files.push(conda_rc.clone());
+            files.push(conda_rc.to_path_buf());
         }
-    } else if conda_rc.is_dir() {
+    } else if file_system.is_dir(conda_rc) {
         // There can be different types of conda rc files in the directory.
         // .condarc, condarc, .condarc.yml, condarc.yaml, etc.
         // https://github.com/conda/conda/blob/3ae5d7cf6cbe2b0ff9532359456b7244ae1ea5ef/conda/common/configuration.py#L1315
         // https://conda.io/projects/conda/en/latest/user-guide/configuration/use-condarc.html
-        if let Ok(reader) = fs::read_dir(conda_rc) {
-            for path in reader
-                .filter_map(Result::ok)
-                .map(|e| e.path())
-                .filter(|p| p.is_file())
-            {
+        if let Ok(reader) = file_system.read_dir(conda_rc) {
+            for path in reader.into_iter().filter(|p| file_system.is_file(p)) {
                 let file_name = path
                     .file_name()
                     .unwrap()
@@ -256,7 +266,7 @@ fn get_conda_conda_rc_from_path(conda_rc: &PathBuf) -> Option<Condarc> {
                     || SUPPORTED_EXTENSIONS.contains(&extension.as_str())
                     || file_name.contains("condarc")
                 {
-                    if let Some(ref mut cfg) = parse_conda_rc(&path) {
+                    if let Some(ref mut cfg) = parse_conda_rc(file_system, &path) {
                         env_dirs.append(&mut cfg.env_dirs);
                         files.push(path);
                     }
@@ -273,8 +283,8 @@ fn get_conda_conda_rc_from_path(conda_rc: &PathBuf) -> Option<Condarc> {
     }
 }
 
-fn parse_conda_rc(conda_rc: &Path) -> Option<Condarc> {
-    let reader = fs::read_to_string(conda_rc).ok()?;
+fn parse_conda_rc(file_system: &dyn FileSystem, conda_rc: &Path) -> Option<Condarc> {
+    let reader = file_system.read_to_string(conda_rc).ok()?;
     if let Some(cfg) = parse_conda_rc_contents(&reader) {
         trace!("conda_rc: {:?} with env_dirs {:?}", conda_rc, cfg.env_dirs);
         Some(Condarc {
@@ -4,17 +4,19 @@
 use crate::{
     conda_rc::{get_conda_rc_search_paths, Condarc},
     env_variables::EnvVariables,
-    manager::{find_conda_binary, find_mamba_binary},
-    utils::{is_conda_env, is_conda_install},
+    manager::{find_conda_binary_in, find_mamba_binary_in},
+    utils::{change_root_of_path, is_conda_env_in, is_conda_install_in},
 };
 use log::trace;
-use pet_fs::path::{expand_path, norm_case};
+use pet_fs::{
+    file_system::{FileSystem, OsFileSystem},
+    path::{expand_path, norm_case},
+};
 use pet_python_utils::platform_dirs::Platformdirs;
 use rayon::prelude::*;
 use std::{
-    env, fs,
+    env,
     path::{Path, PathBuf},
-    thread,
     time::SystemTime,
 };
 
@@ -65,22 +67,33 @@ fn is_windows_drive_mount(path: &Path) -> bool {
 pub fn get_conda_environment_paths(
     env_vars: &EnvVariables,
     conda_executable: &Option<PathBuf>,
+) -> Vec<PathBuf> {
+    get_conda_environment_paths_in(&OsFileSystem, env_vars, conda_executable)
+}
+
+/// Same as `get_conda_environment_paths`, looking at the files in the given file system.
+pub fn get_conda_environment_paths_in(
+    file_system: &dyn FileSystem,
+    env_vars: &EnvVariables,
+    conda_executable: &Option<PathBuf>,
 ) -> Vec<PathBuf> {
     let start = SystemTime::now();
-    let mut env_paths = thread::scope(|s| {
-        let mut envs = vec![];
-        for thread in [
-            s.spawn(|| get_conda_envs_from_environment_txt(env_vars)),
-            s.spawn(|| get_conda_environment_paths_from_conda_rc(env_vars)),
-            s.spawn(|| get_conda_environment_paths_from_known_paths(env_vars)),
-            s.spawn(|| get_known_conda_install_locations(env_vars, conda_executable)),
-        ] {
-            if let Ok(mut env_paths) = thread.join() {
-                envs.append(&mut env_paths);
-            }
-        }
-        envs
-    });
+    // Runs on the pool of the caller (i.e. the discovery pool), instead of a thread per source.
+    let ((environment_txt, conda_rc), (known_paths, install_locations)) = rayon::join(
+        || {
+            rayon::join(
+                || get_conda_envs_from_environment_txt_in(file_system, env_vars),
+                || get_conda_environment_paths_from_conda_rc(file_system, env_vars),
+            )
+        },
+        || {
+            rayon::join(
+                || get_conda_environment_paths_from_known_paths(file_system, env_vars),
+                || get_known_conda_install_locations_in(file_system, env_vars, conda_executable),
+            )
+        },
+    );
+    let mut env_paths = [environment_txt, conda_rc, known_paths, install_locations].concat();
 
     env_paths = env_paths.iter().map(norm_case).collect();
     env_paths.sort();
@@ -107,8 +120,8 @@ pub fn get_conda_environment_paths(
     // & then iterate through the list of envs in the envs directory.
     let mut result: Vec<PathBuf> = env_paths
         .par_iter()
-        .filter(|f| f.exists())
-        .flat_map(|path| get_environments(path))
+        .filter(|f| file_system.exists(f))
+        .flat_map(|path| get_environments_in(file_system, path))
         .collect();
 
     result.sort();
@@ -124,16 +137,19 @@ pub fn get_conda_environment_paths(
  * Get the list of conda environments found in conda rc files
  * as well as the directories where conda rc files can be found.
  */
-fn get_conda_environment_paths_from_conda_rc(env_vars: &EnvVariables) -> Vec<PathBuf> {
+fn get_conda_environment_paths_from_conda_rc(
+    file_system: &dyn FileSystem,
+    env_vars: &EnvVariables,
+) -> Vec<PathBuf> {
     // Use the conda rc directories as well.
     let mut env_dirs = vec![];
     for rc_file_dir in get_conda_rc_search_paths(env_vars) {
-        if !rc_file_dir.exists() {
+        if !file_system.exists(&rc_file_dir) {
             trace!(".condarc not found ({:?})", rc_file_dir);
             continue;
         }
 
-        if let Some(conda_rc) = Condarc::from_path(&rc_file_dir) {
+        if let Some(conda_rc) = Condarc::from_path_in(file_system, &rc_file_dir) {
             trace!(
                 "Conda environments in .condarc {:?} {:?}",
                 conda_rc.files,
@@ -144,21 +160,21 @@ fn get_conda_environment_paths_from_conda_rc(env_vars: &EnvVariables) -> Vec<Pat
                     .env_dirs
                     .clone()
                     .into_iter()
-                    .filter(|f| f.exists())
+                    .filter(|f| file_system.exists(f))
                     .collect(),
             );
         }
 
-        if rc_file_dir.is_dir() {
+        if file_system.is_dir(&rc_file_dir) {
             env_dirs.push(rc_file_dir);
-        } else if rc_file_dir.is_file() {
+        } else if file_system.is_file(&rc_file_dir) {
             if let Some(dir) = rc_file_dir.parent() {
                 env_dirs.push(dir.to_path_buf());
             }
         }
     }
 
-    if let Some(conda_rc) = Condarc::from(env_vars) {
+    if let Some(conda_rc) = Condarc::from_in(file_system, env_vars) {
         trace!(
             "Conda environments in .condarc {:?} {:?}",
             conda_rc.files,
@@ -171,7 +187,10 @@ fn get_conda_environment_paths_from_conda_rc(env_vars: &EnvVariables) -> Vec<Pat
     env_dirs
 }
 
-fn get_conda_environment_paths_from_known_paths(env_vars: &EnvVariables) -> Vec<PathBuf> {
+fn get_conda_environment_paths_from_known_paths(
+    file_system: &dyn FileSystem,
+    env_vars: &EnvVariables,
+) -> Vec<PathBuf> {
     let mut env_paths: Vec<PathBuf> = vec![];
     if let Some(ref home) = env_vars.home {
         let mut known_conda_paths = vec![
@@ -212,44 +231,47 @@ fn get_conda_environment_paths_from_known_paths(env_vars: &EnvVariables) -> Vec<
         }
 
         for path in known_conda_paths {
-            if let Ok(entries) = fs::read_dir(path) {
-                for entry in entries.filter_map(Result::ok) {
-                    let path = entry.path();
-                    if path.is_dir() {
-                        env_paths.push(path);
-                    }
-                }
+            if let Ok(entries) = file_system.read_dir(&path) {
+                env_paths.extend(entries.into_iter().filter(|p| file_system.is_dir(p)));
             }
         }
     }
     env_paths.append(&mut env_vars.known_global_search_locations.clone());
     env_paths.sort();
     env_paths.dedup();
-    let env_paths = env_paths.into_iter().filter(|f| f.exists()).collect();
+    let env_paths = env_paths
+        .into_iter()
+        .filter(|f| file_system.exists(f))
+        .collect();
     trace!("Conda environments in known paths {:?}", env_paths);
     env_paths
 }
 
 pub fn get_environments(conda_dir: &Path) -> Vec<PathBuf> {
+    get_environments_in(&OsFileSystem, conda_dir)
+}
+
+/// Same as `get_environments`, looking at the files in the given file system.
+pub fn get_environments_in(file_system: &dyn FileSystem, conda_dir: &Path) -> Vec<PathBuf> {
     let mut envs: Vec<PathBuf> = vec![];
+    let conda_envs_in = |dir: &Path| -> Vec<PathBuf> {
+        file_system
+            .read_dir(dir)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|p| is_conda_env_in(file_system, p))
+            .collect()
+    };
 
-    if is_conda_install(conda_dir) {
+    if is_conda_install_in(file_system, conda_dir) {
         envs.push(conda_dir.to_path_buf());
 
-        if let Ok(entries) = fs::read_dir(conda_dir.join("envs")) {
-            envs.append(
-                &mut entries
-                    .filter_map(Result::ok)
-                    .map(|e| e.path())
-                    .filter(|p| is_conda_env(p))
-                    .collect(),
-            );
-        }
+        envs.append(&mut conda_envs_in(&conda_dir.join("envs")));
         // Then read the .condarc in the conda install folder as well.
-        if let Some(mut conda_rc) = Condarc::from_path(conda_dir) {
+        if let Some(mut conda_rc) = Condarc::from_path_in(file_system, conda_dir) {
             envs.append(&mut conda_rc.env_dirs);
         }
-    } else if is_conda_env(conda_dir) {
+    } else if is_conda_env_in(file_system, conda_dir) {
         envs.push(conda_dir.to_path_buf());
         // If this is a conda environment under an `envs` folder, check if the grandparent
         // is the conda install directory (base env) and include it as well.
@@ -260,38 +282,24 @@ pub fn get_environments(conda_dir: &Path) -> Vec<PathBuf> {
         if let Some(parent) = conda_dir.parent() {
             if parent.file_name().map(|n| n == "envs").unwrap_or(false) {
                 if let Some(grandparent) = parent.parent() {
-                    if is_conda_install(grandparent) && !envs.contains(&grandparent.to_path_buf()) {
+                    if is_conda_install_in(file_system, grandparent)
+                        && !envs.contains(&grandparent.to_path_buf())
+                    {
                         // Recursively get environments from the conda install directory
                         // This will add the base env and any other sibling envs
-                        envs.append(&mut get_environments(grandparent));
+                        envs.append(&mut get_environments_in(file_system, grandparent));
                     }
                 }
             }
         }
-    } else if conda_dir.join("envs").exists() {
+    } else if file_system.exists(&conda_dir.join("envs")) {
         // This could be a directory where conda environments are stored.
         // I.e. its not necessarily the root conda install directory.
         // E.g. C:\Users\donjayamanne\.conda
-        if let Ok(entries) = fs::read_dir(conda_dir.join("envs")) {
-            envs.append(
-                &mut entries
-                    .filter_map(Result::ok)
-                    .map(|e| e.path())
-                    .filter(|p| is_conda_env(p))
-                    .collect(),
-            );
-        }
+        envs.append(&mut conda_envs_in(&conda_dir.join("envs")));
     } else {
         // The dir could already be the `envs` directory.
-        if let Ok(entries) = fs::read_dir(conda_dir) {
-            envs.append(
-                &mut entries
-                    .filter_map(Result::ok)
-                    .map(|e| e.path())
-                    .filter(|p| is_conda_env(p))
-                    .collect(),
-            );
-        }
+        envs.append(&mut conda_envs_in(conda_dir));
     }
 
     envs.sort();
@@ -300,16 +308,25 @@ pub fn get_environments(conda_dir: &Path) -> Vec<PathBuf> {
 }
 
 pub fn get_conda_envs_from_environment_txt(env_vars: &EnvVariables) -> Vec<PathBuf> {
+    get_conda_envs_from_environment_txt_in(&OsFileSystem, env_vars)
+}
+
+/// Same as `get_conda_envs_from_environment_txt`, looking at the files in the given file system.
+pub fn get_conda_envs_from_environment_txt_in(
+    file_system: &dyn FileSystem,
+    env_vars: &EnvVariables,
+) -> Vec<PathBuf> {
     let mut envs: Vec<PathBuf> = vec![];
     if let Some(ref home) = env_vars.home {
         let home = Path::new(&home);
         let environment_txt = home.join(".conda").join("environments.txt");
-        if let Ok(reader) = fs::read_to_string(environment_txt.clone()) {
+        if let Ok(reader) = file_system.read_to_string(&environment_txt) {
             trace!("Found environments.txt file {:?}", environment_txt);
             for line in reader.lines() {
-                let line = norm_case(PathBuf::from(line.to_string()));
+                // The environments are those of the image when searching an alternate root.
+                let line = norm_case(file_system.to_host_path(Path::new(line)));
                 trace!("Conda env in environments.txt file {:?}", line);
-                if line.exists() {
+                if file_system.exists(&line) {
                     envs.push(line);
                 }
             }
@@ -323,13 +340,23 @@ pub fn get_conda_envs_from_environment_txt(env_vars: &EnvVariables) -> Vec<PathB
 pub fn get_known_conda_install_locations(
     env_vars: &EnvVariables,
     conda_executable: &Option<PathBuf>,
+) -> Vec<PathBuf> {
+    get_known_conda_install_locations_in(&OsFileSystem, env_vars, conda_executable)
+}
+
+/// Same as `get_known_conda_install_locations`, looking at the files in the given file system.
+#[cfg(windows)]
+pub fn get_known_conda_install_locations_in(
+    file_system: &dyn FileSystem,
+    env_vars: &EnvVariables,
+    conda_executable: &Option<PathBuf>,
 ) -> Vec<PathBuf> {
     use pet_fs::path::norm_case;
 
     // First, try to find conda from PATH - this handles conda installations on mapped drives
     // and other non-standard locations that aren't in the hardcoded search paths.
     let conda_from_path = if conda_executable.is_none() {
-        find_conda_binary(env_vars)
+        find_conda_binary_in(file_system, env_vars)
     } else {
         None
     };
@@ -425,15 +452,17 @@ pub fn get_known_conda_install_locations(
     // We do not want to have duplicates in different cases.
     // & we'd like to preserve the case of the original path as on disc.
     known_paths = known_paths.iter().map(norm_case).collect();
-    if let Some(conda_dir) = get_conda_dir_from_exe(conda_executable) {
+    if let Some(conda_dir) = get_conda_dir_from_exe_in(file_system, conda_executable) {
         known_paths.push(conda_dir);
     }
     // Add conda installation found from PATH (handles mapped drives and non-standard locations)
-    if let Some(conda_dir) = get_conda_dir_from_exe(&conda_from_path) {
+    if let Some(conda_dir) = get_conda_dir_from_exe_in(file_system, &conda_from_path) {
         known_paths.push(conda_dir);
     }
     // Also check for mamba/micromamba on PATH to discover its install directory
-    if let Some(mamba_dir) = get_conda_dir_from_exe(&find_mamba_binary(env_vars)) {
+    if let Some(mamba_dir) =
+        get_conda_dir_from_exe_in(file_system, &find_mamba_binary_in(file_system, env_vars))
+    {
         known_paths.push(mamba_dir);
     }
     known_paths.sort();
@@ -446,11 +475,21 @@ pub fn get_known_conda_install_locations(
 pub fn get_known_conda_install_locations(
     env_vars: &EnvVariables,
     conda_executable: &Option<PathBuf>,
+) -> Vec<PathBuf> {
+    get_known_conda_install_locations_in(&OsFileSystem, env_vars, conda_executable)
+}
+
+/// Same as `get_known_conda_install_locations`, looking at the files in the given file system.
+#[cfg(unix)]
+pub fn get_known_conda_install_locations_in(
+    file_system: &dyn FileSystem,
+    env_vars: &EnvVariables,
+    conda_executable: &Option<PathBuf>,
 ) -> Vec<PathBuf> {
     // First, try to find conda from PATH - this handles conda installations in
     // non-standard locations that aren't in the hardcoded search paths.
     let conda_from_path = if conda_executable.is_none() {
-        find_conda_binary(env_vars)
+        find_conda_binary_in(file_system, env_vars)
     } else {
         None
     };
@@ -513,32 +552,46 @@ pub fn get_known_conda_install_locations(
         known_paths.push(home.join(".conda"));
         known_paths.push(home.join(".local"));
     }
-    if let Some(conda_dir) = get_conda_dir_from_exe(conda_executable) {
+    if let Some(conda_dir) = get_conda_dir_from_exe_in(file_system, conda_executable) {
         known_paths.push(conda_dir);
     }
     // Add conda installation found from PATH (handles non-standard locations)
-    if let Some(conda_dir) = get_conda_dir_from_exe(&conda_from_path) {
+    if let Some(conda_dir) = get_conda_dir_from_exe_in(file_system, &conda_from_path) {
         known_paths.push(conda_dir);
     }
     // Also check for mamba/micromamba on PATH to discover its install directory
-    if let Some(mamba_dir) = get_conda_dir_from_exe(&find_mamba_binary(env_vars)) {
+    if let Some(mamba_dir) =
+        get_conda_dir_from_exe_in(file_system, &find_mamba_binary_in(file_system, env_vars))
+    {
         known_paths.push(mamba_dir);
     }
     known_paths.sort();
     known_paths.dedup();
-    known_paths.into_iter().filter(|f| f.exists()).collect()
+    known_paths
+        .into_iter()
+        .map(|path| change_root_of_path(&path, &env_vars.root))
+        .filter(|f| file_system.exists(f))
+        .collect()
 }
 
 pub fn get_conda_dir_from_exe(conda_executable: &Option<PathBuf>) -> Option<PathBuf> {
+    get_conda_dir_from_exe_in(&OsFileSystem, conda_executable)
+}
+
+/// Same as `get_conda_dir_from_exe`, looking at the files in the given file system.
+pub fn get_conda_dir_from_exe_in(
+    file_system: &dyn FileSystem,
+    conda_executable: &Option<PathBuf>,
+) -> Option<PathBuf> {
     if let Some(conda_executable) = conda_executable {
-        if conda_executable.is_file() {
+        if file_system.is_file(conda_executable) {
             if let Some(conda_dir) = conda_executable.parent() {
                 // Possible exe is in the install (root prefix) directory.
-                if is_conda_env(conda_dir) {
+                if is_conda_env_in(file_system, conda_dir) {
                     return Some(conda_dir.to_path_buf());
                 } else if let Some(conda_dir) = conda_dir.parent() {
                     // Possible the exe is in the `bin` or `Scripts` directory.
-                    if is_conda_env(conda_dir) {
+                    if is_conda_env_in(file_system, conda_dir) {
                         return Some(conda_dir.to_path_buf());
                     }
                 }
@@ -546,11 +599,11 @@ pub fn get_conda_dir_from_exe(conda_executable: &Option<PathBuf>) -> Option<Path
         } else {
             let conda_dir = conda_executable.clone();
             // Possible exe is in the install (root prefix) directory.
-            if is_conda_env(&conda_dir) {
+            if is_conda_env_in(file_system, &conda_dir) {
                 return Some(conda_dir.to_path_buf());
             } else if let Some(conda_dir) = conda_dir.parent() {
                 // Possible the exe is in the `bin` or `Scripts` directory.
-                if is_conda_env(conda_dir) {
+                if is_conda_env_in(file_system, conda_dir) {
                     return Some(conda_dir.to_path_buf());
                 }
             }
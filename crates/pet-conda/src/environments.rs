@@ -4,23 +4,28 @@
 use crate::{
     manager::CondaManager,
     package::{CondaPackageInfo, Package},
-    utils::{is_conda_env, is_conda_install},
+    utils::{is_conda_env_in, is_conda_install_in},
 };
 use log::{trace, warn};
 use pet_core::{
     arch::Architecture,
     manager::EnvManager,
     python_environment::{PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind},
+    python_version::PythonVersion,
 };
-use pet_fs::path::{norm_case, resolve_symlink};
-use pet_python_utils::executable::{find_executable, find_executables};
+use pet_fs::{
+    file_system::{FileSystem, OsFileSystem},
+    path::{norm_case, resolve_symlink_in},
+};
+use pet_python_utils::arch::get_architecture;
+use pet_python_utils::executable::{find_executable_in, find_executables_in};
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone)]
 pub struct CondaEnvironment {
     pub prefix: PathBuf,
     pub executable: Option<PathBuf>,
-    pub version: Option<String>,
+    pub version: Option<PythonVersion>,
     pub conda_dir: Option<PathBuf>,
     pub arch: Option<Architecture>,
     pub name: Option<String>,
@@ -32,13 +37,27 @@ impl CondaEnvironment {
     }
 
     pub fn to_python_environment(&self, conda_manager: Option<EnvManager>) -> PythonEnvironment {
+        self.to_python_environment_in(&OsFileSystem, conda_manager)
+    }
+
+    /// Same as `to_python_environment`, looking at the files in the given file system.
+    pub fn to_python_environment_in(
+        &self,
+        file_system: &dyn FileSystem,
+        conda_manager: Option<EnvManager>,
+    ) -> PythonEnvironment {
         // This is a root env.
         let builder = PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Conda))
             .executable(self.executable.clone())
             .version(self.version.clone())
             .prefix(Some(self.prefix.clone()))
-            .arch(self.arch.clone())
-            .symlinks(Some(find_executables(&self.prefix)))
+            .arch(
+                self.executable
+                    .as_deref()
+                    .and_then(get_architecture)
+                    .or(self.arch.clone()),
+            )
+            .symlinks(Some(find_executables_in(file_system, &self.prefix)))
             .name(self.name.clone())
             .manager(conda_manager);
 
@@ -50,12 +69,24 @@ pub fn get_conda_environment_info(
     env_path: &Path,
     manager: &Option<CondaManager>,
 ) -> Option<CondaEnvironment> {
-    get_conda_environment_info_with_history_reader(env_path, manager, |env_path| {
-        std::fs::read_to_string(env_path.join("conda-meta").join("history")).ok()
+    get_conda_environment_info_in(&OsFileSystem, env_path, manager)
+}
+
+/// Same as `get_conda_environment_info`, looking at the files in the given file system.
+pub fn get_conda_environment_info_in(
+    file_system: &dyn FileSystem,
+    env_path: &Path,
+    manager: &Option<CondaManager>,
+) -> Option<CondaEnvironment> {
+    get_conda_environment_info_with_history_reader(file_system, env_path, manager, |env_path| {
+        file_system
+            .read_to_string(&env_path.join("conda-meta").join("history"))
+            .ok()
     })
 }
 
 fn get_conda_environment_info_with_history_reader<F>(
+    file_system: &dyn FileSystem,
     env_path: &Path,
     manager: &Option<CondaManager>,
     read_history: F,
@@ -63,7 +94,7 @@ fn get_conda_environment_info_with_history_reader<F>(
 where
     F: FnOnce(&Path) -> Option<String>,
 {
-    if !is_conda_env(env_path) {
+    if !is_conda_env_in(file_system, env_path) {
         return None;
     }
 
@@ -71,13 +102,14 @@ where
     let creation_line = history.as_deref().and_then(get_conda_creation_line);
     let mut conda_install_folder =
         get_conda_installation_used_to_create_conda_env_from_creation_line(
+            file_system,
             env_path,
             creation_line.as_deref(),
         )
         .or_else(|| manager.clone().and_then(|manager| manager.conda_dir));
 
     if let Some(conda_dir) = &conda_install_folder {
-        if conda_dir.exists() {
+        if file_system.exists(conda_dir) {
             trace!(
                 "Conda install folder {}, found, & will be used for the Conda Env: {}",
                 conda_dir.display(),
@@ -95,16 +127,23 @@ where
         trace!("Conda install folder not found for {}", env_path.display());
     }
 
-    let executable = find_executable(env_path);
+    let executable = find_executable_in(file_system, env_path);
     let package_info = executable.as_ref().and_then(|_| {
-        CondaPackageInfo::from_history(env_path, &Package::Python, history.as_deref())
+        CondaPackageInfo::from_history(file_system, env_path, &Package::Python, history.as_deref())
     });
-    let name = get_conda_env_name(env_path, &conda_install_folder, creation_line.as_deref());
+    let name = get_conda_env_name(
+        file_system,
+        env_path,
+        &conda_install_folder,
+        creation_line.as_deref(),
+    );
 
     Some(CondaEnvironment {
         prefix: env_path.into(),
         executable,
-        version: package_info.as_ref().map(|info| info.version.clone()),
+        version: package_info
+            .as_ref()
+            .and_then(|info| PythonVersion::parse_lossless(&info.version)),
         conda_dir: conda_install_folder,
         arch: package_info.and_then(|info| info.arch),
         name,
@@ -116,38 +155,50 @@ where
  * This function returns the path to the conda installation that created the environment.
  */
 pub fn get_conda_installation_used_to_create_conda_env(env_path: &Path) -> Option<PathBuf> {
-    let history = std::fs::read_to_string(env_path.join("conda-meta").join("history")).ok();
+    get_conda_installation_used_to_create_conda_env_in(&OsFileSystem, env_path)
+}
+
+/// Same as `get_conda_installation_used_to_create_conda_env`, looking at the files in the given file system.
+pub fn get_conda_installation_used_to_create_conda_env_in(
+    file_system: &dyn FileSystem,
+    env_path: &Path,
+) -> Option<PathBuf> {
+    let history = file_system
+        .read_to_string(&env_path.join("conda-meta").join("history"))
+        .ok();
     let creation_line = history.as_deref().and_then(get_conda_creation_line);
     get_conda_installation_used_to_create_conda_env_from_creation_line(
+        file_system,
         env_path,
         creation_line.as_deref(),
     )
 }
 
 fn get_conda_installation_used_to_create_conda_env_from_creation_line(
+    file_system: &dyn FileSystem,
     env_path: &Path,
     creation_line: Option<&str>,
 ) -> Option<PathBuf> {
     if let Some(parent) = env_path.ancestors().nth(2) {
-        if is_conda_install(parent) {
+        if is_conda_install_in(file_system, parent) {
             return Some(parent.to_path_buf());
         }
     }
 
     if let Some(line) = creation_line {
-        if let Some(conda_dir) = get_conda_dir_from_cmd(line) {
-            if is_conda_install(&conda_dir) {
+        if let Some(conda_dir) = get_conda_dir_from_cmd(file_system, line) {
+            if is_conda_install_in(file_system, &conda_dir) {
                 return Some(conda_dir);
             }
             if let Some(conda_dir) = conda_dir.parent() {
-                if is_conda_install(conda_dir) {
+                if is_conda_install_in(file_system, conda_dir) {
                     return Some(conda_dir.into());
                 }
             }
         }
     }
 
-    if is_conda_install(env_path) {
+    if is_conda_install_in(file_system, env_path) {
         Some(env_path.to_path_buf())
     } else {
         None
@@ -169,11 +220,12 @@ fn get_conda_creation_line(history: &str) -> Option<String> {
 }
 
 fn get_conda_env_name(
+    file_system: &dyn FileSystem,
     prefix: &Path,
     conda_dir: &Option<PathBuf>,
     creation_line: Option<&str>,
 ) -> Option<String> {
-    let mut name = if is_conda_install(prefix) {
+    let mut name = if is_conda_install_in(file_system, prefix) {
         Some("base".to_string())
     } else {
         prefix
@@ -221,7 +273,7 @@ fn get_conda_executable_from_cmd(cmd_line: &str) -> Option<PathBuf> {
     (!executable.is_empty()).then(|| PathBuf::from(executable))
 }
 
-fn get_conda_dir_from_cmd(cmd_line: &str) -> Option<PathBuf> {
+fn get_conda_dir_from_cmd(file_system: &dyn FileSystem, cmd_line: &str) -> Option<PathBuf> {
     // Sample lines
     // # cmd: <conda install directory>\Scripts\conda-script.py create -n sample
     // # cmd: <conda install directory>\Scripts\conda-script.py create -p <full path>
@@ -229,7 +281,7 @@ fn get_conda_dir_from_cmd(cmd_line: &str) -> Option<PathBuf> {
     // cmd_line: "# cmd: /usr/bin/conda create -p ./prefix-envs/.conda1 python=3.12 -y"
     let conda_exe = get_conda_executable_from_cmd(cmd_line)?; // Sometimes the path can be as follows, where `/usr/bin/conda` could be a symlink.
                                                               // cmd_line: "# cmd: /usr/bin/conda create -p ./prefix-envs/.conda1 python=3.12 -y"
-    let conda_exe = resolve_symlink(&conda_exe).unwrap_or(conda_exe);
+    let conda_exe = resolve_symlink_in(file_system, &conda_exe).unwrap_or(conda_exe);
     if let Some(cmd_line) = conda_exe.parent() {
         if let Some(conda_dir) = cmd_line.file_name() {
             if conda_dir.to_string_lossy().to_lowercase() == "bin"
@@ -309,6 +361,38 @@ pub fn get_activation_command(
 mod tests {
     use super::*;
 
+    #[test]
+    #[cfg(unix)]
+    fn environment_info_looks_at_the_files_in_the_file_system() {
+        let file_system = pet_fs::file_system::MemoryFileSystem::new()
+            .file("/opt/miniconda3/conda-meta/history", "")
+            .file("/opt/miniconda3/condabin/conda", "")
+            .file(
+                "/opt/miniconda3/envs/demo/conda-meta/history",
+                "# cmd: /opt/miniconda3/bin/conda create -n demo python=3.12\n",
+            )
+            .file(
+                "/opt/miniconda3/envs/demo/conda-meta/python-3.12.4-h99e199e_1.json",
+                "{}",
+            )
+            .file("/opt/miniconda3/envs/demo/bin/python", "");
+
+        let env = get_conda_environment_info_in(
+            &file_system,
+            Path::new("/opt/miniconda3/envs/demo"),
+            &None,
+        )
+        .unwrap();
+
+        assert_eq!(env.name, Some("demo".to_string()));
+        assert_eq!(env.conda_dir, Some(PathBuf::from("/opt/miniconda3")));
+        assert_eq!(
+            env.executable,
+            Some(PathBuf::from("/opt/miniconda3/envs/demo/bin/python"))
+        );
+        assert_eq!(env.version, PythonVersion::parse("3.12.4"));
+    }
+
     #[test]
     fn parses_unicode_conda_executable_without_invalid_byte_indices() {
         let line = "# CMD: /Users/İpek/miniconda3/bin/conda CREATE -n sample";
@@ -322,19 +406,19 @@ mod tests {
     #[cfg(windows)]
     fn parse_cmd_line() {
         let line = "# cmd: C:\\Users\\donja\\miniconda3\\lib\\site-packages\\conda\\__main__.py create --yes --prefix .conda python=3.9";
-        let conda_dir = get_conda_dir_from_cmd(line).unwrap();
+        let conda_dir = get_conda_dir_from_cmd(&OsFileSystem, line).unwrap();
 
         assert_eq!(conda_dir, PathBuf::from("C:\\Users\\donja\\miniconda3"));
 
         let line =
             "# cmd: C:\\Users\\donja\\miniconda3\\Scripts\\conda-script.py create -n samlpe1";
-        let conda_dir = get_conda_dir_from_cmd(line).unwrap();
+        let conda_dir = get_conda_dir_from_cmd(&OsFileSystem, line).unwrap();
 
         assert_eq!(conda_dir, PathBuf::from("C:\\Users\\donja\\miniconda3"));
 
         // From root install folder
         let line = "# cmd: build.py --product miniconda --python 3.9 --installer-type exe --output-dir C:\\ci\\containers\\000029l07m4\\tmp\\build\\dd3144c1\\output-installer/220421/ --standalone C:\\ci\\containers\\000029l07m4\\tmp\\build\\dd3144c1\\mc/standalone_conda/conda.exe";
-        let conda_dir = get_conda_dir_from_cmd(line);
+        let conda_dir = get_conda_dir_from_cmd(&OsFileSystem, line);
 
         assert!(conda_dir.is_none());
     }
@@ -343,7 +427,7 @@ mod tests {
     #[cfg(unix)]
     fn parse_cmd_line() {
         let line = "# cmd: /Users/donjayamanne/.pyenv/versions/mambaforge-22.11.1-3/lib/python3.10/site-packages/conda/__main__.py create --yes --prefix .conda python=3.12";
-        let conda_dir = get_conda_dir_from_cmd(line).unwrap();
+        let conda_dir = get_conda_dir_from_cmd(&OsFileSystem, line).unwrap();
 
         assert_eq!(
             conda_dir,
@@ -394,7 +478,12 @@ mod tests {
 
         let history = std::fs::read_to_string(&history_file).unwrap();
         let creation_line = get_conda_creation_line(&history);
-        let name = get_conda_env_name(&env_path, &conda_dir, creation_line.as_deref());
+        let name = get_conda_env_name(
+            &OsFileSystem,
+            &env_path,
+            &conda_dir,
+            creation_line.as_deref(),
+        );
         assert!(
             name.is_none(),
             "Path-based external env should return None for name, got {:?}",
@@ -429,7 +518,12 @@ mod tests {
 
         let history = std::fs::read_to_string(&history_file).unwrap();
         let creation_line = get_conda_creation_line(&history);
-        let name = get_conda_env_name(&env_path, &conda_dir, creation_line.as_deref());
+        let name = get_conda_env_name(
+            &OsFileSystem,
+            &env_path,
+            &conda_dir,
+            creation_line.as_deref(),
+        );
         assert_eq!(
             name,
             Some("myenv".to_string()),
@@ -452,7 +546,7 @@ mod tests {
         std::fs::create_dir_all(&conda_meta_dir).unwrap();
 
         // When env is under conda_dir/envs/, name should be the folder name
-        let name = get_conda_env_name(&env_path, &Some(conda_dir), None);
+        let name = get_conda_env_name(&OsFileSystem, &env_path, &Some(conda_dir), None);
         assert_eq!(
             name,
             Some("myenv".to_string()),
@@ -477,7 +571,7 @@ mod tests {
         // conda_dir is known but env is NOT under it (external environment)
         let conda_dir = Some(std::path::PathBuf::from("/some/other/conda"));
 
-        let name = get_conda_env_name(&env_path, &conda_dir, None);
+        let name = get_conda_env_name(&OsFileSystem, &env_path, &conda_dir, None);
         assert!(
             name.is_none(),
             "External env without history should return None for safe path-based activation, got {:?}",
@@ -510,7 +604,12 @@ mod tests {
 
         let history = std::fs::read_to_string(&history_file).unwrap();
         let creation_line = get_conda_creation_line(&history);
-        let name = get_conda_env_name(&env_path, &conda_dir, creation_line.as_deref());
+        let name = get_conda_env_name(
+            &OsFileSystem,
+            &env_path,
+            &conda_dir,
+            creation_line.as_deref(),
+        );
         assert!(
             name.is_none(),
             "External env with mismatched name should return None, got {:?}",
@@ -528,11 +627,12 @@ mod tests {
         std::fs::create_dir_all(env_path.join("conda-meta")).unwrap();
 
         let reads = std::cell::Cell::new(0);
-        let environment = get_conda_environment_info_with_history_reader(&env_path, &None, |_| {
-            reads.set(reads.get() + 1);
-            Some("# cmd: conda create -p env\n+defaults::python-3.12.0-build".into())
-        })
-        .unwrap();
+        let environment =
+            get_conda_environment_info_with_history_reader(&OsFileSystem, &env_path, &None, |_| {
+                reads.set(reads.get() + 1);
+                Some("# cmd: conda create -p env\n+defaults::python-3.12.0-build".into())
+            })
+            .unwrap();
 
         assert_eq!(reads.get(), 1);
         assert_eq!(environment.prefix, env_path);
@@ -4,10 +4,10 @@
 use conda_info::CondaInfo;
 use env_variables::EnvVariables;
 use environment_locations::{
-    get_conda_dir_from_exe, get_conda_environment_paths, get_conda_envs_from_environment_txt,
-    get_environments,
+    get_conda_dir_from_exe_in, get_conda_environment_paths_in,
+    get_conda_envs_from_environment_txt_in, get_environments_in,
 };
-use environments::{get_conda_environment_info, CondaEnvironment};
+use environments::{get_conda_environment_info_in, CondaEnvironment};
 use log::error;
 use manager::{get_mamba_manager, is_mamba_executable, CondaManager};
 use pet_core::{
@@ -16,21 +16,23 @@ use pet_core::{
     os_environment::Environment,
     python_environment::{PythonEnvironment, PythonEnvironmentKind},
     reporter::Reporter,
+    search_root::{limit_to_search_root_budget, SearchRootBudget, Unlimited},
     Locator, LocatorKind, RefreshStatePersistence, RefreshStateSyncScope,
 };
-use pet_fs::path::norm_case;
+use pet_fs::{
+    file_system::{FileMetadata, FileSystem},
+    path::norm_case,
+};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashMap, HashSet},
-    fs,
     path::{Path, PathBuf},
     sync::{Arc, RwLock},
-    thread,
     time::SystemTime,
 };
 use telemetry::{get_conda_rcs_and_env_dirs, report_missing_envs};
-use utils::{is_conda_env, is_conda_install};
+use utils::{is_conda_env_in, is_conda_install_in};
 
 mod conda_info;
 pub mod conda_rc;
@@ -71,10 +73,10 @@ struct FileFingerprint {
 }
 
 impl FileFingerprint {
-    fn from_metadata(metadata: fs::Metadata) -> Option<Self> {
+    fn from_metadata(metadata: FileMetadata) -> Option<Self> {
         Some(Self {
-            modified: metadata.modified().ok()?,
-            len: metadata.len(),
+            modified: metadata.modified?,
+            len: metadata.len,
         })
     }
 }
@@ -86,16 +88,16 @@ struct CondaEnvironmentFingerprint {
 }
 
 impl CondaEnvironmentFingerprint {
-    fn from_prefix(prefix: &Path) -> Option<Self> {
+    fn from_prefix(file_system: &dyn FileSystem, prefix: &Path) -> Option<Self> {
         let conda_meta = prefix.join("conda-meta");
-        let history = match fs::metadata(conda_meta.join("history")) {
+        let history = match file_system.metadata(&conda_meta.join("history")) {
             Ok(metadata) => Some(FileFingerprint::from_metadata(metadata)?),
             Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
             Err(_) => return None,
         };
 
         Some(Self {
-            conda_meta: FileFingerprint::from_metadata(fs::metadata(conda_meta).ok()?)?,
+            conda_meta: FileFingerprint::from_metadata(file_system.metadata(&conda_meta).ok()?)?,
             history,
         })
     }
@@ -122,6 +124,7 @@ pub struct Conda {
     pub env_vars: EnvVariables,
     conda_executable: Arc<RwLock<Option<PathBuf>>>,
     environment_info_cache: CondaEnvironmentInfoCache,
+    file_system: Arc<dyn FileSystem>,
 }
 
 impl Conda {
@@ -144,6 +147,7 @@ impl Conda {
             env_vars: EnvVariables::from(env),
             conda_executable: Arc::new(RwLock::new(None)),
             environment_info_cache,
+            file_system: env.file_system(),
         }
     }
 
@@ -155,10 +159,11 @@ impl Conda {
 
     fn get_environment_details(&self, path: &Path) -> Option<CondaEnvironmentDetails> {
         self.get_or_load_environment_details(path, || {
-            let environment = get_conda_environment_info(path, &None)?;
+            let environment =
+                get_conda_environment_info_in(self.file_system.as_ref(), path, &None)?;
             let conda_dir = environment.conda_dir.clone();
             Some(CondaEnvironmentDetails {
-                environment: environment.to_python_environment(None),
+                environment: environment.to_python_environment_in(self.file_system.as_ref(), None),
                 conda_dir,
             })
         })
@@ -173,7 +178,8 @@ impl Conda {
         F: FnOnce() -> Option<CondaEnvironmentDetails>,
     {
         let cache_key = norm_case(path);
-        let fingerprint_before = CondaEnvironmentFingerprint::from_prefix(path);
+        let fingerprint_before =
+            CondaEnvironmentFingerprint::from_prefix(self.file_system.as_ref(), path);
         if let Some(fingerprint) = &fingerprint_before {
             if let Some(cached) = self
                 .environment_info_cache
@@ -195,7 +201,8 @@ impl Conda {
                 .remove(&cache_key);
             return None;
         };
-        let fingerprint_after = CondaEnvironmentFingerprint::from_prefix(path);
+        let fingerprint_after =
+            CondaEnvironmentFingerprint::from_prefix(self.file_system.as_ref(), path);
         let mut cache = self
             .environment_info_cache
             .write()
@@ -226,10 +233,11 @@ impl CondaLocator for Conda {
         let user_provided_conda_exe = conda_executable.is_some();
         // Try the provided executable first (could be conda or mamba for backwards compat),
         // then fall back to mamba/micromamba found on PATH if conda is unavailable.
-        let conda_info = CondaInfo::from(conda_executable).or_else(|| {
-            let mamba_exe = manager::find_mamba_binary(&self.env_vars);
-            CondaInfo::from(mamba_exe)
-        })?;
+        let conda_info =
+            CondaInfo::from_in(self.file_system.as_ref(), conda_executable).or_else(|| {
+                let mamba_exe = manager::find_mamba_binary(&self.env_vars);
+                CondaInfo::from_in(self.file_system.as_ref(), mamba_exe)
+            })?;
         let environments_map = self.environments.clone_map();
         let new_envs = conda_info
             .envs
@@ -257,21 +265,23 @@ impl CondaLocator for Conda {
     }
 
     fn get_info_for_telemetry(&self, conda_executable: Option<PathBuf>) -> CondaTelemetryInfo {
-        let can_spawn_conda = CondaInfo::from(conda_executable).is_some();
+        let can_spawn_conda =
+            CondaInfo::from_in(self.file_system.as_ref(), conda_executable).is_some();
         let environments = self.environments.values();
         let (conda_rcs, env_dirs) = get_conda_rcs_and_env_dirs(&self.env_vars, &environments);
         let mut environments_txt = None;
         let mut environments_txt_exists = None;
         if let Some(ref home) = self.env_vars.home {
             let file = Path::new(&home).join(".conda").join("environments.txt");
-            environments_txt_exists = Some(file.exists());
+            environments_txt_exists = Some(self.file_system.exists(&file));
             environments_txt = Some(file);
         }
 
         let conda_exe = &self.conda_executable.read().unwrap().clone();
-        let envs_found = get_conda_environment_paths(&self.env_vars, conda_exe);
+        let file_system = self.file_system.as_ref();
+        let envs_found = get_conda_environment_paths_in(file_system, &self.env_vars, conda_exe);
         let mut user_provided_env_found = None;
-        if let Some(conda_dir) = get_conda_dir_from_exe(conda_exe) {
+        if let Some(conda_dir) = get_conda_dir_from_exe_in(file_system, conda_exe) {
             let conda_dir = norm_case(conda_dir);
             user_provided_env_found = Some(envs_found.contains(&conda_dir));
         }
@@ -283,15 +293,19 @@ impl CondaLocator for Conda {
             user_provided_env_found,
             environments_txt,
             environments_txt_exists,
-            environments_from_txt: get_conda_envs_from_environment_txt(&self.env_vars),
+            environments_from_txt: get_conda_envs_from_environment_txt_in(
+                file_system,
+                &self.env_vars,
+            ),
         }
     }
 
     fn find_and_report(&self, reporter: &dyn Reporter, conda_dir: &Path) {
-        if !is_conda_install(conda_dir) {
+        let file_system = self.file_system.as_ref();
+        if !is_conda_install_in(file_system, conda_dir) {
             return;
         }
-        if let Some(manager) = CondaManager::from(conda_dir) {
+        if let Some(manager) = CondaManager::from_in(file_system, conda_dir) {
             if let Some(conda_dir) = manager.conda_dir.clone() {
                 // Keep track to search again later.
                 // Possible we'll find environments in other directories created using this manager
@@ -301,7 +315,7 @@ impl CondaLocator for Conda {
                 let _ = self
                     .mamba_managers
                     .get_or_insert_with(conda_dir.clone(), || {
-                        let mgr = get_mamba_manager(&conda_dir);
+                        let mgr = get_mamba_manager(self.file_system.as_ref(), &conda_dir);
                         if let Some(ref m) = mgr {
                             reporter.report_manager(&m.to_manager());
                         }
@@ -309,9 +323,11 @@ impl CondaLocator for Conda {
                     });
 
                 // Find all the environments in the conda install folder. (under `envs` folder)
-                for conda_env in
-                    get_conda_environments(&get_environments(&conda_dir), &manager.clone().into())
-                {
+                for conda_env in get_conda_environments(
+                    file_system,
+                    &get_environments_in(file_system, &conda_dir),
+                    &manager.clone().into(),
+                ) {
                     // If reported earlier, no point processing this again.
                     if self.environments.contains_key(&conda_env.prefix) {
                         continue;
@@ -322,9 +338,10 @@ impl CondaLocator for Conda {
                     let manager = conda_env
                         .clone()
                         .conda_dir
-                        .and_then(|p| CondaManager::from(&p))
+                        .and_then(|p| CondaManager::from_in(file_system, &p))
                         .unwrap_or(manager.clone());
-                    let env = conda_env.to_python_environment(Some(manager.to_manager()));
+                    let env =
+                        conda_env.to_python_environment_in(file_system, Some(manager.to_manager()));
                     self.environments
                         .insert(conda_env.prefix.clone(), env.clone());
                     reporter.report_manager(&manager.to_manager());
@@ -336,9 +353,64 @@ impl CondaLocator for Conda {
 }
 
 impl Conda {
+    /// Reports the conda environment in `path` along with its manager, if not already reported.
+    fn report_environment_in(&self, path: PathBuf, reporter: &dyn Reporter) -> Option<()> {
+        let details = self.get_environment_details(&path)?;
+        let prefix = path.clone();
+
+        let Some(conda_dir) = details.conda_dir else {
+            error!(
+                "Unable to find Conda Manager for the Conda env: {:?}",
+                details.environment
+            );
+            self.environments
+                .insert(prefix, details.environment.clone());
+            reporter.report_environment(&details.environment);
+            return None;
+        };
+
+        if self.environments.contains_key(&prefix) {
+            return None;
+        }
+
+        let manager = self.managers.get_or_insert_with(conda_dir.clone(), || {
+            CondaManager::from_in(self.file_system.as_ref(), &conda_dir)
+        });
+
+        let mut environment = details.environment;
+        if let Some(manager) = manager {
+            environment.manager = Some(manager.to_manager());
+            self.environments
+                .insert(prefix.clone(), environment.clone());
+            reporter.report_manager(&manager.to_manager());
+            reporter.report_environment(&environment);
+
+            let _ = self
+                .mamba_managers
+                .get_or_insert_with(conda_dir.clone(), || {
+                    let mgr = get_mamba_manager(self.file_system.as_ref(), &conda_dir);
+                    if let Some(ref m) = mgr {
+                        reporter.report_manager(&m.to_manager());
+                    }
+                    mgr
+                });
+        } else {
+            error!(
+                "Unable to find Conda Manager for Conda env (even though we have a conda_dir {:?}): Env Details = {:?}",
+                conda_dir, environment
+            );
+            self.environments
+                .insert(prefix.clone(), environment.clone());
+            reporter.report_environment(&environment);
+        }
+        Some(())
+    }
+
     fn get_manager(&self, conda_dir: &Path) -> Option<CondaManager> {
         self.managers
-            .get_or_insert_with(conda_dir.to_path_buf(), || CondaManager::from(conda_dir))
+            .get_or_insert_with(conda_dir.to_path_buf(), || {
+                CondaManager::from_in(self.file_system.as_ref(), conda_dir)
+            })
     }
 }
 
@@ -400,12 +472,12 @@ impl Locator for Conda {
         let mut prefix = env.prefix.clone();
         if prefix.is_none() {
             if let Some(parent_dir) = &env.executable.parent() {
-                if is_conda_env(parent_dir) {
+                if is_conda_env_in(env.file_system.as_ref(), parent_dir) {
                     // This is a conda env (most likely root conda env as the exe is in the same directory (generally on windows))
                     prefix = Some(parent_dir.to_path_buf());
                 } else if parent_dir.ends_with("bin") || parent_dir.ends_with("Scripts") {
                     if let Some(parent_dir) = parent_dir.parent() {
-                        if is_conda_env(parent_dir) {
+                        if is_conda_env_in(env.file_system.as_ref(), parent_dir) {
                             // This is a conda env
                             prefix = Some(parent_dir.to_path_buf());
                         }
@@ -415,7 +487,7 @@ impl Locator for Conda {
         }
 
         let path = prefix.as_ref()?;
-        if !is_conda_env(path) {
+        if !is_conda_env_in(env.file_system.as_ref(), path) {
             return None;
         }
 
@@ -442,101 +514,66 @@ impl Locator for Conda {
         Some(environment)
     }
     fn find(&self, reporter: &dyn Reporter) {
+        self.find_with_budget(reporter, &Unlimited);
+    }
+    fn find_with_budget(&self, reporter: &dyn Reporter, budget: &dyn SearchRootBudget) {
         // Discovery outputs are rebuilt on every refresh. The separate environment info cache
         // survives and is invalidated by conda metadata fingerprints.
         self.clear();
 
         let env_vars = self.env_vars.clone();
         let executable = self.conda_executable.read().unwrap().clone();
-        thread::scope(|s| {
-            if let Some(ref exe) = executable {
-                if is_mamba_executable(exe) {
-                    if let Some(mamba_dir) = get_conda_dir_from_exe(&executable) {
-                        if let Some(mamba_mgr) = get_mamba_manager(&mamba_dir) {
-                            self.mamba_managers.insert(mamba_dir, mamba_mgr.clone());
-                            reporter.report_manager(&mamba_mgr.to_manager());
-                        }
+        if let Some(ref exe) = executable {
+            if is_mamba_executable(exe) {
+                if let Some(mamba_dir) =
+                    get_conda_dir_from_exe_in(self.file_system.as_ref(), &executable)
+                {
+                    if let Some(mamba_mgr) =
+                        get_mamba_manager(self.file_system.as_ref(), &mamba_dir)
+                    {
+                        self.mamba_managers.insert(mamba_dir, mamba_mgr.clone());
+                        reporter.report_manager(&mamba_mgr.to_manager());
                     }
                 }
             }
+        }
 
-            let possible_conda_envs = get_conda_environment_paths(&env_vars, &executable);
-            let active_prefixes: HashSet<PathBuf> =
-                possible_conda_envs.iter().map(norm_case).collect();
+        let possible_conda_envs = limit_to_search_root_budget(
+            budget,
+            get_conda_environment_paths_in(self.file_system.as_ref(), &env_vars, &executable),
+        );
+        let active_prefixes: HashSet<PathBuf> = possible_conda_envs.iter().map(norm_case).collect();
+        // Runs on the pool of the caller (i.e. the discovery pool), instead of a thread per environment.
+        rayon::scope(|s| {
             for path in possible_conda_envs {
-                s.spawn(move || {
-                    let details = self.get_environment_details(&path)?;
-                    let prefix = path.clone();
-
-                    let Some(conda_dir) = details.conda_dir else {
-                        error!(
-                            "Unable to find Conda Manager for the Conda env: {:?}",
-                            details.environment
-                        );
-                        self.environments
-                            .insert(prefix, details.environment.clone());
-                        reporter.report_environment(&details.environment);
-                        return None;
-                    };
-
-                    if self.environments.contains_key(&prefix) {
-                        return None;
-                    }
-
-                    let manager = self
-                        .managers
-                        .get_or_insert_with(conda_dir.clone(), || CondaManager::from(&conda_dir));
-
-                    let mut environment = details.environment;
-                    if let Some(manager) = manager {
-                        environment.manager = Some(manager.to_manager());
-                        self.environments
-                            .insert(prefix.clone(), environment.clone());
-                        reporter.report_manager(&manager.to_manager());
-                        reporter.report_environment(&environment);
-
-                        let _ = self
-                            .mamba_managers
-                            .get_or_insert_with(conda_dir.clone(), || {
-                                let mgr = get_mamba_manager(&conda_dir);
-                                if let Some(ref m) = mgr {
-                                    reporter.report_manager(&m.to_manager());
-                                }
-                                mgr
-                            });
-                    } else {
-                        error!(
-                            "Unable to find Conda Manager for Conda env (even though we have a conda_dir {:?}): Env Details = {:?}",
-                            conda_dir, environment
-                        );
-                        self.environments
-                            .insert(prefix.clone(), environment.clone());
-                        reporter.report_environment(&environment);
-                    }
-                    Some(())
+                s.spawn(move |_| {
+                    self.report_environment_in(path, reporter);
                 });
             }
-
-            self.environment_info_cache
-                .write()
-                .expect("conda environment info cache lock poisoned")
-                .retain(|prefix, _| active_prefixes.contains(prefix));
         });
+
+        self.environment_info_cache
+            .write()
+            .expect("conda environment info cache lock poisoned")
+            .retain(|prefix, _| active_prefixes.contains(prefix));
     }
 }
 fn get_conda_environments(
+    file_system: &dyn FileSystem,
     paths: &Vec<PathBuf>,
     manager: &Option<CondaManager>,
 ) -> Vec<CondaEnvironment> {
     paths
         .par_iter()
-        .filter_map(|path| get_conda_environment_info(path, manager))
+        .filter_map(|path| get_conda_environment_info_in(file_system, path, manager))
         .collect()
 }
 #[cfg(test)]
 mod tests {
     use super::*;
     use pet_core::os_environment::EnvironmentApi;
+    use pet_core::python_version::PythonVersion;
+    use std::fs;
     use std::sync::atomic::{AtomicUsize, Ordering};
 
     fn test_details(prefix: &Path, load: usize) -> CondaEnvironmentDetails {
@@ -546,7 +583,7 @@ mod tests {
                 Some(PythonEnvironmentKind::Conda),
                 Some(prefix.to_path_buf()),
                 None,
-                Some(load.to_string()),
+                PythonVersion::parse(&load.to_string()),
             ),
             conda_dir: None,
         }
@@ -575,7 +612,7 @@ mod tests {
                 Some(test_details(&prefix, load))
             })
             .unwrap();
-        assert_eq!(first.environment.version.as_deref(), Some("1"));
+        assert_eq!(first.environment.version, Some("1".parse().unwrap()));
 
         let refresh = Conda::from_shared_environment_cache(&environment, &shared);
         let cached = refresh
@@ -583,7 +620,7 @@ mod tests {
                 panic!("unchanged metadata should reuse the shared cache")
             })
             .unwrap();
-        assert_eq!(cached.environment.version.as_deref(), Some("1"));
+        assert_eq!(cached.environment.version, Some("1".parse().unwrap()));
         assert_eq!(loads.load(Ordering::Relaxed), 1);
 
         fs::write(&history, "updated history with a different length").unwrap();
@@ -593,7 +630,7 @@ mod tests {
                 Some(test_details(&prefix, load))
             })
             .unwrap();
-        assert_eq!(refreshed.environment.version.as_deref(), Some("2"));
+        assert_eq!(refreshed.environment.version, Some("2".parse().unwrap()));
         assert_eq!(loads.load(Ordering::Relaxed), 2);
 
         fs::remove_dir_all(prefix).unwrap();
@@ -4,18 +4,19 @@
 use crate::{
     conda_info::CondaInfo,
     env_variables::EnvVariables,
-    environments::get_conda_installation_used_to_create_conda_env,
+    environments::get_conda_installation_used_to_create_conda_env_in,
     package::CondaPackageInfo,
-    utils::{is_conda_env, is_conda_install},
+    utils::{is_conda_env_in, is_conda_install_in},
 };
 use log::trace;
 use pet_core::{manager::EnvManager, manager::EnvManagerType};
+use pet_fs::file_system::{FileSystem, OsFileSystem};
 use std::{
     env,
     path::{Path, PathBuf},
 };
 
-fn get_conda_executable(path: &Path) -> Option<PathBuf> {
+fn get_conda_executable(file_system: &dyn FileSystem, path: &Path) -> Option<PathBuf> {
     #[cfg(windows)]
     let relative_path_to_conda_exe = vec![
         PathBuf::from("Scripts").join("conda.exe"),
@@ -28,7 +29,7 @@ fn get_conda_executable(path: &Path) -> Option<PathBuf> {
 
     for relative_path in relative_path_to_conda_exe {
         let exe = path.join(&relative_path);
-        if exe.exists() {
+        if file_system.exists(&exe) {
             return Some(exe);
         }
     }
@@ -36,7 +37,7 @@ fn get_conda_executable(path: &Path) -> Option<PathBuf> {
     None
 }
 
-fn get_mamba_executable(path: &Path) -> Option<PathBuf> {
+fn get_mamba_executable(file_system: &dyn FileSystem, path: &Path) -> Option<PathBuf> {
     #[cfg(windows)]
     let relative_paths = vec![
         PathBuf::from("Scripts").join("mamba.exe"),
@@ -56,7 +57,7 @@ fn get_mamba_executable(path: &Path) -> Option<PathBuf> {
 
     for relative_path in relative_paths {
         let exe = path.join(&relative_path);
-        if exe.exists() {
+        if file_system.exists(&exe) {
             return Some(exe);
         }
     }
@@ -90,11 +91,19 @@ fn get_mamba_bin_names() -> Vec<&'static str> {
 
 /// Find the conda binary on the PATH environment variable
 pub fn find_conda_binary(env_vars: &EnvVariables) -> Option<PathBuf> {
+    find_conda_binary_in(&OsFileSystem, env_vars)
+}
+
+/// Same as `find_conda_binary`, looking at the files in the given file system.
+pub fn find_conda_binary_in(
+    file_system: &dyn FileSystem,
+    env_vars: &EnvVariables,
+) -> Option<PathBuf> {
     let paths = env_vars.path.clone()?;
     for path in env::split_paths(&paths) {
         for bin in get_conda_bin_names() {
             let conda_path = path.join(bin);
-            if conda_path.is_file() || conda_path.is_symlink() {
+            if file_system.is_file(&conda_path) || file_system.is_symlink(&conda_path) {
                 return Some(conda_path);
             }
         }
@@ -104,11 +113,19 @@ pub fn find_conda_binary(env_vars: &EnvVariables) -> Option<PathBuf> {
 
 /// Find a mamba or micromamba binary on the PATH environment variable
 pub fn find_mamba_binary(env_vars: &EnvVariables) -> Option<PathBuf> {
+    find_mamba_binary_in(&OsFileSystem, env_vars)
+}
+
+/// Same as `find_mamba_binary`, looking at the files in the given file system.
+pub fn find_mamba_binary_in(
+    file_system: &dyn FileSystem,
+    env_vars: &EnvVariables,
+) -> Option<PathBuf> {
     let paths = env_vars.path.clone()?;
     for path in env::split_paths(&paths) {
         for bin in get_mamba_bin_names() {
             let mamba_path = path.join(bin);
-            if mamba_path.is_file() || mamba_path.is_symlink() {
+            if file_system.is_file(&mamba_path) || file_system.is_symlink(&mamba_path) {
                 return Some(mamba_path);
             }
         }
@@ -133,15 +150,19 @@ impl CondaManager {
         }
     }
     pub fn from(path: &Path) -> Option<CondaManager> {
-        if !is_conda_env(path) {
+        Self::from_in(&OsFileSystem, path)
+    }
+    /// Same as `from`, looking at the files in the given file system.
+    pub fn from_in(file_system: &dyn FileSystem, path: &Path) -> Option<CondaManager> {
+        if !is_conda_env_in(file_system, path) {
             return None;
         }
 
         // If this environment is in a folder named `envs`, then the parent directory of `envs` is the root conda install folder.
         if let Some(parent) = path.ancestors().nth(2) {
-            if is_conda_install(parent) {
-                if let Some(manager) =
-                    get_conda_manager(parent).or_else(|| get_mamba_manager(parent))
+            if is_conda_install_in(file_system, parent) {
+                if let Some(manager) = get_conda_manager(file_system, parent)
+                    .or_else(|| get_mamba_manager(file_system, parent))
                 {
                     return Some(manager);
                 }
@@ -153,9 +174,11 @@ impl CondaManager {
         // Or a conda env created using `-p` flag.
         // Get the conda install folder from the history file.
         // Or its in a location such as `~/.conda/envs` or `~/miniconda3/envs` where the conda install folder is not a parent of this path.
-        if let Some(conda_install_folder) = get_conda_installation_used_to_create_conda_env(path) {
-            get_conda_manager(&conda_install_folder)
-                .or_else(|| get_mamba_manager(&conda_install_folder))
+        if let Some(conda_install_folder) =
+            get_conda_installation_used_to_create_conda_env_in(file_system, path)
+        {
+            get_conda_manager(file_system, &conda_install_folder)
+                .or_else(|| get_mamba_manager(file_system, &conda_install_folder))
         } else {
             // If this is a conda env and the parent is `.conda/envs`, then this is definitely NOT a root conda install folder.
             // Hence never use conda installs from these env paths.
@@ -169,7 +192,9 @@ impl CondaManager {
                 }
             }
 
-            if let Some(manager) = get_conda_manager(path).or_else(|| get_mamba_manager(path)) {
+            if let Some(manager) = get_conda_manager(file_system, path)
+                .or_else(|| get_mamba_manager(file_system, path))
+            {
                 Some(manager)
             } else {
                 trace!("No conda or mamba manager found for path: {:?}", path);
@@ -191,9 +216,11 @@ impl CondaManager {
     }
 }
 
-fn get_conda_manager(path: &Path) -> Option<CondaManager> {
-    let conda_exe = get_conda_executable(path)?;
-    if let Some(conda_pkg) = CondaPackageInfo::from(path, &crate::package::Package::Conda) {
+fn get_conda_manager(file_system: &dyn FileSystem, path: &Path) -> Option<CondaManager> {
+    let conda_exe = get_conda_executable(file_system, path)?;
+    if let Some(conda_pkg) =
+        CondaPackageInfo::from_in(file_system, path, &crate::package::Package::Conda)
+    {
         Some(CondaManager {
             executable: conda_exe,
             version: Some(conda_pkg.version),
@@ -215,8 +242,8 @@ pub fn is_mamba_executable(exe: &Path) -> bool {
     }
 }
 
-pub(crate) fn get_mamba_manager(path: &Path) -> Option<CondaManager> {
-    let mamba_exe = get_mamba_executable(path)?;
+pub(crate) fn get_mamba_manager(file_system: &dyn FileSystem, path: &Path) -> Option<CondaManager> {
+    let mamba_exe = get_mamba_executable(file_system, path)?;
     // We cannot reliably determine the mamba/micromamba version from package metadata alone.
     // The conda package version in conda-meta is the conda version, not the mamba version.
     // Determining the mamba version would require spawning the mamba process.
@@ -4,9 +4,10 @@
 use lazy_static::lazy_static;
 use log::warn;
 use pet_core::arch::Architecture;
+use pet_fs::file_system::{FileSystem, OsFileSystem};
 use regex::Regex;
 use serde::Deserialize;
-use std::fs::read_to_string;
+use std::fmt;
 use std::path::{Path, PathBuf};
 
 lazy_static! {
@@ -25,8 +26,6 @@ lazy_static! {
         .expect("error parsing Version regex for Conda Package Version in conda");
 }
 
-use std::{fmt, fs};
-
 #[derive(Debug, Clone, PartialEq)]
 pub enum Package {
     Conda,
@@ -62,16 +61,24 @@ pub struct CondaPackageInfo {
 
 impl CondaPackageInfo {
     pub fn from(path: &Path, package: &Package) -> Option<Self> {
-        let history = fs::read_to_string(path.join("conda-meta").join("history")).ok();
-        Self::from_history(path, package, history.as_deref())
+        Self::from_in(&OsFileSystem, path, package)
+    }
+
+    /// Same as `from`, looking at the files in the given file system.
+    pub fn from_in(file_system: &dyn FileSystem, path: &Path, package: &Package) -> Option<Self> {
+        let history = file_system
+            .read_to_string(&path.join("conda-meta").join("history"))
+            .ok();
+        Self::from_history(file_system, path, package, history.as_deref())
     }
 
     pub(crate) fn from_history(
+        file_system: &dyn FileSystem,
         path: &Path,
         package: &Package,
         history: Option<&str>,
     ) -> Option<Self> {
-        get_conda_package_info(path, package, history)
+        get_conda_package_info(file_system, path, package, history)
     }
 }
 
@@ -83,12 +90,13 @@ struct CondaMetaPackageStructure {
 
 /// Get the details of a conda package from the 'conda-meta' directory.
 fn get_conda_package_info(
+    file_system: &dyn FileSystem,
     path: &Path,
     name: &Package,
     history: Option<&str>,
 ) -> Option<CondaPackageInfo> {
-    if let Some(info) =
-        history.and_then(|history| get_conda_package_info_from_history(path, name, history))
+    if let Some(info) = history
+        .and_then(|history| get_conda_package_info_from_history(file_system, path, name, history))
     {
         Some(info)
     } else {
@@ -97,11 +105,12 @@ fn get_conda_package_info(
             name, path
         );
 
-        get_conda_package_info_from_package_json(path, name)
+        get_conda_package_info_from_package_json(file_system, path, name)
     }
 }
 
 fn get_conda_package_info_from_history(
+    file_system: &dyn FileSystem,
     path: &Path,
     name: &Package,
     history_contents: &str,
@@ -152,7 +161,7 @@ fn get_conda_package_info_from_history(
                 // }
                 // 32bit channel is https://repo.anaconda.com/pkgs/main/win-32/
                 // 64bit channel is "channel": "https://repo.anaconda.com/pkgs/main/osx-arm64",
-                if let Ok(contents) = read_to_string(&package_path) {
+                if let Ok(contents) = file_system.read_to_string(&package_path) {
                     if let Ok(js) = serde_json::from_str::<CondaMetaPackageStructure>(&contents) {
                         if let Some(channel) = js.channel {
                             if channel.ends_with("64") {
@@ -183,6 +192,7 @@ fn get_conda_package_info_from_history(
 }
 
 fn get_conda_package_info_from_package_json(
+    file_system: &dyn FileSystem,
     path: &Path,
     name: &Package,
 ) -> Option<CondaPackageInfo> {
@@ -191,9 +201,7 @@ fn get_conda_package_info_from_package_json(
     let path = path.join("conda-meta");
 
     // Fallback, slower approach of enumerating all files.
-    let entries = fs::read_dir(path).ok()?;
-    for entry in entries.filter_map(Result::ok) {
-        let path = entry.path();
+    for path in file_system.read_dir(&path).ok()? {
         let file_name = path.file_name().unwrap_or_default().to_string_lossy();
         if file_name.starts_with(&package_name) && file_name.ends_with(".json") {
             if let Some(captures) = regex.captures(&file_name) {
@@ -208,7 +216,7 @@ fn get_conda_package_info_from_package_json(
                     // }
                     // 32bit channel is https://repo.anaconda.com/pkgs/main/win-32/
                     // 64bit channel is "channel": "https://repo.anaconda.com/pkgs/main/osx-arm64",
-                    if let Ok(contents) = read_to_string(&path) {
+                    if let Ok(contents) = file_system.read_to_string(&path) {
                         if let Ok(js) = serde_json::from_str::<CondaMetaPackageStructure>(&contents)
                         {
                             if let Some(channel) = js.channel {
@@ -1,13 +1,21 @@
 // Copyright (c) Microsoft Corporation. All rights reserved.
 // Licensed under the MIT License.
 
+use pet_fs::file_system::{FileSystem, OsFileSystem};
 use std::path::{Path, PathBuf};
 
 /// conda-meta must exist as this contains a mandatory `history` file.
 pub fn is_conda_install(path: &Path) -> bool {
-    if (path.join("condabin").exists() || path.join("envs").exists())
-        && path.join("conda-meta").exists()
-    {
+    is_conda_install_in(&OsFileSystem, path)
+}
+
+/// Same as `is_conda_install`, looking at the files in the given file system.
+pub fn is_conda_install_in(file_system: &dyn FileSystem, path: &Path) -> bool {
+    let is_install = |path: &Path| {
+        (file_system.exists(&path.join("condabin")) || file_system.exists(&path.join("envs")))
+            && file_system.exists(&path.join("conda-meta"))
+    };
+    if is_install(path) {
         // For https://github.com/microsoft/vscode-python/issues/24247
         // Possible the env has a condabin or envs folder but its not the install directory.
         // & in fact its just a regular conda env.
@@ -16,9 +24,7 @@ pub fn is_conda_install(path: &Path) -> bool {
             if let Some(parent) = parent.parent() {
                 // If the grand parent is a conda install directory,
                 // then this is definitely not a conda install dir.
-                if (parent.join("condabin").exists() || parent.join("envs").exists())
-                    && parent.join("conda-meta").exists()
-                {
+                if is_install(parent) {
                     return false;
                 }
             }
@@ -34,26 +40,30 @@ pub fn is_conda_install(path: &Path) -> bool {
 /// The root conda installation folder is also a conda environment (its the base environment).
 /// Exclude Pixi environments to avoid misidentifying them as conda environments.
 pub fn is_conda_env(path: &Path) -> bool {
-    path.join("conda-meta").is_dir() && !path.join("conda-meta").join("pixi").is_file()
+    is_conda_env_in(&OsFileSystem, path)
 }
 
-/// Only used in tests, noop in production.
+/// Same as `is_conda_env`, looking at the files in the given file system.
+pub fn is_conda_env_in(file_system: &dyn FileSystem, path: &Path) -> bool {
+    let conda_meta = path.join("conda-meta");
+    file_system.is_dir(&conda_meta) && !file_system.is_file(&conda_meta.join("pixi"))
+}
+
+/// Change the root of the path to the alternate root directory (e.g. an unpacked container image).
 ///
-/// Change the root of the path to a new root.
 /// Lets assume some config file is located in the root directory /etc/config/config.toml.
-/// We cannot test this unless we create such a file on the root of the filesystem.
-/// Thats very risky and not recommended (ideally we want to create stuff in separate test folders).
-/// The solution is to change the root of the path to a test folder.
+/// When searching a container image in `/images/debian` this file is `/images/debian/etc/config/config.toml`.
+/// Tests use the same mechanism, as creating files in the root of the filesystem is very risky.
+/// Relative paths & paths already under the new root are returned as is.
 pub fn change_root_of_path(path: &Path, new_root: &Option<PathBuf>) -> PathBuf {
     if cfg!(windows) {
         return path.to_path_buf();
     }
-    if let Some(new_root) = new_root {
-        // This only applies in tests.
-        // We need this, as the root folder cannot be mocked.
-        // Strip the first `/` (this path is only for testing purposes)
-        new_root.join(&path.to_string_lossy()[1..])
-    } else {
-        path.to_path_buf()
+    match new_root {
+        Some(new_root) if path.has_root() && !path.starts_with(new_root) => {
+            // Strip the first `/`.
+            new_root.join(&path.to_string_lossy()[1..])
+        }
+        _ => path.to_path_buf(),
     }
 }
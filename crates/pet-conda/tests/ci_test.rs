@@ -2,6 +2,7 @@
 // Licensed under the MIT License.
 #![cfg(unix)]
 
+use pet_core::python_version::PythonVersion;
 use serde::Deserialize;
 use std::path::PathBuf;
 use std::sync::Once;
@@ -141,7 +142,9 @@ fn detect_new_conda_env() {
     assert_eq!(env.kind, Some(PythonEnvironmentKind::Conda));
     assert_eq!(env.executable, prefix.join("bin").join("python").into());
     assert!(
-        env.version.clone().unwrap_or_default().starts_with("3.10"),
+        env.version
+            .as_ref()
+            .is_some_and(|v| (v.major(), v.minor()) == (3, 10)),
         "Expected 3.10, but got Version: {:?}",
         env.version
     );
@@ -189,7 +192,9 @@ fn detect_conda_env_from_path() {
     assert_eq!(env.kind, Some(PythonEnvironmentKind::Conda));
     assert_eq!(env.executable, exe.clone().into());
     assert!(
-        env.version.clone().unwrap_or_default().starts_with("3.10"),
+        env.version
+            .as_ref()
+            .is_some_and(|v| (v.major(), v.minor()) == (3, 10)),
         "Expected 3.10, but got Version: {:?}",
         env.version
     );
@@ -326,7 +331,9 @@ fn detect_new_conda_env_created_with_p_flag_with_python() {
     assert_eq!(env.kind, Some(PythonEnvironmentKind::Conda));
     assert_eq!(env.executable, exe.into());
     assert!(
-        env.version.clone().unwrap_or_default().starts_with("3.10"),
+        env.version
+            .as_ref()
+            .is_some_and(|v| (v.major(), v.minor()) == (3, 10)),
         "Expected 3.10, but got Version: {:?}",
         env.version
     );
@@ -466,9 +473,9 @@ fn create_conda_env(mode: &CondaCreateEnvNameOrPath, python_version: Option<Stri
         .expect("Failed to execute command");
 }
 
-fn get_version(value: &str) -> String {
+fn get_version(value: &str) -> PythonVersion {
     // Regex to extract just the d.d.d version from the full version string
     let re = regex::Regex::new(r"\d+\.\d+\.\d+").unwrap();
     let captures = re.captures(value).unwrap();
-    captures.get(0).unwrap().as_str().to_string()
+    captures.get(0).unwrap().as_str().parse().unwrap()
 }
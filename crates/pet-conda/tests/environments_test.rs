@@ -21,7 +21,7 @@ fn find_root_conda_env() {
         env.executable,
         Some(path.clone().join("bin").join("python"))
     );
-    assert_eq!(env.version, Some("3.10.9".into()));
+    assert_eq!(env.version, Some("3.10.9".parse().unwrap()));
 }
 
 #[cfg(unix)]
@@ -38,7 +38,7 @@ fn find_root_conda_env_without_history_file() {
         env.executable,
         Some(path.clone().join("bin").join("python"))
     );
-    assert_eq!(env.version, Some("3.10.9".into()));
+    assert_eq!(env.version, Some("3.10.9".parse().unwrap()));
 }
 
 #[cfg(unix)]
@@ -56,7 +56,7 @@ fn find_conda_env() {
         env.executable,
         Some(path.clone().join("bin").join("python"))
     );
-    assert_eq!(env.version, Some("3.12.2".into()));
+    assert_eq!(env.version, Some("3.12.2".parse().unwrap()));
 }
 
 #[cfg(unix)]
@@ -23,14 +23,14 @@ fn sync_refresh_state_full_replaces_all_caches() {
         Some(PythonEnvironmentKind::Conda),
         Some(PathBuf::from("/envs/a")),
         None,
-        Some("3.10.0".into()),
+        Some("3.10.0".parse().unwrap()),
     );
     let env_b = PythonEnvironment::new(
         Some(PathBuf::from("/envs/b/bin/python")),
         Some(PythonEnvironmentKind::Conda),
         Some(PathBuf::from("/envs/b")),
         None,
-        Some("3.11.0".into()),
+        Some("3.11.0".parse().unwrap()),
     );
     shared
         .environments
@@ -73,14 +73,14 @@ fn sync_refresh_state_global_filtered_merges_caches() {
         Some(PythonEnvironmentKind::Conda),
         Some(PathBuf::from("/envs/a")),
         None,
-        Some("3.10.0".into()),
+        Some("3.10.0".parse().unwrap()),
     );
     let env_b = PythonEnvironment::new(
         Some(PathBuf::from("/envs/b/bin/python")),
         Some(PythonEnvironmentKind::Conda),
         Some(PathBuf::from("/envs/b")),
         None,
-        Some("3.11.0".into()),
+        Some("3.11.0".parse().unwrap()),
     );
     shared.environments.insert(PathBuf::from("/envs/a"), env_a);
     shared.environments.insert(PathBuf::from("/envs/b"), env_b);
@@ -100,14 +100,14 @@ fn sync_refresh_state_global_filtered_merges_caches() {
         Some(PythonEnvironmentKind::Conda),
         Some(PathBuf::from("/envs/a")),
         None,
-        Some("3.10.1".into()),
+        Some("3.10.1".parse().unwrap()),
     );
     let env_c = PythonEnvironment::new(
         Some(PathBuf::from("/envs/c/bin/python")),
         Some(PythonEnvironmentKind::Conda),
         Some(PathBuf::from("/envs/c")),
         None,
-        Some("3.12.0".into()),
+        Some("3.12.0".parse().unwrap()),
     );
     transient
         .environments
@@ -137,7 +137,7 @@ fn sync_refresh_state_global_filtered_merges_caches() {
             .get(&PathBuf::from("/envs/a"))
             .unwrap()
             .version,
-        Some("3.10.1".into())
+        Some("3.10.1".parse().unwrap())
     );
     assert!(shared.environments.get(&PathBuf::from("/envs/b")).is_some());
     assert!(shared.environments.get(&PathBuf::from("/envs/c")).is_some());
@@ -176,7 +176,7 @@ fn sync_refresh_state_full_then_global_filtered_preserves_entries() {
             Some(PythonEnvironmentKind::Conda),
             Some(PathBuf::from("/envs/a")),
             None,
-            Some("3.10.0".into()),
+            Some("3.10.0".parse().unwrap()),
         ),
     );
     transient_full.environments.insert(
@@ -186,7 +186,7 @@ fn sync_refresh_state_full_then_global_filtered_preserves_entries() {
             Some(PythonEnvironmentKind::Conda),
             Some(PathBuf::from("/envs/b")),
             None,
-            Some("3.11.0".into()),
+            Some("3.11.0".parse().unwrap()),
         ),
     );
     shared.sync_refresh_state_from(&transient_full, &RefreshStateSyncScope::Full);
@@ -201,7 +201,7 @@ fn sync_refresh_state_full_then_global_filtered_preserves_entries() {
             Some(PythonEnvironmentKind::Conda),
             Some(PathBuf::from("/envs/c")),
             None,
-            Some("3.12.0".into()),
+            Some("3.12.0".parse().unwrap()),
         ),
     );
     shared.sync_refresh_state_from(
@@ -234,7 +234,7 @@ fn sync_refresh_state_irrelevant_scope_is_noop() {
         Some(PythonEnvironmentKind::Conda),
         Some(PathBuf::from("/envs/a")),
         None,
-        Some("3.10.0".into()),
+        Some("3.10.0".parse().unwrap()),
     );
     shared.environments.insert(PathBuf::from("/envs/a"), env_a);
 
@@ -276,7 +276,7 @@ fn find_conda_env_without_manager() {
     assert_eq!(env.arch, Architecture::X64.into());
     assert_eq!(env.kind, Some(PythonEnvironmentKind::Conda));
     assert_eq!(env.executable, path.join("bin").join("python").into());
-    assert_eq!(env.version, "3.12.2".to_string().into());
+    assert_eq!(env.version, Some("3.12.2".parse().unwrap()));
     assert_eq!(env.manager, None);
     assert_eq!(env.name, "env_python_3".to_string().into());
 }
@@ -319,7 +319,7 @@ fn find_conda_env_without_manager_but_detect_manager_from_history() {
     assert_eq!(env.arch, Architecture::X64.into());
     assert_eq!(env.kind, Some(PythonEnvironmentKind::Conda));
     assert_eq!(env.executable, path.join("bin").join("python").into());
-    assert_eq!(env.version, "3.12.2".to_string().into());
+    assert_eq!(env.version, Some("3.12.2".parse().unwrap()));
     assert_eq!(
         env.manager.clone().unwrap().executable,
         conda_dir.join("bin").join("conda")
@@ -5,9 +5,17 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
+#[non_exhaustive]
 pub enum Architecture {
     X64,
     X86,
+    Arm64,
+    Arm,
+    Ppc64le,
+    S390x,
+    Riscv64,
+    /// Universal (fat) macOS binary, containing both `x86_64` and `arm64` code.
+    Universal2,
 }
 
 impl Ord for Architecture {
@@ -26,10 +34,15 @@ impl std::fmt::Display for Architecture {
         write!(
             f,
             "{}",
-            if *self == Architecture::X64 {
-                "x64"
-            } else {
-                "x86"
+            match self {
+                Architecture::X64 => "x64",
+                Architecture::X86 => "x86",
+                Architecture::Arm64 => "arm64",
+                Architecture::Arm => "arm",
+                Architecture::Ppc64le => "ppc64le",
+                Architecture::S390x => "s390x",
+                Architecture::Riscv64 => "riscv64",
+                Architecture::Universal2 => "universal2",
             }
         )
         .unwrap_or_default();
@@ -105,6 +118,23 @@ mod tests {
         assert_eq!(json, "\"x86\"");
     }
 
+    #[test]
+    fn test_architecture_display_matches_serialized_value() {
+        for arch in [
+            Architecture::X64,
+            Architecture::X86,
+            Architecture::Arm64,
+            Architecture::Arm,
+            Architecture::Ppc64le,
+            Architecture::S390x,
+            Architecture::Riscv64,
+            Architecture::Universal2,
+        ] {
+            let json = serde_json::to_string(&arch).unwrap();
+            assert_eq!(json, format!("\"{arch}\""));
+        }
+    }
+
     #[test]
     fn test_architecture_deserialize() {
         let arch: Architecture = serde_json::from_str("\"x64\"").unwrap();
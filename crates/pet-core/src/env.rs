@@ -1,13 +1,15 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use std::path::PathBuf;
+use std::{fmt, path::PathBuf, sync::Arc};
 
-use pet_fs::path::norm_case;
+use pet_fs::{
+    file_system::{os_file_system, FileSystem},
+    path::norm_case,
+};
 
-use crate::pyvenv_cfg::PyVenvCfg;
+use crate::{python_version::PythonVersion, pyvenv_cfg::PyVenvCfg};
 
-#[derive(Debug)]
 pub struct PythonEnv {
     /// Executable of the Python environment.
     ///
@@ -19,17 +21,33 @@ pub struct PythonEnv {
     /// Environment prefix
     pub prefix: Option<PathBuf>,
     /// Version of the Python environment.
-    pub version: Option<String>,
+    pub version: Option<PythonVersion>,
     /// Possible symlink (or known alternative link).
     /// For instance:
     ///
     /// If `executable` is `/opt/homebrew/Cellar/python@3.12/3.12.3/Frameworks/Python.framework/Versions/3.12/bin/python3.12`,
     /// then `symlink`` can be `/opt/homebrew/bin/python3.12` (or vice versa).
     pub symlinks: Option<Vec<PathBuf>>,
+    /// File system the environment was found in, locators use this to look at the files of the environment.
+    pub file_system: Arc<dyn FileSystem>,
 }
 
 impl PythonEnv {
-    pub fn new(executable: PathBuf, prefix: Option<PathBuf>, version: Option<String>) -> Self {
+    pub fn new(
+        executable: PathBuf,
+        prefix: Option<PathBuf>,
+        version: Option<PythonVersion>,
+    ) -> Self {
+        PythonEnv::new_in(os_file_system(), executable, prefix, version)
+    }
+
+    /// Same as `new`, for an environment found in the given file system.
+    pub fn new_in(
+        file_system: Arc<dyn FileSystem>,
+        executable: PathBuf,
+        prefix: Option<PathBuf>,
+        version: Option<PythonVersion>,
+    ) -> Self {
         let mut prefix = prefix.clone();
         if let Some(value) = prefix {
             prefix = norm_case(value).into();
@@ -42,7 +60,7 @@ impl PythonEnv {
             exe.pop();
             if exe.ends_with("Scripts") || exe.ends_with("bin") {
                 exe.pop();
-                if PyVenvCfg::find(&exe).is_some() {
+                if PyVenvCfg::find_in(file_system.as_ref(), &exe).is_some() {
                     prefix = Some(exe);
                 }
             }
@@ -52,6 +70,19 @@ impl PythonEnv {
             prefix,
             version,
             symlinks: None,
+            file_system,
         }
     }
 }
+
+impl fmt::Debug for PythonEnv {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The file system is left out, it is the same for all environments of a refresh.
+        f.debug_struct("PythonEnv")
+            .field("executable", &self.executable)
+            .field("prefix", &self.prefix)
+            .field("version", &self.version)
+            .field("symlinks", &self.symlinks)
+            .finish_non_exhaustive()
+    }
+}
@@ -1,21 +1,27 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use std::{any::Any, path::PathBuf};
+use std::{any::Any, path::PathBuf, time::Duration};
 
+use clap::ValueEnum;
 use env::PythonEnv;
 use manager::EnvManager;
 use python_environment::{PythonEnvironment, PythonEnvironmentKind};
 use reporter::Reporter;
+use search_root::SearchRootBudget;
+use serde::{Deserialize, Serialize};
 
 pub mod arch;
 pub mod cache;
 pub mod env;
 pub mod manager;
 pub mod os_environment;
+pub mod platform_tags;
 pub mod python_environment;
+pub mod python_version;
 pub mod pyvenv_cfg;
 pub mod reporter;
+pub mod search_root;
 pub mod telemetry;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -24,10 +30,12 @@ pub struct LocatorResult {
     pub environments: Vec<PythonEnvironment>,
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Configuration {
     /// These are paths like workspace folders, where we can look for environments.
     pub workspace_directories: Option<Vec<PathBuf>>,
+    /// Number of levels of sub directories of the workspace folders searched for environments.
+    pub workspace_search_depth: Option<usize>,
     pub executables: Option<Vec<PathBuf>>,
     pub conda_executable: Option<PathBuf>,
     pub pipenv_executable: Option<PathBuf>,
@@ -38,21 +46,60 @@ pub struct Configuration {
     pub environment_directories: Option<Vec<PathBuf>>,
     /// Directory to cache the Python environment details.
     pub cache_directory: Option<PathBuf>,
+    /// Executables that locate environments PET does not know about (plugins).
+    pub external_locators: Option<Vec<ExternalLocatorConfig>>,
+    /// Maximum time allowed for a spawned Python to report its details.
+    pub resolve_timeout: Option<Duration>,
+    /// Alternate root directory searched instead of `/`, e.g. an unpacked container image.
+    /// Python is never spawned when set, as the executables are not those of this machine.
+    pub root_directory: Option<PathBuf>,
+    /// Number of threads searching for environments, defaults to the number of CPUs (at least 4).
+    pub discovery_threads: Option<usize>,
+    /// What to do with search roots on network (or FUSE) file systems, defaults to `Reduce`.
+    pub network_filesystem_policy: Option<NetworkFilesystemPolicy>,
+    /// Whether directories on other file systems (mounts) are searched when walking the workspace folders.
+    pub cross_mount_boundaries: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+/// What to do with search roots (workspace folders & directories containing environments,
+/// including those searched by the locators, e.g. `~/.pyenv/versions`) on network (NFS, SMB, etc) or FUSE (e.g. sshfs) file systems, as these can be very slow to search.
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum NetworkFilesystemPolicy {
+    /// Searched like any other directory.
+    Search,
+    /// Searched with a reduced budget, i.e. a limited number of directories are looked at.
+    #[default]
+    Reduce,
+    /// Not searched.
+    Skip,
+}
+
+/// An executable that speaks the external locator protocol, see the `pet-external` crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalLocatorConfig {
+    pub executable: PathBuf,
+    /// Maximum time allowed for each request sent to the plugin.
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum LocatorKind {
     Conda,
+    External,
     Hatch,
     Homebrew,
     LinuxGlobal,
     MacCommandLineTools,
     MacPythonOrg,
     MacXCode,
+    Nox,
     PipEnv,
     Pixi,
     Poetry,
     PyEnv,
+    Tox,
     Uv,
     Venv,
     VirtualEnv,
@@ -145,7 +192,8 @@ pub trait Locator: Any + Send + Sync {
     /// long-lived shared locator.
     ///
     /// Override this only when `refresh_state()` returns
-    /// `RefreshStatePersistence::SyncedDiscoveryState`.
+    /// `RefreshStatePersistence::SyncedDiscoveryState`, or to drop cache entries of a
+    /// `RefreshStatePersistence::SelfHydratingCache` that the refresh made stale.
     fn sync_refresh_state_from(&self, _source: &dyn Locator, _scope: &RefreshStateSyncScope) {
         //
     }
@@ -160,6 +208,13 @@ pub trait Locator: Any + Send + Sync {
     fn try_from(&self, env: &PythonEnv) -> Option<PythonEnvironment>;
     /// Finds all environments specific to this locator.
     fn find(&self, reporter: &dyn Reporter);
+    /// Same as `find`, looking at (at most) the budget of directories in each search root.
+    ///
+    /// Override this (and have `find` call it with `Unlimited`) if the locator enumerates
+    /// directories that could be on a network file system, e.g. those in the home directory.
+    fn find_with_budget(&self, reporter: &dyn Reporter, _budget: &dyn SearchRootBudget) {
+        self.find(reporter);
+    }
 }
 
 impl dyn Locator {
@@ -8,15 +8,51 @@ use std::{
 };
 
 use log::trace;
+use pet_fs::file_system::{os_file_system, FileSystem, RootedFileSystem};
 use pet_fs::path::norm_case;
 
+/// Directories with the Python installs of the system, in addition to `PATH`.
+const UNIX_GLOBAL_SEARCH_LOCATIONS: [&str; 27] = [
+    "/bin",
+    "/etc",
+    "/lib",
+    "/lib/x86_64-linux-gnu",
+    "/lib64",
+    "/sbin",
+    "/snap/bin",
+    "/usr/bin",
+    "/usr/games",
+    "/usr/include",
+    "/usr/lib",
+    "/usr/lib/x86_64-linux-gnu",
+    "/usr/lib64",
+    "/usr/libexec",
+    "/usr/local",
+    "/usr/local/bin",
+    "/usr/local/etc",
+    "/usr/local/games",
+    "/usr/local/lib",
+    "/usr/local/sbin",
+    "/usr/sbin",
+    "/usr/share",
+    "/home/bin",
+    "/home/sbin",
+    "/opt",
+    "/opt/bin",
+    "/opt/sbin",
+];
+
 pub trait Environment: Send + Sync {
     fn get_user_home(&self) -> Option<PathBuf>;
-    /// Only used in tests, None in production.
-    #[allow(dead_code)]
+    /// Alternate root directory searched instead of `/` (e.g. an unpacked container image), see `RootedEnvironment`.
+    /// Tests also use this to create files in "system" locations such as `/etc/conda`.
     fn get_root(&self) -> Option<PathBuf>;
     fn get_env_var(&self, key: String) -> Option<String>;
     fn get_know_global_search_locations(&self) -> Vec<PathBuf>;
+    /// File system the environments are searched in, the real one unless testing.
+    fn file_system(&self) -> Arc<dyn FileSystem> {
+        os_file_system()
+    }
 }
 
 pub struct EnvironmentApi {
@@ -92,41 +128,14 @@ impl Environment for EnvironmentApi {
                 env::split_paths(&self.get_env_var("PATH".to_string()).unwrap_or_default())
                     .collect::<Vec<PathBuf>>();
             trace!("Env PATH: {:?}", paths);
-            vec![
-                PathBuf::from("/bin"),
-                PathBuf::from("/etc"),
-                PathBuf::from("/lib"),
-                PathBuf::from("/lib/x86_64-linux-gnu"),
-                PathBuf::from("/lib64"),
-                PathBuf::from("/sbin"),
-                PathBuf::from("/snap/bin"),
-                PathBuf::from("/usr/bin"),
-                PathBuf::from("/usr/games"),
-                PathBuf::from("/usr/include"),
-                PathBuf::from("/usr/lib"),
-                PathBuf::from("/usr/lib/x86_64-linux-gnu"),
-                PathBuf::from("/usr/lib64"),
-                PathBuf::from("/usr/libexec"),
-                PathBuf::from("/usr/local"),
-                PathBuf::from("/usr/local/bin"),
-                PathBuf::from("/usr/local/etc"),
-                PathBuf::from("/usr/local/games"),
-                PathBuf::from("/usr/local/lib"),
-                PathBuf::from("/usr/local/sbin"),
-                PathBuf::from("/usr/sbin"),
-                PathBuf::from("/usr/share"),
-                PathBuf::from("/home/bin"),
-                PathBuf::from("/home/sbin"),
-                PathBuf::from("/opt"),
-                PathBuf::from("/opt/bin"),
-                PathBuf::from("/opt/sbin"),
-            ]
-            .iter()
-            .for_each(|p| {
-                if !paths.contains(p) {
-                    paths.push(p.clone());
-                }
-            });
+            UNIX_GLOBAL_SEARCH_LOCATIONS
+                .iter()
+                .map(PathBuf::from)
+                .for_each(|p| {
+                    if !paths.contains(&p) {
+                        paths.push(p);
+                    }
+                });
 
             if let Some(home) = self.get_user_home() {
                 paths.push(home.join(".local").join("bin"));
@@ -149,6 +158,58 @@ impl Environment for EnvironmentApi {
     }
 }
 
+/// Environment of an alternate root directory, e.g. an unpacked container image or a build sysroot.
+///
+/// Paths are those of the host (`<root>/usr/bin`), see `FileSystem::to_image_path` for mapping them to the image.
+/// The environment variables of the host do not apply to the image, hence none are set.
+pub struct RootedEnvironment {
+    root: PathBuf,
+    user_home: Option<PathBuf>,
+    file_system: Arc<dyn FileSystem>,
+}
+
+impl RootedEnvironment {
+    pub fn new(root: PathBuf) -> Self {
+        let file_system = RootedFileSystem::new(&root);
+        let root = file_system
+            .root_directory()
+            .map(PathBuf::from)
+            .unwrap_or(root);
+        // Home of the same user (e.g. a sysroot of this machine), else of `root` (e.g. a container image).
+        let user_home = get_user_home()
+            .map(|home| root.join(home.components().skip(1).collect::<PathBuf>()))
+            .filter(|home| home.is_dir())
+            .or_else(|| Some(root.join("root")).filter(|home| home.is_dir()));
+        RootedEnvironment {
+            root,
+            user_home,
+            file_system: Arc::new(file_system),
+        }
+    }
+}
+
+impl Environment for RootedEnvironment {
+    fn get_user_home(&self) -> Option<PathBuf> {
+        self.user_home.clone()
+    }
+    fn get_root(&self) -> Option<PathBuf> {
+        Some(self.root.clone())
+    }
+    fn get_env_var(&self, _key: String) -> Option<String> {
+        None
+    }
+    fn get_know_global_search_locations(&self) -> Vec<PathBuf> {
+        UNIX_GLOBAL_SEARCH_LOCATIONS
+            .iter()
+            .map(|location| self.root.join(&location[1..]))
+            .filter(|location| location.exists())
+            .collect()
+    }
+    fn file_system(&self) -> Arc<dyn FileSystem> {
+        self.file_system.clone()
+    }
+}
+
 #[cfg(windows)]
 fn get_user_home() -> Option<PathBuf> {
     let home = env::var("USERPROFILE").or_else(|_| env::var("HOME"));
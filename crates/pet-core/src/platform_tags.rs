@@ -0,0 +1,33 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use serde::{Deserialize, Serialize};
+
+/// Wheel compatibility tags of an interpreter, see
+/// <https://packaging.python.org/en/latest/specifications/platform-compatibility-tags/>.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformTags {
+    /// Supported tags, most preferred first (same order as `packaging.tags.sys_tags()`),
+    /// e.g. `cp312-cp312-manylinux_2_28_x86_64`.
+    pub tags: Vec<String>,
+    /// Platform part of the tags, most preferred first, e.g. `manylinux_2_28_x86_64`.
+    pub platforms: Vec<String>,
+    /// C library the interpreter is linked against (Linux only).
+    pub libc: Option<Libc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Libc {
+    pub flavor: LibcFlavor,
+    /// E.g. `2.36` for glibc or `1.2` for musl, if known.
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LibcFlavor {
+    Glibc,
+    Musl,
+}
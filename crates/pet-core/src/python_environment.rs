@@ -7,9 +7,13 @@ use pet_fs::path::norm_case;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-use crate::{arch::Architecture, manager::EnvManager};
+use crate::{
+    arch::Architecture, manager::EnvManager, platform_tags::PlatformTags,
+    python_version::PythonVersion,
+};
 
 #[derive(Parser, ValueEnum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[non_exhaustive]
 pub enum PythonEnvironmentKind {
     Conda,
     Pixi,
@@ -20,18 +24,22 @@ pub enum PythonEnvironmentKind {
     Pipenv,
     Poetry,
     Hatch,
+    Tox, // Environments created by tox in the `.tox` directory of a project.
+    Nox, // Environments created by nox in the `.nox` directory of a project.
     MacPythonOrg,
     MacCommandLineTools,
     LinuxGlobal,
     MacXCode,
     Uv,
     UvWorkspace,
+    UvScript, // Environments uv creates to run a script with inline metadata (PEP 723), see `script`.
     Venv,
     VirtualEnv,
     VirtualEnvWrapper,
     WinPython,
     WindowsStore,
     WindowsRegistry,
+    External, // Environments reported by external locators (plugins), see `external_kind`.
 }
 impl Ord for PythonEnvironmentKind {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
@@ -57,7 +65,12 @@ pub struct PythonEnvironment {
     // Python executable, can be empty in the case of conda envs that do not have Python installed in them.
     pub executable: Option<PathBuf>,
     pub kind: Option<PythonEnvironmentKind>,
-    pub version: Option<String>,
+    /// Other kinds the environment was identified as, e.g. a uv venv in a Poetry project has the kind `Uv` & the tag `Poetry`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<PythonEnvironmentKind>>,
+    // Sent over the wire as the `version` string along with the structured `versionInfo`.
+    #[serde(flatten, with = "crate::python_version::version_with_info")]
+    pub version: Option<PythonVersion>,
     // SysPrefix for the environment.
     pub prefix: Option<PathBuf>,
     pub manager: Option<EnvManager>,
@@ -77,6 +90,20 @@ pub struct PythonEnvironment {
     /// If None, no known issues have been detected (but this doesn't guarantee
     /// the environment is fully functional - we don't spawn Python to verify).
     pub error: Option<String>,
+    /// The kind of environment as reported by an external locator (plugin),
+    /// e.g. `CompanyBundle`. Only set when `kind` is `External`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_kind: Option<String>,
+    /// Wheel compatibility tags, only set when requested (e.g. `includePlatformTags` of `resolve`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform_tags: Option<PlatformTags>,
+    /// The environment (by its ID, i.e. the executable or the prefix) this virtual environment was created from,
+    /// as recorded in `pyvenv.cfg`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_environment: Option<PathBuf>,
+    /// The script (with inline metadata as per PEP 723) the environment was created for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<PathBuf>,
 }
 
 impl Ord for PythonEnvironment {
@@ -105,7 +132,7 @@ impl PythonEnvironment {
         kind: Option<PythonEnvironmentKind>,
         prefix: Option<PathBuf>,
         manager: Option<EnvManager>,
-        version: Option<String>,
+        version: Option<PythonVersion>,
     ) -> Self {
         Self {
             executable,
@@ -123,11 +150,17 @@ impl std::fmt::Display for PythonEnvironment {
         writeln!(
             f,
             "Environment ({})",
-            self.kind
-                .map(|v| format!("{v:?}"))
-                .unwrap_or("Unknown".to_string())
+            self.external_kind.clone().unwrap_or(
+                self.kind
+                    .map(|v| format!("{v:?}"))
+                    .unwrap_or("Unknown".to_string())
+            )
         )
         .unwrap_or_default();
+        if let Some(tags) = &self.tags {
+            let tags: Vec<String> = tags.iter().map(|tag| format!("{tag:?}")).collect();
+            writeln!(f, "   Tags        : {}", tags.join(", ")).unwrap_or_default();
+        }
         if let Some(name) = &self.display_name {
             writeln!(f, "   Display-Name: {name}").unwrap_or_default();
         }
@@ -183,6 +216,22 @@ impl std::fmt::Display for PythonEnvironment {
                 }
             }
         }
+        if let Some(script) = &self.script {
+            writeln!(
+                f,
+                "   Script      : {}",
+                script.to_str().unwrap_or_default()
+            )
+            .unwrap_or_default();
+        }
+        if let Some(base_environment) = &self.base_environment {
+            writeln!(
+                f,
+                "   Base Env    : {}",
+                base_environment.to_str().unwrap_or_default()
+            )
+            .unwrap_or_default();
+        }
         if let Some(error) = &self.error {
             writeln!(f, "   Error       : {error}").unwrap_or_default();
         }
@@ -198,19 +247,25 @@ pub struct PythonEnvironmentBuilder {
     name: Option<String>,
     executable: Option<PathBuf>,
     kind: Option<PythonEnvironmentKind>,
-    version: Option<String>,
+    tags: Option<Vec<PythonEnvironmentKind>>,
+    version: Option<PythonVersion>,
     prefix: Option<PathBuf>,
     manager: Option<EnvManager>,
     project: Option<PathBuf>,
     arch: Option<Architecture>,
     symlinks: Option<Vec<PathBuf>>,
     error: Option<String>,
+    external_kind: Option<String>,
+    platform_tags: Option<PlatformTags>,
+    base_environment: Option<PathBuf>,
+    script: Option<PathBuf>,
 }
 
 impl PythonEnvironmentBuilder {
     pub fn new(kind: Option<PythonEnvironmentKind>) -> Self {
         Self {
             kind,
+            tags: None,
             display_name: None,
             name: None,
             executable: None,
@@ -221,11 +276,16 @@ impl PythonEnvironmentBuilder {
             arch: None,
             symlinks: None,
             error: None,
+            external_kind: None,
+            platform_tags: None,
+            base_environment: None,
+            script: None,
         }
     }
     pub fn from_environment(env: PythonEnvironment) -> Self {
         Self {
             kind: env.kind,
+            tags: env.tags,
             display_name: env.display_name,
             name: env.name,
             executable: env.executable,
@@ -236,9 +296,18 @@ impl PythonEnvironmentBuilder {
             arch: env.arch,
             symlinks: env.symlinks,
             error: env.error,
+            external_kind: env.external_kind,
+            platform_tags: env.platform_tags,
+            base_environment: env.base_environment,
+            script: env.script,
         }
     }
 
+    pub fn tags(mut self, tags: Option<Vec<PythonEnvironmentKind>>) -> Self {
+        self.tags = tags;
+        self
+    }
+
     pub fn display_name(mut self, display_name: Option<String>) -> Self {
         self.display_name = display_name;
         self
@@ -262,7 +331,7 @@ impl PythonEnvironmentBuilder {
         self
     }
 
-    pub fn version(mut self, version: Option<String>) -> Self {
+    pub fn version(mut self, version: Option<PythonVersion>) -> Self {
         self.version = version;
         self
     }
@@ -303,6 +372,26 @@ impl PythonEnvironmentBuilder {
         self
     }
 
+    pub fn external_kind(mut self, external_kind: Option<String>) -> Self {
+        self.external_kind = external_kind;
+        self
+    }
+
+    pub fn platform_tags(mut self, platform_tags: Option<PlatformTags>) -> Self {
+        self.platform_tags = platform_tags;
+        self
+    }
+
+    pub fn base_environment(mut self, base_environment: Option<PathBuf>) -> Self {
+        self.base_environment = base_environment;
+        self
+    }
+
+    pub fn script(mut self, script: Option<PathBuf>) -> Self {
+        self.script = script;
+        self
+    }
+
     fn update_symlinks_and_exe(&mut self, symlinks: Option<Vec<PathBuf>>) {
         let mut all = self.symlinks.clone().unwrap_or_default();
         if let Some(ref exe) = self.executable {
@@ -352,6 +441,7 @@ impl PythonEnvironmentBuilder {
             name: self.name,
             executable,
             kind: self.kind,
+            tags: self.tags,
             version: self.version,
             prefix: self.prefix,
             manager: self.manager,
@@ -359,6 +449,10 @@ impl PythonEnvironmentBuilder {
             arch: self.arch,
             symlinks,
             error: self.error,
+            external_kind: self.external_kind,
+            platform_tags: self.platform_tags,
+            base_environment: self.base_environment,
+            script: self.script,
         }
     }
 }
@@ -440,7 +534,10 @@ pub fn get_environment_key(env: &PythonEnvironment) -> Option<PathBuf> {
 
 #[cfg(test)]
 mod tests {
-    use super::{get_environment_key, PythonEnvironment, PythonEnvironmentKind};
+    use super::{
+        get_environment_key, PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind,
+    };
+    use crate::platform_tags::PlatformTags;
     use std::path::PathBuf;
 
     #[cfg(windows)]
@@ -524,4 +621,76 @@ mod tests {
     fn environment_key_returns_none_without_executable_or_prefix() {
         assert_eq!(get_environment_key(&PythonEnvironment::default()), None);
     }
+
+    #[test]
+    fn version_is_sent_as_a_string_with_structured_version_info() {
+        let environment = PythonEnvironment {
+            version: Some("3.13.0rc2t".parse().unwrap()),
+            ..Default::default()
+        };
+
+        let value = serde_json::to_value(&environment).unwrap();
+        assert_eq!(value["version"], "3.13.0rc2t");
+        assert_eq!(
+            value["versionInfo"],
+            serde_json::json!({
+                "major": 3,
+                "minor": 13,
+                "micro": 0,
+                "releaseLevel": "candidate",
+                "serial": 2,
+                "freeThreaded": true
+            })
+        );
+
+        let environment: PythonEnvironment = serde_json::from_value(value).unwrap();
+        assert_eq!(environment.version, Some("3.13.0rc2t".parse().unwrap()));
+    }
+
+    #[test]
+    fn version_that_cannot_be_parsed_is_sent_without_version_info() {
+        let value = serde_json::json!({ "version": "3.12-custom", "versionInfo": null });
+
+        let environment: PythonEnvironment = serde_json::from_value(value).unwrap();
+        assert_eq!(
+            environment.version.as_ref().unwrap().as_str(),
+            "3.12-custom"
+        );
+
+        let value = serde_json::to_value(&environment).unwrap();
+        assert_eq!(value["version"], "3.12-custom");
+        assert_eq!(value["versionInfo"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn environment_without_version_sends_null_version_fields() {
+        let value = serde_json::to_value(PythonEnvironment::default()).unwrap();
+        assert_eq!(value["version"], serde_json::Value::Null);
+        assert_eq!(value["versionInfo"], serde_json::Value::Null);
+        assert!(value.as_object().unwrap().contains_key("version"));
+        assert!(value.as_object().unwrap().contains_key("versionInfo"));
+
+        let environment: PythonEnvironment = serde_json::from_value(value).unwrap();
+        assert_eq!(environment.version, None);
+    }
+
+    #[test]
+    fn builder_keeps_the_platform_tags_of_the_environment() {
+        let platform_tags = PlatformTags {
+            tags: vec!["cp312-cp312-linux_x86_64".to_string()],
+            platforms: vec!["linux_x86_64".to_string()],
+            libc: None,
+        };
+        let environment = PythonEnvironment {
+            kind: Some(PythonEnvironmentKind::Venv),
+            platform_tags: Some(platform_tags.clone()),
+            ..Default::default()
+        };
+
+        let environment = PythonEnvironmentBuilder::from_environment(environment)
+            .name(Some("env".to_string()))
+            .build();
+
+        assert_eq!(environment.platform_tags, Some(platform_tags));
+    }
 }
@@ -0,0 +1,823 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! PEP 440 versions of Python interpreters, along with version specifiers (e.g. `>=3.9,<3.13`).
+//!
+//! On the wire a version is always the string it was parsed from (e.g. `3.13.0rc1` or `3.13t`),
+//! the structured `versionInfo` is sent alongside it.
+//! Versions reported by tools that are not PEP 440 versions are kept as is, without a `versionInfo`.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+lazy_static! {
+    /// Same as `packaging.version.VERSION_PATTERN`.
+    static ref PEP440: Regex = Regex::new(
+        r"(?x)
+        ^v?
+        (?:(?P<epoch>[0-9]+)!)?
+        (?P<release>[0-9]+(?:\.[0-9]+)*)
+        (?P<pre>[-_.]?(?P<pre_l>alpha|a|beta|b|preview|pre|c|rc)[-_.]?(?P<pre_n>[0-9]+)?)?
+        (?P<post>(?:-(?P<post_n1>[0-9]+))|(?:[-_.]?(?P<post_l>post|rev|r)[-_.]?(?P<post_n2>[0-9]+)?))?
+        (?P<dev>[-_.]?dev[-_.]?(?P<dev_n>[0-9]+)?)?
+        (?:\+(?P<local>[a-z0-9]+(?:[-_.][a-z0-9]+)*))?
+        $"
+    )
+    .expect("error parsing PEP 440 version regex");
+    /// String form of `sys.version_info`, e.g. `3.8.1.final.0` (found in `pyvenv.cfg` files).
+    static ref VERSION_INFO: Regex = Regex::new(
+        r"^(?P<release>\d+\.\d+\.\d+)\.(?P<level>alpha|beta|candidate|final)(?:\.(?P<serial>\d+))?$"
+    )
+    .expect("error parsing version_info regex");
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PreReleaseKind {
+    Alpha,
+    Beta,
+    Rc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PreRelease {
+    pub kind: PreReleaseKind,
+    pub number: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseLevel {
+    Alpha,
+    Beta,
+    Candidate,
+    Final,
+}
+
+/// Same as `sys.version_info`, along with whether the build is free-threaded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionInfo {
+    pub major: u64,
+    pub minor: u64,
+    pub micro: u64,
+    pub release_level: ReleaseLevel,
+    pub serial: u64,
+    pub free_threaded: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum LocalSegment {
+    Number(u64),
+    String(String),
+}
+
+impl PartialOrd for LocalSegment {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LocalSegment {
+    /// Numeric segments always sort after alphanumeric ones.
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (LocalSegment::Number(a), LocalSegment::Number(b)) => a.cmp(b),
+            (LocalSegment::String(a), LocalSegment::String(b)) => a.cmp(b),
+            (LocalSegment::Number(_), LocalSegment::String(_)) => Ordering::Greater,
+            (LocalSegment::String(_), LocalSegment::Number(_)) => Ordering::Less,
+        }
+    }
+}
+
+/// A PEP 440 version, e.g. `3.12.1`, `3.13.0rc1`, `3.14.0a1.dev0`, `3.12.1+local`.
+/// Free-threaded builds are denoted with a `t` suffix, e.g. `3.13t`.
+///
+/// Versions are compared as per PEP 440 (`3.12` == `3.12.0` < `3.12.1rc1` < `3.12.1`),
+/// free-threaded builds sort after the regular build of the same version.
+/// Versions that are not PEP 440 versions (see `parse_lossless`) sort before all others, by their text.
+#[derive(Debug, Clone)]
+pub struct PythonVersion {
+    epoch: u64,
+    release: Vec<u64>,
+    pre: Option<PreRelease>,
+    post: Option<u64>,
+    dev: Option<u64>,
+    local: Vec<LocalSegment>,
+    free_threaded: bool,
+    /// The original text, this is what is displayed & sent over the wire.
+    text: String,
+    /// Whether the text is a PEP 440 version, else only the text is known.
+    valid: bool,
+}
+
+impl PythonVersion {
+    /// Parses a PEP 440 version, the `sys.version_info` form (`3.8.1.final.0`) is accepted as well,
+    /// as are the `t` suffix of free-threaded builds & the trailing `+` of builds from source (`3.13.0a1+`).
+    pub fn parse(value: &str) -> Option<Self> {
+        let text = value.trim();
+        let mut normalized = text.to_lowercase();
+        if normalized.ends_with('+') {
+            normalized.pop();
+        }
+        let free_threaded = normalized.len() > 1
+            && normalized.ends_with('t')
+            && normalized[..normalized.len() - 1].ends_with(|c: char| c.is_ascii_digit());
+        if free_threaded {
+            normalized.pop();
+        }
+
+        if let Some(captures) = VERSION_INFO.captures(&normalized) {
+            let pre = match &captures["level"] {
+                "alpha" => Some(PreReleaseKind::Alpha),
+                "beta" => Some(PreReleaseKind::Beta),
+                "candidate" => Some(PreReleaseKind::Rc),
+                _ => None,
+            };
+            let number = match captures.name("serial") {
+                Some(serial) => serial.as_str().parse().ok()?,
+                None => 0,
+            };
+            return Some(PythonVersion {
+                epoch: 0,
+                release: parse_release(&captures["release"])?,
+                pre: pre.map(|kind| PreRelease { kind, number }),
+                post: None,
+                dev: None,
+                local: vec![],
+                free_threaded,
+                text: text.to_string(),
+                valid: true,
+            });
+        }
+
+        let captures = PEP440.captures(&normalized)?;
+        let number = |name: &str| -> Option<u64> {
+            captures.name(name).and_then(|n| n.as_str().parse().ok())
+        };
+        let pre = match captures.name("pre_l").map(|l| l.as_str()) {
+            Some("a" | "alpha") => Some(PreReleaseKind::Alpha),
+            Some("b" | "beta") => Some(PreReleaseKind::Beta),
+            Some(_) => Some(PreReleaseKind::Rc),
+            None => None,
+        };
+        let post = if captures.name("post").is_some() {
+            Some(number("post_n1").or(number("post_n2")).unwrap_or_default())
+        } else {
+            None
+        };
+        let dev = if captures.name("dev").is_some() {
+            Some(number("dev_n").unwrap_or_default())
+        } else {
+            None
+        };
+        let local = captures
+            .name("local")
+            .map(|local| {
+                local
+                    .as_str()
+                    .split(['-', '_', '.'])
+                    .map(|segment| match segment.parse() {
+                        Ok(number) => LocalSegment::Number(number),
+                        Err(_) => LocalSegment::String(segment.to_string()),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(PythonVersion {
+            epoch: number("epoch").unwrap_or_default(),
+            release: parse_release(&captures["release"])?,
+            pre: pre.map(|kind| PreRelease {
+                kind,
+                number: number("pre_n").unwrap_or_default(),
+            }),
+            post,
+            dev,
+            local,
+            free_threaded,
+            text: text.to_string(),
+            valid: true,
+        })
+    }
+
+    /// Same as `parse`, versions that are not PEP 440 versions (e.g. `3.12-custom` or `system`) are kept as is,
+    /// so that the version reported by a tool is never lost. Only empty versions are ignored.
+    pub fn parse_lossless(value: &str) -> Option<Self> {
+        let text = value.trim();
+        if text.is_empty() {
+            return None;
+        }
+        Some(PythonVersion::parse(text).unwrap_or_else(|| PythonVersion {
+            epoch: 0,
+            release: vec![],
+            pre: None,
+            post: None,
+            dev: None,
+            local: vec![],
+            free_threaded: false,
+            text: text.to_string(),
+            valid: false,
+        }))
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+    pub fn release(&self) -> &[u64] {
+        &self.release
+    }
+    pub fn major(&self) -> u64 {
+        self.release_part(0)
+    }
+    pub fn minor(&self) -> u64 {
+        self.release_part(1)
+    }
+    pub fn micro(&self) -> u64 {
+        self.release_part(2)
+    }
+    pub fn pre(&self) -> Option<PreRelease> {
+        self.pre
+    }
+    pub fn post(&self) -> Option<u64> {
+        self.post
+    }
+    pub fn dev(&self) -> Option<u64> {
+        self.dev
+    }
+    pub fn is_prerelease(&self) -> bool {
+        self.pre.is_some() || self.dev.is_some()
+    }
+    pub fn is_free_threaded(&self) -> bool {
+        self.free_threaded
+    }
+    /// Whether the version has at least the `major.minor.micro` parts.
+    pub fn is_complete(&self) -> bool {
+        self.release.len() >= 3
+    }
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+    /// Whether this is a PEP 440 version, i.e. not only the text reported by a tool.
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    /// `None` for versions that are not PEP 440 versions.
+    pub fn version_info(&self) -> Option<VersionInfo> {
+        if !self.valid {
+            return None;
+        }
+        let (release_level, serial) = match (self.pre, self.dev) {
+            (Some(pre), _) => (
+                match pre.kind {
+                    PreReleaseKind::Alpha => ReleaseLevel::Alpha,
+                    PreReleaseKind::Beta => ReleaseLevel::Beta,
+                    PreReleaseKind::Rc => ReleaseLevel::Candidate,
+                },
+                pre.number,
+            ),
+            // Development builds of CPython report themselves as alpha releases.
+            (None, Some(_)) => (ReleaseLevel::Alpha, 0),
+            (None, None) => (ReleaseLevel::Final, 0),
+        };
+        Some(VersionInfo {
+            major: self.major(),
+            minor: self.minor(),
+            micro: self.micro(),
+            release_level,
+            serial,
+            free_threaded: self.free_threaded,
+        })
+    }
+
+    /// Whether the version satisfies all of the specifiers, e.g. `>=3.9,<3.13`.
+    pub fn matches(&self, specifiers: &VersionSpecifiers) -> bool {
+        specifiers.contains(self)
+    }
+
+    fn release_part(&self, index: usize) -> u64 {
+        self.release.get(index).copied().unwrap_or_default()
+    }
+
+    /// The release without the trailing zeros, `3.12.0` is the same as `3.12`.
+    fn trimmed_release(&self) -> &[u64] {
+        let len = self
+            .release
+            .iter()
+            .rposition(|part| *part != 0)
+            .map_or(0, |index| index + 1);
+        &self.release[..len]
+    }
+
+    /// Same as `packaging.version._cmpkey`, without the local segment.
+    fn cmp_public(&self, other: &Self) -> Ordering {
+        // Dev releases without a pre release sort before pre releases.
+        let pre_key = |version: &PythonVersion| match (version.pre, version.post, version.dev) {
+            (None, None, Some(_)) => (0, None),
+            (None, _, _) => (2, None),
+            (Some(pre), _, _) => (1, Some(pre)),
+        };
+        let post_key = |version: &PythonVersion| version.post.map_or(0, |post| post + 1);
+        let dev_key = |version: &PythonVersion| version.dev.map_or(u64::MAX, |dev| dev);
+
+        self.epoch
+            .cmp(&other.epoch)
+            .then_with(|| self.trimmed_release().cmp(other.trimmed_release()))
+            .then_with(|| pre_key(self).cmp(&pre_key(other)))
+            .then_with(|| post_key(self).cmp(&post_key(other)))
+            .then_with(|| dev_key(self).cmp(&dev_key(other)))
+    }
+
+    /// The same version without the local segment.
+    fn public(&self) -> PythonVersion {
+        PythonVersion {
+            local: vec![],
+            ..self.clone()
+        }
+    }
+}
+
+fn parse_release(release: &str) -> Option<Vec<u64>> {
+    release.split('.').map(|part| part.parse().ok()).collect()
+}
+
+impl PartialEq for PythonVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for PythonVersion {}
+
+impl PartialOrd for PythonVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PythonVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.valid, other.valid) {
+            (true, true) => self
+                .cmp_public(other)
+                .then_with(|| self.local.cmp(&other.local))
+                .then_with(|| self.free_threaded.cmp(&other.free_threaded)),
+            (false, false) => self.text.cmp(&other.text),
+            (valid, other_valid) => valid.cmp(&other_valid),
+        }
+    }
+}
+
+impl Hash for PythonVersion {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.valid.hash(state);
+        if !self.valid {
+            self.text.hash(state);
+            return;
+        }
+        self.epoch.hash(state);
+        self.trimmed_release().hash(state);
+        self.pre.hash(state);
+        self.post.hash(state);
+        self.dev.hash(state);
+        self.local.hash(state);
+        self.free_threaded.hash(state);
+    }
+}
+
+impl fmt::Display for PythonVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl FromStr for PythonVersion {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        PythonVersion::parse(value).ok_or_else(|| format!("Invalid Python version {value:?}"))
+    }
+}
+
+impl Serialize for PythonVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.text)
+    }
+}
+
+/// Versions that are not PEP 440 versions are kept as is, as they are when parsing the versions reported by tools.
+impl<'de> Deserialize<'de> for PythonVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        PythonVersion::parse_lossless(&value)
+            .ok_or_else(|| de::Error::custom("empty Python version"))
+    }
+}
+
+/// (De)serializes an optional version as the `version` string & the `versionInfo` object alongside.
+/// Used with `#[serde(flatten, with = "...")]`.
+/// `versionInfo` is `null` for versions that are not PEP 440 versions, these are kept as is when deserializing.
+pub mod version_with_info {
+    use super::{PythonVersion, VersionInfo};
+    use serde::{ser::SerializeMap, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        version: &Option<PythonVersion>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        // Both are `null` when the version is not known, as are the other fields of an environment.
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("version", &version.as_ref().map(PythonVersion::as_str))?;
+        map.serialize_entry::<_, Option<VersionInfo>>(
+            "versionInfo",
+            &version.as_ref().and_then(PythonVersion::version_info),
+        )?;
+        map.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<PythonVersion>, D::Error> {
+        #[derive(Deserialize)]
+        struct Fields {
+            version: Option<String>,
+        }
+        let fields = Fields::deserialize(deserializer)?;
+        Ok(fields
+            .version
+            .and_then(|version| PythonVersion::parse_lossless(&version)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    /// `==`, or `==3.12.*` when the specifier has a wildcard.
+    Equal,
+    /// `!=`, or `!=3.12.*` when the specifier has a wildcard.
+    NotEqual,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+    /// `~=3.12.1` is the same as `>=3.12.1,==3.12.*`.
+    Compatible,
+    /// `===`, compares the strings.
+    Arbitrary,
+}
+
+/// A single PEP 440 version specifier, e.g. `>=3.9` or `==3.12.*`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionSpecifier {
+    pub operator: Operator,
+    pub version: PythonVersion,
+    pub wildcard: bool,
+}
+
+impl VersionSpecifier {
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (operator, version) = [
+            ("===", Operator::Arbitrary),
+            ("~=", Operator::Compatible),
+            ("==", Operator::Equal),
+            ("!=", Operator::NotEqual),
+            ("<=", Operator::LessThanOrEqual),
+            (">=", Operator::GreaterThanOrEqual),
+            ("<", Operator::LessThan),
+            (">", Operator::GreaterThan),
+        ]
+        .into_iter()
+        .find_map(|(prefix, operator)| {
+            value
+                .strip_prefix(prefix)
+                .map(|version| (operator, version.trim()))
+        })?;
+
+        let (version, wildcard) = match version.strip_suffix(".*") {
+            Some(version) if matches!(operator, Operator::Equal | Operator::NotEqual) => {
+                (version, true)
+            }
+            Some(_) => return None,
+            None => (version, false),
+        };
+        let version = PythonVersion::parse(version)?;
+        if operator == Operator::Compatible && version.release.len() < 2 {
+            return None;
+        }
+        Some(VersionSpecifier {
+            operator,
+            version,
+            wildcard,
+        })
+    }
+
+    /// Pre-releases are matched, i.e. `3.13.0rc1` satisfies `>=3.12`.
+    /// Versions that are not PEP 440 versions never match.
+    pub fn contains(&self, version: &PythonVersion) -> bool {
+        if !version.valid {
+            return false;
+        }
+        let spec = &self.version;
+        match self.operator {
+            Operator::Equal if self.wildcard => is_prefix(spec, version),
+            Operator::NotEqual if self.wildcard => !is_prefix(spec, version),
+            Operator::Equal => is_equal(spec, version),
+            Operator::NotEqual => !is_equal(spec, version),
+            Operator::LessThanOrEqual => version.cmp_public(spec) != Ordering::Greater,
+            Operator::GreaterThanOrEqual => version.cmp_public(spec) != Ordering::Less,
+            Operator::LessThan => {
+                // `<3.13` excludes pre-releases of 3.13, unless the specifier is a pre-release.
+                version.cmp_public(spec) == Ordering::Less
+                    && (spec.is_prerelease()
+                        || !version.is_prerelease()
+                        || version.trimmed_release() != spec.trimmed_release())
+            }
+            Operator::GreaterThan => {
+                // `>3.12` excludes post-releases of 3.12, unless the specifier is a post-release.
+                version.cmp_public(spec) == Ordering::Greater
+                    && (spec.post.is_some()
+                        || version.post.is_none()
+                        || version.trimmed_release() != spec.trimmed_release())
+            }
+            Operator::Compatible => {
+                let prefix = PythonVersion {
+                    release: spec.release[..spec.release.len() - 1].to_vec(),
+                    pre: None,
+                    post: None,
+                    dev: None,
+                    ..spec.clone()
+                };
+                version.cmp_public(spec) != Ordering::Less && is_prefix(&prefix, version)
+            }
+            Operator::Arbitrary => version.text.eq_ignore_ascii_case(&spec.text),
+        }
+    }
+}
+
+/// `==3.12.*`, the release of the version starts with that of the specifier.
+fn is_prefix(spec: &PythonVersion, version: &PythonVersion) -> bool {
+    let release = (0..spec.release.len())
+        .map(|index| version.release_part(index))
+        .collect::<Vec<_>>();
+    spec.epoch == version.epoch && release == spec.release
+}
+
+/// `==3.12.1` ignores the local segment of the version, unless the specifier has one.
+fn is_equal(spec: &PythonVersion, version: &PythonVersion) -> bool {
+    if spec.local.is_empty() {
+        version.cmp_public(spec) == Ordering::Equal
+    } else {
+        version.public().cmp_public(spec) == Ordering::Equal && version.local == spec.local
+    }
+}
+
+impl fmt::Display for VersionSpecifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operator = match self.operator {
+            Operator::Equal => "==",
+            Operator::NotEqual => "!=",
+            Operator::LessThan => "<",
+            Operator::LessThanOrEqual => "<=",
+            Operator::GreaterThan => ">",
+            Operator::GreaterThanOrEqual => ">=",
+            Operator::Compatible => "~=",
+            Operator::Arbitrary => "===",
+        };
+        let wildcard = if self.wildcard { ".*" } else { "" };
+        write!(f, "{operator}{}{wildcard}", self.version)
+    }
+}
+
+/// Comma separated PEP 440 version specifiers, e.g. `>=3.9,<3.13`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VersionSpecifiers(pub Vec<VersionSpecifier>);
+
+impl VersionSpecifiers {
+    pub fn parse(value: &str) -> Option<Self> {
+        if value.trim().is_empty() {
+            return Some(VersionSpecifiers::default());
+        }
+        value
+            .split(',')
+            .map(VersionSpecifier::parse)
+            .collect::<Option<Vec<_>>>()
+            .map(VersionSpecifiers)
+    }
+
+    pub fn contains(&self, version: &PythonVersion) -> bool {
+        self.0.iter().all(|specifier| specifier.contains(version))
+    }
+}
+
+impl FromStr for VersionSpecifiers {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        VersionSpecifiers::parse(value)
+            .ok_or_else(|| format!("Invalid version specifiers {value:?}"))
+    }
+}
+
+impl fmt::Display for VersionSpecifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let specifiers = self
+            .0
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        f.write_str(&specifiers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(value: &str) -> PythonVersion {
+        PythonVersion::parse(value).unwrap()
+    }
+
+    fn matches(value: &str, specifiers: &str) -> bool {
+        version(value).matches(&VersionSpecifiers::parse(specifiers).unwrap())
+    }
+
+    #[test]
+    fn parses_release_pre_post_dev_and_local_segments() {
+        let v = version("1!3.14.0a1.post2.dev3+ubuntu.1");
+        assert_eq!(v.epoch(), 1);
+        assert_eq!(v.release(), &[3, 14, 0]);
+        assert_eq!(
+            v.pre(),
+            Some(PreRelease {
+                kind: PreReleaseKind::Alpha,
+                number: 1
+            })
+        );
+        assert_eq!(v.post(), Some(2));
+        assert_eq!(v.dev(), Some(3));
+        assert_eq!(
+            v.local,
+            vec![
+                LocalSegment::String("ubuntu".to_string()),
+                LocalSegment::Number(1)
+            ]
+        );
+        assert_eq!(v.to_string(), "1!3.14.0a1.post2.dev3+ubuntu.1");
+    }
+
+    #[test]
+    fn parses_alternative_spellings() {
+        assert_eq!(version("3.13.0RC1"), version("3.13.0rc1"));
+        assert_eq!(version("3.13.0-preview-1"), version("3.13.0rc1"));
+        assert_eq!(version("v3.12.1"), version("3.12.1"));
+        assert_eq!(version("3.12.1-1"), version("3.12.1.post1"));
+        assert_eq!(version("3.8.1.final.0"), version("3.8.1"));
+        assert_eq!(version("3.12.0.final"), version("3.12.0"));
+        assert_eq!(version("3.12.0.candidate.2"), version("3.12.0rc2"));
+        assert_eq!(version("3.13.0a1+"), version("3.13.0a1"));
+        assert_eq!(version("3.13.0a1+").to_string(), "3.13.0a1+");
+        assert!(PythonVersion::parse("3.x").is_none());
+        assert!(PythonVersion::parse("").is_none());
+        assert!(PythonVersion::parse("system").is_none());
+    }
+
+    #[test]
+    fn parses_free_threaded_suffix() {
+        let v = version("3.13t");
+        assert!(v.is_free_threaded());
+        assert_eq!(v.release(), &[3, 13]);
+        assert!(version("3.13.0rc1t").is_free_threaded());
+        assert!(!version("3.13.0").is_free_threaded());
+        assert_ne!(version("3.13"), version("3.13t"));
+        assert!(version("3.13") < version("3.13t"));
+    }
+
+    #[test]
+    fn orders_as_per_pep_440() {
+        let ordered = [
+            "3.12.0.dev1",
+            "3.12.0a1.dev1",
+            "3.12.0a1",
+            "3.12.0b2",
+            "3.12.0rc1",
+            "3.12",
+            "3.12.0+local",
+            "3.12.0.post1.dev1",
+            "3.12.0.post1",
+            "3.12.1",
+            "3.13",
+            "1!2.7",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(
+                version(pair[0]) < version(pair[1]),
+                "{} < {}",
+                pair[0],
+                pair[1]
+            );
+        }
+        assert_eq!(version("3.12"), version("3.12.0"));
+    }
+
+    #[test]
+    fn version_info() {
+        assert_eq!(
+            version("3.13.0rc2t").version_info().unwrap(),
+            VersionInfo {
+                major: 3,
+                minor: 13,
+                micro: 0,
+                release_level: ReleaseLevel::Candidate,
+                serial: 2,
+                free_threaded: true,
+            }
+        );
+        assert_eq!(
+            version("3.12").version_info().unwrap().release_level,
+            ReleaseLevel::Final
+        );
+        assert_eq!(
+            PythonVersion::parse_lossless("3.12-custom")
+                .unwrap()
+                .version_info(),
+            None
+        );
+    }
+
+    #[test]
+    fn keeps_the_text_of_versions_that_cannot_be_parsed() {
+        let v = PythonVersion::parse_lossless(" 3.12-custom ").unwrap();
+        assert!(!v.is_valid());
+        assert_eq!(v.to_string(), "3.12-custom");
+        assert_eq!(
+            PythonVersion::parse_lossless("3.12.1"),
+            Some(version("3.12.1"))
+        );
+        assert!(PythonVersion::parse_lossless("3.12.1").unwrap().is_valid());
+        assert_eq!(PythonVersion::parse_lossless(" "), None);
+        assert!(v < version("0.1"));
+        assert!(v < PythonVersion::parse_lossless("system").unwrap());
+        assert_eq!(v, PythonVersion::parse_lossless("3.12-custom").unwrap());
+        assert!(!v.matches(&VersionSpecifiers::parse("<3.13").unwrap()));
+    }
+
+    #[test]
+    fn serializes_as_a_string() {
+        let v = version("3.13.0b1");
+        assert_eq!(serde_json::to_string(&v).unwrap(), "\"3.13.0b1\"");
+        assert_eq!(
+            serde_json::from_str::<PythonVersion>("\"3.13.0b1\"").unwrap(),
+            v
+        );
+        let abc = serde_json::from_str::<PythonVersion>("\"abc\"").unwrap();
+        assert!(!abc.is_valid());
+        assert_eq!(serde_json::to_string(&abc).unwrap(), "\"abc\"");
+    }
+
+    #[test]
+    fn specifiers() {
+        assert!(matches("3.12.1", ">=3.9,<3.13"));
+        assert!(!matches("3.13.0", ">=3.9,<3.13"));
+        assert!(!matches("3.13.0rc1", "<3.13"));
+        assert!(matches("3.13.0a1", "<3.13.0rc1"));
+        assert!(matches("3.12.4", "==3.12.*"));
+        assert!(matches("3.12", "==3.12.*"));
+        assert!(!matches("3.13.0", "==3.12.*"));
+        assert!(matches("3.11.9", "!=3.12.*"));
+        assert!(matches("3.12.0", "==3.12"));
+        assert!(matches("3.12.0+local", "==3.12.0"));
+        assert!(!matches("3.12.0", "==3.12.0+local"));
+        assert!(matches("3.12.5", "~=3.12.1"));
+        assert!(!matches("3.13.0", "~=3.12.1"));
+        assert!(matches("3.13.0", "~=3.12"));
+        assert!(!matches("4.0", "~=3.12"));
+        assert!(!matches("3.12.0.post1", ">3.12.0"));
+        assert!(matches("3.12.1", ">3.12.0"));
+        assert!(matches("3.12.1", "<=3.12.1"));
+        assert!(matches("3.13t", ">=3.13"));
+        assert!(matches("3.12.1", "===3.12.1"));
+        assert!(!matches("3.12.1", "===3.12.01"));
+        assert!(matches("3.12.1", ""));
+    }
+
+    #[test]
+    fn invalid_specifiers() {
+        assert!(VersionSpecifiers::parse("3.12").is_none());
+        assert!(VersionSpecifiers::parse("~=3").is_none());
+        assert!(VersionSpecifiers::parse(">=3.12.*").is_none());
+        assert_eq!(
+            VersionSpecifiers::parse(" >= 3.9 , ==3.12.* ")
+                .unwrap()
+                .to_string(),
+            ">=3.9,==3.12.*"
+        );
+    }
+}
@@ -1,12 +1,11 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+use crate::python_version::PythonVersion;
 use lazy_static::lazy_static;
+use pet_fs::file_system::{FileSystem, OsFileSystem};
 use regex::Regex;
-use std::{
-    fs,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
 lazy_static! {
     static ref VERSION: Regex = Regex::new(r"^version\s*=\s*(\d+\.\d+\.\d+)$")
@@ -19,39 +18,34 @@ const PYVENV_CONFIG_FILE: &str = "pyvenv.cfg";
 
 #[derive(Debug)]
 pub struct PyVenvCfg {
-    pub version: Option<String>,
+    pub version: Option<PythonVersion>,
     pub version_major: Option<u64>,
     pub version_minor: Option<u64>,
     pub prompt: Option<String>,
+    /// Directory containing the interpreter the environment was created from (`home`).
+    pub home: Option<PathBuf>,
+    /// The interpreter the environment was created from, written by uv (`base-executable`)
+    /// & Python 3.11+ (`executable`).
+    pub base_executable: Option<PathBuf>,
     pub file_path: PathBuf,
 }
 
 impl PyVenvCfg {
-    fn new(
-        version: Option<String>,
-        version_major: Option<u64>,
-        version_minor: Option<u64>,
-        prompt: Option<String>,
-        file_path: PathBuf,
-    ) -> Self {
-        Self {
-            version,
-            version_major,
-            version_minor,
-            prompt,
-            file_path,
-        }
-    }
     pub fn find(path: &Path) -> Option<Self> {
-        if let Some(ref file) = find(path) {
-            parse(file)
+        PyVenvCfg::find_in(&OsFileSystem, path)
+    }
+
+    /// Same as `find`, looking for the file in the given file system.
+    pub fn find_in(file_system: &dyn FileSystem, path: &Path) -> Option<Self> {
+        if let Some(ref file) = find(file_system, path) {
+            parse(file_system, file)
         } else {
             None
         }
     }
 }
 
-fn find(path: &Path) -> Option<PathBuf> {
+fn find(file_system: &dyn FileSystem, path: &Path) -> Option<PathBuf> {
     // env
     // |__ pyvenv.cfg  <--- check if this file exists
     // |__ bin or Scripts
@@ -60,7 +54,7 @@ fn find(path: &Path) -> Option<PathBuf> {
     // Check if the pyvenv.cfg file is in the current directory.
     // Possible the passed value is the `env`` directory.
     let cfg = path.join(PYVENV_CONFIG_FILE);
-    if cfg.exists() {
+    if file_system.exists(&cfg) {
         return Some(cfg);
     }
 
@@ -68,7 +62,7 @@ fn find(path: &Path) -> Option<PathBuf> {
         // Only windows installations have a `Scripts` directory.
         if path.ends_with("Scripts") {
             let cfg = path.parent()?.join(PYVENV_CONFIG_FILE);
-            if cfg.exists() {
+            if file_system.exists(&cfg) {
                 return Some(cfg);
             }
         }
@@ -76,7 +70,7 @@ fn find(path: &Path) -> Option<PathBuf> {
     // Some windows installations have a `bin` directory. https://github.com/microsoft/vscode-python/issues/24792
     if path.ends_with("bin") {
         let cfg = path.parent()?.join(PYVENV_CONFIG_FILE);
-        if cfg.exists() {
+        if file_system.exists(&cfg) {
             return Some(cfg);
         }
     }
@@ -96,23 +90,25 @@ fn find(path: &Path) -> Option<PathBuf> {
     None
 }
 
-fn parse(file: &Path) -> Option<PyVenvCfg> {
-    let contents = fs::read_to_string(file).ok()?;
-    let mut version: Option<String> = None;
+fn parse(file_system: &dyn FileSystem, file: &Path) -> Option<PyVenvCfg> {
+    let contents = file_system.read_to_string(file).ok()?;
+    let mut version: Option<PythonVersion> = None;
     let mut version_major: Option<u64> = None;
     let mut version_minor: Option<u64> = None;
     let mut prompt: Option<String> = None;
+    let mut home: Option<PathBuf> = None;
+    let mut base_executable: Option<PathBuf> = None;
 
     for line in contents.lines() {
         if version.is_none() {
             if let Some((ver, major, minor)) = parse_version(line, &VERSION) {
-                version = Some(ver);
+                version = PythonVersion::parse_lossless(&ver);
                 version_major = Some(major);
                 version_minor = Some(minor);
                 continue;
             }
             if let Some((ver, major, minor)) = parse_version(line, &VERSION_INFO) {
-                version = Some(ver);
+                version = PythonVersion::parse_lossless(&ver);
                 version_major = Some(major);
                 version_minor = Some(minor);
                 continue;
@@ -121,24 +117,46 @@ fn parse(file: &Path) -> Option<PyVenvCfg> {
         if prompt.is_none() {
             if let Some(p) = parse_prompt(line) {
                 prompt = Some(p);
+                continue;
             }
         }
-        if version.is_some() && prompt.is_some() {
-            break;
+        if let Some((key, value)) = parse_key_value(line) {
+            // Paths in the file are those of the image when searching an alternate root.
+            match key {
+                "home" => home = Some(file_system.to_host_path(Path::new(value))),
+                // uv writes `base-executable`, while venv (3.11+) writes `executable`.
+                "base-executable" => {
+                    base_executable = Some(file_system.to_host_path(Path::new(value)))
+                }
+                "executable" if base_executable.is_none() => {
+                    base_executable = Some(file_system.to_host_path(Path::new(value)))
+                }
+                _ => {}
+            }
         }
     }
 
-    match (version, version_major, version_minor) {
-        (Some(ver), Some(major), Some(minor)) => Some(PyVenvCfg::new(
-            Some(ver),
-            Some(major),
-            Some(minor),
-            prompt,
-            file.to_path_buf(),
-        )),
-        // Even without version info, return the struct - presence of pyvenv.cfg
-        // is sufficient to identify this as a venv environment
-        _ => Some(PyVenvCfg::new(None, None, None, prompt, file.to_path_buf())),
+    // Even without version info, return the struct - presence of pyvenv.cfg
+    // is sufficient to identify this as a venv environment
+    let has_version = version.is_some() && version_major.is_some() && version_minor.is_some();
+    Some(PyVenvCfg {
+        version: if has_version { version } else { None },
+        version_major: if has_version { version_major } else { None },
+        version_minor: if has_version { version_minor } else { None },
+        prompt,
+        home,
+        base_executable,
+        file_path: file.to_path_buf(),
+    })
+}
+
+fn parse_key_value(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once('=')?;
+    let value = value.trim();
+    if value.is_empty() {
+        None
+    } else {
+        Some((key.trim(), value))
     }
 }
 
@@ -192,7 +210,8 @@ fn parse_prompt(line: &str) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Write;
+    use pet_fs::file_system::MemoryFileSystem;
+    use std::{fs, io::Write};
     use tempfile::tempdir;
 
     #[test]
@@ -277,7 +296,7 @@ mod tests {
         let result = PyVenvCfg::find(dir.path());
         assert!(result.is_some());
         let cfg = result.unwrap();
-        assert_eq!(cfg.version, Some("3.11.4".to_string()));
+        assert_eq!(cfg.version, Some("3.11.4".parse().unwrap()));
         assert_eq!(cfg.version_major, Some(3));
         assert_eq!(cfg.version_minor, Some(11));
         assert_eq!(cfg.prompt, Some("test-env".to_string()));
@@ -296,7 +315,7 @@ mod tests {
         let result = PyVenvCfg::find(&bin_dir);
         assert!(result.is_some());
         let cfg = result.unwrap();
-        assert_eq!(cfg.version, Some("3.10.0".to_string()));
+        assert_eq!(cfg.version, Some("3.10.0".parse().unwrap()));
         assert_eq!(cfg.version_major, Some(3));
         assert_eq!(cfg.version_minor, Some(10));
     }
@@ -336,8 +355,74 @@ mod tests {
         let result = PyVenvCfg::find(dir.path());
         assert!(result.is_some());
         let cfg = result.unwrap();
-        assert_eq!(cfg.version, Some("3.12.1.final.0".to_string()));
+        assert_eq!(cfg.version, Some("3.12.1.final.0".parse().unwrap()));
+        assert_eq!(cfg.version.unwrap().to_string(), "3.12.1.final.0");
         assert_eq!(cfg.version_major, Some(3));
         assert_eq!(cfg.version_minor, Some(12));
     }
+
+    #[test]
+    fn test_pyvenv_cfg_base_interpreter() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("pyvenv.cfg");
+        let mut file = fs::File::create(&cfg_path).unwrap();
+        writeln!(file, "home = /usr/local/bin").unwrap();
+        writeln!(file, "include-system-site-packages = false").unwrap();
+        writeln!(file, "version = 3.12.4").unwrap();
+        writeln!(file, "executable = /usr/local/bin/python3.12").unwrap();
+        writeln!(file, "command = /usr/local/bin/python3.12 -m venv /tmp/env").unwrap();
+
+        let cfg = PyVenvCfg::find(dir.path()).unwrap();
+        assert_eq!(cfg.home, Some(PathBuf::from("/usr/local/bin")));
+        assert_eq!(
+            cfg.base_executable,
+            Some(PathBuf::from("/usr/local/bin/python3.12"))
+        );
+    }
+
+    #[test]
+    fn test_pyvenv_cfg_uv_base_executable() {
+        let dir = tempdir().unwrap();
+        let cfg_path = dir.path().join("pyvenv.cfg");
+        let mut file = fs::File::create(&cfg_path).unwrap();
+        writeln!(
+            file,
+            "home = /home/user/.local/share/uv/python/cpython-3.12.4/bin"
+        )
+        .unwrap();
+        writeln!(file, "implementation = CPython").unwrap();
+        writeln!(file, "uv = 0.4.0").unwrap();
+        writeln!(file, "version_info = 3.12.4").unwrap();
+        writeln!(
+            file,
+            "base-executable = /home/user/.local/share/uv/python/cpython-3.12.4/bin/python3.12"
+        )
+        .unwrap();
+
+        let cfg = PyVenvCfg::find(dir.path()).unwrap();
+        assert_eq!(
+            cfg.base_executable,
+            Some(PathBuf::from(
+                "/home/user/.local/share/uv/python/cpython-3.12.4/bin/python3.12"
+            ))
+        );
+        assert_eq!(cfg.version, Some("3.12.4".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_pyvenv_cfg_find_in_file_system() {
+        let file_system = MemoryFileSystem::new()
+            .file(
+                "/app/.venv/pyvenv.cfg",
+                "home = /usr/bin\nversion = 3.12.4\nprompt = app\n",
+            )
+            .file("/app/.venv/bin/python", "");
+
+        let cfg = PyVenvCfg::find_in(&file_system, Path::new("/app/.venv/bin")).unwrap();
+        assert_eq!(cfg.file_path, PathBuf::from("/app/.venv/pyvenv.cfg"));
+        assert_eq!(cfg.home, Some(PathBuf::from("/usr/bin")));
+        assert_eq!(cfg.version, Some("3.12.4".parse().unwrap()));
+        assert_eq!(cfg.prompt, Some("app".to_string()));
+        assert!(PyVenvCfg::find_in(&file_system, Path::new("/app")).is_none());
+    }
 }
@@ -9,4 +9,9 @@ pub trait Reporter: Send + Sync {
     fn report_manager(&self, manager: &EnvManager);
     fn report_environment(&self, env: &PythonEnvironment);
     fn report_telemetry(&self, event: &TelemetryEvent);
+    /// Whether the reports are no longer wanted (e.g. the consumer has gone away),
+    /// in which case the search stops as soon as possible.
+    fn is_cancelled(&self) -> bool {
+        false
+    }
 }
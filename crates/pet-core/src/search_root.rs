@@ -0,0 +1,107 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use pet_fs::file_system::FileSystem;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Number of directories looked at in the search roots of a locator (e.g. `~/.pyenv/versions`),
+/// i.e. how those on network (or FUSE) file systems are searched, see `NetworkFilesystemPolicy`.
+pub trait SearchRootBudget: Send + Sync {
+    /// Maximum number of directories looked at in the search root, `0` if it is not searched.
+    fn get(&self, root: &Path) -> usize;
+}
+
+/// Every directory of the search roots is looked at.
+pub struct Unlimited;
+
+impl SearchRootBudget for Unlimited {
+    fn get(&self, _root: &Path) -> usize {
+        usize::MAX
+    }
+}
+
+/// Directories in the search root, at most the budget of the root.
+pub fn read_search_root(
+    file_system: &dyn FileSystem,
+    budget: &dyn SearchRootBudget,
+    root: &Path,
+) -> Vec<PathBuf> {
+    let budget = budget.get(root);
+    if budget == 0 {
+        return vec![];
+    }
+    file_system
+        .read_dir(root)
+        .unwrap_or_default()
+        .into_iter()
+        // `is_dir` follows symlinks, the directories could be symlinks to the environments.
+        .filter(|path| file_system.is_dir(path))
+        .take(budget)
+        .collect()
+}
+
+/// Directories found in search roots (i.e. their parent directories), at most the budget of each root.
+pub fn limit_to_search_root_budget(
+    budget: &dyn SearchRootBudget,
+    directories: Vec<PathBuf>,
+) -> Vec<PathBuf> {
+    let mut remaining: HashMap<PathBuf, usize> = HashMap::new();
+    directories
+        .into_iter()
+        .filter(|directory| {
+            let Some(root) = directory.parent() else {
+                return true;
+            };
+            let remaining = remaining
+                .entry(root.to_path_buf())
+                .or_insert_with(|| budget.get(root));
+            if *remaining == 0 {
+                return false;
+            }
+            *remaining -= 1;
+            true
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Budget(HashMap<PathBuf, usize>);
+
+    impl SearchRootBudget for Budget {
+        fn get(&self, root: &Path) -> usize {
+            self.0.get(root).cloned().unwrap_or(usize::MAX)
+        }
+    }
+
+    #[test]
+    fn directories_are_limited_to_the_budget_of_their_search_root() {
+        let budget = Budget(HashMap::from([
+            (PathBuf::from("/nfs/envs"), 1),
+            (PathBuf::from("/sshfs/envs"), 0),
+        ]));
+
+        let directories = limit_to_search_root_budget(
+            &budget,
+            vec![
+                PathBuf::from("/nfs/envs/one"),
+                PathBuf::from("/nfs/envs/two"),
+                PathBuf::from("/sshfs/envs/three"),
+                PathBuf::from("/local/envs/four"),
+                PathBuf::from("/local/envs/five"),
+            ],
+        );
+
+        assert_eq!(
+            directories,
+            vec![
+                PathBuf::from("/nfs/envs/one"),
+                PathBuf::from("/local/envs/four"),
+                PathBuf::from("/local/envs/five"),
+            ]
+        );
+    }
+}
@@ -17,4 +17,8 @@ pub struct RefreshPerformance {
     pub breakdown: BTreeMap<String, u128>,
     /// Breakdown of each individual locators such as conda, pyenv, etc.
     pub locators: BTreeMap<String, u128>,
+    /// Time each locator waited for a thread before searching, i.e. in addition to the time in `locators`.
+    pub locators_queue_wait: BTreeMap<String, u128>,
+    /// Number of directories visited while searching the workspace folders.
+    pub workspace_directories_visited: usize,
 }
@@ -17,6 +17,10 @@ pub enum RefreshProgressPhase {
 pub enum RefreshProgressStatus {
     Started,
     Completed,
+    /// A search root on a network (or FUSE) file system was not searched, see `file_system_type`.
+    Skipped,
+    /// A search root on a network (or FUSE) file system was searched with a reduced budget.
+    Reduced,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -32,4 +36,7 @@ pub struct RefreshProgress {
     pub locator_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub locator_elapsed_ms: Option<u128>,
+    /// Type of the file system of the search root that was skipped or reduced, e.g. `nfs`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_system_type: Option<String>,
 }
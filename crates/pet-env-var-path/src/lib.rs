@@ -21,6 +21,23 @@ pub fn get_search_paths_from_env_variables(environment: &dyn Environment) -> Vec
         .collect()
 }
 
+/// Returns the directories of the `PATH` variable in the order a shell would search them.
+///
+/// Unlike `get_search_paths_from_env_variables`, this only looks at `PATH`, preserves order
+/// (first occurrence wins when a directory is listed more than once) and does not exclude
+/// the WindowsApps folder, as the aliases in there are what a command would actually run.
+pub fn get_ordered_search_paths_from_path_variable(environment: &dyn Environment) -> Vec<PathBuf> {
+    let path = environment
+        .get_env_var("PATH".to_string())
+        .unwrap_or_default();
+    let mut seen = HashSet::new();
+    std::env::split_paths(&path)
+        .filter(|p| !p.as_os_str().is_empty())
+        .map(normalize_search_path)
+        .filter(|p| seen.insert(p.clone()))
+        .collect()
+}
+
 fn is_windows_apps_path(search_path: &Path, user_home: Option<&PathBuf>) -> bool {
     if let Some(home) = user_home {
         let apps_path = home
@@ -102,6 +119,28 @@ mod tests {
         }
     }
 
+    struct PathEnvironment {
+        path: String,
+    }
+
+    impl Environment for PathEnvironment {
+        fn get_user_home(&self) -> Option<PathBuf> {
+            None
+        }
+
+        fn get_root(&self) -> Option<PathBuf> {
+            None
+        }
+
+        fn get_env_var(&self, key: String) -> Option<String> {
+            (key == "PATH").then(|| self.path.clone())
+        }
+
+        fn get_know_global_search_locations(&self) -> Vec<PathBuf> {
+            vec![]
+        }
+    }
+
     fn create_test_dir(name: &str) -> PathBuf {
         let unique = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -294,4 +333,30 @@ mod tests {
 
         fs::remove_dir_all(temp).unwrap();
     }
+
+    #[test]
+    fn ordered_search_paths_preserve_path_order_and_drop_duplicates() {
+        let root = create_test_dir("ordered");
+        let first = root.join("first");
+        let second = root.join("second");
+        fs::create_dir_all(&first).unwrap();
+        fs::create_dir_all(&second).unwrap();
+
+        let environment = PathEnvironment {
+            path: std::env::join_paths([&second, &first, &second])
+                .unwrap()
+                .into_string()
+                .unwrap(),
+        };
+
+        assert_eq!(
+            get_ordered_search_paths_from_path_variable(&environment),
+            vec![
+                normalize_search_path(second.clone()),
+                normalize_search_path(first.clone())
+            ]
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
 }
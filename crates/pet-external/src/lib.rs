@@ -0,0 +1,480 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Support for environments located by external executables (plugins).
+//!
+//! Plugins are configured using `Configuration::external_locators` and are sent one request
+//! per process, as JSON on stdin, with the response written as JSON to stdout:
+//!
+//! ```text
+//! > {"method": "supportedCategories", "params": null}
+//! < {"result": ["CompanyBundle"]}
+//! > {"method": "tryFrom", "params": {"executable": "/opt/bundle/bin/python", "prefix": null, "version": null, "symlinks": null}}
+//! < {"result": {"kind": "CompanyBundle", "executable": "/opt/bundle/bin/python", "prefix": "/opt/bundle", "version": "3.12.1"}}
+//! > {"method": "find", "params": {"workspaceDirectories": [], "environmentDirectories": []}}
+//! < {"result": [{"executable": "/opt/bundle/bin/python", "prefix": "/opt/bundle"}]}
+//! ```
+//!
+//! `tryFrom` returns `null` for environments the plugin does not recognize.
+//! Errors are reported as `{"error": {"message": "..."}}`.
+//! Environments are reported with the kind `External` and the custom kind in `externalKind`.
+
+use log::{trace, warn};
+use pet_core::{
+    env::PythonEnv,
+    python_environment::{PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind},
+    python_version::PythonVersion,
+    reporter::Reporter,
+    Configuration, ExternalLocatorConfig, Locator, LocatorKind, RefreshStatePersistence,
+    RefreshStateSyncScope,
+};
+use pet_python_utils::arch::get_architecture;
+use plugin::{ExternalEnvironment, FindParams, Plugin};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex, RwLock},
+};
+
+pub mod plugin;
+
+#[derive(Default)]
+struct State {
+    configs: Vec<ExternalLocatorConfig>,
+    plugins: Vec<Arc<Plugin>>,
+    find_params: FindParams,
+}
+
+pub struct ExternalLocator {
+    state: RwLock<State>,
+    /// Results of `find` & `tryFrom` keyed by executable, including executables that every plugin
+    /// said it does not recognize, so that each plugin is asked about an executable at most once
+    /// per refresh.
+    identified: Mutex<HashMap<PathBuf, Option<PythonEnvironment>>>,
+}
+
+impl Default for ExternalLocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExternalLocator {
+    pub fn new() -> ExternalLocator {
+        ExternalLocator {
+            state: RwLock::new(State::default()),
+            identified: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn plugins(&self) -> Vec<Arc<Plugin>> {
+        self.state
+            .read()
+            .expect("external locator state poisoned")
+            .plugins
+            .iter()
+            .filter(|plugin| !plugin.is_backing_off())
+            .cloned()
+            .collect()
+    }
+
+    fn plugin_count(&self) -> usize {
+        self.state
+            .read()
+            .expect("external locator state poisoned")
+            .plugins
+            .len()
+    }
+
+    /// Forgets the executables that no plugin recognized, so that they are asked about again.
+    fn forget_unidentified(&self) {
+        self.identified
+            .lock()
+            .expect("identified mutex poisoned")
+            .retain(|_, env| env.is_some());
+    }
+
+    fn cache(&self, env: &PythonEnvironment) {
+        let mut identified = self.identified.lock().expect("identified mutex poisoned");
+        for exe in env.executable.iter().chain(env.symlinks.iter().flatten()) {
+            identified.insert(exe.clone(), Some(env.clone()));
+        }
+    }
+}
+
+impl Locator for ExternalLocator {
+    fn get_kind(&self) -> LocatorKind {
+        LocatorKind::External
+    }
+
+    fn refresh_state(&self) -> RefreshStatePersistence {
+        RefreshStatePersistence::SelfHydratingCache
+    }
+
+    fn configure(&self, config: &Configuration) {
+        let configs = config.external_locators.clone().unwrap_or_default();
+        let mut state = self.state.write().expect("external locator state poisoned");
+        state.find_params = FindParams {
+            workspace_directories: config.workspace_directories.clone().unwrap_or_default(),
+            environment_directories: config.environment_directories.clone().unwrap_or_default(),
+        };
+        if state.configs == configs {
+            return;
+        }
+        // Re-enable plugins that were backing off, they might have been fixed.
+        state.plugins = configs.iter().map(|c| Arc::new(Plugin::from(c))).collect();
+        state.configs = configs;
+        self.identified
+            .lock()
+            .expect("identified mutex poisoned")
+            .clear();
+    }
+
+    fn supported_categories(&self) -> Vec<PythonEnvironmentKind> {
+        vec![PythonEnvironmentKind::External]
+    }
+
+    fn try_from(&self, env: &PythonEnv) -> Option<PythonEnvironment> {
+        let plugins = self.plugins();
+        if plugins.is_empty() {
+            return None;
+        }
+        if let Some(result) = self
+            .identified
+            .lock()
+            .expect("identified mutex poisoned")
+            .get(&env.executable)
+        {
+            return result.clone();
+        }
+
+        // Only remember that no plugin recognized the executable when every plugin said so,
+        // a plugin that failed or is backing off might recognize it later.
+        let mut conclusive = plugins.len() == self.plugin_count();
+        let result = plugins
+            .iter()
+            .find_map(|plugin| match plugin.try_from(env) {
+                Ok(Some(external)) => {
+                    let env = to_python_environment(plugin, external);
+                    conclusive &= env.is_some();
+                    env
+                }
+                Ok(None) => None,
+                Err(err) => {
+                    warn!(
+                        "External locator {:?} failed to identify {:?}: {}",
+                        plugin.executable, env.executable, err
+                    );
+                    conclusive = false;
+                    None
+                }
+            });
+        match &result {
+            Some(result) => self.cache(result),
+            None if conclusive => {
+                self.identified
+                    .lock()
+                    .expect("identified mutex poisoned")
+                    .insert(env.executable.clone(), None);
+            }
+            None => {}
+        }
+        result
+    }
+
+    fn sync_refresh_state_from(&self, _source: &dyn Locator, _scope: &RefreshStateSyncScope) {
+        // Environments created since the last refresh might be recognized now.
+        self.forget_unidentified();
+    }
+
+    fn find(&self, reporter: &dyn Reporter) {
+        self.forget_unidentified();
+        let plugins = self.plugins();
+        let params = self
+            .state
+            .read()
+            .expect("external locator state poisoned")
+            .find_params
+            .clone();
+        // Plugins are independent of each other, a slow plugin should not delay the others.
+        // Runs on the pool of the caller (i.e. the discovery pool), instead of a thread per plugin.
+        rayon::scope(|s| {
+            for plugin in plugins {
+                let params = &params;
+                s.spawn(move |_| match plugin.find(params) {
+                    Ok(environments) => {
+                        trace!(
+                            "External locator {:?} found {} environments",
+                            plugin.executable,
+                            environments.len()
+                        );
+                        for env in environments
+                            .into_iter()
+                            .filter_map(|env| to_python_environment(&plugin, env))
+                        {
+                            self.cache(&env);
+                            reporter.report_environment(&env);
+                        }
+                    }
+                    Err(err) => warn!(
+                        "External locator {:?} failed to find environments: {}",
+                        plugin.executable, err
+                    ),
+                });
+            }
+        });
+    }
+}
+
+fn to_python_environment(plugin: &Plugin, env: ExternalEnvironment) -> Option<PythonEnvironment> {
+    let categories = plugin.supported_categories().ok()?;
+    let Some(kind) = env.kind.clone().or_else(|| categories.first().cloned()) else {
+        warn!(
+            "External locator {:?} reported an environment without a kind: {:?}",
+            plugin.executable, env
+        );
+        return None;
+    };
+    if !categories.is_empty() && !categories.contains(&kind) {
+        warn!(
+            "External locator {:?} reported an environment of kind {} which is not one of {:?}",
+            plugin.executable, kind, categories
+        );
+        return None;
+    }
+    if env.executable.is_none() && env.prefix.is_none() {
+        warn!(
+            "External locator {:?} reported an environment without an executable or prefix: {:?}",
+            plugin.executable, env
+        );
+        return None;
+    }
+
+    Some(
+        PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::External))
+            .external_kind(Some(kind))
+            .display_name(env.display_name)
+            .name(env.name)
+            .arch(env.executable.as_deref().and_then(get_architecture))
+            .executable(env.executable)
+            .version(
+                env.version
+                    .as_deref()
+                    .and_then(PythonVersion::parse_lossless),
+            )
+            .prefix(env.prefix)
+            .project(env.project)
+            .symlinks(env.symlinks)
+            .error(env.error)
+            .build(),
+    )
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use pet_core::{manager::EnvManager, telemetry::TelemetryEvent};
+    use std::{
+        fs,
+        os::unix::fs::PermissionsExt,
+        path::Path,
+        time::{Duration, Instant},
+    };
+    use tempfile::TempDir;
+
+    #[derive(Default)]
+    struct TestReporter {
+        environments: Mutex<Vec<PythonEnvironment>>,
+    }
+
+    impl Reporter for TestReporter {
+        fn report_manager(&self, _manager: &EnvManager) {}
+        fn report_environment(&self, env: &PythonEnvironment) {
+            self.environments.lock().unwrap().push(env.clone());
+        }
+        fn report_telemetry(&self, _event: &TelemetryEvent) {}
+    }
+
+    /// Creates a plugin that responds to each method with the given shell commands.
+    fn create_plugin(
+        dir: &Path,
+        name: &str,
+        categories: &str,
+        try_from: &str,
+        find: &str,
+    ) -> PathBuf {
+        let file = dir.join(name);
+        fs::write(
+            &file,
+            format!(
+                "#!/bin/sh\nread -r request\ncase \"$request\" in\n  *'\"supportedCategories\"'*) {categories} ;;\n  *'\"tryFrom\"'*) {try_from} ;;\n  *'\"find\"'*) {find} ;;\nesac\n"
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o755)).unwrap();
+        file
+    }
+
+    fn configure(locator: &ExternalLocator, plugins: &[(PathBuf, Option<Duration>)]) {
+        locator.configure(&Configuration {
+            external_locators: Some(
+                plugins
+                    .iter()
+                    .map(|(executable, timeout)| ExternalLocatorConfig {
+                        executable: executable.clone(),
+                        timeout: *timeout,
+                    })
+                    .collect(),
+            ),
+            ..Default::default()
+        });
+    }
+
+    const CATEGORIES: &str = r#"echo '{"result": ["CompanyBundle"]}'"#;
+    const FIND: &str = r#"echo '{"result": [{"executable": "/opt/bundle/bin/python", "prefix": "/opt/bundle", "version": "3.12.1"}]}'"#;
+    const NOT_FOUND: &str = r#"echo '{"result": null}'"#;
+
+    #[test]
+    fn find_reports_environments_under_the_custom_kind() {
+        let dir = TempDir::new().unwrap();
+        let plugin = create_plugin(dir.path(), "bundles", CATEGORIES, NOT_FOUND, FIND);
+        let locator = ExternalLocator::new();
+        configure(&locator, &[(plugin, None)]);
+        let reporter = TestReporter::default();
+
+        locator.find(&reporter);
+
+        let environments = reporter.environments.lock().unwrap().clone();
+        assert_eq!(environments.len(), 1);
+        assert_eq!(environments[0].kind, Some(PythonEnvironmentKind::External));
+        assert_eq!(
+            environments[0].external_kind,
+            Some("CompanyBundle".to_string())
+        );
+        assert_eq!(
+            environments[0].executable,
+            Some(PathBuf::from("/opt/bundle/bin/python"))
+        );
+        assert_eq!(environments[0].version, Some("3.12.1".parse().unwrap()));
+
+        // Environments found are identified without asking the plugin again.
+        fs::remove_file(dir.path().join("bundles")).unwrap();
+        let env = PythonEnv::new(PathBuf::from("/opt/bundle/bin/python"), None, None);
+        assert_eq!(locator.try_from(&env), Some(environments[0].clone()));
+    }
+
+    #[test]
+    fn try_from_uses_the_plugin_response() {
+        let dir = TempDir::new().unwrap();
+        let plugin = create_plugin(
+            dir.path(),
+            "bundles",
+            r#"echo '{"result": ["CompanyBundle", "LegacyBundle"]}'"#,
+            r#"echo '{"result": {"kind": "LegacyBundle", "executable": "/opt/legacy/python"}}'"#,
+            NOT_FOUND,
+        );
+        let locator = ExternalLocator::new();
+        configure(&locator, &[(plugin, None)]);
+
+        let env = locator
+            .try_from(&PythonEnv::new(
+                PathBuf::from("/opt/legacy/python"),
+                None,
+                None,
+            ))
+            .unwrap();
+
+        assert_eq!(env.kind, Some(PythonEnvironmentKind::External));
+        assert_eq!(env.external_kind, Some("LegacyBundle".to_string()));
+    }
+
+    #[test]
+    fn environments_of_undeclared_kinds_are_ignored() {
+        let dir = TempDir::new().unwrap();
+        let plugin = create_plugin(
+            dir.path(),
+            "bundles",
+            CATEGORIES,
+            r#"echo '{"result": {"kind": "Other", "executable": "/opt/other/python"}}'"#,
+            NOT_FOUND,
+        );
+        let locator = ExternalLocator::new();
+        configure(&locator, &[(plugin, None)]);
+
+        assert_eq!(
+            locator.try_from(&PythonEnv::new(
+                PathBuf::from("/opt/other/python"),
+                None,
+                None
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn failing_plugins_do_not_affect_other_plugins() {
+        let dir = TempDir::new().unwrap();
+        let crashing = create_plugin(dir.path(), "crashing", CATEGORIES, "exit 1", "exit 1");
+        let invalid = create_plugin(dir.path(), "invalid", CATEGORIES, "echo '{'", "echo '{'");
+        let hanging = create_plugin(dir.path(), "hanging", "sleep 5", "sleep 5", "sleep 5");
+        let working = create_plugin(dir.path(), "working", CATEGORIES, NOT_FOUND, FIND);
+        let locator = ExternalLocator::new();
+        configure(
+            &locator,
+            &[
+                (crashing, None),
+                (invalid, None),
+                (hanging, Some(Duration::from_millis(200))),
+                (working, None),
+                (dir.path().join("missing"), None),
+            ],
+        );
+        let reporter = TestReporter::default();
+
+        let start = Instant::now();
+        locator.find(&reporter);
+
+        assert!(start.elapsed() < Duration::from_secs(4));
+        assert_eq!(reporter.environments.lock().unwrap().len(), 1);
+        // Plugins that hang or cannot be spawned are not used for a while.
+        assert_eq!(locator.plugins().len(), 3);
+    }
+
+    #[test]
+    fn only_executables_every_plugin_did_not_recognize_are_remembered() {
+        let dir = TempDir::new().unwrap();
+        let crashing = create_plugin(dir.path(), "crashing", CATEGORIES, "exit 1", "exit 1");
+        let working = create_plugin(dir.path(), "working", CATEGORIES, NOT_FOUND, NOT_FOUND);
+        let unknown = PythonEnv::new(PathBuf::from("/usr/bin/python3"), None, None);
+        let locator = ExternalLocator::new();
+
+        configure(&locator, &[(crashing, None), (working.clone(), None)]);
+        assert_eq!(locator.try_from(&unknown), None);
+        assert!(locator.identified.lock().unwrap().is_empty());
+
+        configure(&locator, &[(working, None)]);
+        assert_eq!(locator.try_from(&unknown), None);
+        assert_eq!(
+            locator.identified.lock().unwrap().get(&unknown.executable),
+            Some(&None)
+        );
+
+        // Each refresh asks the plugins again.
+        locator.find(&TestReporter::default());
+        assert!(locator.identified.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn no_plugins_means_nothing_is_identified() {
+        let locator = ExternalLocator::new();
+
+        assert_eq!(
+            locator.try_from(&PythonEnv::new(
+                PathBuf::from("/usr/bin/python3"),
+                None,
+                None
+            )),
+            None
+        );
+    }
+}
@@ -0,0 +1,391 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use log::{trace, warn};
+use pet_core::{env::PythonEnv, python_version::PythonVersion, ExternalLocatorConfig};
+use pet_python_utils::executable::new_silent_command;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fmt,
+    io::{Read, Write},
+    path::PathBuf,
+    process::{ExitStatus, Stdio},
+    sync::{
+        mpsc::{self, Receiver},
+        Mutex, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Time allowed for each request, unless configured otherwise.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time a plugin is not used for after a failure, doubled for each consecutive failure.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(30);
+const MAX_BACKOFF: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Serialize)]
+struct Request<'a, T> {
+    method: &'a str,
+    params: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    #[serde(default)]
+    result: Value,
+    error: Option<ResponseError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseError {
+    message: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TryFromParams {
+    pub executable: PathBuf,
+    pub prefix: Option<PathBuf>,
+    pub version: Option<PythonVersion>,
+    pub symlinks: Option<Vec<PathBuf>>,
+}
+
+impl From<&PythonEnv> for TryFromParams {
+    fn from(env: &PythonEnv) -> Self {
+        TryFromParams {
+            executable: env.executable.clone(),
+            prefix: env.prefix.clone(),
+            version: env.version.clone(),
+            symlinks: env.symlinks.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindParams {
+    pub workspace_directories: Vec<PathBuf>,
+    pub environment_directories: Vec<PathBuf>,
+}
+
+/// Environment as reported by a plugin.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalEnvironment {
+    /// Custom kind, e.g. `CompanyBundle`.
+    /// Defaults to the first of the categories the plugin supports.
+    pub kind: Option<String>,
+    pub display_name: Option<String>,
+    pub name: Option<String>,
+    pub executable: Option<PathBuf>,
+    pub version: Option<String>,
+    pub prefix: Option<PathBuf>,
+    pub project: Option<PathBuf>,
+    pub symlinks: Option<Vec<PathBuf>>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum PluginError {
+    /// The plugin is not used for a while after an earlier failure.
+    BackingOff,
+    Spawn(std::io::Error),
+    Timeout(Duration),
+    Exit(ExitStatus, String),
+    InvalidResponse(String),
+    /// The plugin reported an error.
+    Plugin(String),
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PluginError::BackingOff => write!(f, "plugin is not used after an earlier failure"),
+            PluginError::Spawn(err) => write!(f, "failed to spawn: {err}"),
+            PluginError::Timeout(timeout) => write!(f, "timed out after {timeout:?}"),
+            PluginError::Exit(status, stderr) => write!(f, "exited with {status}: {stderr}"),
+            PluginError::InvalidResponse(err) => write!(f, "invalid response: {err}"),
+            PluginError::Plugin(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+/// An executable that speaks the external locator protocol.
+///
+/// Each request is sent in a new process, as a single JSON object on stdin
+/// (`{"method": "find", "params": {..}}`), the response is a JSON object on stdout
+/// (`{"result": ..}` or `{"error": {"message": ".."}}`).
+///
+/// Plugins that cannot be spawned, time out or fail the `supportedCategories` request are
+/// not used for a while (longer after each consecutive failure), so that a broken plugin
+/// cannot slow down discovery.
+pub struct Plugin {
+    pub executable: PathBuf,
+    timeout: Duration,
+    categories: OnceLock<Vec<String>>,
+    backoff: Mutex<Backoff>,
+}
+
+#[derive(Debug, Default)]
+struct Backoff {
+    /// Number of consecutive failures.
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+impl Backoff {
+    fn is_waiting(&self, now: Instant) -> bool {
+        self.retry_at.is_some_and(|retry_at| now < retry_at)
+    }
+
+    /// Records a failure, returns the time until the plugin is used again.
+    fn failed(&mut self, now: Instant) -> Duration {
+        let delay = INITIAL_BACKOFF
+            .saturating_mul(1 << self.failures.min(16))
+            .min(MAX_BACKOFF);
+        self.failures += 1;
+        self.retry_at = Some(now + delay);
+        delay
+    }
+
+    fn succeeded(&mut self) {
+        self.failures = 0;
+        self.retry_at = None;
+    }
+}
+
+impl Plugin {
+    pub fn from(config: &ExternalLocatorConfig) -> Plugin {
+        Plugin {
+            executable: config.executable.clone(),
+            timeout: config.timeout.unwrap_or(DEFAULT_TIMEOUT),
+            categories: OnceLock::new(),
+            backoff: Mutex::new(Backoff::default()),
+        }
+    }
+
+    /// Whether the plugin is not being used after an earlier failure.
+    pub fn is_backing_off(&self) -> bool {
+        self.backoff
+            .lock()
+            .expect("backoff mutex poisoned")
+            .is_waiting(Instant::now())
+    }
+
+    /// Custom kinds of environments reported by this plugin.
+    /// Requested once, the first time it is needed.
+    pub fn supported_categories(&self) -> Result<&[String], PluginError> {
+        if let Some(categories) = self.categories.get() {
+            return Ok(categories);
+        }
+        match self.request::<_, Vec<String>>("supportedCategories", Value::Null) {
+            Ok(categories) => Ok(self.categories.get_or_init(|| categories)),
+            Err(err) => {
+                self.back_off(&err);
+                Err(err)
+            }
+        }
+    }
+
+    pub fn try_from(&self, env: &PythonEnv) -> Result<Option<ExternalEnvironment>, PluginError> {
+        self.supported_categories()?;
+        self.request("tryFrom", TryFromParams::from(env))
+    }
+
+    pub fn find(&self, params: &FindParams) -> Result<Vec<ExternalEnvironment>, PluginError> {
+        self.supported_categories()?;
+        self.request("find", params)
+    }
+
+    fn back_off(&self, err: &PluginError) {
+        let now = Instant::now();
+        let mut backoff = self.backoff.lock().expect("backoff mutex poisoned");
+        // Concurrent requests failing for the same reason count as one failure.
+        if backoff.is_waiting(now) {
+            return;
+        }
+        let delay = backoff.failed(now);
+        warn!(
+            "Not using external locator {:?} for {:?}: {}",
+            self.executable, delay, err
+        );
+    }
+
+    fn request<P: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> Result<R, PluginError> {
+        if self.is_backing_off() {
+            return Err(PluginError::BackingOff);
+        }
+        let start = Instant::now();
+        let request = serde_json::to_vec(&Request { method, params })
+            .map_err(|err| PluginError::InvalidResponse(err.to_string()))?;
+        let output = match self.spawn(&request) {
+            Ok(output) => {
+                self.backoff
+                    .lock()
+                    .expect("backoff mutex poisoned")
+                    .succeeded();
+                output
+            }
+            Err(err) => {
+                // The plugin is not usable (missing, hung, etc), do not try it again for a while.
+                if matches!(err, PluginError::Spawn(_) | PluginError::Timeout(_)) {
+                    self.back_off(&err);
+                }
+                return Err(err);
+            }
+        };
+        trace!(
+            "External locator {:?} responded to {} in {:?}",
+            self.executable,
+            method,
+            start.elapsed()
+        );
+        let response = serde_json::from_slice::<Response>(&output)
+            .map_err(|err| PluginError::InvalidResponse(err.to_string()))?;
+        if let Some(error) = response.error {
+            return Err(PluginError::Plugin(error.message));
+        }
+        serde_json::from_value(response.result)
+            .map_err(|err| PluginError::InvalidResponse(err.to_string()))
+    }
+
+    fn spawn(&self, request: &[u8]) -> Result<Vec<u8>, PluginError> {
+        let mut child = new_silent_command(&self.executable)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(PluginError::Spawn)?;
+
+        let deadline = Instant::now() + self.timeout;
+        // Write & read in the background, else a plugin that does not read the request
+        // or writes more than the pipe buffer would block until it exits.
+        write_in_background(child.stdin.take(), request.to_vec());
+        let stdout = read_in_background(child.stdout.take());
+        let stderr = read_in_background(child.stderr.take());
+
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(PluginError::Timeout(self.timeout));
+                }
+                Ok(None) => thread::sleep(Duration::from_millis(10)),
+                Err(err) => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(PluginError::Spawn(err));
+                }
+            }
+        };
+
+        // The output stays open while processes started by the plugin are running.
+        let remaining = || deadline.saturating_duration_since(Instant::now());
+        let stdout = stdout
+            .recv_timeout(remaining())
+            .map_err(|_| PluginError::Timeout(self.timeout))?;
+        let stderr = stderr.recv_timeout(remaining()).unwrap_or_default();
+        if !status.success() {
+            return Err(PluginError::Exit(
+                status,
+                String::from_utf8_lossy(&stderr).trim().to_string(),
+            ));
+        }
+        Ok(stdout)
+    }
+}
+
+fn write_in_background<W: Write + Send + 'static>(writer: Option<W>, buffer: Vec<u8>) {
+    thread::spawn(move || {
+        if let Some(mut writer) = writer {
+            // A plugin that exits without reading the request is not an error in itself.
+            let _ = writer.write_all(&buffer);
+        }
+    });
+}
+
+fn read_in_background<R: Read + Send + 'static>(reader: Option<R>) -> Receiver<Vec<u8>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buffer = vec![];
+        if let Some(mut reader) = reader {
+            let _ = reader.read_to_end(&mut buffer);
+        }
+        let _ = sender.send(buffer);
+    });
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_after_each_failure_and_resets_on_success() {
+        let now = Instant::now();
+        let mut backoff = Backoff::default();
+        assert!(!backoff.is_waiting(now));
+
+        assert_eq!(backoff.failed(now), INITIAL_BACKOFF);
+        assert!(backoff.is_waiting(now));
+        assert!(!backoff.is_waiting(now + INITIAL_BACKOFF));
+        assert_eq!(backoff.failed(now), INITIAL_BACKOFF * 2);
+        for _ in 0..40 {
+            backoff.failed(now);
+        }
+        assert_eq!(backoff.failed(now), MAX_BACKOFF);
+
+        backoff.succeeded();
+        assert!(!backoff.is_waiting(now));
+        assert_eq!(backoff.failed(now), INITIAL_BACKOFF);
+    }
+
+    #[cfg(unix)]
+    fn create_plugin(dir: &std::path::Path, script: &str) -> Plugin {
+        use std::os::unix::fs::PermissionsExt;
+
+        let executable = dir.join("plugin");
+        std::fs::write(&executable, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&executable, std::fs::Permissions::from_mode(0o755)).unwrap();
+        Plugin::from(&ExternalLocatorConfig {
+            executable,
+            timeout: Some(Duration::from_millis(200)),
+        })
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn plugin_not_reading_the_request_times_out() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let plugin = create_plugin(dir.path(), "sleep 5");
+        // Larger than the pipe buffer, so writing the request blocks.
+        let request = vec![b' '; 1024 * 1024];
+
+        let start = Instant::now();
+        let result = plugin.spawn(&request);
+
+        assert!(matches!(result, Err(PluginError::Timeout(_))), "{result:?}");
+        assert!(start.elapsed() < Duration::from_secs(4));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn plugin_leaving_processes_holding_the_output_times_out() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let plugin = create_plugin(dir.path(), "sleep 5 &\necho '{\"result\": []}'");
+
+        let start = Instant::now();
+        let result = plugin.spawn(b"{}");
+
+        assert!(matches!(result, Err(PluginError::Timeout(_))), "{result:?}");
+        assert!(start.elapsed() < Duration::from_secs(4));
+    }
+}
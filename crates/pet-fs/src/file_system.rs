@@ -0,0 +1,708 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! File system used by the locators, so that discovery can run against something other than the disk.
+//!
+//! Anything that runs a program (e.g. spawning Python, conda or poetry) still needs the real files.
+//!
+//! - `OsFileSystem` is the real file system.
+//! - `RootedFileSystem` is the real file system, with symlinks followed within an alternate root directory (see `root`).
+//! - `MemoryFileSystem` is a file system built in memory, for fast & deterministic tests.
+//! - `CachedFileSystem` memoizes another file system for the duration of a refresh,
+//!   as the same files are looked at by a number of locators.
+//! - `ConfiguredFileSystem` is another file system, with its own settings for spawning Python.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs, io,
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    mount::{get_file_system_type, FileSystemType},
+    root,
+};
+
+/// Maximum number of symlinks followed when resolving a path, same as `MAXSYMLINKS` on Linux.
+const MAX_SYMLINK_HOPS: usize = 40;
+
+/// Metadata of a file or directory, symlinks having been followed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileMetadata {
+    pub is_file: bool,
+    pub is_dir: bool,
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+    /// Identifies the file system (mount) the file is on, `None` if unknown.
+    pub device: Option<u64>,
+}
+
+impl From<fs::Metadata> for FileMetadata {
+    fn from(metadata: fs::Metadata) -> Self {
+        FileMetadata {
+            is_file: metadata.is_file(),
+            is_dir: metadata.is_dir(),
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            #[cfg(unix)]
+            device: Some(std::os::unix::fs::MetadataExt::dev(&metadata)),
+            #[cfg(not(unix))]
+            device: None,
+        }
+    }
+}
+
+pub trait FileSystem: fmt::Debug + Send + Sync {
+    /// Paths of the entries of the directory (i.e. `path` joined with the name of each entry).
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+    /// Same as `fs::metadata`, symlinks are followed.
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata>;
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+    /// Target of the symlink, as is (i.e. can be relative to the directory of the symlink).
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf>;
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok_and(|metadata| metadata.is_file)
+    }
+    fn is_dir(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok_and(|metadata| metadata.is_dir)
+    }
+    fn exists(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok()
+    }
+    fn is_symlink(&self, path: &Path) -> bool {
+        self.read_link(path).is_ok()
+    }
+    /// Type of the file system the path is on, `None` if unknown (e.g. not supported on this platform).
+    fn file_system_type(&self, _path: &Path) -> Option<FileSystemType> {
+        None
+    }
+    /// Alternate root directory searched instead of `/`, `None` when searching the machine itself.
+    fn root_directory(&self) -> Option<&Path> {
+        None
+    }
+    /// Directory the details of the Python executables spawned are cached in,
+    /// `None` for that of the process (see `pet_python_utils::cache`).
+    fn cache_directory(&self) -> Option<&Path> {
+        None
+    }
+    /// Maximum time allowed for a spawned Python to report its details, `None` for that of the process.
+    fn spawn_timeout(&self) -> Option<Duration> {
+        None
+    }
+    /// Maps a path of the image (e.g. read from `pyvenv.cfg`) to the host, see `root::to_host_path`.
+    fn to_host_path(&self, path: &Path) -> PathBuf {
+        root::to_host_path(self.root_directory(), path)
+    }
+    /// Maps a path of the host back to the image, see `root::to_image_path`.
+    fn to_image_path(&self, path: &Path) -> PathBuf {
+        root::to_image_path(self.root_directory(), path)
+    }
+}
+
+/// The real file system, shared by everyone that has not been given another file system.
+pub fn os_file_system() -> Arc<dyn FileSystem> {
+    static OS_FILE_SYSTEM: OnceLock<Arc<dyn FileSystem>> = OnceLock::new();
+    OS_FILE_SYSTEM
+        .get_or_init(|| Arc::new(OsFileSystem))
+        .clone()
+}
+
+/// The real file system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsFileSystem;
+
+impl FileSystem for OsFileSystem {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        read_os_dir(path)
+    }
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        fs::metadata(path).map(FileMetadata::from)
+    }
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::read_link(path)
+    }
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::canonicalize(path)
+    }
+    fn file_system_type(&self, path: &Path) -> Option<FileSystemType> {
+        get_file_system_type(&fs::canonicalize(path).ok()?)
+    }
+}
+
+/// The real file system, searched from an alternate root directory (e.g. an unpacked container image).
+/// Symlinks under the root are followed within the root (see `root`).
+#[derive(Debug, Clone)]
+pub struct RootedFileSystem {
+    root: PathBuf,
+}
+
+impl RootedFileSystem {
+    pub fn new(root: &Path) -> RootedFileSystem {
+        RootedFileSystem {
+            root: root::canonicalize_root(root),
+        }
+    }
+}
+
+impl FileSystem for RootedFileSystem {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        read_os_dir(path)
+    }
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        root::metadata(&self.root, path).map(FileMetadata::from)
+    }
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        root::read_to_string(&self.root, path)
+    }
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::read_link(path)
+    }
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        root::canonicalize(&self.root, path)
+    }
+    fn file_system_type(&self, path: &Path) -> Option<FileSystemType> {
+        get_file_system_type(&root::canonicalize(&self.root, path).ok()?)
+    }
+    fn root_directory(&self) -> Option<&Path> {
+        Some(&self.root)
+    }
+}
+
+fn read_os_dir(path: &Path) -> io::Result<Vec<PathBuf>> {
+    Ok(fs::read_dir(path)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .collect())
+}
+
+#[derive(Debug, Clone)]
+enum Entry {
+    File(String),
+    Dir,
+    Symlink(PathBuf),
+}
+
+/// File system built in memory, for tests.
+///
+/// ```
+/// use pet_fs::file_system::{FileSystem, MemoryFileSystem};
+/// use std::path::Path;
+///
+/// let file_system = MemoryFileSystem::new()
+///     .file("/usr/bin/python3.12", "")
+///     .symlink("/usr/bin/python3", "python3.12");
+/// assert!(file_system.is_file(Path::new("/usr/bin/python3")));
+/// ```
+#[derive(Default, Clone)]
+pub struct MemoryFileSystem {
+    entries: BTreeMap<PathBuf, Entry>,
+    /// Directories other file systems are mounted on, everything else is on a local file system.
+    mounts: Vec<(PathBuf, FileSystemType)>,
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> MemoryFileSystem {
+        MemoryFileSystem::default()
+    }
+
+    /// Adds a file, along with its parent directories.
+    pub fn file(
+        mut self,
+        path: impl Into<PathBuf>,
+        contents: impl Into<String>,
+    ) -> MemoryFileSystem {
+        self.add(path.into(), Entry::File(contents.into()));
+        self
+    }
+
+    /// Adds a directory, along with its parent directories.
+    pub fn dir(mut self, path: impl Into<PathBuf>) -> MemoryFileSystem {
+        self.add(path.into(), Entry::Dir);
+        self
+    }
+
+    /// Adds a symlink, along with its parent directories.
+    /// Like a real symlink, a relative `target` is relative to the directory of the symlink.
+    pub fn symlink(
+        mut self,
+        path: impl Into<PathBuf>,
+        target: impl Into<PathBuf>,
+    ) -> MemoryFileSystem {
+        self.add(path.into(), Entry::Symlink(target.into()));
+        self
+    }
+
+    /// Mounts a file system of this type on the directory (added along with its parent directories).
+    pub fn mount_point(
+        mut self,
+        path: impl Into<PathBuf>,
+        file_system_type: FileSystemType,
+    ) -> MemoryFileSystem {
+        let path = path.into();
+        self.add(path.clone(), Entry::Dir);
+        self.mounts.push((path, file_system_type));
+        self
+    }
+
+    fn add(&mut self, path: PathBuf, entry: Entry) {
+        for ancestor in path.ancestors().skip(1) {
+            if ancestor.as_os_str().is_empty() {
+                break;
+            }
+            self.entries
+                .entry(ancestor.to_path_buf())
+                .or_insert(Entry::Dir);
+        }
+        self.entries.insert(path, entry);
+    }
+
+    /// Resolves the symlinks in the path, the path must exist.
+    fn resolve(&self, path: &Path) -> io::Result<PathBuf> {
+        // Components still to be resolved, last one first.
+        let mut pending: Vec<PathBuf> = components(path);
+        let mut resolved = PathBuf::new();
+        let mut hops = 0;
+        while let Some(component) = pending.pop() {
+            match component.components().next() {
+                Some(Component::Normal(_)) => {}
+                Some(Component::ParentDir) => {
+                    resolved.pop();
+                    continue;
+                }
+                Some(Component::CurDir) | None => continue,
+                // The root (or prefix) replaces what has been resolved so far.
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {
+                    resolved.push(&component);
+                    continue;
+                }
+            }
+            let candidate = resolved.join(&component);
+            match self.entries.get(&candidate) {
+                None => return Err(not_found(path)),
+                Some(Entry::Symlink(target)) => {
+                    hops += 1;
+                    if hops > MAX_SYMLINK_HOPS {
+                        return Err(io::Error::other(format!(
+                            "Too many levels of symbolic links in {path:?}"
+                        )));
+                    }
+                    pending.extend(components(target));
+                }
+                Some(_) => resolved = candidate,
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Device & type of the innermost file system the (resolved) path is on,
+    /// devices are numbered in the order the file systems were mounted (0 being `/`).
+    fn get_mount(&self, path: &Path) -> (u64, FileSystemType) {
+        self.mounts
+            .iter()
+            .enumerate()
+            .filter(|(_, (mount, _))| path.starts_with(mount))
+            .max_by_key(|(_, (mount, _))| mount.components().count())
+            .map_or(
+                (0, FileSystemType::Local),
+                |(index, (_, file_system_type))| (index as u64 + 1, *file_system_type),
+            )
+    }
+
+    fn get(&self, path: &Path) -> io::Result<&Entry> {
+        let resolved = self.resolve(path)?;
+        self.entries.get(&resolved).ok_or_else(|| not_found(path))
+    }
+}
+
+impl fmt::Debug for MemoryFileSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryFileSystem")
+            .field("entries", &self.entries.len())
+            .finish()
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let dir = self.resolve(path)?;
+        if !matches!(self.entries.get(&dir), Some(Entry::Dir)) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotADirectory,
+                format!("{path:?} is not a directory"),
+            ));
+        }
+        Ok(self
+            .entries
+            .range(dir.clone()..)
+            .map(|(entry, _)| entry)
+            .take_while(|entry| entry.starts_with(&dir))
+            .filter(|entry| entry.parent() == Some(dir.as_path()))
+            .filter_map(|entry| Some(path.join(entry.file_name()?)))
+            .collect())
+    }
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        let resolved = self.resolve(path)?;
+        let device = Some(self.get_mount(&resolved).0);
+        Ok(
+            match self.entries.get(&resolved).ok_or_else(|| not_found(path))? {
+                Entry::File(contents) => FileMetadata {
+                    is_file: true,
+                    is_dir: false,
+                    len: contents.len() as u64,
+                    modified: None,
+                    device,
+                },
+                _ => FileMetadata {
+                    is_file: false,
+                    is_dir: true,
+                    len: 0,
+                    modified: None,
+                    device,
+                },
+            },
+        )
+    }
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        match self.get(path)? {
+            Entry::File(contents) => Ok(contents.clone()),
+            _ => Err(io::Error::new(
+                io::ErrorKind::IsADirectory,
+                format!("{path:?} is a directory"),
+            )),
+        }
+    }
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        // Only the parent directories are resolved, the symlink itself is not followed.
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => self.resolve(parent)?,
+            _ => PathBuf::new(),
+        };
+        let name = path.file_name().ok_or_else(|| not_found(path))?;
+        match self.entries.get(&parent.join(name)) {
+            Some(Entry::Symlink(target)) => Ok(target.clone()),
+            Some(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{path:?} is not a symlink"),
+            )),
+            None => Err(not_found(path)),
+        }
+    }
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.resolve(path)
+    }
+    fn file_system_type(&self, path: &Path) -> Option<FileSystemType> {
+        Some(self.get_mount(&self.resolve(path).ok()?).1)
+    }
+}
+
+fn components(path: &Path) -> Vec<PathBuf> {
+    path.components()
+        .rev()
+        .map(|component| PathBuf::from(component.as_os_str()))
+        .collect()
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{path:?} not found"))
+}
+
+/// Errors are not `Clone`, hence only their kind & message are memoized.
+type Memoized<T> = Result<T, (io::ErrorKind, String)>;
+
+/// Memoizes another file system, e.g. for the duration of a refresh.
+/// Changes made to the files after they were first looked at are not seen, hence this must be short lived.
+pub struct CachedFileSystem {
+    file_system: Arc<dyn FileSystem>,
+    read_dir: Mutex<HashMap<PathBuf, Memoized<Vec<PathBuf>>>>,
+    metadata: Mutex<HashMap<PathBuf, Memoized<FileMetadata>>>,
+    read_to_string: Mutex<HashMap<PathBuf, Memoized<String>>>,
+    read_link: Mutex<HashMap<PathBuf, Memoized<PathBuf>>>,
+    canonicalize: Mutex<HashMap<PathBuf, Memoized<PathBuf>>>,
+    file_system_type: Mutex<HashMap<PathBuf, Memoized<Option<FileSystemType>>>>,
+}
+
+impl CachedFileSystem {
+    pub fn new(file_system: Arc<dyn FileSystem>) -> CachedFileSystem {
+        CachedFileSystem {
+            file_system,
+            read_dir: Mutex::new(HashMap::new()),
+            metadata: Mutex::new(HashMap::new()),
+            read_to_string: Mutex::new(HashMap::new()),
+            read_link: Mutex::new(HashMap::new()),
+            canonicalize: Mutex::new(HashMap::new()),
+            file_system_type: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl fmt::Debug for CachedFileSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachedFileSystem")
+            .field("file_system", &self.file_system)
+            .finish_non_exhaustive()
+    }
+}
+
+fn memoize<T: Clone>(
+    cache: &Mutex<HashMap<PathBuf, Memoized<T>>>,
+    path: &Path,
+    get: impl FnOnce() -> io::Result<T>,
+) -> io::Result<T> {
+    if let Some(value) = cache.lock().expect("file system cache poisoned").get(path) {
+        return value
+            .clone()
+            .map_err(|(kind, message)| io::Error::new(kind, message));
+    }
+    // The lock is not held while reading, two threads could read the same file, that's fine.
+    let value = get().map_err(|err| (err.kind(), err.to_string()));
+    cache
+        .lock()
+        .expect("file system cache poisoned")
+        .insert(path.to_path_buf(), value.clone());
+    value.map_err(|(kind, message)| io::Error::new(kind, message))
+}
+
+impl FileSystem for CachedFileSystem {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        memoize(&self.read_dir, path, || self.file_system.read_dir(path))
+    }
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        memoize(&self.metadata, path, || self.file_system.metadata(path))
+    }
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        memoize(&self.read_to_string, path, || {
+            self.file_system.read_to_string(path)
+        })
+    }
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        memoize(&self.read_link, path, || self.file_system.read_link(path))
+    }
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        memoize(&self.canonicalize, path, || {
+            self.file_system.canonicalize(path)
+        })
+    }
+    fn file_system_type(&self, path: &Path) -> Option<FileSystemType> {
+        memoize(&self.file_system_type, path, || {
+            Ok(self.file_system.file_system_type(path))
+        })
+        .ok()
+        .flatten()
+    }
+    fn root_directory(&self) -> Option<&Path> {
+        self.file_system.root_directory()
+    }
+    fn cache_directory(&self) -> Option<&Path> {
+        self.file_system.cache_directory()
+    }
+    fn spawn_timeout(&self) -> Option<Duration> {
+        self.file_system.spawn_timeout()
+    }
+}
+
+/// Another file system, with the Python executables spawned using settings of their own
+/// (e.g. those of a `Finder`) instead of those of the process.
+#[derive(Debug)]
+pub struct ConfiguredFileSystem {
+    file_system: Arc<dyn FileSystem>,
+    cache_directory: Option<PathBuf>,
+    spawn_timeout: Option<Duration>,
+}
+
+impl ConfiguredFileSystem {
+    pub fn new(
+        file_system: Arc<dyn FileSystem>,
+        cache_directory: Option<PathBuf>,
+        spawn_timeout: Option<Duration>,
+    ) -> ConfiguredFileSystem {
+        ConfiguredFileSystem {
+            file_system,
+            cache_directory,
+            spawn_timeout,
+        }
+    }
+}
+
+impl FileSystem for ConfiguredFileSystem {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        self.file_system.read_dir(path)
+    }
+    fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        self.file_system.metadata(path)
+    }
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        self.file_system.read_to_string(path)
+    }
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        self.file_system.read_link(path)
+    }
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.file_system.canonicalize(path)
+    }
+    fn file_system_type(&self, path: &Path) -> Option<FileSystemType> {
+        self.file_system.file_system_type(path)
+    }
+    fn root_directory(&self) -> Option<&Path> {
+        self.file_system.root_directory()
+    }
+    fn cache_directory(&self) -> Option<&Path> {
+        self.cache_directory
+            .as_deref()
+            .or_else(|| self.file_system.cache_directory())
+    }
+    fn spawn_timeout(&self) -> Option<Duration> {
+        self.spawn_timeout
+            .or_else(|| self.file_system.spawn_timeout())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn image() -> MemoryFileSystem {
+        MemoryFileSystem::new()
+            .file("/usr/bin/python3.12", "")
+            .symlink("/usr/bin/python3", "python3.12")
+            .symlink("/bin", "/usr/bin")
+            .file("/app/.venv/pyvenv.cfg", "home = /usr/bin\n")
+            .symlink("/app/.venv/bin/python", "/usr/bin/python3")
+            .dir("/app/src")
+    }
+
+    #[test]
+    fn memory_file_system_follows_symlinks() {
+        let file_system = image();
+
+        assert!(file_system.is_file(Path::new("/bin/python3")));
+        assert!(file_system.is_dir(Path::new("/app/.venv/bin")));
+        assert!(!file_system.exists(Path::new("/usr/bin/python2")));
+        assert!(file_system.is_symlink(Path::new("/app/.venv/bin/python")));
+        assert!(!file_system.is_symlink(Path::new("/usr/bin/python3.12")));
+        assert_eq!(
+            file_system
+                .canonicalize(Path::new("/app/.venv/bin/python"))
+                .unwrap(),
+            PathBuf::from("/usr/bin/python3.12")
+        );
+        assert_eq!(
+            file_system.read_link(Path::new("/bin/python3")).unwrap(),
+            PathBuf::from("python3.12")
+        );
+        assert_eq!(
+            file_system
+                .read_to_string(Path::new("/app/.venv/pyvenv.cfg"))
+                .unwrap(),
+            "home = /usr/bin\n"
+        );
+        assert_eq!(
+            file_system.read_dir(Path::new("/bin")).unwrap(),
+            vec![
+                PathBuf::from("/bin/python3"),
+                PathBuf::from("/bin/python3.12")
+            ]
+        );
+        assert_eq!(
+            file_system.read_dir(Path::new("/app")).unwrap(),
+            vec![PathBuf::from("/app/.venv"), PathBuf::from("/app/src")]
+        );
+    }
+
+    #[test]
+    fn memory_file_system_mounts() {
+        let file_system = image()
+            .mount_point("/home", FileSystemType::Nfs)
+            .mount_point("/home/user/remote", FileSystemType::Fuse)
+            .dir("/home/user/project");
+
+        let device = |path: &str| file_system.metadata(Path::new(path)).unwrap().device;
+        let file_system_type = |path: &str| file_system.file_system_type(Path::new(path));
+
+        assert_eq!(file_system_type("/app/src"), Some(FileSystemType::Local));
+        assert_eq!(
+            file_system_type("/home/user/project"),
+            Some(FileSystemType::Nfs)
+        );
+        assert_eq!(
+            file_system_type("/home/user/remote"),
+            Some(FileSystemType::Fuse)
+        );
+        assert_eq!(file_system_type("/home/missing"), None);
+        assert_eq!(device("/app/src"), device("/usr/bin/python3"));
+        assert_eq!(device("/home/user"), device("/home/user/project"));
+        assert_ne!(device("/app/src"), device("/home/user/project"));
+        assert_ne!(device("/home/user/project"), device("/home/user/remote"));
+    }
+
+    #[test]
+    fn memory_file_system_detects_symlink_loops() {
+        let file_system = MemoryFileSystem::new()
+            .symlink("/a", "/b")
+            .symlink("/b", "/a");
+
+        assert!(file_system.canonicalize(Path::new("/a")).is_err());
+        assert!(!file_system.exists(Path::new("/a")));
+    }
+
+    #[derive(Debug)]
+    struct CountingFileSystem {
+        file_system: MemoryFileSystem,
+        reads: AtomicUsize,
+    }
+
+    impl FileSystem for CountingFileSystem {
+        fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            self.file_system.read_dir(path)
+        }
+        fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            self.file_system.metadata(path)
+        }
+        fn read_to_string(&self, path: &Path) -> io::Result<String> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            self.file_system.read_to_string(path)
+        }
+        fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            self.file_system.read_link(path)
+        }
+        fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            self.file_system.canonicalize(path)
+        }
+    }
+
+    #[test]
+    fn cached_file_system_reads_each_path_once() {
+        let counting = Arc::new(CountingFileSystem {
+            file_system: image(),
+            reads: AtomicUsize::new(0),
+        });
+        let file_system = CachedFileSystem::new(counting.clone());
+
+        for _ in 0..3 {
+            assert!(file_system.is_file(Path::new("/usr/bin/python3")));
+            assert!(!file_system.exists(Path::new("/usr/bin/python2")));
+            assert_eq!(
+                file_system
+                    .read_to_string(Path::new("/app/.venv/pyvenv.cfg"))
+                    .unwrap(),
+                "home = /usr/bin\n"
+            );
+        }
+        let err = file_system
+            .metadata(Path::new("/usr/bin/python2"))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert_eq!(counting.reads.load(Ordering::Relaxed), 3);
+    }
+}
@@ -1,5 +1,8 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+pub mod file_system;
 pub mod glob;
+pub mod mount;
 pub mod path;
+pub mod root;
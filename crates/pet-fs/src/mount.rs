@@ -0,0 +1,123 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! File systems directories are on.
+//!
+//! Directories on network (NFS, SMB, etc) & FUSE (e.g. sshfs) file systems can be very slow to search,
+//! hence the search roots on those file systems are identified (on Linux, using the `statfs` magic numbers).
+
+use std::{fmt, path::Path};
+
+/// Type of the file system a directory is on, as far as searching it is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileSystemType {
+    /// A local file system, or one that could not be identified.
+    Local,
+    Nfs,
+    /// SMB & CIFS.
+    Smb,
+    Afs,
+    Ceph,
+    Coda,
+    /// 9P, e.g. the Windows drives in WSL.
+    NineP,
+    /// File systems in user space, e.g. sshfs or rclone.
+    Fuse,
+}
+
+impl FileSystemType {
+    /// Whether the file system is on the network (or in user space), i.e. potentially slow to search.
+    pub fn is_remote(&self) -> bool {
+        *self != FileSystemType::Local
+    }
+
+    /// Name of the file system type, e.g. used in telemetry.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileSystemType::Local => "local",
+            FileSystemType::Nfs => "nfs",
+            FileSystemType::Smb => "smb",
+            FileSystemType::Afs => "afs",
+            FileSystemType::Ceph => "ceph",
+            FileSystemType::Coda => "coda",
+            FileSystemType::NineP => "9p",
+            FileSystemType::Fuse => "fuse",
+        }
+    }
+
+    /// File system type identified by the magic number reported by `statfs` (see `linux/magic.h`).
+    pub fn from_magic(magic: u32) -> FileSystemType {
+        match magic {
+            0x6969 => FileSystemType::Nfs,
+            // SMB, CIFS & SMB2.
+            0x517B | 0xFF53_4D42 | 0xFE53_4D42 => FileSystemType::Smb,
+            // AFS (the kernel client) & OpenAFS.
+            0x5346_414F | 0x6B41_4653 => FileSystemType::Afs,
+            0x00C3_6400 => FileSystemType::Ceph,
+            0x7375_7245 => FileSystemType::Coda,
+            0x0102_1997 => FileSystemType::NineP,
+            0x6573_5546 => FileSystemType::Fuse,
+            _ => FileSystemType::Local,
+        }
+    }
+}
+
+impl fmt::Display for FileSystemType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Type of the file system the path is on, `None` if it cannot be identified on this platform.
+#[cfg(target_os = "linux")]
+pub fn get_file_system_type(path: &Path) -> Option<FileSystemType> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: `statfs` is plain old data, all zeros is a valid value.
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `path` is a valid C string & `stat` is a valid `statfs` to be written to.
+    if unsafe { libc::statfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    // The magic numbers are 32 bits, `f_type` is a signed or unsigned word depending on the platform.
+    Some(FileSystemType::from_magic(stat.f_type as u32))
+}
+
+/// Type of the file system the path is on, `None` if it cannot be identified on this platform.
+#[cfg(not(target_os = "linux"))]
+pub fn get_file_system_type(_path: &Path) -> Option<FileSystemType> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_system_type_from_magic() {
+        assert_eq!(FileSystemType::from_magic(0x6969), FileSystemType::Nfs);
+        assert_eq!(FileSystemType::from_magic(0xFF53_4D42), FileSystemType::Smb);
+        assert_eq!(
+            FileSystemType::from_magic(0x6573_5546),
+            FileSystemType::Fuse
+        );
+        // ext4 & tmpfs.
+        assert_eq!(FileSystemType::from_magic(0xEF53), FileSystemType::Local);
+        assert_eq!(
+            FileSystemType::from_magic(0x0102_1994),
+            FileSystemType::Local
+        );
+        assert!(FileSystemType::Fuse.is_remote());
+        assert!(!FileSystemType::Local.is_remote());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn file_system_type_of_a_directory() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        assert!(get_file_system_type(dir.path()).is_some());
+        assert_eq!(get_file_system_type(&dir.path().join("missing")), None);
+    }
+}
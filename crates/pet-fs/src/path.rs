@@ -1,6 +1,7 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+use crate::file_system::{FileSystem, OsFileSystem};
 use std::{
     env,
     path::{Path, PathBuf},
@@ -371,6 +372,7 @@ pub fn resolve_any_symlink<T: AsRef<Path>>(path: &T) -> Option<PathBuf> {
 ///
 /// Returns `None` if the path is not a symlink or cannot be resolved.
 /// If the real file equals the input, returns `None` (the path is not a symlink).
+/// Symlinks are resolved within the alternate root directory of the file system, if any (see `resolve_symlink_in`).
 ///
 /// # Filtering
 /// This function only resolves symlinks for Python and Conda related executables:
@@ -386,7 +388,16 @@ pub fn resolve_any_symlink<T: AsRef<Path>>(path: &T) -> Option<PathBuf> {
 /// - `norm_case()` - Normalizes path case without resolving symlinks
 /// - `resolve_any_symlink()` - Unfiltered version for any symlink
 pub fn resolve_symlink<T: AsRef<Path>>(exe: &T) -> Option<PathBuf> {
-    let name = exe.as_ref().file_name()?.to_string_lossy();
+    resolve_symlink_in(&OsFileSystem, exe)
+}
+
+/// Same as `resolve_symlink`, looking at the files in the given file system.
+pub fn resolve_symlink_in<T: AsRef<Path>>(
+    file_system: &dyn FileSystem,
+    exe: &T,
+) -> Option<PathBuf> {
+    let exe = exe.as_ref();
+    let name = exe.file_name()?.to_string_lossy();
     // In bin directory of homebrew, we have files like python-build, python-config, python3-config
     if name.ends_with("-config") || name.ends_with("-build") {
         return None;
@@ -398,12 +409,11 @@ pub fn resolve_symlink<T: AsRef<Path>>(exe: &T) -> Option<PathBuf> {
 
     // Running readlink for a path thats not a symlink ends up returning relative paths for some reason.
     // A better solution is to first check if a path is a symlink and then resolve it.
-    let metadata = std::fs::symlink_metadata(exe).ok()?;
-    if metadata.is_file() || !metadata.file_type().is_symlink() {
+    if !file_system.is_symlink(exe) {
         return None;
     }
-    if let Ok(readlink) = std::fs::canonicalize(exe) {
-        if readlink == exe.as_ref().to_path_buf() {
+    if let Ok(readlink) = file_system.canonicalize(exe) {
+        if readlink == exe {
             None
         } else {
             Some(readlink)
@@ -0,0 +1,190 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Alternate root directory (e.g. an unpacked container image or a build sysroot) searched instead of `/`.
+//!
+//! Paths of the image (e.g. `/usr/bin/python3` or the contents of `pyvenv.cfg`) are mapped to the host
+//! (`<root>/usr/bin/python3`) before being read, and symlinks are resolved within the root,
+//! the same way they would be inside a chroot.
+
+use std::{
+    ffi::OsString,
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+/// Maximum number of symlinks followed when resolving a path, same as `MAXSYMLINKS` on Linux.
+const MAX_SYMLINK_HOPS: usize = 40;
+
+/// Canonicalizes the alternate root directory, so that the paths built from the root can be mapped back to the image.
+pub fn canonicalize_root(root: &Path) -> PathBuf {
+    fs::canonicalize(root).unwrap_or(root.to_path_buf())
+}
+
+/// Maps an absolute path of the image to the host, e.g. `/usr/bin/python3` to `<root>/usr/bin/python3`.
+///
+/// Relative paths, paths already under the root & all paths when there is no root are returned as is.
+pub fn to_host_path<P: AsRef<Path>>(root: Option<&Path>, path: P) -> PathBuf {
+    let path = path.as_ref();
+    match root {
+        Some(root) => rebase(path, root),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Maps a path of the host back to the image, e.g. `<root>/usr/bin/python3` to `/usr/bin/python3`.
+pub fn to_image_path<P: AsRef<Path>>(root: Option<&Path>, path: P) -> PathBuf {
+    let path = path.as_ref();
+    match root {
+        Some(root) => unbase(path, root),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Same as `fs::canonicalize`, except that absolute symlink targets are resolved within the root.
+/// E.g. `<root>/usr/bin/python3` linking to `/usr/bin/python3.12` resolves to `<root>/usr/bin/python3.12`,
+/// not to the Python of the host.
+pub fn canonicalize<P: AsRef<Path>>(root: &Path, path: P) -> io::Result<PathBuf> {
+    let path = path.as_ref();
+    if path.starts_with(root) {
+        canonicalize_in(path, root)
+    } else {
+        fs::canonicalize(path)
+    }
+}
+
+/// Same as `fs::metadata`, except that symlinks are followed within the root (see `canonicalize`).
+pub fn metadata<P: AsRef<Path>>(root: &Path, path: P) -> io::Result<fs::Metadata> {
+    fs::metadata(canonicalize(root, path)?)
+}
+
+/// Same as `fs::read_to_string`, except that symlinks are followed within the root (see `canonicalize`).
+pub fn read_to_string<P: AsRef<Path>>(root: &Path, path: P) -> io::Result<String> {
+    fs::read_to_string(canonicalize(root, path)?)
+}
+
+fn rebase(path: &Path, root: &Path) -> PathBuf {
+    if path.starts_with(root) || !matches!(path.components().next(), Some(Component::RootDir)) {
+        return path.to_path_buf();
+    }
+    root.join(path.components().skip(1).collect::<PathBuf>())
+}
+
+fn unbase(path: &Path, root: &Path) -> PathBuf {
+    match path.strip_prefix(root) {
+        Ok(relative) => Path::new(&Component::RootDir).join(relative),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+fn canonicalize_in(path: &Path, root: &Path) -> io::Result<PathBuf> {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    // Names still to be resolved, last one first.
+    let mut pending: Vec<OsString> = names(relative).collect();
+    pending.reverse();
+    let mut resolved = root.to_path_buf();
+    let mut hops = 0;
+    while let Some(name) = pending.pop() {
+        if name == ".." {
+            // `..` of the root is the root itself.
+            if resolved != root {
+                resolved.pop();
+            }
+            continue;
+        }
+        let candidate = resolved.join(&name);
+        if !fs::symlink_metadata(&candidate)?.file_type().is_symlink() {
+            resolved = candidate;
+            continue;
+        }
+        hops += 1;
+        if hops > MAX_SYMLINK_HOPS {
+            return Err(io::Error::other(format!(
+                "Too many levels of symbolic links in {path:?}"
+            )));
+        }
+        let target = fs::read_link(&candidate)?;
+        if target.has_root() {
+            resolved = root.to_path_buf();
+        }
+        pending.extend(names(&target).collect::<Vec<_>>().into_iter().rev());
+    }
+    Ok(resolved)
+}
+
+fn names(path: &Path) -> impl Iterator<Item = OsString> + '_ {
+    path.components().filter_map(|component| match component {
+        Component::Normal(name) => Some(name.to_os_string()),
+        Component::ParentDir => Some(OsString::from("..")),
+        Component::RootDir | Component::Prefix(_) | Component::CurDir => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_paths_between_image_and_host() {
+        let root = Path::new("/images/debian");
+
+        assert_eq!(
+            rebase(Path::new("/usr/bin/python3"), root),
+            PathBuf::from("/images/debian/usr/bin/python3")
+        );
+        assert_eq!(
+            rebase(Path::new("/images/debian/usr/bin"), root),
+            PathBuf::from("/images/debian/usr/bin")
+        );
+        assert_eq!(
+            rebase(Path::new("bin/python"), root),
+            PathBuf::from("bin/python")
+        );
+        assert_eq!(
+            unbase(Path::new("/images/debian/opt/conda"), root),
+            PathBuf::from("/opt/conda")
+        );
+        assert_eq!(
+            unbase(Path::new("/opt/conda"), root),
+            PathBuf::from("/opt/conda")
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn resolves_symlinks_within_the_root() {
+        use std::os::unix::fs::symlink;
+
+        let root = std::env::temp_dir().join(format!("pet-root-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("usr").join("bin")).unwrap();
+        let root = fs::canonicalize(&root).unwrap();
+        fs::write(root.join("usr").join("bin").join("python3.12"), "").unwrap();
+        // Merged /usr, i.e. `/bin` links to `usr/bin`.
+        symlink("usr/bin", root.join("bin")).unwrap();
+        // Absolute target, which must not resolve to the Python of the host.
+        symlink(
+            "/usr/bin/python3.12",
+            root.join("usr").join("bin").join("python3"),
+        )
+        .unwrap();
+        symlink(
+            "../../../../../bin/python3",
+            root.join("usr").join("bin").join("python"),
+        )
+        .unwrap();
+
+        let expected = root.join("usr").join("bin").join("python3.12");
+        assert_eq!(
+            canonicalize_in(&root.join("bin").join("python3"), &root).unwrap(),
+            expected
+        );
+        assert_eq!(
+            canonicalize_in(&root.join("usr").join("bin").join("python"), &root).unwrap(),
+            expected
+        );
+        assert!(canonicalize_in(&root.join("bin").join("missing"), &root).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -1,11 +1,16 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use pet_conda::utils::is_conda_env;
-use pet_fs::path::{expand_path, norm_case};
-use std::{fs, path::PathBuf};
+use pet_conda::utils::is_conda_env_in;
+use pet_core::search_root::{SearchRootBudget, Unlimited};
+use pet_fs::{
+    file_system::{FileSystem, OsFileSystem},
+    path::{expand_path, norm_case},
+};
+use std::path::PathBuf;
 
 fn get_global_virtualenv_dirs(
+    file_system: &dyn FileSystem,
     work_on_home_env_var: Option<String>,
     xdg_data_home: Option<String>,
     user_home: Option<PathBuf>,
@@ -14,14 +19,14 @@ fn get_global_virtualenv_dirs(
 
     if let Some(work_on_home) = work_on_home_env_var {
         let work_on_home = norm_case(expand_path(PathBuf::from(work_on_home)));
-        if work_on_home.exists() {
+        if file_system.exists(&work_on_home) {
             venv_dirs.push(work_on_home);
         }
     }
 
     // Used by pipenv (https://github.com/pypa/pipenv/blob/main/pipenv/utils/shell.py#L184)
     if let Some(xdg_data_home) = xdg_data_home.map(|d| PathBuf::from(d).join("virtualenvs")) {
-        if xdg_data_home.exists() {
+        if file_system.exists(&xdg_data_home) {
             venv_dirs.push(xdg_data_home);
         }
     }
@@ -34,7 +39,7 @@ fn get_global_virtualenv_dirs(
             PathBuf::from(".local").join("share").join("virtualenvs"), // Used by pipenv (https://github.com/pypa/pipenv/blob/main/pipenv/utils/shell.py#L184)
         ] {
             let venv_dir = home.join(dir);
-            if venv_dir.exists() {
+            if file_system.exists(&venv_dir) {
                 venv_dirs.push(venv_dir);
             }
         }
@@ -42,7 +47,7 @@ fn get_global_virtualenv_dirs(
             // https://virtualenvwrapper.readthedocs.io/en/latest/index.html
             // Default recommended location for virtualenvwrapper
             let envs = home.join("Envs");
-            if envs.exists() {
+            if file_system.exists(&envs) {
                 venv_dirs.push(envs);
             }
         }
@@ -56,23 +61,49 @@ pub fn list_global_virtual_envs_paths(
     work_on_home_env_var: Option<String>,
     xdg_data_home: Option<String>,
     user_home: Option<PathBuf>,
+) -> Vec<PathBuf> {
+    list_global_virtual_envs_paths_in(
+        &OsFileSystem,
+        &Unlimited,
+        virtual_env_env_var,
+        work_on_home_env_var,
+        xdg_data_home,
+        user_home,
+    )
+}
+
+/// Same as `list_global_virtual_envs_paths`, looking at the files in the given file system
+/// and (at most) the budget of directories in each of the global virtual env directories.
+pub fn list_global_virtual_envs_paths_in(
+    file_system: &dyn FileSystem,
+    budget: &dyn SearchRootBudget,
+    virtual_env_env_var: Option<String>,
+    work_on_home_env_var: Option<String>,
+    xdg_data_home: Option<String>,
+    user_home: Option<PathBuf>,
 ) -> Vec<PathBuf> {
     let mut python_envs: Vec<PathBuf> = vec![];
 
     if let Some(virtual_env) = virtual_env_env_var {
         let virtual_env = norm_case(expand_path(PathBuf::from(virtual_env)));
-        if virtual_env.exists() {
+        if file_system.exists(&virtual_env) {
             python_envs.push(virtual_env);
         }
     }
 
-    for root_dir in &get_global_virtualenv_dirs(work_on_home_env_var, xdg_data_home, user_home) {
-        if let Ok(dirs) = fs::read_dir(root_dir) {
+    for root_dir in
+        &get_global_virtualenv_dirs(file_system, work_on_home_env_var, xdg_data_home, user_home)
+    {
+        let budget = budget.get(root_dir);
+        if budget == 0 {
+            continue;
+        }
+        if let Ok(dirs) = file_system.read_dir(root_dir) {
             python_envs.append(
                 &mut dirs
-                    .filter_map(Result::ok)
-                    .map(|e| e.path())
-                    .filter(|p| !is_conda_env(p))
+                    .into_iter()
+                    .filter(|p| !is_conda_env_in(file_system, p))
+                    .take(budget)
                     .collect(),
             )
         }
@@ -126,6 +157,7 @@ mod tests {
         }
 
         let mut dirs = get_global_virtualenv_dirs(
+            &OsFileSystem,
             Some(missing_work_on_home.to_string_lossy().to_string()),
             Some(root.join("xdg-data").to_string_lossy().to_string()),
             Some(root.clone()),
@@ -161,6 +193,7 @@ mod tests {
         assert_eq!(dirs, expected_dirs);
 
         let dirs = get_global_virtualenv_dirs(
+            &OsFileSystem,
             Some(work_on_home.to_string_lossy().to_string()),
             None,
             None,
@@ -237,7 +270,7 @@ mod tests {
 
     #[test]
     fn global_virtualenv_dirs_returns_empty_when_all_none() {
-        let dirs = get_global_virtualenv_dirs(None, None, None);
+        let dirs = get_global_virtualenv_dirs(&OsFileSystem, None, None, None);
         assert!(dirs.is_empty());
     }
 
@@ -246,8 +279,12 @@ mod tests {
         let root = create_test_dir("skip-work-on");
         let nonexistent = root.join("does-not-exist");
 
-        let dirs =
-            get_global_virtualenv_dirs(Some(nonexistent.to_string_lossy().to_string()), None, None);
+        let dirs = get_global_virtualenv_dirs(
+            &OsFileSystem,
+            Some(nonexistent.to_string_lossy().to_string()),
+            None,
+            None,
+        );
         assert!(dirs.is_empty());
 
         fs::remove_dir_all(root).unwrap();
@@ -258,7 +295,12 @@ mod tests {
         let root = create_test_dir("skip-xdg");
         let xdg = root.join("xdg-missing");
 
-        let dirs = get_global_virtualenv_dirs(None, Some(xdg.to_string_lossy().to_string()), None);
+        let dirs = get_global_virtualenv_dirs(
+            &OsFileSystem,
+            None,
+            Some(xdg.to_string_lossy().to_string()),
+            None,
+        );
         assert!(dirs.is_empty());
 
         fs::remove_dir_all(root).unwrap();
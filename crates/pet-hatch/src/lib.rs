@@ -32,7 +32,6 @@
 
 use std::{
     collections::{HashMap, HashSet},
-    fs,
     path::{Path, PathBuf},
     sync::{Arc, Mutex},
 };
@@ -44,10 +43,15 @@ use pet_core::{
     python_environment::{PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind},
     pyvenv_cfg::PyVenvCfg,
     reporter::Reporter,
+    search_root::{read_search_root, SearchRootBudget, Unlimited},
     Configuration, Locator, LocatorKind, RefreshStatePersistence,
 };
-use pet_fs::path::{expand_path, norm_case};
-use pet_python_utils::executable::{find_executable, find_executables};
+use pet_fs::{
+    file_system::FileSystem,
+    path::{expand_path, norm_case},
+};
+use pet_python_utils::arch::get_architecture;
+use pet_python_utils::executable::{find_executable_in, find_executables_in};
 use serde::Deserialize;
 
 /// Subdirectory under the Hatch data directory where the default
@@ -115,6 +119,7 @@ pub struct Hatch {
     /// `None` only when the platform data directory itself cannot be
     /// resolved (e.g. no home directory).
     default_virtual_dir: Option<PathBuf>,
+    file_system: Arc<dyn FileSystem>,
     state: Arc<Mutex<HatchState>>,
 }
 
@@ -132,6 +137,7 @@ impl Hatch {
     pub fn from(environment: &dyn Environment) -> Self {
         Self {
             default_virtual_dir: get_default_virtual_dir(environment),
+            file_system: environment.file_system(),
             state: Arc::new(Mutex::new(HatchState::new())),
         }
     }
@@ -168,7 +174,8 @@ impl Hatch {
 
             // Slow path: parse outside the lock so other workspaces are
             // not blocked on this workspace's filesystem.
-            let (virtual_dirs, env_names) = resolve_workspace_hatch_config(workspace);
+            let (virtual_dirs, env_names) =
+                resolve_workspace_hatch_config(self.file_system.as_ref(), workspace);
             let parsed = Arc::new(WorkspaceEntry {
                 virtual_dirs,
                 matcher: EnvNameMatcher::from_names(env_names),
@@ -318,7 +325,7 @@ impl Locator for Hatch {
         // Now that we know this is (likely) a Hatch env, read pyvenv.cfg.
         // Hatch always writes one; if it's missing this isn't actually a
         // Hatch-managed env.
-        let cfg = PyVenvCfg::find(&prefix)?;
+        let cfg = PyVenvCfg::find_in(env.file_system.as_ref(), &prefix)?;
 
         trace!(
             "Hatch env {} found at {}",
@@ -326,6 +333,7 @@ impl Locator for Hatch {
             env.executable.display()
         );
         Some(build_env(
+            env.file_system.as_ref(),
             &prefix,
             &cfg,
             env_name,
@@ -335,6 +343,9 @@ impl Locator for Hatch {
     }
 
     fn find(&self, reporter: &dyn Reporter) {
+        self.find_with_budget(reporter, &Unlimited);
+    }
+    fn find_with_budget(&self, reporter: &dyn Reporter, budget: &dyn SearchRootBudget) {
         // 1. Walk the default storage directory if it currently exists. We
         //    re-check existence here (rather than caching the result of the
         //    check at construction) because the long-lived locator graph is
@@ -342,8 +353,9 @@ impl Locator for Hatch {
         //    create their first env after that point and we still want to
         //    discover it without a restart.
         if let Some(storage) = self.default_virtual_dir.as_deref() {
-            if storage.is_dir() {
-                for env in find_envs_in_default_storage(storage) {
+            if self.file_system.is_dir(storage) {
+                for env in find_envs_in_default_storage(self.file_system.as_ref(), storage, budget)
+                {
                     reporter.report_environment(&env);
                 }
             }
@@ -368,9 +380,12 @@ impl Locator for Hatch {
                 continue;
             }
             for virtual_dir in &entry.virtual_dirs {
-                for env in
-                    find_envs_in_flat_dir(virtual_dir, Some(workspace.clone()), &entry.matcher)
-                {
+                for env in find_envs_in_flat_dir(
+                    self.file_system.as_ref(),
+                    virtual_dir,
+                    Some(workspace.clone()),
+                    &entry.matcher,
+                ) {
                     reporter.report_environment(&env);
                 }
             }
@@ -562,13 +577,18 @@ struct HatchDirs {
 /// has the same shape as `HatchConfig`) for `workspace`, returning both
 /// in a single pass. Returns `(pyproject_hatch, hatch_toml)` where each
 /// is `None` if the corresponding file is missing or unparseable.
-fn read_workspace_hatch_sections(workspace: &Path) -> (Option<HatchConfig>, Option<HatchConfig>) {
-    let pyproject = fs::read_to_string(workspace.join("pyproject.toml"))
+fn read_workspace_hatch_sections(
+    file_system: &dyn FileSystem,
+    workspace: &Path,
+) -> (Option<HatchConfig>, Option<HatchConfig>) {
+    let pyproject = file_system
+        .read_to_string(&workspace.join("pyproject.toml"))
         .ok()
         .and_then(|s| toml::from_str::<PyProject>(&s).ok())
         .and_then(|pp| pp.tool)
         .and_then(|t| t.hatch);
-    let hatch_toml = fs::read_to_string(workspace.join("hatch.toml"))
+    let hatch_toml = file_system
+        .read_to_string(&workspace.join("hatch.toml"))
         .ok()
         .and_then(|s| toml::from_str::<HatchConfig>(&s).ok());
     (pyproject, hatch_toml)
@@ -636,8 +656,11 @@ fn resolve_virtual_paths_against_workspace(workspace: &Path, raw: Vec<String>) -
 /// Single entry point used by `configure()`: parses `pyproject.toml` and
 /// `hatch.toml` ONCE each per workspace and derives both the resolved
 /// virtual directories and the declared env names from the same parse.
-fn resolve_workspace_hatch_config(workspace: &Path) -> (Vec<PathBuf>, HashSet<String>) {
-    let sections = read_workspace_hatch_sections(workspace);
+fn resolve_workspace_hatch_config(
+    file_system: &dyn FileSystem,
+    workspace: &Path,
+) -> (Vec<PathBuf>, HashSet<String>) {
+    let sections = read_workspace_hatch_sections(file_system, workspace);
     let virtual_dirs =
         resolve_virtual_paths_against_workspace(workspace, extract_virtual_paths(&sections));
     let env_names = extract_env_names(&sections);
@@ -659,7 +682,7 @@ fn resolve_workspace_hatch_config(workspace: &Path) -> (Vec<PathBuf>, HashSet<St
 /// `find_envs_in_flat_dir()` handles missing directories at discovery time.
 #[cfg(test)]
 fn resolve_project_virtual_dirs(workspace: &Path) -> Vec<PathBuf> {
-    let sections = read_workspace_hatch_sections(workspace);
+    let sections = read_workspace_hatch_sections(&pet_fs::file_system::OsFileSystem, workspace);
     resolve_virtual_paths_against_workspace(workspace, extract_virtual_paths(&sections))
 }
 
@@ -680,7 +703,7 @@ const HATCH_IMPLICIT_DEFAULT_ENV: &str = "default";
 /// the same directory would be misclassified as Hatch.
 #[cfg(test)]
 fn resolve_project_env_names(workspace: &Path) -> HashSet<String> {
-    let sections = read_workspace_hatch_sections(workspace);
+    let sections = read_workspace_hatch_sections(&pet_fs::file_system::OsFileSystem, workspace);
     extract_env_names(&sections)
 }
 
@@ -690,36 +713,30 @@ fn resolve_project_env_names(workspace: &Path) -> HashSet<String> {
 
 /// Walk `<storage>/<project_name>/<project_id>/<venv_name>/` and report
 /// each leaf venv discovered.
-fn find_envs_in_default_storage(storage: &Path) -> Vec<PythonEnvironment> {
+fn find_envs_in_default_storage(
+    file_system: &dyn FileSystem,
+    storage: &Path,
+    budget: &dyn SearchRootBudget,
+) -> Vec<PythonEnvironment> {
     let mut envs = Vec::new();
-    let project_dirs = match fs::read_dir(storage) {
-        Ok(d) => d,
-        Err(_) => return envs,
-    };
-    for project_entry in project_dirs.filter_map(Result::ok) {
-        let project_dir = project_entry.path();
-        if !project_dir.is_dir() {
-            continue;
-        }
-        let id_dirs = match fs::read_dir(&project_dir) {
+    for project_dir in read_search_root(file_system, budget, storage) {
+        let id_dirs = match file_system.read_dir(&project_dir) {
             Ok(d) => d,
             Err(_) => continue,
         };
-        for id_entry in id_dirs.filter_map(Result::ok) {
-            let id_dir = id_entry.path();
-            if !id_dir.is_dir() {
+        for id_dir in id_dirs {
+            if !file_system.is_dir(&id_dir) {
                 continue;
             }
-            let env_dirs = match fs::read_dir(&id_dir) {
+            let env_dirs = match file_system.read_dir(&id_dir) {
                 Ok(d) => d,
                 Err(_) => continue,
             };
-            for env_entry in env_dirs.filter_map(Result::ok) {
-                let env_dir = env_entry.path();
-                if !env_dir.is_dir() {
+            for env_dir in env_dirs {
+                if !file_system.is_dir(&env_dir) {
                     continue;
                 }
-                if let Some(env) = build_env_from_prefix(&env_dir, None) {
+                if let Some(env) = build_env_from_prefix(file_system, &env_dir, None) {
                     envs.push(env);
                 }
             }
@@ -789,18 +806,18 @@ impl EnvNameMatcher {
 /// envs (so a shared dir like `~/.virtualenvs` only yields envs the
 /// workspace actually declares).
 fn find_envs_in_flat_dir(
+    file_system: &dyn FileSystem,
     dir: &Path,
     project: Option<PathBuf>,
     matcher: &EnvNameMatcher,
 ) -> Vec<PythonEnvironment> {
     let mut envs = Vec::new();
-    let entries = match fs::read_dir(dir) {
+    let entries = match file_system.read_dir(dir) {
         Ok(d) => d,
         Err(_) => return envs,
     };
-    for entry in entries.filter_map(Result::ok) {
-        let env_dir = entry.path();
-        if !env_dir.is_dir() {
+    for env_dir in entries {
+        if !file_system.is_dir(&env_dir) {
             continue;
         }
         let leaf = match env_dir.file_name() {
@@ -810,7 +827,7 @@ fn find_envs_in_flat_dir(
         if !matcher.matches(&leaf) {
             continue;
         }
-        if let Some(env) = build_env_from_prefix(&env_dir, project.clone()) {
+        if let Some(env) = build_env_from_prefix(file_system, &env_dir, project.clone()) {
             envs.push(env);
         }
     }
@@ -818,11 +835,12 @@ fn find_envs_in_flat_dir(
 }
 
 fn build_env_from_prefix(
+    file_system: &dyn FileSystem,
     prefix: &Path,
     project_path: Option<PathBuf>,
 ) -> Option<PythonEnvironment> {
-    let cfg = PyVenvCfg::find(prefix)?;
-    let executable = find_executable(prefix)?;
+    let cfg = PyVenvCfg::find_in(file_system, prefix)?;
+    let executable = find_executable_in(file_system, prefix)?;
     let env_name = cfg
         .prompt
         .clone()
@@ -830,16 +848,18 @@ fn build_env_from_prefix(
     Some(
         PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Hatch))
             .name(env_name)
+            .arch(get_architecture(&executable))
             .executable(Some(executable))
             .version(cfg.version)
             .prefix(Some(prefix.to_path_buf()))
-            .symlinks(Some(find_executables(prefix)))
+            .symlinks(Some(find_executables_in(file_system, prefix)))
             .project(project_path)
             .build(),
     )
 }
 
 fn build_env(
+    file_system: &dyn FileSystem,
     prefix: &Path,
     cfg: &PyVenvCfg,
     fallback_name: String,
@@ -850,9 +870,10 @@ fn build_env(
     PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Hatch))
         .name(Some(env_name))
         .executable(Some(executable.to_path_buf()))
+        .arch(get_architecture(executable))
         .version(cfg.version.clone())
         .prefix(Some(prefix.to_path_buf()))
-        .symlinks(Some(find_executables(prefix)))
+        .symlinks(Some(find_executables_in(file_system, prefix)))
         .project(project_path)
         .build()
 }
@@ -860,7 +881,9 @@ fn build_env(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pet_fs::file_system::{os_file_system, OsFileSystem};
     use std::collections::HashMap;
+    use std::fs;
     use std::sync::Mutex as StdMutex;
     use tempfile::TempDir;
 
@@ -933,6 +956,7 @@ mod tests {
     fn make_locator(default_virtual_dir: Option<PathBuf>) -> Hatch {
         Hatch {
             default_virtual_dir,
+            file_system: os_file_system(),
             state: Arc::new(Mutex::new(HatchState::new())),
         }
     }
@@ -980,7 +1004,7 @@ mod tests {
         let identified = locator.try_from(&env).expect("Hatch env should match");
         assert_eq!(identified.kind, Some(PythonEnvironmentKind::Hatch));
         assert_eq!(identified.name, Some("default".to_string()));
-        assert_eq!(identified.version, Some("3.12.1".to_string()));
+        assert_eq!(identified.version, Some("3.12.1".parse().unwrap()));
         assert_eq!(identified.prefix, Some(norm_case(&prefix)));
         assert!(identified.project.is_none());
     }
@@ -1099,11 +1123,11 @@ mod tests {
         // A bogus shallower entry should be ignored (no pyvenv.cfg here).
         fs::create_dir_all(storage.join("orphan")).unwrap();
 
-        let envs = find_envs_in_default_storage(&storage);
+        let envs = find_envs_in_default_storage(&OsFileSystem, &storage, &Unlimited);
         assert_eq!(envs.len(), 2);
         for env in envs {
             assert_eq!(env.kind, Some(PythonEnvironmentKind::Hatch));
-            assert_eq!(env.version.as_deref(), Some("3.12.1"));
+            assert_eq!(env.version, Some("3.12.1".parse().unwrap()));
         }
     }
 
@@ -1124,7 +1148,12 @@ mod tests {
         let virtual_dirs = resolve_project_virtual_dirs(&project);
         assert_eq!(virtual_dirs.len(), 1);
         let matcher = EnvNameMatcher::from_names(resolve_project_env_names(&project));
-        let envs = find_envs_in_flat_dir(&virtual_dirs[0], Some(project.clone()), &matcher);
+        let envs = find_envs_in_flat_dir(
+            &OsFileSystem,
+            &virtual_dirs[0],
+            Some(project.clone()),
+            &matcher,
+        );
         assert_eq!(envs.len(), 1);
         assert_eq!(envs[0].project, Some(norm_case(&project)));
     }
@@ -1782,7 +1811,7 @@ mod tests {
         let mut raw = HashSet::new();
         raw.insert("default".to_string());
         let matcher = EnvNameMatcher::from_names(raw);
-        let envs = find_envs_in_flat_dir(&shared, None, &matcher);
+        let envs = find_envs_in_flat_dir(&OsFileSystem, &shared, None, &matcher);
         assert_eq!(envs.len(), 1);
         assert_eq!(envs[0].prefix, Some(hatch_env));
     }
@@ -1810,7 +1839,7 @@ mod tests {
         let mut raw = HashSet::new();
         raw.insert("test".to_string());
         let matcher = EnvNameMatcher::from_names(raw);
-        let envs = find_envs_in_flat_dir(&shared, None, &matcher);
+        let envs = find_envs_in_flat_dir(&OsFileSystem, &shared, None, &matcher);
         assert_eq!(envs.len(), 2);
     }
 
@@ -3,6 +3,7 @@
 
 use crate::env_variables::EnvVariables;
 use lazy_static::lazy_static;
+use pet_fs::file_system::FileSystem;
 use regex::Regex;
 use std::path::PathBuf;
 
@@ -21,7 +22,10 @@ lazy_static! {
 //     None
 // }
 
-pub fn get_homebrew_prefix_bin(env_vars: &EnvVariables) -> Vec<PathBuf> {
+pub fn get_homebrew_prefix_bin(
+    file_system: &dyn FileSystem,
+    env_vars: &EnvVariables,
+) -> Vec<PathBuf> {
     // Homebrew install folders documented here https://docs.brew.sh/Installation
     // /opt/homebrew for Apple Silicon,
     // /usr/local for macOS Intel
@@ -35,13 +39,15 @@ pub fn get_homebrew_prefix_bin(env_vars: &EnvVariables) -> Vec<PathBuf> {
     ]
     .iter()
     .map(PathBuf::from)
-    .filter(|p| p.exists())
+    .filter(|p| file_system.exists(p))
     .collect::<Vec<PathBuf>>();
 
     // Check the environment variables
     if let Some(homebrew_prefix) = &env_vars.homebrew_prefix {
         let homebrew_prefix_bin = PathBuf::from(homebrew_prefix).join("bin");
-        if homebrew_prefix_bin.exists() && !homebrew_prefixes.contains(&homebrew_prefix_bin) {
+        if file_system.exists(&homebrew_prefix_bin)
+            && !homebrew_prefixes.contains(&homebrew_prefix_bin)
+        {
             homebrew_prefixes.push(homebrew_prefix_bin);
         }
     }
@@ -52,6 +58,7 @@ pub fn get_homebrew_prefix_bin(env_vars: &EnvVariables) -> Vec<PathBuf> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pet_fs::file_system::OsFileSystem;
     use std::fs;
     use tempfile::tempdir;
 
@@ -68,7 +75,7 @@ mod tests {
             known_global_search_locations: vec![],
         };
 
-        let prefix_bins = get_homebrew_prefix_bin(&env_vars);
+        let prefix_bins = get_homebrew_prefix_bin(&OsFileSystem, &env_vars);
 
         assert!(prefix_bins.contains(&homebrew_bin));
     }
@@ -85,7 +92,7 @@ mod tests {
             known_global_search_locations: vec![],
         };
 
-        let prefix_bins = get_homebrew_prefix_bin(&env_vars);
+        let prefix_bins = get_homebrew_prefix_bin(&OsFileSystem, &env_vars);
 
         assert!(!prefix_bins
             .iter()
@@ -103,7 +110,7 @@ mod tests {
         };
 
         // Should not panic and should return whatever standard paths exist
-        let prefix_bins = get_homebrew_prefix_bin(&env_vars);
+        let prefix_bins = get_homebrew_prefix_bin(&OsFileSystem, &env_vars);
         // All returned paths should actually exist
         for path in &prefix_bins {
             assert!(path.exists(), "{:?} should exist", path);
@@ -126,7 +133,7 @@ mod tests {
             known_global_search_locations: vec![],
         };
 
-        let prefix_bins = get_homebrew_prefix_bin(&env_vars);
+        let prefix_bins = get_homebrew_prefix_bin(&OsFileSystem, &env_vars);
         let count = prefix_bins.iter().filter(|p| **p == custom_bin).count();
         assert_eq!(count, 1, "Custom bin path should appear exactly once");
     }
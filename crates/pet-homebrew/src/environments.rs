@@ -4,10 +4,12 @@
 use crate::sym_links::get_known_symlinks;
 use lazy_static::lazy_static;
 use log::trace;
-use pet_core::python_environment::{
-    PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind,
+use pet_core::{
+    python_environment::{PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind},
+    python_version::PythonVersion,
 };
-use pet_fs::path::resolve_symlink;
+use pet_fs::{file_system::FileSystem, path::resolve_symlink_in};
+use pet_python_utils::arch::get_architecture;
 use regex::Regex;
 use std::path::{Path, PathBuf};
 
@@ -17,6 +19,7 @@ lazy_static! {
 }
 
 pub fn get_python_info(
+    file_system: &dyn FileSystem,
     python_exe_from_bin_dir: &Path,
     resolved_exe: &Path,
 ) -> Option<PythonEnvironment> {
@@ -34,7 +37,7 @@ pub fn get_python_info(
         resolved_exe.to_path_buf(),
     ];
     if let Some(version) = &version {
-        symlinks.append(&mut get_known_symlinks(resolved_exe, version));
+        symlinks.append(&mut get_known_symlinks(file_system, resolved_exe, version));
     }
 
     // Possible the python_exe_from_bin_dir is <full path>/python3.12
@@ -43,7 +46,7 @@ pub fn get_python_info(
     // If they do, then they are also valid symlinks.
     let parent = python_exe_from_bin_dir.parent()?;
     for possible_symlink in [parent.join("python"), parent.join("python3")] {
-        if resolve_symlink(&possible_symlink).unwrap_or_default() == resolved_exe {
+        if resolve_symlink_in(file_system, &possible_symlink).unwrap_or_default() == resolved_exe {
             symlinks.push(possible_symlink);
         }
     }
@@ -53,6 +56,7 @@ pub fn get_python_info(
 
     let env = PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Homebrew))
         .executable(Some(python_exe_from_bin_dir.to_path_buf()))
+        .arch(get_architecture(python_exe_from_bin_dir))
         .version(version)
         .prefix(get_prefix(resolved_exe))
         .symlinks(Some(symlinks))
@@ -60,12 +64,10 @@ pub fn get_python_info(
     Some(env)
 }
 
-fn get_version(resolved_exe: &Path) -> Option<String> {
+fn get_version(resolved_exe: &Path) -> Option<PythonVersion> {
     let python_version = resolved_exe.to_string_lossy().to_string();
-    match PYTHON_VERSION.captures(&python_version) {
-        Some(captures) => captures.get(1).map(|version| version.as_str().to_string()),
-        None => None,
-    }
+    let captures = PYTHON_VERSION.captures(&python_version)?;
+    PythonVersion::parse(captures.get(1)?.as_str())
 }
 
 fn get_prefix(_resolved_file: &Path) -> Option<PathBuf> {
@@ -150,6 +152,7 @@ fn get_prefix(_resolved_file: &Path) -> Option<PathBuf> {
 #[cfg(all(test, unix))]
 mod tests {
     use super::*;
+    use pet_fs::file_system::OsFileSystem;
 
     #[test]
     fn extract_version() {
@@ -157,14 +160,14 @@ mod tests {
             get_version(&PathBuf::from(
                 "/home/linuxbrew/.linuxbrew/Cellar/python@3.12/3.12.4/bin/python3"
             )),
-            Some("3.12.4".to_string())
+            Some("3.12.4".parse().unwrap())
         );
 
         assert_eq!(
             get_version(&PathBuf::from(
                 "/home/linuxbrew/.linuxbrew/Cellar/python@3.11/3.11.9_1/bin/python3.11"
             )),
-            Some("3.11.9".to_string())
+            Some("3.11.9".parse().unwrap())
         );
     }
 
@@ -174,7 +177,7 @@ mod tests {
             get_version(&PathBuf::from(
                 "/opt/homebrew/Cellar/python@3.12/3.12.3/Frameworks/Python.framework/Versions/3.12/bin/python3.12"
             )),
-            Some("3.12.3".to_string())
+            Some("3.12.3".parse().unwrap())
         );
     }
 
@@ -184,7 +187,7 @@ mod tests {
             get_version(&PathBuf::from(
                 "/usr/local/Cellar/python@3.8/3.8.20/Frameworks/Python.framework/Versions/3.8/bin/python3.8"
             )),
-            Some("3.8.20".to_string())
+            Some("3.8.20".parse().unwrap())
         );
     }
 
@@ -215,11 +218,11 @@ mod tests {
         let resolved_exe =
             PathBuf::from("/home/linuxbrew/.linuxbrew/Cellar/python@3.12/3.12.4/bin/python3.12");
 
-        let env = get_python_info(&bin_exe, &resolved_exe).unwrap();
+        let env = get_python_info(&OsFileSystem, &bin_exe, &resolved_exe).unwrap();
 
         assert_eq!(env.kind, Some(PythonEnvironmentKind::Homebrew));
         assert_eq!(env.executable, Some(bin_exe.clone()));
-        assert_eq!(env.version, Some("3.12.4".to_string()));
+        assert_eq!(env.version, Some("3.12.4".parse().unwrap()));
         assert_eq!(env.prefix, None);
         // Both bin exe and resolved exe should be in symlinks
         let symlinks = env.symlinks.unwrap();
@@ -232,7 +235,7 @@ mod tests {
         let bin_exe = PathBuf::from("/home/linuxbrew/.linuxbrew/bin/python3");
         let resolved_exe = PathBuf::from("/home/linuxbrew/.linuxbrew/bin/python3");
 
-        let env = get_python_info(&bin_exe, &resolved_exe).unwrap();
+        let env = get_python_info(&OsFileSystem, &bin_exe, &resolved_exe).unwrap();
 
         assert_eq!(env.kind, Some(PythonEnvironmentKind::Homebrew));
         assert_eq!(env.version, None);
@@ -245,11 +248,11 @@ mod tests {
             "/opt/homebrew/Cellar/python@3.12/3.12.3/Frameworks/Python.framework/Versions/3.12/bin/python3.12",
         );
 
-        let env = get_python_info(&bin_exe, &resolved_exe).unwrap();
+        let env = get_python_info(&OsFileSystem, &bin_exe, &resolved_exe).unwrap();
 
         assert_eq!(env.kind, Some(PythonEnvironmentKind::Homebrew));
         assert_eq!(env.executable, Some(bin_exe.clone()));
-        assert_eq!(env.version, Some("3.12.3".to_string()));
+        assert_eq!(env.version, Some("3.12.3".parse().unwrap()));
         let symlinks = env.symlinks.unwrap();
         assert!(symlinks.contains(&bin_exe));
         assert!(symlinks.contains(&resolved_exe));
@@ -262,10 +265,10 @@ mod tests {
             "/usr/local/Cellar/python@3.8/3.8.20/Frameworks/Python.framework/Versions/3.8/bin/python3.8",
         );
 
-        let env = get_python_info(&bin_exe, &resolved_exe).unwrap();
+        let env = get_python_info(&OsFileSystem, &bin_exe, &resolved_exe).unwrap();
 
         assert_eq!(env.kind, Some(PythonEnvironmentKind::Homebrew));
         assert_eq!(env.executable, Some(bin_exe));
-        assert_eq!(env.version, Some("3.8.20".to_string()));
+        assert_eq!(env.version, Some("3.8.20".parse().unwrap()));
     }
 }
@@ -4,7 +4,7 @@
 use env_variables::EnvVariables;
 use environment_locations::get_homebrew_prefix_bin;
 use environments::get_python_info;
-use pet_conda::utils::is_conda_env;
+use pet_conda::utils::is_conda_env_in;
 use pet_core::{
     env::PythonEnv,
     os_environment::Environment,
@@ -12,10 +12,10 @@ use pet_core::{
     reporter::Reporter,
     Locator, LocatorKind,
 };
-use pet_fs::path::resolve_symlink;
-use pet_python_utils::executable::find_executables;
+use pet_fs::{file_system::FileSystem, path::resolve_symlink_in};
+use pet_python_utils::executable::find_executables_in;
 use pet_virtualenv::is_virtualenv;
-use std::{fs, path::PathBuf, thread};
+use std::{path::PathBuf, sync::Arc};
 use sym_links::is_homebrew_python;
 
 mod env_variables;
@@ -25,12 +25,14 @@ mod sym_links;
 
 pub struct Homebrew {
     environment: EnvVariables,
+    file_system: Arc<dyn FileSystem>,
 }
 
 impl Homebrew {
     pub fn from(environment: &dyn Environment) -> Homebrew {
         Homebrew {
             environment: EnvVariables::from(environment),
+            file_system: environment.file_system(),
         }
     }
 }
@@ -48,17 +50,18 @@ fn from(env: &PythonEnv) -> Option<PythonEnvironment> {
         return None;
     }
 
+    let file_system = env.file_system.as_ref();
     if let Some(prefix) = &env.prefix {
-        if is_conda_env(prefix) {
+        if is_conda_env_in(file_system, prefix) {
             return None;
         }
     }
     // Possible this is a root conda env (hence parent directory is conda install dir).
-    if is_conda_env(env.executable.parent()?) {
+    if is_conda_env_in(file_system, env.executable.parent()?) {
         return None;
     }
     // Possible this is a conda env (hence parent directory is Scripts/bin dir).
-    if is_conda_env(env.executable.parent()?.parent()?) {
+    if is_conda_env_in(file_system, env.executable.parent()?.parent()?) {
         return None;
     }
 
@@ -69,12 +72,14 @@ fn from(env: &PythonEnv) -> Option<PythonEnvironment> {
     // Hence we never end up reporting 3.10 for home brew (as mentioned when you try to resolve the exe it points to existing install, now homebrew).
     let exe = env.executable.clone();
     let exe_file_name = exe.file_name()?;
-    let mut resolved_file = resolve_symlink(&exe).unwrap_or(exe.clone());
+    let mut resolved_file = resolve_symlink_in(file_system, &exe).unwrap_or(exe.clone());
 
     // Possible the resolve exe needs to be resolved once again using canonicalize.
     // Sometimes a symlink points to another symlink, and we need to resolve it to get the real exe.
     // And for some reason even though they are symlinks, they are not resolved by `resolve_symlink`.
-    if let Some(resolved) = resolve_symlink(&exe).or(fs::canonicalize(&exe).ok()) {
+    if let Some(resolved) =
+        resolve_symlink_in(file_system, &exe).or(file_system.canonicalize(&exe).ok())
+    {
         if is_homebrew_python(&resolved) {
             resolved_file = resolved;
         }
@@ -96,6 +101,7 @@ fn from(env: &PythonEnv) -> Option<PythonEnvironment> {
         // Symlink  - /opt/homebrew/Frameworks/Python.framework/Versions/Current/bin/python3.12
         // SysPrefix- /opt/homebrew/opt/python@3.12/Frameworks/Python.framework/Versions/3.12
         get_python_info(
+            file_system,
             &PathBuf::from("/opt/homebrew/bin").join(exe_file_name),
             &resolved_file,
         )
@@ -106,6 +112,7 @@ fn from(env: &PythonEnv) -> Option<PythonEnvironment> {
         // SysPrefix- /home/linuxbrew/.linuxbrew/Cellar/python@3.12/3.12.3
 
         get_python_info(
+            file_system,
             &PathBuf::from("/home/linuxbrew/.linuxbrew/bin").join(exe_file_name),
             &resolved_file,
         )
@@ -116,6 +123,7 @@ fn from(env: &PythonEnv) -> Option<PythonEnvironment> {
         // Real exe - /usr/local/Cellar/python@3.8/3.8.20/Frameworks/Python.framework/Versions/3.8/bin/python3.8
         // SysPrefix- /usr/local/Cellar/python@3.8/3.8.20/Frameworks/Python.framework/Versions/3.8
         get_python_info(
+            file_system,
             &PathBuf::from("/usr/local/bin").join(exe_file_name),
             &resolved_file,
         )
@@ -136,10 +144,13 @@ impl Locator for Homebrew {
     }
 
     fn find(&self, reporter: &dyn Reporter) {
-        let homebrew_prefix_bins = get_homebrew_prefix_bin(&self.environment);
-        thread::scope(|s| {
+        let homebrew_prefix_bins =
+            get_homebrew_prefix_bin(self.file_system.as_ref(), &self.environment);
+        // Runs on the pool of the caller (i.e. the discovery pool), instead of a thread per executable.
+        rayon::scope(|s| {
             for homebrew_prefix_bin in &homebrew_prefix_bins {
-                let homebrew_python_exes = find_executables(homebrew_prefix_bin);
+                let homebrew_python_exes =
+                    find_executables_in(self.file_system.as_ref(), homebrew_prefix_bin);
                 for file in homebrew_python_exes.iter().filter(|f| {
                     let file_name = f
                         .file_name()
@@ -155,12 +166,13 @@ impl Locator for Homebrew {
                         && file_name != "python"
                 }) {
                     let file = file.clone();
-                    s.spawn(move || {
+                    s.spawn(move |_| {
                         // Sometimes we end up with other python installs in the Homebrew bin directory.
                         // E.g. /usr/local/bin is treated as a location where homebrew can be found (homebrew bin)
                         // However this is a very generic location, and we might end up with other python installs here.
                         // Hence call `resolve` to correctly identify homebrew python installs.
-                        let env_to_resolve = PythonEnv::new(file.clone(), None, None);
+                        let env_to_resolve =
+                            PythonEnv::new_in(self.file_system.clone(), file.clone(), None, None);
                         if let Some(env) = from(&env_to_resolve) {
                             reporter.report_environment(&env);
                         }
@@ -174,6 +186,7 @@ impl Locator for Homebrew {
 #[cfg(all(test, unix))]
 mod tests {
     use super::*;
+    use std::fs;
     use tempfile::tempdir;
 
     struct TestEnvironment {
@@ -233,7 +246,7 @@ mod tests {
             homebrew_env.executable,
             Some(PathBuf::from("/home/linuxbrew/.linuxbrew/bin/python3.12"))
         );
-        assert_eq!(homebrew_env.version, Some("3.12.4".to_string()));
+        assert_eq!(homebrew_env.version, Some("3.12.4".parse().unwrap()));
         assert_eq!(homebrew_env.prefix, None);
         assert!(homebrew_env
             .symlinks
@@ -302,7 +315,7 @@ mod tests {
             homebrew_env.executable,
             Some(PathBuf::from("/opt/homebrew/bin/python3.12"))
         );
-        assert_eq!(homebrew_env.version, Some("3.12.3".to_string()));
+        assert_eq!(homebrew_env.version, Some("3.12.3".parse().unwrap()));
     }
 
     #[test]
@@ -325,7 +338,7 @@ mod tests {
             homebrew_env.executable,
             Some(PathBuf::from("/usr/local/bin/python3.8"))
         );
-        assert_eq!(homebrew_env.version, Some("3.8.20".to_string()));
+        assert_eq!(homebrew_env.version, Some("3.8.20".parse().unwrap()));
     }
 
     #[test]
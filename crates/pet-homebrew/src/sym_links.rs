@@ -2,14 +2,12 @@
 // Licensed under the MIT License.
 
 use lazy_static::lazy_static;
-use pet_fs::path::resolve_symlink;
-use pet_python_utils::executable::find_executables;
+use pet_core::python_version::PythonVersion;
+use pet_fs::{file_system::FileSystem, path::resolve_symlink_in};
+use pet_python_utils::executable::find_executables_in;
 use rayon::prelude::*;
 use regex::Regex;
-use std::{
-    fs,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
 lazy_static! {
     static ref PYTHON_VERSION: Regex =
@@ -23,10 +21,12 @@ pub fn is_homebrew_python(exe: &Path) -> bool {
 }
 
 pub fn get_known_symlinks(
+    file_system: &dyn FileSystem,
     symlink_resolved_python_exe: &Path,
-    full_version: &String,
+    full_version: &PythonVersion,
 ) -> Vec<PathBuf> {
-    let mut symlinks = get_known_symlinks_impl(symlink_resolved_python_exe, full_version);
+    let mut symlinks =
+        get_known_symlinks_impl(file_system, symlink_resolved_python_exe, full_version);
 
     // Go through all the exes in all of the above bin directories and verify we have a list of all of them.
     // They too could be symlinks, e.g. we could have `/opt/homebrew/bin/python3` & also `/opt/homebrew/bin/python`
@@ -36,10 +36,10 @@ pub fn get_known_symlinks(
         .par_iter()
         .flat_map(|symlink| {
             if let Some(bin) = symlink.parent() {
-                find_executables(bin)
+                find_executables_in(file_system, bin)
                     .into_iter()
                     .filter(|possible_symlink| {
-                        if let Some(resolved) = resolve_symlink(possible_symlink) {
+                        if let Some(resolved) = resolve_symlink_in(file_system, possible_symlink) {
                             known_symlinks.contains(&resolved)
                         } else {
                             false
@@ -60,8 +60,9 @@ pub fn get_known_symlinks(
 }
 
 pub fn get_known_symlinks_impl(
+    file_system: &dyn FileSystem,
     symlink_resolved_python_exe: &Path,
-    full_version: &String,
+    full_version: &PythonVersion,
 ) -> Vec<PathBuf> {
     if symlink_resolved_python_exe.starts_with("/opt/homebrew") {
         // Real exe - /opt/homebrew/Cellar/python@3.12/3.12.3/Frameworks/Python.framework/Versions/3.12/bin/python3.12
@@ -109,8 +110,8 @@ pub fn get_known_symlinks_impl(
 
                         // Validate the symlinks
                         if symlinks.contains(
-                            &resolve_symlink(&possible_symlink)
-                                .or(fs::canonicalize(&possible_symlink).ok())
+                            &resolve_symlink_in(file_system, &possible_symlink)
+                                .or(file_system.canonicalize(&possible_symlink).ok())
                                 .unwrap_or_default(),
                         ) {
                             symlinks.push(possible_symlink);
@@ -164,8 +165,8 @@ pub fn get_known_symlinks_impl(
 
                         // Validate the symlinks
                         if symlinks.contains(
-                            &resolve_symlink(&possible_symlink)
-                                // .or(fs::canonicalize(&possible_symlink).ok())
+                            &resolve_symlink_in(file_system, &possible_symlink)
+                                // .or(canonicalize(&possible_symlink).ok())
                                 .unwrap_or_default(),
                         ) {
                             symlinks.push(possible_symlink);
@@ -225,8 +226,8 @@ pub fn get_known_symlinks_impl(
                     ] {
                         // Validate the symlinks
                         if symlinks.contains(
-                            &resolve_symlink(&possible_symlink)
-                                .or(fs::canonicalize(&possible_symlink).ok())
+                            &resolve_symlink_in(file_system, &possible_symlink)
+                                .or(file_system.canonicalize(&possible_symlink).ok())
                                 .unwrap_or_default(),
                         ) {
                             symlinks.push(possible_symlink);
@@ -247,6 +248,7 @@ pub fn get_known_symlinks_impl(
 #[cfg(all(test, unix))]
 mod tests {
     use super::*;
+    use pet_fs::file_system::OsFileSystem;
 
     #[test]
     fn homebrew_python_paths_are_recognized_across_supported_prefixes() {
@@ -289,17 +291,20 @@ mod tests {
     fn known_symlink_templates_include_resolved_executable_for_linuxbrew() {
         let resolved_exe =
             PathBuf::from("/home/linuxbrew/.linuxbrew/Cellar/python@3.12/3.12.4/bin/python3.12");
-        let symlinks = get_known_symlinks_impl(&resolved_exe, &"3.12.4".to_string());
+        let symlinks =
+            get_known_symlinks_impl(&OsFileSystem, &resolved_exe, &"3.12.4".parse().unwrap());
 
         assert!(symlinks.contains(&resolved_exe));
     }
 
     #[test]
     fn known_symlink_templates_return_empty_for_unrecognized_paths() {
-        assert!(
-            get_known_symlinks_impl(Path::new("/usr/bin/python3.12"), &"3.12.4".to_string())
-                .is_empty()
-        );
+        assert!(get_known_symlinks_impl(
+            &OsFileSystem,
+            Path::new("/usr/bin/python3.12"),
+            &"3.12.4".parse().unwrap()
+        )
+        .is_empty());
     }
 
     #[test]
@@ -307,7 +312,8 @@ mod tests {
         let resolved_exe = PathBuf::from(
             "/opt/homebrew/Cellar/python@3.12/3.12.3/Frameworks/Python.framework/Versions/3.12/bin/python3.12",
         );
-        let symlinks = get_known_symlinks_impl(&resolved_exe, &"3.12.3".to_string());
+        let symlinks =
+            get_known_symlinks_impl(&OsFileSystem, &resolved_exe, &"3.12.3".parse().unwrap());
 
         assert!(symlinks.contains(&resolved_exe));
         assert!(!symlinks.is_empty());
@@ -318,7 +324,8 @@ mod tests {
         let resolved_exe = PathBuf::from(
             "/usr/local/Cellar/python@3.8/3.8.20/Frameworks/Python.framework/Versions/3.8/bin/python3.8",
         );
-        let symlinks = get_known_symlinks_impl(&resolved_exe, &"3.8.20".to_string());
+        let symlinks =
+            get_known_symlinks_impl(&OsFileSystem, &resolved_exe, &"3.8.20".parse().unwrap());
 
         assert!(symlinks.contains(&resolved_exe));
         assert!(!symlinks.is_empty());
@@ -328,7 +335,8 @@ mod tests {
     fn known_symlink_templates_return_empty_when_version_regex_does_not_match() {
         // Path under /opt/homebrew but without a python@version segment
         let resolved_exe = PathBuf::from("/opt/homebrew/bin/python3.12");
-        let symlinks = get_known_symlinks_impl(&resolved_exe, &"3.12.0".to_string());
+        let symlinks =
+            get_known_symlinks_impl(&OsFileSystem, &resolved_exe, &"3.12.0".parse().unwrap());
 
         // No python@version/ in path, so regex won't capture → returns empty
         assert!(symlinks.is_empty());
@@ -338,7 +346,8 @@ mod tests {
     fn known_symlink_templates_for_linuxbrew_contain_expected_paths() {
         let resolved_exe =
             PathBuf::from("/home/linuxbrew/.linuxbrew/Cellar/python@3.12/3.12.4/bin/python3.12");
-        let symlinks = get_known_symlinks_impl(&resolved_exe, &"3.12.4".to_string());
+        let symlinks =
+            get_known_symlinks_impl(&OsFileSystem, &resolved_exe, &"3.12.4".parse().unwrap());
 
         // The resolved exe itself is always included
         assert!(symlinks.contains(&resolved_exe));
@@ -0,0 +1,182 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Locations & authentication of the daemon (`pet daemon`).
+//!
+//! The daemon listens on a socket (named pipe on Windows) only the current user can connect to,
+//! and clients must send the token of the daemon in `initialize`, which is written to a file only
+//! the current user can read.
+
+use std::{
+    collections::hash_map::RandomState,
+    fs,
+    hash::{BuildHasher, Hasher},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Folder of the socket & token of the daemon, only accessible by the current user.
+///
+/// - Unix: `$XDG_RUNTIME_DIR/pet`, else `<temp>/pet-<uid>`
+/// - Windows: `%LOCALAPPDATA%\pet`, else `<temp>\pet`
+pub fn get_daemon_directory() -> io::Result<PathBuf> {
+    #[cfg(unix)]
+    let dir = match std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir).join("pet"),
+        None => std::env::temp_dir().join(format!("pet-{}", unsafe { libc::getuid() })),
+    };
+    #[cfg(windows)]
+    let dir = std::env::var_os("LOCALAPPDATA")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("pet");
+    create_private_directory(&dir)?;
+    Ok(dir)
+}
+
+/// Creates the folder with access for the current user only.
+/// An existing folder that other users could write to (e.g. created by another user in `/tmp`) is rejected.
+#[cfg(unix)]
+fn create_private_directory(dir: &Path) -> io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+
+    match fs::DirBuilder::new().mode(0o700).create(dir) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+            let metadata = fs::symlink_metadata(dir)?;
+            if !metadata.is_dir()
+                || metadata.uid() != unsafe { libc::getuid() }
+                || metadata.mode() & 0o077 != 0
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("{dir:?} must be a folder only accessible by the current user"),
+                ));
+            }
+            Ok(())
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// The profile of the user is only accessible by that user.
+#[cfg(windows)]
+fn create_private_directory(dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)
+}
+
+/// Default socket of the daemon.
+#[cfg(unix)]
+pub fn get_default_socket_path() -> io::Result<PathBuf> {
+    Ok(get_daemon_directory()?.join("daemon.sock"))
+}
+
+/// Default named pipe of the daemon, one per user.
+#[cfg(windows)]
+pub fn get_default_pipe_name() -> String {
+    let user = std::env::var("USERNAME").unwrap_or_default();
+    format!(r"\\.\pipe\pet-daemon-{user}")
+}
+
+/// Default file the token of the daemon is written to.
+pub fn get_default_token_file() -> io::Result<PathBuf> {
+    Ok(get_daemon_directory()?.join("daemon.token"))
+}
+
+/// Creates a random token (256 bits, hex encoded).
+/// The keys of `RandomState` are seeded from the randomness of the operating system.
+pub fn generate_token() -> String {
+    (0..4u64)
+        .map(|i| {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u64(i);
+            format!("{:016x}", hasher.finish())
+        })
+        .collect()
+}
+
+/// Writes the token to the file, readable by the current user only.
+/// A file left behind by a previous daemon is replaced.
+pub fn write_token_file(path: &Path, token: &str) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(token.as_bytes())
+}
+
+/// Compares the token sent by a client with the token of the daemon, in constant time.
+pub fn is_valid_token(expected: &str, token: &str) -> bool {
+    !expected.is_empty()
+        && expected.len() == token.len()
+        && expected
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_random() {
+        let token = generate_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, generate_token());
+    }
+
+    #[test]
+    fn only_the_daemon_token_is_valid() {
+        let token = generate_token();
+        assert!(is_valid_token(&token, &token));
+        assert!(!is_valid_token(&token, &generate_token()));
+        assert!(!is_valid_token(&token, ""));
+        assert!(!is_valid_token("", ""));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn token_file_is_only_readable_by_the_user() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("daemon.token");
+        fs::write(&file, "stale").unwrap();
+
+        write_token_file(&file, "secret").unwrap();
+
+        assert_eq!(fs::read_to_string(&file).unwrap(), "secret");
+        assert_eq!(
+            fs::metadata(&file).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn folders_other_users_can_access_are_rejected() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let private = dir.path().join("private");
+        create_private_directory(&private).unwrap();
+        create_private_directory(&private).unwrap();
+        assert_eq!(
+            fs::metadata(&private).unwrap().permissions().mode() & 0o777,
+            0o700
+        );
+
+        fs::set_permissions(&private, fs::Permissions::from_mode(0o777)).unwrap();
+        assert_eq!(
+            create_private_directory(&private).unwrap_err().kind(),
+            io::ErrorKind::PermissionDenied
+        );
+    }
+}
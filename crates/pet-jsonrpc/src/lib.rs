@@ -2,9 +2,13 @@
 // Licensed under the MIT License.
 
 use serde::{Deserialize, Serialize};
-use std::io::{self, Write};
+use std::path::PathBuf;
+use transport::Connection;
 
+pub mod daemon;
 pub mod server;
+pub mod transport;
+pub mod watchdog;
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -16,45 +20,176 @@ struct AnyMethodMessage<T> {
 }
 
 pub fn send_message<T: serde::Serialize>(method: &'static str, params: Option<T>) {
-    let payload = AnyMethodMessage {
-        jsonrpc: "2.0".to_string(),
-        method,
-        params,
-    };
-    let message = serde_json::to_string(&payload).unwrap();
-    print!(
-        "Content-Length: {}\r\nContent-Type: application/vscode-jsonrpc; charset=utf-8\r\n\r\n{}",
-        message.len(),
-        message
-    );
-    let _ = io::stdout().flush();
+    Connection::current().send_message(method, params)
 }
 pub fn send_reply<T: serde::Serialize>(id: u32, payload: Option<T>) {
-    let payload = serde_json::json!({
-        "jsonrpc": "2.0",
-        "result": payload,
-        "id": id
-    });
-    let message = serde_json::to_string(&payload).unwrap();
-    print!(
-        "Content-Length: {}\r\nContent-Type: application/vscode-jsonrpc; charset=utf-8\r\n\r\n{}",
-        message.len(),
-        message
-    );
-    let _ = io::stdout().flush();
-}
-
-pub fn send_error(id: Option<u32>, code: i32, message: String) {
-    let payload = serde_json::json!({
+    Connection::current().send_reply(id, payload)
+}
+
+/// Codes of the errors sent in reply to requests.
+/// The protocol errors use the codes defined by JSON-RPC, the rest are in the range reserved for servers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// The payload is not a valid request, e.g. it has no method.
+    InvalidRequest,
+    /// There is no handler for the method of the request.
+    MethodNotFound,
+    /// The params of the request could not be parsed.
+    InvalidParams,
+    /// A handler failed unexpectedly (panicked).
+    InternalError,
+    /// The file or directory in the request does not exist.
+    NotFound,
+    /// The file exists, but is not a Python executable (or environment).
+    NotAPython,
+    /// Python could not be spawned (or did not report its details).
+    SpawnFailed,
+    /// Reserved, not sent yet: the request requires the server to be configured first.
+    ConfigureRequired,
+    /// Reserved, not sent yet: the request was cancelled before it completed.
+    Cancelled,
+}
+
+impl ErrorCode {
+    pub fn code(&self) -> i32 {
+        match self {
+            ErrorCode::InvalidRequest => -32600,
+            ErrorCode::MethodNotFound => -32601,
+            ErrorCode::InvalidParams => -32602,
+            ErrorCode::InternalError => -32603,
+            ErrorCode::NotFound => -32001,
+            ErrorCode::NotAPython => -32002,
+            ErrorCode::SpawnFailed => -32003,
+            ErrorCode::ConfigureRequired => -32004,
+            ErrorCode::Cancelled => -32800,
+        }
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i32(self.code())
+    }
+}
+
+/// Details of an error, sent as the `data` of the error.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorData {
+    /// The file or directory the request failed for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// The underlying cause, such as the parse error or the reason Python could not be spawned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cause: Option<String>,
+}
+
+impl ErrorData {
+    pub fn new(path: Option<PathBuf>, cause: impl ToString) -> Self {
+        ErrorData {
+            path,
+            cause: Some(cause.to_string()),
+        }
+    }
+}
+
+fn create_error_payload(
+    id: Option<u32>,
+    code: ErrorCode,
+    message: String,
+    data: Option<ErrorData>,
+) -> serde_json::Value {
+    let mut error = serde_json::json!({ "code": code, "message": message });
+    if let Some(data) = data {
+        error["data"] = serde_json::to_value(data).unwrap();
+    }
+    serde_json::json!({
         "jsonrpc": "2.0",
-        "error": { "code": code, "message": message },
+        "error": error,
         "id": id
-    });
-    let message = serde_json::to_string(&payload).unwrap();
-    print!(
-        "Content-Length: {}\r\nContent-Type: application/vscode-jsonrpc; charset=utf-8\r\n\r\n{}",
-        message.len(),
-        message
-    );
-    let _ = io::stdout().flush();
+    })
+}
+
+pub fn send_error(id: Option<u32>, code: ErrorCode, message: String, data: Option<ErrorData>) {
+    Connection::current().send_error(id, code, message, data)
+}
+
+impl Connection {
+    pub fn send_message<T: serde::Serialize>(&self, method: &'static str, params: Option<T>) {
+        let payload = AnyMethodMessage {
+            jsonrpc: "2.0".to_string(),
+            method,
+            params,
+        };
+        self.write(&serde_json::to_string(&payload).unwrap());
+    }
+    pub fn send_reply<T: serde::Serialize>(&self, id: u32, payload: Option<T>) {
+        let payload = serde_json::json!({
+            "jsonrpc": "2.0",
+            "result": payload,
+            "id": id
+        });
+        self.write(&serde_json::to_string(&payload).unwrap());
+    }
+    pub fn send_error(
+        &self,
+        id: Option<u32>,
+        code: ErrorCode,
+        message: String,
+        data: Option<ErrorData>,
+    ) {
+        let payload = create_error_payload(id, code, message, data);
+        self.write(&serde_json::to_string(&payload).unwrap());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn error_payload_has_numeric_code_and_structured_data() {
+        let payload = create_error_payload(
+            Some(3),
+            ErrorCode::NotFound,
+            "Executable does not exist".to_string(),
+            Some(ErrorData::new(
+                Some(PathBuf::from("/usr/bin/python3")),
+                "No such file or directory",
+            )),
+        );
+
+        assert_eq!(
+            payload,
+            json!({
+                "jsonrpc": "2.0",
+                "error": {
+                    "code": -32001,
+                    "message": "Executable does not exist",
+                    "data": { "path": "/usr/bin/python3", "cause": "No such file or directory" }
+                },
+                "id": 3
+            })
+        );
+    }
+
+    #[test]
+    fn error_payload_without_data_omits_data() {
+        let payload = create_error_payload(
+            None,
+            ErrorCode::InvalidRequest,
+            "No method".to_string(),
+            None,
+        );
+
+        assert_eq!(
+            payload,
+            json!({
+                "jsonrpc": "2.0",
+                "error": { "code": -32600, "message": "No method" },
+                "id": null
+            })
+        );
+    }
 }
@@ -1,17 +1,21 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use crate::send_error;
+use crate::{
+    send_error,
+    transport::{read_message, with_connection, Connection},
+    ErrorCode, ErrorData,
+};
 use serde_json::{self, Value};
 use std::{
     collections::HashMap,
-    io::{self, Read},
+    io::{self, BufReader, Read},
     sync::Arc,
 };
 
 type RequestHandler<C> = Arc<dyn Fn(Arc<C>, u32, Value)>;
 type NotificationHandler<C> = Arc<dyn Fn(Arc<C>, Value)>;
-type ErrorHandler = Arc<dyn Fn(Option<u32>, i32, String)>;
+type ErrorHandler = Arc<dyn Fn(Option<u32>, ErrorCode, String, Option<ErrorData>)>;
 
 pub struct HandlersKeyedByMethodName<C> {
     context: Arc<C>,
@@ -33,7 +37,7 @@ impl<C> HandlersKeyedByMethodName<C> {
     #[cfg(test)]
     fn new_with_error_handler(
         context: Arc<C>,
-        send_error: impl Fn(Option<u32>, i32, String) + 'static,
+        send_error: impl Fn(Option<u32>, ErrorCode, String, Option<ErrorData>) + 'static,
     ) -> Self {
         HandlersKeyedByMethodName {
             context,
@@ -77,8 +81,9 @@ impl<C> HandlersKeyedByMethodName<C> {
                         eprint!("Failed to find handler for method: {method}");
                         (self.send_error)(
                             Some(id as u32),
-                            -1,
+                            ErrorCode::MethodNotFound,
                             format!("Failed to find handler for request {method}"),
+                            None,
                         );
                     }
                 } else {
@@ -94,8 +99,9 @@ impl<C> HandlersKeyedByMethodName<C> {
                 eprint!("Failed to get method from message: {message}");
                 (self.send_error)(
                     message["id"].as_u64().map(|id| id as u32),
-                    -3,
+                    ErrorCode::InvalidRequest,
                     format!("Failed to extract method from JSONRPC payload {message:?}"),
+                    None,
                 );
             }
         };
@@ -138,6 +144,30 @@ pub fn start_server<C>(handlers: &HandlersKeyedByMethodName<C>) -> ! {
     }
 }
 
+/// Handles the requests of a client connected over a socket, until the client disconnects.
+/// Replies & notifications sent while handling the requests are written to the connection.
+pub fn serve_connection<C>(
+    handlers: &HandlersKeyedByMethodName<C>,
+    reader: impl Read,
+    connection: &Connection,
+) {
+    let mut reader = BufReader::new(reader);
+    loop {
+        match read_message(&mut reader) {
+            Ok(Some(request)) => with_connection(connection, || {
+                if let Err(err) = handle_payload(handlers, &request) {
+                    eprint!("Failed to parse LINE: {request}, {err:?}")
+                }
+            }),
+            Ok(None) => return,
+            Err(err) => {
+                eprint!("Failed to read from {connection:?}, {err:?}");
+                return;
+            }
+        }
+    }
+}
+
 fn handle_payload<C>(
     handlers: &HandlersKeyedByMethodName<C>,
     payload: &str,
@@ -148,7 +178,7 @@ fn handle_payload<C>(
 }
 
 /// Parses the content length from the given line.
-fn get_content_length(line: &str) -> Result<usize, String> {
+pub(crate) fn get_content_length(line: &str) -> Result<usize, String> {
     let line = line.trim();
     if let Some(content_length) = line.find("Content-Length: ") {
         let start = content_length + "Content-Length: ".len();
@@ -178,14 +208,14 @@ mod tests {
     struct TestContext {
         request: Mutex<Option<(u32, Value)>>,
         notification: Mutex<Option<Value>>,
-        errors: Mutex<Vec<(Option<u32>, i32, String)>>,
+        errors: Mutex<Vec<(Option<u32>, ErrorCode, String)>>,
     }
 
     fn create_handlers_with_recorded_errors(
         context: Arc<TestContext>,
     ) -> HandlersKeyedByMethodName<TestContext> {
         let error_context = context.clone();
-        HandlersKeyedByMethodName::new_with_error_handler(context, move |id, code, message| {
+        HandlersKeyedByMethodName::new_with_error_handler(context, move |id, code, message, _| {
             error_context
                 .errors
                 .lock()
@@ -310,7 +340,7 @@ mod tests {
             context.errors.lock().unwrap().as_slice(),
             &[(
                 Some(1),
-                -1,
+                ErrorCode::MethodNotFound,
                 "Failed to find handler for request unknown/request".to_string()
             )]
         );
@@ -333,7 +363,7 @@ mod tests {
             context.errors.lock().unwrap().as_slice(),
             &[(
                 Some(1),
-                -3,
+                ErrorCode::InvalidRequest,
                 format!("Failed to extract method from JSONRPC payload {message:?}")
             )]
         );
@@ -355,7 +385,7 @@ mod tests {
             context.errors.lock().unwrap().as_slice(),
             &[(
                 None,
-                -3,
+                ErrorCode::InvalidRequest,
                 format!("Failed to extract method from JSONRPC payload {message:?}")
             )]
         );
@@ -0,0 +1,523 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use log::error;
+use std::{
+    cell::RefCell,
+    io::{self, BufRead, Read, Write},
+    net::{Ipv4Addr, Shutdown, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+#[cfg(unix)]
+use std::{
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+};
+
+use crate::server::get_content_length;
+
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static CURRENT_CONNECTION: RefCell<Option<Connection>> = const { RefCell::new(None) };
+}
+
+type Writer = Arc<Mutex<Box<dyn Write + Send>>>;
+type Closer = Arc<dyn Fn() + Send + Sync>;
+
+/// The client messages are written to.
+/// Threads without a connection write to stdout, i.e. the client of `pet server`.
+#[derive(Clone)]
+pub struct Connection {
+    id: u64,
+    output: Output,
+}
+
+#[derive(Clone)]
+enum Output {
+    Stdio,
+    Stream { writer: Writer, closer: Closer },
+}
+
+impl std::fmt::Debug for Connection {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.output {
+            Output::Stdio => write!(f, "Connection(stdio)"),
+            Output::Stream { .. } => write!(f, "Connection({})", self.id),
+        }
+    }
+}
+
+impl Connection {
+    pub fn stdio() -> Self {
+        Connection {
+            id: 0,
+            output: Output::Stdio,
+        }
+    }
+
+    /// A connection writing to `writer`, `close` is called to disconnect the client.
+    pub fn new(writer: Box<dyn Write + Send>, close: impl Fn() + Send + Sync + 'static) -> Self {
+        Connection {
+            id: NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed),
+            output: Output::Stream {
+                writer: Arc::new(Mutex::new(writer)),
+                closer: Arc::new(close),
+            },
+        }
+    }
+
+    /// The connection of the current thread, stdio if there is none.
+    pub fn current() -> Self {
+        CURRENT_CONNECTION
+            .with(|connection| connection.borrow().clone())
+            .unwrap_or_else(Connection::stdio)
+    }
+
+    /// Unique id of the connection, 0 for stdio.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Disconnects the client, this is a noop for stdio.
+    pub fn close(&self) {
+        if let Output::Stream { closer, .. } = &self.output {
+            closer();
+        }
+    }
+
+    /// Writes the message with the `Content-Length` header.
+    pub(crate) fn write(&self, message: &str) {
+        let payload = format!(
+            "Content-Length: {}\r\nContent-Type: application/vscode-jsonrpc; charset=utf-8\r\n\r\n{}",
+            message.len(),
+            message
+        );
+        match &self.output {
+            Output::Stdio => {
+                let mut stdout = io::stdout().lock();
+                let _ = stdout.write_all(payload.as_bytes());
+                let _ = stdout.flush();
+            }
+            Output::Stream { writer, .. } => {
+                let mut writer = writer.lock().expect("connection writer mutex poisoned");
+                // The client could have disconnected, there's no one to report this to.
+                let _ = writer.write_all(payload.as_bytes());
+                let _ = writer.flush();
+            }
+        }
+    }
+}
+
+/// Runs `f` with `connection` as the connection of the current thread.
+pub fn with_connection<R>(connection: &Connection, f: impl FnOnce() -> R) -> R {
+    let previous = CURRENT_CONNECTION.with(|current| current.replace(Some(connection.clone())));
+    struct Restore(Option<Connection>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            CURRENT_CONNECTION.with(|current| *current.borrow_mut() = previous);
+        }
+    }
+    let _restore = Restore(previous);
+    f()
+}
+
+/// Same as `thread::spawn`, with the messages of the new thread sent to the connection of the current thread.
+pub fn spawn_with_connection<F, T>(f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let connection = Connection::current();
+    thread::spawn(move || with_connection(&connection, f))
+}
+
+/// Reads the next message (with the `Content-Length` framing), `None` once the stream is closed.
+/// Fails on any line of the headers that is not a `Name: value` header (e.g. the request line of
+/// an HTTP request sent by a browser), as well as on headers without a `Content-Length`.
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let mut content_length = None;
+    let mut has_headers = false;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if line.trim().is_empty() {
+            if has_headers {
+                break;
+            }
+            // Blank lines between messages.
+            continue;
+        }
+        if !is_header(&line) {
+            return Err(invalid(format!("Invalid header {:?}", line.trim_end())));
+        }
+        has_headers = true;
+        if line.starts_with("Content-Length") {
+            content_length = Some(get_content_length(&line).map_err(invalid)?);
+        }
+    }
+    let Some(content_length) = content_length else {
+        return Err(invalid("Missing Content-Length header".to_string()));
+    };
+    let mut buffer = vec![0; content_length];
+    reader.read_exact(&mut buffer)?;
+    Ok(Some(String::from_utf8_lossy(&buffer).to_string()))
+}
+
+/// Whether the line is a `Name: value` header, where the name is made of letters, digits & `-`.
+fn is_header(line: &str) -> bool {
+    line.split_once(": ").is_some_and(|(name, _)| {
+        !name.is_empty() && name.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-')
+    })
+}
+
+/// Listens for clients on a TCP port, a Unix domain socket or a named pipe (Windows).
+pub enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener, PathBuf),
+    #[cfg(windows)]
+    Pipe(pipe::PipeListener),
+}
+
+impl Listener {
+    /// Listens on the loopback interface only, `0` picks a free port.
+    pub fn bind_tcp(port: u16) -> io::Result<Self> {
+        Ok(Listener::Tcp(TcpListener::bind((
+            Ipv4Addr::LOCALHOST,
+            port,
+        ))?))
+    }
+
+    /// Listens on a Unix domain socket, replacing a stale socket left behind by a previous daemon.
+    /// Only the current user can connect to the socket (`0600`).
+    #[cfg(unix)]
+    pub fn bind_unix(path: &Path) -> io::Result<Self> {
+        use std::os::unix::fs::PermissionsExt;
+
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    format!("A daemon is already listening on {path:?}"),
+                ));
+            }
+            std::fs::remove_file(path)?;
+        }
+        // The socket is created with the permissions of the umask, so that no one else can
+        // connect before the permissions are set.
+        let umask = unsafe { libc::umask(0o177) };
+        let listener = UnixListener::bind(path);
+        unsafe { libc::umask(umask) };
+        let listener = Listener::Unix(listener?, path.to_path_buf());
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        Ok(listener)
+    }
+
+    /// Listens on a named pipe that only accepts clients of this machine.
+    /// Fails when another process already created the pipe.
+    #[cfg(windows)]
+    pub fn bind_pipe(name: &str) -> io::Result<Self> {
+        Ok(Listener::Pipe(pipe::PipeListener::bind(name)?))
+    }
+
+    /// The address clients connect to, e.g. `127.0.0.1:1234` or the path of the socket.
+    pub fn address(&self) -> String {
+        match self {
+            Listener::Tcp(listener) => listener
+                .local_addr()
+                .map(|addr| addr.to_string())
+                .unwrap_or_default(),
+            #[cfg(unix)]
+            Listener::Unix(_, path) => path.display().to_string(),
+            #[cfg(windows)]
+            Listener::Pipe(listener) => listener.name().to_string(),
+        }
+    }
+
+    /// Waits for the next client.
+    pub fn accept(&self) -> io::Result<(Box<dyn Read + Send>, Connection)> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, _) = listener.accept()?;
+                let writer = stream.try_clone()?;
+                let closer = stream.try_clone()?;
+                Ok((
+                    Box::new(stream),
+                    Connection::new(Box::new(writer), move || {
+                        let _ = TcpStream::shutdown(&closer, Shutdown::Both);
+                    }),
+                ))
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener, _) => {
+                let (stream, _) = listener.accept()?;
+                let writer = stream.try_clone()?;
+                let closer = stream.try_clone()?;
+                Ok((
+                    Box::new(stream),
+                    Connection::new(Box::new(writer), move || {
+                        let _ = UnixStream::shutdown(&closer, Shutdown::Both);
+                    }),
+                ))
+            }
+            #[cfg(windows)]
+            Listener::Pipe(listener) => {
+                let stream = listener.accept()?;
+                let writer = stream.try_clone()?;
+                let closer = stream.try_clone()?;
+                Ok((
+                    Box::new(stream),
+                    Connection::new(Box::new(writer), move || pipe::disconnect(&closer)),
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Listener::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(windows)]
+mod pipe {
+    use std::{
+        ffi::OsStr,
+        fs::File,
+        io,
+        os::windows::{
+            ffi::OsStrExt,
+            io::{AsRawHandle, FromRawHandle},
+        },
+        sync::Mutex,
+    };
+    use windows_sys::Win32::{
+        Foundation::{GetLastError, ERROR_PIPE_CONNECTED, INVALID_HANDLE_VALUE},
+        Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX},
+        System::Pipes::{
+            ConnectNamedPipe, CreateNamedPipeW, DisconnectNamedPipe, PIPE_READMODE_BYTE,
+            PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+        },
+    };
+
+    const BUFFER_SIZE: u32 = 64 * 1024;
+
+    /// Creates an instance of the pipe for each client.
+    /// The default security descriptor of a pipe only grants write access to the owner,
+    /// administrators & the system.
+    pub struct PipeListener {
+        name: String,
+        wide_name: Vec<u16>,
+        /// Instance created, but not connected to yet.
+        pending: Mutex<Option<File>>,
+    }
+
+    impl PipeListener {
+        pub fn bind(name: &str) -> io::Result<Self> {
+            let wide_name: Vec<u16> = OsStr::new(name).encode_wide().chain([0]).collect();
+            // The first instance is created right away, so that the pipe exists once bound.
+            // Fails when another process owns the pipe.
+            let first = create_instance(&wide_name, FILE_FLAG_FIRST_PIPE_INSTANCE)?;
+            Ok(PipeListener {
+                name: name.to_string(),
+                wide_name,
+                pending: Mutex::new(Some(first)),
+            })
+        }
+
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+
+        /// Waits for a client to connect to the next instance of the pipe.
+        pub fn accept(&self) -> io::Result<File> {
+            let pending = self.pending.lock().expect("pipe mutex poisoned").take();
+            let instance = match pending {
+                Some(instance) => instance,
+                None => create_instance(&self.wide_name, 0)?,
+            };
+            let connected = unsafe {
+                ConnectNamedPipe(instance.as_raw_handle() as _, std::ptr::null_mut()) != 0
+                    || GetLastError() == ERROR_PIPE_CONNECTED
+            };
+            if connected {
+                Ok(instance)
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        }
+    }
+
+    fn create_instance(wide_name: &[u16], flags: u32) -> io::Result<File> {
+        let handle = unsafe {
+            CreateNamedPipeW(
+                wide_name.as_ptr(),
+                PIPE_ACCESS_DUPLEX | flags,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+                PIPE_UNLIMITED_INSTANCES,
+                BUFFER_SIZE,
+                BUFFER_SIZE,
+                0,
+                std::ptr::null(),
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(unsafe { File::from_raw_handle(handle as _) })
+    }
+
+    pub fn disconnect(instance: &File) {
+        unsafe {
+            DisconnectNamedPipe(instance.as_raw_handle() as _);
+        }
+    }
+}
+
+/// Serves the clients of the listener, each on its own thread, until the listener fails.
+/// `serve` is called with the stream & connection of each client, and returns once the client disconnects.
+pub fn start_listener<F>(listener: Listener, serve: F) -> io::Result<()>
+where
+    F: Fn(Box<dyn Read + Send>, Connection) + Send + Sync + 'static,
+{
+    let serve = Arc::new(serve);
+    loop {
+        match listener.accept() {
+            Ok((reader, connection)) => {
+                let serve = serve.clone();
+                thread::spawn(move || serve(reader, connection));
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => {
+                error!(
+                    "Failed to accept a client on {}: {}",
+                    listener.address(),
+                    err
+                );
+                return Err(err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn read_message_reads_framed_messages_until_end_of_stream() {
+        let input = "Content-Length: 2\r\nContent-Type: application/json\r\n\r\n{}\
+            Content-Length: 7\r\n\r\n{\"a\":1}";
+        let mut reader = BufReader::new(input.as_bytes());
+
+        assert_eq!(read_message(&mut reader).unwrap(), Some("{}".to_string()));
+        assert_eq!(
+            read_message(&mut reader).unwrap(),
+            Some("{\"a\":1}".to_string())
+        );
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn read_message_rejects_anything_but_headers() {
+        let http = "POST / HTTP/1.1\r\nContent-Length: 2\r\n\r\n{}";
+        let error = read_message(&mut BufReader::new(http.as_bytes())).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let no_content_length = "Content-Type: application/json\r\n\r\n{}";
+        let error = read_message(&mut BufReader::new(no_content_length.as_bytes())).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn only_the_user_can_connect_to_the_unix_socket() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let socket = dir.path().join("daemon.sock");
+        let _listener = Listener::bind_unix(&socket).unwrap();
+
+        assert_eq!(
+            std::fs::metadata(&socket).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+    }
+
+    #[test]
+    fn messages_are_written_to_the_connection_of_the_thread() {
+        let buffer = SharedBuffer::default();
+        let connection = Connection::new(Box::new(buffer.clone()), || {});
+
+        with_connection(&connection, || {
+            spawn_with_connection(|| crate::send_message("ping", Some(1)))
+                .join()
+                .unwrap();
+        });
+
+        let written = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let mut reader = BufReader::new(written.as_bytes());
+        let message: serde_json::Value =
+            serde_json::from_str(&read_message(&mut reader).unwrap().unwrap()).unwrap();
+        assert_eq!(message["method"], "ping");
+        assert_eq!(message["params"], 1);
+        assert_eq!(Connection::current().id(), 0);
+    }
+
+    #[test]
+    fn clients_of_a_tcp_listener_are_served_concurrently() {
+        let listener = Listener::bind_tcp(0).unwrap();
+        let address = listener.address();
+        thread::spawn(move || {
+            start_listener(listener, |reader, connection| {
+                let mut reader = BufReader::new(reader);
+                while let Ok(Some(message)) = read_message(&mut reader) {
+                    with_connection(&connection, || {
+                        crate::send_message("echo", Some(message));
+                    });
+                }
+            })
+        });
+
+        let clients: Vec<TcpStream> = (0..2)
+            .map(|_| TcpStream::connect(&address).unwrap())
+            .collect();
+        for (index, mut client) in clients.iter().enumerate() {
+            let body = format!("{index}");
+            write!(client, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        }
+        for (index, client) in clients.iter().enumerate() {
+            let mut reader = BufReader::new(client);
+            let message: serde_json::Value =
+                serde_json::from_str(&read_message(&mut reader).unwrap().unwrap()).unwrap();
+            assert_eq!(message["params"], format!("{index}"));
+        }
+    }
+}
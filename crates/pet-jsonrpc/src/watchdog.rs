@@ -0,0 +1,83 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use log::warn;
+use std::{thread, time::Duration};
+
+/// How often the parent process is checked.
+pub const PARENT_PROCESS_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Exits this process once the parent process (generally the editor) is no longer running,
+/// so that the server is not left orphaned when the parent crashes.
+/// `before_exit` is called first, e.g. to wait for the requests in progress.
+pub fn start_parent_process_watchdog<F>(parent_process_id: u32, interval: Duration, before_exit: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    thread::spawn(move || loop {
+        thread::sleep(interval);
+        if !is_process_running(parent_process_id) {
+            warn!(
+                "Parent process {} is no longer running, exiting",
+                parent_process_id
+            );
+            before_exit();
+            std::process::exit(1);
+        }
+    });
+}
+
+#[cfg(unix)]
+pub fn is_process_running(process_id: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(process_id) else {
+        return false;
+    };
+    // Signal 0 only checks whether the process exists.
+    // EPERM means the process exists, but belongs to another user.
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(windows)]
+pub fn is_process_running(process_id: u32) -> bool {
+    use windows_sys::Win32::Foundation::{CloseHandle, WAIT_TIMEOUT};
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, WaitForSingleObject, PROCESS_SYNCHRONIZE,
+    };
+    unsafe {
+        let handle = OpenProcess(PROCESS_SYNCHRONIZE, 0, process_id);
+        if handle.is_null() {
+            return false;
+        }
+        // The handle of a process is signaled once the process exits.
+        let running = WaitForSingleObject(handle, 0) == WAIT_TIMEOUT;
+        CloseHandle(handle);
+        running
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn current_process_is_running() {
+        assert!(is_process_running(std::process::id()));
+    }
+
+    #[test]
+    fn exited_process_is_not_running() {
+        let mut child = if cfg!(windows) {
+            std::process::Command::new("cmd")
+                .args(["/C", "exit"])
+                .spawn()
+                .unwrap()
+        } else {
+            std::process::Command::new("true").spawn().unwrap()
+        };
+        let process_id = child.id();
+        child.wait().unwrap();
+
+        assert!(!is_process_running(process_id));
+    }
+}
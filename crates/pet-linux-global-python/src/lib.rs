@@ -3,32 +3,48 @@
 
 use std::{
     collections::HashSet,
-    fs,
     path::{Path, PathBuf},
     sync::Arc,
-    thread,
 };
 
 use pet_core::{
     arch::Architecture,
     cache::LocatorCache,
     env::PythonEnv,
+    os_environment::Environment,
     python_environment::{PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind},
+    python_version::PythonVersion,
     reporter::Reporter,
     Locator, LocatorKind, RefreshStatePersistence,
 };
-use pet_fs::path::resolve_symlink;
-use pet_python_utils::{env::ResolvedPythonEnv, executable::find_executables};
+use pet_fs::{
+    file_system::{os_file_system, FileSystem},
+    path::resolve_symlink_in,
+};
+use pet_python_utils::arch::get_architecture;
+use pet_python_utils::{
+    env::{is_spawn_allowed, ResolvedPythonEnv},
+    executable::find_executables_in,
+    version::from_sys_prefix_in,
+};
 use pet_virtualenv::is_virtualenv;
 
 pub struct LinuxGlobalPython {
     reported_executables: Arc<LocatorCache<PathBuf, PythonEnvironment>>,
+    file_system: Arc<dyn FileSystem>,
 }
 
 impl LinuxGlobalPython {
     pub fn new() -> LinuxGlobalPython {
         LinuxGlobalPython {
             reported_executables: Arc::new(LocatorCache::new()),
+            file_system: os_file_system(),
+        }
+    }
+    pub fn from(environment: &dyn Environment) -> LinuxGlobalPython {
+        LinuxGlobalPython {
+            reported_executables: Arc::new(LocatorCache::new()),
+            file_system: environment.file_system(),
         }
     }
 
@@ -42,12 +58,21 @@ impl LinuxGlobalPython {
             Path::new("/usr/bin"),
             Path::new("/usr/local/bin"),
         ]
-        .map(|p| fs::canonicalize(p).unwrap_or(p.to_path_buf()))
+        .map(|p| {
+            let p = self.file_system.to_host_path(p);
+            self.file_system.canonicalize(&p).unwrap_or(p)
+        })
         .into();
-        thread::scope(|s| {
+        // Runs on the pool of the caller (i.e. the discovery pool), instead of a thread per directory.
+        rayon::scope(|s| {
             for bin in bin_dirs {
-                s.spawn(move || {
-                    find_and_report_global_pythons_in(&bin, reporter, &self.reported_executables);
+                s.spawn(move |_| {
+                    find_and_report_global_pythons_in(
+                        self.file_system.as_ref(),
+                        &bin,
+                        reporter,
+                        &self.reported_executables,
+                    );
                 });
             }
         });
@@ -82,16 +107,20 @@ impl Locator for LinuxGlobalPython {
 
         // If we do not have a version, then we cannot use this method.
         // Without version means we have not spawned the Python exe, thus do not have the real info.
-        env.version.clone()?;
+        // Unless spawning is disabled, then the details found in the files are the best we have.
+        if is_spawn_allowed(env.file_system.as_ref()) {
+            env.version.clone()?;
+        }
         let executable = env.executable.clone();
 
         // Resolve the canonical path once — used for both the path guard and cache fallback.
-        let canonical = fs::canonicalize(&executable).ok();
+        let canonical = env.file_system.canonicalize(&executable).ok();
 
         // We only support python environments in /bin, /usr/bin, /usr/local/bin.
         // Check both the original and canonical paths so that symlinks from other
         // locations (e.g. /bin → /usr/bin) are still accepted.
         let dominated = |p: &Path| {
+            let p = env.file_system.to_image_path(p);
             p.starts_with("/bin") || p.starts_with("/usr/bin") || p.starts_with("/usr/local/bin")
         };
         if !dominated(&executable) && !canonical.as_ref().is_some_and(|c| dominated(c)) {
@@ -141,41 +170,89 @@ impl Locator for LinuxGlobalPython {
 }
 
 fn find_and_report_global_pythons_in(
+    file_system: &dyn FileSystem,
     bin: &Path,
     reporter: Option<&dyn Reporter>,
     reported_executables: &Arc<LocatorCache<PathBuf, PythonEnvironment>>,
 ) {
-    let python_executables = find_executables(bin);
+    let python_executables = find_executables_in(file_system, bin);
 
     for exe in python_executables.clone().iter() {
         if reported_executables.contains_key(exe) {
             continue;
         }
-        if let Some(resolved) = ResolvedPythonEnv::from(exe) {
-            if let Some(env) = get_python_in_bin(&resolved.to_python_env(), resolved.is64_bit) {
-                resolved.add_to_cache(env.clone());
-
-                // Collect all entries to insert atomically
-                let mut entries = Vec::new();
-                if let Some(symlinks) = &env.symlinks {
-                    for symlink in symlinks {
-                        entries.push((symlink.clone(), env.clone()));
-                    }
-                }
-                if let Some(exe) = env.executable.clone() {
-                    entries.push((exe, env.clone()));
+        let env = if is_spawn_allowed(file_system) {
+            ResolvedPythonEnv::from_in(file_system, exe).and_then(|resolved| {
+                let env =
+                    get_python_in_bin(file_system, &resolved.to_python_env(), resolved.is64_bit)?;
+                resolved.add_to_cache_in(file_system, env.clone());
+                Some(env)
+            })
+        } else {
+            get_python_from_files(file_system, exe)
+        };
+        if let Some(env) = env {
+            // Collect all entries to insert atomically
+            let mut entries = Vec::new();
+            if let Some(symlinks) = &env.symlinks {
+                for symlink in symlinks {
+                    entries.push((symlink.clone(), env.clone()));
                 }
-                reported_executables.insert_many(entries);
+            }
+            if let Some(exe) = env.executable.clone() {
+                entries.push((exe, env.clone()));
+            }
+            reported_executables.insert_many(entries);
 
-                if let Some(reporter) = reporter {
-                    reporter.report_environment(&env);
-                }
+            if let Some(reporter) = reporter {
+                reporter.report_environment(&env);
             }
         }
     }
 }
 
-fn get_python_in_bin(env: &PythonEnv, is_64bit: bool) -> Option<PythonEnvironment> {
+/// Gets the details of a Python without spawning it (e.g. in an alternate root directory),
+/// from the files installed with it such as `/usr/lib/python3.12` or `/usr/include/python3.12/patchlevel.h`.
+fn get_python_from_files(
+    file_system: &dyn FileSystem,
+    executable: &Path,
+) -> Option<PythonEnvironment> {
+    let real_exe = file_system
+        .canonicalize(executable)
+        .unwrap_or(executable.to_path_buf());
+    let prefix = real_exe.parent()?.parent()?;
+    // The major & minor version from the name, e.g. `python3.12`.
+    let name = real_exe.file_name()?.to_string_lossy().to_string();
+    let short_version = name
+        .strip_prefix("python")
+        .and_then(|version| version.split_once('.'))
+        .and_then(|(major, minor)| Some((major.parse().ok()?, minor.parse().ok()?)));
+    let version = from_sys_prefix_in(file_system, prefix, short_version).or_else(|| {
+        let (major, minor) = short_version?;
+        // Only when the standard library is installed, i.e. this is not a stray executable.
+        if file_system.is_dir(&prefix.join("lib").join(&name)) {
+            PythonVersion::parse(&format!("{major}.{minor}"))
+        } else {
+            None
+        }
+    })?;
+    let is_64bit = get_architecture(&real_exe) != Some(Architecture::X86);
+    get_python_in_bin(
+        file_system,
+        &PythonEnv::new(
+            executable.to_path_buf(),
+            Some(prefix.to_path_buf()),
+            Some(version),
+        ),
+        is_64bit,
+    )
+}
+
+fn get_python_in_bin(
+    file_system: &dyn FileSystem,
+    env: &PythonEnv,
+    is_64bit: bool,
+) -> Option<PythonEnvironment> {
     // If we do not have the prefix, then do not try
     // This method will be called with resolved Python where prefix & version is available.
     if env.version.clone().is_none() || env.prefix.clone().is_none() {
@@ -202,7 +279,9 @@ fn get_python_in_bin(env: &PythonEnv, is_64bit: bool) -> Option<PythonEnvironmen
     // let bin = executable.parent()?;
     // We use canonicalize to get the real path of the symlink.
     // Only used in this case, see notes for resolve_symlink.
-    if let Some(symlink) = resolve_symlink(&executable).or(fs::canonicalize(&executable).ok()) {
+    if let Some(symlink) =
+        resolve_symlink_in(file_system, &executable).or(file_system.canonicalize(&executable).ok())
+    {
         // Ensure this is a symlink in the bin or usr/bin directory.
         if symlink.starts_with(bin) {
             symlinks.push(symlink);
@@ -210,7 +289,7 @@ fn get_python_in_bin(env: &PythonEnv, is_64bit: bool) -> Option<PythonEnvironmen
             resolved_exe_is_from_another_dir = Some(symlink);
         }
     }
-    if let Ok(symlink) = fs::canonicalize(&executable) {
+    if let Ok(symlink) = file_system.canonicalize(&executable) {
         // Ensure this is a symlink in the bin or usr/bin directory.
         if symlink.starts_with(bin) {
             symlinks.push(symlink);
@@ -222,9 +301,9 @@ fn get_python_in_bin(env: &PythonEnv, is_64bit: bool) -> Option<PythonEnvironmen
     // Look for other symlinks in the same folder
     // We know that on linux there are sym links in the same folder as the exe.
     // & they all point to one exe and have the same version and same prefix.
-    for possible_symlink in find_executables(bin).iter() {
-        if let Some(ref symlink) =
-            resolve_symlink(&possible_symlink).or(fs::canonicalize(possible_symlink).ok())
+    for possible_symlink in find_executables_in(file_system, bin).iter() {
+        if let Some(ref symlink) = resolve_symlink_in(file_system, &possible_symlink)
+            .or(file_system.canonicalize(possible_symlink).ok())
         {
             // Generally the file /bin/python3 is a symlink to /usr/bin/python3.12
             // Generally the file /bin/python3.12 is a symlink to /usr/bin/python3.12
@@ -250,13 +329,13 @@ fn get_python_in_bin(env: &PythonEnv, is_64bit: bool) -> Option<PythonEnvironmen
 
     Some(
         PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::LinuxGlobal))
-            .executable(Some(executable))
-            .version(env.version.clone())
-            .arch(if is_64bit {
+            .arch(get_architecture(&executable).or(if is_64bit {
                 Some(Architecture::X64)
             } else {
                 Some(Architecture::X86)
-            })
+            }))
+            .executable(Some(executable))
+            .version(env.version.clone())
             .prefix(env.prefix.clone())
             .symlinks(Some(symlinks))
             .build(),
@@ -267,6 +346,7 @@ fn get_python_in_bin(env: &PythonEnv, is_64bit: bool) -> Option<PythonEnvironmen
 mod tests {
     use super::*;
     use pet_core::python_environment::PythonEnvironmentKind;
+    use pet_fs::file_system::OsFileSystem;
     use std::fs;
     use tempfile::tempdir;
 
@@ -285,7 +365,7 @@ mod tests {
     }
 
     fn create_env(executable: PathBuf, prefix: PathBuf) -> PythonEnv {
-        PythonEnv::new(executable, Some(prefix), Some("3.12.1".to_string()))
+        PythonEnv::new(executable, Some(prefix), Some("3.12.1".parse().unwrap()))
     }
 
     #[test]
@@ -293,10 +373,10 @@ mod tests {
         let dir = tempdir().unwrap();
         let executable = dir.path().join(PYTHON_EXE);
         let versionless = PythonEnv::new(executable.clone(), Some(dir.path().to_path_buf()), None);
-        let prefixless = PythonEnv::new(executable, None, Some("3.12.1".to_string()));
+        let prefixless = PythonEnv::new(executable, None, Some("3.12.1".parse().unwrap()));
 
-        assert!(get_python_in_bin(&versionless, true).is_none());
-        assert!(get_python_in_bin(&prefixless, true).is_none());
+        assert!(get_python_in_bin(&OsFileSystem, &versionless, true).is_none());
+        assert!(get_python_in_bin(&OsFileSystem, &prefixless, true).is_none());
     }
 
     #[test]
@@ -308,12 +388,12 @@ mod tests {
         let expected_executable = env.executable.clone();
         let expected_prefix = env.prefix.clone();
 
-        let environment = get_python_in_bin(&env, true).unwrap();
+        let environment = get_python_in_bin(&OsFileSystem, &env, true).unwrap();
 
         assert_eq!(environment.kind, Some(PythonEnvironmentKind::LinuxGlobal));
         assert_eq!(environment.executable, Some(expected_executable.clone()));
         assert_eq!(environment.prefix, expected_prefix);
-        assert_eq!(environment.version, Some("3.12.1".to_string()));
+        assert_eq!(environment.version, Some("3.12.1".parse().unwrap()));
         assert_eq!(environment.arch, Some(Architecture::X64));
         assert!(environment.symlinks.unwrap().contains(&expected_executable));
     }
@@ -325,7 +405,7 @@ mod tests {
         create_executable(&executable);
         let env = create_env(executable, dir.path().to_path_buf());
 
-        let environment = get_python_in_bin(&env, false).unwrap();
+        let environment = get_python_in_bin(&OsFileSystem, &env, false).unwrap();
 
         assert_eq!(environment.arch, Some(Architecture::X86));
     }
@@ -340,7 +420,7 @@ mod tests {
         let mut env = create_env(executable.clone(), dir.path().to_path_buf());
         env.symlinks = Some(vec![known_symlink.clone(), executable.clone()]);
 
-        let environment = get_python_in_bin(&env, true).unwrap();
+        let environment = get_python_in_bin(&OsFileSystem, &env, true).unwrap();
         let symlinks = environment.symlinks.unwrap();
 
         assert_eq!(
@@ -362,7 +442,7 @@ mod tests {
         symlink(&versioned_executable, &executable).unwrap();
         let env = create_env(executable.clone(), dir.path().to_path_buf());
 
-        let environment = get_python_in_bin(&env, true).unwrap();
+        let environment = get_python_in_bin(&OsFileSystem, &env, true).unwrap();
         let symlinks = environment.symlinks.unwrap();
 
         assert!(symlinks.contains(&executable));
@@ -382,7 +462,7 @@ mod tests {
         symlink(&real_executable, &executable).unwrap();
         let env = create_env(executable.clone(), link_dir.path().to_path_buf());
 
-        let environment = get_python_in_bin(&env, true).unwrap();
+        let environment = get_python_in_bin(&OsFileSystem, &env, true).unwrap();
         let symlinks = environment.symlinks.unwrap();
 
         assert!(symlinks.contains(&executable));
@@ -412,7 +492,7 @@ mod tests {
         let env = PythonEnv::new(
             executable,
             Some(dir.path().to_path_buf()),
-            Some("3.12.1".to_string()),
+            Some("3.12.1".parse().unwrap()),
         );
 
         assert!(locator.try_from(&env).is_none());
@@ -432,7 +512,7 @@ mod tests {
         let env = PythonEnv::new(
             executable,
             Some(dir.path().to_path_buf()),
-            Some("3.12.1".to_string()),
+            Some("3.12.1".parse().unwrap()),
         );
 
         assert!(locator.try_from(&env).is_none());
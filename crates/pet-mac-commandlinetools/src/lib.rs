@@ -2,17 +2,20 @@
 // Licensed under the MIT License.
 
 use pet_core::{
-    arch::Architecture,
     env::PythonEnv,
+    os_environment::Environment,
     python_environment::{PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind},
     reporter::Reporter,
     Locator, LocatorKind,
 };
-use pet_fs::path::resolve_symlink;
+use pet_fs::file_system::{os_file_system, FileSystem};
+use pet_fs::path::resolve_symlink_in;
+use pet_python_utils::arch::get_architecture;
 use pet_python_utils::version;
-use pet_python_utils::{env::ResolvedPythonEnv, executable::find_executables};
+use pet_python_utils::{env::ResolvedPythonEnv, executable::find_executables_in};
 use pet_virtualenv::is_virtualenv;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Returns `true` when `name` is `python`, `python3`, or `python3.<minor>`
 /// (where minor is one or more ASCII digits).
@@ -83,11 +86,20 @@ fn is_cmdlinetools_python_path(path_str: &str) -> bool {
     false
 }
 
-pub struct MacCmdLineTools {}
+pub struct MacCmdLineTools {
+    file_system: Arc<dyn FileSystem>,
+}
 
 impl MacCmdLineTools {
     pub fn new() -> MacCmdLineTools {
-        MacCmdLineTools {}
+        MacCmdLineTools {
+            file_system: os_file_system(),
+        }
+    }
+    pub fn from(environment: &dyn Environment) -> MacCmdLineTools {
+        MacCmdLineTools {
+            file_system: environment.file_system(),
+        }
     }
 }
 impl Default for MacCmdLineTools {
@@ -119,6 +131,7 @@ impl Locator for MacCmdLineTools {
             return None;
         }
 
+        let file_system = env.file_system.as_ref();
         let mut version = env.version.clone();
         let mut prefix = env.prefix.clone();
         let mut symlinks = vec![env.executable.clone()];
@@ -132,7 +145,7 @@ impl Locator for MacCmdLineTools {
         // We know that /Library/Developer/CommandLineTools/usr/bin/python3 is actually a symlink to
         // /Library/Developer/CommandLineTools/Library/Frameworks/Python3.framework/Versions/3.9/bin/python3.9
         // Verify this and add that to the list of symlinks as well.
-        if let Some(symlink) = resolve_symlink(&env.executable) {
+        if let Some(symlink) = resolve_symlink_in(file_system, &env.executable) {
             symlinks.push(symlink);
         }
 
@@ -143,16 +156,17 @@ impl Locator for MacCmdLineTools {
             .starts_with("/Library/Developer/CommandLineTools/usr/bin")
         {
             let exe = PathBuf::from("/Library/Developer/CommandLineTools/usr/bin/python3");
-            if let Some(symlink) = resolve_symlink(&exe) {
+            if let Some(symlink) = resolve_symlink_in(file_system, &exe) {
                 if symlinks.contains(&symlink) {
                     symlinks.push(symlink);
 
                     // Rest of the files in this directory are also symlinks to the same exe.
-                    for exe in find_executables(PathBuf::from(
-                        "/Library/Developer/CommandLineTools/usr/bin",
-                    )) {
+                    for exe in find_executables_in(
+                        file_system,
+                        PathBuf::from("/Library/Developer/CommandLineTools/usr/bin"),
+                    ) {
                         if !symlinks.contains(&exe) {
-                            if let Some(symlink) = resolve_symlink(&exe) {
+                            if let Some(symlink) = resolve_symlink_in(file_system, &exe) {
                                 if symlinks.contains(&symlink) {
                                     symlinks.push(exe);
                                 }
@@ -171,19 +185,16 @@ impl Locator for MacCmdLineTools {
         // Hence we must spawn and verify it points to the same Python exe.
         for possible_exes in [PathBuf::from("/usr/bin/python3")] {
             if !symlinks.contains(&possible_exes) {
-                if let Some(resolved_env) = ResolvedPythonEnv::from(&possible_exes) {
+                if let Some(resolved_env) = ResolvedPythonEnv::from_in(file_system, &possible_exes)
+                {
                     if symlinks.contains(&resolved_env.executable) {
                         resolved_environments.push(resolved_env.clone());
 
                         symlinks.push(possible_exes);
                         // Use the latest accurate information we have.
+                        arch = Some(resolved_env.architecture());
                         version = Some(resolved_env.version);
                         prefix = Some(resolved_env.prefix);
-                        arch = if resolved_env.is64_bit {
-                            Some(Architecture::X64)
-                        } else {
-                            Some(Architecture::X86)
-                        };
                     }
                 }
             }
@@ -197,7 +208,7 @@ impl Locator for MacCmdLineTools {
         }) {
             let python3 = real_exe.with_file_name("python3");
             if !symlinks.contains(&python3) {
-                if let Some(symlink) = resolve_symlink(&python3) {
+                if let Some(symlink) = resolve_symlink_in(file_system, &python3) {
                     if symlinks.contains(&symlink) {
                         symlinks.push(python3);
                     }
@@ -209,9 +220,9 @@ impl Locator for MacCmdLineTools {
         symlinks.dedup();
 
         // Find other exes that are symlinks to the same exe in /Library/Developer/CommandLineTools/usr/bin
-        for exe in find_executables("/Library/Developer/CommandLineTools/usr/bin") {
+        for exe in find_executables_in(file_system, "/Library/Developer/CommandLineTools/usr/bin") {
             if !symlinks.contains(&exe) {
-                if let Some(symlink) = resolve_symlink(&exe) {
+                if let Some(symlink) = resolve_symlink_in(file_system, &exe) {
                     if symlinks.contains(&symlink) {
                         symlinks.push(exe);
                     }
@@ -233,20 +244,16 @@ impl Locator for MacCmdLineTools {
 
         if version.is_none() {
             if let Some(prefix) = &prefix {
-                version = version::from_header_files(prefix);
+                version = version::from_header_files_in(file_system, prefix);
             }
         }
 
         if version.is_none() || prefix.is_none() {
-            if let Some(resolved_env) = ResolvedPythonEnv::from(&env.executable) {
+            if let Some(resolved_env) = ResolvedPythonEnv::from_in(file_system, &env.executable) {
                 resolved_environments.push(resolved_env.clone());
+                arch = Some(resolved_env.architecture());
                 version = Some(resolved_env.version);
                 prefix = Some(resolved_env.prefix);
-                arch = if resolved_env.is64_bit {
-                    Some(Architecture::X64)
-                } else {
-                    Some(Architecture::X86)
-                };
             }
         }
 
@@ -254,7 +261,7 @@ impl Locator for MacCmdLineTools {
             .executable(Some(env.executable.clone()))
             .version(version)
             .prefix(prefix)
-            .arch(arch)
+            .arch(arch.or_else(|| get_architecture(&env.executable)))
             .symlinks(Some(symlinks.clone()))
             .build();
 
@@ -262,7 +269,7 @@ impl Locator for MacCmdLineTools {
         // We do this here, to ensure we keep track of the symlinks as well,
         // I.e. if any of the symlinks change, then the cache is invalidated.
         for resolved_env in resolved_environments {
-            resolved_env.add_to_cache(env.clone());
+            resolved_env.add_to_cache_in(file_system, env.clone());
         }
 
         Some(env)
@@ -275,7 +282,8 @@ impl Locator for MacCmdLineTools {
             return;
         }
 
-        for exe in find_executables("/Library/Developer/CommandLineTools/usr")
+        let file_system = self.file_system.as_ref();
+        for exe in find_executables_in(file_system, "/Library/Developer/CommandLineTools/usr")
             .iter()
             .filter(
                 |f|                     // If this file name is `python3`, then ignore this for now.
@@ -286,9 +294,9 @@ impl Locator for MacCmdLineTools {
             )
         {
             // These files should end up being symlinks to something like /Library/Developer/CommandLineTools/Library/Frameworks/Python3.framework/Versions/3.9/bin/python3.9
-            let mut env = PythonEnv::new(exe.to_owned(), None, None);
+            let mut env = PythonEnv::new_in(self.file_system.clone(), exe.to_owned(), None, None);
             let mut symlinks = vec![];
-            if let Some(symlink) = resolve_symlink(exe) {
+            if let Some(symlink) = resolve_symlink_in(file_system, exe) {
                 // Symlinks must exist, they always point to something like the following
                 // /Library/Developer/CommandLineTools/Library/Frameworks/Python3.framework/Versions/3.9/bin/python3.9
                 symlinks.push(symlink);
@@ -297,7 +305,7 @@ impl Locator for MacCmdLineTools {
             // Also check whether the corresponding python and python3 files in this directory point to the same files.
             for python_exe in &["python", "python3"] {
                 let python_exe = exe.with_file_name(python_exe);
-                if let Some(symlink) = resolve_symlink(&python_exe) {
+                if let Some(symlink) = resolve_symlink_in(file_system, &python_exe) {
                     if symlinks.contains(&symlink) {
                         symlinks.push(python_exe);
                     }
@@ -3,22 +3,34 @@
 
 use pet_core::{
     env::PythonEnv,
+    os_environment::Environment,
     python_environment::{PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind},
     reporter::Reporter,
     Locator, LocatorKind,
 };
-use pet_fs::path::resolve_symlink;
-use pet_python_utils::executable::find_executables;
+use pet_fs::file_system::{os_file_system, FileSystem};
+use pet_fs::path::resolve_symlink_in;
+use pet_python_utils::arch::get_architecture;
+use pet_python_utils::executable::find_executables_in;
 use pet_python_utils::version;
 use pet_virtualenv::is_virtualenv;
-use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-pub struct MacPythonOrg {}
+pub struct MacPythonOrg {
+    file_system: Arc<dyn FileSystem>,
+}
 
 impl MacPythonOrg {
     pub fn new() -> MacPythonOrg {
-        MacPythonOrg {}
+        MacPythonOrg {
+            file_system: os_file_system(),
+        }
+    }
+    pub fn from(environment: &dyn Environment) -> MacPythonOrg {
+        MacPythonOrg {
+            file_system: environment.file_system(),
+        }
     }
 }
 impl Default for MacPythonOrg {
@@ -45,7 +57,9 @@ impl Locator for MacPythonOrg {
             return None;
         }
 
-        let mut executable = resolve_symlink(&env.executable).unwrap_or(env.executable.clone());
+        let file_system = env.file_system.as_ref();
+        let mut executable =
+            resolve_symlink_in(file_system, &env.executable).unwrap_or(env.executable.clone());
         if !is_mac_python_org_framework_path(&executable) {
             return None;
         }
@@ -56,7 +70,7 @@ impl Locator for MacPythonOrg {
             // This is a symlink to the python executable, lets resolve it
             let exe_to_resolve =
                 "/Library/Frameworks/Python.framework/Versions/Current/bin/python3";
-            if let Some(exe) = resolve_symlink(&exe_to_resolve) {
+            if let Some(exe) = resolve_symlink_in(file_system, &exe_to_resolve) {
                 if exe.starts_with("/Library/Frameworks/Python.framework/Versions")
                     && !exe.starts_with("/Library/Frameworks/Python.framework/Versions/Current")
                 {
@@ -71,7 +85,7 @@ impl Locator for MacPythonOrg {
             // Check if this is the current version.
             let exe_to_resolve =
                 "/Library/Frameworks/Python.framework/Versions/Current/bin/python3";
-            if let Some(exe) = resolve_symlink(&exe_to_resolve) {
+            if let Some(exe) = resolve_symlink_in(file_system, &exe_to_resolve) {
                 if exe == executable {
                     // Yes, this is the current version
                     version_is_current = true;
@@ -82,14 +96,14 @@ impl Locator for MacPythonOrg {
         }
 
         let prefix = executable.parent()?.parent()?;
-        let version = version::from_header_files(prefix)?;
+        let version = version::from_header_files_in(file_system, prefix)?;
 
         // Also look for other python* files in the same directory as the above executable
-        for exe in find_executables(executable.parent()?) {
+        for exe in find_executables_in(file_system, executable.parent()?) {
             if symlinks.contains(&exe) {
                 continue;
             }
-            if let Some(symlink) = resolve_symlink(&exe) {
+            if let Some(symlink) = resolve_symlink_in(file_system, &exe) {
                 if symlinks.contains(&symlink) {
                     symlinks.push(exe);
                 }
@@ -103,7 +117,7 @@ impl Locator for MacPythonOrg {
             PathBuf::from("/usr/local/bin"),
             PathBuf::from("/Library/Frameworks/Python.framework/Versions/Current/bin"),
         ] {
-            for file in find_executables(&bin) {
+            for file in find_executables_in(file_system, &bin) {
                 // If we're looking in the `Current/bin`, then no need to resolve symlinks
                 // As we already know this is the current version.
                 // Note: We can resolve the symlink for /Library/Frameworks/Python.framework/Versions/Current/bin/python3
@@ -114,7 +128,7 @@ impl Locator for MacPythonOrg {
                     symlinks.push(file);
                     continue;
                 }
-                if let Some(symlink) = resolve_symlink(&file) {
+                if let Some(symlink) = resolve_symlink_in(file_system, &file) {
                     if symlinks.contains(&symlink) {
                         symlinks.push(file);
                     }
@@ -128,6 +142,7 @@ impl Locator for MacPythonOrg {
         Some(
             PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::MacPythonOrg))
                 .executable(Some(executable.clone()))
+                .arch(get_architecture(&executable))
                 .version(Some(version))
                 .prefix(Some(prefix.to_path_buf()))
                 .symlinks(Some(symlinks))
@@ -140,9 +155,11 @@ impl Locator for MacPythonOrg {
             return;
         }
 
-        if let Ok(reader) = fs::read_dir("/Library/Frameworks/Python.framework/Versions/") {
-            for file in reader.filter_map(Result::ok) {
-                let prefix = file.path();
+        if let Ok(reader) = self
+            .file_system
+            .read_dir(Path::new("/Library/Frameworks/Python.framework/Versions/"))
+        {
+            for prefix in reader {
                 // Ignore the `/Library/Frameworks/Python.framework/Versions/Current` folder, as this only contains symlinks to the actual python installations
                 // We will account for the symlinks in these folder later
                 if prefix
@@ -153,10 +170,14 @@ impl Locator for MacPythonOrg {
                 }
 
                 let executable = prefix.join("bin").join("python3");
-                let version = version::from_header_files(&prefix);
-
-                if let Some(env) = self.try_from(&PythonEnv::new(executable, Some(prefix), version))
-                {
+                let version = version::from_header_files_in(self.file_system.as_ref(), &prefix);
+
+                if let Some(env) = self.try_from(&PythonEnv::new_in(
+                    self.file_system.clone(),
+                    executable,
+                    Some(prefix),
+                    version,
+                )) {
                     reporter.report_environment(&env);
                 }
             }
@@ -351,7 +372,7 @@ mod tests {
             Some(PathBuf::from(
                 "/Library/Frameworks/Python.framework/Versions/3.12",
             )),
-            Some("3.12.0".to_string()),
+            Some("3.12.0".parse().unwrap()),
         );
 
         assert!(locator.try_from(&env).is_none());
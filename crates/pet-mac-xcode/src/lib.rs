@@ -2,15 +2,15 @@
 // Licensed under the MIT License.
 
 use pet_core::{
-    arch::Architecture,
     env::PythonEnv,
     python_environment::{PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind},
     reporter::Reporter,
     Locator, LocatorKind,
 };
-use pet_fs::path::resolve_symlink;
+use pet_fs::path::resolve_symlink_in;
+use pet_python_utils::arch::get_architecture;
 use pet_python_utils::version;
-use pet_python_utils::{env::ResolvedPythonEnv, executable::find_executables};
+use pet_python_utils::{env::ResolvedPythonEnv, executable::find_executables_in};
 use pet_virtualenv::is_virtualenv;
 use std::path::PathBuf;
 
@@ -53,6 +53,7 @@ impl Locator for MacXCode {
             return None;
         }
 
+        let file_system = env.file_system.as_ref();
         let mut version = env.version.clone();
         let mut prefix = env.prefix.clone();
         let mut symlinks = vec![env.executable.clone()];
@@ -66,11 +67,11 @@ impl Locator for MacXCode {
         // We know that /Applications/Xcode.app/Contents/Developer/usr/bin/python3 is actually a symlink to
         // /Applications/Xcode.app/Contents/Developer/Library/Frameworks/Python3.framework/Versions/3.9/bin/python3.9
         // Verify this and add that to the list of symlinks as well.
-        if let Some(symlink) = resolve_symlink(&env.executable) {
+        if let Some(symlink) = resolve_symlink_in(file_system, &env.executable) {
             symlinks.push(symlink.clone());
 
             // All exes in the bin directory of the symlink are also symlinks (thats generally of the form /Applications/Xcode.app/Contents/Developer/Library/Frameworks/Python3.framework/Versions/3.9/bin/python3.9)
-            for exe in find_executables(symlink.parent().unwrap()) {
+            for exe in find_executables_in(file_system, symlink.parent().unwrap()) {
                 symlinks.push(exe);
             }
         }
@@ -85,12 +86,12 @@ impl Locator for MacXCode {
             "/Applications/{xcode_folder_name}/Contents/Developer/usr/bin"
         ));
         let exe = bin.join("python3");
-        if let Some(symlink) = resolve_symlink(&exe) {
+        if let Some(symlink) = resolve_symlink_in(file_system, &exe) {
             if symlinks.contains(&symlink) {
                 symlinks.push(exe.clone());
 
                 // All exes in this directory are symlinks
-                for exe in find_executables(bin) {
+                for exe in find_executables_in(file_system, bin) {
                     symlinks.push(exe);
                 }
             }
@@ -104,18 +105,15 @@ impl Locator for MacXCode {
         // Hence we must spawn and verify it points to the same Python exe.
         for possible_exes in [PathBuf::from("/usr/bin/python3")] {
             if !symlinks.contains(&possible_exes) {
-                if let Some(resolved_env) = ResolvedPythonEnv::from(&possible_exes) {
+                if let Some(resolved_env) = ResolvedPythonEnv::from_in(file_system, &possible_exes)
+                {
                     if symlinks.contains(&resolved_env.executable) {
                         resolved_environments.push(resolved_env.clone());
                         symlinks.push(possible_exes);
                         // Use the latest accurate information we have.
+                        arch = Some(resolved_env.architecture());
                         version = Some(resolved_env.version);
                         prefix = Some(resolved_env.prefix);
-                        arch = if resolved_env.is64_bit {
-                            Some(Architecture::X64)
-                        } else {
-                            Some(Architecture::X86)
-                        };
                     }
                 }
             }
@@ -129,7 +127,7 @@ impl Locator for MacXCode {
         }) {
             let python3 = real_exe.with_file_name("python3");
             if !symlinks.contains(&python3) {
-                if let Some(symlink) = resolve_symlink(&python3) {
+                if let Some(symlink) = resolve_symlink_in(file_system, &python3) {
                     if symlinks.contains(&symlink) {
                         symlinks.push(python3);
                     }
@@ -154,19 +152,15 @@ impl Locator for MacXCode {
 
         if version.is_none() {
             if let Some(prefix) = &prefix {
-                version = version::from_header_files(prefix);
+                version = version::from_header_files_in(file_system, prefix);
             }
         }
         if version.is_none() || prefix.is_none() {
-            if let Some(resolved_env) = ResolvedPythonEnv::from(&env.executable) {
+            if let Some(resolved_env) = ResolvedPythonEnv::from_in(file_system, &env.executable) {
                 resolved_environments.push(resolved_env.clone());
+                arch = Some(resolved_env.architecture());
                 version = Some(resolved_env.version);
                 prefix = Some(resolved_env.prefix);
-                arch = if resolved_env.is64_bit {
-                    Some(Architecture::X64)
-                } else {
-                    Some(Architecture::X86)
-                };
             }
         }
 
@@ -174,7 +168,7 @@ impl Locator for MacXCode {
             .executable(Some(env.executable.clone()))
             .version(version)
             .prefix(prefix)
-            .arch(arch)
+            .arch(arch.or_else(|| get_architecture(&env.executable)))
             .symlinks(Some(symlinks))
             .build();
 
@@ -182,7 +176,7 @@ impl Locator for MacXCode {
         // We do this here, to ensure we keep track of the symlinks as well,
         // I.e. if any of the symlinks change, then the cache is invalidated.
         for resolved_env in resolved_environments {
-            resolved_env.add_to_cache(env.clone());
+            resolved_env.add_to_cache_in(file_system, env.clone());
         }
 
         Some(env)
@@ -451,7 +445,7 @@ mod tests {
             Some(PathBuf::from(
                 "/Applications/Xcode.app/Contents/Developer/Library/Frameworks/Python3.framework/Versions/3.9",
             )),
-            Some("3.9.6".to_string()),
+            Some("3.9.6".parse().unwrap()),
         );
 
         assert!(locator.try_from(&env).is_none());
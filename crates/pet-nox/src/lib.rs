@@ -0,0 +1,151 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Environments created by [nox](https://nox.thea.codes) for the sessions of a project,
+//! i.e. the sub directories of the `.nox` directory next to the `noxfile.py` of the project,
+//! e.g. `<project>/.nox/tests-3-12`.
+//!
+//! These are virtual environments (or conda environments when using the conda backend).
+
+use std::path::{Path, PathBuf};
+
+use pet_core::{
+    env::PythonEnv,
+    python_environment::{PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind},
+    pyvenv_cfg::PyVenvCfg,
+    reporter::Reporter,
+    Locator, LocatorKind,
+};
+use pet_fs::file_system::FileSystem;
+use pet_python_utils::arch::get_architecture;
+use pet_python_utils::executable::find_executables_in;
+
+/// Directory (relative to the project) nox creates the session environments in.
+pub const NOX_DIRECTORY: &str = ".nox";
+
+/// Whether nox is configured in the project, i.e. there is a `noxfile.py`.
+pub fn is_nox_project(file_system: &dyn FileSystem, project: &Path) -> bool {
+    file_system.is_file(&project.join("noxfile.py"))
+}
+
+/// The project of an environment in the `.nox` directory of the project.
+fn get_project(file_system: &dyn FileSystem, prefix: &Path) -> Option<PathBuf> {
+    let nox_dir = prefix.parent()?;
+    if nox_dir.file_name()? != NOX_DIRECTORY {
+        return None;
+    }
+    let project = nox_dir.parent()?;
+    is_nox_project(file_system, project).then(|| project.to_path_buf())
+}
+
+fn get_prefix(env: &PythonEnv) -> Option<PathBuf> {
+    if let Some(prefix) = &env.prefix {
+        return Some(prefix.clone());
+    }
+    let parent = env.executable.parent()?;
+    if parent.ends_with("bin") || parent.ends_with("Scripts") {
+        parent.parent().map(Path::to_path_buf)
+    } else {
+        // Conda environments on Windows have the executable in the prefix.
+        Some(parent.to_path_buf())
+    }
+}
+
+pub struct Nox {}
+
+impl Nox {
+    pub fn new() -> Nox {
+        Nox {}
+    }
+}
+impl Default for Nox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Locator for Nox {
+    fn get_kind(&self) -> LocatorKind {
+        LocatorKind::Nox
+    }
+    fn supported_categories(&self) -> Vec<PythonEnvironmentKind> {
+        vec![PythonEnvironmentKind::Nox]
+    }
+
+    fn try_from(&self, env: &PythonEnv) -> Option<PythonEnvironment> {
+        let file_system = env.file_system.as_ref();
+        let prefix = get_prefix(env)?;
+        let project = get_project(file_system, &prefix)?;
+
+        // The (slugified) name of the session, e.g. `tests-3-12` for the session `tests(python='3.12')`.
+        let name = prefix
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string());
+        let version = env
+            .version
+            .clone()
+            .or_else(|| PyVenvCfg::find_in(file_system, &prefix).and_then(|cfg| cfg.version));
+        let symlinks = find_executables_in(file_system, &prefix);
+
+        Some(
+            PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Nox))
+                .name(name)
+                .executable(Some(env.executable.clone()))
+                .arch(get_architecture(&env.executable))
+                .version(version)
+                .prefix(Some(prefix))
+                .project(Some(project))
+                .symlinks(Some(symlinks))
+                .build(),
+        )
+    }
+
+    fn find(&self, _reporter: &dyn Reporter) {
+        // The environments are in the workspace folders, see `find_python_environments_in_workspace_folder_recursive`.
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use pet_fs::file_system::MemoryFileSystem;
+    use std::sync::Arc;
+
+    const PYVENV_CFG: &str = "home = /usr/bin\nversion = 3.12.1\n";
+
+    fn identify(file_system: MemoryFileSystem, executable: &str) -> Option<PythonEnvironment> {
+        let env = PythonEnv::new_in(Arc::new(file_system), executable.into(), None, None);
+        Nox::new().try_from(&env)
+    }
+
+    #[test]
+    fn nox_session_env_is_identified_next_to_the_noxfile() {
+        let file_system = MemoryFileSystem::new()
+            .file("/project/noxfile.py", "import nox\n")
+            .file("/project/.nox/tests-3-12/pyvenv.cfg", PYVENV_CFG)
+            .file("/project/.nox/tests-3-12/bin/python", "");
+
+        let env = identify(file_system, "/project/.nox/tests-3-12/bin/python").unwrap();
+
+        assert_eq!(env.kind, Some(PythonEnvironmentKind::Nox));
+        assert_eq!(env.name, Some("tests-3-12".to_string()));
+        assert_eq!(env.project, Some(PathBuf::from("/project")));
+        assert_eq!(env.prefix, Some(PathBuf::from("/project/.nox/tests-3-12")));
+        assert_eq!(env.version, Some("3.12.1".parse().unwrap()));
+    }
+
+    #[test]
+    fn envs_outside_of_nox_projects_are_not_nox_envs() {
+        let file_system = MemoryFileSystem::new()
+            .file("/project/noxfile.py", "")
+            .file("/project/.venv/pyvenv.cfg", PYVENV_CFG)
+            .file("/project/.venv/bin/python", "");
+        assert!(identify(file_system, "/project/.venv/bin/python").is_none());
+
+        // A `.nox` directory without a `noxfile.py`.
+        let file_system = MemoryFileSystem::new()
+            .file("/project/.nox/tests-3-12/pyvenv.cfg", PYVENV_CFG)
+            .file("/project/.nox/tests-3-12/bin/python", "");
+        assert!(identify(file_system, "/project/.nox/tests-3-12/bin/python").is_none());
+    }
+}
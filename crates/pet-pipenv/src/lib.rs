@@ -11,15 +11,17 @@ use pet_core::LocatorKind;
 use pet_core::{
     python_environment::{PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind},
     reporter::Reporter,
+    search_root::{read_search_root, SearchRootBudget, Unlimited},
     Configuration, Locator, RefreshStatePersistence,
 };
+use pet_fs::file_system::FileSystem;
 use pet_fs::path::norm_case;
-use pet_python_utils::executable::find_executables;
+use pet_python_utils::arch::get_architecture;
+use pet_python_utils::executable::find_executables_in;
 use pet_python_utils::version;
 use regex::Regex;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
-use std::{fs, path::PathBuf};
 
 mod env_variables;
 pub mod manager;
@@ -37,12 +39,15 @@ lazy_static! {
 /// Returns the list of directories where pipenv stores centralized virtual environments.
 /// These are the known locations where pipenv creates virtualenvs when not using in-project mode.
 /// See: https://github.com/pypa/pipenv/blob/main/pipenv/utils/shell.py#L184
-fn get_pipenv_virtualenv_dirs(env_vars: &EnvVariables) -> Vec<PathBuf> {
+fn get_pipenv_virtualenv_dirs(
+    file_system: &dyn FileSystem,
+    env_vars: &EnvVariables,
+) -> Vec<PathBuf> {
     let mut dirs: Vec<PathBuf> = vec![];
 
     // WORKON_HOME can be used by pipenv as well
     if let Some(workon_home) = &env_vars.workon_home {
-        if workon_home.exists() {
+        if file_system.exists(workon_home) {
             trace!("Pipenv: Found WORKON_HOME directory: {:?}", workon_home);
             dirs.push(norm_case(workon_home));
         }
@@ -51,7 +56,7 @@ fn get_pipenv_virtualenv_dirs(env_vars: &EnvVariables) -> Vec<PathBuf> {
     // XDG_DATA_HOME/virtualenvs (common on Linux)
     if let Some(xdg_data_home) = &env_vars.xdg_data_home {
         let xdg_venvs = PathBuf::from(xdg_data_home).join("virtualenvs");
-        if xdg_venvs.exists() {
+        if file_system.exists(&xdg_venvs) {
             trace!("Pipenv: Found XDG_DATA_HOME/virtualenvs: {:?}", xdg_venvs);
             dirs.push(norm_case(xdg_venvs));
         }
@@ -60,7 +65,7 @@ fn get_pipenv_virtualenv_dirs(env_vars: &EnvVariables) -> Vec<PathBuf> {
     if let Some(home) = &env_vars.home {
         // ~/.local/share/virtualenvs - default pipenv location on macOS/Linux
         let local_share_venvs = home.join(".local").join("share").join("virtualenvs");
-        if local_share_venvs.exists() {
+        if file_system.exists(&local_share_venvs) {
             trace!(
                 "Pipenv: Found ~/.local/share/virtualenvs: {:?}",
                 local_share_venvs
@@ -70,14 +75,14 @@ fn get_pipenv_virtualenv_dirs(env_vars: &EnvVariables) -> Vec<PathBuf> {
 
         // ~/.venvs - alternative pipenv location
         let dot_venvs = home.join(".venvs");
-        if dot_venvs.exists() {
+        if file_system.exists(&dot_venvs) {
             trace!("Pipenv: Found ~/.venvs: {:?}", dot_venvs);
             dirs.push(norm_case(dot_venvs));
         }
 
         // ~/.virtualenvs - can also be used by pipenv
         let dot_virtualenvs = home.join(".virtualenvs");
-        if dot_virtualenvs.exists() {
+        if file_system.exists(&dot_virtualenvs) {
             trace!("Pipenv: Found ~/.virtualenvs: {:?}", dot_virtualenvs);
             dirs.push(norm_case(dot_virtualenvs));
         }
@@ -116,13 +121,14 @@ fn is_in_pipenv_centralized_dir(env: &PythonEnv, env_vars: &EnvVariables) -> boo
         }
     };
 
-    let pipenv_dirs = get_pipenv_virtualenv_dirs(env_vars);
+    let file_system = env.file_system.as_ref();
+    let pipenv_dirs = get_pipenv_virtualenv_dirs(file_system, env_vars);
     for pipenv_dir in &pipenv_dirs {
         if let Some(parent) = prefix.parent() {
             if norm_case(parent) == *pipenv_dir {
                 // Primary check: .project file (pipenv always creates this for centralized envs)
                 let project_file = prefix.join(".project");
-                if project_file.exists() {
+                if file_system.exists(&project_file) {
                     trace!(
                         "Pipenv: Detected centralized pipenv env at {:?} (in {:?}, has .project file)",
                         prefix,
@@ -163,8 +169,9 @@ fn is_in_pipenv_centralized_dir(env: &PythonEnv, env_vars: &EnvVariables) -> boo
 }
 
 fn get_pipenv_project(env: &PythonEnv) -> Option<PathBuf> {
+    let file_system = env.file_system.as_ref();
     if let Some(prefix) = &env.prefix {
-        if let Some(project) = get_pipenv_project_from_prefix(prefix) {
+        if let Some(project) = get_pipenv_project_from_prefix(file_system, prefix) {
             return Some(project);
         }
         // If there's no .project file, but the venv lives inside the project folder
@@ -172,7 +179,7 @@ fn get_pipenv_project(env: &PythonEnv) -> Option<PathBuf> {
         // directory of the venv. Detect that by checking for a Pipfile next to the venv.
         if let Some(parent) = prefix.parent() {
             let project_folder = parent;
-            if project_folder.join("Pipfile").exists() {
+            if file_system.exists(&project_folder.join("Pipfile")) {
                 return Some(project_folder.to_path_buf());
             }
         }
@@ -193,7 +200,7 @@ fn get_pipenv_project(env: &PythonEnv) -> Option<PathBuf> {
         };
         if let Some(venv_dir) = venv_dir {
             if let Some(project_dir) = venv_dir.parent() {
-                if project_dir.join("Pipfile").exists() {
+                if file_system.exists(&project_dir.join("Pipfile")) {
                     return Some(project_dir.to_path_buf());
                 }
             }
@@ -205,18 +212,18 @@ fn get_pipenv_project(env: &PythonEnv) -> Option<PathBuf> {
     if bin.file_name().unwrap_or_default() == Path::new("bin")
         || bin.file_name().unwrap_or_default() == Path::new("Scripts")
     {
-        get_pipenv_project_from_prefix(env.executable.parent()?.parent()?)
+        get_pipenv_project_from_prefix(file_system, env.executable.parent()?.parent()?)
     } else {
-        get_pipenv_project_from_prefix(env.executable.parent()?)
+        get_pipenv_project_from_prefix(file_system, env.executable.parent()?)
     }
 }
 
-fn get_pipenv_project_from_prefix(prefix: &Path) -> Option<PathBuf> {
+fn get_pipenv_project_from_prefix(file_system: &dyn FileSystem, prefix: &Path) -> Option<PathBuf> {
     let project_file = prefix.join(".project");
-    if !project_file.exists() {
+    if !file_system.exists(&project_file) {
         return None;
     }
-    let contents = fs::read_to_string(project_file).ok()?;
+    let contents = file_system.read_to_string(&project_file).ok()?;
     let project_folder = norm_case(PathBuf::from(contents.trim().to_string()));
     // Return the project folder path even if it doesn't exist.
     // This allows us to identify pipenv environments in centralized directories
@@ -225,10 +232,11 @@ fn get_pipenv_project_from_prefix(prefix: &Path) -> Option<PathBuf> {
 }
 
 fn is_pipenv_from_project(env: &PythonEnv) -> bool {
+    let file_system = env.file_system.as_ref();
     // If the env prefix is inside a project folder, check that folder for a Pipfile.
     if let Some(prefix) = &env.prefix {
         if let Some(project_dir) = prefix.parent() {
-            if project_dir.join("Pipfile").exists() {
+            if file_system.exists(&project_dir.join("Pipfile")) {
                 return true;
             }
         }
@@ -244,7 +252,7 @@ fn is_pipenv_from_project(env: &PythonEnv) -> bool {
         };
         if let Some(venv_dir) = venv_dir {
             if let Some(project_dir) = venv_dir.parent() {
-                if project_dir.join("Pipfile").exists() {
+                if file_system.exists(&project_dir.join("Pipfile")) {
                     return true;
                 }
             }
@@ -273,7 +281,7 @@ fn is_pipenv(env: &PythonEnv, env_vars: &EnvVariables) -> bool {
     // Check if there's a .project file pointing to a project with a Pipfile
     if let Some(project_path) = get_pipenv_project(env) {
         let pipfile_path = project_path.join(env_vars.pipenv_pipfile.clone());
-        if pipfile_path.exists() {
+        if env.file_system.exists(&pipfile_path) {
             trace!(
                 "Pipenv: {:?} identified via .project file pointing to project with Pipfile at {:?}",
                 env.executable,
@@ -306,10 +314,10 @@ fn is_pipenv(env: &PythonEnv, env_vars: &EnvVariables) -> bool {
 /// - If WORKON_HOME is set, use that
 /// - Linux/macOS: ~/.local/share/virtualenvs/
 /// - Windows: %USERPROFILE%\.virtualenvs\
-fn get_virtualenvs_dir(env_vars: &EnvVariables) -> Option<PathBuf> {
+fn get_virtualenvs_dir(file_system: &dyn FileSystem, env_vars: &EnvVariables) -> Option<PathBuf> {
     // First check WORKON_HOME environment variable
     if let Some(workon_home) = &env_vars.workon_home {
-        if workon_home.is_dir() {
+        if file_system.is_dir(workon_home) {
             return Some(workon_home.clone());
         }
     }
@@ -318,12 +326,12 @@ fn get_virtualenvs_dir(env_vars: &EnvVariables) -> Option<PathBuf> {
     if let Some(home) = &env_vars.home {
         if std::env::consts::OS == "windows" {
             let dir = home.join(".virtualenvs");
-            if dir.is_dir() {
+            if file_system.is_dir(&dir) {
                 return Some(dir);
             }
         } else {
             let dir = home.join(".local").join("share").join("virtualenvs");
-            if dir.is_dir() {
+            if file_system.is_dir(&dir) {
                 return Some(dir);
             }
         }
@@ -332,65 +340,62 @@ fn get_virtualenvs_dir(env_vars: &EnvVariables) -> Option<PathBuf> {
     None
 }
 
-/// Discover pipenv environments from the virtualenvs directory
-fn list_environments(env_vars: &EnvVariables) -> Vec<PythonEnvironment> {
+/// Discover pipenv environments from the virtualenvs directory, looking at (at most) the budget of directories
+fn list_environments(
+    file_system: &dyn FileSystem,
+    env_vars: &EnvVariables,
+    budget: &dyn SearchRootBudget,
+) -> Vec<PythonEnvironment> {
     let mut environments = vec![];
 
-    if let Some(virtualenvs_dir) = get_virtualenvs_dir(env_vars) {
+    if let Some(virtualenvs_dir) = get_virtualenvs_dir(file_system, env_vars) {
         trace!("Searching for pipenv environments in {:?}", virtualenvs_dir);
 
-        if let Ok(entries) = fs::read_dir(&virtualenvs_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if !path.is_dir() {
-                    continue;
-                }
+        for path in read_search_root(file_system, budget, &virtualenvs_dir) {
+            // Check if this directory is a valid virtualenv with a .project file
+            let project_file = path.join(".project");
+            if !file_system.exists(&project_file) {
+                continue;
+            }
 
-                // Check if this directory is a valid virtualenv with a .project file
-                let project_file = path.join(".project");
-                if !project_file.exists() {
+            // Read the project path from .project file
+            if let Ok(project_contents) = file_system.read_to_string(&project_file) {
+                let project_path = PathBuf::from(project_contents.trim());
+                let project_path = norm_case(project_path);
+
+                // Check if the project has a Pipfile
+                if !file_system.exists(&project_path.join(&env_vars.pipenv_pipfile)) {
                     continue;
                 }
 
-                // Read the project path from .project file
-                if let Ok(project_contents) = fs::read_to_string(&project_file) {
-                    let project_path = PathBuf::from(project_contents.trim());
-                    let project_path = norm_case(project_path);
-
-                    // Check if the project has a Pipfile
-                    if !project_path.join(&env_vars.pipenv_pipfile).exists() {
-                        continue;
-                    }
-
-                    // Find the Python executable in the virtualenv
-                    let bin_dir = if std::env::consts::OS == "windows" {
-                        path.join("Scripts")
-                    } else {
-                        path.join("bin")
-                    };
+                // Find the Python executable in the virtualenv
+                let bin_dir = if std::env::consts::OS == "windows" {
+                    path.join("Scripts")
+                } else {
+                    path.join("bin")
+                };
 
-                    let python_exe = if std::env::consts::OS == "windows" {
-                        bin_dir.join("python.exe")
-                    } else {
-                        bin_dir.join("python")
-                    };
-
-                    if python_exe.is_file() {
-                        let symlinks = find_executables(&bin_dir);
-                        let version = version::from_creator_for_virtual_env(&path);
-
-                        let env =
-                            PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Pipenv))
-                                .executable(Some(norm_case(python_exe)))
-                                .version(version)
-                                .prefix(Some(norm_case(path.clone())))
-                                .project(Some(project_path))
-                                .symlinks(Some(symlinks))
-                                .build();
-
-                        trace!("Found pipenv environment: {:?}", env);
-                        environments.push(env);
-                    }
+                let python_exe = if std::env::consts::OS == "windows" {
+                    bin_dir.join("python.exe")
+                } else {
+                    bin_dir.join("python")
+                };
+
+                if file_system.is_file(&python_exe) {
+                    let symlinks = find_executables_in(file_system, &bin_dir);
+                    let version = version::from_creator_for_virtual_env(&path);
+
+                    let env = PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Pipenv))
+                        .arch(get_architecture(&python_exe))
+                        .executable(Some(norm_case(python_exe)))
+                        .version(version)
+                        .prefix(Some(norm_case(path.clone())))
+                        .project(Some(project_path))
+                        .symlinks(Some(symlinks))
+                        .build();
+
+                    trace!("Found pipenv environment: {:?}", env);
+                    environments.push(env);
                 }
             }
         }
@@ -402,6 +407,7 @@ fn list_environments(env_vars: &EnvVariables) -> Vec<PythonEnvironment> {
 pub struct PipEnv {
     env_vars: EnvVariables,
     pipenv_executable: Arc<RwLock<Option<PathBuf>>>,
+    file_system: Arc<dyn FileSystem>,
 }
 
 impl PipEnv {
@@ -409,6 +415,7 @@ impl PipEnv {
         PipEnv {
             env_vars: EnvVariables::from(environment),
             pipenv_executable: Arc::new(RwLock::new(None)),
+            file_system: environment.file_system(),
         }
     }
 }
@@ -458,7 +465,7 @@ impl Locator for PipEnv {
             }
         }
         let bin = env.executable.parent()?;
-        let symlinks = find_executables(bin);
+        let symlinks = find_executables_in(env.file_system.as_ref(), bin);
         let mut version = env.version.clone();
         if version.is_none() && prefix.is_some() {
             if let Some(prefix) = &prefix {
@@ -468,6 +475,7 @@ impl Locator for PipEnv {
         Some(
             PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Pipenv))
                 .executable(Some(env.executable.clone()))
+                .arch(get_architecture(&env.executable))
                 .version(version)
                 .prefix(prefix)
                 .project(project_path)
@@ -477,15 +485,20 @@ impl Locator for PipEnv {
     }
 
     fn find(&self, reporter: &dyn Reporter) {
+        self.find_with_budget(reporter, &Unlimited);
+    }
+    fn find_with_budget(&self, reporter: &dyn Reporter, budget: &dyn SearchRootBudget) {
         // First, find and report the pipenv manager
         let pipenv_exe = self.pipenv_executable.read().unwrap().clone();
-        if let Some(manager) = PipenvManager::find(pipenv_exe, &self.env_vars) {
+        if let Some(manager) =
+            PipenvManager::find_in(self.file_system.as_ref(), pipenv_exe, &self.env_vars)
+        {
             trace!("Found pipenv manager: {:?}", manager);
             reporter.report_manager(&manager.to_manager());
         }
 
         // Then discover and report pipenv environments
-        let environments = list_environments(&self.env_vars);
+        let environments = list_environments(self.file_system.as_ref(), &self.env_vars, budget);
         for env in environments {
             reporter.report_environment(&env);
         }
@@ -495,6 +508,7 @@ impl Locator for PipEnv {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pet_fs::file_system::{os_file_system, OsFileSystem};
     use std::sync::atomic::{AtomicU64, Ordering};
 
     static TEST_DIR_COUNTER: AtomicU64 = AtomicU64::new(0);
@@ -542,12 +556,11 @@ mod tests {
         std::fs::write(venv_dir.join("pyvenv.cfg"), b"version = 3.12.0\n").unwrap();
 
         // Construct PythonEnv directly
-        let env = PythonEnv {
-            executable: norm_case(python_exe.clone()),
-            prefix: Some(norm_case(venv_dir.clone())),
-            version: None,
-            symlinks: None,
-        };
+        let env = PythonEnv::new(
+            norm_case(python_exe.clone()),
+            Some(norm_case(venv_dir.clone())),
+            None,
+        );
 
         // Validate helper infers project
         let inferred = get_pipenv_project(&env).expect("expected project path");
@@ -557,6 +570,7 @@ mod tests {
         let locator = PipEnv {
             env_vars: create_test_env_vars(None),
             pipenv_executable: Arc::new(RwLock::new(None)),
+            file_system: os_file_system(),
         };
         let result = locator
             .try_from(&env)
@@ -601,12 +615,11 @@ mod tests {
         .unwrap();
 
         // Construct PythonEnv
-        let env = PythonEnv {
-            executable: norm_case(python_exe.clone()),
-            prefix: Some(norm_case(venv_dir.clone())),
-            version: None,
-            symlinks: None,
-        };
+        let env = PythonEnv::new(
+            norm_case(python_exe.clone()),
+            Some(norm_case(venv_dir.clone())),
+            None,
+        );
 
         // Create env_vars with home pointing to our temp directory
         let env_vars = EnvVariables {
@@ -634,6 +647,7 @@ mod tests {
         let locator = PipEnv {
             env_vars,
             pipenv_executable: Arc::new(RwLock::new(None)),
+            file_system: os_file_system(),
         };
         let result = locator
             .try_from(&env)
@@ -671,12 +685,11 @@ mod tests {
         std::fs::write(venv_dir.join(".project"), "/path/to/deleted/project").unwrap();
 
         // Construct PythonEnv
-        let env = PythonEnv {
-            executable: norm_case(python_exe.clone()),
-            prefix: Some(norm_case(venv_dir.clone())),
-            version: None,
-            symlinks: None,
-        };
+        let env = PythonEnv::new(
+            norm_case(python_exe.clone()),
+            Some(norm_case(venv_dir.clone())),
+            None,
+        );
 
         let env_vars = EnvVariables {
             pipenv_max_depth: 3,
@@ -701,6 +714,7 @@ mod tests {
         let locator = PipEnv {
             env_vars,
             pipenv_executable: Arc::new(RwLock::new(None)),
+            file_system: os_file_system(),
         };
         let result = locator
             .try_from(&env)
@@ -737,12 +751,11 @@ mod tests {
         // Explicitly NOT creating .project file
 
         // Construct PythonEnv
-        let env = PythonEnv {
-            executable: norm_case(python_exe.clone()),
-            prefix: Some(norm_case(venv_dir.clone())),
-            version: None,
-            symlinks: None,
-        };
+        let env = PythonEnv::new(
+            norm_case(python_exe.clone()),
+            Some(norm_case(venv_dir.clone())),
+            None,
+        );
 
         let env_vars = EnvVariables {
             pipenv_max_depth: 3,
@@ -767,6 +780,7 @@ mod tests {
         let locator = PipEnv {
             env_vars,
             pipenv_executable: Arc::new(RwLock::new(None)),
+            file_system: os_file_system(),
         };
         let result = locator
             .try_from(&env)
@@ -815,7 +829,7 @@ mod tests {
             path: None,
         };
 
-        let dirs = get_pipenv_virtualenv_dirs(&env_vars);
+        let dirs = get_pipenv_virtualenv_dirs(&OsFileSystem, &env_vars);
         assert!(
             dirs.contains(&norm_case(&workon_home)),
             "Expected WORKON_HOME in dirs: {:?}",
@@ -841,7 +855,7 @@ mod tests {
             path: None,
         };
 
-        let dirs = get_pipenv_virtualenv_dirs(&env_vars);
+        let dirs = get_pipenv_virtualenv_dirs(&OsFileSystem, &env_vars);
         assert!(
             dirs.contains(&norm_case(&xdg_venvs)),
             "Expected XDG_DATA_HOME/virtualenvs in dirs: {:?}",
@@ -859,7 +873,7 @@ mod tests {
 
         let env_vars = create_test_env_vars(Some(temp.clone()));
 
-        let dirs = get_pipenv_virtualenv_dirs(&env_vars);
+        let dirs = get_pipenv_virtualenv_dirs(&OsFileSystem, &env_vars);
         assert!(
             dirs.contains(&norm_case(&local_share_venvs)),
             "Expected ~/.local/share/virtualenvs in dirs: {:?}",
@@ -877,7 +891,7 @@ mod tests {
 
         let env_vars = create_test_env_vars(Some(temp.clone()));
 
-        let dirs = get_pipenv_virtualenv_dirs(&env_vars);
+        let dirs = get_pipenv_virtualenv_dirs(&OsFileSystem, &env_vars);
         assert!(
             dirs.contains(&norm_case(&dot_venvs)),
             "Expected ~/.venvs in dirs: {:?}",
@@ -895,7 +909,7 @@ mod tests {
 
         let env_vars = create_test_env_vars(Some(temp.clone()));
 
-        let dirs = get_pipenv_virtualenv_dirs(&env_vars);
+        let dirs = get_pipenv_virtualenv_dirs(&OsFileSystem, &env_vars);
         assert!(
             dirs.contains(&norm_case(&dot_virtualenvs)),
             "Expected ~/.virtualenvs in dirs: {:?}",
@@ -913,7 +927,7 @@ mod tests {
 
         let env_vars = create_test_env_vars(Some(temp.clone()));
 
-        let dirs = get_pipenv_virtualenv_dirs(&env_vars);
+        let dirs = get_pipenv_virtualenv_dirs(&OsFileSystem, &env_vars);
         assert!(
             dirs.is_empty(),
             "Expected empty dirs when nothing exists: {:?}",
@@ -937,7 +951,7 @@ mod tests {
             path: None,
         };
 
-        let dirs = get_pipenv_virtualenv_dirs(&env_vars);
+        let dirs = get_pipenv_virtualenv_dirs(&OsFileSystem, &env_vars);
         assert!(
             !dirs.contains(&norm_case(temp.join("does_not_exist"))),
             "Non-existent WORKON_HOME should not be in dirs"
@@ -969,7 +983,7 @@ mod tests {
             path: None,
         };
 
-        let result = get_virtualenvs_dir(&env_vars);
+        let result = get_virtualenvs_dir(&OsFileSystem, &env_vars);
         assert_eq!(result, Some(workon_home));
 
         std::fs::remove_dir_all(&temp).ok();
@@ -987,7 +1001,7 @@ mod tests {
 
         let env_vars = create_test_env_vars(Some(temp.clone()));
 
-        let result = get_virtualenvs_dir(&env_vars);
+        let result = get_virtualenvs_dir(&OsFileSystem, &env_vars);
         assert_eq!(result, Some(expected));
 
         std::fs::remove_dir_all(&temp).ok();
@@ -1000,12 +1014,50 @@ mod tests {
 
         let env_vars = create_test_env_vars(Some(temp.clone()));
 
-        let result = get_virtualenvs_dir(&env_vars);
+        let result = get_virtualenvs_dir(&OsFileSystem, &env_vars);
         assert_eq!(result, None);
 
         std::fs::remove_dir_all(&temp).ok();
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn list_environments_looks_at_the_files_in_the_file_system() {
+        let file_system = pet_fs::file_system::MemoryFileSystem::new()
+            .file(
+                "/home/user/.local/share/virtualenvs/app-AbCd1234/bin/python",
+                "",
+            )
+            .file(
+                "/home/user/.local/share/virtualenvs/app-AbCd1234/.project",
+                "/work/app\n",
+            )
+            .file(
+                "/home/user/.local/share/virtualenvs/gone-AbCd1234/bin/python",
+                "",
+            )
+            .file(
+                "/home/user/.local/share/virtualenvs/gone-AbCd1234/.project",
+                "/work/gone\n",
+            )
+            .file("/work/app/Pipfile", "");
+
+        let environments = list_environments(
+            &file_system,
+            &create_test_env_vars(Some(PathBuf::from("/home/user"))),
+            &Unlimited,
+        );
+
+        assert_eq!(environments.len(), 1);
+        assert_eq!(
+            environments[0].prefix,
+            Some(PathBuf::from(
+                "/home/user/.local/share/virtualenvs/app-AbCd1234"
+            ))
+        );
+        assert_eq!(environments[0].project, Some(PathBuf::from("/work/app")));
+    }
+
     // ── is_pipenv_from_project ────────────────────────────────────
 
     #[test]
@@ -1027,12 +1079,7 @@ mod tests {
         std::fs::write(&python_exe, b"").unwrap();
         std::fs::write(project_dir.join("Pipfile"), b"[[source]]\n").unwrap();
 
-        let env = PythonEnv {
-            executable: norm_case(&python_exe),
-            prefix: Some(norm_case(&venv_dir)),
-            version: None,
-            symlinks: None,
-        };
+        let env = PythonEnv::new(norm_case(&python_exe), Some(norm_case(&venv_dir)), None);
 
         assert!(is_pipenv_from_project(&env));
 
@@ -1058,12 +1105,7 @@ mod tests {
         std::fs::write(&python_exe, b"").unwrap();
         std::fs::write(project_dir.join("Pipfile"), b"[[source]]\n").unwrap();
 
-        let env = PythonEnv {
-            executable: norm_case(&python_exe),
-            prefix: None,
-            version: None,
-            symlinks: None,
-        };
+        let env = PythonEnv::new(norm_case(&python_exe), None, None);
 
         assert!(is_pipenv_from_project(&env));
 
@@ -1089,12 +1131,7 @@ mod tests {
         std::fs::write(&python_exe, b"").unwrap();
         // No Pipfile created
 
-        let env = PythonEnv {
-            executable: norm_case(&python_exe),
-            prefix: Some(norm_case(&venv_dir)),
-            version: None,
-            symlinks: None,
-        };
+        let env = PythonEnv::new(norm_case(&python_exe), Some(norm_case(&venv_dir)), None);
 
         assert!(!is_pipenv_from_project(&env));
 
@@ -1122,12 +1159,7 @@ mod tests {
         std::fs::write(&python_exe, b"").unwrap();
         // No Pipfile, no .project, not in centralized dir
 
-        let env = PythonEnv {
-            executable: norm_case(&python_exe),
-            prefix: Some(norm_case(&venv_dir)),
-            version: None,
-            symlinks: None,
-        };
+        let env = PythonEnv::new(norm_case(&python_exe), Some(norm_case(&venv_dir)), None);
 
         let env_vars = create_test_env_vars(Some(temp.clone()));
         assert!(!is_pipenv(&env, &env_vars));
@@ -1155,12 +1187,7 @@ mod tests {
         } else {
             venv_dir.join("bin").join("python")
         };
-        let env = PythonEnv {
-            executable: python_exe,
-            prefix: Some(venv_dir.clone()),
-            version: None,
-            symlinks: None,
-        };
+        let env = PythonEnv::new(python_exe, Some(venv_dir.clone()), None);
 
         let result = get_pipenv_project(&env);
         assert_eq!(result, Some(norm_case(project_path)));
@@ -1179,12 +1206,7 @@ mod tests {
         };
         std::fs::create_dir_all(&bin_dir).unwrap();
 
-        let env = PythonEnv {
-            executable: bin_dir.join("python"),
-            prefix: Some(venv_dir.clone()),
-            version: None,
-            symlinks: None,
-        };
+        let env = PythonEnv::new(bin_dir.join("python"), Some(venv_dir.clone()), None);
 
         let result = get_pipenv_project(&env);
         assert_eq!(result, None);
@@ -1199,6 +1221,7 @@ mod tests {
         let locator = PipEnv {
             env_vars: create_test_env_vars(None),
             pipenv_executable: Arc::new(RwLock::new(None)),
+            file_system: os_file_system(),
         };
         assert_eq!(locator.get_kind(), LocatorKind::PipEnv);
         assert_eq!(
@@ -1230,12 +1253,7 @@ mod tests {
         std::fs::write(venv_dir.join(".project"), b"/some/project").unwrap();
 
         // PythonEnv without prefix set
-        let env = PythonEnv {
-            executable: norm_case(&python_exe),
-            prefix: None,
-            version: None,
-            symlinks: None,
-        };
+        let env = PythonEnv::new(norm_case(&python_exe), None, None);
 
         let env_vars = create_test_env_vars(Some(temp.clone()));
         assert!(
@@ -1253,12 +1271,7 @@ mod tests {
 
         // Executable whose parent directory is neither "bin" nor "Scripts",
         // so is_in_pipenv_centralized_dir cannot derive a prefix.
-        let env = PythonEnv {
-            executable: temp.join("random").join("python"),
-            prefix: None,
-            version: None,
-            symlinks: None,
-        };
+        let env = PythonEnv::new(temp.join("random").join("python"), None, None);
 
         let env_vars = create_test_env_vars(Some(temp.clone()));
         assert!(!is_in_pipenv_centralized_dir(&env, &env_vars));
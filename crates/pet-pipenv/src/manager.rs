@@ -3,6 +3,7 @@
 
 use log::trace;
 use pet_core::manager::{EnvManager, EnvManagerType};
+use pet_fs::file_system::{FileSystem, OsFileSystem};
 use std::{env, path::PathBuf};
 
 use crate::env_variables::EnvVariables;
@@ -14,9 +15,18 @@ pub struct PipenvManager {
 
 impl PipenvManager {
     pub fn find(executable: Option<PathBuf>, env_variables: &EnvVariables) -> Option<Self> {
+        Self::find_in(&OsFileSystem, executable, env_variables)
+    }
+
+    /// Same as `find`, looking at the files in the given file system.
+    pub fn find_in(
+        file_system: &dyn FileSystem,
+        executable: Option<PathBuf>,
+        env_variables: &EnvVariables,
+    ) -> Option<Self> {
         // If an explicit executable path is provided, check if it exists
         if let Some(executable) = executable {
-            if executable.is_file() {
+            if file_system.is_file(&executable) {
                 return Some(PipenvManager { executable });
             }
         }
@@ -65,7 +75,7 @@ impl PipenvManager {
             }
 
             for executable in search_paths {
-                if executable.is_file() {
+                if file_system.is_file(&executable) {
                     return Some(PipenvManager { executable });
                 }
             }
@@ -74,12 +84,12 @@ impl PipenvManager {
             if let Some(env_path) = &env_variables.path {
                 for each in env::split_paths(env_path) {
                     let executable = each.join("pipenv");
-                    if executable.is_file() {
+                    if file_system.is_file(&executable) {
                         return Some(PipenvManager { executable });
                     }
                     if std::env::consts::OS == "windows" {
                         let executable = each.join("pipenv.exe");
-                        if executable.is_file() {
+                        if file_system.is_file(&executable) {
                             return Some(PipenvManager { executable });
                         }
                     }
@@ -7,24 +7,33 @@ use pet_conda::package::{CondaPackageInfo, Package};
 use pet_core::{
     env::PythonEnv,
     python_environment::{PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind},
+    python_version::PythonVersion,
     reporter::Reporter,
     Locator, LocatorKind,
 };
-use pet_python_utils::executable::find_executables;
+use pet_fs::file_system::{FileSystem, OsFileSystem};
+use pet_python_utils::arch::get_architecture;
+use pet_python_utils::executable::find_executables_in;
 
 pub fn is_pixi_env(path: &Path) -> bool {
-    path.join("conda-meta").join("pixi").is_file()
+    is_pixi_env_in(&OsFileSystem, path)
+}
+
+/// Same as `is_pixi_env`, looking at the files in the given file system.
+pub fn is_pixi_env_in(file_system: &dyn FileSystem, path: &Path) -> bool {
+    file_system.is_file(&path.join("conda-meta").join("pixi"))
 }
 
 fn get_pixi_prefix(env: &PythonEnv) -> Option<PathBuf> {
+    let file_system = env.file_system.as_ref();
     env.prefix.clone().or_else(|| {
         env.executable.parent().and_then(|parent_dir| {
-            if is_pixi_env(parent_dir) {
+            if is_pixi_env_in(file_system, parent_dir) {
                 Some(parent_dir.to_path_buf())
             } else if parent_dir.ends_with("bin") || parent_dir.ends_with("Scripts") {
                 parent_dir
                     .parent()
-                    .filter(|parent| is_pixi_env(parent))
+                    .filter(|parent| is_pixi_env_in(file_system, parent))
                     .map(|parent| parent.to_path_buf())
             } else {
                 None
@@ -55,8 +64,9 @@ impl Locator for Pixi {
     }
 
     fn try_from(&self, env: &PythonEnv) -> Option<PythonEnvironment> {
+        let file_system = env.file_system.as_ref();
         get_pixi_prefix(env).and_then(|prefix| {
-            if !is_pixi_env(&prefix) {
+            if !is_pixi_env_in(file_system, &prefix) {
                 return None;
             }
 
@@ -66,14 +76,15 @@ impl Locator for Pixi {
                 .unwrap_or_default()
                 .to_string();
 
-            let symlinks = find_executables(&prefix);
+            let symlinks = find_executables_in(file_system, &prefix);
 
-            let version = CondaPackageInfo::from(&prefix, &Package::Python)
-                .map(|package_info| package_info.version);
+            let version = CondaPackageInfo::from_in(file_system, &prefix, &Package::Python)
+                .and_then(|package_info| PythonVersion::parse_lossless(&package_info.version));
 
             Some(
                 PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Pixi))
                     .executable(Some(env.executable.clone()))
+                    .arch(get_architecture(&env.executable))
                     .name(Some(name))
                     .prefix(Some(prefix))
                     .symlinks(Some(symlinks))
@@ -136,7 +147,7 @@ mod tests {
         let env = PythonEnv::new(
             executable.clone(),
             Some(prefix.clone()),
-            Some("3.12.0".to_string()),
+            Some("3.12.0".parse().unwrap()),
         );
 
         let pixi_env = locator.try_from(&env).unwrap();
@@ -1,12 +1,10 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use std::{
-    fs,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
 use log::{error, trace};
+use pet_fs::file_system::{FileSystem, OsFileSystem};
 use pet_python_utils::platform_dirs::Platformdirs;
 
 use crate::env_variables::EnvVariables;
@@ -43,26 +41,42 @@ impl Config {
         }
     }
     pub fn find_global(env: &EnvVariables) -> Option<Self> {
-        let file = find_config_file(env);
-        create_config(file, env)
+        Config::find_global_in(&OsFileSystem, env)
+    }
+    /// Same as `find_global`, looking at the files in the given file system.
+    pub fn find_global_in(file_system: &dyn FileSystem, env: &EnvVariables) -> Option<Self> {
+        let file = find_config_file_in(file_system, env);
+        create_config(file_system, file, env)
     }
     pub fn find_local(path: &Path, env: &EnvVariables) -> Option<Self> {
+        Config::find_local_in(&OsFileSystem, path, env)
+    }
+    /// Same as `find_local`, looking at the files in the given file system.
+    pub fn find_local_in(
+        file_system: &dyn FileSystem,
+        path: &Path,
+        env: &EnvVariables,
+    ) -> Option<Self> {
         let file = path.join("poetry.toml");
-        if file.is_file() {
-            create_config(Some(file), env)
+        if file_system.is_file(&file) {
+            create_config(file_system, Some(file), env)
         } else {
             None
         }
     }
 }
 
-fn create_config(file: Option<PathBuf>, env: &EnvVariables) -> Option<Config> {
+fn create_config(
+    file_system: &dyn FileSystem,
+    file: Option<PathBuf>,
+    env: &EnvVariables,
+) -> Option<Config> {
     if let Some(file) = &file {
         trace!("Parsing Poetry config file => {:?}", file);
     }
 
-    let cfg = file.clone().and_then(|f| parse(&f));
-    let cache_dir = get_cache_dir(&cfg, env);
+    let cfg = file.clone().and_then(|f| parse(file_system, &f));
+    let cache_dir = get_cache_dir(file_system, &cfg, env);
     let virtualenvs_path_from_env_var = env
         .poetry_virtualenvs_path
         .clone()
@@ -82,7 +96,7 @@ fn create_config(file: Option<PathBuf>, env: &EnvVariables) -> Option<Config> {
 
     // Give preference to the virtualenvs path from the env var
     if let Some(virtualenvs_path_from_env_var) = virtualenvs_path_from_env_var {
-        if virtualenvs_path_from_env_var.exists() {
+        if file_system.exists(&virtualenvs_path_from_env_var) {
             return Some(Config::new(
                 file,
                 virtualenvs_path_from_env_var,
@@ -120,17 +134,21 @@ fn resolve_virtualenvs_path(virtualenvs_path: &Path, cache_dir: &Option<PathBuf>
     virtualenvs_path.to_path_buf()
 }
 /// Maps to DEFAULT_CACHE_DIR in poetry
-fn get_cache_dir(cfg: &Option<ConfigToml>, env: &EnvVariables) -> Option<PathBuf> {
+fn get_cache_dir(
+    file_system: &dyn FileSystem,
+    cfg: &Option<ConfigToml>,
+    env: &EnvVariables,
+) -> Option<PathBuf> {
     // Cache dir in env variables takes precedence
     if let Some(cache_dir) = env.poetry_cache_dir.clone() {
-        if cache_dir.is_dir() {
+        if file_system.is_dir(&cache_dir) {
             trace!("Poetry cache dir from env variable: {:?}", cache_dir);
             return Some(cache_dir);
         }
     }
     // Check cache dir in config.
     if let Some(cache_dir) = cfg.as_ref().and_then(|cfg| cfg.cache_dir.clone()) {
-        if cache_dir.is_dir() {
+        if file_system.is_dir(&cache_dir) {
             trace!("Poetry cache dir from config: {:?}", cache_dir);
             return Some(cache_dir);
         }
@@ -142,10 +160,10 @@ fn get_cache_dir(cfg: &Option<ConfigToml>, env: &EnvVariables) -> Option<PathBuf
 }
 
 /// Maps to CONFIG_DIR in poetry
-fn get_config_dir(env: &EnvVariables) -> Option<PathBuf> {
+fn get_config_dir(file_system: &dyn FileSystem, env: &EnvVariables) -> Option<PathBuf> {
     if let Some(config) = env.poetry_config_dir.clone() {
         // Ensure we have a valid directory setup in the env variables.
-        if config.is_dir() {
+        if file_system.is_dir(&config) {
             return Some(config);
         }
     }
@@ -153,9 +171,14 @@ fn get_config_dir(env: &EnvVariables) -> Option<PathBuf> {
 }
 
 pub fn find_config_file(env: &EnvVariables) -> Option<PathBuf> {
-    let config_dir = get_config_dir(env)?;
+    find_config_file_in(&OsFileSystem, env)
+}
+
+/// Same as `find_config_file`, looking at the files in the given file system.
+pub fn find_config_file_in(file_system: &dyn FileSystem, env: &EnvVariables) -> Option<PathBuf> {
+    let config_dir = get_config_dir(file_system, env)?;
     let file = config_dir.join("config.toml");
-    if file.exists() {
+    if file_system.exists(&file) {
         Some(file)
     } else {
         None
@@ -169,8 +192,8 @@ struct ConfigToml {
     virtualenvs_path: Option<PathBuf>,
 }
 
-fn parse(file: &Path) -> Option<ConfigToml> {
-    let contents = fs::read_to_string(file).ok()?;
+fn parse(file_system: &dyn FileSystem, file: &Path) -> Option<ConfigToml> {
+    let contents = file_system.read_to_string(file).ok()?;
     let cfg = parse_contents(&contents);
     trace!("Poetry config file for {:?} is {:?}", file, cfg);
     cfg
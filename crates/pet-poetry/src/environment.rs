@@ -6,19 +6,21 @@ use std::path::PathBuf;
 use pet_core::python_environment::{
     PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind,
 };
-use pet_python_utils::{executable::find_executables, version};
+use pet_fs::file_system::FileSystem;
+use pet_python_utils::{arch::get_architecture, executable::find_executables_in, version};
 
 use crate::manager::PoetryManager;
 
 pub fn create_poetry_env(
+    file_system: &dyn FileSystem,
     prefix: &PathBuf,
     project_dir: PathBuf,
     manager: Option<PoetryManager>,
 ) -> Option<PythonEnvironment> {
-    if !prefix.exists() {
+    if !file_system.exists(prefix) {
         return None;
     }
-    let executables = find_executables(prefix);
+    let executables = find_executables_in(file_system, prefix);
     if executables.is_empty() {
         return None;
     }
@@ -26,6 +28,7 @@ pub fn create_poetry_env(
     Some(
         PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Poetry))
             .executable(Some(executables[0].clone()))
+            .arch(get_architecture(&executables[0]))
             .prefix(Some(prefix.clone()))
             .version(version)
             .manager(manager.map(|m| m.to_manager()))
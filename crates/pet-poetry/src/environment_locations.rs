@@ -5,13 +5,11 @@ use base64::{engine::general_purpose, Engine as _};
 use lazy_static::lazy_static;
 use log::trace;
 use pet_core::python_environment::PythonEnvironment;
-use pet_fs::path::norm_case;
+use pet_core::search_root::{read_search_root, SearchRootBudget};
+use pet_fs::{file_system::FileSystem, path::norm_case};
 use regex::Regex;
 use sha2::{Digest, Sha256};
-use std::{
-    fs,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
 use crate::{
     config::Config, env_variables::EnvVariables, environment::create_poetry_env,
@@ -24,9 +22,11 @@ lazy_static! {
 }
 
 pub fn list_environments(
+    file_system: &dyn FileSystem,
     env: &EnvVariables,
     workspace_dirs: &[PathBuf],
     manager: Option<PoetryManager>,
+    budget: &dyn SearchRootBudget,
 ) -> Option<Vec<PythonEnvironment>> {
     if workspace_dirs.is_empty() {
         return None;
@@ -34,7 +34,12 @@ pub fn list_environments(
 
     let workspace_dirs = workspace_dirs
         .iter()
-        .map(|workspace_dir| (workspace_dir, PyProjectToml::find(workspace_dir)))
+        .map(|workspace_dir| {
+            (
+                workspace_dir,
+                PyProjectToml::find_in(file_system, workspace_dir),
+            )
+        })
         .filter_map(|(workspace_dir, pyproject_toml)| {
             pyproject_toml.map(|pyproject_toml| (workspace_dir, pyproject_toml))
         })
@@ -42,10 +47,10 @@ pub fn list_environments(
 
     let mut envs = vec![];
 
-    let global_config = Config::find_global(env);
+    let global_config = Config::find_global_in(file_system, env);
     let mut global_envs = vec![];
     if let Some(config) = global_config.clone() {
-        global_envs = list_all_environments_from_config(&config).unwrap_or_default();
+        global_envs = list_all_environments_from_config(file_system, &config, budget);
     }
 
     if workspace_dirs.is_empty() {
@@ -62,8 +67,14 @@ pub fn list_environments(
         );
 
         for virtual_env in [
-            list_all_environments_from_project_config(&global_config, workspace_dir, env)
-                .unwrap_or_default(),
+            list_all_environments_from_project_config(
+                file_system,
+                &global_config,
+                workspace_dir,
+                env,
+                budget,
+            )
+            .unwrap_or_default(),
             global_envs.clone(),
         ]
         .concat()
@@ -76,9 +87,12 @@ pub fn list_environments(
                 .unwrap_or_default();
             // Look for .venv as well, in case we create the virtual envs in the local project folder.
             if name.starts_with(&virtualenv_prefix) || name.starts_with(".venv") {
-                if let Some(env) =
-                    create_poetry_env(&virtual_env, workspace_dir.clone(), manager.clone())
-                {
+                if let Some(env) = create_poetry_env(
+                    file_system,
+                    &virtual_env,
+                    workspace_dir.clone(),
+                    manager.clone(),
+                ) {
                     envs.push(env);
                 }
             }
@@ -89,18 +103,22 @@ pub fn list_environments(
 }
 
 fn list_all_environments_from_project_config(
+    file_system: &dyn FileSystem,
     global: &Option<Config>,
     path: &Path,
     env: &EnvVariables,
+    budget: &dyn SearchRootBudget,
 ) -> Option<Vec<PathBuf>> {
-    let local = Config::find_local(path, env);
+    let local = Config::find_local_in(file_system, path, env);
     trace!("Poetry Project ({:?}) config file => {:?}", path, local);
     let mut envs = vec![];
 
     if let Some(local) = &local {
-        if let Some(project_envs) = list_all_environments_from_config(local) {
-            envs.extend(project_envs);
-        }
+        envs.extend(list_all_environments_from_config(
+            file_system,
+            local,
+            budget,
+        ));
     }
 
     // Check if we're allowed to use .venv as a poetry env
@@ -109,7 +127,7 @@ fn list_all_environments_from_project_config(
     if should_use_local_venv_as_poetry_env(global, &local, env) {
         // If virtualenvs are in the project, then look for .venv
         let venv = path.join(".venv");
-        if venv.is_dir() {
+        if file_system.is_dir(&venv) {
             envs.push(venv);
         }
     }
@@ -153,15 +171,12 @@ fn should_use_local_venv_as_poetry_env(
     value
 }
 
-fn list_all_environments_from_config(cfg: &Config) -> Option<Vec<PathBuf>> {
-    Some(
-        fs::read_dir(&cfg.virtualenvs_path)
-            .ok()?
-            .filter_map(Result::ok)
-            .map(|entry| entry.path())
-            .filter(|path| path.is_dir())
-            .collect(),
-    )
+fn list_all_environments_from_config(
+    file_system: &dyn FileSystem,
+    cfg: &Config,
+    budget: &dyn SearchRootBudget,
+) -> Vec<PathBuf> {
+    read_search_root(file_system, budget, &cfg.virtualenvs_path)
 }
 
 // Source from https://github.com/python-poetry/poetry/blob/5bab98c9500f1050c6bb6adfb55580a23173f18d/src/poetry/utils/env/env_manager.py#L752C1-L757C63
@@ -4,7 +4,8 @@
 use lazy_static::lazy_static;
 use log::{error, trace};
 use pet_core::python_environment::PythonEnvironment;
-use pet_python_utils::executable::new_silent_command;
+use pet_fs::file_system::FileSystem;
+use pet_python_utils::{env::is_spawn_allowed, executable::new_silent_command};
 use regex::Regex;
 use std::{path::PathBuf, time::SystemTime};
 
@@ -16,17 +17,21 @@ lazy_static! {
 }
 
 pub fn list_environments(
+    file_system: &dyn FileSystem,
     executable: &PathBuf,
     workspace_dirs: &Vec<PathBuf>,
     manager: &PoetryManager,
 ) -> Vec<PythonEnvironment> {
     let mut envs = vec![];
     for workspace_dir in workspace_dirs {
-        if let Some(workspace_envs) = get_environments(executable, workspace_dir) {
+        if let Some(workspace_envs) = get_environments(file_system, executable, workspace_dir) {
             for workspace_env in workspace_envs {
-                if let Some(env) =
-                    create_poetry_env(&workspace_env, workspace_dir.clone(), Some(manager.clone()))
-                {
+                if let Some(env) = create_poetry_env(
+                    file_system,
+                    &workspace_env,
+                    workspace_dir.clone(),
+                    Some(manager.clone()),
+                ) {
                     envs.push(env);
                 }
             }
@@ -35,7 +40,14 @@ pub fn list_environments(
     envs
 }
 
-fn get_environments(executable: &PathBuf, workspace_dir: &PathBuf) -> Option<Vec<PathBuf>> {
+fn get_environments(
+    file_system: &dyn FileSystem,
+    executable: &PathBuf,
+    workspace_dir: &PathBuf,
+) -> Option<Vec<PathBuf>> {
+    if !is_spawn_allowed(file_system) {
+        return None;
+    }
     let start = SystemTime::now();
     let result = new_silent_command(executable)
         .arg("env")
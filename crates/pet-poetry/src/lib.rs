@@ -11,9 +11,12 @@ use pet_core::{
     os_environment::Environment,
     python_environment::{PythonEnvironment, PythonEnvironmentKind},
     reporter::Reporter,
+    search_root::{SearchRootBudget, Unlimited},
     Configuration, Locator, LocatorKind, LocatorResult, RefreshStatePersistence,
     RefreshStateSyncScope,
 };
+use pet_fs::file_system::FileSystem;
+use pet_python_utils::env::is_spawn_allowed;
 use pet_virtualenv::is_virtualenv;
 use regex::Regex;
 use std::{
@@ -84,7 +87,7 @@ fn has_poetry_cache_components(path: &Path) -> bool {
 /// Check if a .venv directory is an in-project Poetry environment
 /// This is for the case when virtualenvs.in-project = true is set.
 /// We check if the parent directory has Poetry configuration files.
-fn is_in_project_poetry_environment(path: &Path) -> bool {
+fn is_in_project_poetry_environment(file_system: &dyn FileSystem, path: &Path) -> bool {
     // Check if this is a .venv directory
     let dir_name = path
         .file_name()
@@ -99,7 +102,7 @@ fn is_in_project_poetry_environment(path: &Path) -> bool {
         // Check for poetry.toml - a local Poetry configuration file
         // Its presence indicates this project uses Poetry
         let poetry_toml = parent.join("poetry.toml");
-        if poetry_toml.is_file() {
+        if file_system.is_file(&poetry_toml) {
             trace!(
                 "Found in-project Poetry environment: {:?} with poetry.toml at {:?}",
                 path,
@@ -110,8 +113,8 @@ fn is_in_project_poetry_environment(path: &Path) -> bool {
 
         // Check if pyproject.toml contains Poetry configuration
         let pyproject_toml = parent.join("pyproject.toml");
-        if pyproject_toml.is_file() {
-            if let Ok(contents) = std::fs::read_to_string(&pyproject_toml) {
+        if file_system.is_file(&pyproject_toml) {
+            if let Ok(contents) = file_system.read_to_string(&pyproject_toml) {
                 // Look for [tool.poetry] or poetry as build backend
                 if contents.contains("[tool.poetry]")
                     || contents.contains("poetry.core.masonry.api")
@@ -143,6 +146,7 @@ pub struct Poetry {
     pub workspace_directories: Arc<RwLock<Vec<PathBuf>>>,
     pub env_vars: EnvVariables,
     pub poetry_executable: Arc<RwLock<Option<PathBuf>>>,
+    file_system: Arc<dyn FileSystem>,
     search_result: Arc<RwLock<Option<LocatorResult>>>,
 }
 
@@ -153,6 +157,7 @@ impl Poetry {
             workspace_directories: Arc::new(RwLock::new(vec![])),
             env_vars: EnvVariables::from(environment),
             poetry_executable: Arc::new(RwLock::new(None)),
+            file_system: environment.file_system(),
         }
     }
     fn clear(&self) {
@@ -198,7 +203,7 @@ impl Poetry {
         self.search_result.write().unwrap().replace(merged);
     }
 
-    fn find_with_cache(&self) -> Option<LocatorResult> {
+    fn find_with_cache(&self, budget: &dyn SearchRootBudget) -> Option<LocatorResult> {
         // First check if we have cached results
         {
             let search_result = self.search_result.read().unwrap();
@@ -208,7 +213,8 @@ impl Poetry {
         }
 
         // First find the manager
-        let manager = manager::PoetryManager::find(
+        let manager = manager::PoetryManager::find_in(
+            self.file_system.as_ref(),
             self.poetry_executable.read().unwrap().clone(),
             &self.env_vars,
         );
@@ -222,7 +228,14 @@ impl Poetry {
         }
 
         let workspace_dirs = self.workspace_directories.read().unwrap().clone();
-        let envs = list_environments(&self.env_vars, &workspace_dirs, manager).unwrap_or_default();
+        let envs = list_environments(
+            self.file_system.as_ref(),
+            &self.env_vars,
+            &workspace_dirs,
+            manager,
+            budget,
+        )
+        .unwrap_or_default();
         result.environments.extend(envs.clone());
 
         // Having a value in the search result means that we have already searched for environments
@@ -242,12 +255,17 @@ impl PoetryLocator for Poetry {
         reporter: &dyn Reporter,
         poetry_executable: Option<PathBuf>,
     ) -> Option<()> {
+        // Poetry of an alternate root directory (e.g. a container image) is not that of this machine.
+        if !is_spawn_allowed(self.file_system.as_ref()) {
+            return None;
+        }
         let user_provided_poetry_exe = poetry_executable.is_some();
         let manager = PoetryManager::find(poetry_executable.clone(), &self.env_vars)?;
         let poetry_executable = manager.executable.clone();
 
         let workspace_dirs = self.workspace_directories.read().unwrap().clone();
         let environments_using_spawn = environment_locations_spawn::list_environments(
+            self.file_system.as_ref(),
             &poetry_executable,
             &workspace_dirs,
             &manager,
@@ -317,7 +335,7 @@ impl Locator for Poetry {
         }
 
         // First, check if the environment is in our cache
-        if let Some(result) = self.find_with_cache() {
+        if let Some(result) = self.find_with_cache(&Unlimited) {
             for found_env in result.environments {
                 if let Some(symlinks) = &found_env.symlinks {
                     if symlinks.contains(&env.executable) {
@@ -337,6 +355,7 @@ impl Locator for Poetry {
                     prefix
                 );
                 return environment::create_poetry_env(
+                    env.file_system.as_ref(),
                     prefix,
                     prefix.clone(), // We don't have the project directory, use prefix
                     None,           // No manager available in this fallback case
@@ -344,11 +363,12 @@ impl Locator for Poetry {
             }
 
             // Check for in-project .venv Poetry environment
-            if is_in_project_poetry_environment(prefix) {
+            if is_in_project_poetry_environment(env.file_system.as_ref(), prefix) {
                 trace!("Identified in-project Poetry environment: {:?}", prefix);
                 // For in-project .venv, the project directory is the parent
                 let project_dir = prefix.parent().unwrap_or(prefix).to_path_buf();
                 return environment::create_poetry_env(
+                    env.file_system.as_ref(),
                     prefix,
                     project_dir,
                     None, // No manager available in this fallback case
@@ -360,8 +380,11 @@ impl Locator for Poetry {
     }
 
     fn find(&self, reporter: &dyn Reporter) {
+        self.find_with_budget(reporter, &Unlimited);
+    }
+    fn find_with_budget(&self, reporter: &dyn Reporter, budget: &dyn SearchRootBudget) {
         self.clear();
-        if let Some(result) = self.find_with_cache() {
+        if let Some(result) = self.find_with_cache(budget) {
             for manager in result.managers {
                 reporter.report_manager(&manager.clone());
             }
@@ -4,7 +4,10 @@
 use lazy_static::lazy_static;
 use log::trace;
 use pet_core::manager::{EnvManager, EnvManagerType};
-use pet_fs::path::resolve_any_symlink;
+use pet_fs::{
+    file_system::{FileSystem, OsFileSystem},
+    path::resolve_any_symlink,
+};
 use regex::Regex;
 use std::{env, path::PathBuf};
 
@@ -24,8 +27,17 @@ pub struct PoetryManager {
 
 impl PoetryManager {
     pub fn find(executable: Option<PathBuf>, env_variables: &EnvVariables) -> Option<Self> {
+        Self::find_in(&OsFileSystem, executable, env_variables)
+    }
+
+    /// Same as `find`, looking at the files in the given file system.
+    pub fn find_in(
+        file_system: &dyn FileSystem,
+        executable: Option<PathBuf>,
+        env_variables: &EnvVariables,
+    ) -> Option<Self> {
         if let Some(executable) = executable {
-            if executable.is_file() {
+            if file_system.is_file(&executable) {
                 let version = Self::extract_version_from_path(&executable);
                 return Some(PoetryManager {
                     executable,
@@ -120,7 +132,7 @@ impl PoetryManager {
                 );
             }
             for executable in search_paths {
-                if executable.is_file() {
+                if file_system.is_file(&executable) {
                     let version = Self::extract_version_from_path(&executable);
                     return Some(PoetryManager {
                         executable,
@@ -133,7 +145,7 @@ impl PoetryManager {
             if let Some(env_path) = &env_variables.path {
                 for each in env::split_paths(env_path) {
                     let executable = each.join("poetry");
-                    if executable.is_file() {
+                    if file_system.is_file(&executable) {
                         let version = Self::extract_version_from_path(&executable);
                         return Some(PoetryManager {
                             executable,
@@ -142,7 +154,7 @@ impl PoetryManager {
                     }
                     if std::env::consts::OS == "windows" {
                         let executable = each.join("poetry.exe");
-                        if executable.is_file() {
+                        if file_system.is_file(&executable) {
                             let version = Self::extract_version_from_path(&executable);
                             return Some(PoetryManager {
                                 executable,
@@ -1,13 +1,11 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use std::{
-    fs,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
 use lazy_static::lazy_static;
 use log::{error, trace};
+use pet_fs::file_system::FileSystem;
 use regex::Regex;
 
 lazy_static! {
@@ -34,15 +32,15 @@ impl PyProjectToml {
             name: normalized_name,
         }
     }
-    pub fn find(path: &Path) -> Option<Self> {
+    pub fn find_in(file_system: &dyn FileSystem, path: &Path) -> Option<Self> {
         trace!("Finding poetry file in {:?}", path);
-        parse(&path.join("pyproject.toml"))
+        parse(file_system, &path.join("pyproject.toml"))
     }
 }
 
-fn parse(file: &Path) -> Option<PyProjectToml> {
+fn parse(file_system: &dyn FileSystem, file: &Path) -> Option<PyProjectToml> {
     trace!("Parsing poetry file: {:?}", file);
-    let contents = fs::read_to_string(file).ok()?;
+    let contents = file_system.read_to_string(file).ok()?;
     parse_contents(&contents, file)
 }
 
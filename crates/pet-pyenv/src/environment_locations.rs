@@ -2,7 +2,7 @@
 // Licensed under the MIT License.
 
 use crate::env_variables::EnvVariables;
-use pet_fs::path::norm_case;
+use pet_fs::{file_system::FileSystem, path::norm_case};
 use std::path::PathBuf;
 
 #[cfg(windows)]
@@ -17,7 +17,10 @@ pub fn get_home_pyenv_dir(env_vars: &EnvVariables) -> Option<PathBuf> {
     Some(norm_case(home.join(".pyenv")))
 }
 
-pub fn get_binary_from_known_paths(env_vars: &EnvVariables) -> Option<PathBuf> {
+pub fn get_binary_from_known_paths(
+    env_vars: &EnvVariables,
+    file_system: &dyn FileSystem,
+) -> Option<PathBuf> {
     for known_path in &env_vars.known_global_search_locations {
         let exe = if cfg!(windows) {
             // pyenv-win provides pyenv.bat, not pyenv.exe
@@ -25,7 +28,7 @@ pub fn get_binary_from_known_paths(env_vars: &EnvVariables) -> Option<PathBuf> {
         } else {
             known_path.join("pyenv")
         };
-        if exe.is_file() {
+        if file_system.is_file(&exe) {
             return Some(norm_case(exe));
         }
     }
@@ -49,6 +52,7 @@ pub fn get_pyenv_dir(env_vars: &EnvVariables) -> Option<PathBuf> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pet_fs::file_system::OsFileSystem;
     use std::fs;
     use tempfile::tempdir;
 
@@ -133,7 +137,7 @@ mod tests {
         fs::write(&exe, b"").unwrap();
 
         let env = make_env_vars(None, None, None, vec![dir.path().to_path_buf()]);
-        let result = get_binary_from_known_paths(&env);
+        let result = get_binary_from_known_paths(&env, &OsFileSystem);
         assert!(result.is_some());
     }
 
@@ -141,12 +145,12 @@ mod tests {
     fn get_binary_from_known_paths_returns_none_when_not_found() {
         let dir = tempdir().unwrap();
         let env = make_env_vars(None, None, None, vec![dir.path().to_path_buf()]);
-        assert!(get_binary_from_known_paths(&env).is_none());
+        assert!(get_binary_from_known_paths(&env, &OsFileSystem).is_none());
     }
 
     #[test]
     fn get_binary_from_known_paths_returns_none_for_empty_paths() {
         let env = make_env_vars(None, None, None, vec![]);
-        assert!(get_binary_from_known_paths(&env).is_none());
+        assert!(get_binary_from_known_paths(&env, &OsFileSystem).is_none());
     }
 }
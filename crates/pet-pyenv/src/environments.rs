@@ -1,40 +1,28 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use lazy_static::lazy_static;
 use pet_core::{
     arch::Architecture,
     manager::EnvManager,
     python_environment::{PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind},
+    python_version::PythonVersion,
 };
-use pet_python_utils::executable::find_executables;
+use pet_fs::file_system::FileSystem;
+use pet_python_utils::arch::get_architecture;
+use pet_python_utils::executable::find_executables_in;
 use pet_python_utils::version;
-use regex::Regex;
 use std::path::Path;
 
-lazy_static! {
-    // Stable Versions = like 3.10.10
-    static ref PURE_PYTHON_VERSION: Regex = Regex::new(r"^(\d+\.\d+\.\d+)$")
-        .expect("error parsing Version regex for Python Version in pyenv");
-    // Dev Versions = like 3.10-dev
-    static ref DEV_PYTHON_VERSION: Regex = Regex::new(r"^(\d+\.\d+-.*)$")
-        .expect("error parsing Version regex for Dev Python Version in pyenv");
-    // Alpha, rc Versions = like 3.10.0a3
-    static ref BETA_PYTHON_VERSION: Regex = Regex::new(r"^(\d+\.\d+.\d+\w\d+)")
-        .expect("error parsing Version regex for Alpha Python Version in pyenv");
-    // win32 versions, rc Versions = like 3.11.0a-win32
-    static ref WIN32_PYTHON_VERSION: Regex = Regex::new(r"^(\d+\.\d+.\d+\w\d+)-win32")
-        .expect("error parsing Version regex for Win32 Python Version in pyenv");
-}
-
 pub fn get_generic_python_environment(
+    file_system: &dyn FileSystem,
     executable: &Path,
     path: &Path,
     manager: &Option<EnvManager>,
 ) -> Option<PythonEnvironment> {
     let file_name = path.file_name()?.to_string_lossy().to_string();
     // If we can get the version from the header files, thats more accurate.
-    let version = version::from_header_files(path).or_else(|| get_version(&file_name));
+    let version =
+        version::from_header_files_in(file_system, path).or_else(|| get_version(&file_name));
 
     let arch = if file_name.ends_with("-win32") {
         Some(Architecture::X86)
@@ -48,62 +36,47 @@ pub fn get_generic_python_environment(
             .version(version)
             .prefix(Some(path.to_path_buf()))
             .manager(manager.clone())
-            .arch(arch)
-            .symlinks(Some(find_executables(path)))
+            .arch(get_architecture(executable).or(arch))
+            .symlinks(Some(find_executables_in(file_system, path)))
             .build(),
     )
 }
 
 pub fn get_virtual_env_environment(
+    file_system: &dyn FileSystem,
     executable: &Path,
     path: &Path,
     manager: &Option<EnvManager>,
 ) -> Option<PythonEnvironment> {
-    let version = version::from_pyvenv_cfg(path)?;
+    let version = version::from_pyvenv_cfg_in(file_system, path)?;
     Some(
         PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::PyenvVirtualEnv))
             .executable(Some(executable.to_path_buf()))
+            .arch(get_architecture(executable))
             .version(Some(version))
             .prefix(Some(path.to_path_buf()))
             .manager(manager.clone())
-            .symlinks(Some(find_executables(path)))
+            .symlinks(Some(find_executables_in(file_system, path)))
             .build(),
     )
 }
 
-fn get_version(folder_name: &str) -> Option<String> {
-    // Stable Versions = like 3.10.10
-    match PURE_PYTHON_VERSION.captures(folder_name) {
-        Some(captures) => captures.get(1).map(|version| version.as_str().to_string()),
-        None => {
-            // Dev Versions = like 3.10-dev
-            match DEV_PYTHON_VERSION.captures(folder_name) {
-                Some(captures) => captures.get(1).map(|version| version.as_str().to_string()),
-                None => {
-                    // Alpha, rc Versions = like 3.10.0a3
-                    match BETA_PYTHON_VERSION.captures(folder_name) {
-                        Some(captures) => {
-                            captures.get(1).map(|version| version.as_str().to_string())
-                        }
-                        None => {
-                            // win32 versions, rc Versions = like 3.11.0a-win32
-                            match WIN32_PYTHON_VERSION.captures(folder_name) {
-                                Some(captures) => {
-                                    captures.get(1).map(|version| version.as_str().to_string())
-                                }
-                                None => None,
-                            }
-                        }
-                    }
-                }
-            }
-        }
+/// Version from the name of the folder, e.g. `3.10.10`, `3.10.0a3`, `3.13.0t`, `3.10-dev` or `3.11.0a4-win32`.
+fn get_version(folder_name: &str) -> Option<PythonVersion> {
+    let folder_name = folder_name.strip_suffix("-win32").unwrap_or(folder_name);
+    let version = PythonVersion::parse(folder_name)?;
+    // Partial versions such as `3.10` are aliases, only dev versions are allowed to be partial.
+    if version.is_complete() || version.dev().is_some() {
+        Some(version)
+    } else {
+        None
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pet_fs::file_system::OsFileSystem;
     use std::fs;
     use std::path::PathBuf;
     use tempfile::tempdir;
@@ -111,34 +84,38 @@ mod tests {
     // get_version tests
     #[test]
     fn get_version_parses_stable_version() {
-        assert_eq!(get_version("3.10.10"), Some("3.10.10".to_string()));
-        assert_eq!(get_version("3.12.0"), Some("3.12.0".to_string()));
-        assert_eq!(get_version("2.7.18"), Some("2.7.18".to_string()));
+        assert_eq!(get_version("3.10.10"), Some("3.10.10".parse().unwrap()));
+        assert_eq!(get_version("3.12.0"), Some("3.12.0".parse().unwrap()));
+        assert_eq!(get_version("2.7.18"), Some("2.7.18".parse().unwrap()));
     }
 
     #[test]
     fn get_version_parses_dev_version() {
-        assert_eq!(get_version("3.10-dev"), Some("3.10-dev".to_string()));
-        assert_eq!(get_version("3.13-dev"), Some("3.13-dev".to_string()));
+        assert_eq!(get_version("3.10-dev"), Some("3.10-dev".parse().unwrap()));
+        assert_eq!(get_version("3.10-dev").unwrap().to_string(), "3.10-dev");
+        assert_eq!(get_version("3.13-dev"), Some("3.13-dev".parse().unwrap()));
     }
 
     #[test]
     fn get_version_parses_alpha_rc_version() {
-        assert_eq!(get_version("3.10.0a3"), Some("3.10.0a3".to_string()));
-        assert_eq!(get_version("3.12.0b1"), Some("3.12.0b1".to_string()));
+        assert_eq!(get_version("3.10.0a3"), Some("3.10.0a3".parse().unwrap()));
+        assert_eq!(get_version("3.12.0b1"), Some("3.12.0b1".parse().unwrap()));
     }
 
     #[test]
-    fn get_version_returns_none_for_multi_letter_prerelease() {
-        // Known limitation: BETA_PYTHON_VERSION regex uses \w (single char) so multi-letter
-        // pre-release tags like "rc" are not captured. Real pyenv installs can have rc versions
-        // (e.g. 3.13.0rc1), but version detection falls back to header files in that case.
-        assert_eq!(get_version("3.11.0rc2"), None);
+    fn get_version_parses_multi_letter_prerelease_and_free_threaded_versions() {
+        assert_eq!(get_version("3.11.0rc2"), Some("3.11.0rc2".parse().unwrap()));
+        let version = get_version("3.13.0t").unwrap();
+        assert!(version.is_free_threaded());
+        assert_eq!(version.to_string(), "3.13.0t");
     }
 
     #[test]
     fn get_version_parses_win32_version() {
-        assert_eq!(get_version("3.11.0a4-win32"), Some("3.11.0a4".to_string()));
+        assert_eq!(
+            get_version("3.11.0a4-win32"),
+            Some("3.11.0a4".parse().unwrap())
+        );
     }
 
     #[test]
@@ -172,14 +149,14 @@ mod tests {
         };
         fs::write(&exe, b"").unwrap();
 
-        let result = get_generic_python_environment(&exe, &env_path, &None).unwrap();
+        let result = get_generic_python_environment(&OsFileSystem, &exe, &env_path, &None).unwrap();
 
         assert_eq!(result.kind, Some(PythonEnvironmentKind::Pyenv));
         assert_eq!(
             result.executable.as_ref().unwrap().file_name(),
             exe.file_name()
         );
-        assert_eq!(result.version, Some("3.12.0".to_string()));
+        assert_eq!(result.version, Some("3.12.0".parse().unwrap()));
         assert_eq!(
             result.prefix.as_ref().unwrap().file_name(),
             env_path.file_name()
@@ -204,7 +181,7 @@ mod tests {
         };
         fs::write(&exe, b"").unwrap();
 
-        let result = get_generic_python_environment(&exe, &env_path, &None).unwrap();
+        let result = get_generic_python_environment(&OsFileSystem, &exe, &env_path, &None).unwrap();
 
         assert_eq!(result.arch, Some(Architecture::X86));
     }
@@ -226,7 +203,7 @@ mod tests {
         };
         fs::write(&exe, b"").unwrap();
 
-        let result = get_generic_python_environment(&exe, &env_path, &None).unwrap();
+        let result = get_generic_python_environment(&OsFileSystem, &exe, &env_path, &None).unwrap();
 
         assert!(result.arch.is_none());
     }
@@ -253,7 +230,9 @@ mod tests {
             pet_core::manager::EnvManagerType::Pyenv,
             Some("2.4.0".to_string()),
         );
-        let result = get_generic_python_environment(&exe, &env_path, &Some(mgr.clone())).unwrap();
+        let result =
+            get_generic_python_environment(&OsFileSystem, &exe, &env_path, &Some(mgr.clone()))
+                .unwrap();
 
         assert_eq!(result.manager, Some(mgr));
     }
@@ -275,7 +254,7 @@ mod tests {
         };
         fs::write(&exe, b"").unwrap();
 
-        let result = get_generic_python_environment(&exe, &env_path, &None).unwrap();
+        let result = get_generic_python_environment(&OsFileSystem, &exe, &env_path, &None).unwrap();
 
         assert_eq!(result.kind, Some(PythonEnvironmentKind::Pyenv));
         // No version extractable from folder name and no header files
@@ -300,7 +279,7 @@ mod tests {
         };
         fs::write(&exe, b"").unwrap();
 
-        let result = get_virtual_env_environment(&exe, &env_path, &None);
+        let result = get_virtual_env_environment(&OsFileSystem, &exe, &env_path, &None);
 
         assert!(result.is_none());
     }
@@ -327,10 +306,10 @@ mod tests {
         )
         .unwrap();
 
-        let result = get_virtual_env_environment(&exe, &env_path, &None).unwrap();
+        let result = get_virtual_env_environment(&OsFileSystem, &exe, &env_path, &None).unwrap();
 
         assert_eq!(result.kind, Some(PythonEnvironmentKind::PyenvVirtualEnv));
-        assert_eq!(result.version, Some("3.12.0".to_string()));
+        assert_eq!(result.version, Some("3.12.0".parse().unwrap()));
         assert_eq!(
             result.executable.as_ref().unwrap().file_name(),
             exe.file_name()
@@ -2,26 +2,28 @@
 // Licensed under the MIT License.
 
 use std::{
-    fs,
-    path::PathBuf,
+    collections::HashMap,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
-    thread,
 };
 
 use env_variables::EnvVariables;
 use environments::{get_generic_python_environment, get_virtual_env_environment};
 use log::trace;
 use manager::PyEnvInfo;
-use pet_conda::{utils::is_conda_env, CondaLocator};
+use pet_conda::{utils::is_conda_env_in, CondaLocator};
 use pet_core::{
     env::PythonEnv,
     manager::{EnvManager, EnvManagerType},
     os_environment::Environment,
     python_environment::{PythonEnvironment, PythonEnvironmentKind},
     reporter::Reporter,
+    search_root::{read_search_root, SearchRootBudget, Unlimited},
     Locator, LocatorKind, RefreshStatePersistence,
 };
-use pet_python_utils::executable::find_executable;
+use pet_fs::file_system::FileSystem;
+use pet_python_utils::executable::find_executable_in;
+use pet_shims::{add_shim_symlinks, find_python_shims_in, ShimResolver};
 
 pub mod env_variables;
 mod environment_locations;
@@ -31,6 +33,8 @@ mod manager;
 pub struct PyEnv {
     pub env_vars: EnvVariables,
     pub conda_locator: Arc<dyn CondaLocator>,
+    shim_resolver: ShimResolver,
+    file_system: Arc<dyn FileSystem>,
     manager: Arc<Mutex<Option<EnvManager>>>,
     versions_dir: Arc<Mutex<Option<PathBuf>>>,
 }
@@ -43,6 +47,8 @@ impl PyEnv {
         PyEnv {
             env_vars: EnvVariables::from(environment),
             conda_locator,
+            shim_resolver: ShimResolver::from(environment),
+            file_system: environment.file_system(),
             manager: Arc::new(Mutex::new(None)),
             versions_dir: Arc::new(Mutex::new(None)),
         }
@@ -61,7 +67,7 @@ impl PyEnv {
             .lock()
             .expect("versions_dir mutex poisoned");
         if managers.is_none() || versions.is_none() {
-            let pyenv_info = PyEnvInfo::from(&self.env_vars);
+            let pyenv_info = PyEnvInfo::from(&self.env_vars, self.file_system.as_ref());
             trace!("PyEnv Info {:?}", pyenv_info);
             if let Some(ref exe) = pyenv_info.exe {
                 let version = pyenv_info.version.clone();
@@ -78,6 +84,35 @@ impl PyEnv {
         }
         (managers.clone(), versions.clone())
     }
+    /// Groups the pyenv shims by the version folder they currently resolve to.
+    /// Only the global configuration (`PYENV_VERSION` & the global version file) is used,
+    /// as there's no current directory when discovering environments.
+    fn get_shims_by_version_dir(&self, versions: &Path) -> HashMap<PathBuf, Vec<PathBuf>> {
+        let mut shims_by_version_dir: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+        let Some(shims_dir) = versions.parent().map(|root| root.join("shims")) else {
+            return shims_by_version_dir;
+        };
+        for shim in find_python_shims_in(self.file_system.as_ref(), &shims_dir) {
+            if let Some(version_dir) = self
+                .shim_resolver
+                .resolve(&shim, None)
+                .and_then(|target| get_version_dir(versions, &target))
+            {
+                shims_by_version_dir
+                    .entry(version_dir)
+                    .or_default()
+                    .push(shim);
+            }
+        }
+        shims_by_version_dir
+    }
+}
+
+/// Returns the folder in `versions` that contains the executable, i.e. `<versions>/3.12.1`.
+fn get_version_dir(versions: &Path, executable: &Path) -> Option<PathBuf> {
+    let relative = executable.strip_prefix(versions).ok()?;
+    let version = relative.components().next()?;
+    Some(versions.join(version))
 }
 
 impl Locator for PyEnv {
@@ -96,17 +131,18 @@ impl Locator for PyEnv {
     }
 
     fn try_from(&self, env: &PythonEnv) -> Option<PythonEnvironment> {
+        let file_system = env.file_system.as_ref();
         if let Some(prefix) = &env.prefix {
-            if is_conda_env(prefix) {
+            if is_conda_env_in(file_system, prefix) {
                 return None;
             }
         }
         // Possible this is a root conda env (hence parent directory is conda install dir).
-        if is_conda_env(env.executable.parent()?) {
+        if is_conda_env_in(file_system, env.executable.parent()?) {
             return None;
         }
         // Possible this is a conda env (hence parent directory is Scripts/bin dir).
-        if is_conda_env(env.executable.parent()?.parent()?) {
+        if is_conda_env_in(file_system, env.executable.parent()?.parent()?) {
             return None;
         }
 
@@ -117,14 +153,22 @@ impl Locator for PyEnv {
 
         let (manager, versions) = self.get_manager_versions_dir();
         if let Some(versions) = versions {
-            if env.executable.starts_with(versions) {
-                let env_path = env.prefix.clone()?;
-                if let Some(env) = get_virtual_env_environment(&env.executable, &env_path, &manager)
+            if env.executable.starts_with(&versions) {
+                // Without a prefix (i.e. without spawning Python) the version folder is the prefix.
+                let env_path = env
+                    .prefix
+                    .clone()
+                    .or_else(|| get_version_dir(&versions, &env.executable))?;
+                if let Some(env) =
+                    get_virtual_env_environment(file_system, &env.executable, &env_path, &manager)
                 {
                     return Some(env);
-                } else if let Some(env) =
-                    get_generic_python_environment(&env.executable, &env_path, &manager)
-                {
+                } else if let Some(env) = get_generic_python_environment(
+                    file_system,
+                    &env.executable,
+                    &env_path,
+                    &manager,
+                ) {
                     return Some(env);
                 }
             }
@@ -133,6 +177,9 @@ impl Locator for PyEnv {
     }
 
     fn find(&self, reporter: &dyn Reporter) {
+        self.find_with_budget(reporter, &Unlimited);
+    }
+    fn find_with_budget(&self, reporter: &dyn Reporter, budget: &dyn SearchRootBudget) {
         self.clear();
 
         let (manager, versions) = self.get_manager_versions_dir();
@@ -143,28 +190,38 @@ impl Locator for PyEnv {
 
         if let Some(versions) = versions {
             let conda_locator = self.conda_locator.clone();
-            thread::scope(|s| {
-                if let Ok(reader) = fs::read_dir(versions) {
-                    for path in reader.filter_map(Result::ok).map(|e| e.path()) {
-                        let conda_locator = conda_locator.clone();
-                        let manager = manager.clone();
-                        let path = path.clone();
-                        s.spawn(move || {
-                            if let Some(executable) = find_executable(&path) {
-                                if is_conda_env(&path) {
-                                    conda_locator.find_and_report(reporter, &path);
-                                } else if let Some(env) =
-                                    get_virtual_env_environment(&executable, &path, &manager)
-                                {
-                                    reporter.report_environment(&env)
-                                } else if let Some(env) =
-                                    get_generic_python_environment(&executable, &path, &manager)
-                                {
-                                    reporter.report_environment(&env)
-                                }
+            let file_system = self.file_system.as_ref();
+            let shims_by_version_dir = self.get_shims_by_version_dir(&versions);
+            // Runs on the pool of the caller (i.e. the discovery pool), instead of a thread per version.
+            rayon::scope(|s| {
+                for path in read_search_root(file_system, budget, &versions) {
+                    let conda_locator = conda_locator.clone();
+                    let manager = manager.clone();
+                    let path = path.clone();
+                    let shims = shims_by_version_dir.get(&path).cloned().unwrap_or_default();
+                    s.spawn(move |_| {
+                        if let Some(executable) = find_executable_in(file_system, &path) {
+                            if is_conda_env_in(file_system, &path) {
+                                conda_locator.find_and_report(reporter, &path);
+                            } else if let Some(mut env) = get_virtual_env_environment(
+                                file_system,
+                                &executable,
+                                &path,
+                                &manager,
+                            )
+                            .or_else(|| {
+                                get_generic_python_environment(
+                                    file_system,
+                                    &executable,
+                                    &path,
+                                    &manager,
+                                )
+                            }) {
+                                add_shim_symlinks(&mut env, &shims);
+                                reporter.report_environment(&env)
                             }
-                        });
-                    }
+                        }
+                    });
                 }
             });
         } else {
@@ -6,9 +6,10 @@ use crate::{
     environment_locations::{get_binary_from_known_paths, get_home_pyenv_dir, get_pyenv_dir},
 };
 use lazy_static::lazy_static;
+use pet_fs::file_system::FileSystem;
 use regex::Regex;
 use std::path::Path;
-use std::{fs, path::PathBuf};
+use std::path::PathBuf;
 
 lazy_static! {
     // Sample /opt/homebrew/Cellar/pyenv/2.4.0/libexec/pyenv
@@ -28,12 +29,12 @@ pub struct PyEnvInfo {
 }
 
 impl PyEnvInfo {
-    pub fn from(environment: &EnvVariables) -> PyEnvInfo {
-        get_pyenv_info(environment)
+    pub fn from(environment: &EnvVariables, file_system: &dyn FileSystem) -> PyEnvInfo {
+        get_pyenv_info(environment, file_system)
     }
 }
 
-fn get_pyenv_info(environment: &EnvVariables) -> PyEnvInfo {
+fn get_pyenv_info(environment: &EnvVariables, file_system: &dyn FileSystem) -> PyEnvInfo {
     let mut pyenv = PyEnvInfo {
         exe: None,
         versions: None,
@@ -41,15 +42,15 @@ fn get_pyenv_info(environment: &EnvVariables) -> PyEnvInfo {
     };
     if let Some(dir) = get_pyenv_dir(environment) {
         let versions = dir.join("versions");
-        if versions.exists() {
+        if file_system.exists(&versions) {
             pyenv.versions = Some(versions);
         }
         let exe = dir.join("bin").join("pyenv");
-        if exe.exists() {
+        if file_system.exists(&exe) {
             pyenv.exe = Some(exe);
         }
     }
-    if let Some(exe) = get_binary_from_known_paths(environment) {
+    if let Some(exe) = get_binary_from_known_paths(environment, file_system) {
         pyenv.exe = Some(exe);
     }
 
@@ -57,13 +58,13 @@ fn get_pyenv_info(environment: &EnvVariables) -> PyEnvInfo {
         if let Some(path) = get_home_pyenv_dir(environment) {
             if pyenv.exe.is_none() {
                 let exe = path.join("bin").join("pyenv");
-                if exe.exists() {
+                if file_system.exists(&exe) {
                     pyenv.exe = Some(exe);
                 }
             }
             if pyenv.versions.is_none() {
                 let versions = path.join("versions");
-                if versions.exists() {
+                if file_system.exists(&versions) {
                     pyenv.versions = Some(versions);
                 }
             }
@@ -72,7 +73,7 @@ fn get_pyenv_info(environment: &EnvVariables) -> PyEnvInfo {
 
     // Get the version of the pyenv manager
     if let Some(ref exe) = pyenv.exe {
-        pyenv.version = get_pyenv_manager_version(exe, environment);
+        pyenv.version = get_pyenv_manager_version(exe, environment, file_system);
     }
 
     pyenv
@@ -82,6 +83,7 @@ fn get_pyenv_info(environment: &EnvVariables) -> PyEnvInfo {
 fn get_pyenv_manager_version(
     _pyenv_binary_path: &Path,
     environment: &EnvVariables,
+    file_system: &dyn FileSystem,
 ) -> Option<String> {
     // In windows, the version is stored in the `.pyenv/.version` file
 
@@ -90,14 +92,14 @@ fn get_pyenv_manager_version(
         .or_else(|| get_home_pyenv_dir(environment)?.parent().map(PathBuf::from))?;
 
     let mut version_file = pyenv_dir.join(".version");
-    if !version_file.exists() {
+    if !file_system.exists(&version_file) {
         // We might have got the path `~/.pyenv/pyenv-win`
         version_file = pyenv_dir.parent()?.join(".version");
-        if !version_file.exists() {
+        if !file_system.exists(&version_file) {
             return None;
         }
     }
-    let version = fs::read_to_string(version_file).ok()?;
+    let version = file_system.read_to_string(&version_file).ok()?;
     Some(
         PYENV_VERSION_FROM_VERSION_FILE
             .captures(&version)?
@@ -108,8 +110,12 @@ fn get_pyenv_manager_version(
 }
 
 #[cfg(unix)]
-fn get_pyenv_manager_version(pyenv_exe: &Path, _environment: &EnvVariables) -> Option<String> {
-    let real_path = fs::read_link(pyenv_exe).ok()?;
+fn get_pyenv_manager_version(
+    pyenv_exe: &Path,
+    _environment: &EnvVariables,
+    file_system: &dyn FileSystem,
+) -> Option<String> {
+    let real_path = file_system.read_link(pyenv_exe).ok()?;
     // Look for version in path
     // Sample /opt/homebrew/Cellar/pyenv/2.4.0/libexec/pyenv
     Some(
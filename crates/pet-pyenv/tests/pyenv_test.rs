@@ -210,7 +210,7 @@ fn find_pyenv_envs() {
             ".pyenv/versions/3.9.9/bin/python",
         ])),
         kind: Some(PythonEnvironmentKind::Pyenv),
-        version: Some("3.9.9".to_string()),
+        version: Some("3.9.9".parse().unwrap()),
         prefix: Some(resolve_test_path(&[
             home.to_str().unwrap(),
             ".pyenv/versions/3.9.9",
@@ -222,6 +222,11 @@ fn find_pyenv_envs() {
             ".pyenv/versions/3.9.9/bin/python",
         ])]),
         error: None,
+        external_kind: None,
+        platform_tags: None,
+        base_environment: None,
+        script: None,
+        tags: None,
     };
     let expected_virtual_env = PythonEnvironment {
         display_name: None,
@@ -232,7 +237,7 @@ fn find_pyenv_envs() {
             ".pyenv/versions/my-virtual-env/bin/python",
         ])),
         kind: Some(PythonEnvironmentKind::PyenvVirtualEnv),
-        version: Some("3.10.13".to_string()),
+        version: Some("3.10.13".parse().unwrap()),
         prefix: Some(resolve_test_path(&[
             home.to_str().unwrap(),
             ".pyenv/versions/my-virtual-env",
@@ -244,6 +249,11 @@ fn find_pyenv_envs() {
             ".pyenv/versions/my-virtual-env/bin/python",
         ])]),
         error: None,
+        external_kind: None,
+        platform_tags: None,
+        base_environment: None,
+        script: None,
+        tags: None,
     };
     let expected_3_12_1 = PythonEnvironment {
         display_name: None,
@@ -254,7 +264,7 @@ fn find_pyenv_envs() {
             ".pyenv/versions/3.12.1/bin/python",
         ])),
         kind: Some(PythonEnvironmentKind::Pyenv),
-        version: Some("3.12.1".to_string()),
+        version: Some("3.12.1".parse().unwrap()),
         prefix: Some(resolve_test_path(&[
             home.to_str().unwrap(),
             ".pyenv/versions/3.12.1",
@@ -266,6 +276,11 @@ fn find_pyenv_envs() {
             ".pyenv/versions/3.12.1/bin/python",
         ])]),
         error: None,
+        external_kind: None,
+        platform_tags: None,
+        base_environment: None,
+        script: None,
+        tags: None,
     };
     let expected_3_13_dev = PythonEnvironment {
         display_name: None,
@@ -276,7 +291,7 @@ fn find_pyenv_envs() {
             ".pyenv/versions/3.13-dev/bin/python",
         ])),
         kind: Some(PythonEnvironmentKind::Pyenv),
-        version: Some("3.13-dev".to_string()),
+        version: Some("3.13-dev".parse().unwrap()),
         prefix: Some(resolve_test_path(&[
             home.to_str().unwrap(),
             ".pyenv/versions/3.13-dev",
@@ -288,6 +303,11 @@ fn find_pyenv_envs() {
             ".pyenv/versions/3.13-dev/bin/python",
         ])]),
         error: None,
+        external_kind: None,
+        platform_tags: None,
+        base_environment: None,
+        script: None,
+        tags: None,
     };
     let expected_3_12_1a3 = PythonEnvironment {
         display_name: None,
@@ -298,7 +318,7 @@ fn find_pyenv_envs() {
             ".pyenv/versions/3.12.1a3/bin/python",
         ])),
         kind: Some(PythonEnvironmentKind::Pyenv),
-        version: Some("3.12.1a3".to_string()),
+        version: Some("3.12.1a3".parse().unwrap()),
         prefix: Some(resolve_test_path(&[
             home.to_str().unwrap(),
             ".pyenv/versions/3.12.1a3",
@@ -310,6 +330,11 @@ fn find_pyenv_envs() {
             ".pyenv/versions/3.12.1a3/bin/python",
         ])]),
         error: None,
+        external_kind: None,
+        platform_tags: None,
+        base_environment: None,
+        script: None,
+        tags: None,
     };
     let expected_no_gil = PythonEnvironment {
         display_name: None,
@@ -320,7 +345,7 @@ fn find_pyenv_envs() {
             ".pyenv/versions/nogil-3.9.10-1/bin/python",
         ])),
         kind: Some(PythonEnvironmentKind::Pyenv),
-        version: Some("3.9.10".to_string()),
+        version: Some("3.9.10".parse().unwrap()),
         prefix: Some(resolve_test_path(&[
             home.to_str().unwrap(),
             ".pyenv/versions/nogil-3.9.10-1",
@@ -332,6 +357,11 @@ fn find_pyenv_envs() {
             ".pyenv/versions/nogil-3.9.10-1/bin/python",
         ])]),
         error: None,
+        external_kind: None,
+        platform_tags: None,
+        base_environment: None,
+        script: None,
+        tags: None,
     };
     let expected_pypy = PythonEnvironment {
         display_name: None,
@@ -342,7 +372,7 @@ fn find_pyenv_envs() {
             ".pyenv/versions/pypy3.9-7.3.15/bin/python",
         ])),
         kind: Some(PythonEnvironmentKind::Pyenv),
-        version: Some("3.9.18".to_string()),
+        version: Some("3.9.18".parse().unwrap()),
         prefix: Some(resolve_test_path(&[
             home.to_str().unwrap(),
             ".pyenv/versions/pypy3.9-7.3.15",
@@ -354,6 +384,11 @@ fn find_pyenv_envs() {
             ".pyenv/versions/pypy3.9-7.3.15/bin/python",
         ])]),
         error: None,
+        external_kind: None,
+        platform_tags: None,
+        base_environment: None,
+        script: None,
+        tags: None,
     };
 
     let expected_conda_root = PythonEnvironment {
@@ -362,12 +397,17 @@ fn find_pyenv_envs() {
         name: Some("base".to_string()),
         executable: Some(conda_dir.join("bin").join("python")),
         kind: Some(PythonEnvironmentKind::Conda),
-        version: Some("3.11.5".to_string()),
+        version: Some("3.11.5".parse().unwrap()),
         prefix: Some(conda_dir.clone()),
         manager: Some(expected_conda_manager.clone()),
         arch: Some(Architecture::X64),
         symlinks: Some(vec![conda_dir.join("bin").join("python")]),
         error: None,
+        external_kind: None,
+        platform_tags: None,
+        base_environment: None,
+        script: None,
+        tags: None,
     };
     let expected_conda_one = PythonEnvironment {
         display_name: None,
@@ -375,12 +415,17 @@ fn find_pyenv_envs() {
         name: Some("one".to_string()),
         executable: Some(conda_dir.join("envs").join("one").join("python")),
         kind: Some(PythonEnvironmentKind::Conda),
-        version: Some("3.11.1".to_string()),
+        version: Some("3.11.1".parse().unwrap()),
         prefix: Some(conda_dir.join("envs").join("one")),
         manager: Some(expected_conda_manager.clone()),
         arch: None,
         symlinks: Some(vec![conda_dir.join("envs").join("one").join("python")]),
         error: None,
+        external_kind: None,
+        platform_tags: None,
+        base_environment: None,
+        script: None,
+        tags: None,
     };
     let expected_conda_two = PythonEnvironment {
         display_name: None,
@@ -388,12 +433,17 @@ fn find_pyenv_envs() {
         name: Some("two".to_string()),
         executable: Some(conda_dir.join("envs").join("two").join("python")),
         kind: Some(PythonEnvironmentKind::Conda),
-        version: Some("3.11.1".to_string()),
+        version: Some("3.11.1".parse().unwrap()),
         prefix: Some(conda_dir.join("envs").join("two")),
         manager: Some(expected_conda_manager.clone()),
         symlinks: Some(vec![conda_dir.join("envs").join("two").join("python")]),
         arch: None,
         error: None,
+        external_kind: None,
+        platform_tags: None,
+        base_environment: None,
+        script: None,
+        tags: None,
     };
 
     let mut expected_envs = vec![
@@ -455,7 +505,7 @@ fn resolve_pyenv_environment() {
         name: None,
         executable: Some(executable.clone()),
         kind: Some(PythonEnvironmentKind::Pyenv),
-        version: Some("3.9.9".to_string()),
+        version: Some("3.9.9".parse().unwrap()),
         prefix: Some(resolve_test_path(&[
             home.to_str().unwrap(),
             ".pyenv/versions/3.9.9",
@@ -464,6 +514,11 @@ fn resolve_pyenv_environment() {
         arch: None,
         symlinks: Some(vec![executable]),
         error: None,
+        external_kind: None,
+        platform_tags: None,
+        base_environment: None,
+        script: None,
+        tags: None,
     };
     let expected_virtual_env = PythonEnvironment {
         display_name: None,
@@ -474,7 +529,7 @@ fn resolve_pyenv_environment() {
             ".pyenv/versions/my-virtual-env/bin/python",
         ])),
         kind: Some(PythonEnvironmentKind::PyenvVirtualEnv),
-        version: Some("3.10.13".to_string()),
+        version: Some("3.10.13".parse().unwrap()),
         prefix: Some(resolve_test_path(&[
             home.to_str().unwrap(),
             ".pyenv/versions/my-virtual-env",
@@ -486,6 +541,11 @@ fn resolve_pyenv_environment() {
             ".pyenv/versions/my-virtual-env/bin/python",
         ])]),
         error: None,
+        external_kind: None,
+        platform_tags: None,
+        base_environment: None,
+        script: None,
+        tags: None,
     };
 
     // Resolve regular Python installs in Pyenv
@@ -588,3 +648,73 @@ fn pyenv_refresh_state_self_hydrates_without_sync() {
     assert_eq!(resolved.kind, Some(PythonEnvironmentKind::Pyenv));
     assert_eq!(resolved.prefix, Some(prefix));
 }
+
+#[test]
+#[cfg(unix)]
+fn pyenv_shims_are_reported_as_symlinks_of_the_global_version() {
+    use crate::common::create_test_environment;
+    use pet_conda::Conda;
+    use pet_core::{env::PythonEnv, python_environment::PythonEnvironmentKind, Locator};
+    use pet_pyenv::PyEnv;
+    use pet_reporter::{cache::CacheReporter, collect};
+    use std::{collections::HashMap, fs, sync::Arc};
+
+    let home = tempfile::tempdir().unwrap();
+    let pyenv_root = home.path().join(".pyenv");
+    for version in ["3.11.9", "3.12.1"] {
+        let bin = pyenv_root.join("versions").join(version).join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        fs::write(bin.join("python"), "").unwrap();
+        fs::write(bin.join("python3"), "").unwrap();
+    }
+    let shims = pyenv_root.join("shims");
+    fs::create_dir_all(&shims).unwrap();
+    fs::write(shims.join("python"), "").unwrap();
+    fs::write(shims.join("python3"), "").unwrap();
+    fs::write(pyenv_root.join("version"), "3.12.1\n").unwrap();
+
+    let environment = create_test_environment(
+        HashMap::new(),
+        Some(home.path().to_path_buf()),
+        vec![],
+        None,
+    );
+    let conda = Arc::new(Conda::from(&environment));
+    let locator = PyEnv::from(&environment, conda);
+    let reporter = Arc::new(collect::create_reporter());
+    locator.find(&CacheReporter::new(reporter.clone()));
+
+    let environments = reporter.environments.lock().unwrap().clone();
+    let global = environments
+        .iter()
+        .find(|env| env.prefix == Some(pyenv_root.join("versions").join("3.12.1")))
+        .unwrap();
+    assert_eq!(
+        global.executable,
+        Some(pyenv_root.join("versions/3.12.1/bin/python"))
+    );
+    let symlinks = global.symlinks.clone().unwrap();
+    assert!(symlinks.contains(&shims.join("python")));
+    assert!(symlinks.contains(&shims.join("python3")));
+
+    let other = environments
+        .iter()
+        .find(|env| env.prefix == Some(pyenv_root.join("versions").join("3.11.9")))
+        .unwrap();
+    assert!(!other
+        .symlinks
+        .clone()
+        .unwrap()
+        .contains(&shims.join("python")));
+
+    // Identified without a prefix, i.e. without spawning Python.
+    let env = locator
+        .try_from(&PythonEnv::new(
+            pyenv_root.join("versions/3.11.9/bin/python"),
+            None,
+            None,
+        ))
+        .unwrap();
+    assert_eq!(env.kind, Some(PythonEnvironmentKind::Pyenv));
+    assert_eq!(env.prefix, Some(pyenv_root.join("versions").join("3.11.9")));
+}
@@ -0,0 +1,324 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use log::trace;
+use pet_core::arch::Architecture;
+use std::{fs::File, io::Read, path::Path};
+
+/// Number of bytes read from the start of the executable.
+/// Large enough for the ELF & Mach-O headers and the PE header of the executables we know of.
+const HEADER_SIZE: usize = 1024;
+
+// ELF machine types (`e_machine`).
+const EM_386: u16 = 0x03;
+const EM_ARM: u16 = 0x28;
+const EM_PPC64: u16 = 0x15;
+const EM_S390: u16 = 0x16;
+const EM_X86_64: u16 = 0x3e;
+const EM_AARCH64: u16 = 0xb7;
+const EM_RISCV: u16 = 0xf3;
+
+// PE machine types (`IMAGE_FILE_MACHINE_*`).
+const IMAGE_FILE_MACHINE_I386: u16 = 0x014c;
+const IMAGE_FILE_MACHINE_ARMNT: u16 = 0x01c4;
+const IMAGE_FILE_MACHINE_AMD64: u16 = 0x8664;
+const IMAGE_FILE_MACHINE_ARM64: u16 = 0xaa64;
+
+// Mach-O cpu types.
+const CPU_TYPE_X86: u32 = 7;
+const CPU_TYPE_X86_64: u32 = 0x0100_0007;
+const CPU_TYPE_ARM: u32 = 12;
+const CPU_TYPE_ARM64: u32 = 0x0100_000c;
+
+/// Gets the architecture of an executable from its ELF, PE or Mach-O header,
+/// without having to spawn it. Symlinks are followed.
+pub fn get_architecture(executable: &Path) -> Option<Architecture> {
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    File::open(executable)
+        .ok()?
+        .take(HEADER_SIZE as u64)
+        .read_to_end(&mut header)
+        .ok()?;
+    let arch = parse_architecture(&header);
+    trace!("Architecture of {:?} is {:?}", executable, arch);
+    arch
+}
+
+/// Gets the architecture from the first bytes of an ELF, PE or Mach-O binary.
+pub fn parse_architecture(header: &[u8]) -> Option<Architecture> {
+    match header.get(0..4)? {
+        [0x7f, b'E', b'L', b'F'] => parse_elf(header),
+        [b'M', b'Z', ..] => parse_pe(header),
+        [0xca, 0xfe, 0xba, 0xbe] | [0xca, 0xfe, 0xba, 0xbf] => parse_fat_mach_o(header),
+        [0xce, 0xfa, 0xed, 0xfe] | [0xcf, 0xfa, 0xed, 0xfe] => {
+            mach_o_architecture(read_u32(header, 4, Endian::Little)?)
+        }
+        [0xfe, 0xed, 0xfa, 0xce] | [0xfe, 0xed, 0xfa, 0xcf] => {
+            mach_o_architecture(read_u32(header, 4, Endian::Big)?)
+        }
+        _ => None,
+    }
+}
+
+fn parse_elf(header: &[u8]) -> Option<Architecture> {
+    let is_64_bit = *header.get(4)? == 2;
+    let endian = match header.get(5)? {
+        1 => Endian::Little,
+        2 => Endian::Big,
+        _ => return None,
+    };
+    match read_u16(header, 18, endian)? {
+        EM_X86_64 => Some(Architecture::X64),
+        EM_386 => Some(Architecture::X86),
+        EM_AARCH64 => Some(Architecture::Arm64),
+        EM_ARM => Some(Architecture::Arm),
+        EM_PPC64 if endian == Endian::Little => Some(Architecture::Ppc64le),
+        EM_S390 if is_64_bit => Some(Architecture::S390x),
+        EM_RISCV if is_64_bit => Some(Architecture::Riscv64),
+        _ => None,
+    }
+}
+
+fn parse_pe(header: &[u8]) -> Option<Architecture> {
+    let pe_offset = read_u32(header, 0x3c, Endian::Little)? as usize;
+    if header.get(pe_offset..pe_offset + 4)? != b"PE\0\0" {
+        return None;
+    }
+    match read_u16(header, pe_offset + 4, Endian::Little)? {
+        IMAGE_FILE_MACHINE_AMD64 => Some(Architecture::X64),
+        IMAGE_FILE_MACHINE_I386 => Some(Architecture::X86),
+        IMAGE_FILE_MACHINE_ARM64 => Some(Architecture::Arm64),
+        IMAGE_FILE_MACHINE_ARMNT => Some(Architecture::Arm),
+        _ => None,
+    }
+}
+
+/// Universal binaries contain code for a number of architectures.
+fn parse_fat_mach_o(header: &[u8]) -> Option<Architecture> {
+    let is_64_bit = header[3] == 0xbf;
+    let count = read_u32(header, 4, Endian::Big)? as usize;
+    // Java class files share the magic number, with the version in place of the count.
+    if count == 0 || count >= 45 {
+        return None;
+    }
+    let entry_size = if is_64_bit { 32 } else { 20 };
+    let architectures = (0..count)
+        .filter_map(|index| read_u32(header, 8 + index * entry_size, Endian::Big))
+        .filter_map(mach_o_architecture)
+        .collect::<Vec<_>>();
+    if architectures.contains(&Architecture::X64) && architectures.contains(&Architecture::Arm64) {
+        Some(Architecture::Universal2)
+    } else {
+        architectures.into_iter().next()
+    }
+}
+
+fn mach_o_architecture(cpu_type: u32) -> Option<Architecture> {
+    match cpu_type {
+        CPU_TYPE_X86_64 => Some(Architecture::X64),
+        CPU_TYPE_X86 => Some(Architecture::X86),
+        CPU_TYPE_ARM64 => Some(Architecture::Arm64),
+        CPU_TYPE_ARM => Some(Architecture::Arm),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Endian {
+    Little,
+    Big,
+}
+
+pub(crate) fn read_u16(bytes: &[u8], offset: usize, endian: Endian) -> Option<u16> {
+    let bytes: [u8; 2] = bytes.get(offset..offset + 2)?.try_into().ok()?;
+    Some(match endian {
+        Endian::Little => u16::from_le_bytes(bytes),
+        Endian::Big => u16::from_be_bytes(bytes),
+    })
+}
+
+pub(crate) fn read_u32(bytes: &[u8], offset: usize, endian: Endian) -> Option<u32> {
+    let bytes: [u8; 4] = bytes.get(offset..offset + 4)?.try_into().ok()?;
+    Some(match endian {
+        Endian::Little => u32::from_le_bytes(bytes),
+        Endian::Big => u32::from_be_bytes(bytes),
+    })
+}
+
+pub(crate) fn read_u64(bytes: &[u8], offset: usize, endian: Endian) -> Option<u64> {
+    let bytes: [u8; 8] = bytes.get(offset..offset + 8)?.try_into().ok()?;
+    Some(match endian {
+        Endian::Little => u64::from_le_bytes(bytes),
+        Endian::Big => u64::from_be_bytes(bytes),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Smallest ELF header with the given class, data encoding & machine.
+    fn elf(class: u8, data: u8, machine: u16) -> Vec<u8> {
+        let mut header = vec![0; 64];
+        header[0..4].copy_from_slice(b"\x7fELF");
+        header[4] = class;
+        header[5] = data;
+        let machine = if data == 1 {
+            machine.to_le_bytes()
+        } else {
+            machine.to_be_bytes()
+        };
+        header[18..20].copy_from_slice(&machine);
+        header
+    }
+
+    /// DOS stub pointing to a PE header at 0x80.
+    fn pe(machine: u16) -> Vec<u8> {
+        let mut header = vec![0; 0x100];
+        header[0..2].copy_from_slice(b"MZ");
+        header[0x3c..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        header[0x80..0x84].copy_from_slice(b"PE\0\0");
+        header[0x84..0x86].copy_from_slice(&machine.to_le_bytes());
+        header
+    }
+
+    fn mach_o(cpu_type: u32) -> Vec<u8> {
+        let mut header = vec![0; 32];
+        header[0..4].copy_from_slice(&0xfeedfacfu32.to_le_bytes());
+        header[4..8].copy_from_slice(&cpu_type.to_le_bytes());
+        header
+    }
+
+    fn fat_mach_o(cpu_types: &[u32]) -> Vec<u8> {
+        let mut header = vec![];
+        header.extend(0xcafebabeu32.to_be_bytes());
+        header.extend((cpu_types.len() as u32).to_be_bytes());
+        for cpu_type in cpu_types {
+            header.extend(cpu_type.to_be_bytes());
+            header.extend([0; 16]);
+        }
+        header
+    }
+
+    #[test]
+    fn elf_machine_types() {
+        assert_eq!(
+            parse_architecture(&elf(2, 1, EM_X86_64)),
+            Some(Architecture::X64)
+        );
+        assert_eq!(
+            parse_architecture(&elf(1, 1, EM_386)),
+            Some(Architecture::X86)
+        );
+        assert_eq!(
+            parse_architecture(&elf(2, 1, EM_AARCH64)),
+            Some(Architecture::Arm64)
+        );
+        assert_eq!(
+            parse_architecture(&elf(1, 1, EM_ARM)),
+            Some(Architecture::Arm)
+        );
+        assert_eq!(
+            parse_architecture(&elf(2, 1, EM_PPC64)),
+            Some(Architecture::Ppc64le)
+        );
+        assert_eq!(
+            parse_architecture(&elf(2, 2, EM_S390)),
+            Some(Architecture::S390x)
+        );
+        assert_eq!(
+            parse_architecture(&elf(2, 1, EM_RISCV)),
+            Some(Architecture::Riscv64)
+        );
+    }
+
+    #[test]
+    fn elf_variants_without_a_matching_architecture() {
+        // Big endian ppc64, 31-bit s390 & 32-bit RISC-V.
+        assert_eq!(parse_architecture(&elf(2, 2, EM_PPC64)), None);
+        assert_eq!(parse_architecture(&elf(1, 2, EM_S390)), None);
+        assert_eq!(parse_architecture(&elf(1, 1, EM_RISCV)), None);
+    }
+
+    #[test]
+    fn pe_machine_types() {
+        assert_eq!(
+            parse_architecture(&pe(IMAGE_FILE_MACHINE_AMD64)),
+            Some(Architecture::X64)
+        );
+        assert_eq!(
+            parse_architecture(&pe(IMAGE_FILE_MACHINE_I386)),
+            Some(Architecture::X86)
+        );
+        assert_eq!(
+            parse_architecture(&pe(IMAGE_FILE_MACHINE_ARM64)),
+            Some(Architecture::Arm64)
+        );
+        assert_eq!(
+            parse_architecture(&pe(IMAGE_FILE_MACHINE_ARMNT)),
+            Some(Architecture::Arm)
+        );
+    }
+
+    #[test]
+    fn pe_header_out_of_range() {
+        let mut header = pe(IMAGE_FILE_MACHINE_AMD64);
+        header[0x3c..0x40].copy_from_slice(&0x1000u32.to_le_bytes());
+        assert_eq!(parse_architecture(&header), None);
+    }
+
+    #[test]
+    fn mach_o_cpu_types() {
+        assert_eq!(
+            parse_architecture(&mach_o(CPU_TYPE_X86_64)),
+            Some(Architecture::X64)
+        );
+        assert_eq!(
+            parse_architecture(&mach_o(CPU_TYPE_ARM64)),
+            Some(Architecture::Arm64)
+        );
+    }
+
+    #[test]
+    fn fat_mach_o_with_intel_and_arm_is_universal2() {
+        assert_eq!(
+            parse_architecture(&fat_mach_o(&[CPU_TYPE_X86_64, CPU_TYPE_ARM64])),
+            Some(Architecture::Universal2)
+        );
+        assert_eq!(
+            parse_architecture(&fat_mach_o(&[CPU_TYPE_ARM64])),
+            Some(Architecture::Arm64)
+        );
+    }
+
+    #[test]
+    fn java_class_file_is_not_a_fat_mach_o() {
+        let mut header = vec![0xca, 0xfe, 0xba, 0xbe];
+        // Minor & major version (Java 8).
+        header.extend([0, 0, 0, 52]);
+        header.extend([0; 32]);
+        assert_eq!(parse_architecture(&header), None);
+    }
+
+    #[test]
+    fn unknown_and_truncated_headers() {
+        assert_eq!(parse_architecture(b""), None);
+        assert_eq!(parse_architecture(b"#!/bin/sh\n"), None);
+        assert_eq!(parse_architecture(b"\x7fELF"), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn get_architecture_follows_symlinks() {
+        let tmp = TempDir::new().unwrap();
+        let executable = tmp.path().join("python3.12");
+        fs::write(&executable, elf(2, 1, EM_AARCH64)).unwrap();
+        let symlink = tmp.path().join("python");
+        std::os::unix::fs::symlink(&executable, &symlink).unwrap();
+
+        assert_eq!(get_architecture(&symlink), Some(Architecture::Arm64));
+        assert_eq!(get_architecture(&tmp.path().join("missing")), None);
+    }
+}
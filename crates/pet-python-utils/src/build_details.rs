@@ -17,12 +17,11 @@
 
 use lazy_static::lazy_static;
 use log::{trace, warn};
+use pet_core::python_version::PythonVersion;
+use pet_fs::file_system::{FileSystem, OsFileSystem};
 use regex::Regex;
 use serde::Deserialize;
-use std::{
-    fs,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
 const BUILD_DETAILS_FILE: &str = "build-details.json";
 
@@ -49,6 +48,8 @@ pub struct BuildDetails {
     pub language: Language,
     #[serde(default)]
     pub implementation: Option<Implementation>,
+    #[serde(default)]
+    pub abi: Option<Abi>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -63,6 +64,16 @@ pub struct Implementation {
     pub name: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct Abi {
+    /// ABI flags, e.g. `["t"]` for free-threaded builds.
+    #[serde(default)]
+    pub flags: Vec<String>,
+    /// E.g. `".cpython-314-x86_64-linux-gnu.so"`.
+    #[serde(default)]
+    pub extension_suffix: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct VersionInfo {
     pub major: u64,
@@ -86,15 +97,24 @@ impl BuildDetails {
     /// `(major, minor)` matches the supplied hint. Useful when callers already
     /// know the expected version (e.g. from `pyvenv.cfg`).
     pub fn find_with_hint(prefix: &Path, pyver: Option<(u64, u64)>) -> Option<Self> {
-        let file = find_file(prefix, pyver)?;
-        parse(&file)
+        Self::find_with_hint_in(&OsFileSystem, prefix, pyver)
+    }
+
+    /// Same as [`Self::find_with_hint`], looking at the files in the given file system.
+    pub fn find_with_hint_in(
+        file_system: &dyn FileSystem,
+        prefix: &Path,
+        pyver: Option<(u64, u64)>,
+    ) -> Option<Self> {
+        let file = find_file(file_system, prefix, pyver)?;
+        parse(file_system, &file)
     }
 
-    /// Returns a CPython-style version string built from `version_info`.
+    /// Returns the CPython-style version built from `version_info`.
     ///
     /// Examples: `"3.14.0"`, `"3.14.0a0"`, `"3.14.0b1"`, `"3.14.0rc2"`.
     /// This matches the `PY_VERSION` literal that `patchlevel.h` exposes.
-    pub fn version_string(&self) -> String {
+    pub fn version(&self) -> Option<PythonVersion> {
         let v = &self.language.version_info;
         let suffix = match v.releaselevel.as_str() {
             "alpha" => format!("a{}", v.serial),
@@ -112,16 +132,20 @@ impl BuildDetails {
                 String::new()
             }
         };
-        format!("{}.{}.{}{}", v.major, v.minor, v.micro, suffix)
+        PythonVersion::parse(&format!("{}.{}.{}{}", v.major, v.minor, v.micro, suffix))
     }
 }
 
-fn find_file(prefix: &Path, pyver: Option<(u64, u64)>) -> Option<PathBuf> {
+fn find_file(
+    file_system: &dyn FileSystem,
+    prefix: &Path,
+    pyver: Option<(u64, u64)>,
+) -> Option<PathBuf> {
     let prefix = strip_bin(prefix);
 
     // Windows-style: <prefix>/Lib/build-details.json
     let win_path = prefix.join("Lib").join(BUILD_DETAILS_FILE);
-    if win_path.is_file() {
+    if file_system.is_file(&win_path) {
         return Some(win_path);
     }
 
@@ -135,7 +159,7 @@ fn find_file(prefix: &Path, pyver: Option<(u64, u64)>) -> Option<PathBuf> {
                 let candidate = lib_dir
                     .join(format!("{impl_prefix}{major}.{minor}{suffix}"))
                     .join(BUILD_DETAILS_FILE);
-                if candidate.is_file() {
+                if file_system.is_file(&candidate) {
                     return Some(candidate);
                 }
             }
@@ -145,11 +169,10 @@ fn find_file(prefix: &Path, pyver: Option<(u64, u64)>) -> Option<PathBuf> {
     // Slow path: enumerate `lib/` and pick deterministically (highest
     // `(major, minor)`) so multi-version prefixes don't depend on `read_dir`
     // iteration order.
-    let entries = fs::read_dir(&lib_dir).ok()?;
+    let entries = file_system.read_dir(&lib_dir).ok()?;
     let mut best: Option<(u64, u64, PathBuf)> = None;
-    for entry in entries.filter_map(Result::ok) {
-        let path = entry.path();
-        if !path.is_dir() {
+    for path in entries {
+        if !file_system.is_dir(&path) {
             continue;
         }
         let name = match path.file_name().and_then(|n| n.to_str()) {
@@ -161,7 +184,7 @@ fn find_file(prefix: &Path, pyver: Option<(u64, u64)>) -> Option<PathBuf> {
             None => continue,
         };
         let candidate = path.join(BUILD_DETAILS_FILE);
-        if !candidate.is_file() {
+        if !file_system.is_file(&candidate) {
             continue;
         }
         // `unwrap()`s here are safe: the regex guarantees both groups are
@@ -189,8 +212,8 @@ fn strip_bin(prefix: &Path) -> PathBuf {
     prefix.to_path_buf()
 }
 
-fn parse(file: &Path) -> Option<BuildDetails> {
-    let contents = match fs::read_to_string(file) {
+fn parse(file_system: &dyn FileSystem, file: &Path) -> Option<BuildDetails> {
+    let contents = match file_system.read_to_string(file) {
         Ok(c) => c,
         Err(e) => {
             warn!("Failed to read {} at {:?}: {}", BUILD_DETAILS_FILE, file, e);
@@ -228,6 +251,7 @@ fn is_supported_schema(version: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
     use std::io::Write;
     use tempfile::tempdir;
 
@@ -301,7 +325,7 @@ mod tests {
         let bd = BuildDetails::find(prefix).expect("should find file");
         assert_eq!(bd.schema_version, "1.0");
         assert_eq!(bd.language.version, "3.14");
-        assert_eq!(bd.version_string(), "3.14.1");
+        assert_eq!(bd.version().unwrap().to_string(), "3.14.1");
         assert_eq!(
             bd.implementation.as_ref().map(|i| i.name.as_str()),
             Some("cpython")
@@ -315,7 +339,7 @@ mod tests {
         write(&prefix.join("Lib").join("build-details.json"), SAMPLE_FINAL);
 
         let bd = BuildDetails::find(prefix).expect("should find file");
-        assert_eq!(bd.version_string(), "3.14.1");
+        assert_eq!(bd.version().unwrap().to_string(), "3.14.1");
     }
 
     #[test]
@@ -331,7 +355,7 @@ mod tests {
         );
 
         let bd = BuildDetails::find(&prefix.join("bin")).expect("should find via bin");
-        assert_eq!(bd.version_string(), "3.14.1");
+        assert_eq!(bd.version().unwrap().to_string(), "3.14.1");
     }
 
     #[test]
@@ -341,11 +365,11 @@ mod tests {
         write(&prefix.join("Lib").join("build-details.json"), SAMPLE_FINAL);
 
         let bd = BuildDetails::find(&prefix.join("Scripts")).expect("should find via Scripts");
-        assert_eq!(bd.version_string(), "3.14.1");
+        assert_eq!(bd.version().unwrap().to_string(), "3.14.1");
     }
 
     #[test]
-    fn version_string_handles_pre_releases() {
+    fn version_handles_pre_releases() {
         let dir = tempdir().unwrap();
         let prefix = dir.path();
         write(
@@ -357,7 +381,7 @@ mod tests {
         );
 
         let bd = BuildDetails::find(prefix).unwrap();
-        assert_eq!(bd.version_string(), "3.14.0a0");
+        assert_eq!(bd.version().unwrap().to_string(), "3.14.0a0");
     }
 
     #[test]
@@ -436,9 +460,9 @@ mod tests {
         );
 
         let bd = BuildDetails::find_with_hint(prefix, Some((3, 14))).unwrap();
-        assert_eq!(bd.version_string(), "3.14.0");
+        assert_eq!(bd.version().unwrap().to_string(), "3.14.0");
         let bd = BuildDetails::find_with_hint(prefix, Some((3, 10))).unwrap();
-        assert_eq!(bd.version_string(), "3.10.0");
+        assert_eq!(bd.version().unwrap().to_string(), "3.10.0");
     }
 
     #[test]
@@ -470,7 +494,7 @@ mod tests {
         );
 
         let bd = BuildDetails::find(prefix).unwrap();
-        assert_eq!(bd.version_string(), "3.15.0");
+        assert_eq!(bd.version().unwrap().to_string(), "3.15.0");
     }
 
     #[test]
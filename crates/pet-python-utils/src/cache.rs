@@ -11,6 +11,8 @@ use std::{
     time::SystemTime,
 };
 
+use pet_fs::file_system::FileSystem;
+
 use crate::{
     env::ResolvedPythonEnv,
     fs_cache::{delete_cache_file, get_cache_from_file, store_cache_in_file},
@@ -31,7 +33,16 @@ pub fn clear_cache() -> io::Result<()> {
 }
 
 pub fn create_cache(executable: PathBuf) -> Arc<Mutex<Box<dyn CacheEntry>>> {
-    CACHE.create_cache(executable)
+    CACHE.create_cache(CACHE.get_cache_directory(), executable)
+}
+
+/// Same as `create_cache`, in the cache directory of the file system if it has one of its own.
+pub fn create_cache_in(file_system: &dyn FileSystem, executable: PathBuf) -> LockableCacheEntry {
+    let cache_directory = match file_system.cache_directory() {
+        Some(cache_directory) => Some(cache_directory.to_path_buf()),
+        None => CACHE.get_cache_directory(),
+    };
+    CACHE.create_cache(cache_directory, executable)
 }
 
 pub fn get_cache_directory() -> Option<PathBuf> {
@@ -48,14 +59,15 @@ pub type LockableCacheEntry = Arc<Mutex<Box<dyn CacheEntry>>>;
 /// Uses in memory cache as well as a file cache as backing store.
 struct CacheImpl {
     cache_dir: Arc<Mutex<Option<PathBuf>>>,
-    locks: Mutex<HashMap<PathBuf, LockableCacheEntry>>,
+    /// Entries by cache directory & executable, as finders can use cache directories of their own.
+    locks: Mutex<HashMap<(Option<PathBuf>, PathBuf), LockableCacheEntry>>,
 }
 
 impl CacheImpl {
     fn new(cache_dir: Option<PathBuf>) -> CacheImpl {
         CacheImpl {
             cache_dir: Arc::new(Mutex::new(cache_dir)),
-            locks: Mutex::new(HashMap::<PathBuf, LockableCacheEntry>::new()),
+            locks: Mutex::new(HashMap::new()),
         }
     }
 
@@ -101,21 +113,20 @@ impl CacheImpl {
             Ok(())
         }
     }
-    fn create_cache(&self, executable: PathBuf) -> LockableCacheEntry {
-        let cache_directory = self
-            .cache_dir
-            .lock()
-            .expect("cache_dir mutex poisoned")
-            .clone();
+    fn create_cache(
+        &self,
+        cache_directory: Option<PathBuf>,
+        executable: PathBuf,
+    ) -> LockableCacheEntry {
         match self
             .locks
             .lock()
             .expect("locks mutex poisoned")
-            .entry(executable.clone())
+            .entry((cache_directory.clone(), executable.clone()))
         {
             Entry::Occupied(lock) => lock.get().clone(),
             Entry::Vacant(lock) => {
-                let cache = Box::new(CacheEntryImpl::create(cache_directory.clone(), executable))
+                let cache = Box::new(CacheEntryImpl::create(cache_directory, executable))
                     as Box<dyn CacheEntry + 'static>;
                 lock.insert(Arc::new(Mutex::new(cache))).clone()
             }
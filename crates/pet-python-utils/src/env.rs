@@ -2,26 +2,171 @@
 // Licensed under the MIT License.
 
 use log::{error, trace, warn};
-use pet_core::{arch::Architecture, env::PythonEnv, python_environment::PythonEnvironment};
+use pet_core::{
+    arch::Architecture, env::PythonEnv, python_environment::PythonEnvironment,
+    python_version::PythonVersion,
+};
+use pet_fs::file_system::{FileSystem, OsFileSystem};
+use pet_shims::get_shim_kind;
 use serde::{Deserialize, Serialize};
 use std::{
+    ffi::OsString,
     path::{Path, PathBuf},
-    process::Stdio,
+    process::{Command, ExitStatus, Stdio},
+    sync::atomic::{AtomicU64, Ordering},
     thread,
     time::{Duration, Instant, SystemTime},
 };
 
-use crate::{cache::create_cache, executable::new_silent_command};
+use crate::{
+    arch::get_architecture,
+    cache::{create_cache, create_cache_in},
+    executable::new_silent_command,
+};
 
 const PYTHON_INFO_JSON_SEPARATOR: &str = "093385e9-59f7-4a16-a604-14bf206256fe";
-const PYTHON_INFO_CMD:&str = "import json, sys; print('093385e9-59f7-4a16-a604-14bf206256fe');print(json.dumps({'version': '.'.join(str(n) for n in sys.version_info), 'sys_prefix': sys.prefix, 'executable': sys.executable, 'is64_bit': sys.maxsize > 2**32}))";
+// Python is spawned with `-S` (so `site`, `sitecustomize` & `.pth` files are not run), hence
+// the `sys.prefix` of a virtual env is computed here the same way `site.venv()` does it
+// (only required for Python 3.10 and earlier, later versions do this at startup).
+// The current directory is removed from `sys.path`, so that a `json.py` in there cannot be imported.
+const PYTHON_INFO_CMD: &str = concat!(
+    "import sys; sys.path = [p for p in sys.path if p not in ('', '.')]; import json, os; ",
+    "exe_dir = os.path.dirname(os.path.abspath(sys.executable)); ",
+    "venv = any(os.path.isfile(os.path.join(d, 'pyvenv.cfg')) for d in (exe_dir, os.path.dirname(exe_dir))); ",
+    "print('093385e9-59f7-4a16-a604-14bf206256fe');",
+    "print(json.dumps({'version': '.'.join(str(n) for n in sys.version_info), ",
+    "'sys_prefix': os.path.dirname(exe_dir) if venv else sys.prefix, ",
+    "'executable': sys.executable, 'is64_bit': sys.maxsize > 2**32}))"
+);
+
+/// Arguments used to spawn Python in isolated mode.
+/// `-I` is not used, as Python 2 does not support it; `-E -s` is what `-I` implies.
+/// * `-E` ignores all `PYTHON*` environment variables.
+/// * `-s` does not add the user site-packages directory to `sys.path`.
+/// * `-S` does not import `site` (nor `sitecustomize`), so nothing from the environment runs.
+const ISOLATION_ARGS: [&str; 3] = ["-E", "-s", "-S"];
+
+/// Environment variables passed through to the spawned Python, everything else is scrubbed.
+/// These are required to start a process & load shared libraries on all platforms.
+const SPAWN_ENV_VARS: [&str; 15] = [
+    "PATH",
+    "PATHEXT",
+    "HOME",
+    "USERPROFILE",
+    "SYSTEMROOT",
+    "SYSTEMDRIVE",
+    "WINDIR",
+    "COMSPEC",
+    "TEMP",
+    "TMP",
+    "TMPDIR",
+    "LD_LIBRARY_PATH",
+    "DYLD_LIBRARY_PATH",
+    "LANG",
+    "LC_ALL",
+];
+
+/// Prefixes of the environment variables also passed through to shims (e.g. `PYENV_VERSION`),
+/// as the shim managers select the interpreter to run based on them.
+const SHIM_ENV_VAR_PREFIXES: [&str; 4] = ["PYENV", "ASDF_", "MISE_", "RTX_"];
 
-/// Maximum wall-clock time to wait for a spawned Python interpreter to print
+/// Default maximum wall-clock time to wait for a spawned Python interpreter to print
 /// its info JSON before we give up and kill it. Stale cached paths on Windows
 /// (Store stubs, vanished network shares, EDR-stalled `CreateProcess`) can
 /// otherwise block `resolve` for tens to hundreds of seconds (Fixes #463).
 const RESOLVE_SPAWN_TIMEOUT: Duration = Duration::from_secs(15);
 
+/// Timeout in milliseconds configured by the client, 0 when the default is to be used.
+static SPAWN_TIMEOUT_MS: AtomicU64 = AtomicU64::new(0);
+
+/// Sets the maximum time to wait for a spawned Python to report its details.
+/// `None` restores the default of 15 seconds.
+pub fn set_spawn_timeout(timeout: Option<Duration>) {
+    SPAWN_TIMEOUT_MS.store(to_spawn_timeout_ms(timeout), Ordering::Relaxed);
+}
+
+fn to_spawn_timeout_ms(timeout: Option<Duration>) -> u64 {
+    timeout.map_or(0, |t| t.as_millis().clamp(1, u64::MAX as u128) as u64)
+}
+
+fn from_spawn_timeout_ms(millis: u64) -> Duration {
+    match millis {
+        0 => RESOLVE_SPAWN_TIMEOUT,
+        millis => Duration::from_millis(millis),
+    }
+}
+
+/// Executables of an alternate root directory (e.g. a container image) are not those of this machine,
+/// hence are never run.
+pub fn is_spawn_allowed(file_system: &dyn FileSystem) -> bool {
+    file_system.root_directory().is_none()
+}
+
+pub fn get_spawn_timeout() -> Duration {
+    from_spawn_timeout_ms(SPAWN_TIMEOUT_MS.load(Ordering::Relaxed))
+}
+
+/// Why spawning Python to get its details failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SpawnFailureKind {
+    /// Python did not report its details within the timeout & was killed.
+    Timeout,
+    /// The file does not exist, cannot be accessed or is not an executable.
+    NotExecutable,
+    /// The executable was built for an architecture that cannot run on this machine.
+    WrongArchitecture,
+    /// Python was terminated by a signal (e.g. a segmentation fault).
+    Signal,
+    /// Python exited with a non-zero exit code.
+    ExitCode,
+    /// Python ran, but its output could not be parsed.
+    BadOutput,
+    /// Spawning is disabled, e.g. when searching an alternate root directory.
+    Disabled,
+}
+
+impl std::fmt::Display for SpawnFailureKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SpawnFailureKind::Timeout => "timeout",
+                SpawnFailureKind::NotExecutable => "notExecutable",
+                SpawnFailureKind::WrongArchitecture => "wrongArchitecture",
+                SpawnFailureKind::Signal => "signal",
+                SpawnFailureKind::ExitCode => "exitCode",
+                SpawnFailureKind::BadOutput => "badOutput",
+                SpawnFailureKind::Disabled => "disabled",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpawnFailure {
+    pub kind: SpawnFailureKind,
+    pub message: String,
+}
+
+impl SpawnFailure {
+    fn new(kind: SpawnFailureKind, message: impl Into<String>) -> Self {
+        SpawnFailure {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+/// Formatted as `<code>: <message>`, this is what ends up in `PythonEnvironment.error`.
+impl std::fmt::Display for SpawnFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", self.kind, self.message)
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct InterpreterInfo {
     pub version: String,
@@ -35,7 +180,7 @@ pub struct InterpreterInfo {
 pub struct ResolvedPythonEnv {
     pub executable: PathBuf,
     pub prefix: PathBuf,
-    pub version: String,
+    pub version: PythonVersion,
     pub is64_bit: bool,
     pub symlinks: Option<Vec<PathBuf>>,
 }
@@ -50,20 +195,28 @@ impl ResolvedPythonEnv {
         env.symlinks.clone_from(&self.symlinks);
         env
     }
-    pub fn add_to_cache(&self, environment: PythonEnvironment) {
-        // Verify whether we have been given the right exe.
-        let arch = Some(if self.is64_bit {
+    /// Architecture from the header of the executable, else from the bitness reported by Python.
+    pub fn architecture(&self) -> Architecture {
+        get_architecture(&self.executable).unwrap_or(if self.is64_bit {
             Architecture::X64
         } else {
             Architecture::X86
-        });
+        })
+    }
+    pub fn add_to_cache(&self, environment: PythonEnvironment) {
+        self.add_to_cache_in(&OsFileSystem, environment)
+    }
+    /// Same as `add_to_cache`, in the cache directory of the file system if it has one of its own.
+    pub fn add_to_cache_in(&self, file_system: &dyn FileSystem, environment: PythonEnvironment) {
+        // Verify whether we have been given the right exe.
+        let arch = Some(self.architecture());
         let symlinks = environment.symlinks.clone().unwrap_or_default();
         if symlinks.contains(&self.executable)
-            && environment.version.clone().unwrap_or_default() == self.version
+            && environment.version.as_ref() == Some(&self.version)
             && environment.prefix.clone().unwrap_or_default() == self.prefix
             && environment.arch == arch
         {
-            let cache = create_cache(self.executable.clone());
+            let cache = create_cache_in(file_system, self.executable.clone());
             let entry = cache.lock().expect("cache mutex poisoned");
             entry.track_symlinks(symlinks)
         } else {
@@ -73,55 +226,115 @@ impl ResolvedPythonEnv {
             );
         }
     }
+    /// Gets the previously resolved details of the executable, without spawning Python.
+    pub fn from_cache(executable: &Path) -> Option<Self> {
+        let cache = create_cache(executable.to_path_buf());
+        let entry = cache.lock().expect("cache mutex poisoned");
+        entry.get()
+    }
     /// Given the executable path, resolve the python environment by spawning python.
     /// If we had previously spawned Python and we have the symlinks to this as well,
     /// & all of them are the same as when this exe was previously spawned,
     /// & mtime & ctimes of none of the exes (symlinks) have changed, then we can use the cached info.
-    pub fn from(
+    pub fn from(executable: &Path) -> Option<Self> {
+        Self::resolve(executable).ok()
+    }
+    /// Same as `from`, looking at the files in the given file system.
+    pub fn from_in(file_system: &dyn FileSystem, executable: &Path) -> Option<Self> {
+        Self::resolve_in(file_system, executable).ok()
+    }
+    /// Same as `from`, but with the reason why Python could not be spawned.
+    pub fn resolve(executable: &Path) -> Result<Self, SpawnFailure> {
+        Self::resolve_in(&OsFileSystem, executable)
+    }
+    /// Same as `resolve`, looking at the files in the given file system.
+    pub fn resolve_in(
+        file_system: &dyn FileSystem,
         executable: &Path,
-        // known_symlinks: &Vec<PathBuf>,
-        // cache: &dyn Cache,
-    ) -> Option<Self> {
-        let cache = create_cache(executable.to_path_buf());
+    ) -> Result<Self, SpawnFailure> {
+        if !is_spawn_allowed(file_system) {
+            return Err(SpawnFailure::new(
+                SpawnFailureKind::Disabled,
+                "Python is not spawned when searching an alternate root directory",
+            ));
+        }
+        let cache = create_cache_in(file_system, executable.to_path_buf());
         let entry = cache.lock().expect("cache mutex poisoned");
         if let Some(env) = entry.get() {
-            Some(env)
-        } else if let Some(env) = get_interpreter_details(executable) {
-            entry.store(env.clone());
-            Some(env)
-        } else {
-            None
+            return Ok(env);
         }
+        let timeout = file_system
+            .spawn_timeout()
+            .unwrap_or_else(get_spawn_timeout);
+        let env = get_interpreter_details_with_timeout(executable, timeout)?;
+        entry.store(env.clone());
+        Ok(env)
     }
 }
 
-fn get_interpreter_details(executable: &Path) -> Option<ResolvedPythonEnv> {
-    get_interpreter_details_with_timeout(executable, RESOLVE_SPAWN_TIMEOUT)
+/// Spawns the executable with only the allowed subset of `env` as its environment.
+/// The variables of the shim managers are kept for shims, so that they run the selected interpreter.
+fn create_isolated_command(
+    executable: &str,
+    is_shim: bool,
+    env: impl IntoIterator<Item = (OsString, OsString)>,
+) -> Command {
+    let mut command = new_silent_command(executable);
+    command
+        .args(ISOLATION_ARGS)
+        .args(["-c", PYTHON_INFO_CMD])
+        .env_clear()
+        .envs(env.into_iter().filter(|(key, _)| {
+            key.to_str().is_some_and(|key| {
+                SPAWN_ENV_VARS
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(key))
+                    || (is_shim
+                        && SHIM_ENV_VAR_PREFIXES
+                            .iter()
+                            .any(|prefix| key.to_uppercase().starts_with(prefix)))
+            })
+        }))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    command
 }
 
 fn get_interpreter_details_with_timeout(
     executable: &Path,
     timeout: Duration,
-) -> Option<ResolvedPythonEnv> {
+) -> Result<ResolvedPythonEnv, SpawnFailure> {
+    get_interpreter_details_with_env(executable, timeout, std::env::vars_os())
+}
+
+fn get_interpreter_details_with_env(
+    executable: &Path,
+    timeout: Duration,
+    env: impl IntoIterator<Item = (OsString, OsString)>,
+) -> Result<ResolvedPythonEnv, SpawnFailure> {
+    let failed = |failure: SpawnFailure| {
+        error!("Failed to resolve Python {:?}: {}", executable, failure);
+        Err(failure)
+    };
     // Spawn the python exe and get the version, sys.prefix and sys.executable.
-    let executable = executable.to_str()?;
+    let Some(executable_str) = executable.to_str() else {
+        return failed(SpawnFailure::new(
+            SpawnFailureKind::NotExecutable,
+            "Path is not valid UTF-8",
+        ));
+    };
     let start = SystemTime::now();
-    trace!("Executing Python: {} -c {}", executable, PYTHON_INFO_CMD);
-    let mut child = match new_silent_command(executable)
-        .args(["-c", PYTHON_INFO_CMD])
-        .stdin(Stdio::null())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-    {
+    trace!(
+        "Executing Python: {} {} -c {}",
+        executable_str,
+        ISOLATION_ARGS.join(" "),
+        PYTHON_INFO_CMD
+    );
+    let is_shim = get_shim_kind(executable).is_some();
+    let mut child = match create_isolated_command(executable_str, is_shim, env).spawn() {
         Ok(child) => child,
-        Err(err) => {
-            error!(
-                "Failed to spawn Python to resolve info {:?}: {}",
-                executable, err
-            );
-            return None;
-        }
+        Err(err) => return failed(classify_spawn_error(executable, &err)),
     };
 
     // Poll for completion up to the timeout. A stale cached path on Windows
@@ -139,70 +352,139 @@ fn get_interpreter_details_with_timeout(
                     );
                     let _ = child.kill();
                     let _ = child.wait();
-                    return None;
+                    return Err(SpawnFailure::new(
+                        SpawnFailureKind::Timeout,
+                        format!("No response within {}ms", timeout.as_millis()),
+                    ));
                 }
                 thread::sleep(Duration::from_millis(25));
             }
             Err(err) => {
-                error!(
-                    "Failed to wait on Python interpreter spawn for {:?}: {}",
-                    executable, err
-                );
                 let _ = child.kill();
                 let _ = child.wait();
-                return None;
+                return failed(SpawnFailure::new(
+                    SpawnFailureKind::NotExecutable,
+                    format!("Failed to wait on Python: {err}"),
+                ));
             }
         }
     }
 
-    let result = child.wait_with_output();
-    match result {
-        Ok(output) => {
-            let output = String::from_utf8(output.stdout).unwrap().trim().to_string();
-            trace!(
-                "Executed Python {:?} in {:?} & produced an output {:?}",
-                executable,
-                start.elapsed(),
-                output
-            );
-            if let Some((_, output)) = output.split_once(PYTHON_INFO_JSON_SEPARATOR) {
-                if let Ok(info) = serde_json::from_str::<InterpreterInfo>(output) {
-                    let mut symlinks = vec![
-                        PathBuf::from(executable),
-                        PathBuf::from(info.executable.clone()),
-                    ];
-                    symlinks.sort();
-                    symlinks.dedup();
-                    Some(ResolvedPythonEnv {
-                        executable: PathBuf::from(info.executable.clone()),
-                        prefix: PathBuf::from(info.sys_prefix),
-                        version: info.version.trim().to_string(),
-                        is64_bit: info.is64_bit,
-                        symlinks: Some(symlinks),
-                    })
-                } else {
-                    error!(
-                            "Python Execution for {:?} produced an output {:?} that could not be parsed as JSON",
-                            executable, output,
-                        );
-                    None
-                }
-            } else {
-                error!(
-                    "Python Execution for {:?} produced an output {:?} without a separator",
-                    executable, output,
+    let output = match child.wait_with_output() {
+        Ok(output) => output,
+        Err(err) => {
+            return failed(SpawnFailure::new(
+                SpawnFailureKind::NotExecutable,
+                format!("Failed to read the output of Python: {err}"),
+            ))
+        }
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    trace!(
+        "Executed Python {:?} in {:?} & produced an output {:?}",
+        executable,
+        start.elapsed(),
+        stdout
+    );
+    // Python could print the details & still fail on exit (e.g. an `atexit` handler),
+    // hence the output is used whenever it can be parsed.
+    if let Some(env) = parse_interpreter_details(executable, &stdout) {
+        return Ok(env);
+    }
+    if let Some(failure) = classify_exit_status(output.status, &output.stderr) {
+        return failed(failure);
+    }
+    let message = match stdout.split_once(PYTHON_INFO_JSON_SEPARATOR) {
+        Some((_, json)) => format!("Output {json:?} could not be parsed"),
+        None => format!("Output {stdout:?} does not contain the separator"),
+    };
+    failed(SpawnFailure::new(SpawnFailureKind::BadOutput, message))
+}
+
+fn parse_interpreter_details(executable: &Path, output: &str) -> Option<ResolvedPythonEnv> {
+    let (_, output) = output.split_once(PYTHON_INFO_JSON_SEPARATOR)?;
+    let info = serde_json::from_str::<InterpreterInfo>(output).ok()?;
+    let version = PythonVersion::parse(&info.version)?;
+    let mut symlinks = vec![
+        executable.to_path_buf(),
+        PathBuf::from(info.executable.clone()),
+    ];
+    symlinks.sort();
+    symlinks.dedup();
+    Some(ResolvedPythonEnv {
+        executable: PathBuf::from(info.executable),
+        prefix: PathBuf::from(info.sys_prefix),
+        version,
+        is64_bit: info.is64_bit,
+        symlinks: Some(symlinks),
+    })
+}
+
+fn classify_spawn_error(executable: &Path, err: &std::io::Error) -> SpawnFailure {
+    // ENOEXEC (8) on Linux, EBADARCH (86) on macOS,
+    // ERROR_BAD_EXE_FORMAT (193) & ERROR_EXE_MACHINE_TYPE_MISMATCH (216) on Windows.
+    let bad_format = if cfg!(windows) {
+        matches!(err.raw_os_error(), Some(193) | Some(216))
+    } else if cfg!(target_os = "macos") {
+        matches!(err.raw_os_error(), Some(8) | Some(86))
+    } else {
+        err.raw_os_error() == Some(8)
+    };
+    if bad_format {
+        if let (Some(arch), Some(host)) = (get_architecture(executable), host_architecture()) {
+            if arch != host && arch != Architecture::Universal2 {
+                return SpawnFailure::new(
+                    SpawnFailureKind::WrongArchitecture,
+                    format!("Built for {arch}, cannot run on {host}"),
                 );
-                None
             }
         }
-        Err(err) => {
-            error!(
-                "Failed to execute Python to resolve info {:?}: {}",
-                executable, err
-            );
-            None
+    }
+    SpawnFailure::new(
+        SpawnFailureKind::NotExecutable,
+        format!("Failed to spawn Python: {err}"),
+    )
+}
+
+/// Gets the failure for a Python process that did not exit successfully.
+fn classify_exit_status(status: ExitStatus, stderr: &[u8]) -> Option<SpawnFailure> {
+    if status.success() {
+        return None;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return Some(SpawnFailure::new(
+                SpawnFailureKind::Signal,
+                format!("Terminated by signal {signal}"),
+            ));
         }
     }
+    // The last line of stderr is generally the most useful (e.g. the exception of a traceback).
+    let stderr = String::from_utf8_lossy(stderr);
+    let mut message = match status.code() {
+        Some(code) => format!("Exited with code {code}"),
+        None => "Exited without an exit code".to_string(),
+    };
+    if let Some(line) = stderr.lines().rev().find(|line| !line.trim().is_empty()) {
+        message.push_str(": ");
+        message.push_str(line.trim());
+    }
+    Some(SpawnFailure::new(SpawnFailureKind::ExitCode, message))
+}
+
+fn host_architecture() -> Option<Architecture> {
+    match std::env::consts::ARCH {
+        "x86_64" => Some(Architecture::X64),
+        "x86" => Some(Architecture::X86),
+        "aarch64" => Some(Architecture::Arm64),
+        "arm" => Some(Architecture::Arm),
+        "powerpc64" => Some(Architecture::Ppc64le),
+        "s390x" => Some(Architecture::S390x),
+        "riscv64" => Some(Architecture::Riscv64),
+        _ => None,
+    }
 }
 
 #[cfg(all(test, unix))]
@@ -210,14 +492,10 @@ mod tests {
     use super::*;
     use std::os::unix::fs::PermissionsExt;
 
-    /// Regression test for #463: a spawn that never exits must not block the
-    /// resolve path indefinitely. We use a shell script that sleeps far longer
-    /// than the test timeout and assert that the call returns None promptly
-    /// (well under the script's sleep duration).
-    #[test]
-    fn get_interpreter_details_times_out_on_hanging_executable() {
+    fn create_script(name: &str, contents: &str, mode: u32) -> (PathBuf, PathBuf) {
         let tmp_dir = std::env::temp_dir().join(format!(
-            "pet_resolve_timeout_{}_{}",
+            "pet_resolve_{}_{}_{}",
+            name,
             std::process::id(),
             std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
@@ -225,24 +503,150 @@ mod tests {
                 .as_nanos()
         ));
         std::fs::create_dir_all(&tmp_dir).unwrap();
-        let fake_exe = tmp_dir.join("hangs");
-        std::fs::write(&fake_exe, "#!/bin/sh\nsleep 60\n").unwrap();
+        let fake_exe = tmp_dir.join(name);
+        std::fs::write(&fake_exe, contents).unwrap();
         let mut perms = std::fs::metadata(&fake_exe).unwrap().permissions();
-        perms.set_mode(0o755);
+        perms.set_mode(mode);
         std::fs::set_permissions(&fake_exe, perms).unwrap();
+        (tmp_dir, fake_exe)
+    }
+
+    fn run_script(
+        name: &str,
+        contents: &str,
+        mode: u32,
+    ) -> Result<ResolvedPythonEnv, SpawnFailure> {
+        run_script_with_env(name, contents, mode, std::env::vars_os())
+    }
+
+    fn run_script_with_env(
+        name: &str,
+        contents: &str,
+        mode: u32,
+        env: impl IntoIterator<Item = (OsString, OsString)>,
+    ) -> Result<ResolvedPythonEnv, SpawnFailure> {
+        let (tmp_dir, fake_exe) = create_script(name, contents, mode);
+        let result = get_interpreter_details_with_env(&fake_exe, Duration::from_secs(10), env);
+        let _ = std::fs::remove_dir_all(&tmp_dir);
+        result
+    }
+
+    /// Regression test for #463: a spawn that never exits must not block the
+    /// resolve path indefinitely. We use a shell script that sleeps far longer
+    /// than the test timeout and assert that the call fails promptly
+    /// (well under the script's sleep duration).
+    #[test]
+    fn get_interpreter_details_times_out_on_hanging_executable() {
+        let (tmp_dir, fake_exe) = create_script("hangs", "#!/bin/sh\nsleep 60\n", 0o755);
 
         let start = Instant::now();
         let result = get_interpreter_details_with_timeout(&fake_exe, Duration::from_millis(200));
         let elapsed = start.elapsed();
 
-        let _ = std::fs::remove_file(&fake_exe);
-        let _ = std::fs::remove_dir(&tmp_dir);
+        let _ = std::fs::remove_dir_all(&tmp_dir);
 
-        assert!(result.is_none(), "hanging spawn must return None");
+        assert_eq!(
+            result.unwrap_err().kind,
+            SpawnFailureKind::Timeout,
+            "hanging spawn must time out"
+        );
         assert!(
             elapsed < Duration::from_secs(5),
             "spawn must be killed near the timeout (took {:?})",
             elapsed
         );
     }
+
+    #[test]
+    fn spawn_failures_are_classified() {
+        let failure =
+            |name: &str, contents: &str, mode: u32| run_script(name, contents, mode).unwrap_err();
+
+        let not_executable = failure("not_executable", "#!/bin/sh\n", 0o644);
+        assert_eq!(not_executable.kind, SpawnFailureKind::NotExecutable);
+
+        let exit_code = failure(
+            "exit_code",
+            "#!/bin/sh\necho 'ImportError: boom' >&2\nexit 3\n",
+            0o755,
+        );
+        assert_eq!(exit_code.kind, SpawnFailureKind::ExitCode);
+        assert_eq!(exit_code.message, "Exited with code 3: ImportError: boom");
+        assert_eq!(
+            exit_code.to_string(),
+            "exitCode: Exited with code 3: ImportError: boom"
+        );
+
+        let signal = failure("signal", "#!/bin/sh\nkill -9 $$\n", 0o755);
+        assert_eq!(signal.kind, SpawnFailureKind::Signal);
+        assert_eq!(signal.message, "Terminated by signal 9");
+
+        let bad_output = failure(
+            "bad_output",
+            "#!/bin/sh\necho 093385e9-59f7-4a16-a604-14bf206256fe\necho '{not json'\n",
+            0o755,
+        );
+        assert_eq!(bad_output.kind, SpawnFailureKind::BadOutput);
+    }
+
+    #[test]
+    fn spawned_python_is_isolated_and_environment_is_scrubbed() {
+        // Prints the arguments & environment variables in place of the interpreter details.
+        let script = "#!/bin/sh\necho 093385e9-59f7-4a16-a604-14bf206256fe\n\
+            printf '{\"version\": \"3.12.0\", \"sys_prefix\": \"%s\", \"executable\": \"%s\", \"is64_bit\": true}' \"$1 $2 $3\" \"${PYTHONPATH:-scrubbed}\"\n";
+        let parent_env = std::env::vars_os().chain([(
+            OsString::from("PYTHONPATH"),
+            OsString::from("/should/not/be/seen"),
+        )]);
+        let env = run_script_with_env("isolated", script, 0o755, parent_env).unwrap();
+        assert_eq!(env.prefix, PathBuf::from("-E -s -S"));
+        assert_eq!(env.executable, PathBuf::from("scrubbed"));
+        assert_eq!(env.version, PythonVersion::parse("3.12.0").unwrap());
+    }
+
+    #[test]
+    fn shims_are_spawned_with_the_variables_of_their_manager() {
+        // Prints the selected version in place of the interpreter details.
+        let script = "#!/bin/sh\necho 093385e9-59f7-4a16-a604-14bf206256fe\n\
+            printf '{\"version\": \"3.12.0\", \"sys_prefix\": \"%s\", \"executable\": \"%s\", \"is64_bit\": true}' \"${PYENV_VERSION:-scrubbed}\" \"${ASDF_PYTHON_VERSION:-scrubbed}\"\n";
+        let parent_env = || {
+            std::env::vars_os().chain([
+                (OsString::from("PYENV_VERSION"), OsString::from("3.11.9")),
+                (
+                    OsString::from("ASDF_PYTHON_VERSION"),
+                    OsString::from("3.10.4"),
+                ),
+            ])
+        };
+
+        let (tmp_dir, _) = create_script("python", script, 0o755);
+        let shims = tmp_dir.join(".pyenv").join("shims");
+        std::fs::create_dir_all(&shims).unwrap();
+        std::fs::rename(tmp_dir.join("python"), shims.join("python")).unwrap();
+        let shim = get_interpreter_details_with_env(
+            &shims.join("python"),
+            Duration::from_secs(10),
+            parent_env(),
+        );
+        let _ = std::fs::remove_dir_all(&tmp_dir);
+        let shim = shim.unwrap();
+        assert_eq!(shim.prefix, PathBuf::from("3.11.9"));
+        assert_eq!(shim.executable, PathBuf::from("3.10.4"));
+
+        let env = run_script_with_env("python", script, 0o755, parent_env()).unwrap();
+        assert_eq!(env.prefix, PathBuf::from("scrubbed"));
+        assert_eq!(env.executable, PathBuf::from("scrubbed"));
+    }
+
+    #[test]
+    fn spawn_timeout_is_configurable() {
+        let configured = |timeout| from_spawn_timeout_ms(to_spawn_timeout_ms(timeout));
+        assert_eq!(configured(None), RESOLVE_SPAWN_TIMEOUT);
+        assert_eq!(
+            configured(Some(Duration::from_millis(1500))),
+            Duration::from_millis(1500)
+        );
+        // A zero timeout is not mistaken for "use the default".
+        assert_eq!(configured(Some(Duration::ZERO)), Duration::from_millis(1));
+    }
 }
@@ -3,12 +3,10 @@
 
 use lazy_static::lazy_static;
 use log::trace;
+use pet_fs::file_system::{FileSystem, OsFileSystem};
 use regex::Regex;
 use std::ffi::OsStr;
-use std::{
-    fs,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
 lazy_static! {
     static ref WINDOWS_EXE: Regex =
@@ -20,15 +18,12 @@ lazy_static! {
 /// Checks if a path is a broken symlink (symlink that points to a non-existent target).
 /// Returns true if the path is a symlink and its target does not exist.
 pub fn is_broken_symlink(path: &Path) -> bool {
-    // First check if it's a symlink using symlink_metadata (doesn't follow symlinks)
-    if let Ok(metadata) = fs::symlink_metadata(path) {
-        if metadata.file_type().is_symlink() {
-            // Now check if the target exists using regular metadata (follows symlinks)
-            // If this fails or returns false for exists(), then it's broken
-            return !path.exists();
-        }
-    }
-    false
+    is_broken_symlink_in(&OsFileSystem, path)
+}
+
+fn is_broken_symlink_in(file_system: &dyn FileSystem, path: &Path) -> bool {
+    // `exists` follows the symlink, if the target does not exist then it's broken.
+    file_system.is_symlink(path) && !file_system.exists(path)
 }
 
 /// Result of looking for an executable in an environment path.
@@ -43,7 +38,7 @@ pub enum ExecutableResult {
 }
 
 #[cfg(windows)]
-pub fn find_executable(env_path: &Path) -> Option<PathBuf> {
+pub fn find_executable_in(file_system: &dyn FileSystem, env_path: &Path) -> Option<PathBuf> {
     [
         env_path.join("Scripts").join("python.exe"),
         env_path.join("Scripts").join("python3.exe"),
@@ -53,11 +48,11 @@ pub fn find_executable(env_path: &Path) -> Option<PathBuf> {
         env_path.join("python3.exe"),
     ]
     .into_iter()
-    .find(|path| path.is_file())
+    .find(|path| file_system.is_file(path))
 }
 
 #[cfg(unix)]
-pub fn find_executable(env_path: &Path) -> Option<PathBuf> {
+pub fn find_executable_in(file_system: &dyn FileSystem, env_path: &Path) -> Option<PathBuf> {
     [
         env_path.join("bin").join("python"),
         env_path.join("bin").join("python3"),
@@ -65,13 +60,24 @@ pub fn find_executable(env_path: &Path) -> Option<PathBuf> {
         env_path.join("python3"),
     ]
     .into_iter()
-    .find(|path| path.is_file())
+    .find(|path| file_system.is_file(path))
+}
+
+pub fn find_executable(env_path: &Path) -> Option<PathBuf> {
+    find_executable_in(&OsFileSystem, env_path)
 }
 
 /// Finds an executable in the environment path, including broken symlinks.
 /// This is useful for detecting virtual environments that have broken Python executables.
-#[cfg(windows)]
 pub fn find_executable_or_broken(env_path: &Path) -> ExecutableResult {
+    find_executable_or_broken_in(&OsFileSystem, env_path)
+}
+
+#[cfg(windows)]
+pub fn find_executable_or_broken_in(
+    file_system: &dyn FileSystem,
+    env_path: &Path,
+) -> ExecutableResult {
     let candidates = [
         env_path.join("Scripts").join("python.exe"),
         env_path.join("Scripts").join("python3.exe"),
@@ -82,22 +88,26 @@ pub fn find_executable_or_broken(env_path: &Path) -> ExecutableResult {
     ];
 
     // First try to find a valid executable
-    if let Some(path) = candidates.iter().find(|path| path.is_file()) {
+    if let Some(path) = candidates.iter().find(|path| file_system.is_file(path)) {
         return ExecutableResult::Found(path.clone());
     }
 
     // Then check for broken symlinks
-    if let Some(path) = candidates.iter().find(|path| is_broken_symlink(path)) {
+    if let Some(path) = candidates
+        .iter()
+        .find(|path| is_broken_symlink_in(file_system, path))
+    {
         return ExecutableResult::Broken(path.clone());
     }
 
     ExecutableResult::NotFound
 }
 
-/// Finds an executable in the environment path, including broken symlinks.
-/// This is useful for detecting virtual environments that have broken Python executables.
 #[cfg(unix)]
-pub fn find_executable_or_broken(env_path: &Path) -> ExecutableResult {
+pub fn find_executable_or_broken_in(
+    file_system: &dyn FileSystem,
+    env_path: &Path,
+) -> ExecutableResult {
     let candidates = [
         env_path.join("bin").join("python"),
         env_path.join("bin").join("python3"),
@@ -106,12 +116,15 @@ pub fn find_executable_or_broken(env_path: &Path) -> ExecutableResult {
     ];
 
     // First try to find a valid executable
-    if let Some(path) = candidates.iter().find(|path| path.is_file()) {
+    if let Some(path) = candidates.iter().find(|path| file_system.is_file(path)) {
         return ExecutableResult::Found(path.clone());
     }
 
     // Then check for broken symlinks
-    if let Some(path) = candidates.iter().find(|path| is_broken_symlink(path)) {
+    if let Some(path) = candidates
+        .iter()
+        .find(|path| is_broken_symlink_in(file_system, path))
+    {
         return ExecutableResult::Broken(path.clone());
     }
 
@@ -119,6 +132,14 @@ pub fn find_executable_or_broken(env_path: &Path) -> ExecutableResult {
 }
 
 pub fn find_executables<T: AsRef<Path>>(env_path: T) -> Vec<PathBuf> {
+    find_executables_in(&OsFileSystem, env_path)
+}
+
+/// Same as `find_executables`, looking for the executables in the given file system.
+pub fn find_executables_in<T: AsRef<Path>>(
+    file_system: &dyn FileSystem,
+    env_path: T,
+) -> Vec<PathBuf> {
     let mut env_path = env_path.as_ref().to_path_buf();
     // Never find exes in pyenv shims folder, they are not valid exes.
     // Pyenv can be installed at custom locations (e.g., ~/.pl/pyenv via PYENV_ROOT),
@@ -131,12 +152,12 @@ pub fn find_executables<T: AsRef<Path>>(env_path: T) -> Vec<PathBuf> {
     if cfg!(windows) {
         // Only windows can have a Scripts folder
         let bin = "Scripts";
-        if env_path.join(bin).exists() {
+        if file_system.exists(&env_path.join(bin)) {
             env_path = env_path.join(bin);
         }
     }
     let bin = "bin"; // Windows can have bin as well, https://github.com/microsoft/vscode-python/issues/24792
-    if env_path.join(bin).exists() {
+    if file_system.exists(&env_path.join(bin)) {
         env_path = env_path.join(bin);
     }
 
@@ -160,15 +181,14 @@ pub fn find_executables<T: AsRef<Path>>(env_path: T) -> Vec<PathBuf> {
     // If you install python@3.10, then only a python3.10 exe is created in that bin directory.
     // As a compromise, we only enumerate if this is a bin directory and there are no python exes
     // Else enumerating entire directories is very expensive.
-    if env_path.join(python_exe).exists()
-        || env_path.join(python3_exe).exists()
+    if file_system.exists(&env_path.join(python_exe))
+        || file_system.exists(&env_path.join(python3_exe))
         || env_path.ends_with(bin)
     {
         // Enumerate this directory and get all `python` & `pythonX.X` files.
-        if let Ok(entries) = fs::read_dir(env_path) {
-            for entry in entries.filter_map(Result::ok) {
-                let file = entry.path();
-                if file.is_file() && is_python_executable_name(&file) {
+        if let Ok(entries) = file_system.read_dir(&env_path) {
+            for file in entries {
+                if file_system.is_file(&file) && is_python_executable_name(&file) {
                     python_executables.push(file);
                 }
             }
@@ -280,6 +300,8 @@ pub fn new_silent_command(program: impl AsRef<OsStr>) -> std::process::Command {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pet_fs::file_system::MemoryFileSystem;
+    use std::fs;
 
     #[test]
     fn is_python_executable_test() {
@@ -556,4 +578,32 @@ mod tests {
 
         let _ = fs::remove_dir_all(&temp_dir);
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn find_executables_in_file_system() {
+        let file_system = MemoryFileSystem::new()
+            .file("/usr/bin/python3.12", "")
+            .symlink("/usr/bin/python3", "python3.12")
+            .symlink("/usr/bin/python2", "python2.7")
+            .file("/usr/bin/pip", "")
+            .file("/app/.venv/pyvenv.cfg", "")
+            .symlink("/app/.venv/bin/python", "/usr/bin/missing");
+
+        assert_eq!(
+            find_executables_in(&file_system, "/usr"),
+            vec![
+                PathBuf::from("/usr/bin/python3"),
+                PathBuf::from("/usr/bin/python3.12")
+            ]
+        );
+        assert_eq!(
+            find_executable_in(&file_system, Path::new("/usr")),
+            Some(PathBuf::from("/usr/bin/python3"))
+        );
+        assert!(matches!(
+            find_executable_or_broken_in(&file_system, Path::new("/app/.venv")),
+            ExecutableResult::Broken(path) if path == Path::new("/app/.venv/bin/python")
+        ));
+    }
 }
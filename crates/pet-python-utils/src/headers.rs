@@ -2,8 +2,10 @@
 // Licensed under the MIT License.
 
 use lazy_static::lazy_static;
+use pet_core::python_version::PythonVersion;
+use pet_fs::file_system::{FileSystem, OsFileSystem};
 use regex::Regex;
-use std::{fs, path::Path};
+use std::path::Path;
 
 lazy_static! {
     static ref VERSION: Regex = Regex::new(r#"#define\s+PY_VERSION\s+"((\d+\.?)*.*)\""#)
@@ -17,12 +19,17 @@ pub struct Headers {
 }
 
 impl Headers {
-    pub fn get_version(path: &Path) -> Option<String> {
+    pub fn get_version(path: &Path) -> Option<PythonVersion> {
+        Headers::get_version_in(&OsFileSystem, path)
+    }
+
+    /// Same as `get_version`, looking at the files in the given file system.
+    pub fn get_version_in(file_system: &dyn FileSystem, path: &Path) -> Option<PythonVersion> {
         let mut path = path.to_path_buf();
         if cfg!(windows) {
             // Only Windows can have a Scripts folder
             let bin = "Scripts";
-            if path.join(bin).exists() {
+            if file_system.exists(&path.join(bin)) {
                 path = path.join(bin);
             }
         }
@@ -30,7 +37,7 @@ impl Headers {
         if path.ends_with(bin) {
             path.pop();
         }
-        get_version(&path, None)
+        get_version_in(file_system, &path, None)
     }
 }
 
@@ -40,29 +47,37 @@ impl Headers {
 // /* Version as a string */
 // #define PY_VERSION              "3.10.2"
 // /*--end constants--*/
-pub fn get_version(sys_prefix: &Path, pyver: Option<(u64, u64)>) -> Option<String> {
+pub fn get_version(sys_prefix: &Path, pyver: Option<(u64, u64)>) -> Option<PythonVersion> {
+    get_version_in(&OsFileSystem, sys_prefix, pyver)
+}
+
+/// Same as `get_version`, looking at the files in the given file system.
+pub fn get_version_in(
+    file_system: &dyn FileSystem,
+    sys_prefix: &Path,
+    pyver: Option<(u64, u64)>,
+) -> Option<PythonVersion> {
     // Generally the files are in Headers in windows and include in unix
     // However they can also be in Headers on Mac (command line tools python, hence make no assumptions)
     for headers_path in [sys_prefix.join("Headers"), sys_prefix.join("include")] {
-        if !headers_path.exists() {
+        if !file_system.exists(&headers_path) {
             continue;
         }
         let patchlevel_h = headers_path.join("patchlevel.h");
-        if let Some(version) = valid_version_from_header(&patchlevel_h, pyver) {
+        if let Some(version) = valid_version_from_header(file_system, &patchlevel_h, pyver) {
             return Some(version);
         } else {
             // Try the other path
             // Sometimes we have it in a sub directory such as `python3.10` or `pypy3.9`
-            if let Ok(readdir) = fs::read_dir(&headers_path) {
-                for path in readdir.filter_map(Result::ok) {
-                    if let Ok(t) = path.file_type() {
-                        if !t.is_dir() {
-                            continue;
-                        }
+            if let Ok(paths) = file_system.read_dir(&headers_path) {
+                for path in paths {
+                    if !file_system.is_dir(&path) {
+                        continue;
                     }
-                    let path = path.path();
                     let patchlevel_h = path.join("patchlevel.h");
-                    if let Some(version) = valid_version_from_header(&patchlevel_h, pyver) {
+                    if let Some(version) =
+                        valid_version_from_header(file_system, &patchlevel_h, pyver)
+                    {
                         return Some(version);
                     }
                 }
@@ -72,22 +87,20 @@ pub fn get_version(sys_prefix: &Path, pyver: Option<(u64, u64)>) -> Option<Strin
     None
 }
 
-fn valid_version_from_header(header: &Path, pyver: Option<(u64, u64)>) -> Option<String> {
-    let contents = fs::read_to_string(header).ok()?;
+fn valid_version_from_header(
+    file_system: &dyn FileSystem,
+    header: &Path,
+    pyver: Option<(u64, u64)>,
+) -> Option<PythonVersion> {
+    let contents = file_system.read_to_string(header).ok()?;
     for line in contents.lines() {
         if let Some(captures) = VERSION.captures(line) {
-            let version = captures.get(1)?.as_str();
-            if let Some(pyver) = pyver {
-                let parts: Vec<u64> = version
-                    .splitn(3, ".")
-                    .take(2)
-                    .flat_map(str::parse::<u64>)
-                    .collect();
-                if parts.len() == 2 && (parts[0], parts[1]) == pyver {
-                    return Some(version.to_string());
-                }
-            } else {
-                return Some(version.to_string());
+            let Some(version) = PythonVersion::parse(captures.get(1)?.as_str()) else {
+                continue;
+            };
+            match pyver {
+                Some(pyver) if (version.major(), version.minor()) != pyver => {}
+                _ => return Some(version),
             }
         }
     }
@@ -1,6 +1,7 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+pub mod arch;
 pub mod build_details;
 pub mod cache;
 pub mod env;
@@ -8,4 +9,5 @@ pub mod executable;
 pub mod fs_cache;
 mod headers;
 pub mod platform_dirs;
+pub mod platform_tags;
 pub mod version;
@@ -0,0 +1,847 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Computes the wheel compatibility tags of an interpreter without spawning it.
+//!
+//! The tags are the same (and in the same order) as those returned by `packaging.tags.sys_tags()`,
+//! computed from:
+//!
+//! * the version & ABI flags (`build-details.json`, else the `_sysconfigdata_*.py` file),
+//! * the architecture & binary format of the executable,
+//! * the C library (the ELF `PT_INTERP` distinguishes glibc from musl),
+//!   and its version (read from the system libc, or the apk database for musl).
+
+use lazy_static::lazy_static;
+use log::trace;
+use pet_core::{
+    arch::Architecture,
+    platform_tags::{Libc, LibcFlavor, PlatformTags},
+    python_version::PythonVersion,
+    pyvenv_cfg::PyVenvCfg,
+};
+use regex::{bytes, Regex};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use pet_fs::file_system::{FileSystem, OsFileSystem};
+
+use crate::{
+    arch::{parse_architecture, read_u16, read_u32, read_u64, Endian},
+    build_details::BuildDetails,
+};
+
+/// Number of bytes read from the start of the executable.
+/// Large enough for the ELF program headers of the executables we know of.
+const HEADER_SIZE: usize = 4096;
+const PT_INTERP: u32 = 3;
+
+/// Architectures that have `manylinux2014` wheels.
+const MANYLINUX2014_ARCHS: [&str; 7] = [
+    "x86_64", "i686", "aarch64", "armv7l", "ppc64", "ppc64le", "s390x",
+];
+
+lazy_static! {
+    static ref ABIFLAGS: Regex =
+        Regex::new(r"'ABIFLAGS':\s*'([^']*)'").expect("error parsing ABIFLAGS regex");
+    static ref SOABI: Regex =
+        Regex::new(r"'SOABI':\s*'([^']*)'").expect("error parsing SOABI regex");
+    static ref VERSION: Regex =
+        Regex::new(r"^(\d+)\.(\d+)").expect("error parsing version regex");
+    static ref STDLIB_DIRNAME: Regex =
+        Regex::new(r"^(?:python|pypy)(\d+)\.(\d+)t?$").expect("error parsing stdlib regex");
+    static ref FREE_THREADED_EXE: Regex =
+        Regex::new(r"^python\d+\.\d+t(\.exe)?$").expect("error parsing executable regex");
+    static ref GLIBC_VERSION: bytes::Regex =
+        bytes::Regex::new(r"release version (\d+)\.(\d+)").expect("error parsing glibc regex");
+    static ref MACOS_VERSION: Regex =
+        Regex::new(r"<key>ProductVersion</key>\s*<string>(\d+)\.(\d+)")
+            .expect("error parsing macOS version regex");
+    /// Versions of glibc keyed by the path of libc, reading libc is not free.
+    static ref GLIBC_VERSIONS: Mutex<HashMap<PathBuf, Option<(u32, u32)>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Computes the wheel tags supported by the interpreter.
+/// `prefix` & `version` are used when known, else they are inferred from the files on disc.
+pub fn get_platform_tags(
+    executable: &Path,
+    prefix: Option<&Path>,
+    version: Option<&PythonVersion>,
+) -> Option<PlatformTags> {
+    get_platform_tags_in(&OsFileSystem, executable, prefix, version)
+}
+
+/// Same as `get_platform_tags`, looking at the files in the given file system.
+/// The C library & the paths of `pyvenv.cfg` are looked up within the alternate root directory of the file system, if any.
+pub fn get_platform_tags_in(
+    file_system: &dyn FileSystem,
+    executable: &Path,
+    prefix: Option<&Path>,
+    version: Option<&PythonVersion>,
+) -> Option<PlatformTags> {
+    let header = read_header(executable)?;
+    let arch = parse_architecture(&header)?;
+    let prefixes = get_prefixes(file_system, executable, prefix);
+    let build_details = prefixes
+        .iter()
+        .find_map(|prefix| BuildDetails::find(prefix));
+
+    let (major, minor) = version
+        .cloned()
+        .or_else(|| build_details.as_ref().and_then(BuildDetails::version))
+        .or_else(|| prefix.and_then(PyVenvCfg::find).and_then(|cfg| cfg.version))
+        .filter(PythonVersion::is_valid)
+        .map(|version| (version.major() as u32, version.minor() as u32))
+        .or_else(|| {
+            prefixes
+                .iter()
+                .find_map(|prefix| get_stdlib_version(prefix))
+        })?;
+
+    let abi = get_abi(
+        file_system,
+        executable,
+        &prefixes,
+        build_details.as_ref(),
+        (major, minor),
+    );
+    let (platforms, libc) = match header.get(0..2)? {
+        [0x7f, b'E'] => get_linux_platforms(file_system, executable, &header, &arch)?,
+        [b'M', b'Z'] => (vec![get_windows_platform(&arch)?], None),
+        _ => (get_mac_platforms(&arch, get_macos_version()?)?, None),
+    };
+    let tags = if abi.implementation == "pypy" {
+        get_generic_tags(&abi, (major, minor), &platforms)
+    } else {
+        get_cpython_tags(&abi, (major, minor), &platforms)
+    };
+    trace!(
+        "Computed {} platform tags for {:?} ({:?})",
+        tags.len(),
+        executable,
+        abi
+    );
+    Some(PlatformTags {
+        tags,
+        platforms,
+        libc,
+    })
+}
+
+fn read_header(executable: &Path) -> Option<Vec<u8>> {
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    File::open(executable)
+        .ok()?
+        .take(HEADER_SIZE as u64)
+        .read_to_end(&mut header)
+        .ok()?;
+    Some(header)
+}
+
+fn parse_major_minor(version: &str) -> Option<(u32, u32)> {
+    let captures = VERSION.captures(version)?;
+    Some((captures[1].parse().ok()?, captures[2].parse().ok()?))
+}
+
+/// The prefix of the environment & the prefix of the base interpreter (e.g. for virtual envs),
+/// as the ABI details are only found in the latter.
+fn get_prefixes(
+    file_system: &dyn FileSystem,
+    executable: &Path,
+    prefix: Option<&Path>,
+) -> Vec<PathBuf> {
+    let mut prefixes = vec![];
+    if let Some(prefix) = prefix {
+        prefixes.push(prefix.to_path_buf());
+        // `home` is a path of the image when searching an alternate root directory.
+        if let Some(home) = get_pyvenv_cfg_home(prefix).map(|home| file_system.to_host_path(&home))
+        {
+            prefixes.extend(home.parent().map(Path::to_path_buf));
+            prefixes.push(home);
+        }
+    }
+    let executable = file_system
+        .canonicalize(executable)
+        .unwrap_or(executable.to_path_buf());
+    if let Some(bin) = executable.parent() {
+        if bin.ends_with("bin") {
+            prefixes.extend(bin.parent().map(Path::to_path_buf));
+        } else {
+            // Windows installs have the executable in the prefix.
+            prefixes.push(bin.to_path_buf());
+        }
+    }
+    let mut unique = vec![];
+    for prefix in prefixes {
+        if !unique.contains(&prefix) {
+            unique.push(prefix);
+        }
+    }
+    unique
+}
+
+/// The `home` key of `pyvenv.cfg`, i.e. the directory of the base interpreter.
+fn get_pyvenv_cfg_home(prefix: &Path) -> Option<PathBuf> {
+    let contents = fs::read_to_string(prefix.join("pyvenv.cfg")).ok()?;
+    contents.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        if key.trim().eq_ignore_ascii_case("home") {
+            Some(PathBuf::from(value.trim()))
+        } else {
+            None
+        }
+    })
+}
+
+/// Version from the name of the standard library directory, e.g. `lib/python3.12`.
+fn get_stdlib_version(prefix: &Path) -> Option<(u32, u32)> {
+    fs::read_dir(prefix.join("lib"))
+        .ok()?
+        .filter_map(Result::ok)
+        .find_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let captures = STDLIB_DIRNAME.captures(&name)?;
+            Some((captures[1].parse().ok()?, captures[2].parse().ok()?))
+        })
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct AbiInfo {
+    /// `cpython` or `pypy`.
+    implementation: String,
+    /// E.g. `t` for free-threaded builds, `d` for debug builds.
+    flags: String,
+    /// E.g. `pypy310-pp73`.
+    soabi: Option<String>,
+}
+
+fn get_abi(
+    file_system: &dyn FileSystem,
+    executable: &Path,
+    prefixes: &[PathBuf],
+    build_details: Option<&BuildDetails>,
+    (major, minor): (u32, u32),
+) -> AbiInfo {
+    let file_name = file_system
+        .canonicalize(executable)
+        .unwrap_or(executable.to_path_buf())
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let implementation = build_details
+        .and_then(|details| details.implementation.as_ref())
+        .map(|implementation| implementation.name.to_lowercase())
+        .unwrap_or(if file_name.starts_with("pypy") {
+            "pypy".to_string()
+        } else {
+            "cpython".to_string()
+        });
+
+    if let Some(abi) = build_details.and_then(|details| details.abi.as_ref()) {
+        return AbiInfo {
+            implementation,
+            flags: abi.flags.concat(),
+            // E.g. `.cpython-314-x86_64-linux-gnu.so` or `.cp314-win_amd64.pyd`.
+            soabi: abi
+                .extension_suffix
+                .as_ref()
+                .and_then(|suffix| suffix.split('.').nth(1).map(ToString::to_string)),
+        };
+    }
+    for prefix in prefixes {
+        for dir in [
+            format!("python{major}.{minor}"),
+            format!("python{major}.{minor}t"),
+            format!("pypy{major}.{minor}"),
+        ] {
+            let Ok(reader) = fs::read_dir(prefix.join("lib").join(dir)) else {
+                continue;
+            };
+            for entry in reader.filter_map(Result::ok) {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if !name.starts_with("_sysconfigdata") || !name.ends_with(".py") {
+                    continue;
+                }
+                let Ok(contents) = fs::read_to_string(entry.path()) else {
+                    continue;
+                };
+                if let Some(flags) = ABIFLAGS.captures(&contents) {
+                    return AbiInfo {
+                        implementation,
+                        flags: flags[1].to_string(),
+                        soabi: SOABI.captures(&contents).map(|soabi| soabi[1].to_string()),
+                    };
+                }
+            }
+        }
+    }
+    // Windows does not have sysconfig data files, only free-threaded builds have ABI flags.
+    AbiInfo {
+        implementation,
+        flags: if FREE_THREADED_EXE.is_match(&file_name) {
+            "t".to_string()
+        } else {
+            String::new()
+        },
+        soabi: None,
+    }
+}
+
+/// Same as `packaging.tags.cpython_tags()` followed by `compatible_tags()`.
+fn get_cpython_tags(
+    abi: &AbiInfo,
+    (major, minor): (u32, u32),
+    platforms: &[String],
+) -> Vec<String> {
+    let interpreter = format!("cp{major}{minor}");
+    let mut abis = vec![format!("{interpreter}{}", abi.flags)];
+    if abi.flags.contains('d') {
+        // Debug builds can also load regular extension modules.
+        let threading = if abi.flags.contains('t') { "t" } else { "" };
+        abis.push(format!("{interpreter}{threading}"));
+    }
+    let use_abi3 = major == 3 && minor >= 2 && !abi.flags.contains('t');
+
+    let mut tags = vec![];
+    for abi in &abis {
+        tags.extend(platforms.iter().map(|p| format!("{interpreter}-{abi}-{p}")));
+    }
+    if use_abi3 {
+        tags.extend(platforms.iter().map(|p| format!("{interpreter}-abi3-{p}")));
+    }
+    tags.extend(platforms.iter().map(|p| format!("{interpreter}-none-{p}")));
+    if use_abi3 {
+        for older in (2..minor).rev() {
+            tags.extend(
+                platforms
+                    .iter()
+                    .map(|p| format!("cp{major}{older}-abi3-{p}")),
+            );
+        }
+    }
+    tags.extend(get_compatible_tags(
+        Some(&interpreter),
+        (major, minor),
+        platforms,
+    ));
+    tags
+}
+
+/// Same as `packaging.tags.generic_tags()` followed by `compatible_tags()`, used for PyPy.
+fn get_generic_tags(
+    abi: &AbiInfo,
+    (major, minor): (u32, u32),
+    platforms: &[String],
+) -> Vec<String> {
+    let interpreter = format!("pp{major}{minor}");
+    let mut abis = vec![];
+    if let Some(soabi) = &abi.soabi {
+        abis.push(soabi.replace(['-', '.'], "_"));
+    }
+    abis.push("none".to_string());
+
+    let mut tags = vec![];
+    for abi in &abis {
+        tags.extend(platforms.iter().map(|p| format!("{interpreter}-{abi}-{p}")));
+    }
+    tags.extend(get_compatible_tags(Some("pp3"), (major, minor), platforms));
+    tags
+}
+
+/// Same as `packaging.tags.compatible_tags()`, i.e. pure Python wheels.
+fn get_compatible_tags(
+    interpreter: Option<&str>,
+    (major, minor): (u32, u32),
+    platforms: &[String],
+) -> Vec<String> {
+    let mut versions = vec![format!("py{major}{minor}"), format!("py{major}")];
+    versions.extend((0..minor).rev().map(|older| format!("py{major}{older}")));
+
+    let mut tags = vec![];
+    for version in &versions {
+        tags.extend(platforms.iter().map(|p| format!("{version}-none-{p}")));
+    }
+    if let Some(interpreter) = interpreter {
+        tags.push(format!("{interpreter}-none-any"));
+    }
+    tags.extend(versions.iter().map(|version| format!("{version}-none-any")));
+    tags
+}
+
+fn get_linux_platforms(
+    file_system: &dyn FileSystem,
+    executable: &Path,
+    header: &[u8],
+    arch: &Architecture,
+) -> Option<(Vec<String>, Option<Libc>)> {
+    let arch = match arch {
+        Architecture::X64 => "x86_64",
+        Architecture::X86 => "i686",
+        Architecture::Arm64 => "aarch64",
+        Architecture::Arm => "armv7l",
+        Architecture::Ppc64le => "ppc64le",
+        Architecture::S390x => "s390x",
+        Architecture::Riscv64 => "riscv64",
+        // Universal2 binaries are macOS only, architectures added later have no known Linux platforms.
+        _ => return None,
+    };
+    let libc = read_elf_interpreter(executable, header)
+        .map(|interpreter| get_libc(file_system, &interpreter));
+    let mut platforms = match &libc {
+        Some(Libc {
+            flavor: LibcFlavor::Glibc,
+            version: Some(version),
+        }) => parse_major_minor(version)
+            .map(|version| get_manylinux_platforms(version, arch))
+            .unwrap_or_default(),
+        Some(Libc {
+            flavor: LibcFlavor::Musl,
+            version: Some(version),
+        }) => parse_major_minor(version)
+            .map(|version| get_musllinux_platforms(version, arch))
+            .unwrap_or_default(),
+        _ => vec![],
+    };
+    platforms.push(format!("linux_{arch}"));
+    Some((platforms, libc))
+}
+
+/// Same as `packaging._manylinux.platform_tags()`.
+fn get_manylinux_platforms((major, minor): (u32, u32), arch: &str) -> Vec<String> {
+    if major != 2 {
+        return vec![];
+    }
+    let is_x86 = arch == "x86_64" || arch == "i686";
+    let too_old_minor = if is_x86 { 4 } else { 16 };
+    let mut platforms = vec![];
+    for minor in ((too_old_minor + 1)..=minor).rev() {
+        platforms.push(format!("manylinux_2_{minor}_{arch}"));
+        let legacy = match minor {
+            17 if MANYLINUX2014_ARCHS.contains(&arch) => Some("manylinux2014"),
+            12 if is_x86 => Some("manylinux2010"),
+            5 if is_x86 => Some("manylinux1"),
+            _ => None,
+        };
+        if let Some(legacy) = legacy {
+            platforms.push(format!("{legacy}_{arch}"));
+        }
+    }
+    platforms
+}
+
+/// Same as `packaging._musllinux.platform_tags()`.
+fn get_musllinux_platforms((major, minor): (u32, u32), arch: &str) -> Vec<String> {
+    (0..=minor)
+        .rev()
+        .map(|minor| format!("musllinux_{major}_{minor}_{arch}"))
+        .collect()
+}
+
+/// Path of the dynamic loader (`PT_INTERP`), e.g. `/lib64/ld-linux-x86-64.so.2`.
+fn read_elf_interpreter(executable: &Path, header: &[u8]) -> Option<PathBuf> {
+    let is_64_bit = *header.get(4)? == 2;
+    let endian = match header.get(5)? {
+        1 => Endian::Little,
+        2 => Endian::Big,
+        _ => return None,
+    };
+    let (program_headers, entry_size, count) = if is_64_bit {
+        (
+            read_u64(header, 32, endian)? as usize,
+            read_u16(header, 54, endian)? as usize,
+            read_u16(header, 56, endian)? as usize,
+        )
+    } else {
+        (
+            read_u32(header, 28, endian)? as usize,
+            read_u16(header, 42, endian)? as usize,
+            read_u16(header, 44, endian)? as usize,
+        )
+    };
+    for index in 0..count {
+        let entry = program_headers + index * entry_size;
+        if read_u32(header, entry, endian)? != PT_INTERP {
+            continue;
+        }
+        let (offset, size) = if is_64_bit {
+            (
+                read_u64(header, entry + 8, endian)?,
+                read_u64(header, entry + 32, endian)?,
+            )
+        } else {
+            (
+                read_u32(header, entry + 4, endian)? as u64,
+                read_u32(header, entry + 16, endian)? as u64,
+            )
+        };
+        if size == 0 || size > 4096 {
+            return None;
+        }
+        let mut interpreter = vec![0; size as usize];
+        let mut file = File::open(executable).ok()?;
+        file.seek(SeekFrom::Start(offset)).ok()?;
+        file.read_exact(&mut interpreter).ok()?;
+        let interpreter = String::from_utf8_lossy(&interpreter)
+            .trim_end_matches('\0')
+            .to_string();
+        return Some(PathBuf::from(interpreter));
+    }
+    // Statically linked.
+    None
+}
+
+fn get_libc(file_system: &dyn FileSystem, interpreter: &Path) -> Libc {
+    let is_musl = interpreter
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().contains("musl"));
+    if is_musl {
+        Libc {
+            flavor: LibcFlavor::Musl,
+            version: get_musl_version(
+                &file_system.to_host_path(Path::new("/lib/apk/db/installed")),
+            )
+            .map(|(major, minor)| format!("{major}.{minor}")),
+        }
+    } else {
+        Libc {
+            flavor: LibcFlavor::Glibc,
+            version: get_glibc_version(file_system, interpreter)
+                .map(|(major, minor)| format!("{major}.{minor}")),
+        }
+    }
+}
+
+/// Reads the version from the banner of `libc.so.6`, found next to the dynamic loader.
+/// The loader & the library folders are paths of the image, mapped to the alternate root directory if any.
+fn get_glibc_version(file_system: &dyn FileSystem, interpreter: &Path) -> Option<(u32, u32)> {
+    let interpreter = file_system.to_host_path(interpreter);
+    let interpreter = file_system
+        .canonicalize(&interpreter)
+        .unwrap_or(interpreter);
+    let libc = interpreter
+        .parent()
+        .map(Path::to_path_buf)
+        .into_iter()
+        .chain(
+            ["/lib64", "/usr/lib64", "/lib", "/usr/lib"]
+                .map(|dir| file_system.to_host_path(Path::new(dir))),
+        )
+        .map(|dir| dir.join("libc.so.6"))
+        .find(|libc| libc.is_file())?;
+
+    let mut versions = GLIBC_VERSIONS
+        .lock()
+        .expect("glibc versions mutex poisoned");
+    *versions.entry(libc.clone()).or_insert_with(|| {
+        let contents = fs::read(&libc).ok()?;
+        let captures = GLIBC_VERSION.captures(&contents)?;
+        let major = std::str::from_utf8(&captures[1]).ok()?.parse().ok()?;
+        let minor = std::str::from_utf8(&captures[2]).ok()?.parse().ok()?;
+        trace!("glibc version of {:?} is {}.{}", libc, major, minor);
+        Some((major, minor))
+    })
+}
+
+/// Reads the version of the `musl` package from the apk database (Alpine).
+fn get_musl_version(apk_database: &Path) -> Option<(u32, u32)> {
+    let contents = fs::read_to_string(apk_database).ok()?;
+    contents.split("\n\n").find_map(|package| {
+        let mut lines = package.lines();
+        if !lines.clone().any(|line| line == "P:musl") {
+            return None;
+        }
+        lines.find_map(|line| line.strip_prefix("V:").and_then(parse_major_minor))
+    })
+}
+
+fn get_windows_platform(arch: &Architecture) -> Option<String> {
+    match arch {
+        Architecture::X64 => Some("win_amd64".to_string()),
+        Architecture::X86 => Some("win32".to_string()),
+        Architecture::Arm64 => Some("win_arm64".to_string()),
+        _ => None,
+    }
+}
+
+fn get_macos_version() -> Option<(u32, u32)> {
+    let contents = fs::read_to_string("/System/Library/CoreServices/SystemVersion.plist").ok()?;
+    let captures = MACOS_VERSION.captures(&contents)?;
+    Some((captures[1].parse().ok()?, captures[2].parse().ok()?))
+}
+
+/// Same as `packaging.tags.mac_platforms()`.
+fn get_mac_platforms(arch: &Architecture, (major, minor): (u32, u32)) -> Option<Vec<String>> {
+    let arch = match arch {
+        Architecture::X64 => "x86_64",
+        Architecture::Arm64 => "arm64",
+        // Universal binaries run natively.
+        Architecture::Universal2 if cfg!(target_arch = "aarch64") => "arm64",
+        Architecture::Universal2 => "x86_64",
+        _ => return None,
+    };
+    let mut platforms = vec![];
+    if major == 10 {
+        for minor in (0..=minor).rev() {
+            for format in get_mac_binary_formats((10, minor), arch) {
+                platforms.push(format!("macosx_10_{minor}_{format}"));
+            }
+        }
+    }
+    if major >= 11 {
+        for major in (11..=major).rev() {
+            for format in get_mac_binary_formats((major, 0), arch) {
+                platforms.push(format!("macosx_{major}_0_{format}"));
+            }
+        }
+        for minor in (4..=16).rev() {
+            if arch == "x86_64" {
+                for format in get_mac_binary_formats((10, minor), arch) {
+                    platforms.push(format!("macosx_10_{minor}_{format}"));
+                }
+            } else {
+                platforms.push(format!("macosx_10_{minor}_universal2"));
+            }
+        }
+    }
+    Some(platforms)
+}
+
+fn get_mac_binary_formats(version: (u32, u32), arch: &str) -> Vec<&str> {
+    if arch == "x86_64" {
+        if version < (10, 4) {
+            return vec![];
+        }
+        vec![arch, "intel", "fat64", "fat32", "universal2", "universal"]
+    } else {
+        vec![arch, "universal2"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// 64-bit little endian x86_64 ELF with a `PT_INTERP` program header.
+    fn elf_with_interpreter(interpreter: &str) -> Vec<u8> {
+        let mut elf = vec![0; 0x200];
+        elf[0..4].copy_from_slice(b"\x7fELF");
+        elf[4] = 2;
+        elf[5] = 1;
+        elf[18] = 0x3e;
+        // Program headers at 64, 56 bytes each, 2 of them.
+        elf[32..40].copy_from_slice(&64u64.to_le_bytes());
+        elf[54..56].copy_from_slice(&56u16.to_le_bytes());
+        elf[56..58].copy_from_slice(&2u16.to_le_bytes());
+        // First is PT_LOAD, second is PT_INTERP pointing to 0x100.
+        elf[64..68].copy_from_slice(&1u32.to_le_bytes());
+        elf[120..124].copy_from_slice(&PT_INTERP.to_le_bytes());
+        elf[128..136].copy_from_slice(&0x100u64.to_le_bytes());
+        elf[152..160].copy_from_slice(&(interpreter.len() as u64 + 1).to_le_bytes());
+        elf[0x100..0x100 + interpreter.len()].copy_from_slice(interpreter.as_bytes());
+        elf
+    }
+
+    fn cpython(flags: &str) -> AbiInfo {
+        AbiInfo {
+            implementation: "cpython".to_string(),
+            flags: flags.to_string(),
+            soabi: None,
+        }
+    }
+
+    #[test]
+    fn reads_the_elf_interpreter() {
+        let tmp = TempDir::new().unwrap();
+        let executable = tmp.path().join("python");
+        let elf = elf_with_interpreter("/lib/ld-musl-x86_64.so.1");
+        fs::write(&executable, &elf).unwrap();
+
+        assert_eq!(
+            read_elf_interpreter(&executable, &elf),
+            Some(PathBuf::from("/lib/ld-musl-x86_64.so.1"))
+        );
+        assert_eq!(
+            get_libc(&OsFileSystem, Path::new("/lib/ld-musl-x86_64.so.1")).flavor,
+            LibcFlavor::Musl
+        );
+        assert_eq!(
+            get_libc(&OsFileSystem, Path::new("/lib64/ld-linux-x86-64.so.2")).flavor,
+            LibcFlavor::Glibc
+        );
+    }
+
+    #[test]
+    fn manylinux_platforms_include_legacy_aliases() {
+        let platforms = get_manylinux_platforms((2, 28), "x86_64");
+        assert_eq!(platforms[0], "manylinux_2_28_x86_64");
+        let index = platforms
+            .iter()
+            .position(|p| p == "manylinux_2_17_x86_64")
+            .unwrap();
+        assert_eq!(platforms[index + 1], "manylinux2014_x86_64");
+        assert_eq!(platforms.last().unwrap(), "manylinux1_x86_64");
+
+        let platforms = get_manylinux_platforms((2, 17), "aarch64");
+        assert_eq!(
+            platforms,
+            vec!["manylinux_2_17_aarch64", "manylinux2014_aarch64"]
+        );
+    }
+
+    #[test]
+    fn musllinux_platforms() {
+        assert_eq!(
+            get_musllinux_platforms((1, 2), "aarch64"),
+            vec![
+                "musllinux_1_2_aarch64",
+                "musllinux_1_1_aarch64",
+                "musllinux_1_0_aarch64"
+            ]
+        );
+    }
+
+    #[test]
+    fn musl_version_from_apk_database() {
+        let tmp = TempDir::new().unwrap();
+        let database = tmp.path().join("installed");
+        fs::write(
+            &database,
+            "C:Q1\nP:busybox\nV:1.36.1-r2\n\nC:Q2\nP:musl\nV:1.2.4-r2\nA:x86_64\n",
+        )
+        .unwrap();
+        assert_eq!(get_musl_version(&database), Some((1, 2)));
+    }
+
+    #[test]
+    fn cpython_tags_are_in_packaging_order() {
+        let platforms = vec![
+            "manylinux_2_17_x86_64".to_string(),
+            "linux_x86_64".to_string(),
+        ];
+        let tags = get_cpython_tags(&cpython(""), (3, 12), &platforms);
+
+        assert_eq!(
+            &tags[0..6],
+            &[
+                "cp312-cp312-manylinux_2_17_x86_64",
+                "cp312-cp312-linux_x86_64",
+                "cp312-abi3-manylinux_2_17_x86_64",
+                "cp312-abi3-linux_x86_64",
+                "cp312-none-manylinux_2_17_x86_64",
+                "cp312-none-linux_x86_64",
+            ]
+        );
+        assert_eq!(tags[6], "cp311-abi3-manylinux_2_17_x86_64");
+        assert!(tags.contains(&"cp32-abi3-linux_x86_64".to_string()));
+        assert!(!tags.contains(&"cp31-abi3-linux_x86_64".to_string()));
+        assert_eq!(
+            &tags[tags.len() - 16..tags.len() - 13],
+            &["py30-none-linux_x86_64", "cp312-none-any", "py312-none-any"]
+        );
+        assert_eq!(tags.last().unwrap(), "py30-none-any");
+    }
+
+    #[test]
+    fn free_threaded_builds_do_not_support_abi3() {
+        let platforms = vec!["win_amd64".to_string()];
+        let tags = get_cpython_tags(&cpython("t"), (3, 13), &platforms);
+
+        assert_eq!(tags[0], "cp313-cp313t-win_amd64");
+        assert_eq!(tags[1], "cp313-none-win_amd64");
+        assert!(!tags.iter().any(|tag| tag.contains("abi3")));
+    }
+
+    #[test]
+    fn pypy_tags_use_the_soabi() {
+        let abi = AbiInfo {
+            implementation: "pypy".to_string(),
+            flags: String::new(),
+            soabi: Some("pypy310-pp73".to_string()),
+        };
+        let tags = get_generic_tags(&abi, (3, 10), &["linux_x86_64".to_string()]);
+        assert_eq!(
+            &tags[0..3],
+            &[
+                "pp310-pypy310_pp73-linux_x86_64",
+                "pp310-none-linux_x86_64",
+                "py310-none-linux_x86_64"
+            ]
+        );
+        assert!(tags.contains(&"pp3-none-any".to_string()));
+    }
+
+    #[test]
+    fn mac_platforms_on_arm64() {
+        let platforms = get_mac_platforms(&Architecture::Arm64, (14, 2)).unwrap();
+        assert_eq!(
+            &platforms[0..4],
+            &[
+                "macosx_14_0_arm64",
+                "macosx_14_0_universal2",
+                "macosx_13_0_arm64",
+                "macosx_13_0_universal2"
+            ]
+        );
+        assert!(platforms.contains(&"macosx_11_0_arm64".to_string()));
+        assert!(platforms.contains(&"macosx_10_16_universal2".to_string()));
+        assert!(!platforms.contains(&"macosx_10_16_arm64".to_string()));
+    }
+
+    #[test]
+    fn abi_flags_from_sysconfig_data_of_the_base_interpreter() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path().join("base");
+        let stdlib = base.join("lib").join("python3.13t");
+        fs::create_dir_all(&stdlib).unwrap();
+        fs::write(
+            stdlib.join("_sysconfigdata_t_linux_x86_64-linux-gnu.py"),
+            "build_time_vars = {'ABIFLAGS': 't',\n 'SOABI': 'cpython-313t-x86_64-linux-gnu',\n}",
+        )
+        .unwrap();
+        let venv = tmp.path().join("venv");
+        fs::create_dir_all(&venv).unwrap();
+        fs::write(
+            venv.join("pyvenv.cfg"),
+            format!("home = {}\nversion = 3.13.1\n", base.join("bin").display()),
+        )
+        .unwrap();
+
+        let executable = venv.join("bin").join("python");
+        let prefixes = get_prefixes(&OsFileSystem, &executable, Some(&venv));
+        assert!(prefixes.contains(&base));
+        assert_eq!(
+            get_abi(&OsFileSystem, &executable, &prefixes, None, (3, 13)),
+            AbiInfo {
+                implementation: "cpython".to_string(),
+                flags: "t".to_string(),
+                soabi: Some("cpython-313t-x86_64-linux-gnu".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn tags_for_an_executable_with_a_glibc_interpreter() {
+        let tmp = TempDir::new().unwrap();
+        let executable = tmp.path().join("bin").join("python3.12");
+        fs::create_dir_all(executable.parent().unwrap()).unwrap();
+        fs::write(
+            &executable,
+            elf_with_interpreter("/lib64/ld-linux-x86-64.so.2"),
+        )
+        .unwrap();
+
+        let tags = get_platform_tags(&executable, None, Some(&"3.12.1".parse().unwrap())).unwrap();
+
+        assert_eq!(tags.libc.as_ref().unwrap().flavor, LibcFlavor::Glibc);
+        assert_eq!(tags.platforms.last().unwrap(), "linux_x86_64");
+        assert!(tags.tags[0].starts_with("cp312-cp312-"));
+        assert!(tags.tags.contains(&"py3-none-any".to_string()));
+    }
+}
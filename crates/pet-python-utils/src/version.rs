@@ -4,27 +4,38 @@
 use crate::build_details::BuildDetails;
 use crate::headers::{self, Headers};
 use log::{trace, warn};
-use pet_core::pyvenv_cfg::PyVenvCfg;
-use pet_fs::path::resolve_symlink;
+use pet_core::{python_version::PythonVersion, pyvenv_cfg::PyVenvCfg};
+use pet_fs::{
+    file_system::{FileSystem, OsFileSystem},
+    path::resolve_symlink,
+};
 use std::{
     path::{Path, PathBuf},
     time::UNIX_EPOCH,
 };
 
-pub fn from_header_files(prefix: &Path) -> Option<String> {
+pub fn from_header_files(prefix: &Path) -> Option<PythonVersion> {
     Headers::get_version(prefix)
 }
-pub fn from_pyvenv_cfg(prefix: &Path) -> Option<String> {
+/// Same as `from_header_files`, looking at the files in the given file system.
+pub fn from_header_files_in(file_system: &dyn FileSystem, prefix: &Path) -> Option<PythonVersion> {
+    Headers::get_version_in(file_system, prefix)
+}
+pub fn from_pyvenv_cfg(prefix: &Path) -> Option<PythonVersion> {
     PyVenvCfg::find(prefix).and_then(|cfg| cfg.version)
 }
+/// Same as `from_pyvenv_cfg`, looking at the files in the given file system.
+pub fn from_pyvenv_cfg_in(file_system: &dyn FileSystem, prefix: &Path) -> Option<PythonVersion> {
+    PyVenvCfg::find_in(file_system, prefix).and_then(|cfg| cfg.version)
+}
 /// Reads the Python version from a `build-details.json` file ([PEP 739])
 /// installed by Python 3.14+ in the platform-independent stdlib directory.
 ///
 /// [PEP 739]: https://peps.python.org/pep-0739/
-pub fn from_build_details(prefix: &Path) -> Option<String> {
-    BuildDetails::find(prefix).map(|bd| bd.version_string())
+pub fn from_build_details(prefix: &Path) -> Option<PythonVersion> {
+    BuildDetails::find(prefix).and_then(|bd| bd.version())
 }
-pub fn from_creator_for_virtual_env(prefix: &Path) -> Option<String> {
+pub fn from_creator_for_virtual_env(prefix: &Path) -> Option<PythonVersion> {
     if let Some(version) = Headers::get_version(prefix) {
         return Some(version);
     }
@@ -62,7 +73,7 @@ pub fn from_creator_for_virtual_env(prefix: &Path) -> Option<String> {
             // Prefer build-details.json (Python 3.14+) over header parsing — it's
             // a single small file read vs. recursively scanning include/Headers.
             BuildDetails::find_with_hint(sys_root, pyver)
-                .map(|bd| bd.version_string())
+                .and_then(|bd| bd.version())
                 .or_else(|| headers::get_version(sys_root, pyver))
         }
     } else if cfg!(windows) {
@@ -73,7 +84,7 @@ pub fn from_creator_for_virtual_env(prefix: &Path) -> Option<String> {
     }
 }
 
-pub fn from_prefix(prefix: &Path) -> Option<String> {
+pub fn from_prefix(prefix: &Path) -> Option<PythonVersion> {
     if let Some(version) = from_pyvenv_cfg(prefix) {
         Some(version)
     } else if let Some(version) = from_build_details(prefix) {
@@ -83,6 +94,22 @@ pub fn from_prefix(prefix: &Path) -> Option<String> {
     }
 }
 
+/// Version of a regular install of Python (e.g. `/usr`) from the files installed with it.
+/// `pyver` is the major & minor version (e.g. from the name `python3.12`), when several versions share the prefix.
+pub fn from_sys_prefix(sys_prefix: &Path, pyver: Option<(u64, u64)>) -> Option<PythonVersion> {
+    from_sys_prefix_in(&OsFileSystem, sys_prefix, pyver)
+}
+/// Same as `from_sys_prefix`, looking at the files in the given file system.
+pub fn from_sys_prefix_in(
+    file_system: &dyn FileSystem,
+    sys_prefix: &Path,
+    pyver: Option<(u64, u64)>,
+) -> Option<PythonVersion> {
+    BuildDetails::find_with_hint_in(file_system, sys_prefix, pyver)
+        .and_then(|bd| bd.version())
+        .or_else(|| headers::get_version_in(file_system, sys_prefix, pyver))
+}
+
 /// When creating virtual envs using `python -m venv` or the like,
 /// The executable in the new environment ends up being a symlink to the python executable used to create the env.
 /// Using this information its possible to determine the version of the Python environment used to create the env.
@@ -113,7 +140,7 @@ fn get_python_exe_used_to_create_venv<T: AsRef<Path>>(executable: T) -> Option<P
 /// Then we can use the pyvenv.cfg to get the version of the virtual environment.
 fn get_version_from_pyvenv_if_pyvenv_cfg_and_exe_created_same_time(
     prefix: &Path,
-) -> Option<String> {
+) -> Option<PythonVersion> {
     let cfg = PyVenvCfg::find(prefix)?;
     let pyvenv_cfg = prefix.join("pyvenv.cfg");
     if !pyvenv_cfg.exists() {
@@ -196,7 +223,7 @@ mod tests {
             }"#,
         );
 
-        assert_eq!(from_prefix(prefix), Some("3.11.5".to_string()));
+        assert_eq!(from_prefix(prefix), Some("3.11.5".parse().unwrap()));
     }
 
     /// `build-details.json` should win over header parsing when both exist —
@@ -230,6 +257,6 @@ mod tests {
             }"#,
         );
 
-        assert_eq!(from_prefix(prefix), Some("3.14.1".to_string()));
+        assert_eq!(from_prefix(prefix), Some("3.14.1".parse().unwrap()));
     }
 }
@@ -13,11 +13,11 @@ use common::resolve_test_path;
 fn version_from_sys_prefix() {
     let path: PathBuf = resolve_test_path(&["unix", "pyvenv_cfg", ".venv"]);
     let version = version::from_prefix(&path).unwrap();
-    assert_eq!(version, "3.12.1");
+    assert_eq!(version.to_string(), "3.12.1");
 
     let path: PathBuf = resolve_test_path(&["unix", "pyvenv_cfg", ".venv", "bin"]);
     let version = version::from_prefix(&path).unwrap();
-    assert_eq!(version, "3.12.1");
+    assert_eq!(version.to_string(), "3.12.1");
 }
 
 #[cfg(unix)]
@@ -25,11 +25,11 @@ fn version_from_sys_prefix() {
 fn version_from_sys_prefix_using_version_info_format() {
     let path: PathBuf = resolve_test_path(&["unix", "pyvenv_cfg", "hatch_env"]);
     let version = version::from_prefix(&path).unwrap();
-    assert_eq!(version, "3.9.6.final.0");
+    assert_eq!(version.to_string(), "3.9.6.final.0");
 
     let path: PathBuf = resolve_test_path(&["unix", "pyvenv_cfg", "hatch_env", "bin"]);
     let version = version::from_prefix(&path).unwrap();
-    assert_eq!(version, "3.9.6.final.0");
+    assert_eq!(version.to_string(), "3.9.6.final.0");
 }
 
 #[cfg(unix)]
@@ -68,19 +68,19 @@ fn no_version_for_invalid_paths() {
 fn version_from_header_files() {
     let path: PathBuf = resolve_test_path(&["unix", "headers", "python3.9.9"]);
     let version = version::from_prefix(&path).unwrap();
-    assert_eq!(version, "3.9.9");
+    assert_eq!(version.to_string(), "3.9.9");
 
     let path: PathBuf = resolve_test_path(&["unix", "headers", "python3.9.9", "bin"]);
     let version = version::from_prefix(&path).unwrap();
-    assert_eq!(version, "3.9.9");
+    assert_eq!(version.to_string(), "3.9.9");
 
     let path: PathBuf = resolve_test_path(&["unix", "headers", "python3.10-dev", "bin"]);
     let version = version::from_prefix(&path).unwrap();
-    assert_eq!(version, "3.10.14+");
+    assert_eq!(version.to_string(), "3.10.14+");
 
     let path: PathBuf = resolve_test_path(&["unix", "headers", "python3.13", "bin"]);
     let version = version::from_prefix(&path).unwrap();
-    assert_eq!(version, "3.13.0a5");
+    assert_eq!(version.to_string(), "3.13.0a5");
 }
 
 #[cfg(unix)]
@@ -89,20 +89,20 @@ fn version_from_build_details_json() {
     // Final release: micro version, no pre-release suffix.
     let path: PathBuf = resolve_test_path(&["unix", "build_details", "python3.14"]);
     let version = version::from_prefix(&path).unwrap();
-    assert_eq!(version, "3.14.2");
+    assert_eq!(version.to_string(), "3.14.2");
 
     let path: PathBuf = resolve_test_path(&["unix", "build_details", "python3.14", "bin"]);
     let version = version::from_prefix(&path).unwrap();
-    assert_eq!(version, "3.14.2");
+    assert_eq!(version.to_string(), "3.14.2");
 
     // Alpha pre-release: serial appended as `aN`.
     let path: PathBuf = resolve_test_path(&["unix", "build_details", "python3.15a"]);
     let version = version::from_prefix(&path).unwrap();
-    assert_eq!(version, "3.15.0a1");
+    assert_eq!(version.to_string(), "3.15.0a1");
 
     // Direct accessor returns the same value.
     let direct =
         version::from_build_details(&resolve_test_path(&["unix", "build_details", "python3.14"]))
             .unwrap();
-    assert_eq!(direct, "3.14.2");
+    assert_eq!(direct.to_string(), "3.14.2");
 }
@@ -32,6 +32,9 @@ impl Reporter for CacheReporter {
     fn report_telemetry(&self, event: &pet_core::telemetry::TelemetryEvent) {
         self.reporter.report_telemetry(event);
     }
+    fn is_cancelled(&self) -> bool {
+        self.reporter.is_cancelled()
+    }
     fn report_manager(&self, manager: &EnvManager) {
         // First check with read lock
         {
@@ -122,7 +125,7 @@ mod tests {
             Some(PythonEnvironmentKind::Venv),
             Some(PathBuf::from("/tmp/.venv")),
             None,
-            Some("3.12.0".to_string()),
+            Some("3.12.0".parse().unwrap()),
         );
 
         reporter.report_environment(&environment);
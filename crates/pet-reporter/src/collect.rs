@@ -69,7 +69,7 @@ mod tests {
             Some(PythonEnvironmentKind::Venv),
             Some(PathBuf::from("/tmp/.venv")),
             Some(manager.clone()),
-            Some("3.12.0".to_string()),
+            Some("3.12.0".parse().unwrap()),
         );
 
         reporter.report_manager(&manager);
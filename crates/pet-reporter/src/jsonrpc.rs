@@ -9,11 +9,15 @@ use pet_core::{
     reporter::Reporter,
     telemetry::{get_telemetry_event_name, TelemetryEvent},
 };
-use pet_jsonrpc::send_message;
+use pet_jsonrpc::{send_message, transport::Connection};
 use serde::{Deserialize, Serialize};
 
 pub struct JsonRpcReporter {
     report_only: Option<PythonEnvironmentKind>,
+    /// Kinds of environments that are never reported.
+    exclude_kinds: Vec<PythonEnvironmentKind>,
+    /// Environments are reported from many threads, hence the connection is captured when created.
+    connection: Connection,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -31,42 +35,54 @@ impl Reporter for JsonRpcReporter {
             data: event.clone(),
         };
         trace!("Telemetry event {:?}", event.event);
-        send_message("telemetry", Some(event))
+        self.connection.send_message("telemetry", Some(event))
     }
     fn report_manager(&self, manager: &EnvManager) {
         trace!("Reporting Manager {:?}", manager);
-        send_message("manager", manager.into())
+        self.connection.send_message("manager", manager.into())
     }
 
     fn report_environment(&self, env: &PythonEnvironment) {
-        if !should_report_environment(self.report_only, env) {
+        if !should_report_environment(self.report_only, &self.exclude_kinds, env) {
             trace!(
-                "Skip Reporting Environment ({:?}) {:?} due to refresh request to report only {:?}",
+                "Skip Reporting Environment ({:?}) {:?} due to refresh request to report only {:?} & exclude {:?}",
                 env.kind,
                 env.executable
                     .clone()
                     .unwrap_or(env.prefix.clone().unwrap_or_default()),
-                self.report_only
+                self.report_only,
+                self.exclude_kinds
             );
             return;
         }
         trace!("Reporting Environment {:?}", env);
-        send_message("environment", env.into())
+        self.connection.send_message("environment", env.into())
     }
 }
 
 fn should_report_environment(
     report_only: Option<PythonEnvironmentKind>,
+    exclude_kinds: &[PythonEnvironmentKind],
     env: &PythonEnvironment,
 ) -> bool {
+    if env.kind.is_some_and(|kind| exclude_kinds.contains(&kind)) {
+        return false;
+    }
     match report_only {
         Some(kind) => env.kind == Some(kind),
         None => true,
     }
 }
 
-pub fn create_reporter(report_only: Option<PythonEnvironmentKind>) -> impl Reporter {
-    JsonRpcReporter { report_only }
+pub fn create_reporter(
+    report_only: Option<PythonEnvironmentKind>,
+    exclude_kinds: Vec<PythonEnvironmentKind>,
+) -> impl Reporter {
+    JsonRpcReporter {
+        report_only,
+        exclude_kinds,
+        connection: Connection::current(),
+    }
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Eq, Clone)]
@@ -122,7 +138,7 @@ mod tests {
             Some(kind),
             Some(PathBuf::from("/tmp/.venv")),
             None,
-            Some("3.12.0".to_string()),
+            Some("3.12.0".parse().unwrap()),
         )
     }
 
@@ -130,7 +146,7 @@ mod tests {
     fn environment_filter_allows_all_without_requested_kind() {
         let environment = create_environment(PythonEnvironmentKind::Venv);
 
-        assert!(should_report_environment(None, &environment));
+        assert!(should_report_environment(None, &[], &environment));
     }
 
     #[test]
@@ -139,6 +155,7 @@ mod tests {
 
         assert!(should_report_environment(
             Some(PythonEnvironmentKind::Poetry),
+            &[],
             &environment
         ));
     }
@@ -149,17 +166,41 @@ mod tests {
 
         assert!(!should_report_environment(
             Some(PythonEnvironmentKind::Poetry),
+            &[],
             &environment
         ));
     }
 
+    #[test]
+    fn environment_filter_rejects_excluded_kinds() {
+        let exclude_kinds = [PythonEnvironmentKind::Tox, PythonEnvironmentKind::Nox];
+
+        assert!(!should_report_environment(
+            None,
+            &exclude_kinds,
+            &create_environment(PythonEnvironmentKind::Tox)
+        ));
+        assert!(!should_report_environment(
+            Some(PythonEnvironmentKind::Nox),
+            &exclude_kinds,
+            &create_environment(PythonEnvironmentKind::Nox)
+        ));
+        assert!(should_report_environment(
+            None,
+            &exclude_kinds,
+            &create_environment(PythonEnvironmentKind::Venv)
+        ));
+    }
+
     #[test]
     fn telemetry_data_serializes_event_name_and_payload() {
         let event = TelemetryEvent::RefreshPerformance(
             pet_core::telemetry::refresh_performance::RefreshPerformance {
                 total: 10,
                 locators: BTreeMap::new(),
+                locators_queue_wait: BTreeMap::new(),
                 breakdown: BTreeMap::new(),
+                workspace_directories_visited: 0,
             },
         );
         let payload = TelemetryData {
@@ -187,6 +228,7 @@ mod tests {
             phase_elapsed_ms: None,
             locator_name: Some("Conda".to_string()),
             locator_elapsed_ms: Some(10),
+            file_system_type: None,
         });
         let payload = TelemetryData {
             event: get_telemetry_event_name(&event).to_string(),
@@ -128,7 +128,7 @@ mod tests {
             Some(kind),
             Some(PathBuf::from("/tmp/env")),
             None,
-            Some("3.12.0".to_string()),
+            Some("3.12.0".parse().unwrap()),
         )
     }
 
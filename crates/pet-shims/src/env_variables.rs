@@ -0,0 +1,20 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use pet_core::os_environment::Environment;
+
+#[derive(Debug, Clone)]
+// NOTE: Do not implement Default trait, as we do not want to ever forget to set the values.
+// Lets be explicit, this way we never miss a value (in Windows or Unix).
+pub struct EnvVariables {
+    /// Overrides the pyenv version for the current shell, e.g. `3.12.1` or `3.12:3.11`.
+    pub pyenv_version: Option<String>,
+}
+
+impl EnvVariables {
+    pub fn from(env: &dyn Environment) -> Self {
+        EnvVariables {
+            pyenv_version: env.get_env_var("PYENV_VERSION".to_string()),
+        }
+    }
+}
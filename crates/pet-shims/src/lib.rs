@@ -0,0 +1,285 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use env_variables::EnvVariables;
+use lazy_static::lazy_static;
+use log::trace;
+use pet_core::{os_environment::Environment, python_environment::PythonEnvironment};
+use pet_fs::file_system::{FileSystem, OsFileSystem};
+use regex::Regex;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub mod env_variables;
+pub mod pyenv;
+pub mod scoop;
+
+lazy_static! {
+    static ref PYTHON_SHIM_NAME: Regex =
+        Regex::new(r"^python(\d+(\.\d+)*)?$").expect("error parsing Python shim name regex");
+}
+
+/// Version managers that put shims (small wrapper programs) on `PATH`.
+/// The shim decides which interpreter to run based on the current directory and configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShimKind {
+    Pyenv,
+    PyenvWin,
+    Scoop,
+    Mise,
+    Asdf,
+}
+
+impl ShimKind {
+    /// Name of the tool that owns the shim.
+    pub fn tool(&self) -> &'static str {
+        match self {
+            ShimKind::Pyenv | ShimKind::PyenvWin => "pyenv",
+            ShimKind::Scoop => "scoop",
+            ShimKind::Mise => "mise",
+            ShimKind::Asdf => "asdf",
+        }
+    }
+}
+
+/// Identifies a shims folder by its layout.
+///
+/// - pyenv: `<PYENV_ROOT>/shims`
+/// - pyenv-win: `~/.pyenv/pyenv-win/shims`
+/// - scoop: `~/scoop/shims`, `C:\ProgramData\scoop\shims`
+/// - mise: `~/.local/share/mise/shims`
+/// - asdf: `~/.asdf/shims`
+pub fn get_shims_dir_kind(dir: &Path) -> Option<ShimKind> {
+    if !dir.ends_with("shims") {
+        return None;
+    }
+    let owner = dir.parent()?.file_name()?.to_string_lossy().to_lowercase();
+    if owner.contains("pyenv-win") {
+        Some(ShimKind::PyenvWin)
+    } else if owner.contains("pyenv") {
+        Some(ShimKind::Pyenv)
+    } else if owner.contains("scoop") {
+        Some(ShimKind::Scoop)
+    } else if owner.contains("mise") || owner.contains("rtx") {
+        Some(ShimKind::Mise)
+    } else if owner.contains("asdf") {
+        Some(ShimKind::Asdf)
+    } else {
+        None
+    }
+}
+
+/// Identifies the shim manager that owns a file, if the file is a shim.
+/// Scoop can be installed into any folder (`SCOOP`), hence its shims are also recognized by
+/// the `.shim` file next to them.
+pub fn get_shim_kind(shim: &Path) -> Option<ShimKind> {
+    get_shim_kind_in(&OsFileSystem, shim)
+}
+
+/// Same as `get_shim_kind`, looking at the files in the given file system.
+pub fn get_shim_kind_in(file_system: &dyn FileSystem, shim: &Path) -> Option<ShimKind> {
+    get_shims_dir_kind(shim.parent()?).or_else(|| {
+        if shim.extension().is_some() && scoop::get_shim_file(file_system, shim).is_some() {
+            Some(ShimKind::Scoop)
+        } else {
+            None
+        }
+    })
+}
+
+/// Lists the Python shims (`python`, `python3`, `python3.12`, ...) in a shims folder.
+pub fn find_python_shims(dir: &Path) -> Vec<PathBuf> {
+    find_python_shims_in(&OsFileSystem, dir)
+}
+
+/// Same as `find_python_shims`, looking at the files in the given file system.
+pub fn find_python_shims_in(file_system: &dyn FileSystem, dir: &Path) -> Vec<PathBuf> {
+    let mut shims = file_system
+        .read_dir(dir)
+        .map(|entries| {
+            entries
+                .into_iter()
+                .filter(|path| file_system.is_file(path) && is_python_shim_name(path))
+                .collect::<Vec<PathBuf>>()
+        })
+        .unwrap_or_default();
+    shims.sort();
+    shims
+}
+
+fn is_python_shim_name(shim: &Path) -> bool {
+    PYTHON_SHIM_NAME.is_match(&get_shim_name(shim).to_lowercase())
+}
+
+/// Name of the command a shim runs, e.g. `python3.12` for `python3.12`, `python3.12.bat` & `python3.12.exe`.
+/// Not using `Path::file_stem`, as that turns `python3.12` into `python3`.
+pub(crate) fn get_shim_name(shim: &Path) -> String {
+    let name = shim.file_name().unwrap_or_default().to_string_lossy();
+    [".exe", ".bat", ".cmd"]
+        .iter()
+        .find_map(|ext| {
+            name.len()
+                .checked_sub(ext.len())
+                .filter(|len| name.is_char_boundary(*len))
+                .filter(|len| name[*len..].eq_ignore_ascii_case(ext))
+                .map(|len| name[..len].to_string())
+        })
+        .unwrap_or(name.to_string())
+}
+
+/// Resolves shims to the interpreters they would run, without spawning the shim or its tool.
+pub struct ShimResolver {
+    env_vars: EnvVariables,
+    file_system: Arc<dyn FileSystem>,
+}
+
+impl ShimResolver {
+    pub fn from(environment: &dyn Environment) -> ShimResolver {
+        ShimResolver {
+            env_vars: EnvVariables::from(environment),
+            file_system: environment.file_system(),
+        }
+    }
+
+    /// Resolves the shim as if it were run from `cwd`.
+    /// Without a `cwd`, project specific configuration (such as `.python-version`) is ignored.
+    ///
+    /// Returns `None` if this is not a shim or the target cannot be determined without
+    /// spawning (e.g. mise & asdf, or pyenv's `system` version).
+    pub fn resolve(&self, shim: &Path, cwd: Option<&Path>) -> Option<PathBuf> {
+        let file_system = self.file_system.as_ref();
+        let kind = get_shim_kind_in(file_system, shim)?;
+        let target = match kind {
+            ShimKind::Pyenv | ShimKind::PyenvWin => pyenv::resolve_pyenv_shim(
+                file_system,
+                shim.parent()?.parent()?,
+                shim,
+                cwd,
+                &self.env_vars,
+                kind == ShimKind::PyenvWin,
+            ),
+            ShimKind::Scoop => scoop::resolve_scoop_shim(file_system, shim),
+            ShimKind::Mise | ShimKind::Asdf => None,
+        };
+        trace!("Resolved {:?} shim {:?} to {:?}", kind, shim, target);
+        target
+    }
+}
+
+/// Adds shims to the symlinks of the environment they resolve to.
+///
+/// Shims are deliberately added after the environment has been built, as the builder would pick
+/// the shortest path (generally the shim) as the executable of the environment.
+pub fn add_shim_symlinks(environment: &mut PythonEnvironment, shims: &[PathBuf]) {
+    if shims.is_empty() {
+        return;
+    }
+    let mut symlinks = environment.symlinks.clone().unwrap_or_default();
+    if symlinks.is_empty() {
+        if let Some(executable) = &environment.executable {
+            symlinks.push(executable.clone());
+        }
+    }
+    symlinks.extend(shims.iter().cloned());
+    symlinks.sort();
+    symlinks.dedup();
+    environment.symlinks = Some(symlinks);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn shims_dir_kind_is_detected_from_folder_layout() {
+        assert_eq!(
+            get_shims_dir_kind(Path::new("/home/user/.pyenv/shims")),
+            Some(ShimKind::Pyenv)
+        );
+        assert_eq!(
+            get_shims_dir_kind(Path::new("/home/user/.pl/pyenv/shims")),
+            Some(ShimKind::Pyenv)
+        );
+        assert_eq!(
+            get_shims_dir_kind(Path::new("/Users/user/.pyenv/pyenv-win/shims")),
+            Some(ShimKind::PyenvWin)
+        );
+        assert_eq!(
+            get_shims_dir_kind(Path::new("/Users/user/scoop/shims")),
+            Some(ShimKind::Scoop)
+        );
+        assert_eq!(
+            get_shims_dir_kind(Path::new("/home/user/.local/share/mise/shims")),
+            Some(ShimKind::Mise)
+        );
+        assert_eq!(
+            get_shims_dir_kind(Path::new("/home/user/.asdf/shims")),
+            Some(ShimKind::Asdf)
+        );
+        assert_eq!(get_shims_dir_kind(Path::new("/usr/bin")), None);
+        assert_eq!(get_shims_dir_kind(Path::new("/home/user/shims")), None);
+    }
+
+    #[test]
+    fn scoop_shim_in_custom_folder_is_detected_by_shim_file() {
+        let dir = TempDir::new().unwrap();
+        let shim = dir.path().join("python.exe");
+        fs::write(&shim, "").unwrap();
+        fs::write(dir.path().join("python.shim"), "path = C:\\python.exe").unwrap();
+
+        assert_eq!(get_shim_kind(&shim), Some(ShimKind::Scoop));
+        assert_eq!(get_shim_kind(&dir.path().join("pip.exe")), None);
+    }
+
+    #[test]
+    fn python_shims_are_listed() {
+        let dir = TempDir::new().unwrap();
+        for file in [
+            "python",
+            "python3",
+            "python3.12",
+            "python.bat",
+            "python.exe",
+            "python.shim",
+            "pip",
+            "pythonw",
+        ] {
+            fs::write(dir.path().join(file), "").unwrap();
+        }
+
+        let shims = find_python_shims(dir.path())
+            .into_iter()
+            .map(|shim| shim.file_name().unwrap().to_string_lossy().to_string())
+            .collect::<Vec<String>>();
+
+        assert_eq!(
+            shims,
+            vec![
+                "python",
+                "python.bat",
+                "python.exe",
+                "python3",
+                "python3.12"
+            ]
+        );
+    }
+
+    #[test]
+    fn shims_are_added_as_symlinks_without_changing_the_executable() {
+        let executable = PathBuf::from("/home/user/.pyenv/versions/3.12.1/bin/python");
+        let shim = PathBuf::from("/home/user/.pyenv/shims/python");
+        let mut environment = PythonEnvironment {
+            executable: Some(executable.clone()),
+            ..Default::default()
+        };
+
+        add_shim_symlinks(&mut environment, &[shim.clone(), shim.clone()]);
+
+        assert_eq!(environment.executable, Some(executable.clone()));
+        assert_eq!(environment.symlinks, Some(vec![shim, executable]));
+    }
+}
@@ -0,0 +1,328 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use lazy_static::lazy_static;
+use log::trace;
+use pet_fs::file_system::FileSystem;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+use crate::{env_variables::EnvVariables, get_shim_name};
+
+lazy_static! {
+    // Stable versions that can be selected by a prefix, e.g. `3.12` selects `3.12.4`.
+    static ref NUMERIC_VERSION: Regex =
+        Regex::new(r"^\d+(\.\d+)*$").expect("error parsing numeric version regex in shims");
+}
+
+/// Resolves a pyenv (or pyenv-win) shim to the interpreter it would run.
+///
+/// `root` is the folder containing `shims`, `versions` and the global `version` file,
+/// i.e. `~/.pyenv` for pyenv and `~/.pyenv/pyenv-win` for pyenv-win.
+///
+/// The version is selected the same way pyenv does it:
+/// 1. `PYENV_VERSION`
+/// 2. The first `.python-version` file found in `cwd` or any of its parents
+/// 3. The global `version` file in `root`
+///
+/// Returns `None` when the selected version is `system` (or not installed), as that requires
+/// searching `PATH` without the shims, which is best left to the caller.
+pub fn resolve_pyenv_shim(
+    file_system: &dyn FileSystem,
+    root: &Path,
+    shim: &Path,
+    cwd: Option<&Path>,
+    env_vars: &EnvVariables,
+    is_pyenv_win: bool,
+) -> Option<PathBuf> {
+    let shim_name = get_shim_name(shim);
+    let versions_dir = root.join("versions");
+    let versions = get_selected_versions(file_system, root, cwd, env_vars);
+    trace!("Pyenv shim {:?} selected versions {:?}", shim, versions);
+
+    for version in versions {
+        if version == "system" {
+            continue;
+        }
+        let Some(prefix) = find_version_dir(file_system, &versions_dir, &version) else {
+            continue;
+        };
+        let candidates = if is_pyenv_win {
+            vec![
+                prefix.join(format!("{shim_name}.exe")),
+                prefix.join("python.exe"),
+            ]
+        } else {
+            vec![prefix.join("bin").join(&shim_name)]
+        };
+        if let Some(exe) = candidates.into_iter().find(|exe| file_system.is_file(exe)) {
+            return Some(exe);
+        }
+    }
+    None
+}
+
+/// Returns the versions selected for the shell, in order of preference.
+pub fn get_selected_versions(
+    file_system: &dyn FileSystem,
+    root: &Path,
+    cwd: Option<&Path>,
+    env_vars: &EnvVariables,
+) -> Vec<String> {
+    if let Some(versions) = &env_vars.pyenv_version {
+        let versions = split_versions(versions);
+        if !versions.is_empty() {
+            return versions;
+        }
+    }
+    if let Some(cwd) = cwd {
+        for dir in cwd.ancestors() {
+            if let Some(versions) = read_version_file(file_system, &dir.join(".python-version")) {
+                return versions;
+            }
+        }
+    }
+    for file in ["version", "global", "default"] {
+        if let Some(versions) = read_version_file(file_system, &root.join(file)) {
+            return versions;
+        }
+    }
+    vec![]
+}
+
+fn read_version_file(file_system: &dyn FileSystem, file: &Path) -> Option<Vec<String>> {
+    let contents = file_system.read_to_string(file).ok()?;
+    let versions = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .flat_map(split_versions)
+        .collect::<Vec<String>>();
+    if versions.is_empty() {
+        None
+    } else {
+        Some(versions)
+    }
+}
+
+fn split_versions(versions: &str) -> Vec<String> {
+    versions
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| v.to_string())
+        .collect()
+}
+
+/// Finds the installed version folder for a version name.
+/// Exact names win, followed by the `python-` prefixed form and finally the latest
+/// installed version that starts with the given prefix (e.g. `3.12` => `3.12.4`).
+fn find_version_dir(
+    file_system: &dyn FileSystem,
+    versions_dir: &Path,
+    version: &str,
+) -> Option<PathBuf> {
+    let exact = versions_dir.join(version);
+    if file_system.is_dir(&exact) {
+        return Some(exact);
+    }
+    if let Some(version) = version.strip_prefix("python-") {
+        let exact = versions_dir.join(version);
+        if file_system.is_dir(&exact) {
+            return Some(exact);
+        }
+    }
+    if !NUMERIC_VERSION.is_match(version) {
+        return None;
+    }
+    let prefix = format!("{version}.");
+    file_system
+        .read_dir(versions_dir)
+        .ok()?
+        .into_iter()
+        .filter(|path| file_system.is_dir(path))
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().to_string();
+            if name.starts_with(&prefix) && NUMERIC_VERSION.is_match(&name) {
+                Some((parse_version(&name), path))
+            } else {
+                None
+            }
+        })
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, path)| path)
+}
+
+fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .split('.')
+        .map(|part| part.parse::<u64>().unwrap_or_default())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pet_fs::file_system::OsFileSystem;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn env_vars(pyenv_version: Option<&str>) -> EnvVariables {
+        EnvVariables {
+            pyenv_version: pyenv_version.map(|v| v.to_string()),
+        }
+    }
+
+    fn create_version(root: &Path, version: &str, exes: &[&str]) -> PathBuf {
+        let bin = root.join("versions").join(version).join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        for exe in exes {
+            fs::write(bin.join(exe), "").unwrap();
+        }
+        bin
+    }
+
+    #[test]
+    fn pyenv_version_variable_takes_precedence() {
+        let root = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        fs::write(root.path().join("version"), "3.11.9\n").unwrap();
+        fs::write(project.path().join(".python-version"), "3.10.1\n").unwrap();
+
+        assert_eq!(
+            get_selected_versions(
+                &OsFileSystem,
+                root.path(),
+                Some(project.path()),
+                &env_vars(Some("3.12.1:3.11.9"))
+            ),
+            vec!["3.12.1".to_string(), "3.11.9".to_string()]
+        );
+    }
+
+    #[test]
+    fn python_version_file_is_found_in_parent_of_cwd() {
+        let root = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        let nested = project.path().join("src").join("pkg");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.path().join("version"), "3.11.9\n").unwrap();
+        fs::write(project.path().join(".python-version"), "# pinned\n3.10.1\n").unwrap();
+
+        assert_eq!(
+            get_selected_versions(&OsFileSystem, root.path(), Some(&nested), &env_vars(None)),
+            vec!["3.10.1".to_string()]
+        );
+    }
+
+    #[test]
+    fn global_version_file_is_used_without_local_version() {
+        let root = TempDir::new().unwrap();
+        fs::write(root.path().join("version"), "3.11.9\n").unwrap();
+
+        assert_eq!(
+            get_selected_versions(&OsFileSystem, root.path(), None, &env_vars(None)),
+            vec!["3.11.9".to_string()]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shim_resolves_to_executable_of_selected_version() {
+        let root = TempDir::new().unwrap();
+        let bin = create_version(root.path(), "3.12.1", &["python", "python3"]);
+        fs::write(root.path().join("version"), "3.12.1").unwrap();
+
+        assert_eq!(
+            resolve_pyenv_shim(
+                &OsFileSystem,
+                root.path(),
+                &root.path().join("shims").join("python3"),
+                None,
+                &env_vars(None),
+                false
+            ),
+            Some(bin.join("python3"))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shim_falls_through_to_next_version_with_the_executable() {
+        let root = TempDir::new().unwrap();
+        create_version(root.path(), "3.12.1", &["python", "python3.12"]);
+        let bin = create_version(root.path(), "3.11.9", &["python", "python3.11"]);
+
+        assert_eq!(
+            resolve_pyenv_shim(
+                &OsFileSystem,
+                root.path(),
+                &root.path().join("shims").join("python3.11"),
+                None,
+                &env_vars(Some("3.12.1:3.11.9")),
+                false
+            ),
+            Some(bin.join("python3.11"))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn version_prefix_selects_latest_installed_version() {
+        let root = TempDir::new().unwrap();
+        create_version(root.path(), "3.12.2", &["python"]);
+        let bin = create_version(root.path(), "3.12.10", &["python"]);
+        create_version(root.path(), "3.13.0", &["python"]);
+
+        assert_eq!(
+            resolve_pyenv_shim(
+                &OsFileSystem,
+                root.path(),
+                &root.path().join("shims").join("python"),
+                None,
+                &env_vars(Some("3.12")),
+                false
+            ),
+            Some(bin.join("python"))
+        );
+    }
+
+    #[test]
+    fn system_version_is_not_resolved() {
+        let root = TempDir::new().unwrap();
+        fs::write(root.path().join("version"), "system").unwrap();
+
+        assert_eq!(
+            resolve_pyenv_shim(
+                &OsFileSystem,
+                root.path(),
+                &root.path().join("shims").join("python"),
+                None,
+                &env_vars(None),
+                false
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn pyenv_win_shim_resolves_to_exe_in_version_folder() {
+        let root = TempDir::new().unwrap();
+        let version = root.path().join("versions").join("3.12.1");
+        fs::create_dir_all(&version).unwrap();
+        fs::write(version.join("python.exe"), "").unwrap();
+        fs::write(root.path().join("version"), "3.12.1").unwrap();
+
+        assert_eq!(
+            resolve_pyenv_shim(
+                &OsFileSystem,
+                root.path(),
+                &root.path().join("shims").join("python3.bat"),
+                None,
+                &env_vars(None),
+                true
+            ),
+            Some(version.join("python.exe"))
+        );
+    }
+}
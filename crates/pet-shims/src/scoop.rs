@@ -0,0 +1,92 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use pet_fs::file_system::FileSystem;
+use std::path::{Path, PathBuf};
+
+/// Resolves a scoop shim (e.g. `~/scoop/shims/python.exe`) to the interpreter it runs.
+///
+/// Every scoop shim executable has a `<name>.shim` file next to it with the target, e.g.
+/// `path = "C:\Users\user\scoop\apps\python\current\python.exe"`.
+pub fn resolve_scoop_shim(file_system: &dyn FileSystem, shim: &Path) -> Option<PathBuf> {
+    let contents = file_system
+        .read_to_string(&get_shim_file(file_system, shim)?)
+        .ok()?;
+    let target = contents.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        if key.trim() == "path" {
+            Some(value.trim().trim_matches('"').to_string())
+        } else {
+            None
+        }
+    })?;
+    if target.is_empty() {
+        None
+    } else {
+        Some(PathBuf::from(target))
+    }
+}
+
+/// Returns the `.shim` file that describes a scoop shim, if there is one.
+pub fn get_shim_file(file_system: &dyn FileSystem, shim: &Path) -> Option<PathBuf> {
+    let shim_file = shim.with_extension("shim");
+    if file_system.is_file(&shim_file) {
+        Some(shim_file)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pet_fs::file_system::OsFileSystem;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn shim_file_with_quoted_path_is_resolved() {
+        let dir = TempDir::new().unwrap();
+        let shim = dir.path().join("python.exe");
+        fs::write(&shim, "").unwrap();
+        fs::write(
+            dir.path().join("python.shim"),
+            "path = \"C:\\Users\\user\\scoop\\apps\\python\\current\\python.exe\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            resolve_scoop_shim(&OsFileSystem, &shim),
+            Some(PathBuf::from(
+                "C:\\Users\\user\\scoop\\apps\\python\\current\\python.exe"
+            ))
+        );
+    }
+
+    #[test]
+    fn shim_file_with_unquoted_path_and_args_is_resolved() {
+        let dir = TempDir::new().unwrap();
+        let shim = dir.path().join("python3.exe");
+        fs::write(
+            dir.path().join("python3.shim"),
+            "path = C:\\scoop\\apps\\python\\current\\python.exe\nargs = -X utf8\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            resolve_scoop_shim(&OsFileSystem, &shim),
+            Some(PathBuf::from(
+                "C:\\scoop\\apps\\python\\current\\python.exe"
+            ))
+        );
+    }
+
+    #[test]
+    fn missing_shim_file_is_not_resolved() {
+        let dir = TempDir::new().unwrap();
+        let shim = dir.path().join("python.exe");
+        fs::write(&shim, "").unwrap();
+
+        assert_eq!(resolve_scoop_shim(&OsFileSystem, &shim), None);
+    }
+}
@@ -3,19 +3,12 @@
 
 use std::ops::Deref;
 
-use lazy_static::lazy_static;
 use log::warn;
 use pet_core::{
-    python_environment::PythonEnvironment, reporter::Reporter,
+    python_environment::PythonEnvironment, python_version::PythonVersion, reporter::Reporter,
     telemetry::inaccurate_python_info::InaccuratePythonEnvironmentInfo,
 };
 use pet_fs::path::norm_case;
-use regex::Regex;
-
-lazy_static! {
-    static ref PYTHON_VERSION: Regex = Regex::new(r"(\d+\.\d+\.\d+).*")
-        .expect("Error creating Python Version Regex for comparison");
-}
 
 pub fn report_inaccuracies_identified_after_resolving(
     _reporter: &dyn Reporter,
@@ -60,10 +53,11 @@ pub fn report_inaccuracies_identified_after_resolving(
         invalid_arch = false;
     }
 
-    let invalid_version = are_versions_different(
-        &resolved.version.clone()?,
-        &env.version.clone().unwrap_or_default(),
-    );
+    let resolved_version = resolved.version.as_ref()?;
+    let invalid_version = env
+        .version
+        .as_ref()
+        .and_then(|version| are_versions_different(resolved_version, version));
 
     if invalid_executable
         || executable_not_in_symlinks
@@ -89,12 +83,12 @@ pub fn report_inaccuracies_identified_after_resolving(
     None
 }
 
-fn are_versions_different(actual: &str, expected: &str) -> Option<bool> {
-    let actual = PYTHON_VERSION.captures(actual)?;
-    let actual = actual.get(1)?.as_str().to_string();
-    let expected = PYTHON_VERSION.captures(expected)?;
-    let expected = expected.get(1)?.as_str().to_string();
-    Some(actual != expected)
+/// Only `major.minor.micro` is compared, e.g. `3.12.7rc1` is the same as `3.12.7`.
+fn are_versions_different(actual: &PythonVersion, expected: &PythonVersion) -> Option<bool> {
+    if !actual.is_complete() || !expected.is_complete() {
+        return None;
+    }
+    Some(actual.release()[..3] != expected.release()[..3])
 }
 
 #[cfg(test)]
@@ -123,7 +117,7 @@ mod tests {
         PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Venv))
             .executable(Some(executable))
             .prefix(Some(prefix))
-            .version(Some(version.to_string()))
+            .version(PythonVersion::parse(version))
             .symlinks(Some(symlinks))
             .build()
     }
@@ -191,7 +185,7 @@ mod tests {
         // env has no prefix
         let env = PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Venv))
             .executable(Some(exe.clone()))
-            .version(Some("3.12.7".to_string()))
+            .version(PythonVersion::parse("3.12.7"))
             .symlinks(Some(vec![exe.clone()]))
             .build();
         let resolved = make_env(exe.clone(), prefix, "3.12.7", vec![exe]);
@@ -205,36 +199,44 @@ mod tests {
 
     // ── are_versions_different ────────────────────────────────────
 
+    fn versions_differ(actual: &str, expected: &str) -> Option<bool> {
+        are_versions_different(
+            &PythonVersion::parse(actual)?,
+            &PythonVersion::parse(expected)?,
+        )
+    }
+
     #[test]
     fn same_version_is_not_different() {
-        assert_eq!(are_versions_different("3.12.7", "3.12.7"), Some(false));
+        assert_eq!(versions_differ("3.12.7", "3.12.7"), Some(false));
     }
 
     #[test]
     fn different_patch_version_is_detected() {
-        assert_eq!(are_versions_different("3.12.7", "3.12.6"), Some(true));
+        assert_eq!(versions_differ("3.12.7", "3.12.6"), Some(true));
     }
 
     #[test]
     fn different_minor_version_is_detected() {
-        assert_eq!(are_versions_different("3.13.0", "3.12.7"), Some(true));
+        assert_eq!(versions_differ("3.13.0", "3.12.7"), Some(true));
     }
 
     #[test]
     fn version_with_suffix_compares_only_numeric_part() {
-        // "3.12.7+" or "3.12.7rc1" — the regex extracts only the digits
-        assert_eq!(are_versions_different("3.12.7rc1", "3.12.7"), Some(false));
+        // "3.12.7+" or "3.12.7rc1" — only the release is compared
+        assert_eq!(versions_differ("3.12.7rc1", "3.12.7"), Some(false));
+        assert_eq!(versions_differ("3.12.7.final.0", "3.12.7"), Some(false));
     }
 
     #[test]
     fn non_version_strings_return_none() {
-        assert_eq!(are_versions_different("not-a-version", "3.12.7"), None);
-        assert_eq!(are_versions_different("3.12.7", "not-a-version"), None);
+        assert_eq!(versions_differ("not-a-version", "3.12.7"), None);
+        assert_eq!(versions_differ("3.12.7", "not-a-version"), None);
     }
 
     #[test]
     fn empty_expected_version_returns_none() {
-        assert_eq!(are_versions_different("3.12.7", ""), None);
+        assert_eq!(versions_differ("3.12.7", ""), None);
     }
 
     // ── executable mismatch ───────────────────────────────────────
@@ -262,7 +264,7 @@ mod tests {
 
         let env = PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Venv))
             .prefix(Some(prefix.clone()))
-            .version(Some("3.12.7".to_string()))
+            .version(PythonVersion::parse("3.12.7"))
             .symlinks(Some(vec![exe.clone()]))
             .build();
         let resolved = make_env(exe.clone(), prefix, "3.12.7", vec![exe]);
@@ -321,14 +323,14 @@ mod tests {
         let env = PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Venv))
             .executable(Some(exe.clone()))
             .prefix(Some(prefix.clone()))
-            .version(Some("3.12.7".to_string()))
+            .version(PythonVersion::parse("3.12.7"))
             .symlinks(Some(vec![exe.clone()]))
             .arch(Some(Architecture::X64))
             .build();
         let resolved = PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Venv))
             .executable(Some(exe.clone()))
             .prefix(Some(prefix))
-            .version(Some("3.12.7".to_string()))
+            .version(PythonVersion::parse("3.12.7"))
             .symlinks(Some(vec![exe]))
             .arch(Some(Architecture::X86))
             .build();
@@ -351,7 +353,7 @@ mod tests {
         let resolved = PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Venv))
             .executable(Some(exe.clone()))
             .prefix(Some(prefix))
-            .version(Some("3.12.7".to_string()))
+            .version(PythonVersion::parse("3.12.7"))
             .symlinks(Some(vec![exe]))
             .arch(Some(Architecture::X64))
             .build();
@@ -0,0 +1,234 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Environments created by [tox](https://tox.wiki) in the `.tox` directory of a project,
+//! e.g. `<project>/.tox/py311`.
+//!
+//! These are virtual environments (hence would otherwise be identified as Venv),
+//! they are identified by the `.tox-info.json` file tox 4 writes in each environment,
+//! or (for older versions of tox) by the tox configuration of the project.
+
+use std::path::{Path, PathBuf};
+
+use log::trace;
+use pet_core::{
+    env::PythonEnv,
+    python_environment::{PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind},
+    pyvenv_cfg::PyVenvCfg,
+    reporter::Reporter,
+    Locator, LocatorKind,
+};
+use pet_fs::file_system::FileSystem;
+use pet_python_utils::arch::get_architecture;
+use pet_python_utils::executable::find_executables_in;
+use serde::Deserialize;
+
+/// Directory (relative to the project) tox creates the environments in.
+pub const TOX_DIRECTORY: &str = ".tox";
+
+/// Contents of the `.tox-info.json` file written by tox 4.
+#[derive(Deserialize, Debug)]
+struct ToxInfo {
+    #[serde(rename = "ToxEnv")]
+    tox_env: Option<ToxEnvInfo>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ToxEnvInfo {
+    name: Option<String>,
+}
+
+fn read_tox_info(file_system: &dyn FileSystem, prefix: &Path) -> Option<ToxInfo> {
+    let contents = file_system
+        .read_to_string(&prefix.join(".tox-info.json"))
+        .ok()?;
+    match serde_json::from_str(&contents) {
+        Ok(info) => Some(info),
+        Err(err) => {
+            trace!("Failed to parse .tox-info.json in {:?}: {}", prefix, err);
+            None
+        }
+    }
+}
+
+/// Whether tox is configured in the project, i.e. there is a `tox.ini` or `tox.toml`,
+/// a `[tool.tox]` table in `pyproject.toml` or a `[tox:tox]` section in `setup.cfg`.
+pub fn is_tox_project(file_system: &dyn FileSystem, project: &Path) -> bool {
+    if file_system.is_file(&project.join("tox.ini"))
+        || file_system.is_file(&project.join("tox.toml"))
+    {
+        return true;
+    }
+    if let Ok(contents) = file_system.read_to_string(&project.join("pyproject.toml")) {
+        if let Ok(pyproject) = toml::from_str::<toml::Table>(&contents) {
+            if pyproject
+                .get("tool")
+                .and_then(|tool| tool.get("tox"))
+                .is_some()
+            {
+                return true;
+            }
+        }
+    }
+    file_system
+        .read_to_string(&project.join("setup.cfg"))
+        .is_ok_and(|contents| contents.lines().any(|line| line.trim() == "[tox:tox]"))
+}
+
+/// The project of an environment in the `.tox` directory of the project.
+fn get_project(prefix: &Path) -> Option<PathBuf> {
+    let tox_dir = prefix.parent()?;
+    if tox_dir.file_name()? != TOX_DIRECTORY {
+        return None;
+    }
+    tox_dir.parent().map(Path::to_path_buf)
+}
+
+fn get_prefix(env: &PythonEnv) -> Option<PathBuf> {
+    if let Some(prefix) = &env.prefix {
+        return Some(prefix.clone());
+    }
+    let bin = env.executable.parent()?;
+    if bin.ends_with("bin") || bin.ends_with("Scripts") {
+        bin.parent().map(Path::to_path_buf)
+    } else {
+        None
+    }
+}
+
+pub struct Tox {}
+
+impl Tox {
+    pub fn new() -> Tox {
+        Tox {}
+    }
+}
+impl Default for Tox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Locator for Tox {
+    fn get_kind(&self) -> LocatorKind {
+        LocatorKind::Tox
+    }
+    fn supported_categories(&self) -> Vec<PythonEnvironmentKind> {
+        vec![PythonEnvironmentKind::Tox]
+    }
+
+    fn try_from(&self, env: &PythonEnv) -> Option<PythonEnvironment> {
+        let file_system = env.file_system.as_ref();
+        let prefix = get_prefix(env)?;
+        let project = get_project(&prefix)?;
+        let tox_info = read_tox_info(file_system, &prefix);
+        if tox_info.is_none() && !is_tox_project(file_system, &project) {
+            return None;
+        }
+
+        // The name of the tox environment, e.g. `py311`.
+        let name = tox_info
+            .and_then(|info| info.tox_env)
+            .and_then(|tox_env| tox_env.name)
+            .or_else(|| {
+                prefix
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+            });
+        let version = env
+            .version
+            .clone()
+            .or_else(|| PyVenvCfg::find_in(file_system, &prefix).and_then(|cfg| cfg.version));
+        let symlinks = find_executables_in(file_system, &prefix);
+
+        Some(
+            PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Tox))
+                .name(name)
+                .executable(Some(env.executable.clone()))
+                .arch(get_architecture(&env.executable))
+                .version(version)
+                .prefix(Some(prefix))
+                .project(Some(project))
+                .symlinks(Some(symlinks))
+                .build(),
+        )
+    }
+
+    fn find(&self, _reporter: &dyn Reporter) {
+        // The environments are in the workspace folders, see `find_python_environments_in_workspace_folder_recursive`.
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use pet_fs::file_system::MemoryFileSystem;
+    use std::sync::Arc;
+
+    const PYVENV_CFG: &str = "home = /usr/bin\nversion = 3.11.4\n";
+
+    fn identify(file_system: MemoryFileSystem, executable: &str) -> Option<PythonEnvironment> {
+        let env = PythonEnv::new_in(Arc::new(file_system), executable.into(), None, None);
+        Tox::new().try_from(&env)
+    }
+
+    #[test]
+    fn tox_env_is_identified_by_tox_info() {
+        let file_system = MemoryFileSystem::new()
+            .file("/project/.tox/py311/pyvenv.cfg", PYVENV_CFG)
+            .file(
+                "/project/.tox/py311/.tox-info.json",
+                r#"{"ToxEnv": {"name": "py311", "type": "VirtualEnvRunner"}, "Python": {"version_info": [3, 11, 4, "final", 0]}}"#,
+            )
+            .file("/project/.tox/py311/bin/python", "");
+
+        let env = identify(file_system, "/project/.tox/py311/bin/python").unwrap();
+
+        assert_eq!(env.kind, Some(PythonEnvironmentKind::Tox));
+        assert_eq!(env.name, Some("py311".to_string()));
+        assert_eq!(env.project, Some(PathBuf::from("/project")));
+        assert_eq!(env.prefix, Some(PathBuf::from("/project/.tox/py311")));
+        assert_eq!(env.version, Some("3.11.4".parse().unwrap()));
+    }
+
+    #[test]
+    fn tox_env_is_identified_by_the_tox_configuration_of_the_project() {
+        for (file, contents) in [
+            ("/project/tox.ini", "[tox]\nenv_list = lint\n"),
+            (
+                "/project/pyproject.toml",
+                "[tool.tox]\nenv_list = [\"lint\"]\n",
+            ),
+            (
+                "/project/setup.cfg",
+                "[metadata]\nname = app\n\n[tox:tox]\n",
+            ),
+        ] {
+            let file_system = MemoryFileSystem::new()
+                .file(file, contents)
+                .file("/project/.tox/lint/pyvenv.cfg", PYVENV_CFG)
+                .file("/project/.tox/lint/bin/python", "");
+
+            let env = identify(file_system, "/project/.tox/lint/bin/python")
+                .unwrap_or_else(|| panic!("tox env not identified with {file}"));
+
+            assert_eq!(env.kind, Some(PythonEnvironmentKind::Tox));
+            assert_eq!(env.name, Some("lint".to_string()));
+            assert_eq!(env.project, Some(PathBuf::from("/project")));
+        }
+    }
+
+    #[test]
+    fn venvs_outside_of_tox_projects_are_not_tox_envs() {
+        // A `.tox` directory without tox configuration or `.tox-info.json`.
+        let file_system = MemoryFileSystem::new()
+            .file("/project/pyproject.toml", "[project]\nname = \"app\"\n")
+            .file("/project/.tox/py311/pyvenv.cfg", PYVENV_CFG)
+            .file("/project/.tox/py311/bin/python", "")
+            .file("/project/.venv/pyvenv.cfg", PYVENV_CFG)
+            .file("/project/.venv/bin/python", "");
+
+        assert!(identify(file_system.clone(), "/project/.tox/py311/bin/python").is_none());
+        assert!(identify(file_system, "/project/.venv/bin/python").is_none());
+    }
+}
@@ -1,5 +1,6 @@
+pub mod script;
+
 use std::{
-    fs,
     path::{Path, PathBuf},
     sync::{Arc, Mutex},
 };
@@ -10,12 +11,15 @@ use pet_core::{
     env::PythonEnv,
     os_environment::Environment,
     python_environment::{PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind},
+    python_version::PythonVersion,
     pyvenv_cfg::PyVenvCfg,
     reporter::Reporter,
+    search_root::{read_search_root, SearchRootBudget, Unlimited},
     Configuration, Locator, LocatorKind, RefreshStatePersistence,
 };
-use pet_fs::path::norm_case;
-use pet_python_utils::executable::{find_executable, find_executables};
+use pet_fs::{file_system::FileSystem, path::norm_case};
+use pet_python_utils::arch::get_architecture;
+use pet_python_utils::executable::{find_executable_in, find_executables_in};
 use serde::Deserialize;
 
 pub struct Uv {
@@ -23,6 +27,7 @@ pub struct Uv {
     /// Directory where uv stores managed Python installations,
     /// e.g. `~/.local/share/uv/python` on Unix or `%APPDATA%\uv\python` on Windows.
     uv_install_dir: Option<PathBuf>,
+    file_system: Arc<dyn FileSystem>,
 }
 
 /// Represents information stored in a `pyvenv.cfg` generated by uv
@@ -33,8 +38,8 @@ struct UvVenv {
 }
 
 impl UvVenv {
-    fn maybe_from_file(file: &Path) -> Option<Self> {
-        let contents = fs::read_to_string(file).ok()?;
+    fn maybe_from_file(file_system: &dyn FileSystem, file: &Path) -> Option<Self> {
+        let contents = file_system.read_to_string(file).ok()?;
         let mut uv_version = None;
         let mut python_version = None;
         let mut prompt = None;
@@ -77,9 +82,11 @@ impl Uv {
     }
 
     pub fn from(environment: &dyn Environment) -> Self {
+        let file_system = environment.file_system();
         Self {
             workspace_directories: Arc::new(Mutex::new(Vec::new())),
-            uv_install_dir: get_uv_python_install_dir(environment),
+            uv_install_dir: get_uv_python_install_dir(environment, file_system.as_ref()),
+            file_system,
         }
     }
 }
@@ -97,6 +104,7 @@ impl Locator for Uv {
         vec![
             PythonEnvironmentKind::Uv,
             PythonEnvironmentKind::UvWorkspace,
+            PythonEnvironmentKind::UvScript,
         ]
     }
 
@@ -118,16 +126,17 @@ impl Locator for Uv {
         }
 
         // Check if this is a uv-managed virtual environment (pyvenv.cfg with uv marker)
+        let file_system = env.file_system.as_ref();
         let cfg = env
             .executable
             .parent()
-            .and_then(PyVenvCfg::find)
+            .and_then(|bin| PyVenvCfg::find_in(file_system, bin))
             .or_else(|| {
                 env.prefix
                     .as_ref()
-                    .and_then(|prefix| PyVenvCfg::find(prefix))
+                    .and_then(|prefix| PyVenvCfg::find_in(file_system, prefix))
             })?;
-        let uv_venv = UvVenv::maybe_from_file(&cfg.file_path)?;
+        let uv_venv = UvVenv::maybe_from_file(file_system, &cfg.file_path)?;
         trace!(
             "uv-managed venv found in {}, made by uv {}",
             env.executable.display(),
@@ -141,7 +150,7 @@ impl Locator for Uv {
         let pyproject = prefix
             .as_ref()
             .and_then(|prefix| prefix.parent())
-            .and_then(parse_pyproject_toml_in);
+            .and_then(|project| parse_pyproject_toml_in(file_system, project));
         let kind = if pyproject
             .and_then(|pyproject| pyproject.tool)
             .and_then(|t| t.uv)
@@ -157,17 +166,26 @@ impl Locator for Uv {
             PythonEnvironmentBuilder::new(Some(kind))
                 .name(uv_venv.prompt)
                 .executable(Some(env.executable.clone()))
-                .version(Some(uv_venv.python_version))
-                .symlinks(prefix.as_ref().map(find_executables))
+                .arch(get_architecture(&env.executable))
+                .version(PythonVersion::parse_lossless(&uv_venv.python_version))
+                .symlinks(
+                    prefix
+                        .as_ref()
+                        .map(|prefix| find_executables_in(file_system, prefix)),
+                )
                 .prefix(prefix)
                 .build(),
         )
     }
 
     fn find(&self, reporter: &dyn Reporter) {
+        self.find_with_budget(reporter, &Unlimited);
+    }
+    fn find_with_budget(&self, reporter: &dyn Reporter, budget: &dyn SearchRootBudget) {
         // Discover globally-installed Python versions from `uv python install`
         if let Some(ref install_dir) = self.uv_install_dir {
-            for env in find_managed_python_installs(install_dir) {
+            for env in find_managed_python_installs(self.file_system.as_ref(), install_dir, budget)
+            {
                 reporter.report_environment(&env);
             }
         }
@@ -179,7 +197,7 @@ impl Locator for Uv {
             .expect("workspace_directories mutex poisoned")
             .clone();
         for workspace in &workspaces {
-            for env in list_envs_in_directory(workspace) {
+            for env in list_envs_in_directory(self.file_system.as_ref(), workspace) {
                 reporter.report_environment(&env);
             }
         }
@@ -191,6 +209,7 @@ impl Uv {
     fn try_from_managed_install(&self, env: &PythonEnv) -> Option<PythonEnvironment> {
         let install_dir = self.uv_install_dir.as_ref()?;
         let executable = &env.executable;
+        let file_system = env.file_system.as_ref();
 
         // Check if the executable lives under the uv install directory.
         // Both paths are normalized (install_dir via norm_case at construction,
@@ -209,7 +228,7 @@ impl Uv {
 
         // Skip minor-version junction/symlink directories (e.g., cpython-3.12-*)
         // to avoid duplicating the actual patch-version directories they point to.
-        if is_symlink_or_junction(&prefix) {
+        if is_symlink_or_junction(file_system, &prefix) {
             return None;
         }
 
@@ -222,34 +241,41 @@ impl Uv {
         Some(
             PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Uv))
                 .executable(Some(executable.clone()))
+                .arch(get_architecture(executable))
                 .version(Some(version))
                 .prefix(Some(prefix.clone()))
-                .symlinks(Some(find_executables(&prefix)))
+                .symlinks(Some(find_executables_in(file_system, &prefix)))
                 .build(),
         )
     }
 }
 
 /// Determine the directory where uv stores managed Python installations.
-fn get_uv_python_install_dir(environment: &dyn Environment) -> Option<PathBuf> {
+fn get_uv_python_install_dir(
+    environment: &dyn Environment,
+    file_system: &dyn FileSystem,
+) -> Option<PathBuf> {
     // 1. Check UV_PYTHON_INSTALL_DIR env var
     if let Some(dir) = environment.get_env_var("UV_PYTHON_INSTALL_DIR".to_string()) {
         let path = PathBuf::from(dir);
-        if path.is_dir() {
+        if file_system.is_dir(&path) {
             return Some(norm_case(path));
         }
     }
 
     // 2. Platform-specific defaults
-    get_default_uv_python_install_dir(environment)
+    get_default_uv_python_install_dir(environment, file_system)
 }
 
 #[cfg(windows)]
-fn get_default_uv_python_install_dir(environment: &dyn Environment) -> Option<PathBuf> {
+fn get_default_uv_python_install_dir(
+    environment: &dyn Environment,
+    file_system: &dyn FileSystem,
+) -> Option<PathBuf> {
     // Windows: %APPDATA%\uv\python
     let appdata = environment.get_env_var("APPDATA".to_string())?;
     let path = PathBuf::from(appdata).join("uv").join("python");
-    if path.is_dir() {
+    if file_system.is_dir(&path) {
         Some(norm_case(path))
     } else {
         None
@@ -257,17 +283,20 @@ fn get_default_uv_python_install_dir(environment: &dyn Environment) -> Option<Pa
 }
 
 #[cfg(unix)]
-fn get_default_uv_python_install_dir(environment: &dyn Environment) -> Option<PathBuf> {
+fn get_default_uv_python_install_dir(
+    environment: &dyn Environment,
+    file_system: &dyn FileSystem,
+) -> Option<PathBuf> {
     // Unix: $XDG_DATA_HOME/uv/python or ~/.local/share/uv/python
     if let Some(xdg) = environment.get_env_var("XDG_DATA_HOME".to_string()) {
         let path = PathBuf::from(xdg).join("uv").join("python");
-        if path.is_dir() {
+        if file_system.is_dir(&path) {
             return Some(norm_case(path));
         }
     }
     let home = environment.get_user_home()?;
     let path = home.join(".local").join("share").join("uv").join("python");
-    if path.is_dir() {
+    if file_system.is_dir(&path) {
         Some(norm_case(path))
     } else {
         None
@@ -277,22 +306,16 @@ fn get_default_uv_python_install_dir(environment: &dyn Environment) -> Option<Pa
 /// Scan the uv Python install directory for managed Python installations.
 /// Skips symlinks and junctions (minor-version aliases like `cpython-3.12-*`)
 /// to avoid duplicating the actual patch-version directories they point to.
-fn find_managed_python_installs(install_dir: &Path) -> Vec<PythonEnvironment> {
+fn find_managed_python_installs(
+    file_system: &dyn FileSystem,
+    install_dir: &Path,
+    budget: &dyn SearchRootBudget,
+) -> Vec<PythonEnvironment> {
     let mut envs = Vec::new();
-    let entries = match fs::read_dir(install_dir) {
-        Ok(entries) => entries,
-        Err(_) => return envs,
-    };
-
-    for entry in entries.filter_map(Result::ok) {
-        let path = entry.path();
-        if !path.is_dir() {
-            continue;
-        }
-
+    for path in read_search_root(file_system, budget, install_dir) {
         // Skip symlinks (Unix) and junctions (Windows) — these are minor-version
         // aliases (e.g., cpython-3.12-*) pointing to full patch-version directories.
-        if is_symlink_or_junction(&path) {
+        if is_symlink_or_junction(file_system, &path) {
             trace!("Skipping symlink/junction directory {}", path.display());
             continue;
         }
@@ -307,13 +330,14 @@ fn find_managed_python_installs(install_dir: &Path) -> Vec<PythonEnvironment> {
             None => continue,
         };
 
-        if let Some(executable) = find_executable(&path) {
+        if let Some(executable) = find_executable_in(file_system, &path) {
             trace!("uv-managed Python {} found in {}", version, path.display());
             let env = PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Uv))
+                .arch(get_architecture(&executable))
                 .executable(Some(executable))
                 .version(Some(version))
                 .prefix(Some(path.clone()))
-                .symlinks(Some(find_executables(&path)))
+                .symlinks(Some(find_executables_in(file_system, &path)))
                 .build();
             envs.push(env);
         }
@@ -323,26 +347,28 @@ fn find_managed_python_installs(install_dir: &Path) -> Vec<PythonEnvironment> {
 }
 
 /// Check if a path is a symlink (Unix) or junction/symlink (Windows).
+/// Junctions are only known to the disk, hence they are not looked for in other file systems.
 #[cfg(windows)]
-fn is_symlink_or_junction(path: &Path) -> bool {
+fn is_symlink_or_junction(file_system: &dyn FileSystem, path: &Path) -> bool {
+    if file_system.is_symlink(path) {
+        return true;
+    }
     use std::os::windows::fs::MetadataExt;
     const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
-    fs::symlink_metadata(path)
+    std::fs::symlink_metadata(path)
         .map(|m| m.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0)
         .unwrap_or(false)
 }
 
 #[cfg(unix)]
-fn is_symlink_or_junction(path: &Path) -> bool {
-    fs::symlink_metadata(path)
-        .map(|m| m.file_type().is_symlink())
-        .unwrap_or(false)
+fn is_symlink_or_junction(file_system: &dyn FileSystem, path: &Path) -> bool {
+    file_system.is_symlink(path)
 }
 
 /// Parse version from a uv Python install directory name.
 /// Directory names follow the pattern: `cpython-3.14.3-linux-x86_64-gnu`
-/// Returns the version string (e.g., "3.14.3").
-fn parse_version_from_uv_dir_name(dir_name: &str) -> Option<String> {
+/// Returns the version (e.g., "3.14.3").
+fn parse_version_from_uv_dir_name(dir_name: &str) -> Option<PythonVersion> {
     // Format: <implementation>-<version>-<os>-<arch>-<libc>
     // e.g., cpython-3.14.3-linux-x86_64-gnu, pypy-3.10.14-linux-x86_64-gnu
     let mut parts = dir_name.splitn(3, '-');
@@ -353,48 +379,25 @@ fn parse_version_from_uv_dir_name(dir_name: &str) -> Option<String> {
     if platform.is_empty() {
         return None;
     }
+    // Pre-release versions are allowed (e.g., "3.14.0a4", "3.13.0rc1").
+    let version = PythonVersion::parse(version)?;
     // Verify at minimum X.Y format (e.g., "3.12" or "3.12.3").
-    // Major and minor must always be purely numeric.
-    // Only the patch component (3rd+) may have a pre-release suffix (e.g., "0a4", "0rc1").
-    let components: Vec<&str> = version.split('.').collect();
-    if components.len() < 2 {
-        return None;
-    }
-
-    let is_numeric = |c: &str| !c.is_empty() && c.chars().all(|ch| ch.is_ascii_digit());
-
-    // Major and minor must be purely numeric.
-    if !is_numeric(components[0]) || !is_numeric(components[1]) {
-        return None;
-    }
-
-    // For X.Y versions (no patch), we're done.
-    if components.len() == 2 {
-        return Some(version.to_string());
-    }
-
-    // Any components between minor and the last must be purely numeric.
-    let middle = &components[2..components.len() - 1];
-    if !middle.iter().all(|c| is_numeric(c)) {
-        return None;
-    }
-
-    // The last component (patch or beyond) must start with a digit
-    // (allows pre-release suffix like "0a4").
-    let last = components.last()?;
-    if last.is_empty() || !last.starts_with(|ch: char| ch.is_ascii_digit()) {
+    if version.release().len() < 2 {
         return None;
     }
-    Some(version.to_string())
+    Some(version)
 }
 
 /// Walks up from `project_path` looking for a workspace that this project belongs to.
 /// Starts from `project_path` itself because a project can also define `[tool.uv.workspace]`
 /// alongside `[project]` (i.e. the workspace root is itself a package).
 /// Returns the workspace environment if found and the project is a valid member.
-fn find_workspace_for_project(project_path: &Path) -> Option<PythonEnvironment> {
+fn find_workspace_for_project(
+    file_system: &dyn FileSystem,
+    project_path: &Path,
+) -> Option<PythonEnvironment> {
     for candidate in project_path.ancestors() {
-        let pyproject = parse_pyproject_toml_in(candidate);
+        let pyproject = parse_pyproject_toml_in(file_system, candidate);
         let workspace = pyproject
             .as_ref()
             .and_then(|pp| pp.tool.as_ref())
@@ -418,17 +421,20 @@ fn find_workspace_for_project(project_path: &Path) -> Option<PythonEnvironment>
             candidate,
             project_path
         );
-        return build_workspace_env(candidate);
+        return build_workspace_env(file_system, candidate);
     }
     None
 }
 
 /// Builds a `PythonEnvironment` for a uv workspace root if it has a `.venv` with a valid
 /// uv-managed pyvenv.cfg.
-fn build_workspace_env(workspace_root: &Path) -> Option<PythonEnvironment> {
+fn build_workspace_env(
+    file_system: &dyn FileSystem,
+    workspace_root: &Path,
+) -> Option<PythonEnvironment> {
     let prefix = workspace_root.join(".venv");
     let pyvenv_cfg = prefix.join("pyvenv.cfg");
-    if !pyvenv_cfg.exists() {
+    if !file_system.exists(&pyvenv_cfg) {
         trace!(
             "Workspace at {} does not have a virtual environment",
             workspace_root.display()
@@ -437,20 +443,21 @@ fn build_workspace_env(workspace_root: &Path) -> Option<PythonEnvironment> {
     }
     let unix_executable = prefix.join("bin/python");
     let windows_executable = prefix.join("Scripts/python.exe");
-    let executable = if unix_executable.exists() {
+    let executable = if file_system.exists(&unix_executable) {
         Some(unix_executable)
-    } else if windows_executable.exists() {
+    } else if file_system.exists(&windows_executable) {
         Some(windows_executable)
     } else {
         None
     };
-    if let Some(uv_venv) = UvVenv::maybe_from_file(&pyvenv_cfg) {
+    if let Some(uv_venv) = UvVenv::maybe_from_file(file_system, &pyvenv_cfg) {
         Some(
             PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::UvWorkspace))
                 .name(uv_venv.prompt)
+                .arch(executable.as_deref().and_then(get_architecture))
                 .executable(executable)
-                .version(Some(uv_venv.python_version))
-                .symlinks(Some(find_executables(&prefix)))
+                .version(PythonVersion::parse_lossless(&uv_venv.python_version))
+                .symlinks(Some(find_executables_in(file_system, &prefix)))
                 .prefix(Some(prefix))
                 .build(),
         )
@@ -463,9 +470,9 @@ fn build_workspace_env(workspace_root: &Path) -> Option<PythonEnvironment> {
     }
 }
 
-fn list_envs_in_directory(path: &Path) -> Vec<PythonEnvironment> {
+fn list_envs_in_directory(file_system: &dyn FileSystem, path: &Path) -> Vec<PythonEnvironment> {
     let mut envs = Vec::new();
-    let pyproject = parse_pyproject_toml_in(path);
+    let pyproject = parse_pyproject_toml_in(file_system, path);
     let Some(pyproject) = pyproject else {
         return envs;
     };
@@ -473,9 +480,9 @@ fn list_envs_in_directory(path: &Path) -> Vec<PythonEnvironment> {
     let prefix = path.join(".venv");
     let unix_executable = prefix.join("bin/python");
     let windows_executable = prefix.join("Scripts/python.exe");
-    let executable = if unix_executable.exists() {
+    let executable = if file_system.exists(&unix_executable) {
         Some(unix_executable)
-    } else if windows_executable.exists() {
+    } else if file_system.exists(&windows_executable) {
         Some(windows_executable)
     } else {
         None
@@ -487,14 +494,15 @@ fn list_envs_in_directory(path: &Path) -> Vec<PythonEnvironment> {
         .is_some()
     {
         trace!("Workspace found in {}", path.display());
-        if let Some(uv_venv) = UvVenv::maybe_from_file(&pyvenv_cfg) {
+        if let Some(uv_venv) = UvVenv::maybe_from_file(file_system, &pyvenv_cfg) {
             trace!("uv-managed venv found for workspace in {}", path.display());
             let env = PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::UvWorkspace))
                 .name(uv_venv.prompt)
-                .symlinks(Some(find_executables(&prefix)))
+                .symlinks(Some(find_executables_in(file_system, &prefix)))
                 .prefix(Some(prefix))
+                .arch(executable.as_deref().and_then(get_architecture))
                 .executable(executable)
-                .version(Some(uv_venv.python_version))
+                .version(PythonVersion::parse_lossless(&uv_venv.python_version))
                 .build();
             envs.push(env);
         } else {
@@ -505,21 +513,22 @@ fn list_envs_in_directory(path: &Path) -> Vec<PythonEnvironment> {
         }
     // prioritize the workspace over the project if it's the same venv
     } else if let Some(project) = pyproject.project {
-        if let Some(uv_venv) = UvVenv::maybe_from_file(&pyvenv_cfg) {
+        if let Some(uv_venv) = UvVenv::maybe_from_file(file_system, &pyvenv_cfg) {
             trace!("uv-managed venv found for project in {}", path.display());
             let env = PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Uv))
                 .name(uv_venv.prompt)
-                .symlinks(Some(find_executables(&prefix)))
+                .symlinks(Some(find_executables_in(file_system, &prefix)))
                 .prefix(Some(prefix))
-                .version(Some(uv_venv.python_version))
+                .version(PythonVersion::parse_lossless(&uv_venv.python_version))
                 .display_name(project.name)
+                .arch(executable.as_deref().and_then(get_architecture))
                 .executable(executable)
                 .build();
             envs.push(env);
         } else {
             trace!("No uv-managed venv found in {}", path.display());
         }
-        if let Some(workspace) = find_workspace_for_project(path) {
+        if let Some(workspace) = find_workspace_for_project(file_system, path) {
             envs.push(workspace);
         }
     }
@@ -527,8 +536,10 @@ fn list_envs_in_directory(path: &Path) -> Vec<PythonEnvironment> {
     envs
 }
 
-fn parse_pyproject_toml_in(path: &Path) -> Option<PyProjectToml> {
-    let contents = fs::read_to_string(path.join("pyproject.toml")).ok()?;
+fn parse_pyproject_toml_in(file_system: &dyn FileSystem, path: &Path) -> Option<PyProjectToml> {
+    let contents = file_system
+        .read_to_string(&path.join("pyproject.toml"))
+        .ok()?;
     toml::from_str(&contents).ok()
 }
 
@@ -638,6 +649,7 @@ struct UvWorkspace {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pet_fs::file_system::{os_file_system, OsFileSystem};
     use tempfile::TempDir;
 
     #[test]
@@ -655,7 +667,7 @@ prompt = test-env"#;
 
         std::fs::write(&cfg_path, contents).unwrap();
 
-        let uv_venv = UvVenv::maybe_from_file(&cfg_path);
+        let uv_venv = UvVenv::maybe_from_file(&OsFileSystem, &cfg_path);
         assert!(uv_venv.is_some());
 
         let uv_venv = uv_venv.unwrap();
@@ -675,7 +687,7 @@ prompt = test-env"#;
 
         std::fs::write(&cfg_path, contents).unwrap();
 
-        let uv_venv = UvVenv::maybe_from_file(&cfg_path);
+        let uv_venv = UvVenv::maybe_from_file(&OsFileSystem, &cfg_path);
         assert!(
             uv_venv.is_none(),
             "Should return None when 'uv' field is missing"
@@ -693,7 +705,7 @@ prompt = test-env"#;
 
         std::fs::write(&cfg_path, contents).unwrap();
 
-        let uv_venv = UvVenv::maybe_from_file(&cfg_path);
+        let uv_venv = UvVenv::maybe_from_file(&OsFileSystem, &cfg_path);
         assert!(
             uv_venv.is_none(),
             "Should return None when 'version_info' field is missing"
@@ -711,7 +723,7 @@ version_info = 3.11.0"#;
 
         std::fs::write(&cfg_path, contents).unwrap();
 
-        let uv_venv = UvVenv::maybe_from_file(&cfg_path);
+        let uv_venv = UvVenv::maybe_from_file(&OsFileSystem, &cfg_path);
         assert!(
             uv_venv.is_some(),
             "Should identify a uv venv even when the optional 'prompt' field is missing"
@@ -738,7 +750,8 @@ include-system-site-packages = false"#;
 
         std::fs::write(&cfg_path, contents).unwrap();
 
-        let uv_venv = UvVenv::maybe_from_file(&cfg_path).expect("uv venv should be identified");
+        let uv_venv = UvVenv::maybe_from_file(&OsFileSystem, &cfg_path)
+            .expect("uv venv should be identified");
         assert_eq!(uv_venv.uv_version, "0.5.4");
         assert_eq!(uv_venv.python_version, "3.13.0");
         assert_eq!(uv_venv.prompt, None);
@@ -755,7 +768,7 @@ include-system-site-packages = false"#;
 
         std::fs::write(&cfg_path, contents).unwrap();
 
-        let uv_venv = UvVenv::maybe_from_file(&cfg_path);
+        let uv_venv = UvVenv::maybe_from_file(&OsFileSystem, &cfg_path);
         assert!(uv_venv.is_some());
 
         let uv_venv = uv_venv.unwrap();
@@ -766,7 +779,8 @@ include-system-site-packages = false"#;
 
     #[test]
     fn test_uv_venv_parse_nonexistent_file() {
-        let uv_venv = UvVenv::maybe_from_file(Path::new("/nonexistent/path/pyvenv.cfg"));
+        let uv_venv =
+            UvVenv::maybe_from_file(&OsFileSystem, Path::new("/nonexistent/path/pyvenv.cfg"));
         assert!(uv_venv.is_none());
     }
 
@@ -783,7 +797,7 @@ members = ["packages/*"]"#;
 
         std::fs::write(&pyproject_path, contents).unwrap();
 
-        let pyproject = parse_pyproject_toml_in(temp_dir.path());
+        let pyproject = parse_pyproject_toml_in(&OsFileSystem, temp_dir.path());
         assert!(pyproject.is_some());
 
         let pyproject = pyproject.unwrap();
@@ -809,7 +823,7 @@ dev-dependencies = ["pytest"]"#;
 
         std::fs::write(&pyproject_path, contents).unwrap();
 
-        let pyproject = parse_pyproject_toml_in(temp_dir.path());
+        let pyproject = parse_pyproject_toml_in(&OsFileSystem, temp_dir.path());
         assert!(pyproject.is_some());
 
         let pyproject = pyproject.unwrap();
@@ -823,7 +837,7 @@ dev-dependencies = ["pytest"]"#;
     #[test]
     fn test_parse_pyproject_toml_missing_file() {
         let temp_dir = TempDir::new().unwrap();
-        let pyproject = parse_pyproject_toml_in(temp_dir.path());
+        let pyproject = parse_pyproject_toml_in(&OsFileSystem, temp_dir.path());
         assert!(pyproject.is_none());
     }
 
@@ -837,7 +851,7 @@ name = "invalid"#;
 
         std::fs::write(&pyproject_path, contents).unwrap();
 
-        let pyproject = parse_pyproject_toml_in(temp_dir.path());
+        let pyproject = parse_pyproject_toml_in(&OsFileSystem, temp_dir.path());
         assert!(pyproject.is_none());
     }
 
@@ -869,7 +883,7 @@ prompt = workspace-env"#;
         let python_path = bin_path.join("python");
         std::fs::File::create(&python_path).unwrap();
 
-        let envs = list_envs_in_directory(project_path);
+        let envs = list_envs_in_directory(&OsFileSystem, project_path);
         assert_eq!(envs.len(), 1);
         assert_eq!(envs[0].kind, Some(PythonEnvironmentKind::UvWorkspace));
         assert_eq!(envs[0].name, Some("workspace-env".to_string()));
@@ -906,7 +920,7 @@ prompt = my-project"#;
         let python_path = bin_path.join("python");
         std::fs::File::create(&python_path).unwrap();
 
-        let envs = list_envs_in_directory(project_path);
+        let envs = list_envs_in_directory(&OsFileSystem, project_path);
         assert_eq!(envs.len(), 1);
         assert_eq!(envs[0].kind, Some(PythonEnvironmentKind::Uv));
         assert_eq!(envs[0].display_name, Some("my-project".to_string()));
@@ -915,7 +929,7 @@ prompt = my-project"#;
     #[test]
     fn test_list_envs_in_directory_no_pyproject() {
         let temp_dir = TempDir::new().unwrap();
-        let envs = list_envs_in_directory(temp_dir.path());
+        let envs = list_envs_in_directory(&OsFileSystem, temp_dir.path());
         assert_eq!(envs.len(), 0);
     }
 
@@ -930,7 +944,7 @@ prompt = my-project"#;
 name = "my-project""#;
         std::fs::write(&pyproject_path, pyproject_contents).unwrap();
 
-        let envs = list_envs_in_directory(project_path);
+        let envs = list_envs_in_directory(&OsFileSystem, project_path);
         assert_eq!(envs.len(), 0);
     }
 
@@ -1036,7 +1050,7 @@ prompt = my-workspace"#;
         std::fs::create_dir_all(&member_path).unwrap();
 
         // find_workspace_for_project should find the workspace
-        let env = find_workspace_for_project(&member_path);
+        let env = find_workspace_for_project(&OsFileSystem, &member_path);
         assert!(env.is_some());
         let env = env.unwrap();
         assert_eq!(env.kind, Some(PythonEnvironmentKind::UvWorkspace));
@@ -1066,7 +1080,7 @@ prompt = mono-repo"#;
         std::fs::write(venv_path.join("pyvenv.cfg"), pyvenv_contents).unwrap();
 
         // find_workspace_for_project should find the workspace at project_path itself
-        let env = find_workspace_for_project(workspace_root);
+        let env = find_workspace_for_project(&OsFileSystem, workspace_root);
         assert!(
             env.is_some(),
             "Should discover workspace at project_path itself"
@@ -1103,7 +1117,7 @@ prompt = my-workspace"#;
         std::fs::create_dir_all(&excluded_path).unwrap();
 
         // Should NOT find a workspace for the excluded project
-        let env = find_workspace_for_project(&excluded_path);
+        let env = find_workspace_for_project(&OsFileSystem, &excluded_path);
         assert!(env.is_none());
     }
 
@@ -1121,7 +1135,7 @@ exclude = ["packages/legacy"]"#;
 
         std::fs::write(&pyproject_path, contents).unwrap();
 
-        let pyproject = parse_pyproject_toml_in(temp_dir.path());
+        let pyproject = parse_pyproject_toml_in(&OsFileSystem, temp_dir.path());
         assert!(pyproject.is_some());
         let pyproject = pyproject.unwrap();
         let workspace = pyproject.tool.unwrap().uv.unwrap().workspace.unwrap();
@@ -1133,7 +1147,7 @@ exclude = ["packages/legacy"]"#;
     fn test_parse_version_from_uv_dir_name_cpython() {
         assert_eq!(
             parse_version_from_uv_dir_name("cpython-3.14.3-linux-x86_64-gnu"),
-            Some("3.14.3".to_string())
+            Some("3.14.3".parse().unwrap())
         );
     }
 
@@ -1141,7 +1155,7 @@ exclude = ["packages/legacy"]"#;
     fn test_parse_version_from_uv_dir_name_minor_only() {
         assert_eq!(
             parse_version_from_uv_dir_name("cpython-3.12-macos-aarch64-none"),
-            Some("3.12".to_string())
+            Some("3.12".parse().unwrap())
         );
     }
 
@@ -1149,7 +1163,7 @@ exclude = ["packages/legacy"]"#;
     fn test_parse_version_from_uv_dir_name_pypy() {
         assert_eq!(
             parse_version_from_uv_dir_name("pypy-3.10.14-linux-x86_64-gnu"),
-            Some("3.10.14".to_string())
+            Some("3.10.14".parse().unwrap())
         );
     }
 
@@ -1201,11 +1215,11 @@ exclude = ["packages/legacy"]"#;
         // Pre-release versions like "3.14.0a4" are valid uv install dirs.
         assert_eq!(
             parse_version_from_uv_dir_name("cpython-3.14.0a4-linux-x86_64-gnu"),
-            Some("3.14.0a4".to_string())
+            Some("3.14.0a4".parse().unwrap())
         );
         assert_eq!(
             parse_version_from_uv_dir_name("cpython-3.13.0rc1-linux-x86_64-gnu"),
-            Some("3.13.0rc1".to_string())
+            Some("3.13.0rc1".parse().unwrap())
         );
     }
 
@@ -1228,10 +1242,10 @@ exclude = ["packages/legacy"]"#;
             std::fs::File::create(bin_dir.join("python3.12")).unwrap();
         }
 
-        let envs = find_managed_python_installs(install_dir);
+        let envs = find_managed_python_installs(&OsFileSystem, install_dir, &Unlimited);
         assert_eq!(envs.len(), 1);
         assert_eq!(envs[0].kind, Some(PythonEnvironmentKind::Uv));
-        assert_eq!(envs[0].version, Some("3.12.3".to_string()));
+        assert_eq!(envs[0].version, Some("3.12.3".parse().unwrap()));
     }
 
     #[test]
@@ -1255,20 +1269,21 @@ exclude = ["packages/legacy"]"#;
             }
         }
 
-        let envs = find_managed_python_installs(install_dir);
+        let envs = find_managed_python_installs(&OsFileSystem, install_dir, &Unlimited);
         assert_eq!(envs.len(), 2);
     }
 
     #[test]
     fn test_find_managed_python_installs_empty_dir() {
         let temp_dir = TempDir::new().unwrap();
-        let envs = find_managed_python_installs(temp_dir.path());
+        let envs = find_managed_python_installs(&OsFileSystem, temp_dir.path(), &Unlimited);
         assert_eq!(envs.len(), 0);
     }
 
     #[test]
     fn test_find_managed_python_installs_nonexistent_dir() {
-        let envs = find_managed_python_installs(Path::new("/nonexistent/path"));
+        let envs =
+            find_managed_python_installs(&OsFileSystem, Path::new("/nonexistent/path"), &Unlimited);
         assert_eq!(envs.len(), 0);
     }
 
@@ -1281,7 +1296,7 @@ exclude = ["packages/legacy"]"#;
         let non_version_dir = install_dir.join("some-random-dir");
         std::fs::create_dir_all(&non_version_dir).unwrap();
 
-        let envs = find_managed_python_installs(install_dir);
+        let envs = find_managed_python_installs(&OsFileSystem, install_dir, &Unlimited);
         assert_eq!(envs.len(), 0);
     }
 
@@ -1312,6 +1327,7 @@ exclude = ["packages/legacy"]"#;
         let uv = Uv {
             workspace_directories: Arc::new(Mutex::new(Vec::new())),
             uv_install_dir: Some(install_dir),
+            file_system: os_file_system(),
         };
 
         let python_env = PythonEnv::new(executable, Some(version_dir.clone()), None);
@@ -1322,7 +1338,7 @@ exclude = ["packages/legacy"]"#;
         );
         let env = result.unwrap();
         assert_eq!(env.kind, Some(PythonEnvironmentKind::Uv));
-        assert_eq!(env.version, Some("3.12.3".to_string()));
+        assert_eq!(env.version, Some("3.12.3".parse().unwrap()));
     }
 
     #[test]
@@ -1346,6 +1362,7 @@ exclude = ["packages/legacy"]"#;
         let uv = Uv {
             workspace_directories: Arc::new(Mutex::new(Vec::new())),
             uv_install_dir: Some(install_dir),
+            file_system: os_file_system(),
         };
 
         let python_env = PythonEnv::new(exe, Some(other_dir), None);
@@ -0,0 +1,593 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Environments uv creates to run scripts with inline metadata (PEP 723), e.g. `uv run script.py`.
+//!
+//! ```python
+//! # /// script
+//! # requires-python = ">=3.12"
+//! # dependencies = ["requests"]
+//! # ///
+//! ```
+//!
+//! These are virtual environments in the `environments-v2` directory of the uv cache, named after
+//! the script & a hash of its path (`<script name>-<hash>`), or just a hash of the requirements.
+//! As the hashes cannot be computed without uv, the environment of a script is the only one of the environments
+//! named after the script (or when there are none, of the environments named with a hash) with a Python that
+//! satisfies `requires-python` & all of the dependencies of the script installed.
+//! Scripts with the same name in other directories could have such an environment too, hence none is reported
+//! if several match.
+//!
+//! Note: The environment reported is a match by name, not a certainty, it could be that of a script with
+//! the same name & dependencies in another directory (of which there is only one in the uv cache).
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use log::trace;
+use pet_core::{
+    os_environment::Environment,
+    python_environment::{PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind},
+    python_version::{PythonVersion, VersionSpecifiers},
+};
+use pet_fs::{
+    file_system::{FileSystem, OsFileSystem},
+    path::norm_case,
+};
+use pet_python_utils::arch::get_architecture;
+use pet_python_utils::executable::{find_executable_in, find_executables_in};
+use serde::Deserialize;
+
+use crate::UvVenv;
+
+/// Directory of the uv cache with the environments created to run scripts (& tools).
+pub const ENVIRONMENTS_DIRECTORY: &str = "environments-v2";
+
+const SCRIPT_METADATA_START: &str = "# /// script";
+const METADATA_END: &str = "# ///";
+
+/// The inline metadata of a script (PEP 723) relevant to its environment.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScriptMetadata {
+    pub requires_python: Option<VersionSpecifiers>,
+    /// Normalized names of the dependencies, e.g. `typing-extensions`.
+    pub dependencies: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+struct ScriptMetadataToml {
+    requires_python: Option<String>,
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+/// Parses the `script` metadata block of a script, `None` if there is none (or it is invalid).
+pub fn parse_script_metadata(contents: &str) -> Option<ScriptMetadata> {
+    let mut lines = contents
+        .lines()
+        .skip_while(|line| line.trim_end() != SCRIPT_METADATA_START);
+    lines.next()?;
+    let mut toml = String::new();
+    for line in lines {
+        let line = line.trim_end();
+        if line == METADATA_END {
+            let metadata: ScriptMetadataToml = match toml::from_str(&toml) {
+                Ok(metadata) => metadata,
+                Err(err) => {
+                    trace!("Invalid script metadata: {}", err);
+                    return None;
+                }
+            };
+            return Some(ScriptMetadata {
+                requires_python: metadata
+                    .requires_python
+                    .as_deref()
+                    .and_then(VersionSpecifiers::parse),
+                dependencies: metadata
+                    .dependencies
+                    .iter()
+                    .filter_map(|requirement| get_requirement_name(requirement))
+                    .collect(),
+            });
+        }
+        // Every line of the block is a comment, `#` or `# <content>`.
+        if line == "#" {
+            toml.push('\n');
+        } else {
+            toml.push_str(line.strip_prefix("# ")?);
+            toml.push('\n');
+        }
+    }
+    None
+}
+
+/// Reads the inline metadata of a Python script, `None` if this is not a script with inline metadata.
+pub fn read_script_metadata(script: &Path) -> Option<ScriptMetadata> {
+    read_script_metadata_in(&OsFileSystem, script)
+}
+
+/// Same as `read_script_metadata`, reading the script from the given file system.
+pub fn read_script_metadata_in(
+    file_system: &dyn FileSystem,
+    script: &Path,
+) -> Option<ScriptMetadata> {
+    let extension = script.extension()?.to_str()?;
+    if !extension.eq_ignore_ascii_case("py") && !extension.eq_ignore_ascii_case("pyw") {
+        return None;
+    }
+    parse_script_metadata(&file_system.read_to_string(script).ok()?)
+}
+
+/// Name of the package of a requirement such as `requests[socks]>=2.31`, normalized as per PEP 503.
+fn get_requirement_name(requirement: &str) -> Option<String> {
+    let name: String = requirement
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .collect();
+    if name.is_empty() {
+        None
+    } else {
+        Some(normalize_name(&name))
+    }
+}
+
+/// Package names are case insensitive & runs of `-`, `_` & `.` are equivalent (PEP 503).
+fn normalize_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        } else {
+            normalized.push(c.to_ascii_lowercase());
+        }
+    }
+    normalized
+}
+
+/// Determine the uv cache directory.
+pub fn get_uv_cache_dir(environment: &dyn Environment) -> Option<PathBuf> {
+    let file_system = environment.file_system();
+    let file_system = file_system.as_ref();
+    // 1. Check UV_CACHE_DIR env var
+    if let Some(dir) = environment.get_env_var("UV_CACHE_DIR".to_string()) {
+        let path = PathBuf::from(dir);
+        if file_system.is_dir(&path) {
+            return Some(norm_case(path));
+        }
+    }
+
+    // 2. Platform-specific defaults
+    get_default_uv_cache_dir(environment, file_system)
+}
+
+#[cfg(windows)]
+fn get_default_uv_cache_dir(
+    environment: &dyn Environment,
+    file_system: &dyn FileSystem,
+) -> Option<PathBuf> {
+    // Windows: %LOCALAPPDATA%\uv\cache
+    let local_appdata = environment.get_env_var("LOCALAPPDATA".to_string())?;
+    let path = PathBuf::from(local_appdata).join("uv").join("cache");
+    if file_system.is_dir(&path) {
+        Some(norm_case(path))
+    } else {
+        None
+    }
+}
+
+#[cfg(unix)]
+fn get_default_uv_cache_dir(
+    environment: &dyn Environment,
+    file_system: &dyn FileSystem,
+) -> Option<PathBuf> {
+    // Unix: $XDG_CACHE_HOME/uv or ~/.cache/uv
+    if let Some(xdg) = environment.get_env_var("XDG_CACHE_HOME".to_string()) {
+        let path = PathBuf::from(xdg).join("uv");
+        if file_system.is_dir(&path) {
+            return Some(norm_case(path));
+        }
+    }
+    let home = environment.get_user_home()?;
+    let path = home.join(".cache").join("uv");
+    if file_system.is_dir(&path) {
+        Some(norm_case(path))
+    } else {
+        None
+    }
+}
+
+/// The environment uv runs the script in, `None` if this is not a script with inline metadata.
+/// The environment has no executable (& an error) if uv has not created an environment for the script yet,
+/// or if several environments match the script.
+/// The environment is matched by the name of the script, not its path (see the module documentation).
+pub fn find_script_environment(
+    environment: &dyn Environment,
+    script: &Path,
+) -> Option<PythonEnvironment> {
+    let file_system = environment.file_system();
+    let file_system = file_system.as_ref();
+    let metadata = read_script_metadata_in(file_system, script)?;
+    let builder = PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::UvScript))
+        .name(
+            script
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string()),
+        )
+        .script(Some(script.to_path_buf()));
+    let mut candidates = get_uv_cache_dir(environment)
+        .and_then(|cache_dir| {
+            find_cached_environments(
+                file_system,
+                &cache_dir.join(ENVIRONMENTS_DIRECTORY),
+                script,
+                &metadata,
+            )
+        })
+        .unwrap_or_default();
+    if candidates.len() > 1 {
+        trace!(
+            "uv environments {:?} could all be that of the script {:?}",
+            candidates
+                .iter()
+                .map(|(prefix, _)| prefix)
+                .collect::<Vec<_>>(),
+            script
+        );
+        return Some(
+            builder
+                .error(Some(format!(
+                    "{} uv environments match the name & dependencies of the script, these could be those of scripts with the same name in other directories",
+                    candidates.len()
+                )))
+                .build(),
+        );
+    }
+    let Some((prefix, uv_venv)) = candidates.pop() else {
+        trace!("No uv environment found for the script {:?}", script);
+        return Some(
+            builder
+                .error(Some(
+                    "No uv environment found for the script, it is created by `uv run`".to_string(),
+                ))
+                .build(),
+        );
+    };
+    trace!(
+        "uv environment {:?} found for the script {:?}, matched by its name & dependencies",
+        prefix,
+        script
+    );
+    let executable = find_executable_in(file_system, &prefix);
+    Some(
+        builder
+            .arch(executable.as_deref().and_then(get_architecture))
+            .executable(executable)
+            .version(PythonVersion::parse_lossless(&uv_venv.python_version))
+            .symlinks(Some(find_executables_in(file_system, &prefix)))
+            .prefix(Some(prefix))
+            .build(),
+    )
+}
+
+/// Finds the environments in the `environments-v2` directory of the uv cache that could be that of the script.
+fn find_cached_environments(
+    file_system: &dyn FileSystem,
+    environments_dir: &Path,
+    script: &Path,
+    metadata: &ScriptMetadata,
+) -> Option<Vec<(PathBuf, UvVenv)>> {
+    let script_name = normalize_name(script.file_stem()?.to_str()?);
+    let mut named = vec![];
+    let mut hashed = vec![];
+    for prefix in file_system.read_dir(environments_dir).ok()? {
+        let Some(dir_name) = prefix.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let candidates = match dir_name.rsplit_once('-') {
+            Some((name, hash)) if is_hash(hash) && normalize_name(name) == script_name => {
+                &mut named
+            }
+            None if is_hash(dir_name) => &mut hashed,
+            _ => continue,
+        };
+        let Some(uv_venv) = UvVenv::maybe_from_file(file_system, &prefix.join("pyvenv.cfg")) else {
+            continue;
+        };
+        if !is_satisfied_by(file_system, metadata, &prefix, &uv_venv) {
+            trace!(
+                "uv environment {:?} does not satisfy {:?}",
+                prefix,
+                metadata
+            );
+            continue;
+        }
+        candidates.push((prefix, uv_venv));
+    }
+    // An environment named with a hash can only be told apart by the dependencies.
+    if named.is_empty() && !metadata.dependencies.is_empty() {
+        named = hashed;
+    }
+    Some(named)
+}
+
+/// uv uses the first 16 hex digits of a hash to name the cache entries.
+fn is_hash(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Whether the Python of the environment satisfies `requires-python` & all of the dependencies are installed.
+fn is_satisfied_by(
+    file_system: &dyn FileSystem,
+    metadata: &ScriptMetadata,
+    prefix: &Path,
+    uv_venv: &UvVenv,
+) -> bool {
+    if let Some(requires_python) = &metadata.requires_python {
+        match PythonVersion::parse(&uv_venv.python_version) {
+            Some(version) if version.matches(requires_python) => {}
+            _ => return false,
+        }
+    }
+    if metadata.dependencies.is_empty() {
+        return true;
+    }
+    let installed = get_installed_distributions(file_system, prefix);
+    metadata
+        .dependencies
+        .iter()
+        .all(|dependency| installed.contains(dependency))
+}
+
+/// Normalized names of the distributions installed in the environment, i.e. of the `*.dist-info` directories.
+fn get_installed_distributions(file_system: &dyn FileSystem, prefix: &Path) -> HashSet<String> {
+    // `Lib/site-packages` on Windows, `lib/python3.X/site-packages` elsewhere.
+    let mut site_packages = vec![prefix.join("Lib").join("site-packages")];
+    if let Ok(entries) = file_system.read_dir(&prefix.join("lib")) {
+        site_packages.extend(entries.iter().map(|entry| entry.join("site-packages")));
+    }
+    site_packages
+        .iter()
+        .filter_map(|dir| file_system.read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| {
+            let (name, _version) = entry
+                .file_name()?
+                .to_str()?
+                .strip_suffix(".dist-info")?
+                .split_once('-')?;
+            Some(normalize_name(name))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pet_core::os_environment::EnvironmentApi;
+    use pet_python_utils::executable::find_executable;
+    use std::collections::HashMap;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const SCRIPT: &str = r#"#!/usr/bin/env -S uv run --script
+# /// script
+# requires-python = ">=3.12"
+# dependencies = [
+#   "Requests[socks]>=2.31",
+#   "typing_extensions",
+# ]
+# ///
+
+import requests
+"#;
+
+    struct TestEnvironment {
+        vars: HashMap<String, String>,
+    }
+
+    impl Environment for TestEnvironment {
+        fn get_user_home(&self) -> Option<PathBuf> {
+            None
+        }
+        fn get_root(&self) -> Option<PathBuf> {
+            None
+        }
+        fn get_env_var(&self, key: String) -> Option<String> {
+            self.vars.get(&key).cloned()
+        }
+        fn get_know_global_search_locations(&self) -> Vec<PathBuf> {
+            vec![]
+        }
+    }
+
+    fn with_cache_dir(cache_dir: &Path) -> TestEnvironment {
+        TestEnvironment {
+            vars: HashMap::from([(
+                "UV_CACHE_DIR".to_string(),
+                cache_dir.to_string_lossy().to_string(),
+            )]),
+        }
+    }
+
+    /// Creates an environment in the uv cache, with these distributions installed.
+    fn create_cached_env(
+        cache_dir: &Path,
+        name: &str,
+        version: &str,
+        installed: &[&str],
+    ) -> PathBuf {
+        let prefix = cache_dir.join(ENVIRONMENTS_DIRECTORY).join(name);
+        let (bin, exe, site_packages) = if cfg!(windows) {
+            (
+                "Scripts",
+                "python.exe",
+                prefix.join("Lib").join("site-packages"),
+            )
+        } else {
+            (
+                "bin",
+                "python",
+                prefix.join("lib").join("python3.12").join("site-packages"),
+            )
+        };
+        fs::create_dir_all(prefix.join(bin)).unwrap();
+        fs::write(prefix.join(bin).join(exe), "").unwrap();
+        fs::write(
+            prefix.join("pyvenv.cfg"),
+            format!("home = /usr/bin\nuv = 0.8.0\nversion_info = {version}\n"),
+        )
+        .unwrap();
+        fs::create_dir_all(&site_packages).unwrap();
+        for distribution in installed {
+            fs::create_dir_all(site_packages.join(format!("{distribution}.dist-info"))).unwrap();
+        }
+        prefix
+    }
+
+    fn write_script(dir: &Path, name: &str, contents: &str) -> PathBuf {
+        let script = dir.join(name);
+        fs::write(&script, contents).unwrap();
+        script
+    }
+
+    #[test]
+    fn parse_script_metadata_block() {
+        let metadata = parse_script_metadata(SCRIPT).unwrap();
+
+        assert_eq!(
+            metadata.requires_python,
+            Some(VersionSpecifiers::parse(">=3.12").unwrap())
+        );
+        assert_eq!(
+            metadata.dependencies,
+            vec!["requests".to_string(), "typing-extensions".to_string()]
+        );
+        assert_eq!(
+            parse_script_metadata("# /// script\n# ///\n"),
+            Some(ScriptMetadata::default())
+        );
+    }
+
+    #[test]
+    fn parse_script_without_metadata_block() {
+        assert_eq!(parse_script_metadata("import sys\n"), None);
+        // Other types of blocks are not the metadata of the script.
+        assert_eq!(parse_script_metadata("# /// pyproject\n# ///\n"), None);
+        // Not closed.
+        assert_eq!(
+            parse_script_metadata("# /// script\n# dependencies = []\nimport sys\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn script_environment_is_the_one_named_after_the_script() {
+        let cache_dir = TempDir::new().unwrap();
+        let scripts = TempDir::new().unwrap();
+        let script = write_script(scripts.path(), "fetch.py", SCRIPT);
+        // Python 3.11 does not satisfy `requires-python`.
+        create_cached_env(
+            cache_dir.path(),
+            "fetch-0a1b2c3d4e5f6a7b",
+            "3.11.9",
+            &["requests-2.32.3", "typing_extensions-4.12.2"],
+        );
+        // Another script.
+        create_cached_env(
+            cache_dir.path(),
+            "other-1a1b2c3d4e5f6a7b",
+            "3.12.4",
+            &["requests-2.32.3", "typing_extensions-4.12.2"],
+        );
+        let prefix = create_cached_env(
+            cache_dir.path(),
+            "fetch-9f8e7d6c5b4a3f2e",
+            "3.12.4",
+            &["requests-2.32.3", "typing_extensions-4.12.2", "idna-3.7"],
+        );
+
+        let env = find_script_environment(&with_cache_dir(cache_dir.path()), &script).unwrap();
+
+        assert_eq!(env.kind, Some(PythonEnvironmentKind::UvScript));
+        assert_eq!(env.name, Some("fetch.py".to_string()));
+        assert_eq!(env.script, Some(script));
+        assert_eq!(env.prefix, Some(prefix.clone()));
+        assert_eq!(env.executable, find_executable(&prefix));
+        assert_eq!(env.version, Some("3.12.4".parse().unwrap()));
+        assert_eq!(env.error, None);
+    }
+
+    #[test]
+    fn script_environment_is_not_guessed_when_several_match() {
+        let cache_dir = TempDir::new().unwrap();
+        let scripts = TempDir::new().unwrap();
+        let script = write_script(scripts.path(), "fetch.py", SCRIPT);
+        // Environments of scripts named `fetch.py` in two directories.
+        for name in ["fetch-0a1b2c3d4e5f6a7b", "fetch-9f8e7d6c5b4a3f2e"] {
+            create_cached_env(
+                cache_dir.path(),
+                name,
+                "3.12.4",
+                &["requests-2.32.3", "typing_extensions-4.12.2"],
+            );
+        }
+
+        let env = find_script_environment(&with_cache_dir(cache_dir.path()), &script).unwrap();
+
+        assert_eq!(env.prefix, None);
+        assert_eq!(env.executable, None);
+        assert!(env.error.unwrap().starts_with("2 uv environments"));
+        assert_eq!(env.script, Some(script));
+    }
+
+    #[test]
+    fn script_environment_named_with_a_hash_needs_the_dependencies_installed() {
+        let cache_dir = TempDir::new().unwrap();
+        let scripts = TempDir::new().unwrap();
+        let script = write_script(scripts.path(), "fetch.py", SCRIPT);
+        create_cached_env(
+            cache_dir.path(),
+            "1a1b2c3d4e5f6a7b",
+            "3.12.4",
+            &["requests-2.32.3"],
+        );
+        let prefix = create_cached_env(
+            cache_dir.path(),
+            "2a1b2c3d4e5f6a7b",
+            "3.13.0",
+            &["requests-2.32.3", "typing_extensions-4.12.2"],
+        );
+
+        let env = find_script_environment(&with_cache_dir(cache_dir.path()), &script).unwrap();
+        assert_eq!(env.prefix, Some(prefix));
+
+        // Without dependencies any of these environments could be the one of the script.
+        let script = write_script(
+            scripts.path(),
+            "hello.py",
+            "# /// script\n# requires-python = \">=3.12\"\n# ///\nprint('hello')\n",
+        );
+        let env = find_script_environment(&with_cache_dir(cache_dir.path()), &script).unwrap();
+        assert_eq!(env.prefix, None);
+        assert_eq!(env.executable, None);
+        assert!(env.error.is_some());
+        assert_eq!(env.script, Some(script));
+    }
+
+    #[test]
+    fn files_without_inline_metadata_are_not_scripts() {
+        let scripts = TempDir::new().unwrap();
+        let environment = EnvironmentApi::new();
+
+        let script = write_script(scripts.path(), "plain.py", "import sys\n");
+        assert!(find_script_environment(&environment, &script).is_none());
+        let script = write_script(scripts.path(), "python", SCRIPT);
+        assert!(find_script_environment(&environment, &script).is_none());
+    }
+}
@@ -10,14 +10,19 @@ use pet_core::{
     reporter::Reporter,
     Locator, LocatorKind,
 };
-use pet_python_utils::executable::{find_executable_or_broken, find_executables, ExecutableResult};
+use pet_fs::file_system::{FileSystem, OsFileSystem};
+use pet_python_utils::arch::get_architecture;
+use pet_python_utils::env::is_spawn_allowed;
+use pet_python_utils::executable::{
+    find_executable_or_broken_in, find_executables_in, ExecutableResult,
+};
 use pet_python_utils::version;
 
 fn is_venv_internal(env: &PythonEnv) -> Option<bool> {
     // env path cannot be empty.
     Some(
-        PyVenvCfg::find(env.executable.parent()?).is_some()
-            || PyVenvCfg::find(&env.prefix.clone()?).is_some(),
+        PyVenvCfg::find_in(env.file_system.as_ref(), env.executable.parent()?).is_some()
+            || PyVenvCfg::find_in(env.file_system.as_ref(), &env.prefix.clone()?).is_some(),
     )
 }
 pub fn is_venv(env: &PythonEnv) -> bool {
@@ -31,19 +36,28 @@ pub fn is_venv_dir(path: &Path) -> bool {
 /// This function can detect broken environments (e.g., with broken symlinks)
 /// and will return them with an error field set.
 pub fn try_environment_from_venv_dir(path: &Path) -> Option<PythonEnvironment> {
+    try_environment_from_venv_dir_in(&OsFileSystem, path)
+}
+
+/// Same as `try_environment_from_venv_dir`, looking at the files in the given file system.
+pub fn try_environment_from_venv_dir_in(
+    file_system: &dyn FileSystem,
+    path: &Path,
+) -> Option<PythonEnvironment> {
     // Check if this is a venv directory
-    let cfg = PyVenvCfg::find(path)?;
+    let cfg = PyVenvCfg::find_in(file_system, path)?;
 
     let prefix = path.to_path_buf();
     let version = version::from_creator_for_virtual_env(&prefix).or(cfg.version.clone());
     let name = cfg.prompt;
 
-    match find_executable_or_broken(path) {
+    match find_executable_or_broken_in(file_system, path) {
         ExecutableResult::Found(executable) => {
-            let symlinks = find_executables(&prefix);
+            let symlinks = find_executables_in(file_system, &prefix);
             Some(
                 PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Venv))
                     .name(name)
+                    .arch(get_architecture(&executable))
                     .executable(Some(executable))
                     .version(version)
                     .prefix(Some(prefix))
@@ -109,18 +123,26 @@ impl Locator for Venv {
             };
             let mut symlinks = vec![];
             if let Some(ref prefix) = prefix {
-                symlinks.append(&mut find_executables(prefix));
+                symlinks.append(&mut find_executables_in(env.file_system.as_ref(), prefix));
             }
 
             // Get the name from the prefix if it exists.
-            let cfg = PyVenvCfg::find(env.executable.parent()?)
-                .or_else(|| PyVenvCfg::find(&env.prefix.clone()?));
+            let cfg = PyVenvCfg::find_in(env.file_system.as_ref(), env.executable.parent()?)
+                .or_else(|| PyVenvCfg::find_in(env.file_system.as_ref(), &env.prefix.clone()?));
+            // Python will not be spawned to get the version, `pyvenv.cfg` is the best we have.
+            let version = match version {
+                None if !is_spawn_allowed(env.file_system.as_ref()) => {
+                    cfg.as_ref().and_then(|cfg| cfg.version.clone())
+                }
+                version => version,
+            };
             let name = cfg.and_then(|cfg| cfg.prompt);
 
             Some(
                 PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Venv))
                     .name(name)
                     .executable(Some(env.executable.clone()))
+                    .arch(get_architecture(&env.executable))
                     .version(version)
                     .prefix(prefix)
                     .symlinks(Some(symlinks))
@@ -140,6 +162,7 @@ impl Locator for Venv {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pet_core::arch::Architecture;
     use std::fs;
     use std::io::Write;
     use tempfile::tempdir;
@@ -448,13 +471,13 @@ mod tests {
 
         // Version provided in PythonEnv should be used as-is
         let mut env = PythonEnv::new(python_path, Some(dir.path().to_path_buf()), None);
-        env.version = Some("3.11.5".to_string());
+        env.version = Some("3.11.5".parse().unwrap());
         let venv = Venv::new();
         let result = venv.try_from(&env);
 
         assert!(result.is_some());
         let py_env = result.unwrap();
-        assert_eq!(py_env.version, Some("3.11.5".to_string()));
+        assert_eq!(py_env.version, Some("3.11.5".parse().unwrap()));
     }
 
     #[test]
@@ -481,7 +504,7 @@ mod tests {
 
         assert_eq!(result.kind, Some(PythonEnvironmentKind::Venv));
         assert!(result.name.is_none());
-        assert_eq!(result.version, Some("3.10.0".to_string()));
+        assert_eq!(result.version, Some("3.10.0".parse().unwrap()));
     }
 
     #[test]
@@ -510,4 +533,27 @@ mod tests {
         let env = PythonEnv::new(python_path, None, None);
         assert!(is_venv(&env));
     }
+
+    #[test]
+    fn test_try_from_reads_architecture_from_executable_header() {
+        let dir = tempdir().unwrap();
+        #[cfg(windows)]
+        let python_path = dir.path().join("Scripts").join("python.exe");
+        #[cfg(unix)]
+        let python_path = dir.path().join("bin").join("python");
+        fs::create_dir_all(python_path.parent().unwrap()).unwrap();
+        fs::write(dir.path().join("pyvenv.cfg"), "version = 3.12.1\n").unwrap();
+
+        // 64-bit little endian ELF header for aarch64.
+        let mut header = vec![0; 64];
+        header[0..4].copy_from_slice(b"\x7fELF");
+        header[4] = 2;
+        header[5] = 1;
+        header[18] = 0xb7;
+        fs::write(&python_path, header).unwrap();
+
+        let env = PythonEnv::new(python_path, Some(dir.path().to_path_buf()), None);
+        let environment = Venv::new().try_from(&env).unwrap();
+        assert_eq!(environment.arch, Some(Architecture::Arm64));
+    }
 }
@@ -9,7 +9,9 @@ use pet_core::{
     reporter::Reporter,
     Locator, LocatorKind,
 };
-use pet_python_utils::executable::find_executables;
+use pet_fs::file_system::{FileSystem, OsFileSystem};
+use pet_python_utils::arch::get_architecture;
+use pet_python_utils::executable::find_executables_in;
 use pet_python_utils::version;
 
 pub fn is_virtualenv(env: &PythonEnv) -> bool {
@@ -23,21 +25,27 @@ pub fn is_virtualenv(env: &PythonEnv) -> bool {
         }
     }
     if let Some(bin) = env.executable.parent() {
-        return is_virtualenv_dir(bin);
+        return is_virtualenv_dir_in(env.file_system.as_ref(), bin);
     }
 
     false
 }
 
 pub fn is_virtualenv_dir(path: &Path) -> bool {
+    is_virtualenv_dir_in(&OsFileSystem, path)
+}
+
+/// Same as `is_virtualenv_dir`, looking at the files in the given file system.
+pub fn is_virtualenv_dir_in(file_system: &dyn FileSystem, path: &Path) -> bool {
     if cfg!(windows) {
-        is_virtualenv_dir_impl(path, "Scripts") || is_virtualenv_dir_impl(path, "bin")
+        is_virtualenv_dir_impl(file_system, path, "Scripts")
+            || is_virtualenv_dir_impl(file_system, path, "bin")
     } else {
-        is_virtualenv_dir_impl(path, "bin")
+        is_virtualenv_dir_impl(file_system, path, "bin")
     }
 }
 
-fn is_virtualenv_dir_impl(path: &Path, bin: &str) -> bool {
+fn is_virtualenv_dir_impl(file_system: &dyn FileSystem, path: &Path, bin: &str) -> bool {
     // Check if the executable is in a bin or Scripts directory.
     // Possible for some reason we do not have the prefix.
     let mut path = path.to_path_buf();
@@ -67,13 +75,14 @@ fn is_virtualenv_dir_impl(path: &Path, bin: &str) -> bool {
     // const directory = path.dirname(interpreterPath);
     // const files = await fsapi.readdir(directory);
     // const regex = /^activate(\.([A-z]|\d)+)?$/i;
-    if path.join("activate").exists() || path.join("activate.bat").exists() {
+    if file_system.exists(&path.join("activate")) || file_system.exists(&path.join("activate.bat"))
+    {
         return true;
     }
 
     // Support for activate.ps, etc.
-    if let Ok(files) = std::fs::read_dir(path) {
-        for file in files.filter_map(Result::ok).map(|e| e.path()) {
+    if let Ok(files) = file_system.read_dir(&path) {
+        for file in files {
             if file
                 .file_name()
                 .unwrap_or_default()
@@ -121,11 +130,12 @@ impl Locator for VirtualEnv {
             };
             let mut symlinks = vec![];
             if let Some(ref prefix) = env.prefix {
-                symlinks.append(&mut find_executables(prefix));
+                symlinks.append(&mut find_executables_in(env.file_system.as_ref(), prefix));
             }
             Some(
                 PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::VirtualEnv))
                     .executable(Some(env.executable.clone()))
+                    .arch(get_architecture(&env.executable))
                     .version(version)
                     .prefix(env.prefix.clone())
                     .symlinks(Some(symlinks))
@@ -2,26 +2,29 @@
 // Licensed under the MIT License.
 
 use crate::env_variables::EnvVariables;
-use pet_fs::path::norm_case;
+use pet_fs::{file_system::FileSystem, path::norm_case};
 use std::path::PathBuf;
 
 #[cfg(windows)]
-fn get_default_virtualenvwrapper_path(env_vars: &EnvVariables) -> Option<PathBuf> {
+fn get_default_virtualenvwrapper_path(
+    file_system: &dyn FileSystem,
+    env_vars: &EnvVariables,
+) -> Option<PathBuf> {
     // In Windows, the default path for WORKON_HOME is %USERPROFILE%\Envs.
     // If 'Envs' is not available we should default to '.virtualenvs'. Since that
     // is also valid for windows.
 
     if let Some(user_home) = &env_vars.home {
         let home = user_home.join("Envs");
-        if home.exists() {
+        if file_system.exists(&home) {
             return Some(norm_case(home));
         }
         let home = user_home.join(".virtualenvs");
-        if home.exists() {
+        if file_system.exists(&home) {
             return Some(norm_case(home));
         }
         let home = user_home.join("virtualenvs");
-        if home.exists() {
+        if file_system.exists(&home) {
             return Some(norm_case(home));
         }
     }
@@ -29,31 +32,38 @@ fn get_default_virtualenvwrapper_path(env_vars: &EnvVariables) -> Option<PathBuf
 }
 
 #[cfg(unix)]
-fn get_default_virtualenvwrapper_path(env_vars: &EnvVariables) -> Option<PathBuf> {
+fn get_default_virtualenvwrapper_path(
+    file_system: &dyn FileSystem,
+    env_vars: &EnvVariables,
+) -> Option<PathBuf> {
     if let Some(home) = &env_vars.home {
         let home = home.join(".virtualenvs");
-        if home.exists() {
+        if file_system.exists(&home) {
             return Some(norm_case(&home));
         }
     }
     None
 }
 
-pub fn get_work_on_home_path(environment: &EnvVariables) -> Option<PathBuf> {
+pub fn get_work_on_home_path(
+    file_system: &dyn FileSystem,
+    environment: &EnvVariables,
+) -> Option<PathBuf> {
     // The WORKON_HOME variable contains the path to the root directory of all virtualenvwrapper environments.
     // If the interpreter path belongs to one of them then it is a virtualenvwrapper type of environment.
     if let Some(work_on_home) = &environment.workon_home {
         let work_on_home = norm_case(PathBuf::from(work_on_home));
-        if work_on_home.exists() {
+        if file_system.exists(&work_on_home) {
             return Some(work_on_home);
         }
     }
-    get_default_virtualenvwrapper_path(environment)
+    get_default_virtualenvwrapper_path(file_system, environment)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pet_fs::file_system::OsFileSystem;
     use std::{
         fs,
         time::{SystemTime, UNIX_EPOCH},
@@ -81,7 +91,7 @@ mod tests {
         };
 
         assert_eq!(
-            get_work_on_home_path(&env_variables),
+            get_work_on_home_path(&OsFileSystem, &env_variables),
             Some(norm_case(&workon_home))
         );
 
@@ -104,7 +114,7 @@ mod tests {
         };
 
         assert_eq!(
-            get_work_on_home_path(&env_variables),
+            get_work_on_home_path(&OsFileSystem, &env_variables),
             Some(norm_case(default_home))
         );
 
@@ -123,7 +133,7 @@ mod tests {
         };
 
         assert_eq!(
-            get_work_on_home_path(&env_variables),
+            get_work_on_home_path(&OsFileSystem, &env_variables),
             Some(norm_case(default_home))
         );
 
@@ -142,7 +152,7 @@ mod tests {
         };
 
         assert_eq!(
-            get_work_on_home_path(&env_variables),
+            get_work_on_home_path(&OsFileSystem, &env_variables),
             Some(norm_case(default_home))
         );
 
@@ -157,7 +167,7 @@ mod tests {
             workon_home: Some(workon_home.join("missing").to_string_lossy().to_string()),
         };
 
-        assert_eq!(get_work_on_home_path(&env_variables), None);
+        assert_eq!(get_work_on_home_path(&OsFileSystem, &env_variables), None);
 
         fs::remove_dir_all(workon_home).unwrap();
     }
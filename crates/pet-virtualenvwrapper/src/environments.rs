@@ -3,21 +3,22 @@
 
 use crate::{env_variables::EnvVariables, environment_locations::get_work_on_home_path};
 use pet_core::env::PythonEnv;
-use pet_fs::path::norm_case;
+use pet_fs::{file_system::FileSystem, path::norm_case};
 use pet_virtualenv::is_virtualenv;
-use std::{
-    fs,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
-fn is_under_work_on_home(executable: &Path, work_on_home_dir: &Path) -> bool {
+fn is_under_work_on_home(
+    file_system: &dyn FileSystem,
+    executable: &Path,
+    work_on_home_dir: &Path,
+) -> bool {
     if executable.starts_with(work_on_home_dir) {
         return true;
     }
 
     if let (Ok(executable), Ok(work_on_home_dir)) = (
-        fs::canonicalize(executable),
-        fs::canonicalize(work_on_home_dir),
+        file_system.canonicalize(executable),
+        file_system.canonicalize(work_on_home_dir),
     ) {
         return norm_case(executable).starts_with(norm_case(work_on_home_dir));
     }
@@ -33,8 +34,11 @@ pub fn is_virtualenvwrapper(env: &PythonEnv, environment: &EnvVariables) -> bool
     // For environment to be a virtualenvwrapper based it has to follow these two rules:
     // 1. It should be in a sub-directory under the WORKON_HOME
     // 2. It should be a valid virtualenv environment
-    if let Some(work_on_home_dir) = get_work_on_home_path(environment) {
-        if is_under_work_on_home(&env.executable, &work_on_home_dir) && is_virtualenv(env) {
+    let file_system = env.file_system.as_ref();
+    if let Some(work_on_home_dir) = get_work_on_home_path(file_system, environment) {
+        if is_under_work_on_home(file_system, &env.executable, &work_on_home_dir)
+            && is_virtualenv(env)
+        {
             return true;
         }
     }
@@ -44,9 +48,9 @@ pub fn is_virtualenvwrapper(env: &PythonEnv, environment: &EnvVariables) -> bool
 
 pub fn get_project(env: &PythonEnv) -> Option<PathBuf> {
     let project_file = env.prefix.clone()?.join(".project");
-    let contents = fs::read_to_string(project_file).ok()?;
+    let contents = env.file_system.read_to_string(&project_file).ok()?;
     let project_folder = norm_case(PathBuf::from(contents.trim().to_string()));
-    if fs::metadata(&project_folder).is_ok() {
+    if env.file_system.exists(&project_folder) {
         Some(norm_case(&project_folder))
     } else {
         None
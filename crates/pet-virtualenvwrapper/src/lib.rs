@@ -10,7 +10,8 @@ use pet_core::{
     reporter::Reporter,
     Locator, LocatorKind,
 };
-use pet_python_utils::executable::find_executables;
+use pet_python_utils::arch::get_architecture;
+use pet_python_utils::executable::find_executables_in;
 use pet_python_utils::version;
 
 mod env_variables;
@@ -51,7 +52,7 @@ impl Locator for VirtualEnvWrapper {
         let mut symlinks = vec![];
         let mut name = None;
         if let Some(ref prefix) = env.prefix {
-            symlinks.append(&mut find_executables(prefix));
+            symlinks.append(&mut find_executables_in(env.file_system.as_ref(), prefix));
             name = prefix.file_name().and_then(|f| f.to_str());
         }
 
@@ -59,6 +60,7 @@ impl Locator for VirtualEnvWrapper {
             PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::VirtualEnvWrapper))
                 .name(name.map(String::from))
                 .executable(Some(env.executable.clone()))
+                .arch(get_architecture(&env.executable))
                 .version(version)
                 .prefix(env.prefix.clone())
                 .project(get_project(env))
@@ -193,7 +195,7 @@ mod tests {
         let env = PythonEnv::new(
             executable.clone(),
             Some(prefix.clone()),
-            Some("3.12.1".to_string()),
+            Some("3.12.1".parse().unwrap()),
         );
 
         let virtualenvwrapper_env = locator.try_from(&env).unwrap();
@@ -210,7 +212,10 @@ mod tests {
                 .map(pet_fs::path::norm_case),
             Some(pet_fs::path::norm_case(executable))
         );
-        assert_eq!(virtualenvwrapper_env.version, Some("3.12.1".to_string()));
+        assert_eq!(
+            virtualenvwrapper_env.version,
+            Some("3.12.1".parse().unwrap())
+        );
         assert_eq!(
             virtualenvwrapper_env.prefix,
             Some(pet_fs::path::norm_case(prefix.clone()))
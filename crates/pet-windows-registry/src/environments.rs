@@ -9,9 +9,12 @@ use pet_core::reporter::Reporter;
 use pet_core::{
     arch::Architecture,
     python_environment::{PythonEnvironmentBuilder, PythonEnvironmentKind},
+    python_version::PythonVersion,
     LocatorResult,
 };
 #[cfg(windows)]
+use pet_python_utils::arch::get_architecture;
+#[cfg(windows)]
 use pet_windows_store::is_windows_app_folder_in_program_files;
 #[cfg(windows)]
 use std::{path::PathBuf, sync::Arc};
@@ -375,19 +378,17 @@ fn get_registry_pythons_from_key_for_company(
                         ))
                         .display_name(Some(display_name))
                         .executable(Some(executable.clone()))
-                        .version(if version.is_empty() {
-                            None
-                        } else {
-                            Some(version)
-                        })
+                        .version(PythonVersion::parse_lossless(&version))
                         .prefix(env_path)
-                        .arch(if architecture.contains("32") {
-                            Some(Architecture::X86)
-                        } else if architecture.contains("64") {
-                            Some(Architecture::X64)
-                        } else {
-                            None
-                        })
+                        .arch(
+                            get_architecture(&executable).or(if architecture.contains("32") {
+                                Some(Architecture::X86)
+                            } else if architecture.contains("64") {
+                                Some(Architecture::X64)
+                            } else {
+                                None
+                            }),
+                        )
                         .build();
 
                         if let Some(reporter) = reporter {
@@ -3,7 +3,7 @@
 
 #[cfg(windows)]
 use environments::get_registry_pythons;
-use pet_conda::{utils::is_conda_env, CondaLocator};
+use pet_conda::{utils::is_conda_env_in, CondaLocator};
 use pet_core::{
     env::PythonEnv,
     python_environment::{PythonEnvironment, PythonEnvironmentKind},
@@ -156,7 +156,7 @@ impl Locator for WindowsRegistry {
         // a Python environment via an Installer that ends up in Windows Registry
         // However that environment is a conda environment.
         if let Some(env_path) = &env.prefix {
-            if is_conda_env(env_path) {
+            if is_conda_env_in(env.file_system.as_ref(), env_path) {
                 return None;
             }
         }
@@ -12,9 +12,11 @@ use pet_core::python_environment::PythonEnvironment;
 #[cfg(windows)]
 use pet_core::{arch::Architecture, python_environment::PythonEnvironmentBuilder};
 #[cfg(windows)]
-use pet_fs::path::norm_case;
+use pet_fs::{file_system::FileSystem, path::norm_case};
 #[cfg(windows)]
-use pet_python_utils::executable::find_executables;
+use pet_python_utils::arch::get_architecture;
+#[cfg(windows)]
+use pet_python_utils::executable::find_executables_in;
 #[cfg(windows)]
 use regex::Regex;
 use std::path::PathBuf;
@@ -90,11 +92,11 @@ impl PotentialPython {
                 .display_name(Some(result.display_name))
                 .executable(Some(exe.clone()))
                 .prefix(Some(env_path.clone()))
-                .arch(if result.is64_bit {
+                .arch(get_architecture(&exe).or(if result.is64_bit {
                     Some(Architecture::X64)
                 } else {
                     None
-                })
+                }))
                 // We only have the partial version, no point returning bogus info.
                 // .version(Some(self.version.clone()))
                 .symlinks(Some(symlinks))
@@ -111,7 +113,10 @@ impl PotentialPython {
 }
 
 #[cfg(windows)]
-pub fn list_store_pythons(environment: &EnvVariables) -> Option<Vec<PythonEnvironment>> {
+pub fn list_store_pythons(
+    file_system: &dyn FileSystem,
+    environment: &EnvVariables,
+) -> Option<Vec<PythonEnvironment>> {
     use crate::environment_locations::get_search_locations;
     use log::{trace, warn};
     use std::collections::HashMap;
@@ -122,11 +127,7 @@ pub fn list_store_pythons(environment: &EnvVariables) -> Option<Vec<PythonEnviro
     trace!("Searching for Windows Store Python in {:?}", apps_path);
 
     let mut potential_matches: HashMap<String, PotentialPython> = HashMap::new();
-    for path in std::fs::read_dir(apps_path)
-        .ok()?
-        .filter_map(Result::ok)
-        .map(|f| f.path())
-    {
+    for path in file_system.read_dir(&apps_path).ok()? {
         if let Some(name) = path.file_name() {
             let name = name.to_string_lossy().to_string();
             if name.starts_with("PythonSoftwareFoundation.Python.") {
@@ -146,7 +147,7 @@ pub fn list_store_pythons(environment: &EnvVariables) -> Option<Vec<PythonEnviro
                         path: Some(path.clone()),
                         name: Some(name.clone()),
                         version: simple_version.to_string(),
-                        symlinks: find_symlinks(path, simple_version.to_string()),
+                        symlinks: find_symlinks(file_system, path, simple_version.to_string()),
                         ..Default::default()
                     };
                     potential_matches.insert(simple_version.to_string(), item);
@@ -172,7 +173,7 @@ pub fn list_store_pythons(environment: &EnvVariables) -> Option<Vec<PythonEnviro
                         let item = PotentialPython {
                             exe: Some(path.clone()),
                             version: simple_version.to_string(),
-                            symlinks: find_symlinks(path, simple_version.to_string()),
+                            symlinks: find_symlinks(file_system, path, simple_version.to_string()),
                             ..Default::default()
                         };
                         potential_matches.insert(simple_version.to_string(), item);
@@ -207,7 +208,11 @@ pub fn list_store_pythons(environment: &EnvVariables) -> Option<Vec<PythonEnviro
 /// Given an exe from a sub directory of WindowsApp path, find the symlinks (reparse points)
 /// for the same environment but from the WindowsApp directory.
 #[cfg(windows)]
-fn find_symlinks(exe_in_windows_app_path: PathBuf, version: String) -> Vec<PathBuf> {
+fn find_symlinks(
+    file_system: &dyn FileSystem,
+    exe_in_windows_app_path: PathBuf,
+    version: String,
+) -> Vec<PathBuf> {
     let mut symlinks = vec![];
     if let Some(bin_dir) = exe_in_windows_app_path.parent() {
         if let Some(windows_app_path) = bin_dir.parent() {
@@ -218,7 +223,7 @@ fn find_symlinks(exe_in_windows_app_path: PathBuf, version: String) -> Vec<PathB
 
             let possible_exe =
                 windows_app_path.join(PathBuf::from(format!("python{}.exe", version)));
-            if possible_exe.exists() {
+            if file_system.exists(&possible_exe) {
                 symlinks.push(possible_exe);
             }
 
@@ -229,7 +234,7 @@ fn find_symlinks(exe_in_windows_app_path: PathBuf, version: String) -> Vec<PathB
             // However if we have just one Python exe of the form Python3.x.ex, then python.exe and Python3.exe are symlinks.
             let mut number_of_python_exes_with_versions = 0;
             let mut exes = vec![];
-            find_executables(windows_app_path)
+            find_executables_in(file_system, windows_app_path)
                 .into_iter()
                 .for_each(|exe| {
                     if let Some(name) = exe.file_name().and_then(|s| s.to_str()) {
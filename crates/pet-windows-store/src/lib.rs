@@ -15,6 +15,7 @@ use pet_core::LocatorKind;
 use pet_core::{
     os_environment::Environment, Locator, RefreshStatePersistence, RefreshStateSyncScope,
 };
+use pet_fs::file_system::FileSystem;
 #[cfg(any(windows, test))]
 use pet_fs::path::norm_case;
 use std::path::{Path, PathBuf};
@@ -68,6 +69,8 @@ pub struct WindowsStore {
     pub env_vars: EnvVariables,
     #[allow(dead_code)]
     environments: Arc<RwLock<Option<Arc<Vec<CachedStoreEnvironment>>>>>,
+    #[allow(dead_code)]
+    file_system: Arc<dyn FileSystem>,
 }
 
 impl WindowsStore {
@@ -75,6 +78,7 @@ impl WindowsStore {
         WindowsStore {
             env_vars: EnvVariables::from(environment),
             environments: Arc::new(RwLock::new(None)),
+            file_system: environment.file_system(),
         }
     }
     #[cfg(windows)]
@@ -88,7 +92,7 @@ impl WindowsStore {
         }
 
         let envs = Arc::new(
-            list_store_pythons(&self.env_vars)
+            list_store_pythons(self.file_system.as_ref(), &self.env_vars)
                 .unwrap_or_default()
                 .into_iter()
                 .map(CachedStoreEnvironment::from_environment)
@@ -11,16 +11,19 @@ use lazy_static::lazy_static;
 use log::trace;
 use pet_core::{
     env::PythonEnv,
+    os_environment::Environment,
     python_environment::{PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind},
+    python_version::PythonVersion,
     reporter::Reporter,
     Locator, LocatorKind, RefreshStatePersistence, RefreshStateSyncScope,
 };
+use pet_fs::file_system::{os_file_system, FileSystem};
 use pet_fs::path::norm_case;
-use pet_python_utils::executable::find_executables;
+use pet_python_utils::arch::get_architecture;
+use pet_python_utils::executable::find_executables_in;
 use pet_virtualenv::is_virtualenv;
 use regex::Regex;
 use std::{
-    fs,
     path::{Path, PathBuf},
     sync::{Arc, Mutex},
 };
@@ -54,12 +57,21 @@ pub struct WinPython {
     /// cleared at the start of each `find()` (refresh). Mirrors the pattern
     /// used by `WindowsStore` and `WindowsRegistry`.
     cached_environments: Arc<Mutex<Option<Arc<Vec<PythonEnvironment>>>>>,
+    #[cfg_attr(not(windows), allow(dead_code))]
+    file_system: Arc<dyn FileSystem>,
 }
 
 impl WinPython {
     pub fn new() -> WinPython {
         WinPython {
             cached_environments: Arc::new(Mutex::new(None)),
+            file_system: os_file_system(),
+        }
+    }
+    pub fn from(environment: &dyn Environment) -> WinPython {
+        WinPython {
+            cached_environments: Arc::new(Mutex::new(None)),
+            file_system: environment.file_system(),
         }
     }
 
@@ -111,9 +123,9 @@ impl Default for WinPython {
 }
 
 /// Check if a directory is a WinPython installation root by looking for marker files.
-fn is_winpython_root(path: &Path) -> bool {
+fn is_winpython_root(file_system: &dyn FileSystem, path: &Path) -> bool {
     for marker in WINPYTHON_MARKER_FILES {
-        if path.join(marker).exists() {
+        if file_system.exists(&path.join(marker)) {
             return true;
         }
     }
@@ -132,7 +144,10 @@ fn is_python_folder_name(name: &str) -> bool {
 
 /// Given a Python executable path, try to find the WinPython root directory.
 /// Returns (winpython_root, python_folder) if found.
-fn find_winpython_root(executable: &Path) -> Option<(PathBuf, PathBuf)> {
+fn find_winpython_root(
+    file_system: &dyn FileSystem,
+    executable: &Path,
+) -> Option<(PathBuf, PathBuf)> {
     // Typical structure:
     // WPy64-31300/python-3.13.0.amd64/python.exe
     // or
@@ -143,9 +158,9 @@ fn find_winpython_root(executable: &Path) -> Option<(PathBuf, PathBuf)> {
     // Walk up the directory tree looking for WinPython markers
     for _ in 0..5 {
         // Check if current directory has WinPython marker files
-        if is_winpython_root(current) {
+        if is_winpython_root(file_system, current) {
             // Find the python folder within this WinPython root
-            if let Some(python_folder) = find_python_folder_in_winpython(current) {
+            if let Some(python_folder) = find_python_folder_in_winpython(file_system, current) {
                 return Some((current.to_path_buf(), python_folder));
             }
         }
@@ -155,7 +170,7 @@ fn find_winpython_root(executable: &Path) -> Option<(PathBuf, PathBuf)> {
             let name_str = name.to_string_lossy();
             if is_winpython_dir_name(&name_str) {
                 // This might be the WinPython root
-                if let Some(python_folder) = find_python_folder_in_winpython(current) {
+                if let Some(python_folder) = find_python_folder_in_winpython(file_system, current) {
                     return Some((current.to_path_buf(), python_folder));
                 }
             }
@@ -169,12 +184,14 @@ fn find_winpython_root(executable: &Path) -> Option<(PathBuf, PathBuf)> {
 }
 
 /// Find the Python installation folder within a WinPython root directory.
-fn find_python_folder_in_winpython(winpython_root: &Path) -> Option<PathBuf> {
-    let entries = fs::read_dir(winpython_root).ok()?;
+fn find_python_folder_in_winpython(
+    file_system: &dyn FileSystem,
+    winpython_root: &Path,
+) -> Option<PathBuf> {
+    let entries = file_system.read_dir(winpython_root).ok()?;
 
-    for entry in entries.filter_map(Result::ok) {
-        let path = entry.path();
-        if path.is_dir() {
+    for path in entries {
+        if file_system.is_dir(&path) {
             if let Some(name) = path.file_name() {
                 let name_str = name.to_string_lossy();
                 if is_python_folder_name(&name_str) {
@@ -184,7 +201,7 @@ fn find_python_folder_in_winpython(winpython_root: &Path) -> Option<PathBuf> {
                     } else {
                         "python"
                     });
-                    if python_exe.exists() {
+                    if file_system.exists(&python_exe) {
                         return Some(path);
                     }
                 }
@@ -197,7 +214,7 @@ fn find_python_folder_in_winpython(winpython_root: &Path) -> Option<PathBuf> {
 
 /// Get the version from the Python folder name.
 /// Example: "python-3.13.0.amd64" -> "3.13.0"
-fn version_from_folder_name(folder_name: &str) -> Option<String> {
+fn version_from_folder_name(folder_name: &str) -> Option<PythonVersion> {
     let name = folder_name.to_lowercase();
     if let Some(stripped) = name.strip_prefix("python-") {
         // Remove architecture suffix if present
@@ -205,14 +222,14 @@ fn version_from_folder_name(folder_name: &str) -> Option<String> {
             .strip_suffix(".amd64")
             .or_else(|| stripped.strip_suffix(".win32"))
             .unwrap_or(stripped);
-        Some(version_part.to_string())
+        PythonVersion::parse(version_part)
     } else {
         None
     }
 }
 
 /// Get the display name for a WinPython installation.
-fn get_display_name(winpython_root: &Path, version: Option<&str>) -> Option<String> {
+fn get_display_name(winpython_root: &Path, version: Option<&PythonVersion>) -> Option<String> {
     let folder_name = winpython_root.file_name()?.to_string_lossy().to_string();
 
     if let Some(ver) = version {
@@ -269,7 +286,8 @@ impl Locator for WinPython {
         }
 
         // Try to find the WinPython root from the executable path
-        let (winpython_root, python_folder) = find_winpython_root(&env.executable)?;
+        let (winpython_root, python_folder) =
+            find_winpython_root(env.file_system.as_ref(), &env.executable)?;
 
         trace!(
             "Found WinPython installation at {:?} (python folder: {:?})",
@@ -278,6 +296,7 @@ impl Locator for WinPython {
         );
 
         Some(build_environment(
+            env.file_system.as_ref(),
             env.executable.clone(),
             winpython_root,
             python_folder,
@@ -303,10 +322,11 @@ impl Locator for WinPython {
 /// Build a `PythonEnvironment` for a discovered WinPython install. Shared by
 /// `try_from()` and the search-path discovery walk.
 fn build_environment(
+    file_system: &dyn FileSystem,
     executable: PathBuf,
     winpython_root: PathBuf,
     python_folder: PathBuf,
-    explicit_version: Option<String>,
+    explicit_version: Option<PythonVersion>,
     extra_symlinks: Option<Vec<PathBuf>>,
 ) -> PythonEnvironment {
     // Normalize the canonical executable too so it matches its corresponding
@@ -341,14 +361,14 @@ fn build_environment(
     }
 
     // Add executables from the python folder root.
-    for exe in find_executables(&python_folder) {
+    for exe in find_executables_in(file_system, &python_folder) {
         push(exe, &mut symlinks, &mut seen);
     }
 
     // Add python* (but not pip*) executables from Scripts/.
     let scripts_dir = python_folder.join("Scripts");
-    if scripts_dir.exists() {
-        for exe in find_executables(&scripts_dir) {
+    if file_system.exists(&scripts_dir) {
+        for exe in find_executables_in(file_system, &scripts_dir) {
             let exe_name = exe.file_name().map(|n| n.to_string_lossy().to_lowercase());
             if exe_name
                 .as_ref()
@@ -361,10 +381,11 @@ fn build_environment(
 
     symlinks.sort();
 
-    let display_name = get_display_name(&winpython_root, version.as_deref());
+    let display_name = get_display_name(&winpython_root, version.as_ref());
 
     PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::WinPython))
         .display_name(display_name)
+        .arch(get_architecture(&executable))
         .executable(Some(executable))
         .version(version)
         .prefix(Some(python_folder))
@@ -389,16 +410,17 @@ fn discover_environments_in(
 ) -> Vec<PythonEnvironment> {
     let mut found: Vec<PythonEnvironment> = Vec::new();
     let mut seen_executables: std::collections::HashSet<PathBuf> = std::collections::HashSet::new();
+    let file_system = locator.file_system.as_ref();
 
     for search_path in search_paths {
-        if !search_path.exists() {
+        if !file_system.exists(&search_path) {
             continue;
         }
         trace!("Searching for WinPython in {:?}", search_path);
 
         // The search path itself may *be* a WinPython install (when supplied
         // via WINPYTHON_HOME pointing directly at, e.g., `D:\WPy64-31300`).
-        if is_winpython_root(&search_path)
+        if is_winpython_root(file_system, &search_path)
             || search_path
                 .file_name()
                 .and_then(|n| n.to_str())
@@ -409,17 +431,16 @@ fn discover_environments_in(
         }
 
         // Otherwise treat it as a directory that may contain WinPython installs.
-        if let Ok(entries) = fs::read_dir(&search_path) {
-            for entry in entries.filter_map(Result::ok) {
-                let path = entry.path();
-                if !path.is_dir() {
+        if let Ok(entries) = file_system.read_dir(&search_path) {
+            for path in entries {
+                if !file_system.is_dir(&path) {
                     continue;
                 }
                 let is_match = path
                     .file_name()
                     .and_then(|n| n.to_str())
                     .is_some_and(is_winpython_dir_name)
-                    || is_winpython_root(&path);
+                    || is_winpython_root(file_system, &path);
                 if is_match {
                     collect_install(&path, locator, &mut found, &mut seen_executables);
                 }
@@ -437,18 +458,24 @@ fn collect_install(
     found: &mut Vec<PythonEnvironment>,
     seen_executables: &mut std::collections::HashSet<PathBuf>,
 ) {
-    let Some(python_folder) = find_python_folder_in_winpython(winpython_root) else {
+    let file_system = locator.file_system.as_ref();
+    let Some(python_folder) = find_python_folder_in_winpython(file_system, winpython_root) else {
         return;
     };
     let python_exe = python_folder.join("python.exe");
-    if !python_exe.exists() {
+    if !file_system.exists(&python_exe) {
         return;
     }
     let normed_exe = norm_case(&python_exe);
     if !seen_executables.insert(normed_exe.clone()) {
         return;
     }
-    let env = PythonEnv::new(python_exe.clone(), Some(python_folder.clone()), None);
+    let env = PythonEnv::new_in(
+        locator.file_system.clone(),
+        python_exe.clone(),
+        Some(python_folder.clone()),
+        None,
+    );
     if let Some(found_env) = locator.try_from(&env) {
         found.push(found_env);
     }
@@ -522,7 +549,8 @@ fn build_search_paths(userprofile: Option<String>, winpython_home: Option<String
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs::File;
+    use pet_fs::file_system::OsFileSystem;
+    use std::fs::{self, File};
     use tempfile::tempdir;
 
     #[test]
@@ -555,19 +583,19 @@ mod tests {
     fn test_version_from_folder_name() {
         assert_eq!(
             version_from_folder_name("python-3.13.0.amd64"),
-            Some("3.13.0".to_string())
+            Some("3.13.0".parse().unwrap())
         );
         assert_eq!(
             version_from_folder_name("python-3.9.0"),
-            Some("3.9.0".to_string())
+            Some("3.9.0".parse().unwrap())
         );
         assert_eq!(
             version_from_folder_name("python-3.8.0.win32"),
-            Some("3.8.0".to_string())
+            Some("3.8.0".parse().unwrap())
         );
         assert_eq!(
             version_from_folder_name("Python-3.10.5.amd64"),
-            Some("3.10.5".to_string())
+            Some("3.10.5".parse().unwrap())
         );
 
         assert_eq!(version_from_folder_name("python"), None);
@@ -579,7 +607,7 @@ mod tests {
         // Use a simple directory name that works on all platforms
         let path = PathBuf::from("WPy64-31300");
         assert_eq!(
-            get_display_name(&path, Some("3.13.0")),
+            get_display_name(&path, Some(&"3.13.0".parse().unwrap())),
             Some("WinPython 3.13.0".to_string())
         );
         assert_eq!(
@@ -594,7 +622,7 @@ mod tests {
         let winpython_marker = dir.path().join(".winpython");
         File::create(&winpython_marker).unwrap();
 
-        assert!(is_winpython_root(dir.path()));
+        assert!(is_winpython_root(&OsFileSystem, dir.path()));
     }
 
     #[test]
@@ -603,13 +631,13 @@ mod tests {
         let winpython_ini = dir.path().join("winpython.ini");
         File::create(&winpython_ini).unwrap();
 
-        assert!(is_winpython_root(dir.path()));
+        assert!(is_winpython_root(&OsFileSystem, dir.path()));
     }
 
     #[test]
     fn test_is_winpython_root_without_marker() {
         let dir = tempdir().unwrap();
-        assert!(!is_winpython_root(dir.path()));
+        assert!(!is_winpython_root(&OsFileSystem, dir.path()));
     }
 
     #[test]
@@ -623,7 +651,7 @@ mod tests {
         let python_exe = python_folder.join("python.exe");
         File::create(&python_exe).unwrap();
 
-        let result = find_python_folder_in_winpython(dir.path());
+        let result = find_python_folder_in_winpython(&OsFileSystem, dir.path());
         assert!(result.is_some());
         assert_eq!(result.unwrap(), python_folder);
     }
@@ -635,7 +663,7 @@ mod tests {
         fs::create_dir_all(&python_folder).unwrap();
 
         // No python.exe created
-        let result = find_python_folder_in_winpython(dir.path());
+        let result = find_python_folder_in_winpython(&OsFileSystem, dir.path());
         assert!(result.is_none());
     }
 
@@ -654,7 +682,7 @@ mod tests {
         let python_exe = python_folder.join("python.exe");
         File::create(&python_exe).unwrap();
 
-        let result = find_winpython_root(&python_exe);
+        let result = find_winpython_root(&OsFileSystem, &python_exe);
         assert!(result.is_some());
         let (root, folder) = result.unwrap();
         assert_eq!(root, winpython_root);
@@ -675,7 +703,7 @@ mod tests {
         let python_exe = python_folder.join("python.exe");
         File::create(&python_exe).unwrap();
 
-        let result = find_winpython_root(&python_exe);
+        let result = find_winpython_root(&OsFileSystem, &python_exe);
         assert!(result.is_some());
         let (root, folder) = result.unwrap();
         assert_eq!(root, winpython_root);
@@ -697,7 +725,7 @@ mod tests {
 
         File::create(&python_exe).unwrap();
 
-        let result = find_winpython_root(&python_exe);
+        let result = find_winpython_root(&OsFileSystem, &python_exe);
         assert!(result.is_none());
     }
 
@@ -0,0 +1,26 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Lists the Python environments in a folder (defaults to the current directory).
+//!
+//! cargo run --example find -- /path/to/workspace
+
+use pet::finder::Finder;
+
+fn main() {
+    let workspace = std::env::args().nth(1).unwrap_or(".".to_string());
+    for environment in Finder::new().workspace(workspace).find_iter() {
+        println!(
+            "{:<20} {}",
+            environment
+                .kind
+                .map(|kind| format!("{kind:?}"))
+                .unwrap_or("Unknown".to_string()),
+            environment
+                .executable
+                .or(environment.prefix)
+                .unwrap_or_default()
+                .display()
+        );
+    }
+}
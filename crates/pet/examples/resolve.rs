@@ -0,0 +1,22 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Resolves the environment of a Python executable.
+//!
+//! cargo run --example resolve -- /usr/bin/python3
+
+use pet::finder::Finder;
+
+fn main() {
+    let Some(executable) = std::env::args().nth(1) else {
+        eprintln!("Usage: resolve <python executable>");
+        std::process::exit(1);
+    };
+    match Finder::new().resolve(&executable) {
+        Some(environment) => println!(
+            "{}",
+            serde_json::to_string_pretty(&environment).expect("failed to serialize environment")
+        ),
+        None => println!("No environment found for {executable}"),
+    }
+}
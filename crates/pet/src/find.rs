@@ -1,40 +1,162 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
 use log::{trace, warn};
-use pet_conda::utils::is_conda_env;
+use pet_conda::utils::is_conda_env_in;
 use pet_core::env::PythonEnv;
 use pet_core::os_environment::Environment;
 use pet_core::python_environment::PythonEnvironmentKind;
 use pet_core::reporter::Reporter;
+use pet_core::search_root::{read_search_root, SearchRootBudget};
 use pet_core::telemetry::refresh_progress::{
     RefreshProgress, RefreshProgressPhase, RefreshProgressStatus,
 };
 use pet_core::telemetry::TelemetryEvent;
-use pet_core::{Configuration, Locator, LocatorKind};
+use pet_core::{Configuration, Locator, LocatorKind, NetworkFilesystemPolicy};
 use pet_env_var_path::get_search_paths_from_env_variables;
-use pet_global_virtualenvs::list_global_virtual_envs_paths;
-use pet_pixi::is_pixi_env;
+use pet_fs::file_system::{CachedFileSystem, FileSystem};
+use pet_fs::mount::FileSystemType;
+use pet_global_virtualenvs::list_global_virtual_envs_paths_in;
+use pet_nox::NOX_DIRECTORY;
+use pet_pixi::is_pixi_env_in;
 use pet_python_utils::executable::{
-    find_executable, find_executables, should_search_for_environments_in_path,
+    find_executable_in, find_executables_in, should_search_for_environments_in_path,
 };
-use pet_venv::try_environment_from_venv_dir;
-use pet_virtualenv::is_virtualenv_dir;
+use pet_shims::{add_shim_symlinks, find_python_shims_in, get_shims_dir_kind, ShimResolver};
+use pet_tox::TOX_DIRECTORY;
+use pet_uv::script::find_script_environment;
+use pet_venv::try_environment_from_venv_dir_in;
+use pet_virtualenv::is_virtualenv_dir_in;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
-use std::fs;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
-use std::{sync::Arc, thread};
 use tracing::{info_span, instrument};
 
-use crate::locators::identify_python_environment_using_locators;
+use crate::locators::{identify_python_environment_using_locators, TaggingReporter};
+use crate::pool::get_discovery_pool;
+
+/// Levels of sub directories of the workspace folders searched for environments, unless configured otherwise.
+pub const DEFAULT_WORKSPACE_SEARCH_DEPTH: usize = 1;
+
+/// Directories in workspace folders that are never searched for environments.
+const WORKSPACE_DIRECTORIES_TO_SKIP: [&str; 4] = [".git", "__pycache__", "node_modules", "target"];
+
+/// Directories looked at (at most) in each search root on a network (or FUSE) file system,
+/// when the `NetworkFilesystemPolicy` is `Reduce`.
+pub const NETWORK_FILESYSTEM_DIRECTORY_BUDGET: usize = 50;
+
+/// How far a workspace folder is searched for environments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkspaceSearchLimits {
+    /// Levels of sub directories searched.
+    pub depth: usize,
+    /// Maximum number of directories visited, no limit if `None`.
+    pub max_directories: Option<usize>,
+    /// Whether sub directories on other file systems (mounts) are searched.
+    pub cross_mount_boundaries: bool,
+}
+
+impl WorkspaceSearchLimits {
+    pub fn from_configuration(configuration: &Configuration) -> WorkspaceSearchLimits {
+        WorkspaceSearchLimits {
+            depth: configuration
+                .workspace_search_depth
+                .unwrap_or(DEFAULT_WORKSPACE_SEARCH_DEPTH),
+            max_directories: None,
+            cross_mount_boundaries: configuration.cross_mount_boundaries,
+        }
+    }
+}
+
+/// What is done with a search root, given the file system it is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SearchRootDecision {
+    Search,
+    Reduce(FileSystemType),
+    Skip(FileSystemType),
+}
+
+impl SearchRootDecision {
+    /// Number of directories looked at in the search root.
+    fn budget(&self) -> usize {
+        match self {
+            SearchRootDecision::Search => usize::MAX,
+            SearchRootDecision::Reduce(_) => NETWORK_FILESYSTEM_DIRECTORY_BUDGET,
+            SearchRootDecision::Skip(_) => 0,
+        }
+    }
+}
+
+/// Budget of the search roots of a phase (or locator), each root is decided on (and recorded) once.
+struct DecidedSearchRootBudget<F> {
+    decide: F,
+    budgets: Mutex<HashMap<PathBuf, usize>>,
+}
+
+impl<F: Fn(&Path) -> SearchRootDecision + Send + Sync> DecidedSearchRootBudget<F> {
+    fn new(decide: F) -> Self {
+        DecidedSearchRootBudget {
+            decide,
+            budgets: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<F: Fn(&Path) -> SearchRootDecision + Send + Sync> SearchRootBudget
+    for DecidedSearchRootBudget<F>
+{
+    fn get(&self, root: &Path) -> usize {
+        if let Some(budget) = self.budgets.lock().unwrap().get(root) {
+            return *budget;
+        }
+        let budget = (self.decide)(root).budget();
+        self.budgets
+            .lock()
+            .unwrap()
+            .insert(root.to_path_buf(), budget);
+        budget
+    }
+}
+
+fn get_search_root_decision(
+    file_system: &dyn FileSystem,
+    policy: NetworkFilesystemPolicy,
+    path: &Path,
+) -> SearchRootDecision {
+    if policy == NetworkFilesystemPolicy::Search {
+        return SearchRootDecision::Search;
+    }
+    match file_system.file_system_type(path) {
+        Some(file_system_type) if file_system_type.is_remote() => {
+            trace!(
+                "{:?} is on a {} file system, policy is {:?}",
+                path,
+                file_system_type,
+                policy
+            );
+            match policy {
+                NetworkFilesystemPolicy::Skip => SearchRootDecision::Skip(file_system_type),
+                _ => SearchRootDecision::Reduce(file_system_type),
+            }
+        }
+        _ => SearchRootDecision::Search,
+    }
+}
 
 pub struct Summary {
     pub total: Duration,
     pub locators: BTreeMap<LocatorKind, Duration>,
+    /// Time each locator waited for a thread, before searching (see `locators`).
+    pub locators_queue_wait: BTreeMap<LocatorKind, Duration>,
     pub breakdown: BTreeMap<&'static str, Duration>,
+    /// Number of directories visited while searching the workspace folders.
+    pub workspace_directories_visited: usize,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -70,8 +192,42 @@ fn report_refresh_progress(
         phase_elapsed_ms: phase_elapsed.map(|duration| duration.as_millis()),
         locator_name,
         locator_elapsed_ms,
+        file_system_type: None,
     }));
 }
+/// Records search roots that were skipped or searched with a reduced budget (without their paths).
+fn report_search_root_decision(
+    reporter: &dyn Reporter,
+    refresh_id: Option<u64>,
+    refresh_start: Instant,
+    phase: RefreshProgressPhase,
+    locator_name: Option<&str>,
+    decision: SearchRootDecision,
+) {
+    let Some(refresh_id) = refresh_id else {
+        return;
+    };
+    let (status, file_system_type) = match decision {
+        SearchRootDecision::Search => return,
+        SearchRootDecision::Reduce(file_system_type) => {
+            (RefreshProgressStatus::Reduced, file_system_type)
+        }
+        SearchRootDecision::Skip(file_system_type) => {
+            (RefreshProgressStatus::Skipped, file_system_type)
+        }
+    };
+    reporter.report_telemetry(&TelemetryEvent::RefreshProgress(RefreshProgress {
+        refresh_id,
+        phase,
+        status,
+        elapsed_ms: refresh_start.elapsed().as_millis(),
+        phase_elapsed_ms: None,
+        locator_name: locator_name.map(str::to_string),
+        locator_elapsed_ms: None,
+        file_system_type: Some(file_system_type.to_string()),
+    }));
+}
+
 #[instrument(skip(reporter, configuration, locators, environment), fields(search_scope = ?search_scope))]
 pub fn find_and_report_envs(
     reporter: &dyn Reporter,
@@ -84,14 +240,19 @@ pub fn find_and_report_envs(
     let summary = Arc::new(Mutex::new(Summary {
         total: Duration::from_secs(0),
         locators: BTreeMap::new(),
+        locators_queue_wait: BTreeMap::new(),
         breakdown: BTreeMap::new(),
+        workspace_directories_visited: 0,
     }));
     let refresh_start = Instant::now();
 
     // From settings
+    let workspace_search_limits = WorkspaceSearchLimits::from_configuration(&configuration);
     let environment_directories = configuration.environment_directories.unwrap_or_default();
     let workspace_directories = configuration.workspace_directories.unwrap_or_default();
     let executables = configuration.executables.unwrap_or_default();
+    let discovery_threads = configuration.discovery_threads;
+    let network_filesystem_policy = configuration.network_filesystem_policy.unwrap_or_default();
     let search_global = match search_scope {
         Some(SearchScope::Global(_)) => true,
         Some(SearchScope::Workspace) => false,
@@ -101,10 +262,131 @@ pub fn find_and_report_envs(
         Some(SearchScope::Global(kind)) => Some(kind),
         _ => None,
     };
+    // The same files are looked at by a number of locators, hence memoized for this refresh.
+    let file_system: Arc<dyn FileSystem> =
+        Arc::new(CachedFileSystem::new(environment.file_system()));
+    let file_system = &file_system;
+    let decide = |phase: RefreshProgressPhase, locator_name: Option<&str>, path: &Path| {
+        let decision =
+            get_search_root_decision(file_system.as_ref(), network_filesystem_policy, path);
+        report_search_root_decision(
+            reporter,
+            refresh_id,
+            refresh_start,
+            phase,
+            locator_name,
+            decision,
+        );
+        decision
+    };
+    let decide = &decide;
+
+    // Convert to Arc<[PathBuf]> for O(1) cloning in tasks
+    let environment_directories: Arc<[PathBuf]> = environment_directories.into();
+
+    // The phases are queued in this order, the work queued by a phase (e.g. each locator or
+    // workspace folder) is picked up by idle threads before the phases queued after it.
+    get_discovery_pool(discovery_threads).in_place_scope_fifo(|s| {
+        // Step 1: Find in workspace folders.
+        // Queued first, as these are the environments users care about the most,
+        // hence they should be reported before the (generally larger number of) global environments.
+        let summary_for_workspaces = summary.clone();
+        let environment_directories_for_workspaces = environment_directories.clone();
+        s.spawn_fifo(move |_| {
+            let _span = info_span!("workspace_search_phase").entered();
+            let start = Instant::now();
+            report_refresh_progress(
+                reporter,
+                refresh_id,
+                refresh_start,
+                RefreshProgressPhase::Workspaces,
+                RefreshProgressStatus::Started,
+                None,
+                None,
+            );
+            rayon::scope(|s| {
+                // Find environments in the workspace folders.
+                if !workspace_directories.is_empty() {
+                    trace!(
+                        "Searching for environments in workspace folders: {:?}",
+                        workspace_directories
+                    );
+                    // Convert to Arc<[PathBuf]> for O(1) cloning in thread spawns
+                    let global_env_search_paths: Arc<[PathBuf]> =
+                        get_search_paths_from_env_variables(environment).into();
+                    for workspace_folder in workspace_directories {
+                        let limits = match decide(RefreshProgressPhase::Workspaces, None, &workspace_folder) {
+                            SearchRootDecision::Skip(_) => continue,
+                            SearchRootDecision::Reduce(_) => WorkspaceSearchLimits {
+                                max_directories: Some(NETWORK_FILESYSTEM_DIRECTORY_BUDGET),
+                                ..workspace_search_limits
+                            },
+                            SearchRootDecision::Search => workspace_search_limits,
+                        };
+                        let global_env_search_paths = global_env_search_paths.clone();
+                        let environment_directories =
+                            environment_directories_for_workspaces.clone();
+                        let summary = summary_for_workspaces.clone();
+                        s.spawn(move |_| {
+                            let visited = find_python_environments_in_workspace_folder_recursive(
+                                &workspace_folder,
+                                reporter,
+                                locators,
+                                &global_env_search_paths,
+                                &environment_directories,
+                                limits,
+                                file_system,
+                            );
+                            summary.lock().unwrap().workspace_directories_visited += visited;
+                        });
+                    }
+                }
+                // Scripts with inline metadata (PEP 723) are reported with the environment uv runs them in.
+                let executables: Vec<PathBuf> = executables
+                    .into_iter()
+                    .filter(|executable| {
+                        match find_script_environment(environment, executable) {
+                            Some(script_env) => {
+                                reporter.report_environment(&script_env);
+                                false
+                            }
+                            None => true,
+                        }
+                    })
+                    .collect();
+                // Find the python exes provided.
+                if !executables.is_empty() {
+                    trace!("Searching for environment executables: {:?}", executables);
+                    let global_env_search_paths: Vec<PathBuf> =
+                        get_search_paths_from_env_variables(environment);
+                    identify_python_executables_using_locators(
+                        executables,
+                        locators,
+                        reporter,
+                        &global_env_search_paths,
+                        file_system,
+                    );
+                }
+            });
 
-    thread::scope(|s| {
-        // 1. Find using known global locators.
-        s.spawn(|| {
+            let elapsed = start.elapsed();
+            summary_for_workspaces
+                .lock()
+                .unwrap()
+                .breakdown
+                .insert("Workspaces", elapsed);
+            report_refresh_progress(
+                reporter,
+                refresh_id,
+                refresh_start,
+                RefreshProgressPhase::Workspaces,
+                RefreshProgressStatus::Completed,
+                Some(elapsed),
+                None,
+            );
+        });
+        // Step 2: Find using known global locators.
+        s.spawn_fifo(|_| {
             // Find in all the finders
             let _span = info_span!("locators_phase").entered();
             let start = Instant::now();
@@ -118,7 +400,7 @@ pub fn find_and_report_envs(
                 None,
             );
             if search_global {
-                thread::scope(|s| {
+                rayon::scope(|s| {
                     for locator in locators.iter() {
                         if let Some(kind) = &search_kind {
                             if !locator.supported_categories().contains(kind) {
@@ -133,7 +415,12 @@ pub fn find_and_report_envs(
 
                         let locator = locator.clone();
                         let summary = summary.clone();
-                        s.spawn(move || {
+                        let queued = Instant::now();
+                        s.spawn(move |_| {
+                            if reporter.is_cancelled() {
+                                return;
+                            }
+                            let queue_wait = queued.elapsed();
                             let locator_name = format!("{:?}", locator.get_kind());
                             let _span = info_span!("locator_find", locator = %locator_name).entered();
                             let start = Instant::now();
@@ -147,18 +434,32 @@ pub fn find_and_report_envs(
                                 Some((locator_name.clone(), None)),
                             );
                             trace!("Searching using locator: {:?}", locator.get_kind());
-                            locator.find(reporter);
+                            // Search roots of the locator (e.g. `~/.pyenv/versions`) could be on a network file system.
+                            let budget = DecidedSearchRootBudget::new(|root: &Path| {
+                                decide(RefreshProgressPhase::Locators, Some(&locator_name), root)
+                            });
+                            locator.find_with_budget(
+                                &TaggingReporter::new(
+                                    reporter,
+                                    locator.get_kind(),
+                                    locators,
+                                    file_system.clone(),
+                                ),
+                                &budget,
+                            );
                             let elapsed = start.elapsed();
                             trace!(
                                 "Completed searching using locator: {:?} in {:?}",
                                 locator.get_kind(),
                                 elapsed
                             );
-                            summary
-                                .lock()
-                                .unwrap()
-                                .locators
-                                .insert(locator.get_kind(), elapsed);
+                            {
+                                let mut summary = summary.lock().unwrap();
+                                summary.locators.insert(locator.get_kind(), elapsed);
+                                summary
+                                    .locators_queue_wait
+                                    .insert(locator.get_kind(), queue_wait);
+                            }
                             report_refresh_progress(
                                 reporter,
                                 refresh_id,
@@ -188,8 +489,8 @@ pub fn find_and_report_envs(
                 None,
             );
         });
-        // Step 2: Search in PATH variable
-        s.spawn(|| {
+        // Step 3: Search in PATH variable
+        s.spawn_fifo(|_| {
             let _span = info_span!("path_search_phase").entered();
             let start = Instant::now();
             report_refresh_progress(
@@ -209,12 +510,28 @@ pub fn find_and_report_envs(
                     "Searching for environments in global folders: {:?}",
                     global_env_search_paths
                 );
+                // Shims (pyenv, scoop, etc) are not environments, they are reported as symlinks
+                // of the environments they resolve to.
+                let (shim_dirs, search_paths): (Vec<PathBuf>, Vec<PathBuf>) =
+                    global_env_search_paths
+                        .iter()
+                        .cloned()
+                        .partition(|p| get_shims_dir_kind(p).is_some());
                 find_python_environments(
-                    &global_env_search_paths,
+                    &search_paths,
                     reporter,
                     locators,
                     false,
                     &global_env_search_paths,
+                    file_system,
+                );
+                identify_python_shims_using_locators(
+                    &shim_dirs,
+                    &ShimResolver::from(environment),
+                    locators,
+                    reporter,
+                    &global_env_search_paths,
+                    file_system,
                 );
             }
             let elapsed = start.elapsed();
@@ -229,12 +546,9 @@ pub fn find_and_report_envs(
                 None,
             );
         });
-        // Step 3: Search in some global locations for virtual envs.
-        // Convert to Arc<[PathBuf]> for O(1) cloning in thread spawns
-        let environment_directories: Arc<[PathBuf]> = environment_directories.into();
-        let environment_directories_for_step3 = environment_directories.clone();
-        let summary_for_step3 = summary.clone();
-        s.spawn(move || {
+        // Step 4: Search in some global locations for virtual envs.
+        let summary_for_global_virtualenvs = summary.clone();
+        s.spawn_fifo(move |_| {
             let _span = info_span!("global_virtualenvs_phase").entered();
             let start = Instant::now();
             report_refresh_progress(
@@ -250,30 +564,33 @@ pub fn find_and_report_envs(
                 let mut possible_environments = vec![];
 
                 // These are directories that contain environments, hence enumerate these directories.
-                for directory in environment_directories_for_step3.iter() {
-                    if let Ok(reader) = fs::read_dir(directory) {
-                        possible_environments.append(
-                            &mut reader
-                                .filter_map(Result::ok)
-                                // Use path().is_dir() instead of file_type().is_dir() to follow symlinks
-                                // See: https://github.com/microsoft/python-environment-tools/issues/196
-                                .filter(|d| d.path().is_dir())
-                                .map(|p| p.path())
-                                .collect(),
-                        );
-                    }
+                let budget = DecidedSearchRootBudget::new(|root: &Path| {
+                    decide(RefreshProgressPhase::GlobalVirtualEnvs, None, root)
+                });
+                for directory in environment_directories.iter() {
+                    // Symlinks to environments are included, see: https://github.com/microsoft/python-environment-tools/issues/196
+                    possible_environments.extend(read_search_root(
+                        file_system.as_ref(),
+                        &budget,
+                        directory,
+                    ));
                 }
 
-                let search_paths: Vec<PathBuf> = [
-                    list_global_virtual_envs_paths(
-                        environment.get_env_var("VIRTUAL_ENV".into()),
-                        environment.get_env_var("WORKON_HOME".into()),
-                        environment.get_env_var("XDG_DATA_HOME".into()),
-                        environment.get_user_home(),
-                    ),
-                    possible_environments,
-                ]
-                .concat();
+                // The global virtual envs are generally in the home directory (e.g. `~/.virtualenvs`).
+                let user_home = environment
+                    .get_user_home()
+                    .filter(|home| budget.get(home) > 0);
+                let global_virtual_envs = list_global_virtual_envs_paths_in(
+                    file_system.as_ref(),
+                    &budget,
+                    environment.get_env_var("VIRTUAL_ENV".into()),
+                    environment.get_env_var("WORKON_HOME".into()),
+                    environment.get_env_var("XDG_DATA_HOME".into()),
+                    user_home,
+                );
+
+                let search_paths: Vec<PathBuf> =
+                    [global_virtual_envs, possible_environments].concat();
                 let global_env_search_paths: Vec<PathBuf> =
                     get_search_paths_from_env_variables(environment);
 
@@ -288,10 +605,11 @@ pub fn find_and_report_envs(
                     locators,
                     false,
                     &global_env_search_paths,
+                    file_system,
                 );
             }
             let elapsed = start.elapsed();
-            summary_for_step3
+            summary_for_global_virtualenvs
                 .lock()
                 .unwrap()
                 .breakdown
@@ -306,94 +624,24 @@ pub fn find_and_report_envs(
                 None,
             );
         });
-        // Step 4: Find in workspace folders too.
-        // This can be merged with step 2 as well, as we're only look for environments
-        // in some folders.
-        // However we want step 2 to happen faster, as that list of generally much smaller.
-        // This list of folders generally map to workspace folders
-        // & users can have a lot of workspace folders and can have a large number fo files/directories
-        // that could the discovery.
-        let summary_for_step4 = summary.clone();
-        s.spawn(move || {
-            let _span = info_span!("workspace_search_phase").entered();
-            let start = Instant::now();
-            report_refresh_progress(
-                reporter,
-                refresh_id,
-                refresh_start,
-                RefreshProgressPhase::Workspaces,
-                RefreshProgressStatus::Started,
-                None,
-                None,
-            );
-            thread::scope(|s| {
-                // Find environments in the workspace folders.
-                if !workspace_directories.is_empty() {
-                    trace!(
-                        "Searching for environments in workspace folders: {:?}",
-                        workspace_directories
-                    );
-                    // Convert to Arc<[PathBuf]> for O(1) cloning in thread spawns
-                    let global_env_search_paths: Arc<[PathBuf]> =
-                        get_search_paths_from_env_variables(environment).into();
-                    for workspace_folder in workspace_directories {
-                        let global_env_search_paths = global_env_search_paths.clone();
-                        let environment_directories = environment_directories.clone();
-                        s.spawn(move || {
-                            find_python_environments_in_workspace_folder_recursive(
-                                &workspace_folder,
-                                reporter,
-                                locators,
-                                &global_env_search_paths,
-                                &environment_directories,
-                            );
-                        });
-                    }
-                }
-                // Find the python exes provided.
-                if !executables.is_empty() {
-                    trace!("Searching for environment executables: {:?}", executables);
-                    let global_env_search_paths: Vec<PathBuf> =
-                        get_search_paths_from_env_variables(environment);
-                    identify_python_executables_using_locators(
-                        executables,
-                        locators,
-                        reporter,
-                        &global_env_search_paths,
-                    );
-                }
-            });
-
-            let elapsed = start.elapsed();
-            summary_for_step4
-                .lock()
-                .unwrap()
-                .breakdown
-                .insert("Workspaces", elapsed);
-            report_refresh_progress(
-                reporter,
-                refresh_id,
-                refresh_start,
-                RefreshProgressPhase::Workspaces,
-                RefreshProgressStatus::Completed,
-                Some(elapsed),
-                None,
-            );
-        });
     });
     summary.lock().expect("summary mutex poisoned").total = refresh_start.elapsed();
 
     summary
 }
 
-#[instrument(skip(reporter, locators, global_env_search_paths, environment_directories), fields(workspace = %workspace_folder.display()))]
+/// Searches the workspace folder and `search_depth` levels of its sub directories for environments.
+/// Returns the number of directories visited.
+#[instrument(skip(reporter, locators, global_env_search_paths, environment_directories, file_system), fields(workspace = %workspace_folder.display()))]
 pub fn find_python_environments_in_workspace_folder_recursive(
     workspace_folder: &PathBuf,
     reporter: &dyn Reporter,
     locators: &Arc<Vec<Arc<dyn Locator>>>,
     global_env_search_paths: &[PathBuf],
     environment_directories: &[PathBuf],
-) {
+    limits: WorkspaceSearchLimits,
+    file_system: &Arc<dyn FileSystem>,
+) -> usize {
     // When searching in a directory, give preference to some paths.
     let mut paths_to_search_first = vec![
         // Possible this is a virtual env
@@ -406,13 +654,16 @@ pub fn find_python_environments_in_workspace_folder_recursive(
     ];
 
     // Add all subdirectories of .pixi/envs/**
-    if let Ok(reader) = fs::read_dir(workspace_folder.join(".pixi").join("envs")) {
-        reader
-            .filter_map(Result::ok)
-            // Use path().is_dir() instead of file_type().is_dir() to follow symlinks
-            .filter(|d| d.path().is_dir())
-            .map(|p| p.path())
-            .for_each(|p| paths_to_search_first.push(p));
+    if let Ok(entries) = file_system.read_dir(&workspace_folder.join(".pixi").join("envs")) {
+        // `is_dir` follows symlinks, unlike `DirEntry::file_type().is_dir()`
+        paths_to_search_first.extend(entries.into_iter().filter(|p| file_system.is_dir(p)));
+    }
+
+    // Add the tox & nox environments, `.tox/*` & `.nox/*` (these directories are not searched otherwise).
+    for directory in [TOX_DIRECTORY, NOX_DIRECTORY] {
+        if let Ok(entries) = file_system.read_dir(&workspace_folder.join(directory)) {
+            paths_to_search_first.extend(entries.into_iter().filter(|p| file_system.is_dir(p)));
+        }
     }
 
     // Possible this is an environment.
@@ -422,37 +673,192 @@ pub fn find_python_environments_in_workspace_folder_recursive(
         reporter,
         true,
         global_env_search_paths,
+        file_system,
     );
 
     // If this is a virtual env folder, no need to scan this.
-    // Note: calling is_pixi_env after is_conda_env is redundant but kept for consistency.
-    if is_virtualenv_dir(workspace_folder)
-        || is_conda_env(workspace_folder)
-        || is_pixi_env(workspace_folder)
-    {
-        return;
+    if is_environment_dir(file_system.as_ref(), workspace_folder) || limits.depth == 0 {
+        return 1;
     }
-    if let Ok(reader) = fs::read_dir(workspace_folder) {
-        for folder in reader
-            .filter_map(Result::ok)
-            // Use path().is_dir() instead of file_type().is_dir() to follow symlinks
-            .filter(|d| d.path().is_dir())
-            .map(|p| p.path())
+    let walker = WorkspaceWalker {
+        reporter,
+        locators,
+        environment_directories,
+        searched: &paths_to_search_first,
+        limits,
+        device: get_device(file_system.as_ref(), workspace_folder),
+        visited: AtomicUsize::new(1),
+        file_system,
+    };
+    walker.walk(workspace_folder, 1, &[]);
+    walker.visited.into_inner()
+}
+
+/// Identifies the file system (mount) the directory is on.
+fn get_device(file_system: &dyn FileSystem, path: &Path) -> Option<u64> {
+    file_system.metadata(path).ok()?.device
+}
+
+fn is_environment_dir(file_system: &dyn FileSystem, path: &Path) -> bool {
+    // Note: calling is_pixi_env_in after is_conda_env_in is redundant but kept for consistency.
+    is_virtualenv_dir_in(file_system, path)
+        || is_conda_env_in(file_system, path)
+        || is_pixi_env_in(file_system, path)
+}
+
+struct WorkspaceWalker<'a> {
+    reporter: &'a dyn Reporter,
+    locators: &'a Arc<Vec<Arc<dyn Locator>>>,
+    environment_directories: &'a [PathBuf],
+    /// Paths of the workspace folder that have already been searched.
+    searched: &'a [PathBuf],
+    limits: WorkspaceSearchLimits,
+    /// File system (mount) of the workspace folder.
+    device: Option<u64>,
+    visited: AtomicUsize,
+    file_system: &'a Arc<dyn FileSystem>,
+}
+
+impl WorkspaceWalker<'_> {
+    fn is_other_mount(&self, path: &Path) -> bool {
+        if self.limits.cross_mount_boundaries || self.device.is_none() {
+            return false;
+        }
+        get_device(self.file_system.as_ref(), path) != self.device
+    }
+
+    /// Searches the sub directories of `folder`, `depth` being the depth of the sub directories.
+    /// Each sub directory is searched in a separate task on the discovery pool.
+    ///
+    /// Git ignored directories are searched (environments such as `.venv` are generally ignored),
+    /// however we do not descend into them.
+    /// Same for directories on other file systems (mounts), unless configured otherwise.
+    fn walk(&self, folder: &Path, depth: usize, ignore_files: &[&Gitignore]) {
+        let Ok(entries) = self.file_system.read_dir(folder) else {
+            return;
+        };
+        let sub_directories: Vec<PathBuf> = entries
+            .into_iter()
+            // `is_dir` follows symlinks, unlike `DirEntry::file_type().is_dir()`
+            .filter(|p| self.file_system.is_dir(p))
+            .filter(|p| {
+                !WORKSPACE_DIRECTORIES_TO_SKIP
+                    .iter()
+                    .any(|name| p.ends_with(name))
+            })
             .filter(|p| {
                 // If this directory is a sub directory or is in the environment_directories, then do not search in this directory.
-                if environment_directories.contains(p) {
+                if self.environment_directories.contains(p) {
                     return true;
                 }
-                if environment_directories.iter().any(|d| p.starts_with(d)) {
+                if self
+                    .environment_directories
+                    .iter()
+                    .any(|d| p.starts_with(d))
+                {
                     return true;
                 }
                 should_search_for_environments_in_path(p)
             })
-            .filter(|p| !paths_to_search_first.contains(p))
-        {
-            find_python_environments(&[folder], reporter, locators, true, &[]);
+            .filter(|p| !self.searched.contains(p))
+            .collect();
+        if sub_directories.is_empty() {
+            return;
+        }
+
+        let ignore_file = read_ignore_files(self.file_system.as_ref(), folder);
+        let ignore_files: Vec<&Gitignore> = ignore_files
+            .iter()
+            .copied()
+            .chain(ignore_file.as_ref())
+            .collect();
+        let ignore_files = ignore_files.as_slice();
+        rayon::scope(|s| {
+            for sub_directory in sub_directories {
+                if self.reporter.is_cancelled() {
+                    break;
+                }
+                if !self.try_visit() {
+                    trace!(
+                        "Stopped searching {:?} after visiting {} directories",
+                        folder,
+                        self.visited.load(Ordering::Relaxed)
+                    );
+                    break;
+                }
+                s.spawn(move |_| {
+                    find_python_environments_in_paths_with_locators(
+                        std::slice::from_ref(&sub_directory),
+                        self.locators,
+                        self.reporter,
+                        true,
+                        &[],
+                        self.file_system,
+                    );
+                    if depth >= self.limits.depth
+                        || is_environment_dir(self.file_system.as_ref(), &sub_directory)
+                        || is_ignored(ignore_files, &sub_directory)
+                        || self.is_other_mount(&sub_directory)
+                    {
+                        return;
+                    }
+                    self.walk(&sub_directory, depth + 1, ignore_files);
+                });
+            }
+        });
+    }
+
+    /// Counts a directory as visited, unless the maximum number of directories has been visited.
+    fn try_visit(&self) -> bool {
+        self.visited
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |visited| {
+                match self.limits.max_directories {
+                    Some(max_directories) if visited >= max_directories => None,
+                    _ => Some(visited + 1),
+                }
+            })
+            .is_ok()
+    }
+}
+
+/// Rules of the `.gitignore` & `.ignore` files in the directory (the latter taking precedence).
+fn read_ignore_files(file_system: &dyn FileSystem, folder: &Path) -> Option<Gitignore> {
+    let mut builder = GitignoreBuilder::new(folder);
+    let mut found = false;
+    for file in [folder.join(".gitignore"), folder.join(".ignore")] {
+        let Ok(contents) = file_system.read_to_string(&file) else {
+            continue;
+        };
+        // Same as `GitignoreBuilder::add`, which can only read the disk.
+        for line in contents.trim_start_matches('\u{feff}').lines() {
+            if let Err(err) = builder.add_line(Some(file.clone()), line) {
+                warn!("Failed to parse ignore file {:?}: {}", file, err);
+            }
+        }
+        found = true;
+    }
+    if !found {
+        return None;
+    }
+    match builder.build() {
+        Ok(ignore_file) => Some(ignore_file),
+        Err(err) => {
+            warn!("Failed to parse ignore files in {:?}: {}", folder, err);
+            None
+        }
+    }
+}
+
+/// Whether the directory is ignored, the rules of the deepest ignore files taking precedence.
+fn is_ignored(ignore_files: &[&Gitignore], path: &Path) -> bool {
+    for ignore_file in ignore_files.iter().rev() {
+        match ignore_file.matched(path, true) {
+            Match::Ignore(_) => return true,
+            Match::Whitelist(_) => return false,
+            Match::None => continue,
         }
     }
+    false
 }
 
 fn find_python_environments(
@@ -461,21 +867,23 @@ fn find_python_environments(
     locators: &Arc<Vec<Arc<dyn Locator>>>,
     is_workspace_folder: bool,
     global_env_search_paths: &[PathBuf],
+    file_system: &Arc<dyn FileSystem>,
 ) {
     if paths.is_empty() {
         return;
     }
-    thread::scope(|s| {
+    rayon::scope(|s| {
         for item in paths {
             let locators = locators.clone();
             let item = item.clone();
-            s.spawn(move || {
+            s.spawn(move |_| {
                 find_python_environments_in_paths_with_locators(
                     &[item],
                     &locators,
                     reporter,
                     is_workspace_folder,
                     global_env_search_paths,
+                    file_system,
                 );
             });
         }
@@ -488,8 +896,12 @@ fn find_python_environments_in_paths_with_locators(
     reporter: &dyn Reporter,
     is_workspace_folder: bool,
     global_env_search_paths: &[PathBuf],
+    file_system: &Arc<dyn FileSystem>,
 ) {
     for path in paths {
+        if reporter.is_cancelled() {
+            return;
+        }
         let executables = if is_workspace_folder {
             // If we're in a workspace folder, then we only need to look for bin/python or bin/python.exe
             // As workspace folders generally have either virtual env or conda env or the like.
@@ -498,12 +910,14 @@ fn find_python_environments_in_paths_with_locators(
 
             // Paths like /Library/Frameworks/Python.framework/Versions/3.10/bin can end up in the current PATH variable.
             // Hence do not just look for files in a bin directory of the path.
-            if let Some(executable) = find_executable(path) {
+            if let Some(executable) = find_executable_in(file_system.as_ref(), path) {
                 vec![executable]
             } else {
                 // No valid executable found. Check if this is a broken venv.
                 // If so, report it with an error instead of silently skipping.
-                if let Some(broken_env) = try_environment_from_venv_dir(path) {
+                if let Some(broken_env) =
+                    try_environment_from_venv_dir_in(file_system.as_ref(), path)
+                {
                     if broken_env.error.is_some() {
                         reporter.report_environment(&broken_env);
                     }
@@ -513,7 +927,7 @@ fn find_python_environments_in_paths_with_locators(
         } else {
             // Paths like /Library/Frameworks/Python.framework/Versions/3.10/bin can end up in the current PATH variable.
             // Hence do not just look for files in a bin directory of the path.
-            find_executables(path)
+            find_executables_in(file_system.as_ref(), path)
                 .into_iter()
                 .filter(|p| {
                     // Exclude python2 on macOS
@@ -530,20 +944,25 @@ fn find_python_environments_in_paths_with_locators(
             locators,
             reporter,
             global_env_search_paths,
+            file_system,
         );
     }
 }
 
-#[instrument(skip(locators, reporter, global_env_search_paths), fields(executable_count = executables.len()))]
+#[instrument(skip(locators, reporter, global_env_search_paths, file_system), fields(executable_count = executables.len()))]
 pub fn identify_python_executables_using_locators(
     executables: Vec<PathBuf>,
     locators: &Arc<Vec<Arc<dyn Locator>>>,
     reporter: &dyn Reporter,
     global_env_search_paths: &[PathBuf],
+    file_system: &Arc<dyn FileSystem>,
 ) {
     for exe in executables.into_iter() {
+        if reporter.is_cancelled() {
+            return;
+        }
         let executable = exe.clone();
-        let env = PythonEnv::new(exe.to_owned(), None, None);
+        let env = PythonEnv::new_in(file_system.clone(), exe.to_owned(), None, None);
         if let Some(env) =
             identify_python_environment_using_locators(&env, locators, global_env_search_paths)
         {
@@ -558,12 +977,65 @@ pub fn identify_python_executables_using_locators(
     }
 }
 
+/// Identifies the environments that the Python shims in these folders resolve to,
+/// and reports the shims as symlinks of those environments.
+/// Shims that cannot be resolved without spawning are identified like any other executable.
+fn identify_python_shims_using_locators(
+    shim_dirs: &[PathBuf],
+    shim_resolver: &ShimResolver,
+    locators: &Arc<Vec<Arc<dyn Locator>>>,
+    reporter: &dyn Reporter,
+    global_env_search_paths: &[PathBuf],
+    file_system: &Arc<dyn FileSystem>,
+) {
+    for dir in shim_dirs {
+        let mut shims_by_target: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+        for shim in find_python_shims_in(file_system.as_ref(), dir) {
+            if let Some(target) = shim_resolver.resolve(&shim, None) {
+                shims_by_target.entry(target).or_default().push(shim);
+            }
+        }
+        let resolved_shims = shims_by_target
+            .values()
+            .flatten()
+            .cloned()
+            .collect::<Vec<PathBuf>>();
+
+        for (target, shims) in shims_by_target {
+            let env = PythonEnv::new_in(file_system.clone(), target, None, None);
+            if let Some(mut env) =
+                identify_python_environment_using_locators(&env, locators, global_env_search_paths)
+            {
+                add_shim_symlinks(&mut env, &shims);
+                if let Some(manager) = &env.manager {
+                    reporter.report_manager(manager);
+                }
+                reporter.report_environment(&env);
+            }
+        }
+
+        let unresolved = find_executables_in(file_system.as_ref(), dir)
+            .into_iter()
+            .filter(|exe| !resolved_shims.contains(exe))
+            .collect::<Vec<PathBuf>>();
+        identify_python_executables_using_locators(
+            unresolved,
+            locators,
+            reporter,
+            global_env_search_paths,
+            file_system,
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use pet_core::{manager::EnvManager, python_environment::PythonEnvironment};
+    use pet_fs::file_system::{os_file_system, MemoryFileSystem};
     use std::fs;
     use std::path::PathBuf;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
     use std::sync::Mutex as StdMutex;
     use tempfile::TempDir;
 
@@ -892,4 +1364,732 @@ mod tests {
                 && progress.locator_elapsed_ms.is_some()
         }));
     }
+
+    #[derive(Default)]
+    struct CollectingReporter {
+        prefixes: StdMutex<Vec<PathBuf>>,
+        environments: StdMutex<Vec<PythonEnvironment>>,
+    }
+
+    impl Reporter for CollectingReporter {
+        fn report_manager(&self, _manager: &EnvManager) {}
+        fn report_environment(&self, env: &PythonEnvironment) {
+            if let Some(prefix) = &env.prefix {
+                self.prefixes.lock().unwrap().push(prefix.clone());
+            }
+            self.environments.lock().unwrap().push(env.clone());
+        }
+        fn report_telemetry(&self, _event: &TelemetryEvent) {}
+    }
+
+    fn create_venv(prefix: &Path) {
+        let bin = prefix.join(if cfg!(windows) { "Scripts" } else { "bin" });
+        fs::create_dir_all(&bin).unwrap();
+        fs::write(prefix.join("pyvenv.cfg"), "version = 3.12.1\n").unwrap();
+        fs::write(
+            bin.join(if cfg!(windows) {
+                "python.exe"
+            } else {
+                "python"
+            }),
+            "",
+        )
+        .unwrap();
+    }
+
+    fn limits(depth: usize) -> WorkspaceSearchLimits {
+        WorkspaceSearchLimits {
+            depth,
+            max_directories: None,
+            cross_mount_boundaries: false,
+        }
+    }
+
+    fn find_in_workspace(workspace: &Path, search_depth: usize) -> (Vec<PathBuf>, usize) {
+        find_in_workspace_in(&os_file_system(), workspace, limits(search_depth))
+    }
+
+    fn find_in_workspace_in(
+        file_system: &Arc<dyn FileSystem>,
+        workspace: &Path,
+        limits: WorkspaceSearchLimits,
+    ) -> (Vec<PathBuf>, usize) {
+        let reporter = CollectingReporter::default();
+        let locators: Arc<Vec<Arc<dyn Locator>>> = Arc::new(vec![Arc::new(pet_venv::Venv::new())]);
+        let visited = find_python_environments_in_workspace_folder_recursive(
+            &workspace.to_path_buf(),
+            &reporter,
+            &locators,
+            &[],
+            &[],
+            limits,
+            file_system,
+        );
+        let mut prefixes = reporter.prefixes.lock().unwrap().clone();
+        prefixes.sort();
+        prefixes.dedup();
+        let prefixes = prefixes
+            .iter()
+            .map(|p| p.strip_prefix(workspace).unwrap().to_path_buf())
+            .collect();
+        (prefixes, visited)
+    }
+
+    #[test]
+    fn workspace_search_depth_limits_nested_environments() {
+        let tmp = TempDir::new().unwrap();
+        let workspace = tmp.path();
+        create_venv(&workspace.join(".venv"));
+        create_venv(&workspace.join("services").join("api").join(".venv"));
+
+        let (prefixes, visited) = find_in_workspace(workspace, DEFAULT_WORKSPACE_SEARCH_DEPTH);
+        assert_eq!(prefixes, vec![PathBuf::from(".venv")]);
+        // The workspace folder & `services`.
+        assert_eq!(visited, 2);
+
+        let (prefixes, visited) = find_in_workspace(workspace, 3);
+        assert_eq!(
+            prefixes,
+            vec![
+                PathBuf::from(".venv"),
+                PathBuf::from("services").join("api").join(".venv")
+            ]
+        );
+        // The workspace folder, `services`, `services/api` & `services/api/.venv`.
+        assert_eq!(visited, 4);
+    }
+
+    #[test]
+    fn workspace_search_uses_the_file_system_provided() {
+        let exe = if cfg!(windows) {
+            "python.exe"
+        } else {
+            "python"
+        };
+        let file_system: Arc<dyn FileSystem> = Arc::new(
+            MemoryFileSystem::new()
+                .file("/work/app/.venv/pyvenv.cfg", "version = 3.12.1\n")
+                .file(Path::new("/work/app/.venv/bin").join(exe), "")
+                .file("/work/services/api/venv/pyvenv.cfg", "version = 3.11.9\n")
+                .file(Path::new("/work/services/api/venv/bin").join(exe), "")
+                .dir("/work/docs"),
+        );
+
+        let (prefixes, visited) = find_in_workspace_in(&file_system, Path::new("/work"), limits(3));
+
+        assert_eq!(
+            prefixes,
+            vec![
+                PathBuf::from("app").join(".venv"),
+                PathBuf::from("services").join("api").join("venv")
+            ]
+        );
+        // The workspace folder, `app`, `app/.venv`, `docs`, `services`, `services/api` & `services/api/venv`.
+        assert_eq!(visited, 7);
+    }
+
+    #[test]
+    fn environment_directories_are_identified_in_the_file_system_provided() {
+        let file_system = MemoryFileSystem::new()
+            .dir("/work/conda/conda-meta")
+            .file("/work/pixi/conda-meta/pixi", "")
+            .dir("/work/docs");
+
+        assert!(is_environment_dir(&file_system, Path::new("/work/conda")));
+        assert!(is_environment_dir(&file_system, Path::new("/work/pixi")));
+        assert!(!is_environment_dir(&file_system, Path::new("/work/docs")));
+    }
+
+    #[test]
+    fn tox_and_nox_environments_of_the_workspace_are_found() {
+        let file_system: Arc<dyn FileSystem> = Arc::new(
+            MemoryFileSystem::new()
+                .file("/work/tox.ini", "[tox]\n")
+                .file("/work/noxfile.py", "")
+                .file("/work/.tox/py311/pyvenv.cfg", "version = 3.11.4\n")
+                .file(Path::new("/work/.tox/py311/bin").join(python_exe()), "")
+                .file("/work/.nox/tests-3-12/pyvenv.cfg", "version = 3.12.1\n")
+                .file(
+                    Path::new("/work/.nox/tests-3-12/bin").join(python_exe()),
+                    "",
+                ),
+        );
+        let reporter = CollectingReporter::default();
+        let locators: Arc<Vec<Arc<dyn Locator>>> = Arc::new(vec![
+            Arc::new(pet_tox::Tox::new()),
+            Arc::new(pet_nox::Nox::new()),
+            Arc::new(pet_venv::Venv::new()),
+        ]);
+
+        find_python_environments_in_workspace_folder_recursive(
+            &PathBuf::from("/work"),
+            &reporter,
+            &locators,
+            &[],
+            &[],
+            limits(DEFAULT_WORKSPACE_SEARCH_DEPTH),
+            &file_system,
+        );
+
+        let mut environments: Vec<_> = reporter
+            .environments
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|env| (env.kind, env.name.clone(), env.project.clone()))
+            .collect();
+        environments.sort();
+        environments.dedup();
+        assert_eq!(
+            environments,
+            vec![
+                (
+                    Some(PythonEnvironmentKind::Nox),
+                    Some("tests-3-12".to_string()),
+                    Some(PathBuf::from("/work"))
+                ),
+                (
+                    Some(PythonEnvironmentKind::Tox),
+                    Some("py311".to_string()),
+                    Some(PathBuf::from("/work"))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn workspace_search_skips_ignored_directories_and_stops_at_environments() {
+        let tmp = TempDir::new().unwrap();
+        let workspace = tmp.path();
+        fs::write(workspace.join(".gitignore"), ".venv\ndist/\nenvs/\n").unwrap();
+        fs::write(workspace.join(".ignore"), "!envs/\n").unwrap();
+        // Environments are generally ignored, these are still found.
+        create_venv(&workspace.join("app").join(".venv"));
+        // Not searched, as `dist` is ignored.
+        create_venv(&workspace.join("dist").join("app").join(".venv"));
+        // Whitelisted by `.ignore`.
+        create_venv(&workspace.join("envs").join("tools").join(".venv"));
+        // Never searched.
+        create_venv(&workspace.join("node_modules").join("pkg").join(".venv"));
+        create_venv(&workspace.join("target").join(".venv"));
+        // Not searched, as the walk stops at environments.
+        create_venv(&workspace.join("app").join(".venv").join("nested"));
+
+        let (prefixes, visited) = find_in_workspace(workspace, 5);
+
+        assert_eq!(
+            prefixes,
+            vec![
+                PathBuf::from("app").join(".venv"),
+                PathBuf::from("envs").join("tools").join(".venv")
+            ]
+        );
+        // The workspace folder, `app`, `app/.venv`, `dist`, `envs`, `envs/tools` & `envs/tools/.venv`.
+        assert_eq!(visited, 7);
+    }
+
+    /// Records the `find` calls of the locators, & the environments reported.
+    #[derive(Default)]
+    struct Recorder {
+        cancelled: AtomicBool,
+        events: StdMutex<Vec<&'static str>>,
+        running: AtomicUsize,
+        max_running: AtomicUsize,
+    }
+
+    struct RecordingLocator(Arc<Recorder>);
+
+    impl Locator for RecordingLocator {
+        fn get_kind(&self) -> LocatorKind {
+            LocatorKind::Conda
+        }
+        fn supported_categories(&self) -> Vec<PythonEnvironmentKind> {
+            vec![PythonEnvironmentKind::Conda]
+        }
+        fn try_from(&self, _env: &PythonEnv) -> Option<PythonEnvironment> {
+            None
+        }
+        fn find(&self, _reporter: &dyn Reporter) {
+            let running = self.0.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.0.max_running.fetch_max(running, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(20));
+            self.0.running.fetch_sub(1, Ordering::SeqCst);
+            self.0.events.lock().unwrap().push("find");
+        }
+    }
+
+    impl Reporter for Recorder {
+        fn report_manager(&self, _manager: &EnvManager) {}
+        fn report_environment(&self, _env: &PythonEnvironment) {
+            self.events.lock().unwrap().push("environment");
+        }
+        fn report_telemetry(&self, _event: &TelemetryEvent) {}
+        fn is_cancelled(&self) -> bool {
+            self.cancelled.load(Ordering::SeqCst)
+        }
+    }
+
+    fn configuration_with_threads(threads: usize) -> Configuration {
+        Configuration {
+            discovery_threads: Some(threads),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn discovery_is_limited_to_the_threads_configured() {
+        let recorder = Arc::new(Recorder::default());
+        let locators: Arc<Vec<Arc<dyn Locator>>> = Arc::new(
+            (0..8)
+                .map(|_| Arc::new(RecordingLocator(recorder.clone())) as Arc<dyn Locator>)
+                .collect(),
+        );
+
+        find_and_report_envs(
+            recorder.as_ref(),
+            configuration_with_threads(2),
+            &locators,
+            &EmptyEnvironment,
+            None,
+            None,
+        );
+
+        assert_eq!(recorder.events.lock().unwrap().len(), 8);
+        assert!(recorder.max_running.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[test]
+    fn workspace_environments_are_reported_before_global_locators_search() {
+        let tmp = TempDir::new().unwrap();
+        create_venv(&tmp.path().join(".venv"));
+        let recorder = Arc::new(Recorder::default());
+        let locators: Arc<Vec<Arc<dyn Locator>>> = Arc::new(vec![
+            Arc::new(pet_venv::Venv::new()),
+            Arc::new(RecordingLocator(recorder.clone())),
+        ]);
+        let configuration = Configuration {
+            workspace_directories: Some(vec![tmp.path().to_path_buf()]),
+            ..configuration_with_threads(1)
+        };
+
+        let summary = find_and_report_envs(
+            recorder.as_ref(),
+            configuration,
+            &locators,
+            &EmptyEnvironment,
+            None,
+            None,
+        );
+
+        assert_eq!(
+            *recorder.events.lock().unwrap(),
+            vec!["environment", "find"]
+        );
+        let summary = summary.lock().unwrap();
+        assert!(summary.locators.contains_key(&LocatorKind::Conda));
+        assert!(summary
+            .locators_queue_wait
+            .contains_key(&LocatorKind::Conda));
+    }
+
+    #[test]
+    fn nothing_is_searched_once_the_reporter_is_cancelled() {
+        let tmp = TempDir::new().unwrap();
+        create_venv(&tmp.path().join(".venv"));
+        let recorder = Arc::new(Recorder::default());
+        recorder.cancelled.store(true, Ordering::SeqCst);
+        let locators: Arc<Vec<Arc<dyn Locator>>> = Arc::new(vec![
+            Arc::new(pet_venv::Venv::new()),
+            Arc::new(RecordingLocator(recorder.clone())),
+        ]);
+        let configuration = Configuration {
+            workspace_directories: Some(vec![tmp.path().to_path_buf()]),
+            ..configuration_with_threads(1)
+        };
+
+        find_and_report_envs(
+            recorder.as_ref(),
+            configuration,
+            &locators,
+            &EmptyEnvironment,
+            None,
+            None,
+        );
+
+        assert!(recorder.events.lock().unwrap().is_empty());
+    }
+
+    fn python_exe() -> &'static str {
+        if cfg!(windows) {
+            "python.exe"
+        } else {
+            "python"
+        }
+    }
+
+    /// Workspace with an environment in `app` & another in `remote`, a FUSE file system.
+    fn workspace_with_a_mount() -> Arc<dyn FileSystem> {
+        Arc::new(
+            MemoryFileSystem::new()
+                .file("/work/app/.venv/pyvenv.cfg", "version = 3.12.1\n")
+                .file(Path::new("/work/app/.venv/bin").join(python_exe()), "")
+                .mount_point("/work/remote", FileSystemType::Fuse)
+                .file("/work/remote/api/.venv/pyvenv.cfg", "version = 3.11.9\n")
+                .file(
+                    Path::new("/work/remote/api/.venv/bin").join(python_exe()),
+                    "",
+                ),
+        )
+    }
+
+    #[test]
+    fn workspace_search_does_not_cross_mount_boundaries() {
+        let file_system = workspace_with_a_mount();
+
+        let (prefixes, visited) = find_in_workspace_in(&file_system, Path::new("/work"), limits(3));
+        assert_eq!(prefixes, vec![PathBuf::from("app").join(".venv")]);
+        // The workspace folder, `app`, `app/.venv` & `remote` (not searched any further).
+        assert_eq!(visited, 4);
+
+        let (prefixes, _) = find_in_workspace_in(
+            &file_system,
+            Path::new("/work"),
+            WorkspaceSearchLimits {
+                cross_mount_boundaries: true,
+                ..limits(3)
+            },
+        );
+        assert_eq!(
+            prefixes,
+            vec![
+                PathBuf::from("app").join(".venv"),
+                PathBuf::from("remote").join("api").join(".venv")
+            ]
+        );
+    }
+
+    #[test]
+    fn workspace_search_stops_at_the_maximum_number_of_directories() {
+        let file_system: Arc<dyn FileSystem> = Arc::new(
+            MemoryFileSystem::new()
+                .dir("/work/a/b")
+                .dir("/work/c/d")
+                .dir("/work/e"),
+        );
+
+        let (_, visited) = find_in_workspace_in(
+            &file_system,
+            Path::new("/work"),
+            WorkspaceSearchLimits {
+                max_directories: Some(3),
+                ..limits(3)
+            },
+        );
+
+        assert_eq!(visited, 3);
+    }
+
+    #[test]
+    fn workspace_search_reads_the_ignore_files_in_the_file_system() {
+        let file_system: Arc<dyn FileSystem> = Arc::new(
+            MemoryFileSystem::new()
+                .file("/work/.gitignore", "dist/\n")
+                .file("/work/app/.venv/pyvenv.cfg", "version = 3.12.1\n")
+                .file(Path::new("/work/app/.venv/bin").join(python_exe()), "")
+                .file("/work/dist/app/.venv/pyvenv.cfg", "version = 3.12.1\n")
+                .file(Path::new("/work/dist/app/.venv/bin").join(python_exe()), ""),
+        );
+
+        let (prefixes, visited) = find_in_workspace_in(&file_system, Path::new("/work"), limits(3));
+
+        assert_eq!(prefixes, vec![PathBuf::from("app").join(".venv")]);
+        // The workspace folder, `app`, `app/.venv` & `dist` (not searched any further).
+        assert_eq!(visited, 4);
+    }
+
+    /// Counts the environments being identified at the same time.
+    #[derive(Default)]
+    struct ConcurrencyLocator {
+        running: AtomicUsize,
+        max_running: AtomicUsize,
+    }
+
+    impl Locator for ConcurrencyLocator {
+        fn get_kind(&self) -> LocatorKind {
+            LocatorKind::Venv
+        }
+        fn supported_categories(&self) -> Vec<PythonEnvironmentKind> {
+            vec![PythonEnvironmentKind::Venv]
+        }
+        fn try_from(&self, _env: &PythonEnv) -> Option<PythonEnvironment> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(running, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(50));
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            None
+        }
+        fn find(&self, _reporter: &dyn Reporter) {}
+    }
+
+    #[test]
+    fn workspace_sub_directories_are_searched_in_parallel() {
+        let exe = if cfg!(windows) {
+            "Scripts/python.exe"
+        } else {
+            "bin/python"
+        };
+        let file_system: Arc<dyn FileSystem> = Arc::new(
+            ["a", "b", "c", "d"]
+                .iter()
+                .fold(MemoryFileSystem::new(), |file_system, name| {
+                    file_system.file(Path::new("/work").join(name).join(exe), "")
+                }),
+        );
+        let locator = Arc::new(ConcurrencyLocator::default());
+        let locators: Arc<Vec<Arc<dyn Locator>>> = Arc::new(vec![locator.clone()]);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+
+        let visited = pool.install(|| {
+            find_python_environments_in_workspace_folder_recursive(
+                &PathBuf::from("/work"),
+                &CollectingReporter::default(),
+                &locators,
+                &[],
+                &[],
+                limits(1),
+                &file_system,
+            )
+        });
+
+        assert_eq!(visited, 5);
+        assert!(locator.max_running.load(Ordering::SeqCst) > 1);
+    }
+
+    struct FileSystemEnvironment(Arc<dyn FileSystem>);
+
+    impl Environment for FileSystemEnvironment {
+        fn get_user_home(&self) -> Option<PathBuf> {
+            None
+        }
+        fn get_root(&self) -> Option<PathBuf> {
+            None
+        }
+        fn get_env_var(&self, _key: String) -> Option<String> {
+            None
+        }
+        fn get_know_global_search_locations(&self) -> Vec<PathBuf> {
+            Vec::new()
+        }
+        fn file_system(&self) -> Arc<dyn FileSystem> {
+            self.0.clone()
+        }
+    }
+
+    #[derive(Default)]
+    struct EnvironmentsAndProgressReporter {
+        progress: ProgressReporter,
+        environments: CollectingReporter,
+    }
+
+    impl Reporter for EnvironmentsAndProgressReporter {
+        fn report_manager(&self, _manager: &EnvManager) {}
+        fn report_environment(&self, env: &PythonEnvironment) {
+            self.environments.report_environment(env);
+        }
+        fn report_telemetry(&self, event: &TelemetryEvent) {
+            self.progress.report_telemetry(event);
+        }
+    }
+
+    #[test]
+    fn workspace_folders_on_network_file_systems_are_skipped_or_reduced() {
+        let environment = FileSystemEnvironment(workspace_with_a_mount());
+        let locators: Arc<Vec<Arc<dyn Locator>>> = Arc::new(vec![Arc::new(pet_venv::Venv::new())]);
+
+        for (policy, expected_status, expected_prefixes) in [
+            (NetworkFilesystemPolicy::Search, None, 1),
+            (
+                NetworkFilesystemPolicy::Reduce,
+                Some(RefreshProgressStatus::Reduced),
+                1,
+            ),
+            (
+                NetworkFilesystemPolicy::Skip,
+                Some(RefreshProgressStatus::Skipped),
+                0,
+            ),
+        ] {
+            let reporter = EnvironmentsAndProgressReporter::default();
+            let configuration = Configuration {
+                workspace_directories: Some(vec![PathBuf::from("/work/remote")]),
+                workspace_search_depth: Some(2),
+                network_filesystem_policy: Some(policy),
+                ..Default::default()
+            };
+
+            find_and_report_envs(
+                &reporter,
+                configuration,
+                &locators,
+                &environment,
+                Some(SearchScope::Workspace),
+                Some(1),
+            );
+
+            let decisions = reporter
+                .progress
+                .events
+                .lock()
+                .unwrap()
+                .iter()
+                .filter_map(|event| match event {
+                    TelemetryEvent::RefreshProgress(progress)
+                        if progress.file_system_type.is_some() =>
+                    {
+                        Some((
+                            progress.phase,
+                            progress.status,
+                            progress.file_system_type.clone(),
+                        ))
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                decisions,
+                expected_status
+                    .map(|status| (
+                        RefreshProgressPhase::Workspaces,
+                        status,
+                        Some("fuse".to_string())
+                    ))
+                    .into_iter()
+                    .collect::<Vec<_>>(),
+                "{policy:?}"
+            );
+            let mut prefixes = reporter.environments.prefixes.lock().unwrap().clone();
+            prefixes.dedup();
+            assert_eq!(prefixes.len(), expected_prefixes, "{policy:?}");
+        }
+    }
+
+    /// Counts the directories looked at in its search root, like `~/.pyenv/versions`.
+    struct SearchRootLocator {
+        file_system: Arc<dyn FileSystem>,
+        looked_at: AtomicUsize,
+    }
+
+    impl Locator for SearchRootLocator {
+        fn get_kind(&self) -> LocatorKind {
+            LocatorKind::PyEnv
+        }
+        fn supported_categories(&self) -> Vec<PythonEnvironmentKind> {
+            vec![PythonEnvironmentKind::Pyenv]
+        }
+        fn try_from(&self, _env: &PythonEnv) -> Option<PythonEnvironment> {
+            None
+        }
+        fn find(&self, reporter: &dyn Reporter) {
+            self.find_with_budget(reporter, &pet_core::search_root::Unlimited);
+        }
+        fn find_with_budget(&self, _reporter: &dyn Reporter, budget: &dyn SearchRootBudget) {
+            let versions = read_search_root(
+                self.file_system.as_ref(),
+                budget,
+                Path::new("/home/user/.pyenv/versions"),
+            );
+            self.looked_at.store(versions.len(), Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn locator_search_roots_on_network_file_systems_are_skipped_or_reduced() {
+        let file_system: Arc<dyn FileSystem> =
+            Arc::new((0..NETWORK_FILESYSTEM_DIRECTORY_BUDGET + 10).fold(
+                MemoryFileSystem::new().mount_point("/home/user", FileSystemType::Nfs),
+                |file_system, index| {
+                    file_system.dir(format!("/home/user/.pyenv/versions/3.{index}.0"))
+                },
+            ));
+        let environment = FileSystemEnvironment(file_system.clone());
+
+        for (policy, expected_status, expected_looked_at) in [
+            (
+                NetworkFilesystemPolicy::Search,
+                None,
+                NETWORK_FILESYSTEM_DIRECTORY_BUDGET + 10,
+            ),
+            (
+                NetworkFilesystemPolicy::Reduce,
+                Some(RefreshProgressStatus::Reduced),
+                NETWORK_FILESYSTEM_DIRECTORY_BUDGET,
+            ),
+            (
+                NetworkFilesystemPolicy::Skip,
+                Some(RefreshProgressStatus::Skipped),
+                0,
+            ),
+        ] {
+            let reporter = ProgressReporter::default();
+            let locator = Arc::new(SearchRootLocator {
+                file_system: file_system.clone(),
+                looked_at: AtomicUsize::new(usize::MAX),
+            });
+            let locators: Arc<Vec<Arc<dyn Locator>>> = Arc::new(vec![locator.clone()]);
+            let configuration = Configuration {
+                network_filesystem_policy: Some(policy),
+                ..Default::default()
+            };
+
+            find_and_report_envs(
+                &reporter,
+                configuration,
+                &locators,
+                &environment,
+                Some(SearchScope::Global(PythonEnvironmentKind::Pyenv)),
+                Some(1),
+            );
+
+            let decisions = reporter
+                .events
+                .lock()
+                .unwrap()
+                .iter()
+                .filter_map(|event| match event {
+                    TelemetryEvent::RefreshProgress(progress)
+                        if progress.file_system_type.is_some() =>
+                    {
+                        Some((
+                            progress.phase,
+                            progress.status,
+                            progress.locator_name.clone(),
+                            progress.file_system_type.clone(),
+                        ))
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                decisions,
+                expected_status
+                    .map(|status| (
+                        RefreshProgressPhase::Locators,
+                        status,
+                        Some("PyEnv".to_string()),
+                        Some("nfs".to_string())
+                    ))
+                    .into_iter()
+                    .collect::<Vec<_>>(),
+                "{policy:?}"
+            );
+            assert_eq!(
+                locator.looked_at.load(Ordering::SeqCst),
+                expected_looked_at,
+                "{policy:?}"
+            );
+        }
+    }
 }
@@ -0,0 +1,662 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! High level API for embedding the Python environment discovery in other Rust tools.
+//!
+//! ```no_run
+//! use pet::finder::Finder;
+//! use pet_core::python_environment::PythonEnvironmentKind;
+//!
+//! let environments = Finder::new()
+//!     .workspace("/home/user/projects/app")
+//!     .kinds([PythonEnvironmentKind::Venv, PythonEnvironmentKind::Conda])
+//!     .cache_dir("/tmp/pet-cache")
+//!     .find();
+//! for environment in environments {
+//!     println!("{:?} {:?}", environment.kind, environment.executable);
+//! }
+//! ```
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use log::trace;
+use pet_conda::{Conda, CondaLocator};
+use pet_core::{
+    manager::EnvManager,
+    os_environment::Environment,
+    python_environment::{PythonEnvironment, PythonEnvironmentKind},
+    reporter::Reporter,
+    telemetry::TelemetryEvent,
+    Configuration, ExternalLocatorConfig, LocatorKind, NetworkFilesystemPolicy,
+};
+use pet_fs::{
+    file_system::{ConfiguredFileSystem, FileSystem},
+    glob::expand_glob_patterns,
+};
+use pet_poetry::{Poetry, PoetryLocator};
+use pet_reporter::cache::CacheReporter;
+
+use crate::{
+    find::{find_and_report_envs, SearchScope},
+    locators::{create_locators, TaggingReporter},
+    resolve::resolve_environment,
+    root::{create_environment, create_reporter, to_image_environment},
+};
+
+/// Builder used to discover and resolve Python environments.
+///
+/// All of the wiring (locators, configuration, reporters) is created by the `Finder`,
+/// each call to `find`, `find_iter` or `resolve` starts from a clean state.
+#[derive(Debug, Clone, Default)]
+pub struct Finder {
+    workspaces: Vec<PathBuf>,
+    workspace_search_depth: Option<usize>,
+    environment_directories: Vec<PathBuf>,
+    kinds: Vec<PythonEnvironmentKind>,
+    cache_directory: Option<PathBuf>,
+    resolve_timeout: Option<Duration>,
+    conda_executable: Option<PathBuf>,
+    pipenv_executable: Option<PathBuf>,
+    poetry_executable: Option<PathBuf>,
+    external_locators: Vec<ExternalLocatorConfig>,
+    workspace_only: bool,
+    report_missing: bool,
+    root_directory: Option<PathBuf>,
+    discovery_threads: Option<usize>,
+    network_filesystem_policy: Option<NetworkFilesystemPolicy>,
+    cross_mount_boundaries: bool,
+}
+
+impl Finder {
+    pub fn new() -> Finder {
+        Finder::default()
+    }
+
+    /// Adds a workspace folder (or a Python executable) to search for environments.
+    /// Glob patterns are supported (e.g. `/home/user/projects/*`).
+    pub fn workspace(mut self, path: impl Into<PathBuf>) -> Finder {
+        self.workspaces.push(path.into());
+        self
+    }
+
+    /// Adds a number of workspace folders, see `workspace`.
+    pub fn workspaces<I, P>(mut self, paths: I) -> Finder
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.workspaces.extend(paths.into_iter().map(Into::into));
+        self
+    }
+
+    /// Levels of sub directories of the workspace folders searched for environments,
+    /// e.g. `3` finds `services/api/.venv`. Defaults to `1`.
+    pub fn workspace_search_depth(mut self, depth: usize) -> Finder {
+        self.workspace_search_depth = Some(depth);
+        self
+    }
+
+    /// Adds a folder that contains environments (e.g. a custom `WORKON_HOME`).
+    /// Glob patterns are supported.
+    pub fn environment_directory(mut self, path: impl Into<PathBuf>) -> Finder {
+        self.environment_directories.push(path.into());
+        self
+    }
+
+    /// Only return environments of these kinds.
+    /// By default environments of all kinds are returned.
+    pub fn kinds<I>(mut self, kinds: I) -> Finder
+    where
+        I: IntoIterator<Item = PythonEnvironmentKind>,
+    {
+        for kind in kinds {
+            if !self.kinds.contains(&kind) {
+                self.kinds.push(kind);
+            }
+        }
+        self
+    }
+
+    /// Directory used to cache the details of environments between runs.
+    pub fn cache_dir(mut self, path: impl Into<PathBuf>) -> Finder {
+        self.cache_directory = Some(path.into());
+        self
+    }
+
+    /// Maximum time allowed for a spawned Python to report its details, defaults to 15 seconds.
+    pub fn resolve_timeout(mut self, timeout: Duration) -> Finder {
+        self.resolve_timeout = Some(timeout);
+        self
+    }
+
+    pub fn conda_executable(mut self, path: impl Into<PathBuf>) -> Finder {
+        self.conda_executable = Some(path.into());
+        self
+    }
+
+    pub fn pipenv_executable(mut self, path: impl Into<PathBuf>) -> Finder {
+        self.pipenv_executable = Some(path.into());
+        self
+    }
+
+    pub fn poetry_executable(mut self, path: impl Into<PathBuf>) -> Finder {
+        self.poetry_executable = Some(path.into());
+        self
+    }
+
+    /// Adds an executable that locates additional environments (plugin), see `pet_external`.
+    /// `timeout` applies to each request sent to the plugin.
+    pub fn external_locator(
+        mut self,
+        executable: impl Into<PathBuf>,
+        timeout: Option<Duration>,
+    ) -> Finder {
+        self.external_locators.push(ExternalLocatorConfig {
+            executable: executable.into(),
+            timeout,
+        });
+        self
+    }
+
+    /// Only search the workspace folders, skipping global locations such as `PATH`.
+    pub fn workspace_only(mut self, workspace_only: bool) -> Finder {
+        self.workspace_only = workspace_only;
+        self
+    }
+
+    /// Spawn conda & poetry after the search to find environments that could not be found
+    /// by looking at the file system alone. Off by default, as this is slow.
+    pub fn report_missing(mut self, report_missing: bool) -> Finder {
+        self.report_missing = report_missing;
+        self
+    }
+
+    /// Searches this directory instead of `/`, e.g. an unpacked container image.
+    /// Workspace folders are then paths within the image, as are the paths of the environments found.
+    ///
+    /// Note: Python is never spawned when searching an alternate root directory.
+    pub fn root_directory(mut self, path: impl Into<PathBuf>) -> Finder {
+        self.root_directory = Some(path.into());
+        self
+    }
+
+    /// Number of threads searching for environments, defaults to the number of CPUs (at least 4).
+    ///
+    /// Note: The threads are shared by all finders, a different number of threads replaces them.
+    pub fn discovery_threads(mut self, threads: usize) -> Finder {
+        self.discovery_threads = Some(threads);
+        self
+    }
+
+    /// What to do with workspace folders & directories containing environments (including those
+    /// searched by the locators) on network (or FUSE) file systems. Defaults to `Reduce`.
+    pub fn network_filesystem_policy(mut self, policy: NetworkFilesystemPolicy) -> Finder {
+        self.network_filesystem_policy = Some(policy);
+        self
+    }
+
+    /// Search the sub directories of workspace folders that are on other file systems (mounts),
+    /// these are not searched by default.
+    pub fn cross_mount_boundaries(mut self, cross_mount_boundaries: bool) -> Finder {
+        self.cross_mount_boundaries = cross_mount_boundaries;
+        self
+    }
+
+    /// Finds all of the environments, blocking until the search completes.
+    pub fn find(&self) -> Vec<PythonEnvironment> {
+        self.find_iter().collect()
+    }
+
+    /// Starts the search on a background thread and returns the environments as they are found.
+    /// Dropping the iterator stops the search once the next environment is found.
+    ///
+    /// ```no_run
+    /// use pet::finder::Finder;
+    ///
+    /// for environment in Finder::new().workspace(".").find_iter() {
+    ///     println!("{:?}", environment.executable);
+    /// }
+    /// ```
+    pub fn find_iter(&self) -> FindIter {
+        let (sender, receiver) = mpsc::channel();
+        let finder = self.clone();
+        thread::spawn(move || finder.find_and_send(sender));
+        FindIter { receiver }
+    }
+
+    /// Resolves the environment of a Python executable (or environment folder).
+    /// This could spawn the Python executable to get accurate information.
+    pub fn resolve(&self, executable: impl AsRef<Path>) -> Option<PythonEnvironment> {
+        let environment = self.create_environment();
+        let environment = environment.as_ref();
+        let conda_locator = Arc::new(Conda::from(environment));
+        let poetry_locator = Arc::new(Poetry::from(environment));
+        let locators = create_locators(conda_locator, poetry_locator, environment);
+        let config = self.create_config(environment);
+        for locator in locators.iter() {
+            locator.configure(&config);
+        }
+
+        // The executable is a path of the image when searching an alternate root directory.
+        let file_system = environment.file_system();
+        let executable = file_system.to_host_path(executable.as_ref());
+        let result = resolve_environment(&executable, &locators, environment).ok()?;
+        let env = result.resolved.unwrap_or(result.discovered);
+        Some(to_image_environment(file_system.as_ref(), &env))
+    }
+
+    fn find_and_send(self, sender: Sender<PythonEnvironment>) {
+        let reporter = ChannelReporter {
+            sender: Mutex::new(sender),
+            cancelled: AtomicBool::new(false),
+            finder: self.clone(),
+        };
+        self.find_and_report(Arc::new(reporter), None);
+    }
+
+    /// Runs the search, reporting each environment & manager once.
+    /// The search stops early once the reporter is cancelled (see `Reporter::is_cancelled`).
+    pub(crate) fn find_and_report(&self, reporter: Arc<dyn Reporter>, refresh_id: Option<u64>) {
+        let environment = self.create_environment();
+        let environment = environment.as_ref();
+        let conda_locator = Arc::new(Conda::from(environment));
+        let poetry_locator = Arc::new(Poetry::from(environment));
+        let locators = create_locators(conda_locator.clone(), poetry_locator.clone(), environment);
+        let config = self.create_config(environment);
+        for locator in locators.iter() {
+            locator.configure(&config);
+        }
+
+        let reporter = CacheReporter::new(create_reporter(reporter, environment));
+        find_and_report_envs(
+            &reporter,
+            config,
+            &locators,
+            environment,
+            self.search_scope(),
+            refresh_id,
+        );
+        if self.report_missing && !reporter.is_cancelled() {
+            let _ = conda_locator.find_and_report_missing_envs(
+                &TaggingReporter::new(
+                    &reporter,
+                    LocatorKind::Conda,
+                    &locators,
+                    environment.file_system(),
+                ),
+                self.conda_executable.clone(),
+            );
+            let _ = poetry_locator.find_and_report_missing_envs(
+                &TaggingReporter::new(
+                    &reporter,
+                    LocatorKind::Poetry,
+                    &locators,
+                    environment.file_system(),
+                ),
+                self.poetry_executable.clone(),
+            );
+        }
+        trace!("Finder completed");
+    }
+
+    /// Whether the environment is of one of the kinds requested using `kinds`.
+    pub(crate) fn is_requested_kind(&self, env: &PythonEnvironment) -> bool {
+        self.kinds.is_empty() || env.kind.is_some_and(|kind| self.kinds.contains(&kind))
+    }
+
+    fn search_scope(&self) -> Option<SearchScope> {
+        if self.workspace_only {
+            Some(SearchScope::Workspace)
+        } else if let [kind] = self.kinds.as_slice() {
+            Some(SearchScope::Global(*kind))
+        } else {
+            None
+        }
+    }
+
+    /// Environment searched by this finder, with Python spawned using the settings of the finder.
+    fn create_environment(&self) -> Arc<dyn Environment> {
+        let environment = create_environment(self.root_directory.as_deref());
+        let file_system = Arc::new(ConfiguredFileSystem::new(
+            environment.file_system(),
+            self.cache_directory.clone(),
+            self.resolve_timeout,
+        ));
+        Arc::new(FinderEnvironment {
+            environment,
+            file_system,
+        })
+    }
+
+    fn create_config(&self, environment: &dyn Environment) -> Configuration {
+        // Workspaces are paths of the image when searching an alternate root directory.
+        let file_system = environment.file_system();
+        let workspaces: Vec<PathBuf> = self
+            .workspaces
+            .iter()
+            .map(|workspace| file_system.to_host_path(workspace))
+            .collect();
+        let mut search_paths = expand_glob_patterns(&workspaces);
+        search_paths.sort();
+        search_paths.dedup();
+
+        Configuration {
+            workspace_directories: Some(
+                search_paths
+                    .iter()
+                    .filter(|p| p.is_dir())
+                    .cloned()
+                    .collect(),
+            ),
+            workspace_search_depth: self.workspace_search_depth,
+            executables: Some(
+                search_paths
+                    .iter()
+                    .filter(|p| p.is_file())
+                    .cloned()
+                    .collect(),
+            ),
+            environment_directories: Some(
+                expand_glob_patterns(&self.environment_directories)
+                    .into_iter()
+                    .filter(|p| p.is_dir())
+                    .collect(),
+            ),
+            conda_executable: self.conda_executable.clone(),
+            pipenv_executable: self.pipenv_executable.clone(),
+            poetry_executable: self.poetry_executable.clone(),
+            cache_directory: self.cache_directory.clone(),
+            external_locators: Some(self.external_locators.clone()),
+            resolve_timeout: self.resolve_timeout,
+            root_directory: self.root_directory.clone(),
+            discovery_threads: self.discovery_threads,
+            network_filesystem_policy: self.network_filesystem_policy,
+            cross_mount_boundaries: self.cross_mount_boundaries,
+        }
+    }
+}
+
+/// Environments found by `Finder::find_iter`, in the order they are discovered.
+/// The iterator ends once the search has completed.
+#[derive(Debug)]
+pub struct FindIter {
+    receiver: Receiver<PythonEnvironment>,
+}
+
+impl Iterator for FindIter {
+    type Item = PythonEnvironment;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+struct FinderEnvironment {
+    environment: Arc<dyn Environment>,
+    file_system: Arc<dyn FileSystem>,
+}
+
+impl Environment for FinderEnvironment {
+    fn get_user_home(&self) -> Option<PathBuf> {
+        self.environment.get_user_home()
+    }
+    fn get_root(&self) -> Option<PathBuf> {
+        self.environment.get_root()
+    }
+    fn get_env_var(&self, key: String) -> Option<String> {
+        self.environment.get_env_var(key)
+    }
+    fn get_know_global_search_locations(&self) -> Vec<PathBuf> {
+        self.environment.get_know_global_search_locations()
+    }
+    fn file_system(&self) -> Arc<dyn FileSystem> {
+        self.file_system.clone()
+    }
+}
+
+struct ChannelReporter {
+    sender: Mutex<Sender<PythonEnvironment>>,
+    /// Set once the receiver has been dropped, i.e. the iterator is no longer used.
+    cancelled: AtomicBool,
+    finder: Finder,
+}
+
+impl Reporter for ChannelReporter {
+    fn report_telemetry(&self, _event: &TelemetryEvent) {
+        //
+    }
+    fn report_manager(&self, _manager: &EnvManager) {
+        // Managers are available on the environments.
+    }
+    fn report_environment(&self, env: &PythonEnvironment) {
+        if !self.finder.is_requested_kind(env) {
+            return;
+        }
+        if self.cancelled.load(Ordering::Acquire) {
+            return;
+        }
+        // The receiver could have been dropped, in which case there's no one to report to.
+        if self
+            .sender
+            .lock()
+            .expect("sender mutex poisoned")
+            .send(env.clone())
+            .is_err()
+        {
+            self.cancelled.store(true, Ordering::Release);
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pet_core::os_environment::EnvironmentApi;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn create_venv(dir: &Path) -> PathBuf {
+        let bin = dir.join(if cfg!(windows) { "Scripts" } else { "bin" });
+        fs::create_dir_all(&bin).unwrap();
+        fs::write(
+            dir.join("pyvenv.cfg"),
+            "home = /usr/bin\nversion = 3.12.1\n",
+        )
+        .unwrap();
+        let exe = bin.join(if cfg!(windows) {
+            "python.exe"
+        } else {
+            "python"
+        });
+        fs::write(&exe, "").unwrap();
+        exe
+    }
+
+    #[test]
+    fn workspace_folders_and_executables_are_split_in_config() {
+        let workspace = TempDir::new().unwrap();
+        let exe = create_venv(&workspace.path().join(".venv"));
+
+        let config = Finder::new()
+            .workspace(workspace.path())
+            .workspace(&exe)
+            .workspace(workspace.path().join("missing"))
+            .conda_executable("/usr/bin/conda")
+            .create_config(&EnvironmentApi::new());
+
+        assert_eq!(
+            config.workspace_directories,
+            Some(vec![workspace.path().to_path_buf()])
+        );
+        assert_eq!(config.executables, Some(vec![exe]));
+        assert_eq!(
+            config.conda_executable,
+            Some(PathBuf::from("/usr/bin/conda"))
+        );
+    }
+
+    #[test]
+    fn single_kind_limits_the_search_scope() {
+        assert!(Finder::new().search_scope().is_none());
+        assert!(matches!(
+            Finder::new()
+                .kinds([PythonEnvironmentKind::Venv, PythonEnvironmentKind::Venv])
+                .search_scope(),
+            Some(SearchScope::Global(PythonEnvironmentKind::Venv))
+        ));
+        assert!(Finder::new()
+            .kinds([PythonEnvironmentKind::Venv, PythonEnvironmentKind::Conda])
+            .search_scope()
+            .is_none());
+        assert!(matches!(
+            Finder::new()
+                .kinds([PythonEnvironmentKind::Venv])
+                .workspace_only(true)
+                .search_scope(),
+            Some(SearchScope::Workspace)
+        ));
+    }
+
+    #[test]
+    fn channel_reporter_filters_by_kind() {
+        let (sender, receiver) = mpsc::channel();
+        let reporter = ChannelReporter {
+            sender: Mutex::new(sender),
+            cancelled: AtomicBool::new(false),
+            finder: Finder::new().kinds([PythonEnvironmentKind::Venv]),
+        };
+        let venv = PythonEnvironment {
+            kind: Some(PythonEnvironmentKind::Venv),
+            ..Default::default()
+        };
+
+        reporter.report_environment(&PythonEnvironment {
+            kind: Some(PythonEnvironmentKind::Conda),
+            ..Default::default()
+        });
+        reporter.report_environment(&PythonEnvironment::default());
+        reporter.report_environment(&venv);
+        drop(reporter);
+
+        assert_eq!(receiver.iter().collect::<Vec<_>>(), vec![venv]);
+    }
+
+    #[test]
+    fn channel_reporter_is_cancelled_once_the_receiver_is_dropped() {
+        let (sender, receiver) = mpsc::channel();
+        let reporter = ChannelReporter {
+            sender: Mutex::new(sender),
+            cancelled: AtomicBool::new(false),
+            finder: Finder::new(),
+        };
+        reporter.report_environment(&PythonEnvironment::default());
+        assert!(!reporter.is_cancelled());
+
+        drop(receiver);
+        reporter.report_environment(&PythonEnvironment::default());
+
+        assert!(reporter.is_cancelled());
+    }
+
+    #[test]
+    fn resolve_searches_the_root_directory() {
+        let root = TempDir::new().unwrap();
+        create_venv(&root.path().join("home").join("user").join(".venv"));
+        let executable = Path::new("/home/user/.venv")
+            .join(if cfg!(windows) { "Scripts" } else { "bin" })
+            .join(if cfg!(windows) {
+                "python.exe"
+            } else {
+                "python"
+            });
+
+        let env = Finder::new()
+            .root_directory(root.path())
+            .resolve(&executable)
+            .unwrap();
+
+        assert_eq!(env.executable, Some(executable));
+        assert_eq!(env.prefix, Some(PathBuf::from("/home/user/.venv")));
+    }
+
+    #[cfg(unix)]
+    fn create_python(venv: &Path, script: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let exe = create_venv(venv);
+        fs::write(&exe, format!("#!/bin/sh\n{script}\n")).unwrap();
+        fs::set_permissions(&exe, fs::Permissions::from_mode(0o755)).unwrap();
+        exe
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolve_uses_the_cache_directory_of_the_finder() {
+        let workspace = TempDir::new().unwrap();
+        let cache = TempDir::new().unwrap();
+        let venv = workspace.path().join(".venv");
+        let exe = create_python(
+            &venv,
+            &format!(
+                "echo 093385e9-59f7-4a16-a604-14bf206256fe\n\
+                echo '{{\"version\": \"3.12.1\", \"sys_prefix\": \"{}\", \"executable\": \"{}\", \"is64_bit\": true}}'",
+                venv.display(),
+                venv.join("bin").join("python").display()
+            ),
+        );
+
+        let env = Finder::new().cache_dir(cache.path()).resolve(&exe).unwrap();
+
+        assert_eq!(env.version, Some("3.12.1".parse().unwrap()));
+        assert_eq!(fs::read_dir(cache.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolve_uses_the_timeout_of_the_finder() {
+        let workspace = TempDir::new().unwrap();
+        let exe = create_python(&workspace.path().join(".venv"), "sleep 5");
+
+        let start = std::time::Instant::now();
+        let env = Finder::new()
+            .resolve_timeout(Duration::from_millis(200))
+            .resolve(&exe)
+            .unwrap();
+
+        assert!(start.elapsed() < Duration::from_secs(4));
+        assert!(env.error.unwrap().starts_with("timeout"));
+    }
+
+    #[test]
+    fn find_returns_environments_in_the_workspace() {
+        let workspace = TempDir::new().unwrap();
+        create_venv(&workspace.path().join(".venv"));
+
+        let environments = Finder::new()
+            .workspace(workspace.path())
+            .workspace_only(true)
+            .find();
+
+        let prefixes = environments
+            .iter()
+            .filter_map(|env| env.prefix.clone())
+            .collect::<Vec<_>>();
+        assert!(
+            prefixes.iter().any(|prefix| prefix.ends_with(".venv")),
+            "{environments:?}"
+        );
+    }
+}
@@ -2,58 +2,88 @@
 // Licensed under the MIT License.
 
 use crate::find::find_and_report_envs;
-use crate::find::find_python_environments_in_workspace_folder_recursive;
 use crate::find::identify_python_executables_using_locators;
 use crate::find::SearchScope;
-use crate::locators::create_locators;
+use crate::find::{find_python_environments_in_workspace_folder_recursive, WorkspaceSearchLimits};
+use crate::locators::{create_locators, TaggingReporter};
 use log::{error, info, trace, warn};
 use pet::initialize_tracing;
-use pet::resolve::resolve_environment;
+use pet::lineage::{build_environment_graph, EnvironmentGraphNode, LineageReporter};
+use pet::resolve::{
+    for_each_with_concurrency, get_resolve_concurrency, group_executables, resolve_environment,
+};
+use pet::root;
+use pet::trace::{finish_trace_file, set_trace_file, TraceFileFormat};
 use pet_conda::Conda;
 use pet_conda::CondaLocator;
+use pet_core::manager::EnvManager;
+use pet_core::platform_tags::PlatformTags;
 use pet_core::python_environment::PythonEnvironment;
 use pet_core::python_environment::PythonEnvironmentKind;
+use pet_core::python_version::PythonVersion;
 use pet_core::telemetry::refresh_performance::RefreshPerformance;
 use pet_core::telemetry::TelemetryEvent;
 use pet_core::{
-    os_environment::{Environment, EnvironmentApi},
+    os_environment::{Environment, EnvironmentApi, RootedEnvironment},
     reporter::Reporter,
-    Configuration, Locator, RefreshStatePersistence, RefreshStateSyncScope,
+    Configuration, ExternalLocatorConfig, Locator, LocatorKind, NetworkFilesystemPolicy,
+    RefreshStatePersistence, RefreshStateSyncScope,
 };
 use pet_env_var_path::get_search_paths_from_env_variables;
+use pet_fs::file_system::{CachedFileSystem, FileSystem};
 use pet_fs::glob::{expand_glob_pattern, expand_glob_patterns, is_recursive_glob_pattern};
 use pet_fs::path::norm_case;
 use pet_jsonrpc::{
+    daemon::is_valid_token,
     send_error, send_reply,
-    server::{start_server, HandlersKeyedByMethodName},
+    server::{serve_connection, start_server, HandlersKeyedByMethodName},
+    transport::{spawn_with_connection, start_listener, with_connection, Connection, Listener},
+    watchdog::{start_parent_process_watchdog, PARENT_PROCESS_POLL_INTERVAL},
+    ErrorCode, ErrorData,
 };
 use pet_poetry::Poetry;
 use pet_poetry::PoetryLocator;
 use pet_python_utils::cache::clear_cache;
 use pet_python_utils::cache::set_cache_directory;
-use pet_reporter::collect;
-use pet_reporter::{cache::CacheReporter, jsonrpc};
+use pet_python_utils::env::set_spawn_timeout;
+use pet_python_utils::env::ResolvedPythonEnv;
+use pet_python_utils::env::{SpawnFailure, SpawnFailureKind};
+use pet_python_utils::platform_tags::get_platform_tags_in;
+use pet_reporter::collect::{self, CollectReporter};
+use pet_reporter::{cache::CacheReporter, environment::get_environment_key, jsonrpc};
 use pet_telemetry::report_inaccuracies_identified_after_resolving;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use serde_json::{self, Value};
-use std::collections::BTreeMap;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::Duration;
 use std::{
     ops::Deref,
     panic::{self, AssertUnwindSafe},
-    path::PathBuf,
-    sync::{Arc, Condvar, Mutex, RwLock},
-    thread,
+    path::{Path, PathBuf},
+    sync::{Arc, Condvar, Mutex, PoisonError, RwLock},
     time::{Instant, SystemTime},
 };
 use tracing::info_span;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 struct ConfigurationState {
     generation: u64,
     config: Configuration,
+    /// Generation reporting missing envs, or one of the `MISSING_ENVS_*` states.
+    /// Kept per configuration so that each client of a daemon reports its own missing envs.
+    missing_envs_reporting: Arc<AtomicU64>,
+}
+
+impl Default for ConfigurationState {
+    fn default() -> Self {
+        Self {
+            generation: 0,
+            config: Configuration::default(),
+            missing_envs_reporting: Arc::new(AtomicU64::new(MISSING_ENVS_AVAILABLE)),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -282,10 +312,326 @@ impl Drop for RefreshSafetyGuard<'_> {
     }
 }
 
+/// Clients of a daemon with the same configuration & refresh options discover the same
+/// environments, hence they share a single refresh.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SharedRefreshKey {
+    options: RefreshOptions,
+    config: Configuration,
+}
+
+#[derive(Clone)]
+struct SharedRefreshOutcome {
+    result: RefreshResult,
+    perf: RefreshPerformance,
+    locators: RefreshLocators,
+}
+
+#[derive(Default)]
+struct SharedRefreshState {
+    managers: Vec<EnvManager>,
+    environments: Vec<PythonEnvironment>,
+    subscribers: Vec<Arc<dyn Reporter>>,
+    /// Set once the refresh has completed, `Some(None)` if it failed.
+    outcome: Option<Option<SharedRefreshOutcome>>,
+}
+
+struct SharedRefresh {
+    key: SharedRefreshKey,
+    state: Mutex<SharedRefreshState>,
+    completed: Condvar,
+}
+
+impl SharedRefresh {
+    /// Waits for the refresh to complete, `None` if it failed.
+    fn wait_for_outcome(&self) -> Option<SharedRefreshOutcome> {
+        let state = self.state.lock().expect("shared refresh mutex poisoned");
+        let state = self
+            .completed
+            .wait_while(state, |state| state.outcome.is_none())
+            .expect("shared refresh mutex poisoned");
+        state.outcome.clone().flatten()
+    }
+}
+
+/// Requests (other than refreshes) being handled on their own threads, waited for on shutdown.
+#[derive(Debug, Default)]
+struct InFlightRequests {
+    count: Mutex<usize>,
+    changed: Condvar,
+}
+
+/// Marks a request as in flight until dropped.
+struct InFlightRequest(Arc<InFlightRequests>);
+
+impl InFlightRequests {
+    fn start(self: &Arc<Self>) -> InFlightRequest {
+        *self
+            .count
+            .lock()
+            .expect("in flight requests mutex poisoned") += 1;
+        InFlightRequest(self.clone())
+    }
+
+    fn wait_until_idle(&self) {
+        let count = self
+            .count
+            .lock()
+            .expect("in flight requests mutex poisoned");
+        let _guard = self
+            .changed
+            .wait_while(count, |count| *count > 0)
+            .expect("in flight requests condvar poisoned");
+    }
+}
+
+impl Drop for InFlightRequest {
+    fn drop(&mut self) {
+        // Decremented even when the request panicked, hence not `expect`.
+        let mut count = self.0.count.lock().unwrap_or_else(PoisonError::into_inner);
+        *count -= 1;
+        self.0.changed.notify_all();
+    }
+}
+
+/// State shared by all clients of a daemon.
+#[derive(Default)]
+pub struct DaemonState {
+    /// Sent by clients in `initialize`, before any other request.
+    token: String,
+    refreshes: SharedRefreshes,
+    settings: ClientSettings,
+}
+
+/// Configure options that apply to the whole process, hence to every client of a daemon.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ProcessSettings {
+    resolve_timeout: Option<u64>,
+    /// Kept until configured with another directory.
+    cache_directory: Option<PathBuf>,
+    /// Kept until configured with another file.
+    trace_file: Option<PathBuf>,
+}
+
+impl ProcessSettings {
+    fn from_configure_options(options: &ConfigureOptions, previous: Option<&Self>) -> Self {
+        ProcessSettings {
+            resolve_timeout: options.resolve_timeout,
+            cache_directory: options
+                .cache_directory
+                .clone()
+                .or_else(|| previous.and_then(|p| p.cache_directory.clone())),
+            trace_file: options
+                .trace_file
+                .clone()
+                .or_else(|| previous.and_then(|p| p.trace_file.clone())),
+        }
+    }
+
+    /// Why these settings cannot be used while another client uses `other`, if they cannot.
+    fn get_conflict(&self, other: &Self) -> Option<&'static str> {
+        let differ =
+            |a: &Option<PathBuf>, b: &Option<PathBuf>| a.is_some() && b.is_some() && a != b;
+        if self.resolve_timeout != other.resolve_timeout {
+            Some("resolveTimeout")
+        } else if differ(&self.cache_directory, &other.cache_directory) {
+            Some("cacheDirectory")
+        } else if differ(&self.trace_file, &other.trace_file) {
+            Some("traceFile")
+        } else {
+            None
+        }
+    }
+}
+
+/// Process settings of each client connected to a daemon, keyed by the id of its connection.
+#[derive(Debug, Default)]
+struct ClientSettings {
+    clients: Mutex<HashMap<u64, ProcessSettings>>,
+}
+
+impl ClientSettings {
+    /// Records the settings of the client, unless they conflict with those of another connected client.
+    fn configure(&self, client: u64, options: &ConfigureOptions) -> Result<(), String> {
+        let mut clients = self.clients.lock().expect("client settings mutex poisoned");
+        let settings = ProcessSettings::from_configure_options(options, clients.get(&client));
+        for (other, other_settings) in clients.iter().filter(|(other, _)| **other != client) {
+            if let Some(option) = settings.get_conflict(other_settings) {
+                return Err(format!(
+                    "{option} applies to the whole daemon and differs from the one of client {other}"
+                ));
+            }
+        }
+        clients.insert(client, settings);
+        Ok(())
+    }
+
+    fn disconnected(&self, client: u64) {
+        self.clients
+            .lock()
+            .expect("client settings mutex poisoned")
+            .remove(&client);
+    }
+}
+
+/// Refreshes in progress across all clients of a daemon.
+#[derive(Default)]
+pub struct SharedRefreshes {
+    in_progress: Mutex<Vec<Arc<SharedRefresh>>>,
+}
+
+enum SharedRefreshRegistration<'a> {
+    /// The caller runs the refresh, and must complete it.
+    Lead(SharedRefreshLead<'a>),
+    /// Another client is running the refresh, wait for its outcome.
+    Follow(Arc<SharedRefresh>),
+}
+
+impl SharedRefreshes {
+    /// Joins the refresh in progress with the same key, else starts a new one.
+    /// Everything found by the refresh is reported to `reporter`, including what was found before joining.
+    fn register(
+        &self,
+        key: SharedRefreshKey,
+        reporter: Arc<dyn Reporter>,
+    ) -> SharedRefreshRegistration<'_> {
+        let mut in_progress = self
+            .in_progress
+            .lock()
+            .expect("shared refreshes mutex poisoned");
+        if let Some(refresh) = in_progress.iter().find(|refresh| refresh.key == key) {
+            let refresh = refresh.clone();
+            drop(in_progress);
+
+            let mut state = refresh.state.lock().expect("shared refresh mutex poisoned");
+            for manager in state.managers.iter() {
+                reporter.report_manager(manager);
+            }
+            for environment in state.environments.iter() {
+                reporter.report_environment(environment);
+            }
+            if state.outcome.is_none() {
+                state.subscribers.push(reporter);
+            }
+            drop(state);
+            return SharedRefreshRegistration::Follow(refresh);
+        }
+
+        let refresh = Arc::new(SharedRefresh {
+            key,
+            state: Mutex::new(SharedRefreshState {
+                subscribers: vec![reporter],
+                ..Default::default()
+            }),
+            completed: Condvar::new(),
+        });
+        in_progress.push(refresh.clone());
+        SharedRefreshRegistration::Lead(SharedRefreshLead {
+            refreshes: self,
+            refresh,
+            finished: false,
+        })
+    }
+}
+
+/// Completes the shared refresh, as failed if dropped before `complete` (e.g. the refresh panicked),
+/// in which case the followers run their own refresh.
+struct SharedRefreshLead<'a> {
+    refreshes: &'a SharedRefreshes,
+    refresh: Arc<SharedRefresh>,
+    finished: bool,
+}
+
+impl SharedRefreshLead<'_> {
+    /// Reporter of the refresh, forwards everything found to the subscribed clients.
+    fn reporter(&self) -> Arc<dyn Reporter> {
+        Arc::new(SharedRefreshReporter {
+            refresh: self.refresh.clone(),
+        })
+    }
+
+    fn complete(mut self, outcome: SharedRefreshOutcome) {
+        self.finish(Some(outcome));
+    }
+
+    fn finish(&mut self, outcome: Option<SharedRefreshOutcome>) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        self.refreshes
+            .in_progress
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|refresh| !Arc::ptr_eq(refresh, &self.refresh));
+
+        let mut state = self
+            .refresh
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        state.outcome = Some(outcome);
+        state.subscribers.clear();
+        drop(state);
+        self.refresh.completed.notify_all();
+    }
+}
+
+impl Drop for SharedRefreshLead<'_> {
+    fn drop(&mut self) {
+        self.finish(None);
+    }
+}
+
+struct SharedRefreshReporter {
+    refresh: Arc<SharedRefresh>,
+}
+
+impl Reporter for SharedRefreshReporter {
+    fn report_manager(&self, manager: &EnvManager) {
+        // Forwarded while holding the lock, so that clients joining later get each item exactly once.
+        let mut state = self
+            .refresh
+            .state
+            .lock()
+            .expect("shared refresh mutex poisoned");
+        state.managers.push(manager.clone());
+        for subscriber in state.subscribers.iter() {
+            subscriber.report_manager(manager);
+        }
+    }
+
+    fn report_environment(&self, env: &PythonEnvironment) {
+        let mut state = self
+            .refresh
+            .state
+            .lock()
+            .expect("shared refresh mutex poisoned");
+        state.environments.push(env.clone());
+        for subscriber in state.subscribers.iter() {
+            subscriber.report_environment(env);
+        }
+    }
+
+    fn report_telemetry(&self, event: &TelemetryEvent) {
+        let state = self
+            .refresh
+            .state
+            .lock()
+            .expect("shared refresh mutex poisoned");
+        for subscriber in state.subscribers.iter() {
+            subscriber.report_telemetry(event);
+        }
+    }
+}
+
+#[derive(Clone)]
 struct RefreshLocators {
     locators: Arc<Vec<Arc<dyn Locator>>>,
     conda_locator: Arc<Conda>,
     poetry_locator: Arc<Poetry>,
+    /// File system the locators search in.
+    file_system: Arc<dyn FileSystem>,
 }
 
 struct RefreshExecution {
@@ -294,10 +640,14 @@ struct RefreshExecution {
     reporter: Arc<CacheReporter>,
     configuration: Arc<RwLock<ConfigurationState>>,
     refresh_generation: u64,
+    locators: Arc<Vec<Arc<dyn Locator>>>,
     conda_locator: Arc<Conda>,
     poetry_locator: Arc<Poetry>,
+    file_system: Arc<dyn FileSystem>,
     conda_executable: Option<PathBuf>,
     poetry_executable: Option<PathBuf>,
+    /// Everything found by an unfiltered refresh, kept as the last refresh again once the follow-ups complete.
+    snapshot_reporter: Option<Arc<CollectReporter>>,
 }
 
 struct RefreshCompletionGuard<'a> {
@@ -348,7 +698,12 @@ fn send_refresh_replies_for_waiters(
 
 fn send_refresh_errors_for_waiters(completion_guard: &RefreshCompletionGuard<'_>, message: &str) {
     for request_id in completion_guard.drain_request_ids() {
-        send_error(Some(request_id), -4, message.to_string());
+        send_error(
+            Some(request_id),
+            ErrorCode::InternalError,
+            message.to_string(),
+            None,
+        );
     }
 }
 
@@ -390,6 +745,48 @@ where
     Ok(())
 }
 
+/// What the last completed refresh found, used to answer `getEnvironments` & the like without searching again.
+struct RefreshSnapshot {
+    /// Generation of the configuration the refresh ran with, snapshots of older generations are not used.
+    generation: u64,
+    refresh_id: u64,
+    completed_at: SystemTime,
+    environments: Vec<PythonEnvironment>,
+    managers: Vec<EnvManager>,
+}
+
+impl RefreshSnapshot {
+    /// Milliseconds since the Unix epoch.
+    fn completed_at_millis(&self) -> u128 {
+        self.completed_at
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis()
+    }
+}
+
+/// Reports to `reporter`, keeping everything reported for the refresh snapshot.
+struct RefreshSnapshotReporter {
+    reporter: Arc<dyn Reporter>,
+    collect_reporter: Arc<CollectReporter>,
+}
+
+impl Reporter for RefreshSnapshotReporter {
+    fn report_manager(&self, manager: &EnvManager) {
+        self.collect_reporter.report_manager(manager);
+        self.reporter.report_manager(manager);
+    }
+
+    fn report_environment(&self, env: &PythonEnvironment) {
+        self.collect_reporter.report_environment(env);
+        self.reporter.report_environment(env);
+    }
+
+    fn report_telemetry(&self, event: &TelemetryEvent) {
+        self.reporter.report_telemetry(event);
+    }
+}
+
 struct GenerationGuardedReporter {
     reporter: Arc<dyn Reporter>,
     configuration: Arc<RwLock<ConfigurationState>>,
@@ -426,7 +823,7 @@ impl GenerationGuardedReporter {
 }
 
 impl Reporter for GenerationGuardedReporter {
-    fn report_manager(&self, manager: &pet_core::manager::EnvManager) {
+    fn report_manager(&self, manager: &EnvManager) {
         self.report_if_current(
             |reporter| reporter.report_manager(manager),
             || {
@@ -480,12 +877,57 @@ pub struct Context {
     conda_locator: Arc<Conda>,
     os_environment: Arc<dyn Environment>,
     refresh_coordinator: RefreshCoordinator,
+    in_flight_requests: Arc<InFlightRequests>,
+    /// Set once the parent process of `initialize` is being watched.
+    watching_parent_process: AtomicBool,
+    /// Set once a client of the daemon sent the token of the daemon in `initialize`.
+    authenticated: AtomicBool,
+    /// Set by the `shutdown` request, after which only the `exit` notification is handled.
+    shutdown_requested: AtomicBool,
+    mode: ServerMode,
+    /// Last completed refresh, see `getEnvironments`.
+    last_refresh: Mutex<Option<Arc<RefreshSnapshot>>>,
 }
 
+/// How clients are connected to the server.
+pub enum ServerMode {
+    /// A single client, over stdin & stdout (`pet server`).
+    Stdio,
+    /// Any number of clients over a socket (`pet daemon`), each with its own context.
+    /// Refreshes in progress are shared by clients with the same configuration.
+    Daemon(Arc<DaemonState>),
+}
+
+type RequestHandler = fn(Arc<Context>, u32, Value);
+type NotificationHandler = fn(Arc<Context>, Value);
+
+/// Requests handled by the server, these are also reported to the client in the reply to `initialize`.
+const REQUEST_HANDLERS: [(&str, RequestHandler); 15] = [
+    ("initialize", handle_initialize),
+    ("shutdown", handle_shutdown),
+    ("info", handle_info),
+    ("configure", handle_configure),
+    ("refresh", handle_refresh),
+    ("getEnvironments", handle_get_environments),
+    ("getEnvironment", handle_get_environment),
+    ("getManagers", handle_get_managers),
+    ("resolve", handle_resolve),
+    ("resolveMany", handle_resolve_many),
+    ("find", handle_find),
+    ("platformTags", handle_platform_tags),
+    ("environmentGraph", handle_environment_graph),
+    ("condaInfo", handle_conda_telemetry),
+    ("clear", handle_clear_cache),
+];
+
+const NOTIFICATION_HANDLERS: [(&str, NotificationHandler); 1] = [("exit", handle_exit)];
+
+/// Version of the protocol, bumped when requests or notifications change in a way clients need to know about.
+const PROTOCOL_VERSION: &str = "1.0";
+
 const MISSING_ENVS_AVAILABLE: u64 = u64::MAX;
 const MISSING_ENVS_COMPLETED: u64 = u64::MAX - 1;
 
-static MISSING_ENVS_REPORTING_STATE: AtomicU64 = AtomicU64::new(MISSING_ENVS_AVAILABLE);
 static NEXT_REFRESH_ID: AtomicU64 = AtomicU64::new(1);
 
 pub fn start_jsonrpc_server() {
@@ -493,29 +935,279 @@ pub fn start_jsonrpc_server() {
     // Note: This includes log compatibility, so we don't call jsonrpc::initialize_logger
     initialize_tracing(false);
 
-    // These are globals for the the lifetime of the server.
+    start_server(&create_handlers(ServerMode::Stdio))
+}
+
+/// Serves the clients connecting to the listener, until the listener fails.
+/// Clients must send `token` (written to `token_file`) in `initialize` before any other request.
+pub fn start_jsonrpc_daemon(listener: Listener, token: String, token_file: &Path) {
+    initialize_tracing(false);
+
+    // Clients started before the daemon (e.g. `--port 0`) need the address to connect to,
+    // followed by the file to read the token from.
+    println!("{}", listener.address());
+    println!("{}", token_file.display());
+    info!(
+        "Daemon listening on {}, with the token in {:?}",
+        listener.address(),
+        token_file
+    );
+    let daemon = Arc::new(DaemonState {
+        token,
+        ..Default::default()
+    });
+    let result = start_listener(listener, move |reader, connection| {
+        info!("Client {} connected", connection.id());
+        // Each client gets its own context, hence its own configuration.
+        let handlers = create_handlers(ServerMode::Daemon(daemon.clone()));
+        serve_connection(&handlers, reader, &connection);
+        daemon.settings.disconnected(connection.id());
+        info!("Client {} disconnected", connection.id());
+    });
+    if let Err(err) = result {
+        error!("Daemon stopped: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn create_context(mode: ServerMode) -> Context {
+    // These are globals for the the lifetime of the client.
     // Hence passed around as Arcs via the context.
     let environment = EnvironmentApi::new();
     let conda_locator = Arc::new(Conda::from(&environment));
     let poetry_locator = Arc::new(Poetry::from(&environment));
-    let context = Context {
+    Context {
         locators: create_locators(conda_locator.clone(), poetry_locator.clone(), &environment),
         conda_locator,
         configuration: Arc::new(RwLock::new(ConfigurationState::default())),
         configure_in_progress: Arc::new(Mutex::new(())),
         os_environment: Arc::new(environment),
         refresh_coordinator: RefreshCoordinator::default(),
+        in_flight_requests: Arc::default(),
+        watching_parent_process: AtomicBool::new(false),
+        authenticated: AtomicBool::new(false),
+        shutdown_requested: AtomicBool::new(false),
+        mode,
+        last_refresh: Mutex::new(None),
+    }
+}
+
+/// Clients of `pet server` are trusted, clients of the daemon need to send its token.
+fn is_authenticated(context: &Context) -> bool {
+    match context.mode {
+        ServerMode::Stdio => true,
+        ServerMode::Daemon(_) => context.authenticated.load(Ordering::SeqCst),
+    }
+}
+
+fn create_handlers(mode: ServerMode) -> HandlersKeyedByMethodName<Context> {
+    let mut handlers = HandlersKeyedByMethodName::new(Arc::new(create_context(mode)));
+    for (method, handler) in REQUEST_HANDLERS {
+        handlers.add_request_handler(method, move |context, id, params| {
+            if context.shutdown_requested.load(Ordering::SeqCst) {
+                send_error(
+                    Some(id),
+                    ErrorCode::InvalidRequest,
+                    format!("Server is shutting down, cannot handle {method}"),
+                    None,
+                );
+                return;
+            }
+            if method != "initialize" && !is_authenticated(&context) {
+                send_error(
+                    Some(id),
+                    ErrorCode::InvalidRequest,
+                    format!("The token of the daemon must be sent in initialize before {method}"),
+                    None,
+                );
+                return;
+            }
+            handler(context, id, params)
+        });
+    }
+    for (method, handler) in NOTIFICATION_HANDLERS {
+        handlers.add_notification_handler(method, handler);
+    }
+    handlers
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeParams {
+    /// Id of the process that started the server (generally the editor).
+    /// The server exits once this process is no longer running.
+    pub parent_process_id: Option<u32>,
+    /// Token of the daemon, read from its token file. Required by the daemon, ignored otherwise.
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeResult {
+    pub protocol_version: &'static str,
+    pub server_info: InfoResponse,
+    /// Requests handled by the server.
+    pub requests: Vec<&'static str>,
+    /// Notifications handled by the server.
+    pub notifications: Vec<&'static str>,
+    pub features: ServerFeatures,
+}
+
+/// Optional features, clients should check these before relying on them.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerFeatures {
+    /// Whether changes to environments are reported without a `refresh`.
+    pub watch: bool,
+    /// Whether requests can be cancelled.
+    pub cancellation: bool,
+    /// Whether the packages installed in environments can be listed.
+    pub packages: bool,
+}
+
+impl InitializeResult {
+    fn current() -> Self {
+        InitializeResult {
+            protocol_version: PROTOCOL_VERSION,
+            server_info: InfoResponse::current(),
+            requests: REQUEST_HANDLERS.iter().map(|(method, _)| *method).collect(),
+            notifications: NOTIFICATION_HANDLERS
+                .iter()
+                .map(|(method, _)| *method)
+                .collect(),
+            features: ServerFeatures::default(),
+        }
+    }
+}
+
+pub fn handle_initialize(context: Arc<Context>, id: u32, params: Value) {
+    // All of the params are optional, hence `null` is the same as `{}`.
+    let initialize_params = if params.is_null() {
+        Ok(InitializeParams::default())
+    } else {
+        serde_json::from_value::<InitializeParams>(params.clone())
     };
+    match initialize_params {
+        Ok(initialize_params) => {
+            if let ServerMode::Daemon(daemon) = &context.mode {
+                let token = initialize_params.token.as_deref().unwrap_or_default();
+                if !is_valid_token(&daemon.token, token) {
+                    warn!(
+                        "Client {} sent an invalid token",
+                        Connection::current().id()
+                    );
+                    send_error(
+                        Some(id),
+                        ErrorCode::InvalidParams,
+                        "Invalid token, the token is in the token file of the daemon".to_string(),
+                        None,
+                    );
+                    Connection::current().close();
+                    return;
+                }
+                context.authenticated.store(true, Ordering::SeqCst);
+            }
+            match (initialize_params.parent_process_id, &context.mode) {
+                (Some(parent_process_id), ServerMode::Stdio) => {
+                    if context.watching_parent_process.swap(true, Ordering::SeqCst) {
+                        info!(
+                            "Already watching the parent process, ignoring {}",
+                            parent_process_id
+                        );
+                    } else {
+                        info!("Watching parent process {}", parent_process_id);
+                        let context = context.clone();
+                        start_parent_process_watchdog(
+                            parent_process_id,
+                            PARENT_PROCESS_POLL_INTERVAL,
+                            move || {
+                                context.shutdown_requested.store(true, Ordering::SeqCst);
+                                wait_for_requests_in_progress(&context);
+                                finish_trace_file();
+                            },
+                        );
+                    }
+                }
+                (Some(parent_process_id), ServerMode::Daemon(_)) => {
+                    // The daemon outlives its clients, clients disconnect instead.
+                    info!(
+                        "Ignoring parent process {} of a daemon client",
+                        parent_process_id
+                    );
+                }
+                (None, _) => {}
+            }
+            send_reply(id, Some(InitializeResult::current()));
+        }
+        Err(e) => {
+            error!("Failed to parse initialize {params:?}: {e}");
+            send_error(
+                Some(id),
+                ErrorCode::InvalidParams,
+                format!("Failed to parse initialize {params:?}: {e}"),
+                Some(ErrorData::new(None, e)),
+            );
+        }
+    }
+}
+
+/// Stops handling new requests & replies once the requests in progress have completed.
+pub fn handle_shutdown(context: Arc<Context>, id: u32, _params: Value) {
+    context.shutdown_requested.store(true, Ordering::SeqCst);
+    spawn_with_connection(move || {
+        wait_for_requests_in_progress(&context);
+        info!("Shutdown completed");
+        send_reply(id, None::<()>);
+    });
+}
+
+/// Waits for the refreshes & the other requests handled on their own threads.
+fn wait_for_requests_in_progress(context: &Context) {
+    context.refresh_coordinator.wait_until_idle();
+    context.in_flight_requests.wait_until_idle();
+}
+
+/// Same as `spawn_with_connection`, with the request waited for by `shutdown`.
+/// The client is sent an `InternalError` when the handler panics, instead of never getting a reply.
+fn spawn_request<F>(context: Arc<Context>, id: u32, f: F)
+where
+    F: FnOnce(Arc<Context>) + Send + 'static,
+{
+    // Started before spawning, so that a `shutdown` handled next waits for this request.
+    let in_flight = context.in_flight_requests.start();
+    spawn_with_connection(move || {
+        let _in_flight = in_flight;
+        if let Err(panic_payload) = panic::catch_unwind(AssertUnwindSafe(|| f(context))) {
+            let message = panic_payload_message(panic_payload.as_ref());
+            error!("Request {} failed unexpectedly: {}", id, message);
+            send_error(
+                Some(id),
+                ErrorCode::InternalError,
+                format!("Request failed unexpectedly: {message}"),
+                None,
+            );
+        }
+    });
+}
 
-    let mut handlers = HandlersKeyedByMethodName::new(Arc::new(context));
-    handlers.add_request_handler("info", handle_info);
-    handlers.add_request_handler("configure", handle_configure);
-    handlers.add_request_handler("refresh", handle_refresh);
-    handlers.add_request_handler("resolve", handle_resolve);
-    handlers.add_request_handler("find", handle_find);
-    handlers.add_request_handler("condaInfo", handle_conda_telemetry);
-    handlers.add_request_handler("clear", handle_clear_cache);
-    start_server(&handlers)
+/// Exits the server, with a non-zero exit code when `shutdown` was not requested first.
+/// Clients of a daemon are disconnected instead, the daemon keeps serving the other clients.
+pub fn handle_exit(context: Arc<Context>, _params: Value) {
+    let shutdown_requested = context.shutdown_requested.load(Ordering::SeqCst);
+    match context.mode {
+        ServerMode::Stdio => {
+            info!("Exiting (shutdown requested: {})", shutdown_requested);
+            finish_trace_file();
+            std::process::exit(if shutdown_requested { 0 } else { 1 });
+        }
+        ServerMode::Daemon(_) => {
+            info!(
+                "Disconnecting client (shutdown requested: {})",
+                shutdown_requested
+            );
+            Connection::current().close();
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -552,6 +1244,9 @@ pub struct ConfigureOptions {
     /// These are paths like workspace folders, where we can look for environments.
     /// Glob patterns are supported (e.g., "/home/user/projects/*").
     pub workspace_directories: Option<Vec<PathBuf>>,
+    /// Number of levels of sub directories of the workspace folders searched for environments.
+    /// Defaults to 1, i.e. the workspace folders and their immediate sub directories.
+    pub workspace_search_depth: Option<usize>,
     pub conda_executable: Option<PathBuf>,
     pub pipenv_executable: Option<PathBuf>,
     pub poetry_executable: Option<PathBuf>,
@@ -561,6 +1256,46 @@ pub struct ConfigureOptions {
     pub environment_directories: Option<Vec<PathBuf>>,
     /// Directory to cache the Python environment details.
     pub cache_directory: Option<PathBuf>,
+    /// Executables that locate additional environments (plugins).
+    pub external_locators: Option<Vec<ExternalLocatorOptions>>,
+    /// Maximum time (in milliseconds) allowed for a spawned Python to report its details.
+    /// Defaults to 15 seconds.
+    pub resolve_timeout: Option<u64>,
+    /// File the spans (e.g. of each locator) are written to, to be viewed as a flamegraph.
+    /// Applies to the whole server, and is kept until a configure with another file.
+    pub trace_file: Option<PathBuf>,
+    /// Format of the trace file, defaults to the Chrome Trace Event format.
+    pub trace_file_format: Option<TraceFileFormat>,
+    /// Directory searched instead of `/`, e.g. an unpacked container image.
+    /// Workspace & environment directories are then paths within the image, as are the paths reported.
+    /// Applies to the whole server, Python is never spawned while it is set.
+    pub root_directory: Option<PathBuf>,
+    /// Number of threads searching for environments, defaults to the number of CPUs (at least 4).
+    /// The threads are shared by all refreshes (i.e. all clients of a daemon).
+    pub discovery_threads: Option<usize>,
+    /// What to do with workspace, environment & locator directories on network (or FUSE) file systems.
+    /// Defaults to `reduce`.
+    pub network_filesystem_policy: Option<NetworkFilesystemPolicy>,
+    /// Whether sub directories of the workspace directories on other file systems (mounts) are searched.
+    /// Defaults to `false`.
+    pub cross_mount_boundaries: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalLocatorOptions {
+    pub executable: PathBuf,
+    /// Maximum time (in milliseconds) allowed for each request sent to the plugin.
+    pub timeout: Option<u64>,
+}
+
+impl From<ExternalLocatorOptions> for ExternalLocatorConfig {
+    fn from(options: ExternalLocatorOptions) -> Self {
+        ExternalLocatorConfig {
+            executable: options.executable,
+            timeout: options.timeout.map(Duration::from_millis),
+        }
+    }
 }
 
 /// Threshold for glob expansion duration before emitting a warning.
@@ -613,7 +1348,7 @@ pub fn handle_configure(context: Arc<Context>, id: u32, params: Value) {
         Ok(mut configure_options) => {
             info!("Received configure request");
             // Start in a new thread, we can have multiple requests.
-            thread::spawn(move || {
+            spawn_request(context, id, move |context| {
                 let now = Instant::now();
 
                 // Warn before any expansion so a slow workspace pattern cannot delay
@@ -622,21 +1357,42 @@ pub fn handle_configure(context: Arc<Context>, id: u32, params: Value) {
                     warn_for_recursive_environment_patterns(patterns);
                 }
 
-                // Expand glob patterns before acquiring the write lock so we
+                // These apply to every client of a daemon, hence cannot differ between clients.
+                if let ServerMode::Daemon(daemon) = &context.mode {
+                    let client = Connection::current().id();
+                    if let Err(message) = daemon.settings.configure(client, &configure_options) {
+                        error!("Configure failed: {message}");
+                        send_error(Some(id), ErrorCode::InvalidParams, message, None);
+                        return;
+                    }
+                }
+
+                // The directories are paths within the alternate root directory (if any).
+                let file_system =
+                    root::create_file_system(configure_options.root_directory.as_deref());
+                let to_host_path = |path: &PathBuf| file_system.to_host_path(path);
+
+                // Expand glob patterns before acquiring the write lock so we
                 // don't block readers/writers while traversing the filesystem.
                 let workspace_directories =
                     configure_options
                         .workspace_directories
                         .take()
                         .map(|patterns| {
-                            expand_configure_directory_patterns("workspaceDirectories", patterns)
+                            expand_configure_directory_patterns(
+                                "workspaceDirectories",
+                                patterns.iter().map(to_host_path).collect(),
+                            )
                         });
                 let environment_directories =
                     configure_options
                         .environment_directories
                         .take()
                         .map(|patterns| {
-                            expand_configure_directory_patterns("environmentDirectories", patterns)
+                            expand_configure_directory_patterns(
+                                "environmentDirectories",
+                                patterns.iter().map(to_host_path).collect(),
+                            )
                         });
                 let glob_elapsed = now.elapsed();
                 trace!("Glob expansion completed in {:?}", glob_elapsed);
@@ -647,6 +1403,20 @@ pub fn handle_configure(context: Arc<Context>, id: u32, params: Value) {
                     );
                 }
 
+                if let Some(trace_file) = configure_options.trace_file.as_deref() {
+                    let format = configure_options.trace_file_format.unwrap_or_default();
+                    if let Err(e) = set_trace_file(Some(trace_file), format) {
+                        error!("Failed to create the trace file {trace_file:?}: {e}");
+                        send_error(
+                            Some(id),
+                            ErrorCode::InvalidParams,
+                            format!("Failed to create the trace file {trace_file:?}: {e}"),
+                            Some(ErrorData::new(Some(trace_file.to_path_buf()), e)),
+                        );
+                        return;
+                    }
+                }
+
                 if let Err(message) = apply_configure_options(
                     context.configuration.as_ref(),
                     &context.configure_in_progress,
@@ -656,7 +1426,7 @@ pub fn handle_configure(context: Arc<Context>, id: u32, params: Value) {
                     environment_directories,
                 ) {
                     error!("Configure failed: {message}");
-                    send_error(Some(id), -4, message);
+                    send_error(Some(id), ErrorCode::InternalError, message, None);
                     return;
                 }
                 info!("Configure completed in {:?}", now.elapsed());
@@ -667,8 +1437,9 @@ pub fn handle_configure(context: Arc<Context>, id: u32, params: Value) {
             error!("Failed to parse configure options {:?}: {}", params, e);
             send_error(
                 Some(id),
-                -4,
+                ErrorCode::InvalidParams,
                 format!("Failed to parse configure options {params:?}: {e}"),
+                Some(ErrorData::new(None, e)),
             );
         }
     }
@@ -684,6 +1455,9 @@ pub struct RefreshOptions {
     /// Traditionally, search paths are workspace folders.
     /// Glob patterns are supported (e.g., "/home/user/*/venv", "**/.venv").
     pub search_paths: Option<Vec<PathBuf>>,
+    /// If provided, then environments of these kinds are not reported,
+    /// e.g. to hide the tox & nox environments of the workspace folders.
+    pub exclude_kinds: Option<Vec<PythonEnvironmentKind>>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -719,6 +1493,10 @@ fn canonicalize_refresh_options(mut options: RefreshOptions) -> RefreshOptions {
         expanded.dedup();
         options.search_paths = Some(expanded);
     }
+    if let Some(exclude_kinds) = options.exclude_kinds.as_mut() {
+        exclude_kinds.sort();
+        exclude_kinds.dedup();
+    }
 
     options
 }
@@ -755,10 +1533,19 @@ fn apply_configure_options(
     };
 
     next_config.workspace_directories = workspace_directories;
+    next_config.workspace_search_depth = configure_options.workspace_search_depth;
     next_config.conda_executable = configure_options.conda_executable;
     next_config.environment_directories = environment_directories;
     next_config.pipenv_executable = configure_options.pipenv_executable;
     next_config.poetry_executable = configure_options.poetry_executable;
+    next_config.external_locators = configure_options
+        .external_locators
+        .map(|locators| locators.into_iter().map(Into::into).collect());
+    next_config.resolve_timeout = configure_options.resolve_timeout.map(Duration::from_millis);
+    next_config.root_directory = configure_options.root_directory;
+    next_config.discovery_threads = configure_options.discovery_threads;
+    next_config.network_filesystem_policy = configure_options.network_filesystem_policy;
+    next_config.cross_mount_boundaries = configure_options.cross_mount_boundaries.unwrap_or(false);
     // We will not support changing the cache directories once set.
     // No point, supporting such a use case.
     let cache_directory = configure_options.cache_directory;
@@ -819,6 +1606,8 @@ fn apply_configure_options(
         }
     }
 
+    set_spawn_timeout(next_config.resolve_timeout);
+
     // Phase C — Publish: re-take the write lock and atomically install the
     // new config + generation. Refresh threads only ever observe the new
     // generation after every locator has been configured.
@@ -830,7 +1619,9 @@ fn apply_configure_options(
         // reconfiguration can trigger it again (Fixes #395). Done inside the
         // write lock to avoid a TOCTOU window with concurrent refresh threads
         // reading the generation.
-        MISSING_ENVS_REPORTING_STATE.store(MISSING_ENVS_AVAILABLE, Ordering::Release);
+        state
+            .missing_envs_reporting
+            .store(MISSING_ENVS_AVAILABLE, Ordering::Release);
     }
 
     Ok(())
@@ -873,6 +1664,54 @@ fn configure_locators(locators: &Arc<Vec<Arc<dyn Locator>>>, config: &Configurat
     }
 }
 
+/// Locators & environment searched by the requests other than `refresh` (e.g. `resolve` & `find`).
+/// With an alternate root directory, transient locators search the root instead of this machine.
+struct SearchContext {
+    locators: Arc<Vec<Arc<dyn Locator>>>,
+    environment: Arc<dyn Environment>,
+    file_system: Arc<dyn FileSystem>,
+}
+
+impl SearchContext {
+    fn new(context: &Context) -> Self {
+        let config = context.configuration.read().unwrap().config.clone();
+        match config.root_directory.clone() {
+            Some(root) => {
+                let environment: Arc<dyn Environment> =
+                    Arc::new(RootedEnvironment::new(root.clone()));
+                let refresh_locators =
+                    create_refresh_locators(environment.as_ref(), context.conda_locator.as_ref());
+                configure_locators(&refresh_locators.locators, &config);
+                SearchContext {
+                    locators: refresh_locators.locators,
+                    file_system: environment.file_system(),
+                    environment,
+                }
+            }
+            None => SearchContext {
+                locators: context.locators.clone(),
+                environment: context.os_environment.clone(),
+                file_system: context.os_environment.file_system(),
+            },
+        }
+    }
+
+    /// Wraps the reporter so that the environments are reported with the paths of the image.
+    fn create_reporter(&self, reporter: Arc<dyn Reporter>) -> Arc<dyn Reporter> {
+        root::create_reporter(reporter, self.environment.as_ref())
+    }
+
+    /// Maps a path of the image to the host, when searching an alternate root directory.
+    fn to_host_path(&self, path: &Path) -> PathBuf {
+        self.file_system.to_host_path(path)
+    }
+
+    /// Maps the environment found in the alternate root directory to the paths of the image.
+    fn to_image_environment(&self, env: PythonEnvironment) -> PythonEnvironment {
+        root::to_image_environment(self.file_system.as_ref(), &env)
+    }
+}
+
 fn create_refresh_locators(
     environment: &dyn Environment,
     shared_conda_locator: &Conda,
@@ -888,6 +1727,7 @@ fn create_refresh_locators(
         locators,
         conda_locator,
         poetry_locator,
+        file_system: environment.file_system(),
     }
 }
 
@@ -942,8 +1782,9 @@ fn try_begin_missing_env_reporting(
     configuration: &RwLock<ConfigurationState>,
     refresh_generation: u64,
 ) -> bool {
+    let reporting_state = configuration.read().unwrap().missing_envs_reporting.clone();
     try_begin_missing_env_reporting_with_state(
-        &MISSING_ENVS_REPORTING_STATE,
+        reporting_state.as_ref(),
         configuration,
         refresh_generation,
     )
@@ -990,8 +1831,9 @@ fn release_missing_env_reporting_if_stale(
     configuration: &RwLock<ConfigurationState>,
     refresh_generation: u64,
 ) {
+    let reporting_state = configuration.read().unwrap().missing_envs_reporting.clone();
     release_missing_env_reporting_if_stale_with_state(
-        &MISSING_ENVS_REPORTING_STATE,
+        reporting_state.as_ref(),
         configuration,
         refresh_generation,
     );
@@ -1012,8 +1854,12 @@ fn release_missing_env_reporting_if_stale_with_state(
     }
 }
 
-fn complete_missing_env_reporting(refresh_generation: u64) {
-    complete_missing_env_reporting_with_state(&MISSING_ENVS_REPORTING_STATE, refresh_generation);
+fn complete_missing_env_reporting(
+    configuration: &RwLock<ConfigurationState>,
+    refresh_generation: u64,
+) {
+    let reporting_state = configuration.read().unwrap().missing_envs_reporting.clone();
+    complete_missing_env_reporting_with_state(reporting_state.as_ref(), refresh_generation);
 }
 
 fn complete_missing_env_reporting_with_state(reporting_state: &AtomicU64, refresh_generation: u64) {
@@ -1029,19 +1875,127 @@ fn execute_refresh(
     refresh_options: &RefreshOptions,
     configuration_state: &ConfigurationState,
 ) -> RefreshExecution {
-    let refresh_id = NEXT_REFRESH_ID.fetch_add(1, Ordering::Relaxed);
-    let refresh_locators = create_refresh_locators(
-        context.os_environment.deref(),
-        context.conda_locator.as_ref(),
+    let collect_reporter = Arc::new(collect::create_reporter());
+    let client_reporter: Arc<dyn Reporter> = Arc::new(GenerationGuardedReporter::new(
+        Arc::new(RefreshSnapshotReporter {
+            reporter: Arc::new(jsonrpc::create_reporter(
+                refresh_options.search_kind,
+                refresh_options.exclude_kinds.clone().unwrap_or_default(),
+            )),
+            collect_reporter: collect_reporter.clone(),
+        }),
+        context.configuration.clone(),
+        configuration_state.generation,
+    ));
+
+    let mut execution = refresh_for_client(
+        context,
+        refresh_options,
+        configuration_state,
+        client_reporter,
     );
-    let reporter = Arc::new(CacheReporter::new(Arc::new(
-        GenerationGuardedReporter::new(
-            Arc::new(jsonrpc::create_reporter(refresh_options.search_kind)),
-            context.configuration.clone(),
+    // A filtered refresh only finds some of the environments, hence is not a snapshot of all of them.
+    if is_unfiltered_refresh(refresh_options) {
+        store_refresh_snapshot(
+            context,
             configuration_state.generation,
-        ),
-    )));
+            &execution.result,
+            collect_reporter.as_ref(),
+        );
+        execution.snapshot_reporter = Some(collect_reporter);
+    }
+    execution
+}
+
+fn is_unfiltered_refresh(options: &RefreshOptions) -> bool {
+    options.search_kind.is_none()
+        && options.search_paths.is_none()
+        && options.exclude_kinds.as_ref().is_none_or(Vec::is_empty)
+}
+
+/// Finds the environments for a client, sharing the refresh with other clients of a daemon with the same configuration.
+fn refresh_for_client(
+    context: &Context,
+    refresh_options: &RefreshOptions,
+    configuration_state: &ConfigurationState,
+    client_reporter: Arc<dyn Reporter>,
+) -> RefreshExecution {
+    let ServerMode::Daemon(daemon) = &context.mode else {
+        return run_refresh(
+            context,
+            refresh_options,
+            configuration_state,
+            client_reporter,
+            None,
+        );
+    };
+
+    let key = SharedRefreshKey {
+        options: refresh_options.clone(),
+        config: configuration_state.config.clone(),
+    };
+    match daemon.refreshes.register(key, client_reporter.clone()) {
+        SharedRefreshRegistration::Lead(lead) => {
+            let reporter = lead.reporter();
+            run_refresh(
+                context,
+                refresh_options,
+                configuration_state,
+                reporter,
+                Some(lead),
+            )
+        }
+        SharedRefreshRegistration::Follow(refresh) => match refresh.wait_for_outcome() {
+            Some(outcome) => {
+                trace!(
+                    "Joined the refresh {} of another client, generation: {}",
+                    outcome.result.refresh_id,
+                    configuration_state.generation
+                );
+                follow_refresh(
+                    context,
+                    refresh_options,
+                    configuration_state,
+                    client_reporter,
+                    outcome,
+                )
+            }
+            None => {
+                warn!("Shared refresh failed, refreshing again for this client");
+                run_refresh(
+                    context,
+                    refresh_options,
+                    configuration_state,
+                    client_reporter,
+                    None,
+                )
+            }
+        },
+    }
+}
 
+/// Finds the environments, reporting them to `client_reporter`.
+/// `lead` is completed with the outcome when the refresh is shared with other clients of a daemon.
+fn run_refresh(
+    context: &Context,
+    refresh_options: &RefreshOptions,
+    configuration_state: &ConfigurationState,
+    client_reporter: Arc<dyn Reporter>,
+    lead: Option<SharedRefreshLead>,
+) -> RefreshExecution {
+    let refresh_id = NEXT_REFRESH_ID.fetch_add(1, Ordering::Relaxed);
+    let root_directory = configuration_state.config.root_directory.as_deref();
+    let rooted_environment = root_directory.map(|root| RootedEnvironment::new(root.to_path_buf()));
+    let environment: &dyn Environment = match rooted_environment.as_ref() {
+        Some(environment) => environment,
+        None => context.os_environment.deref(),
+    };
+    let refresh_locators = create_refresh_locators(environment, context.conda_locator.as_ref());
+    let reporter = Arc::new(CacheReporter::new(Arc::new(LineageReporter::new(
+        root::create_reporter(client_reporter, environment),
+        refresh_locators.locators.clone(),
+        environment.file_system(),
+    ))));
     let (config, search_scope) =
         build_refresh_config(refresh_options, configuration_state.config.clone());
     if refresh_options.search_paths.is_some() {
@@ -1067,7 +2021,7 @@ fn execute_refresh(
         reporter.as_ref(),
         config,
         &refresh_locators.locators,
-        context.os_environment.deref(),
+        environment,
         search_scope.clone(),
         Some(refresh_id),
     );
@@ -1080,25 +2034,12 @@ fn execute_refresh(
     }
     trace!("Finished refreshing environments in {:?}", summary.total);
 
-    // Refresh runs on a transient locator graph, so apply each locator's refresh-state
-    // contract back into the long-lived shared locator graph only if the generation
-    // still matches the configuration snapshot this refresh started with.
-    if let Err(current_generation) = sync_refresh_locator_state_if_current(
-        context.configuration.as_ref(),
+    sync_refresh_locator_state_for_generation(
+        context,
         configuration_state.generation,
-        || {
-            sync_refresh_locator_state(
-                context.locators.as_ref(),
-                refresh_locators.locators.as_ref(),
-                search_scope.as_ref(),
-            );
-        },
-    ) {
-        warn!(
-            "Skipping refresh state sync for stale generation {} because current generation is {}",
-            configuration_state.generation, current_generation
-        );
-    }
+        &refresh_locators,
+        search_scope.as_ref(),
+    );
 
     let perf = RefreshPerformance {
         total: summary.total.as_millis(),
@@ -1108,28 +2049,155 @@ fn execute_refresh(
             .iter()
             .map(|(k, v)| (format!("{k:?}"), v.as_millis()))
             .collect::<BTreeMap<String, u128>>(),
+        locators_queue_wait: summary
+            .locators_queue_wait
+            .iter()
+            .map(|(k, v)| (format!("{k:?}"), v.as_millis()))
+            .collect::<BTreeMap<String, u128>>(),
         breakdown: summary
             .breakdown
             .clone()
             .iter()
             .map(|(k, v)| (k.to_string(), v.as_millis()))
             .collect::<BTreeMap<String, u128>>(),
+        workspace_directories_visited: summary.workspace_directories_visited,
     };
 
+    let result = RefreshResult::new(summary.total, refresh_id);
+    if let Some(lead) = lead {
+        lead.complete(SharedRefreshOutcome {
+            result: result.clone(),
+            perf: perf.clone(),
+            locators: refresh_locators.clone(),
+        });
+    }
+
     RefreshExecution {
-        result: RefreshResult::new(summary.total, refresh_id),
+        result,
         perf,
         reporter,
         configuration: context.configuration.clone(),
         refresh_generation: configuration_state.generation,
+        locators: refresh_locators.locators,
         conda_locator: refresh_locators.conda_locator,
         poetry_locator: refresh_locators.poetry_locator,
+        file_system: refresh_locators.file_system,
+        conda_executable: configuration_state.config.conda_executable.clone(),
+        poetry_executable: configuration_state.config.poetry_executable.clone(),
+        snapshot_reporter: None,
+    }
+}
+
+/// Completes the refresh of a client using the outcome of a refresh run by another client of the daemon.
+fn follow_refresh(
+    context: &Context,
+    refresh_options: &RefreshOptions,
+    configuration_state: &ConfigurationState,
+    client_reporter: Arc<dyn Reporter>,
+    outcome: SharedRefreshOutcome,
+) -> RefreshExecution {
+    let (_, search_scope) =
+        build_refresh_config(refresh_options, configuration_state.config.clone());
+    sync_refresh_locator_state_for_generation(
+        context,
+        configuration_state.generation,
+        &outcome.locators,
+        search_scope.as_ref(),
+    );
+
+    RefreshExecution {
+        result: outcome.result,
+        perf: outcome.perf,
+        reporter: Arc::new(CacheReporter::new(Arc::new(LineageReporter::new(
+            client_reporter,
+            outcome.locators.locators.clone(),
+            root::create_file_system(configuration_state.config.root_directory.as_deref()),
+        )))),
+        configuration: context.configuration.clone(),
+        refresh_generation: configuration_state.generation,
+        locators: outcome.locators.locators.clone(),
+        conda_locator: outcome.locators.conda_locator,
+        poetry_locator: outcome.locators.poetry_locator,
+        file_system: outcome.locators.file_system,
         conda_executable: configuration_state.config.conda_executable.clone(),
         poetry_executable: configuration_state.config.poetry_executable.clone(),
+        snapshot_reporter: None,
+    }
+}
+
+fn sync_refresh_locator_state_for_generation(
+    context: &Context,
+    refresh_generation: u64,
+    refresh_locators: &RefreshLocators,
+    search_scope: Option<&SearchScope>,
+) {
+    // Refresh runs on a transient locator graph, so apply each locator's refresh-state
+    // contract back into the long-lived shared locator graph only if the generation
+    // still matches the configuration snapshot this refresh started with.
+    if let Err(current_generation) = sync_refresh_locator_state_if_current(
+        context.configuration.as_ref(),
+        refresh_generation,
+        || {
+            sync_refresh_locator_state(
+                context.locators.as_ref(),
+                refresh_locators.locators.as_ref(),
+                search_scope,
+            );
+        },
+    ) {
+        warn!(
+            "Skipping refresh state sync for stale generation {} because current generation is {}",
+            refresh_generation, current_generation
+        );
     }
 }
 
-fn report_refresh_follow_up(execution: RefreshExecution) {
+/// Keeps what the refresh found, unless the configuration changed since the refresh started.
+fn store_refresh_snapshot(
+    context: &Context,
+    refresh_generation: u64,
+    result: &RefreshResult,
+    collect_reporter: &CollectReporter,
+) {
+    let snapshot = RefreshSnapshot {
+        generation: refresh_generation,
+        refresh_id: result.refresh_id,
+        completed_at: SystemTime::now(),
+        environments: collect_reporter
+            .environments
+            .lock()
+            .expect("environments mutex poisoned")
+            .clone(),
+        managers: collect_reporter
+            .managers
+            .lock()
+            .expect("managers mutex poisoned")
+            .clone(),
+    };
+    // Hold the read lock so that a configure cannot complete before the snapshot is stored.
+    let state = context.configuration.read().unwrap();
+    if state.generation == refresh_generation {
+        let mut last_refresh = context
+            .last_refresh
+            .lock()
+            .expect("last refresh mutex poisoned");
+        // The follow-ups of a refresh can complete after a later refresh.
+        if last_refresh
+            .as_ref()
+            .is_none_or(|last| last.refresh_id <= result.refresh_id)
+        {
+            *last_refresh = Some(Arc::new(snapshot));
+        }
+    } else {
+        trace!(
+            "Not keeping the refresh {} of stale generation {}",
+            result.refresh_id,
+            refresh_generation
+        );
+    }
+}
+
+fn report_refresh_follow_up(context: Arc<Context>, execution: RefreshExecution) {
     execution
         .reporter
         .report_telemetry(&TelemetryEvent::RefreshPerformance(execution.perf));
@@ -1143,23 +2211,52 @@ fn report_refresh_follow_up(execution: RefreshExecution) {
         let poetry_locator = execution.poetry_locator.clone();
         let poetry_executable = execution.poetry_executable.clone();
         let reporter_ref = execution.reporter.clone();
+        let locators = execution.locators.clone();
+        let file_system = execution.file_system.clone();
         let configuration = execution.configuration.clone();
         let refresh_generation = execution.refresh_generation;
-        thread::spawn(move || {
+        let refresh_result = execution.result.clone();
+        let snapshot_reporter = execution.snapshot_reporter.clone();
+        spawn_with_connection(move || {
             if !is_current_generation(configuration.as_ref(), refresh_generation) {
                 release_missing_env_reporting_if_stale(configuration.as_ref(), refresh_generation);
                 return Some(());
             }
 
-            conda_locator.find_and_report_missing_envs(reporter_ref.as_ref(), conda_executable);
+            conda_locator.find_and_report_missing_envs(
+                &TaggingReporter::new(
+                    reporter_ref.as_ref(),
+                    LocatorKind::Conda,
+                    &locators,
+                    file_system.clone(),
+                ),
+                conda_executable,
+            );
             if !is_current_generation(configuration.as_ref(), refresh_generation) {
                 release_missing_env_reporting_if_stale(configuration.as_ref(), refresh_generation);
                 return Some(());
             }
 
-            poetry_locator.find_and_report_missing_envs(reporter_ref.as_ref(), poetry_executable);
+            poetry_locator.find_and_report_missing_envs(
+                &TaggingReporter::new(
+                    reporter_ref.as_ref(),
+                    LocatorKind::Poetry,
+                    &locators,
+                    file_system.clone(),
+                ),
+                poetry_executable,
+            );
             if is_current_generation(configuration.as_ref(), refresh_generation) {
-                complete_missing_env_reporting(refresh_generation);
+                complete_missing_env_reporting(configuration.as_ref(), refresh_generation);
+                // Keep the missing environments too.
+                if let Some(snapshot_reporter) = snapshot_reporter {
+                    store_refresh_snapshot(
+                        context.as_ref(),
+                        refresh_generation,
+                        &refresh_result,
+                        snapshot_reporter.as_ref(),
+                    );
+                }
             } else {
                 release_missing_env_reporting_if_stale(configuration.as_ref(), refresh_generation);
             }
@@ -1173,7 +2270,7 @@ pub fn handle_refresh(context: Arc<Context>, id: u32, params: Value) {
     match parse_refresh_options(params.clone()) {
         Ok(refresh_options) => {
             // Start in a new thread, we can have multiple requests.
-            thread::spawn(move || {
+            spawn_with_connection(move || {
                 let _span = info_span!("handle_refresh",
                     search_kind = ?refresh_options.search_kind,
                     has_search_paths = refresh_options.search_paths.is_some()
@@ -1220,7 +2317,7 @@ pub fn handle_refresh(context: Arc<Context>, id: u32, params: Value) {
                                     );
                                     safety_guard.disarm();
                                     finish_refresh_replies(&mut completion_guard, &refresh_result);
-                                    report_refresh_follow_up(execution);
+                                    report_refresh_follow_up(context.clone(), execution);
                                 }
                                 Err(_) => {
                                     error!(
@@ -1241,79 +2338,537 @@ pub fn handle_refresh(context: Arc<Context>, id: u32, params: Value) {
                             return;
                         }
                     }
-                }
+                }
+            });
+        }
+        Err(e) => {
+            error!("Failed to parse refresh {params:?}: {e}");
+            send_error(
+                Some(id),
+                ErrorCode::InvalidParams,
+                format!("Failed to parse refresh {params:?}: {e}"),
+                Some(ErrorData::new(None, e)),
+            );
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetEnvironmentsResult {
+    /// Refresh the environments were found by.
+    pub refresh_id: u64,
+    /// Time the refresh completed, in milliseconds since the Unix epoch.
+    pub completed_at: u128,
+    pub environments: Vec<PythonEnvironment>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetEnvironmentOptions {
+    /// Id of the environment, i.e. the executable or the prefix (when there is no executable).
+    pub id: Option<PathBuf>,
+    /// Executable, prefix or a known symlink of the environment.
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetEnvironmentResult {
+    pub refresh_id: u64,
+    pub completed_at: u128,
+    /// Not set if the refresh did not find the environment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<PythonEnvironment>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetManagersResult {
+    pub refresh_id: u64,
+    pub completed_at: u128,
+    pub managers: Vec<EnvManager>,
+}
+
+/// The last completed refresh, if it ran with the current configuration.
+fn get_last_refresh(context: &Context) -> Option<Arc<RefreshSnapshot>> {
+    let generation = context.configuration.read().unwrap().generation;
+    context
+        .last_refresh
+        .lock()
+        .expect("last refresh mutex poisoned")
+        .clone()
+        .filter(|snapshot| snapshot.generation == generation)
+}
+
+/// Whether the environment would have been reported by a refresh with these options.
+fn environment_matches_refresh_options(env: &PythonEnvironment, options: &RefreshOptions) -> bool {
+    if let Some(search_kind) = options.search_kind {
+        if env.kind != Some(search_kind) {
+            return false;
+        }
+    }
+    if let (Some(exclude_kinds), Some(kind)) = (&options.exclude_kinds, env.kind) {
+        if exclude_kinds.contains(&kind) {
+            return false;
+        }
+    }
+    match &options.search_paths {
+        Some(search_paths) => [&env.executable, &env.prefix, &env.project]
+            .into_iter()
+            .flatten()
+            .any(|path| {
+                search_paths
+                    .iter()
+                    .any(|search_path| path.starts_with(search_path))
+            }),
+        None => true,
+    }
+}
+
+fn environment_matches_options(env: &PythonEnvironment, options: &GetEnvironmentOptions) -> bool {
+    if let Some(id) = &options.id {
+        if get_environment_key(env).as_ref() != Some(id) {
+            return false;
+        }
+    }
+    match &options.path {
+        Some(path) => {
+            env.executable.as_ref() == Some(path)
+                || env.prefix.as_ref() == Some(path)
+                || env
+                    .symlinks
+                    .as_ref()
+                    .is_some_and(|symlinks| symlinks.contains(path))
+        }
+        None => true,
+    }
+}
+
+/// Replies with the environments found by the last refresh, `null` if there was no refresh since the last `configure`.
+pub fn handle_get_environments(context: Arc<Context>, id: u32, params: Value) {
+    match parse_refresh_options(params.clone()) {
+        Ok(refresh_options) => match get_last_refresh(context.as_ref()) {
+            Some(snapshot) => send_reply(
+                id,
+                Some(GetEnvironmentsResult {
+                    refresh_id: snapshot.refresh_id,
+                    completed_at: snapshot.completed_at_millis(),
+                    environments: snapshot
+                        .environments
+                        .iter()
+                        .filter(|env| environment_matches_refresh_options(env, &refresh_options))
+                        .cloned()
+                        .collect(),
+                }),
+            ),
+            None => send_reply(id, None::<GetEnvironmentsResult>),
+        },
+        Err(e) => {
+            error!("Failed to parse getEnvironments {params:?}: {e}");
+            send_error(
+                Some(id),
+                ErrorCode::InvalidParams,
+                format!("Failed to parse getEnvironments {params:?}: {e}"),
+                Some(ErrorData::new(None, e)),
+            );
+        }
+    }
+}
+
+/// Replies with an environment found by the last refresh, `null` if there was no refresh since the last `configure`.
+pub fn handle_get_environment(context: Arc<Context>, id: u32, params: Value) {
+    match serde_json::from_value::<GetEnvironmentOptions>(params.clone()) {
+        Ok(options) if options.id.is_none() && options.path.is_none() => {
+            error!("Either id or path is required by getEnvironment {params:?}");
+            send_error(
+                Some(id),
+                ErrorCode::InvalidParams,
+                format!("Either id or path is required by getEnvironment {params:?}"),
+                None,
+            );
+        }
+        Ok(options) => {
+            let options = GetEnvironmentOptions {
+                id: options.id.map(norm_case),
+                path: options.path.map(norm_case),
+            };
+            match get_last_refresh(context.as_ref()) {
+                Some(snapshot) => send_reply(
+                    id,
+                    Some(GetEnvironmentResult {
+                        refresh_id: snapshot.refresh_id,
+                        completed_at: snapshot.completed_at_millis(),
+                        environment: snapshot
+                            .environments
+                            .iter()
+                            .find(|env| environment_matches_options(env, &options))
+                            .cloned(),
+                    }),
+                ),
+                None => send_reply(id, None::<GetEnvironmentResult>),
+            }
+        }
+        Err(e) => {
+            error!("Failed to parse getEnvironment {params:?}: {e}");
+            send_error(
+                Some(id),
+                ErrorCode::InvalidParams,
+                format!("Failed to parse getEnvironment {params:?}: {e}"),
+                Some(ErrorData::new(None, e)),
+            );
+        }
+    }
+}
+
+/// Replies with the managers found by the last refresh, `null` if there was no refresh since the last `configure`.
+pub fn handle_get_managers(context: Arc<Context>, id: u32, _params: Value) {
+    match get_last_refresh(context.as_ref()) {
+        Some(snapshot) => send_reply(
+            id,
+            Some(GetManagersResult {
+                refresh_id: snapshot.refresh_id,
+                completed_at: snapshot.completed_at_millis(),
+                managers: snapshot.managers.clone(),
+            }),
+        ),
+        None => send_reply(id, None::<GetManagersResult>),
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveOptions {
+    pub executable: PathBuf,
+    /// Whether to include the wheel compatibility tags of the environment.
+    pub include_platform_tags: Option<bool>,
+}
+
+pub fn handle_resolve(context: Arc<Context>, id: u32, params: Value) {
+    match serde_json::from_value::<ResolveOptions>(params.clone()) {
+        Ok(request_options) => {
+            let executable = request_options.executable.clone();
+            let include_platform_tags = request_options.include_platform_tags.unwrap_or_default();
+            // Start in a new thread, we can have multiple resolve requests.
+            spawn_request(context, id, move |context| {
+                let now = SystemTime::now();
+                trace!("Resolving env {:?}", executable);
+                let search_context = SearchContext::new(context.as_ref());
+                match resolve_executable(&search_context, &executable, include_platform_tags) {
+                    ResolveOutcome::Resolved(resolved) => {
+                        trace!(
+                            "Resolved env ({:?}) {executable:?} as {resolved:?}",
+                            now.elapsed()
+                        );
+                        send_reply(id, resolved.into());
+                    }
+                    ResolveOutcome::Discovered(discovered, _) => {
+                        error!(
+                            "Failed to resolve env {executable:?}, returning discovered env {:?}",
+                            discovered
+                        );
+                        send_reply(id, discovered.into());
+                    }
+                    ResolveOutcome::Unknown(failure) => {
+                        error!("Failed to resolve env {executable:?}: {failure}");
+                        send_error(
+                            Some(id),
+                            get_resolve_error_code(
+                                &search_context.to_host_path(&executable),
+                                &failure,
+                            ),
+                            format!("Failed to resolve env {executable:?}: {failure}"),
+                            Some(ErrorData::new(Some(executable.clone()), failure)),
+                        );
+                    }
+                }
+            });
+        }
+        Err(e) => {
+            error!("Failed to parse resolve {params:?}: {e}");
+            send_error(
+                Some(id),
+                ErrorCode::InvalidParams,
+                format!("Failed to parse resolve {params:?}: {e}"),
+                Some(ErrorData::new(None, e)),
+            );
+        }
+    }
+}
+
+/// Gets the error code for an executable (a path of the host) that could not be resolved.
+fn get_resolve_error_code(executable: &Path, failure: &SpawnFailure) -> ErrorCode {
+    if !executable.exists() {
+        ErrorCode::NotFound
+    } else if matches!(
+        failure.kind,
+        SpawnFailureKind::NotExecutable | SpawnFailureKind::BadOutput
+    ) {
+        ErrorCode::NotAPython
+    } else {
+        ErrorCode::SpawnFailed
+    }
+}
+
+enum ResolveOutcome {
+    Resolved(PythonEnvironment),
+    /// Python could not be spawned, hence only the details known from the files on disc.
+    Discovered(PythonEnvironment, Option<SpawnFailure>),
+    /// None of the locators know this environment & Python could not be spawned.
+    Unknown(SpawnFailure),
+}
+
+/// Resolves the executable, a path of the image when searching an alternate root directory.
+fn resolve_executable(
+    search_context: &SearchContext,
+    executable: &Path,
+    include_platform_tags: bool,
+) -> ResolveOutcome {
+    let result = match resolve_environment(
+        &search_context.to_host_path(executable),
+        &search_context.locators,
+        search_context.environment.deref(),
+    ) {
+        Ok(result) => result,
+        Err(failure) => return ResolveOutcome::Unknown(failure),
+    };
+    let Some(mut resolved) = result.resolved else {
+        return ResolveOutcome::Discovered(
+            search_context.to_image_environment(result.discovered),
+            result.failure,
+        );
+    };
+    if include_platform_tags {
+        resolved.platform_tags =
+            get_environment_platform_tags(search_context.file_system.as_ref(), &resolved);
+    }
+    let resolved = search_context.to_image_environment(resolved);
+    // Gather telemetry of this resolved env and see what we got wrong.
+    let jsonrpc_reporter = jsonrpc::create_reporter(None, vec![]);
+    let _ = report_inaccuracies_identified_after_resolving(
+        &jsonrpc_reporter,
+        &result.discovered,
+        &resolved,
+    );
+    ResolveOutcome::Resolved(resolved)
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveManyOptions {
+    pub executables: Vec<PathBuf>,
+    /// Whether to include the wheel compatibility tags of the environments.
+    pub include_platform_tags: Option<bool>,
+    /// Maximum number of Python executables spawned at the same time.
+    pub concurrency: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedNotification {
+    /// The requested executables that are the same environment, i.e. duplicates & symlinks of each other.
+    pub executables: Vec<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<PythonEnvironment>,
+    /// Whether the details of the interpreter were cached, i.e. Python was not spawned.
+    pub cache_hit: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveFailure {
+    pub executable: PathBuf,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveManySummary {
+    /// Number of distinct environments resolved, after removing duplicates & symlinks of each other.
+    pub total: usize,
+    pub resolved: usize,
+    pub cache_hits: usize,
+    pub failures: Vec<ResolveFailure>,
+    /// Time taken in milliseconds.
+    pub duration: u128,
+}
+
+pub fn handle_resolve_many(context: Arc<Context>, id: u32, params: Value) {
+    match serde_json::from_value::<ResolveManyOptions>(params.clone()) {
+        Ok(options) => {
+            spawn_request(context, id, move |context| {
+                let now = Instant::now();
+                let include_platform_tags = options.include_platform_tags.unwrap_or_default();
+                let concurrency = get_resolve_concurrency(options.concurrency);
+                let groups = group_executables(&options.executables);
+                trace!(
+                    "Resolving {} environments ({} executables) with a concurrency of {}",
+                    groups.len(),
+                    options.executables.len(),
+                    concurrency
+                );
+
+                let summary = Mutex::new(ResolveManySummary {
+                    total: groups.len(),
+                    ..Default::default()
+                });
+                let search_context = SearchContext::new(context.as_ref());
+                // Executables are resolved on the rayon pool, whose threads need to be told where to send their messages.
+                let connection = Connection::current();
+                for_each_with_concurrency(&groups, concurrency, |executables| {
+                    let notification = with_connection(&connection, || {
+                        resolve_many_item(&search_context, executables, include_platform_tags)
+                    });
+                    {
+                        let mut summary = summary.lock().expect("summary mutex poisoned");
+                        if notification.cache_hit {
+                            summary.cache_hits += 1;
+                        }
+                        match &notification.error {
+                            Some(reason) => summary.failures.push(ResolveFailure {
+                                executable: executables[0].clone(),
+                                reason: reason.clone(),
+                            }),
+                            None => summary.resolved += 1,
+                        }
+                    }
+                    connection.send_message("resolved", Some(notification));
+                });
+
+                let mut summary = summary.into_inner().expect("summary mutex poisoned");
+                summary.duration = now.elapsed().as_millis();
+                trace!("Resolved many environments {:?}", summary);
+                send_reply(id, Some(summary));
             });
         }
         Err(e) => {
-            error!("Failed to parse refresh {params:?}: {e}");
+            error!("Failed to parse resolveMany {params:?}: {e}");
             send_error(
                 Some(id),
-                -4,
-                format!("Failed to parse refresh {params:?}: {e}"),
+                ErrorCode::InvalidParams,
+                format!("Failed to parse resolveMany {params:?}: {e}"),
+                Some(ErrorData::new(None, e)),
             );
         }
     }
 }
 
+fn resolve_many_item(
+    search_context: &SearchContext,
+    executables: &[PathBuf],
+    include_platform_tags: bool,
+) -> ResolvedNotification {
+    let executable = &executables[0];
+    let executable_on_host = search_context.to_host_path(executable);
+    let cache_hit = ResolvedPythonEnv::from_cache(&executable_on_host).is_some();
+    let (environment, error) =
+        match resolve_executable(search_context, executable, include_platform_tags) {
+            ResolveOutcome::Resolved(resolved) => (Some(resolved), None),
+            ResolveOutcome::Discovered(discovered, failure) => (
+                Some(discovered),
+                Some(match failure {
+                    Some(failure) => format!(
+                        "Failed to spawn Python, only the discovered details are known ({failure})"
+                    ),
+                    None => {
+                        "Failed to spawn Python, only the discovered details are known".to_string()
+                    }
+                }),
+            ),
+            ResolveOutcome::Unknown(_) if !executable_on_host.exists() => {
+                (None, Some("Executable does not exist".to_string()))
+            }
+            ResolveOutcome::Unknown(failure) => (None, Some(failure.to_string())),
+        };
+    ResolvedNotification {
+        executables: executables.to_vec(),
+        environment,
+        cache_hit,
+        error,
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct ResolveOptions {
+#[serde(rename_all = "camelCase")]
+pub struct PlatformTagsOptions {
     pub executable: PathBuf,
+    /// Prefix of the environment, used to locate the ABI details of the interpreter.
+    pub prefix: Option<PathBuf>,
+    /// Version of the interpreter, inferred from the files on disc when not provided.
+    pub version: Option<PythonVersion>,
 }
 
-pub fn handle_resolve(context: Arc<Context>, id: u32, params: Value) {
-    match serde_json::from_value::<ResolveOptions>(params.clone()) {
-        Ok(request_options) => {
-            let executable = request_options.executable.clone();
-            // Start in a new thread, we can have multiple resolve requests.
-            let environment = context.os_environment.clone();
-            thread::spawn(move || {
-                let now = SystemTime::now();
-                trace!("Resolving env {:?}", executable);
-                if let Some(result) =
-                    resolve_environment(&executable, &context.locators, environment.deref())
-                {
-                    if let Some(resolved) = result.resolved {
-                        // Gather telemetry of this resolved env and see what we got wrong.
-                        let jsonrpc_reporter = jsonrpc::create_reporter(None);
-                        let _ = report_inaccuracies_identified_after_resolving(
-                            &jsonrpc_reporter,
-                            &result.discovered,
-                            &resolved,
-                        );
-
-                        trace!(
-                            "Resolved env ({:?}) {executable:?} as {resolved:?}",
-                            now.elapsed()
-                        );
-                        send_reply(id, resolved.into());
-                    } else {
-                        error!(
-                            "Failed to resolve env {executable:?}, returning discovered env {:?}",
-                            result.discovered
+pub fn handle_platform_tags(context: Arc<Context>, id: u32, params: Value) {
+    match serde_json::from_value::<PlatformTagsOptions>(params.clone()) {
+        Ok(options) => {
+            spawn_request(context, id, move |context| {
+                let executable = options.executable;
+                // Paths of the image when searching an alternate root directory.
+                let root_directory = context
+                    .configuration
+                    .read()
+                    .unwrap()
+                    .config
+                    .root_directory
+                    .clone();
+                let file_system = root::create_file_system(root_directory.as_deref());
+                let executable_on_host = file_system.to_host_path(&executable);
+                match get_platform_tags_in(
+                    file_system.as_ref(),
+                    &executable_on_host,
+                    options
+                        .prefix
+                        .map(|prefix| file_system.to_host_path(&prefix))
+                        .as_deref(),
+                    options.version.as_ref(),
+                ) {
+                    Some(tags) => send_reply(id, tags.into()),
+                    None => {
+                        error!("Failed to compute platform tags of {executable:?}");
+                        let code = if executable_on_host.exists() {
+                            ErrorCode::NotAPython
+                        } else {
+                            ErrorCode::NotFound
+                        };
+                        send_error(
+                            Some(id),
+                            code,
+                            format!("Failed to compute platform tags of {executable:?}"),
+                            Some(ErrorData {
+                                path: Some(executable),
+                                cause: None,
+                            }),
                         );
-                        send_reply(id, result.discovered.into());
                     }
-                } else {
-                    error!("Failed to resolve env {executable:?}");
-                    send_error(
-                        Some(id),
-                        -4,
-                        format!("Failed to resolve env {executable:?}"),
-                    );
                 }
             });
         }
         Err(e) => {
-            error!("Failed to parse resolve {params:?}: {e}");
+            error!("Failed to parse platformTags {params:?}: {e}");
             send_error(
                 Some(id),
-                -4,
-                format!("Failed to parse resolve {params:?}: {e}"),
+                ErrorCode::InvalidParams,
+                format!("Failed to parse platformTags {params:?}: {e}"),
+                Some(ErrorData::new(None, e)),
             );
         }
     }
 }
 
+fn get_environment_platform_tags(
+    file_system: &dyn FileSystem,
+    env: &PythonEnvironment,
+) -> Option<PlatformTags> {
+    get_platform_tags_in(
+        file_system,
+        env.executable.as_deref()?,
+        env.prefix.as_deref(),
+        env.version.as_ref(),
+    )
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FindOptions {
@@ -1324,38 +2879,44 @@ pub struct FindOptions {
 }
 
 pub fn handle_find(context: Arc<Context>, id: u32, params: Value) {
-    thread::spawn(
-        move || match serde_json::from_value::<FindOptions>(params.clone()) {
+    spawn_request(context, id, move |context| {
+        match serde_json::from_value::<FindOptions>(params.clone()) {
             Ok(find_options) => {
                 let now = Instant::now();
                 trace!("Finding environments in {:?}", find_options.search_path);
+                let search_context = SearchContext::new(context.as_ref());
+                let search_path = search_context.to_host_path(&find_options.search_path);
                 let global_env_search_paths: Vec<PathBuf> =
-                    get_search_paths_from_env_variables(context.os_environment.as_ref());
+                    get_search_paths_from_env_variables(search_context.environment.as_ref());
+
+                let file_system: Arc<dyn FileSystem> = Arc::new(CachedFileSystem::new(
+                    search_context.environment.file_system(),
+                ));
 
                 let collect_reporter = Arc::new(collect::create_reporter());
-                let reporter = CacheReporter::new(collect_reporter.clone());
-                if find_options.search_path.is_file() {
+                let reporter = CacheReporter::new(Arc::new(LineageReporter::new(
+                    search_context.create_reporter(collect_reporter.clone()),
+                    search_context.locators.clone(),
+                    search_context.file_system.clone(),
+                )));
+                if search_path.is_file() {
                     identify_python_executables_using_locators(
-                        vec![find_options.search_path.clone()],
-                        &context.locators,
+                        vec![search_path],
+                        &search_context.locators,
                         &reporter,
                         &global_env_search_paths,
+                        &file_system,
                     );
                 } else {
+                    let config = context.configuration.read().unwrap().config.clone();
                     find_python_environments_in_workspace_folder_recursive(
-                        &find_options.search_path,
+                        &search_path,
                         &reporter,
-                        &context.locators,
+                        &search_context.locators,
                         &global_env_search_paths,
-                        context
-                            .configuration
-                            .read()
-                            .unwrap()
-                            .config
-                            .clone()
-                            .environment_directories
-                            .as_deref()
-                            .unwrap_or(&[]),
+                        config.environment_directories.as_deref().unwrap_or(&[]),
+                        WorkspaceSearchLimits::from_configuration(&config),
+                        &file_system,
                     );
                 }
 
@@ -1380,16 +2941,84 @@ pub fn handle_find(context: Arc<Context>, id: u32, params: Value) {
                 error!("Failed to parse find {params:?}: {e}");
                 send_error(
                     Some(id),
-                    -4,
+                    ErrorCode::InvalidParams,
                     format!("Failed to parse find {params:?}: {e}"),
+                    Some(ErrorData::new(None, e)),
                 );
             }
-        },
+        }
+    });
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentGraph {
+    /// Environments that were not created from another environment,
+    /// with the environments created from them nested as children.
+    pub roots: Vec<EnvironmentGraphNode>,
+}
+
+pub fn handle_environment_graph(context: Arc<Context>, id: u32, _params: Value) {
+    spawn_request(context, id, move |context| {
+        let now = Instant::now();
+        // The environments of the last refresh already have their base environments,
+        // only search for the environments when there has been no refresh (since the last `configure`).
+        let environments = match get_last_refresh(context.as_ref()) {
+            Some(snapshot) => snapshot.environments.clone(),
+            None => find_environments_with_lineage(context.as_ref()),
+        };
+        trace!(
+            "Environment graph of {} environments built in {:?}",
+            environments.len(),
+            now.elapsed()
+        );
+        send_reply(
+            id,
+            Some(EnvironmentGraph {
+                roots: build_environment_graph(&environments),
+            }),
+        );
+    });
+}
+
+/// Finds the environments along with their base environments.
+fn find_environments_with_lineage(context: &Context) -> Vec<PythonEnvironment> {
+    let configuration_state = context.configuration.read().unwrap().clone();
+    let environment: Arc<dyn Environment> =
+        match configuration_state.config.root_directory.as_deref() {
+            Some(root) => Arc::new(RootedEnvironment::new(root.to_path_buf())),
+            None => context.os_environment.clone(),
+        };
+    // Use a transient set of locators, as a refresh does, so the state of the shared locators is left untouched.
+    let refresh_locators =
+        create_refresh_locators(environment.as_ref(), context.conda_locator.as_ref());
+    configure_locators(&refresh_locators.locators, &configuration_state.config);
+
+    let collect_reporter = Arc::new(collect::create_reporter());
+    let reporter = CacheReporter::new(Arc::new(LineageReporter::new(
+        root::create_reporter(collect_reporter.clone(), environment.as_ref()),
+        refresh_locators.locators.clone(),
+        environment.file_system(),
+    )));
+    find_and_report_envs(
+        &reporter,
+        configuration_state.config.clone(),
+        &refresh_locators.locators,
+        environment.as_ref(),
+        None,
+        None,
     );
+
+    let environments = collect_reporter
+        .environments
+        .lock()
+        .expect("environments mutex poisoned")
+        .clone();
+    environments
 }
 
 pub fn handle_conda_telemetry(context: Arc<Context>, id: u32, _params: Value) {
-    thread::spawn(move || {
+    spawn_request(context, id, move |context| {
         trace!("Gathering conda telemetry");
         let conda_locator = context.conda_locator.clone();
         let conda_executable = context
@@ -1405,11 +3034,16 @@ pub fn handle_conda_telemetry(context: Arc<Context>, id: u32, _params: Value) {
     });
 }
 
-pub fn handle_clear_cache(_context: Arc<Context>, id: u32, _params: Value) {
-    thread::spawn(move || {
+pub fn handle_clear_cache(context: Arc<Context>, id: u32, _params: Value) {
+    spawn_request(context, id, move |_context| {
         if let Err(e) = clear_cache() {
             error!("Failed to clear cache {:?}", e);
-            send_error(Some(id), -4, format!("Failed to clear cache {e:?}"));
+            send_error(
+                Some(id),
+                ErrorCode::InternalError,
+                format!("Failed to clear cache {e:?}"),
+                Some(ErrorData::new(None, e)),
+            );
         } else {
             info!("Cleared cache");
             send_reply(id, None::<()>);
@@ -1433,6 +3067,12 @@ pub(crate) fn build_refresh_config(
     // don't have global locations - they only exist in workspace folders.
     // The reporter will filter results to only report the requested kind.
     if let Some(ref search_paths) = refresh_options.search_paths {
+        // Paths of the image when searching an alternate root directory.
+        let file_system = root::create_file_system(config.root_directory.as_deref());
+        let search_paths: Vec<PathBuf> = search_paths
+            .iter()
+            .map(|path| file_system.to_host_path(path))
+            .collect();
         // Clear workspace directories when explicit search paths are provided.
         config.workspace_directories = None;
         // These workspace folders are only for this refresh.
@@ -1650,6 +3290,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_resolve_options_include_platform_tags_is_optional() {
+        let options: ResolveOptions =
+            serde_json::from_value(json!({ "executable": "/usr/bin/python3" })).unwrap();
+        assert_eq!(options.include_platform_tags, None);
+
+        let options: ResolveOptions = serde_json::from_value(json!({
+            "executable": "/usr/bin/python3",
+            "includePlatformTags": true
+        }))
+        .unwrap();
+        assert_eq!(options.include_platform_tags, Some(true));
+    }
+
+    #[test]
+    fn test_resolve_many_options_and_notification_format() {
+        let options: ResolveManyOptions = serde_json::from_value(json!({
+            "executables": ["/usr/bin/python3", "/usr/bin/python3.12"],
+            "concurrency": 2
+        }))
+        .unwrap();
+        assert_eq!(options.executables.len(), 2);
+        assert_eq!(options.include_platform_tags, None);
+        assert_eq!(options.concurrency, Some(2));
+
+        let notification = ResolvedNotification {
+            executables: vec![PathBuf::from("/missing/python")],
+            environment: None,
+            cache_hit: false,
+            error: Some("Executable does not exist".to_string()),
+        };
+        assert_eq!(
+            serde_json::to_value(notification).unwrap(),
+            json!({
+                "executables": ["/missing/python"],
+                "cacheHit": false,
+                "error": "Executable does not exist"
+            })
+        );
+    }
+
     #[test]
     fn test_info_response_uses_package_version_and_optional_build_metadata() {
         let info = InfoResponse::current();
@@ -1698,7 +3379,7 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_refresh_options_canonicalizes_search_paths() {
+    fn test_parse_refresh_options_canonicalizes_search_paths_and_kinds() {
         let temp_dir = tempfile::tempdir().unwrap();
         let alpha = temp_dir.path().join("alpha");
         let beta = temp_dir.path().join("beta");
@@ -1708,6 +3389,11 @@ mod tests {
         let options = canonicalize_refresh_options(RefreshOptions {
             search_kind: Some(PythonEnvironmentKind::Venv),
             search_paths: Some(vec![beta.clone(), temp_dir.path().join("*"), alpha.clone()]),
+            exclude_kinds: Some(vec![
+                PythonEnvironmentKind::Tox,
+                PythonEnvironmentKind::Nox,
+                PythonEnvironmentKind::Tox,
+            ]),
         });
 
         assert_eq!(
@@ -1715,6 +3401,7 @@ mod tests {
             RefreshOptions {
                 search_kind: Some(PythonEnvironmentKind::Venv),
                 search_paths: Some(vec![norm_case(alpha), norm_case(beta)]),
+                exclude_kinds: Some(vec![PythonEnvironmentKind::Nox, PythonEnvironmentKind::Tox]),
             }
         );
     }
@@ -1724,6 +3411,7 @@ mod tests {
         let configuration = RwLock::new(ConfigurationState {
             generation: 4,
             config: Configuration::default(),
+            ..Default::default()
         });
         let mut synced = false;
 
@@ -1741,6 +3429,7 @@ mod tests {
         let configuration = Arc::new(RwLock::new(ConfigurationState {
             generation: 1,
             config: Configuration::default(),
+            ..Default::default()
         }));
         let inner = Arc::new(RecordingReporter::default());
         let reporter = GenerationGuardedReporter::new(inner.clone(), configuration.clone(), 1);
@@ -1750,7 +3439,7 @@ mod tests {
             Some(PythonEnvironmentKind::Venv),
             Some(PathBuf::from("/tmp")),
             None,
-            Some("3.11.0".to_string()),
+            Some("3.11.0".parse().unwrap()),
         );
         let manager = EnvManager {
             executable: PathBuf::from("/tmp/conda"),
@@ -1760,7 +3449,9 @@ mod tests {
         let telemetry = TelemetryEvent::RefreshPerformance(RefreshPerformance {
             total: 1,
             locators: BTreeMap::new(),
+            locators_queue_wait: BTreeMap::new(),
             breakdown: BTreeMap::new(),
+            workspace_directories_visited: 0,
         });
 
         reporter.report_environment(&environment);
@@ -1787,6 +3478,7 @@ mod tests {
         let configuration = Arc::new(RwLock::new(ConfigurationState {
             generation: 7,
             config: Configuration::default(),
+            ..Default::default()
         }));
         let inner = Arc::new(LockCheckingReporter {
             configuration: configuration.clone(),
@@ -1797,7 +3489,9 @@ mod tests {
         reporter.report_telemetry(&TelemetryEvent::RefreshPerformance(RefreshPerformance {
             total: 1,
             locators: BTreeMap::new(),
+            locators_queue_wait: BTreeMap::new(),
             breakdown: BTreeMap::new(),
+            workspace_directories_visited: 0,
         }));
 
         assert!(*inner.reported.lock().unwrap());
@@ -1835,6 +3529,15 @@ mod tests {
                         poetry_executable: None,
                         environment_directories: None,
                         cache_directory: None,
+                        external_locators: None,
+                        resolve_timeout: None,
+                        trace_file: None,
+                        trace_file_format: None,
+                        root_directory: None,
+                        discovery_threads: None,
+                        network_filesystem_policy: None,
+                        cross_mount_boundaries: None,
+                        workspace_search_depth: None,
                     },
                     Some(workspace_directories),
                     None,
@@ -1894,6 +3597,15 @@ mod tests {
                 poetry_executable: None,
                 environment_directories: None,
                 cache_directory: None,
+                external_locators: None,
+                resolve_timeout: None,
+                trace_file: None,
+                trace_file_format: None,
+                root_directory: None,
+                discovery_threads: None,
+                network_filesystem_policy: None,
+                cross_mount_boundaries: None,
+                workspace_search_depth: None,
             },
             Some(workspace_directories),
             None,
@@ -1937,6 +3649,15 @@ mod tests {
                 poetry_executable: None,
                 environment_directories: None,
                 cache_directory: None,
+                external_locators: None,
+                resolve_timeout: None,
+                trace_file: None,
+                trace_file_format: None,
+                root_directory: None,
+                discovery_threads: None,
+                network_filesystem_policy: None,
+                cross_mount_boundaries: None,
+                workspace_search_depth: None,
             },
             Some(vec![PathBuf::from("/workspace")]),
             None,
@@ -1961,6 +3682,7 @@ mod tests {
         let configuration = RwLock::new(ConfigurationState {
             generation: 2,
             config: Configuration::default(),
+            ..Default::default()
         });
 
         assert!(!try_begin_missing_env_reporting_with_state(
@@ -1980,6 +3702,7 @@ mod tests {
         let configuration = RwLock::new(ConfigurationState {
             generation: 3,
             config: Configuration::default(),
+            ..Default::default()
         });
 
         release_missing_env_reporting_if_stale_with_state(&reporting_state, &configuration, 2);
@@ -1996,6 +3719,7 @@ mod tests {
         let configuration = RwLock::new(ConfigurationState {
             generation: 2,
             config: Configuration::default(),
+            ..Default::default()
         });
 
         assert!(try_begin_missing_env_reporting_with_state(
@@ -2032,6 +3756,7 @@ mod tests {
             RefreshOptions {
                 search_kind: Some(PythonEnvironmentKind::Venv),
                 search_paths: None,
+                exclude_kinds: None,
             },
         );
 
@@ -2088,7 +3813,7 @@ mod tests {
                 Some(PythonEnvironmentKind::Conda),
                 Some(stale_env_path.clone()),
                 None,
-                Some("3.10.0".to_string()),
+                Some("3.10.0".parse().unwrap()),
             ),
         );
         shared.managers.insert(
@@ -2117,7 +3842,7 @@ mod tests {
                 Some(PythonEnvironmentKind::Conda),
                 Some(fresh_env_path.clone()),
                 None,
-                Some("3.11.0".to_string()),
+                Some("3.11.0".parse().unwrap()),
             ),
         );
         refreshed.managers.insert(
@@ -2175,7 +3900,7 @@ mod tests {
                 Some(PythonEnvironmentKind::Conda),
                 Some(stale_env_path.clone()),
                 None,
-                Some("3.10.0".to_string()),
+                Some("3.10.0".parse().unwrap()),
             ),
         );
         refreshed.environments.insert(
@@ -2185,7 +3910,7 @@ mod tests {
                 Some(PythonEnvironmentKind::Conda),
                 Some(fresh_env_path.clone()),
                 None,
-                Some("3.11.0".to_string()),
+                Some("3.11.0".parse().unwrap()),
             ),
         );
 
@@ -2216,7 +3941,7 @@ mod tests {
                 Some(PythonEnvironmentKind::Conda),
                 Some(stale_env_path.clone()),
                 None,
-                Some("3.10.0".to_string()),
+                Some("3.10.0".parse().unwrap()),
             ),
         );
         refreshed.environments.insert(
@@ -2226,7 +3951,7 @@ mod tests {
                 Some(PythonEnvironmentKind::Conda),
                 Some(fresh_env_path.clone()),
                 None,
-                Some("3.11.0".to_string()),
+                Some("3.11.0".parse().unwrap()),
             ),
         );
 
@@ -2246,6 +3971,7 @@ mod tests {
         let configuration = RwLock::new(ConfigurationState {
             generation: 2,
             config: Configuration::default(),
+            ..Default::default()
         });
         let mut synced = false;
 
@@ -2290,6 +4016,8 @@ mod tests {
                 RefreshStatePersistence::SelfHydratingCache,
             ),
             (LocatorKind::Pixi, RefreshStatePersistence::Stateless),
+            (LocatorKind::Tox, RefreshStatePersistence::Stateless),
+            (LocatorKind::Nox, RefreshStatePersistence::Stateless),
             (
                 LocatorKind::Conda,
                 RefreshStatePersistence::SyncedDiscoveryState,
@@ -2326,6 +4054,10 @@ mod tests {
                 LocatorKind::LinuxGlobal,
                 RefreshStatePersistence::SelfHydratingCache,
             ),
+            (
+                LocatorKind::External,
+                RefreshStatePersistence::SelfHydratingCache,
+            ),
         ];
 
         assert_eq!(actual, expected);
@@ -2388,6 +4120,7 @@ mod tests {
             RefreshOptions {
                 search_kind: Some(PythonEnvironmentKind::Venv),
                 search_paths: None,
+                exclude_kinds: None,
             },
         );
 
@@ -2439,6 +4172,7 @@ mod tests {
             RefreshOptions {
                 search_kind: Some(PythonEnvironmentKind::Venv),
                 search_paths: None,
+                exclude_kinds: None,
             },
         );
 
@@ -2499,6 +4233,7 @@ mod tests {
         let refresh_options = RefreshOptions {
             search_kind: Some(PythonEnvironmentKind::Venv),
             search_paths: None,
+            exclude_kinds: None,
         };
 
         let (result_config, search_scope) = build_refresh_config(&refresh_options, config);
@@ -2536,6 +4271,7 @@ mod tests {
         let refresh_options = RefreshOptions {
             search_kind: None,
             search_paths: Some(vec![search_dir.clone()]),
+            exclude_kinds: None,
         };
 
         let (result_config, search_scope) = build_refresh_config(&refresh_options, config);
@@ -2566,6 +4302,7 @@ mod tests {
         let refresh_options = RefreshOptions {
             search_kind: None,
             search_paths: None,
+            exclude_kinds: None,
         };
 
         let (result_config, search_scope) = build_refresh_config(&refresh_options, config);
@@ -2594,6 +4331,7 @@ mod tests {
         let refresh_options = RefreshOptions {
             search_kind: None,
             search_paths: Some(vec![workspace_dir.clone(), executable.clone()]),
+            exclude_kinds: None,
         };
 
         let (result_config, search_scope) = build_refresh_config(&refresh_options, config);
@@ -2642,6 +4380,7 @@ mod tests {
             RefreshOptions {
                 search_kind: Some(PythonEnvironmentKind::Venv),
                 search_paths: None,
+                exclude_kinds: None,
             },
         );
 
@@ -2715,6 +4454,7 @@ mod tests {
         let configuration = Arc::new(RwLock::new(ConfigurationState {
             generation: 1,
             config: Configuration::default(),
+            ..Default::default()
         }));
 
         assert!(try_begin_missing_env_reporting_with_state(
@@ -2773,6 +4513,15 @@ mod tests {
                         poetry_executable: None,
                         environment_directories: None,
                         cache_directory: None,
+                        external_locators: None,
+                        resolve_timeout: None,
+                        trace_file: None,
+                        trace_file_format: None,
+                        root_directory: None,
+                        discovery_threads: None,
+                        network_filesystem_policy: None,
+                        cross_mount_boundaries: None,
+                        workspace_search_depth: None,
                     },
                     Some(vec![PathBuf::from("/workspace")]),
                     None,
@@ -2813,7 +4562,7 @@ mod tests {
                     Some(PythonEnvironmentKind::Venv),
                     Some(PathBuf::from("/tmp")),
                     None,
-                    Some("3.11.0".to_string()),
+                    Some("3.11.0".parse().unwrap()),
                 );
                 reporter.report_environment(&env);
 
@@ -2926,6 +4675,15 @@ mod tests {
                         poetry_executable: None,
                         environment_directories: None,
                         cache_directory: None,
+                        external_locators: None,
+                        resolve_timeout: None,
+                        trace_file: None,
+                        trace_file_format: None,
+                        root_directory: None,
+                        discovery_threads: None,
+                        network_filesystem_policy: None,
+                        cross_mount_boundaries: None,
+                        workspace_search_depth: None,
                     },
                     Some(vec![PathBuf::from("/workspace/a")]),
                     None,
@@ -2950,6 +4708,15 @@ mod tests {
                         poetry_executable: None,
                         environment_directories: None,
                         cache_directory: None,
+                        external_locators: None,
+                        resolve_timeout: None,
+                        trace_file: None,
+                        trace_file_format: None,
+                        root_directory: None,
+                        discovery_threads: None,
+                        network_filesystem_policy: None,
+                        cross_mount_boundaries: None,
+                        workspace_search_depth: None,
                     },
                     Some(vec![PathBuf::from("/workspace/b")]),
                     None,
@@ -3028,6 +4795,15 @@ mod tests {
                 poetry_executable: None,
                 environment_directories: None,
                 cache_directory: None,
+                external_locators: None,
+                resolve_timeout: None,
+                trace_file: None,
+                trace_file_format: None,
+                root_directory: None,
+                discovery_threads: None,
+                network_filesystem_policy: None,
+                cross_mount_boundaries: None,
+                workspace_search_depth: None,
             },
             Some(vec![PathBuf::from("/workspace")]),
             None,
@@ -3051,4 +4827,512 @@ mod tests {
         // acquire it without blocking.
         assert!(configure_in_progress.try_lock().is_ok());
     }
+
+    #[test]
+    fn resolve_searches_the_root_directory_and_reports_paths_of_the_image() {
+        let root = tempfile::tempdir().unwrap();
+        let venv = root.path().join("home").join("user").join(".venv");
+        let bin = venv.join(if cfg!(windows) { "Scripts" } else { "bin" });
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::write(
+            venv.join("pyvenv.cfg"),
+            "home = /usr/bin\nversion = 3.12.1\n",
+        )
+        .unwrap();
+        std::fs::write(bin.join("python"), "").unwrap();
+
+        let context = create_context(ServerMode::Stdio);
+        context.configuration.write().unwrap().config.root_directory =
+            Some(root.path().to_path_buf());
+        let executable = Path::new("/home/user/.venv")
+            .join(if cfg!(windows) { "Scripts" } else { "bin" })
+            .join("python");
+        let outcome = resolve_executable(&SearchContext::new(&context), &executable, false);
+
+        let env = match outcome {
+            ResolveOutcome::Resolved(env) | ResolveOutcome::Discovered(env, _) => env,
+            ResolveOutcome::Unknown(failure) => panic!("{failure:?}"),
+        };
+        assert_eq!(env.executable, Some(executable));
+        assert_eq!(env.prefix, Some(PathBuf::from("/home/user/.venv")));
+    }
+
+    #[test]
+    fn resolve_failures_map_to_error_codes() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().join("python");
+        std::fs::write(&existing, "").unwrap();
+        let missing = dir.path().join("missing");
+        let failure = |kind| SpawnFailure {
+            kind,
+            message: "".to_string(),
+        };
+
+        assert_eq!(
+            get_resolve_error_code(&missing, &failure(SpawnFailureKind::NotExecutable)),
+            ErrorCode::NotFound
+        );
+        assert_eq!(
+            get_resolve_error_code(&existing, &failure(SpawnFailureKind::NotExecutable)),
+            ErrorCode::NotAPython
+        );
+        assert_eq!(
+            get_resolve_error_code(&existing, &failure(SpawnFailureKind::BadOutput)),
+            ErrorCode::NotAPython
+        );
+        assert_eq!(
+            get_resolve_error_code(&existing, &failure(SpawnFailureKind::Timeout)),
+            ErrorCode::SpawnFailed
+        );
+        assert_eq!(
+            get_resolve_error_code(&existing, &failure(SpawnFailureKind::Signal)),
+            ErrorCode::SpawnFailed
+        );
+    }
+
+    #[test]
+    fn initialize_result_lists_unique_methods_and_features() {
+        let result = serde_json::to_value(InitializeResult::current()).unwrap();
+
+        assert_eq!(result["protocolVersion"], PROTOCOL_VERSION);
+        assert_eq!(
+            result["serverInfo"]["petVersion"],
+            env!("CARGO_PKG_VERSION")
+        );
+        let requests: Vec<&str> = result["requests"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|method| method.as_str().unwrap())
+            .collect();
+        let mut unique = requests.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), requests.len());
+        for method in ["initialize", "shutdown", "configure", "refresh", "resolve"] {
+            assert!(requests.contains(&method), "{method} is not listed");
+        }
+        assert_eq!(result["notifications"], serde_json::json!(["exit"]));
+        assert_eq!(
+            result["features"],
+            serde_json::json!({ "watch": false, "cancellation": false, "packages": false })
+        );
+    }
+
+    fn shared_refresh_key(workspace_directory: &str) -> SharedRefreshKey {
+        SharedRefreshKey {
+            options: RefreshOptions::default(),
+            config: Configuration {
+                workspace_directories: Some(vec![PathBuf::from(workspace_directory)]),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn shared_refresh_environment(executable: &str) -> PythonEnvironment {
+        PythonEnvironment {
+            executable: Some(PathBuf::from(executable)),
+            kind: Some(PythonEnvironmentKind::Venv),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn shared_refresh_replays_found_environments_to_joining_clients_and_shares_the_outcome() {
+        let shared_refreshes = SharedRefreshes::default();
+        let leader = Arc::new(RecordingReporter::default());
+        let follower = Arc::new(RecordingReporter::default());
+
+        let SharedRefreshRegistration::Lead(lead) =
+            shared_refreshes.register(shared_refresh_key("/workspace"), leader.clone())
+        else {
+            panic!("first client should run the refresh");
+        };
+        let reporter = lead.reporter();
+        reporter.report_environment(&shared_refresh_environment("/workspace/.venv/bin/python"));
+
+        let SharedRefreshRegistration::Follow(refresh) =
+            shared_refreshes.register(shared_refresh_key("/workspace"), follower.clone())
+        else {
+            panic!("second client should join the refresh in progress");
+        };
+        reporter.report_environment(&shared_refresh_environment("/workspace/env/bin/python"));
+        assert_eq!(leader.environments.lock().unwrap().len(), 2);
+        assert_eq!(
+            *follower.environments.lock().unwrap(),
+            *leader.environments.lock().unwrap()
+        );
+
+        let waiter = thread::spawn(move || refresh.wait_for_outcome());
+        let environment = EnvironmentApi::new();
+        let conda_locator = Conda::from(&environment);
+        lead.complete(SharedRefreshOutcome {
+            result: RefreshResult::new(Duration::from_millis(5), 42),
+            perf: RefreshPerformance {
+                total: 5,
+                locators: BTreeMap::new(),
+                locators_queue_wait: BTreeMap::new(),
+                breakdown: BTreeMap::new(),
+                workspace_directories_visited: 0,
+            },
+            locators: create_refresh_locators(&environment, &conda_locator),
+        });
+
+        let outcome = waiter
+            .join()
+            .unwrap()
+            .expect("refresh should have completed");
+        assert_eq!(outcome.result.refresh_id, 42);
+        // Completed refreshes are not joined, the next refresh discovers the environments again.
+        assert!(matches!(
+            shared_refreshes.register(shared_refresh_key("/workspace"), follower),
+            SharedRefreshRegistration::Lead(_)
+        ));
+    }
+
+    #[test]
+    fn shared_refreshes_are_not_joined_by_clients_with_other_configurations() {
+        let shared_refreshes = SharedRefreshes::default();
+        let _lead = shared_refreshes.register(
+            shared_refresh_key("/workspace"),
+            Arc::new(RecordingReporter::default()),
+        );
+
+        assert!(matches!(
+            shared_refreshes.register(
+                shared_refresh_key("/other"),
+                Arc::new(RecordingReporter::default())
+            ),
+            SharedRefreshRegistration::Lead(_)
+        ));
+    }
+
+    #[test]
+    fn followers_of_a_failed_shared_refresh_get_no_outcome() {
+        let shared_refreshes = SharedRefreshes::default();
+        let lead = shared_refreshes.register(
+            shared_refresh_key("/workspace"),
+            Arc::new(RecordingReporter::default()),
+        );
+        let SharedRefreshRegistration::Follow(refresh) = shared_refreshes.register(
+            shared_refresh_key("/workspace"),
+            Arc::new(RecordingReporter::default()),
+        ) else {
+            panic!("second client should join the refresh in progress");
+        };
+
+        // Dropping the lead without completing it, as happens when the refresh panics.
+        drop(lead);
+
+        assert!(refresh.wait_for_outcome().is_none());
+    }
+
+    #[test]
+    fn missing_env_reporting_is_tracked_per_configuration() {
+        let first = RwLock::new(ConfigurationState::default());
+        let second = RwLock::new(ConfigurationState::default());
+
+        assert!(try_begin_missing_env_reporting(&first, 0));
+        complete_missing_env_reporting(&first, 0);
+
+        assert!(!try_begin_missing_env_reporting(&first, 0));
+        assert!(try_begin_missing_env_reporting(&second, 0));
+    }
+
+    #[test]
+    fn daemon_clients_cannot_configure_different_process_settings() {
+        let options = |value: Value| serde_json::from_value::<ConfigureOptions>(value).unwrap();
+        let settings = ClientSettings::default();
+
+        settings
+            .configure(1, &options(json!({ "cacheDirectory": "/cache" })))
+            .unwrap();
+        // Options not sent keep the value of the other client (cache) or are the same default.
+        settings.configure(2, &options(json!({}))).unwrap();
+        // Each client searches its own root directory.
+        settings
+            .configure(2, &options(json!({ "rootDirectory": "/image" })))
+            .unwrap();
+        let error = settings
+            .configure(2, &options(json!({ "cacheDirectory": "/other" })))
+            .unwrap_err();
+        assert!(error.starts_with("cacheDirectory"), "{error}");
+        // Nor can the first client change them while the other one is connected.
+        settings
+            .configure(1, &options(json!({ "resolveTimeout": 1000 })))
+            .unwrap_err();
+        settings.disconnected(2);
+        settings
+            .configure(1, &options(json!({ "resolveTimeout": 1000 })))
+            .unwrap();
+
+        settings.disconnected(1);
+        settings
+            .configure(3, &options(json!({ "resolveTimeout": 2000 })))
+            .unwrap();
+    }
+
+    #[test]
+    fn requests_in_progress_are_waited_for() {
+        let context = Arc::new(create_context(ServerMode::Stdio));
+        let (release, released) = std::sync::mpsc::channel::<()>();
+        spawn_request(context.clone(), 1, move |_context| {
+            released.recv().unwrap();
+        });
+
+        let (done, waited) = std::sync::mpsc::channel();
+        let waiting_context = context.clone();
+        std::thread::spawn(move || {
+            wait_for_requests_in_progress(&waiting_context);
+            done.send(()).unwrap();
+        });
+        assert!(waited.recv_timeout(Duration::from_millis(200)).is_err());
+
+        release.send(()).unwrap();
+        waited
+            .recv_timeout(Duration::from_secs(5))
+            .expect("the wait should end once the request completes");
+    }
+
+    /// Connection writing the messages to a buffer, along with a function returning the messages written.
+    fn create_buffered_connection() -> (Connection, impl Fn() -> Vec<Value>) {
+        #[derive(Clone, Default)]
+        struct Buffer(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for Buffer {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let buffer = Buffer::default();
+        let connection = Connection::new(Box::new(buffer.clone()), || {});
+        let messages = move || {
+            let written = buffer.0.lock().unwrap().clone();
+            let mut reader = written.as_slice();
+            let mut messages = vec![];
+            while let Some(message) = pet_jsonrpc::transport::read_message(&mut reader).unwrap() {
+                messages.push(serde_json::from_str(&message).unwrap());
+            }
+            messages
+        };
+        (connection, messages)
+    }
+
+    #[test]
+    fn requests_that_panic_are_replied_to_with_an_internal_error() {
+        let context = Arc::new(create_context(ServerMode::Stdio));
+        let (connection, messages) = create_buffered_connection();
+
+        with_connection(&connection, || {
+            spawn_request(context.clone(), 7, |_context| panic!("boom"));
+        });
+        wait_for_requests_in_progress(&context);
+
+        let message = &messages()[0];
+        assert_eq!(message["id"], 7);
+        assert_eq!(message["error"]["code"], ErrorCode::InternalError.code());
+        assert!(message["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("boom"));
+    }
+
+    #[test]
+    fn daemon_clients_must_send_the_token_in_initialize() {
+        let daemon = Arc::new(DaemonState {
+            token: "secret".to_string(),
+            ..Default::default()
+        });
+        let context = Arc::new(create_context(ServerMode::Daemon(daemon)));
+        let (connection, messages) = create_buffered_connection();
+
+        with_connection(&connection, || {
+            handle_initialize(context.clone(), 1, json!({ "token": "guess" }));
+            assert!(!is_authenticated(&context));
+            handle_initialize(context.clone(), 2, Value::Null);
+            assert!(!is_authenticated(&context));
+            handle_initialize(context.clone(), 3, json!({ "token": "secret" }));
+            assert!(is_authenticated(&context));
+        });
+
+        let messages = messages();
+        assert_eq!(
+            messages[0]["error"]["code"],
+            ErrorCode::InvalidParams.code()
+        );
+        assert_eq!(
+            messages[1]["error"]["code"],
+            ErrorCode::InvalidParams.code()
+        );
+        assert_eq!(messages[2]["id"], 3);
+        assert!(messages[2]["result"]["protocolVersion"].is_string());
+        assert!(is_authenticated(&create_context(ServerMode::Stdio)));
+    }
+
+    #[test]
+    fn last_refresh_is_only_used_for_the_configuration_it_ran_with() {
+        let context = create_context(ServerMode::Stdio);
+        let collect_reporter = collect::create_reporter();
+        collect_reporter
+            .report_environment(&shared_refresh_environment("/workspace/.venv/bin/python"));
+        assert!(get_last_refresh(&context).is_none());
+
+        store_refresh_snapshot(
+            &context,
+            0,
+            &RefreshResult::new(Duration::from_millis(5), 7),
+            &collect_reporter,
+        );
+        let snapshot = get_last_refresh(&context).expect("refresh should be kept");
+        assert_eq!(snapshot.refresh_id, 7);
+        assert_eq!(snapshot.environments.len(), 1);
+
+        context.configuration.write().unwrap().generation = 1;
+        assert!(get_last_refresh(&context).is_none());
+
+        // A refresh that completes after a configure is not kept.
+        store_refresh_snapshot(
+            &context,
+            0,
+            &RefreshResult::new(Duration::from_millis(5), 8),
+            &collect_reporter,
+        );
+        assert!(get_last_refresh(&context).is_none());
+    }
+
+    #[test]
+    fn only_unfiltered_refreshes_are_kept_as_the_last_refresh() {
+        assert!(is_unfiltered_refresh(&RefreshOptions::default()));
+        assert!(is_unfiltered_refresh(&RefreshOptions {
+            exclude_kinds: Some(vec![]),
+            ..Default::default()
+        }));
+        assert!(!is_unfiltered_refresh(&RefreshOptions {
+            search_kind: Some(PythonEnvironmentKind::Conda),
+            ..Default::default()
+        }));
+        assert!(!is_unfiltered_refresh(&RefreshOptions {
+            search_paths: Some(vec![PathBuf::from("/workspace")]),
+            ..Default::default()
+        }));
+        assert!(!is_unfiltered_refresh(&RefreshOptions {
+            exclude_kinds: Some(vec![PythonEnvironmentKind::Conda]),
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn follow_ups_of_an_older_refresh_do_not_replace_the_last_refresh() {
+        let context = create_context(ServerMode::Stdio);
+        let collect_reporter = collect::create_reporter();
+        store_refresh_snapshot(
+            &context,
+            0,
+            &RefreshResult::new(Duration::from_millis(5), 7),
+            &collect_reporter,
+        );
+        // The missing environments of the refresh are reported after it completed.
+        collect_reporter.report_environment(&shared_refresh_environment(
+            "/conda/envs/missing/bin/python",
+        ));
+        store_refresh_snapshot(
+            &context,
+            0,
+            &RefreshResult::new(Duration::from_millis(5), 7),
+            &collect_reporter,
+        );
+        let snapshot = get_last_refresh(&context).unwrap();
+        assert_eq!(snapshot.environments.len(), 1);
+
+        store_refresh_snapshot(
+            &context,
+            0,
+            &RefreshResult::new(Duration::from_millis(5), 9),
+            &collect::create_reporter(),
+        );
+        store_refresh_snapshot(
+            &context,
+            0,
+            &RefreshResult::new(Duration::from_millis(5), 7),
+            &collect_reporter,
+        );
+        let snapshot = get_last_refresh(&context).unwrap();
+        assert_eq!(snapshot.refresh_id, 9);
+        assert!(snapshot.environments.is_empty());
+    }
+
+    #[test]
+    fn last_refresh_environments_are_filtered_like_a_refresh() {
+        let workspace_env = PythonEnvironment {
+            project: Some(PathBuf::from("/workspace")),
+            ..shared_refresh_environment("/workspace/.venv/bin/python")
+        };
+        let global_env = PythonEnvironment {
+            kind: Some(PythonEnvironmentKind::Conda),
+            prefix: Some(PathBuf::from("/conda")),
+            ..shared_refresh_environment("/conda/bin/python")
+        };
+
+        let options = RefreshOptions {
+            search_kind: Some(PythonEnvironmentKind::Conda),
+            search_paths: None,
+            exclude_kinds: None,
+        };
+        assert!(!environment_matches_refresh_options(
+            &workspace_env,
+            &options
+        ));
+        assert!(environment_matches_refresh_options(&global_env, &options));
+
+        let options = RefreshOptions {
+            search_kind: None,
+            search_paths: Some(vec![PathBuf::from("/workspace")]),
+            exclude_kinds: None,
+        };
+        assert!(environment_matches_refresh_options(
+            &workspace_env,
+            &options
+        ));
+        assert!(!environment_matches_refresh_options(&global_env, &options));
+        assert!(environment_matches_refresh_options(
+            &global_env,
+            &RefreshOptions::default()
+        ));
+
+        let options = RefreshOptions {
+            exclude_kinds: Some(vec![PythonEnvironmentKind::Conda]),
+            ..RefreshOptions::default()
+        };
+        assert!(environment_matches_refresh_options(
+            &workspace_env,
+            &options
+        ));
+        assert!(!environment_matches_refresh_options(&global_env, &options));
+    }
+
+    #[test]
+    fn last_refresh_environment_is_found_by_id_or_path() {
+        let env = PythonEnvironment {
+            prefix: Some(PathBuf::from("/workspace/.venv")),
+            symlinks: Some(vec![PathBuf::from("/workspace/.venv/bin/python3")]),
+            ..shared_refresh_environment("/workspace/.venv/bin/python")
+        };
+        let find = |id: Option<&str>, path: Option<&str>| {
+            environment_matches_options(
+                &env,
+                &GetEnvironmentOptions {
+                    id: id.map(PathBuf::from),
+                    path: path.map(PathBuf::from),
+                },
+            )
+        };
+
+        assert!(find(Some("/workspace/.venv/bin/python"), None));
+        assert!(!find(Some("/workspace/.venv"), None));
+        assert!(find(None, Some("/workspace/.venv")));
+        assert!(find(None, Some("/workspace/.venv/bin/python3")));
+        assert!(!find(None, Some("/other/bin/python")));
+    }
 }
@@ -3,12 +3,16 @@
 
 use find::find_and_report_envs;
 use find::SearchScope;
-use locators::create_locators;
+use lineage::LineageReporter;
+use locators::{create_locators, TaggingReporter};
 use pet_conda::Conda;
 use pet_conda::CondaLocator;
 use pet_core::os_environment::Environment;
 use pet_core::python_environment::PythonEnvironmentKind;
+use pet_core::ExternalLocatorConfig;
 use pet_core::Locator;
+use pet_core::LocatorKind;
+use pet_core::NetworkFilesystemPolicy;
 use pet_core::{os_environment::EnvironmentApi, reporter::Reporter, Configuration};
 use pet_fs::glob::expand_glob_patterns;
 use pet_poetry::Poetry;
@@ -19,15 +23,28 @@ use resolve::resolve_environment;
 use serde::Serialize;
 use std::path::PathBuf;
 use std::{collections::BTreeMap, env, sync::Arc, time::SystemTime};
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use tracing_subscriber::{
+    fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer as _,
+};
+use which::which_environment;
 
+pub mod capture;
 pub mod find;
+pub mod finder;
+pub mod lineage;
 pub mod locators;
+pub mod pool;
 pub mod resolve;
+pub mod root;
+#[cfg(feature = "async")]
+pub mod stream;
+pub mod trace;
+pub mod which;
 
 /// Initialize tracing subscriber for performance profiling.
 /// Set RUST_LOG=info or RUST_LOG=pet=debug for more detailed traces.
 /// Set PET_TRACE_FORMAT=json for JSON output (useful for analysis tools).
+/// Spans are also written to the trace file, see `trace::set_trace_file`.
 ///
 /// Note: This replaces the env_logger initialization since tracing-subscriber
 /// provides a log compatibility layer via tracing-log.
@@ -46,20 +63,27 @@ pub fn initialize_tracing(verbose: bool) {
             .map(|v| v == "json")
             .unwrap_or(false);
 
+        // The filter only applies to the output, the trace file records the spans regardless.
         if use_json {
             tracing_subscriber::registry()
-                .with(filter)
-                .with(fmt::layer().json().with_writer(std::io::stderr))
+                .with(
+                    fmt::layer()
+                        .json()
+                        .with_writer(std::io::stderr)
+                        .with_filter(filter),
+                )
+                .with(trace::trace_file_layer())
                 .init();
         } else {
             tracing_subscriber::registry()
-                .with(filter)
                 .with(
                     fmt::layer()
                         .with_target(true)
                         .with_timer(fmt::time::uptime())
-                        .with_writer(std::io::stderr),
+                        .with_writer(std::io::stderr)
+                        .with_filter(filter),
                 )
+                .with(trace::trace_file_layer())
                 .init();
         }
     });
@@ -72,6 +96,7 @@ pub struct FindOptions {
     pub verbose: bool,
     pub report_missing: bool,
     pub search_paths: Option<Vec<PathBuf>>,
+    pub workspace_search_depth: Option<usize>,
     pub workspace_only: bool,
     pub cache_directory: Option<PathBuf>,
     pub kind: Option<PythonEnvironmentKind>,
@@ -80,6 +105,15 @@ pub struct FindOptions {
     pub pipenv_executable: Option<PathBuf>,
     pub poetry_executable: Option<PathBuf>,
     pub environment_directories: Option<Vec<PathBuf>>,
+    pub external_locators: Option<Vec<PathBuf>>,
+    /// Alternate root directory searched instead of `/`, e.g. an unpacked container image.
+    pub root_directory: Option<PathBuf>,
+    /// Number of threads searching for environments.
+    pub discovery_threads: Option<usize>,
+    /// What to do with search roots on network (or FUSE) file systems.
+    pub network_filesystem_policy: Option<NetworkFilesystemPolicy>,
+    /// Whether sub directories of the workspace folders on other file systems (mounts) are searched.
+    pub cross_mount_boundaries: bool,
 }
 
 pub fn find_and_report_envs_stdio(options: FindOptions) {
@@ -91,7 +125,10 @@ pub fn find_and_report_envs_stdio(options: FindOptions) {
     // stdio::initialize_logger would conflict with our tracing subscriber.
 
     let now = SystemTime::now();
-    let config = create_config(&options);
+    // Created first, as the search paths are mapped into the alternate root directory (if any).
+    let environment = root::create_environment(options.root_directory.as_deref());
+    let environment = environment.as_ref();
+    let config = create_config(&options, environment);
     let search_scope = if options.workspace_only {
         Some(SearchScope::Workspace)
     } else {
@@ -101,11 +138,10 @@ pub fn find_and_report_envs_stdio(options: FindOptions) {
     if let Some(cache_directory) = options.cache_directory.clone() {
         set_cache_directory(cache_directory);
     }
-    let environment = EnvironmentApi::new();
-    let conda_locator = Arc::new(Conda::from(&environment));
-    let poetry_locator = Arc::new(Poetry::from(&environment));
+    let conda_locator = Arc::new(Conda::from(environment));
+    let poetry_locator = Arc::new(Poetry::from(environment));
 
-    let locators = create_locators(conda_locator.clone(), poetry_locator.clone(), &environment);
+    let locators = create_locators(conda_locator.clone(), poetry_locator.clone(), environment);
     for locator in locators.iter() {
         locator.configure(&config);
     }
@@ -117,7 +153,7 @@ pub fn find_and_report_envs_stdio(options: FindOptions) {
             config,
             conda_locator.as_ref(),
             poetry_locator.as_ref(),
-            &environment,
+            environment,
             search_scope,
         );
     } else {
@@ -127,7 +163,7 @@ pub fn find_and_report_envs_stdio(options: FindOptions) {
             config,
             conda_locator.as_ref(),
             poetry_locator.as_ref(),
-            &environment,
+            environment,
             search_scope,
         );
 
@@ -135,15 +171,21 @@ pub fn find_and_report_envs_stdio(options: FindOptions) {
     }
 }
 
-fn create_config(options: &FindOptions) -> Configuration {
+fn create_config(options: &FindOptions, environment: &dyn Environment) -> Configuration {
     let mut config = Configuration::default();
 
     let mut search_paths = vec![];
     if let Some(dirs) = options.search_paths.as_ref() {
-        search_paths.extend(expand_glob_patterns(dirs));
+        // When searching an alternate root directory, the search paths are paths of the image.
+        let file_system = environment.file_system();
+        let dirs: Vec<PathBuf> = dirs
+            .iter()
+            .map(|dir| file_system.to_host_path(dir))
+            .collect();
+        search_paths.extend(expand_glob_patterns(&dirs));
     }
-    // If workspace folders have been provided do not add cwd.
-    if search_paths.is_empty() {
+    // If workspace folders have been provided do not add cwd (which is not part of an alternate root).
+    if search_paths.is_empty() && options.root_directory.is_none() {
         if let Ok(cwd) = env::current_dir() {
             search_paths.push(cwd);
         }
@@ -165,6 +207,7 @@ fn create_config(options: &FindOptions) -> Configuration {
             .cloned()
             .collect(),
     );
+    config.workspace_search_depth = options.workspace_search_depth;
 
     config.conda_executable = options.conda_executable.clone();
     config.pipenv_executable = options.pipenv_executable.clone();
@@ -175,6 +218,19 @@ fn create_config(options: &FindOptions) -> Configuration {
             .filter(|p| p.is_dir())
             .collect()
     });
+    config.external_locators = options.external_locators.as_ref().map(|executables| {
+        executables
+            .iter()
+            .map(|executable| ExternalLocatorConfig {
+                executable: executable.clone(),
+                timeout: None,
+            })
+            .collect()
+    });
+    config.root_directory = options.root_directory.clone();
+    config.discovery_threads = options.discovery_threads;
+    config.network_filesystem_policy = options.network_filesystem_policy;
+    config.cross_mount_boundaries = options.cross_mount_boundaries;
 
     config
 }
@@ -193,7 +249,11 @@ fn find_envs(
         _ => None,
     };
     let stdio_reporter = Arc::new(stdio::create_reporter(options.print_list, kind));
-    let reporter = CacheReporter::new(stdio_reporter.clone());
+    let reporter = CacheReporter::new(Arc::new(LineageReporter::new(
+        root::create_reporter(stdio_reporter.clone(), environment),
+        locators.clone(),
+        environment.file_system(),
+    )));
 
     let summary =
         find_and_report_envs(&reporter, config, locators, environment, search_scope, None);
@@ -201,10 +261,24 @@ fn find_envs(
         // By now all conda envs have been found
         // Spawn conda
         // & see if we can find more environments by spawning conda.
-        let _ =
-            conda_locator.find_and_report_missing_envs(&reporter, options.conda_executable.clone());
-        let _ = poetry_locator
-            .find_and_report_missing_envs(&reporter, options.poetry_executable.clone());
+        let _ = conda_locator.find_and_report_missing_envs(
+            &TaggingReporter::new(
+                &reporter,
+                LocatorKind::Conda,
+                locators,
+                environment.file_system(),
+            ),
+            options.conda_executable.clone(),
+        );
+        let _ = poetry_locator.find_and_report_missing_envs(
+            &TaggingReporter::new(
+                &reporter,
+                LocatorKind::Poetry,
+                locators,
+                environment.file_system(),
+            ),
+            options.poetry_executable.clone(),
+        );
     }
 
     if options.print_summary {
@@ -214,7 +288,17 @@ fn find_envs(
             println!("Breakdown by each locator:");
             println!("--------------------------");
             for locator in summary.locators.iter() {
-                println!("{:<20} : {:?}", format!("{:?}", locator.0), locator.1);
+                let queue_wait = summary
+                    .locators_queue_wait
+                    .get(locator.0)
+                    .copied()
+                    .unwrap_or_default();
+                println!(
+                    "{:<20} : {:?} (waited {:?})",
+                    format!("{:?}", locator.0),
+                    locator.1,
+                    queue_wait
+                );
             }
             println!()
         }
@@ -306,14 +390,32 @@ fn find_envs_json(
     search_scope: Option<SearchScope>,
 ) {
     let collect_reporter = Arc::new(collect::create_reporter());
-    let reporter = CacheReporter::new(collect_reporter.clone());
+    let reporter = CacheReporter::new(Arc::new(LineageReporter::new(
+        root::create_reporter(collect_reporter.clone(), environment),
+        locators.clone(),
+        environment.file_system(),
+    )));
 
     find_and_report_envs(&reporter, config, locators, environment, search_scope, None);
     if options.report_missing {
-        let _ =
-            conda_locator.find_and_report_missing_envs(&reporter, options.conda_executable.clone());
-        let _ = poetry_locator
-            .find_and_report_missing_envs(&reporter, options.poetry_executable.clone());
+        let _ = conda_locator.find_and_report_missing_envs(
+            &TaggingReporter::new(
+                &reporter,
+                LocatorKind::Conda,
+                locators,
+                environment.file_system(),
+            ),
+            options.conda_executable.clone(),
+        );
+        let _ = poetry_locator.find_and_report_missing_envs(
+            &TaggingReporter::new(
+                &reporter,
+                LocatorKind::Poetry,
+                locators,
+                environment.file_system(),
+            ),
+            options.poetry_executable.clone(),
+        );
     }
 
     let managers = collect_reporter
@@ -375,24 +477,25 @@ pub fn resolve_report_stdio(
         locator.configure(&config);
     }
 
-    if let Some(result) = resolve_environment(&executable, &locators, &environment) {
-        let env = &result.resolved.unwrap_or(result.discovered);
-        if json {
-            println!(
-                "{}",
-                serde_json::to_string_pretty(env).expect("failed to serialize environment as JSON")
-            );
-        } else {
-            println!("Environment found for {executable:?}");
-            if let Some(manager) = &env.manager {
-                reporter.report_manager(manager);
+    match resolve_environment(&executable, &locators, &environment) {
+        Ok(result) => {
+            let env = &result.resolved.unwrap_or(result.discovered);
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(env)
+                        .expect("failed to serialize environment as JSON")
+                );
+            } else {
+                println!("Environment found for {executable:?}");
+                if let Some(manager) = &env.manager {
+                    reporter.report_manager(manager);
+                }
+                reporter.report_environment(env);
             }
-            reporter.report_environment(env);
         }
-    } else if json {
-        println!("null");
-    } else {
-        println!("No environment found for {executable:?}");
+        Err(_) if json => println!("null"),
+        Err(failure) => println!("No environment found for {executable:?} ({failure})"),
     }
 
     if !json {
@@ -402,3 +505,72 @@ pub fn resolve_report_stdio(
         )
     }
 }
+
+pub fn which_report_stdio(
+    name: String,
+    cwd: Option<PathBuf>,
+    verbose: bool,
+    cache_directory: Option<PathBuf>,
+    json: bool,
+) {
+    // Initialize tracing for performance profiling (includes log compatibility)
+    initialize_tracing(verbose);
+
+    let now = SystemTime::now();
+
+    if let Some(cache_directory) = cache_directory.clone() {
+        set_cache_directory(cache_directory);
+    }
+
+    let stdio_reporter = Arc::new(stdio::create_reporter(true, None));
+    let reporter = CacheReporter::new(stdio_reporter.clone());
+    let environment = EnvironmentApi::new();
+    let conda_locator = Arc::new(Conda::from(&environment));
+    let poetry_locator = Arc::new(Poetry::from(&environment));
+
+    let cwd = cwd.or_else(|| env::current_dir().ok()).unwrap_or_default();
+    let config = Configuration {
+        workspace_directories: Some(vec![cwd.clone()]),
+        ..Default::default()
+    };
+
+    let locators = create_locators(conda_locator.clone(), poetry_locator.clone(), &environment);
+    for locator in locators.iter() {
+        locator.configure(&config);
+    }
+
+    let result = which_environment(&name, &cwd, &locators, &environment);
+    let env = result.as_ref().and_then(|r| r.environment.as_ref());
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&env).expect("failed to serialize environment as JSON")
+        );
+        return;
+    }
+
+    match &result {
+        Some(result) => {
+            println!("{name} runs {:?}", result.command);
+            if let Some(shim) = result.shim {
+                match &result.executable {
+                    Some(executable) => println!("{shim:?} shim resolves to {executable:?}"),
+                    None => println!("Unable to resolve the {shim:?} shim"),
+                }
+            }
+            if let Some(env) = env {
+                if let Some(manager) = &env.manager {
+                    reporter.report_manager(manager);
+                }
+                reporter.report_environment(env);
+            } else {
+                println!("No environment found for {:?}", result.command);
+            }
+        }
+        None => println!("{name} not found in {cwd:?}"),
+    }
+    println!(
+        "Which completed in {}ms",
+        now.elapsed().unwrap().as_millis()
+    )
+}
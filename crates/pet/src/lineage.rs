@@ -0,0 +1,477 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use log::trace;
+use pet_core::{
+    env::PythonEnv,
+    manager::EnvManager,
+    python_environment::{get_environment_key, PythonEnvironment},
+    pyvenv_cfg::PyVenvCfg,
+    reporter::Reporter,
+    telemetry::TelemetryEvent,
+    Locator,
+};
+use pet_fs::file_system::{os_file_system, FileSystem, OsFileSystem};
+use pet_python_utils::executable::find_executable_in;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// Gets the interpreter a virtual environment was created from, as recorded in its `pyvenv.cfg`.
+/// The path is returned even if the interpreter no longer exists, as that is exactly what makes the environment unusable.
+pub fn find_base_executable(env: &PythonEnvironment) -> Option<PathBuf> {
+    find_base_executable_in(&OsFileSystem, env)
+}
+
+/// Same as `find_base_executable`, looking at the files in the given file system.
+pub fn find_base_executable_in(
+    file_system: &dyn FileSystem,
+    env: &PythonEnvironment,
+) -> Option<PathBuf> {
+    let cfg = env
+        .prefix
+        .as_ref()
+        .and_then(|prefix| PyVenvCfg::find_in(file_system, prefix))
+        .or_else(|| PyVenvCfg::find_in(file_system, env.executable.as_ref()?.parent()?))?;
+    if let Some(base_executable) = cfg.base_executable {
+        return Some(base_executable);
+    }
+    let home = cfg.home?;
+    if cfg!(unix) {
+        if let (Some(major), Some(minor)) = (cfg.version_major, cfg.version_minor) {
+            let executable = home.join(format!("python{major}.{minor}"));
+            if file_system.is_file(&executable) {
+                return Some(executable);
+            }
+        }
+    }
+    find_executable_in(file_system, &home)
+}
+
+/// Gets the ID of the environment the virtual environment was created from.
+/// The base interpreter is identified using the locators, so that the ID matches the one reported for that environment,
+/// if no locator can identify it then the path to the interpreter is used.
+pub fn resolve_base_environment(
+    env: &PythonEnvironment,
+    locators: &[Arc<dyn Locator>],
+) -> Option<PathBuf> {
+    resolve_base_environment_in(&os_file_system(), env, locators)
+}
+
+/// Same as `resolve_base_environment`, looking at the files in the given file system.
+pub fn resolve_base_environment_in(
+    file_system: &Arc<dyn FileSystem>,
+    env: &PythonEnvironment,
+    locators: &[Arc<dyn Locator>],
+) -> Option<PathBuf> {
+    let base_executable = find_base_executable_in(file_system.as_ref(), env)?;
+    let id = identify_base_executable(file_system, &base_executable, locators)
+        .unwrap_or(base_executable);
+    // A virtual environment cannot be its own base.
+    Some(id).filter(|id| !is_same_environment(env, id))
+}
+
+fn identify_base_executable(
+    file_system: &Arc<dyn FileSystem>,
+    executable: &Path,
+    locators: &[Arc<dyn Locator>],
+) -> Option<PathBuf> {
+    if !file_system.is_file(executable) {
+        return None;
+    }
+    let env = PythonEnv::new_in(file_system.clone(), executable.to_path_buf(), None, None);
+    // Only use the locators, this must not spawn Python.
+    let base = locators.iter().find_map(|locator| locator.try_from(&env))?;
+    trace!(
+        "Base interpreter {:?} identified as {:?}",
+        executable,
+        base.kind
+    );
+    get_environment_key(&base)
+}
+
+fn is_same_environment(env: &PythonEnvironment, id: &Path) -> bool {
+    get_environment_key(env).as_deref() == Some(id)
+        || env
+            .symlinks
+            .as_ref()
+            .is_some_and(|symlinks| symlinks.iter().any(|symlink| symlink == id))
+}
+
+/// Decorator that sets the base environment of the virtual environments before they are reported.
+pub struct LineageReporter {
+    reporter: Arc<dyn Reporter>,
+    locators: Arc<Vec<Arc<dyn Locator>>>,
+    /// File system the environments were found in, e.g. that of an alternate root directory.
+    file_system: Arc<dyn FileSystem>,
+    /// Many virtual environments are created from the same interpreter, identify each one only once.
+    base_environments: Mutex<HashMap<PathBuf, PathBuf>>,
+}
+
+impl LineageReporter {
+    pub fn new(
+        reporter: Arc<dyn Reporter>,
+        locators: Arc<Vec<Arc<dyn Locator>>>,
+        file_system: Arc<dyn FileSystem>,
+    ) -> Self {
+        Self {
+            reporter,
+            locators,
+            file_system,
+            base_environments: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn get_base_environment(&self, env: &PythonEnvironment) -> Option<PathBuf> {
+        let base_executable = find_base_executable_in(self.file_system.as_ref(), env)?;
+        let cached = self
+            .base_environments
+            .lock()
+            .unwrap()
+            .get(&base_executable)
+            .cloned();
+        let id = match cached {
+            Some(id) => id,
+            None => {
+                let id =
+                    identify_base_executable(&self.file_system, &base_executable, &self.locators)
+                        .unwrap_or(base_executable.clone());
+                self.base_environments
+                    .lock()
+                    .unwrap()
+                    .insert(base_executable, id.clone());
+                id
+            }
+        };
+        Some(id).filter(|id| !is_same_environment(env, id))
+    }
+}
+
+impl Reporter for LineageReporter {
+    fn report_manager(&self, manager: &EnvManager) {
+        self.reporter.report_manager(manager);
+    }
+
+    fn report_environment(&self, env: &PythonEnvironment) {
+        if env.base_environment.is_some() {
+            self.reporter.report_environment(env);
+            return;
+        }
+        match self.get_base_environment(env) {
+            Some(base_environment) => {
+                let mut env = env.clone();
+                env.base_environment = Some(base_environment);
+                self.reporter.report_environment(&env);
+            }
+            None => self.reporter.report_environment(env),
+        }
+    }
+
+    fn report_telemetry(&self, event: &TelemetryEvent) {
+        self.reporter.report_telemetry(event);
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentGraphNode {
+    /// ID of the environment, i.e. the executable or the prefix.
+    pub id: PathBuf,
+    /// Not set when the environment is referenced as the base of another environment, but was not discovered,
+    /// e.g. the interpreter has since been uninstalled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<PythonEnvironment>,
+    /// The environments created from this environment.
+    pub children: Vec<EnvironmentGraphNode>,
+}
+
+/// Builds the tree of environments, with the virtual environments nested under the environment they were created from.
+pub fn build_environment_graph(environments: &[PythonEnvironment]) -> Vec<EnvironmentGraphNode> {
+    let environments: Vec<(PathBuf, &PythonEnvironment)> = environments
+        .iter()
+        .filter_map(|env| Some((get_environment_key(env)?, env)))
+        .collect();
+
+    // The base of an environment could be referenced by any of its executables.
+    let mut ids: HashMap<&Path, usize> = HashMap::new();
+    for (index, (_, env)) in environments.iter().enumerate() {
+        for alias in env.symlinks.iter().flatten().chain(env.executable.iter()) {
+            ids.entry(alias.as_path()).or_insert(index);
+        }
+    }
+    for (index, (id, _)) in environments.iter().enumerate() {
+        ids.insert(id.as_path(), index);
+    }
+
+    let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut missing: BTreeMap<&Path, Vec<usize>> = BTreeMap::new();
+    let mut roots = vec![];
+    for (index, (_, env)) in environments.iter().enumerate() {
+        match env.base_environment.as_deref() {
+            Some(base) => match ids.get(base) {
+                Some(parent) if *parent != index => {
+                    children.entry(*parent).or_default().push(index)
+                }
+                Some(_) => roots.push(index),
+                None => missing.entry(base).or_default().push(index),
+            },
+            None => roots.push(index),
+        }
+    }
+
+    let mut visited = HashSet::new();
+    let mut nodes: Vec<EnvironmentGraphNode> = roots
+        .into_iter()
+        .filter_map(|index| build_node(index, &environments, &children, &mut visited))
+        .collect();
+    for (base, indexes) in missing {
+        nodes.push(EnvironmentGraphNode {
+            id: base.to_path_buf(),
+            environment: None,
+            children: build_nodes(&indexes, &environments, &children, &mut visited),
+        });
+    }
+    // Environments that are (incorrectly) the base of each other are never reached from a root.
+    for index in 0..environments.len() {
+        if let Some(node) = build_node(index, &environments, &children, &mut visited) {
+            nodes.push(node);
+        }
+    }
+    nodes.sort_by(|a, b| a.id.cmp(&b.id));
+    nodes
+}
+
+fn build_nodes(
+    indexes: &[usize],
+    environments: &[(PathBuf, &PythonEnvironment)],
+    children: &HashMap<usize, Vec<usize>>,
+    visited: &mut HashSet<usize>,
+) -> Vec<EnvironmentGraphNode> {
+    let mut nodes: Vec<EnvironmentGraphNode> = indexes
+        .iter()
+        .filter_map(|index| build_node(*index, environments, children, visited))
+        .collect();
+    nodes.sort_by(|a, b| a.id.cmp(&b.id));
+    nodes
+}
+
+fn build_node(
+    index: usize,
+    environments: &[(PathBuf, &PythonEnvironment)],
+    children: &HashMap<usize, Vec<usize>>,
+    visited: &mut HashSet<usize>,
+) -> Option<EnvironmentGraphNode> {
+    if !visited.insert(index) {
+        return None;
+    }
+    let (id, env) = &environments[index];
+    Some(EnvironmentGraphNode {
+        id: id.clone(),
+        environment: Some((*env).clone()),
+        children: build_nodes(
+            children.get(&index).map(Vec::as_slice).unwrap_or_default(),
+            environments,
+            children,
+            visited,
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pet_core::{
+        python_environment::{PythonEnvironmentBuilder, PythonEnvironmentKind},
+        LocatorKind,
+    };
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn python_exe(dir: &Path) -> PathBuf {
+        if cfg!(windows) {
+            dir.join("python.exe")
+        } else {
+            dir.join("python")
+        }
+    }
+
+    fn create_base(root: &Path) -> PathBuf {
+        let bin = root.join("base").join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        let executable = python_exe(&bin);
+        fs::write(&executable, "").unwrap();
+        executable
+    }
+
+    fn create_venv(root: &Path, name: &str, base_executable: &Path) -> PythonEnvironment {
+        let prefix = root.join(name);
+        let bin = prefix.join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        fs::write(python_exe(&bin), "").unwrap();
+        fs::write(
+            prefix.join("pyvenv.cfg"),
+            format!(
+                "home = {}\nversion = 3.12.4\n",
+                base_executable.parent().unwrap().display()
+            ),
+        )
+        .unwrap();
+        PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Venv))
+            .executable(Some(python_exe(&bin)))
+            .prefix(Some(prefix))
+            .build()
+    }
+
+    struct BaseLocator {
+        executable: PathBuf,
+        symlink: PathBuf,
+    }
+
+    impl Locator for BaseLocator {
+        fn get_kind(&self) -> LocatorKind {
+            LocatorKind::LinuxGlobal
+        }
+        fn supported_categories(&self) -> Vec<PythonEnvironmentKind> {
+            vec![PythonEnvironmentKind::LinuxGlobal]
+        }
+        fn try_from(&self, env: &PythonEnv) -> Option<PythonEnvironment> {
+            if env.executable != self.executable {
+                return None;
+            }
+            Some(
+                PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::LinuxGlobal))
+                    .executable(Some(self.symlink.clone()))
+                    .symlinks(Some(vec![self.executable.clone()]))
+                    .build(),
+            )
+        }
+        fn find(&self, _reporter: &dyn Reporter) {}
+    }
+
+    #[test]
+    fn base_executable_is_found_in_home() {
+        let root = TempDir::new().unwrap();
+        let base = create_base(root.path());
+        let venv = create_venv(root.path(), ".venv", &base);
+
+        assert_eq!(find_base_executable(&venv), Some(base));
+    }
+
+    #[test]
+    fn base_environment_uses_the_id_of_the_identified_environment() {
+        let root = TempDir::new().unwrap();
+        let base = create_base(root.path());
+        let venv = create_venv(root.path(), ".venv", &base);
+        let symlink = PathBuf::from("/usr/bin/python3");
+        let locators: Vec<Arc<dyn Locator>> = vec![Arc::new(BaseLocator {
+            executable: base.clone(),
+            symlink: symlink.clone(),
+        })];
+
+        assert_eq!(resolve_base_environment(&venv, &locators), Some(symlink));
+        assert_eq!(resolve_base_environment(&venv, &[]), Some(base));
+    }
+
+    #[test]
+    fn environments_without_pyvenv_cfg_have_no_base() {
+        let root = TempDir::new().unwrap();
+        let base = create_base(root.path());
+        let env = PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::LinuxGlobal))
+            .executable(Some(base.clone()))
+            .prefix(Some(base.parent().unwrap().parent().unwrap().to_path_buf()))
+            .build();
+
+        assert_eq!(resolve_base_environment(&env, &[]), None);
+    }
+
+    #[test]
+    fn graph_nests_virtual_environments_under_their_base() {
+        let base = PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::Pyenv))
+            .executable(Some(PathBuf::from("/pyenv/versions/3.10.4/bin/python")))
+            .symlinks(Some(vec![PathBuf::from(
+                "/pyenv/versions/3.10.4/bin/python3.10",
+            )]))
+            .build();
+        let venv = |name: &str, base: &str| PythonEnvironment {
+            executable: Some(PathBuf::from(format!("/{name}/bin/python"))),
+            kind: Some(PythonEnvironmentKind::Venv),
+            base_environment: Some(PathBuf::from(base)),
+            ..Default::default()
+        };
+        let environments = vec![
+            venv("b", "/pyenv/versions/3.10.4/bin/python3.10"),
+            base.clone(),
+            venv("a", "/pyenv/versions/3.10.4/bin/python"),
+            venv("nested", "/a/bin/python"),
+            venv("orphan", "/uninstalled/bin/python"),
+        ];
+
+        let graph = build_environment_graph(&environments);
+
+        assert_eq!(graph.len(), 2);
+        assert_eq!(
+            graph[0].id,
+            PathBuf::from("/pyenv/versions/3.10.4/bin/python")
+        );
+        assert_eq!(graph[0].environment, Some(base));
+        let children: Vec<_> = graph[0].children.iter().map(|c| c.id.clone()).collect();
+        assert_eq!(
+            children,
+            vec![
+                PathBuf::from("/a/bin/python"),
+                PathBuf::from("/b/bin/python")
+            ]
+        );
+        assert_eq!(
+            graph[0].children[0].children[0].id,
+            PathBuf::from("/nested/bin/python")
+        );
+        assert_eq!(graph[1].id, PathBuf::from("/uninstalled/bin/python"));
+        assert_eq!(graph[1].environment, None);
+        assert_eq!(graph[1].children[0].id, PathBuf::from("/orphan/bin/python"));
+    }
+
+    #[test]
+    fn graph_includes_environments_that_are_the_base_of_each_other() {
+        let env = |name: &str, base: &str| PythonEnvironment {
+            executable: Some(PathBuf::from(format!("/{name}/bin/python"))),
+            base_environment: Some(PathBuf::from(base)),
+            ..Default::default()
+        };
+        let environments = vec![env("a", "/b/bin/python"), env("b", "/a/bin/python")];
+
+        let graph = build_environment_graph(&environments);
+
+        assert_eq!(graph.len(), 1);
+        assert_eq!(graph[0].children.len(), 1);
+    }
+
+    struct CollectReporter(Mutex<Vec<PythonEnvironment>>);
+    impl Reporter for CollectReporter {
+        fn report_manager(&self, _manager: &EnvManager) {}
+        fn report_environment(&self, env: &PythonEnvironment) {
+            self.0.lock().unwrap().push(env.clone());
+        }
+        fn report_telemetry(&self, _event: &TelemetryEvent) {}
+    }
+
+    #[test]
+    fn lineage_reporter_sets_base_environment() {
+        let root = TempDir::new().unwrap();
+        let base = create_base(root.path());
+        let first = create_venv(root.path(), "first", &base);
+        let second = create_venv(root.path(), "second", &base);
+        let collect = Arc::new(CollectReporter(Mutex::new(vec![])));
+        let reporter = LineageReporter::new(collect.clone(), Arc::new(vec![]), os_file_system());
+
+        reporter.report_environment(&first);
+        reporter.report_environment(&second);
+
+        let reported = collect.0.lock().unwrap();
+        assert_eq!(reported[0].base_environment, Some(base.clone()));
+        assert_eq!(reported[1].base_environment, Some(base));
+    }
+}
@@ -3,23 +3,29 @@
 
 use log::{info, trace};
 use pet_conda::Conda;
-use pet_core::arch::Architecture;
 use pet_core::env::PythonEnv;
+use pet_core::manager::EnvManager;
 use pet_core::os_environment::Environment;
 use pet_core::python_environment::{
     PythonEnvironment, PythonEnvironmentBuilder, PythonEnvironmentKind,
 };
-use pet_core::Locator;
+use pet_core::reporter::Reporter;
+use pet_core::telemetry::TelemetryEvent;
+use pet_core::{Locator, LocatorKind};
+use pet_external::ExternalLocator;
+use pet_fs::file_system::FileSystem;
 use pet_hatch::Hatch;
 use pet_linux_global_python::LinuxGlobalPython;
 use pet_mac_commandlinetools::MacCmdLineTools;
 use pet_mac_python_org::MacPythonOrg;
 use pet_mac_xcode::MacXCode;
+use pet_nox::Nox;
 use pet_pipenv::PipEnv;
 use pet_pixi::Pixi;
 use pet_poetry::Poetry;
 use pet_pyenv::PyEnv;
-use pet_python_utils::env::ResolvedPythonEnv;
+use pet_python_utils::env::{ResolvedPythonEnv, SpawnFailure};
+use pet_tox::Tox;
 use pet_uv::Uv;
 use pet_venv::Venv;
 use pet_virtualenv::VirtualEnv;
@@ -28,81 +34,415 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use tracing::{info_span, instrument};
 
+/// How the environments identified by a locator relate to the environments of the other locators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocatorScope {
+    /// When this locator identifies an environment, no other locator is asked.
+    Exclusive,
+    /// Environments created or managed by a tool, these can overlap with those of other tools
+    /// (e.g. a uv venv in a Poetry project), hence all of the matching locators of this scope are merged.
+    Managed,
+    /// Identifies any environment with a given layout (e.g. anything with a `pyvenv.cfg`),
+    /// only used when no other locator identifies the environment.
+    Generic,
+}
+
+/// The platforms a locator is used on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocatorPlatform {
+    All,
+    Windows,
+    Unix,
+    MacOS,
+    /// All Unix platforms other than macOS.
+    Linux,
+}
+
+impl LocatorPlatform {
+    pub fn is_current(&self) -> bool {
+        match self {
+            LocatorPlatform::All => true,
+            LocatorPlatform::Windows => cfg!(windows),
+            LocatorPlatform::Unix => cfg!(unix),
+            LocatorPlatform::MacOS => std::env::consts::OS == "macos",
+            LocatorPlatform::Linux => {
+                std::env::consts::OS != "macos" && std::env::consts::OS != "windows"
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LocatorOrder {
+    pub kind: LocatorKind,
+    pub scope: LocatorScope,
+    pub platform: LocatorPlatform,
+    /// Why the locator is at this position in the chain.
+    pub reason: &'static str,
+}
+
+/// The order in which the locators are asked to identify an environment, the first match is the `kind` of the environment.
+pub const LOCATOR_ORDER: &[LocatorOrder] = &[
+    LocatorOrder {
+        kind: LocatorKind::WindowsStore,
+        scope: LocatorScope::Exclusive,
+        platform: LocatorPlatform::Windows,
+        reason: "Store installs are also listed in the registry, but can only be used via the WindowsApps aliases.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::WindowsRegistry,
+        scope: LocatorScope::Exclusive,
+        platform: LocatorPlatform::Windows,
+        reason: "Installations registered as per PEP 514 (including Conda installs).",
+    },
+    LocatorOrder {
+        kind: LocatorKind::WinPython,
+        scope: LocatorScope::Exclusive,
+        platform: LocatorPlatform::Windows,
+        reason: "Portable distribution, identified by its folder layout.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::PyEnv,
+        scope: LocatorScope::Managed,
+        platform: LocatorPlatform::All,
+        reason: "Versions & pyenv-virtualenv environments under the pyenv root, these would otherwise be identified as global Python or plain virtual environments.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::Pixi,
+        scope: LocatorScope::Exclusive,
+        platform: LocatorPlatform::All,
+        reason: "Pixi environments contain a `conda-meta` directory, hence must be identified before Conda.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::Tox,
+        scope: LocatorScope::Managed,
+        platform: LocatorPlatform::All,
+        reason: "Environments in the `.tox` directory of a project, these are created with virtualenv (or uv with tox-uv) & would otherwise be identified as Uv or Venv.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::Nox,
+        scope: LocatorScope::Managed,
+        platform: LocatorPlatform::All,
+        reason: "Session environments in the `.nox` directory of a project, these are virtual (or conda) environments & would otherwise be identified as Uv, Venv or Conda.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::Conda,
+        scope: LocatorScope::Managed,
+        platform: LocatorPlatform::All,
+        reason: "Conda installs & environments.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::Uv,
+        scope: LocatorScope::Managed,
+        platform: LocatorPlatform::All,
+        reason: "Virtual environments created by uv, these are identified by `pyvenv.cfg` & would otherwise be identified as Venv.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::Poetry,
+        scope: LocatorScope::Managed,
+        platform: LocatorPlatform::All,
+        reason: "Poetry environments are virtual environments, hence must be identified before the generic virtual environments.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::PipEnv,
+        scope: LocatorScope::Managed,
+        platform: LocatorPlatform::All,
+        reason: "Pipenv is a superset of VirtualEnvWrapper.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::Hatch,
+        scope: LocatorScope::Managed,
+        platform: LocatorPlatform::All,
+        reason: "A Hatch project can configure `dirs.env.virtual` to a directory that overlaps with `WORKON_HOME`, Hatch must claim its environments before VirtualEnvWrapper.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::VirtualEnvWrapper,
+        scope: LocatorScope::Managed,
+        platform: LocatorPlatform::All,
+        reason: "VirtualEnvWrapper is a superset of Venv, its environments are the ones in `WORKON_HOME`.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::Venv,
+        scope: LocatorScope::Generic,
+        platform: LocatorPlatform::All,
+        reason: "Any environment with a `pyvenv.cfg`, Venv is a superset of VirtualEnv.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::VirtualEnv,
+        scope: LocatorScope::Generic,
+        platform: LocatorPlatform::All,
+        reason: "The most generic of the virtual environments, hence the last of them.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::Homebrew,
+        scope: LocatorScope::Exclusive,
+        platform: LocatorPlatform::Unix,
+        reason: "Installations in the Homebrew prefix, before the global Python of the OS.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::MacXCode,
+        scope: LocatorScope::Exclusive,
+        platform: LocatorPlatform::MacOS,
+        reason: "The Python bundled with Xcode.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::MacCommandLineTools,
+        scope: LocatorScope::Exclusive,
+        platform: LocatorPlatform::MacOS,
+        reason: "The Python bundled with the Command Line Tools, `/usr/bin/python3` is a shim for this.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::MacPythonOrg,
+        scope: LocatorScope::Exclusive,
+        platform: LocatorPlatform::MacOS,
+        reason: "Installers from python.org.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::LinuxGlobal,
+        scope: LocatorScope::Exclusive,
+        platform: LocatorPlatform::Linux,
+        reason: "Catch all for the Python installations in the global locations of Linux, hence this must be the last of the built-in locators.",
+    },
+    LocatorOrder {
+        kind: LocatorKind::External,
+        scope: LocatorScope::Exclusive,
+        platform: LocatorPlatform::All,
+        reason: "Plugins configured by the user, each request spawns a process, hence these are only asked about the environments none of the built-in locators identify.",
+    },
+];
+
+/// Gets the position & scope of a locator, see `LOCATOR_ORDER`.
+pub fn get_locator_order(kind: LocatorKind) -> Option<&'static LocatorOrder> {
+    LOCATOR_ORDER.iter().find(|order| order.kind == kind)
+}
+
 pub fn create_locators(
     conda_locator: Arc<Conda>,
     poetry_locator: Arc<Poetry>,
     environment: &dyn Environment,
 ) -> Arc<Vec<Arc<dyn Locator>>> {
-    // NOTE: The order of the items matter.
-
-    let mut locators: Vec<Arc<dyn Locator>> = vec![];
+    let locators = LOCATOR_ORDER
+        .iter()
+        .filter(|order| order.platform.is_current())
+        .filter_map(|order| {
+            trace!("Using locator {:?}: {}", order.kind, order.reason);
+            create_locator(order.kind, &conda_locator, &poetry_locator, environment)
+        })
+        .collect();
+    Arc::new(locators)
+}
 
-    // 1. Windows store Python
-    // 2. Windows registry python
-    // 3. WinPython
-    if cfg!(windows) {
-        #[cfg(windows)]
-        use pet_windows_registry::WindowsRegistry;
-        #[cfg(windows)]
-        use pet_windows_store::WindowsStore;
-        #[cfg(windows)]
-        use pet_winpython::WinPython;
+fn create_locator(
+    kind: LocatorKind,
+    conda_locator: &Arc<Conda>,
+    poetry_locator: &Arc<Poetry>,
+    environment: &dyn Environment,
+) -> Option<Arc<dyn Locator>> {
+    match kind {
+        LocatorKind::External => Some(Arc::new(ExternalLocator::new())),
         #[cfg(windows)]
-        locators.push(Arc::new(WindowsStore::from(environment)));
+        LocatorKind::WindowsStore => {
+            Some(Arc::new(pet_windows_store::WindowsStore::from(environment)))
+        }
         #[cfg(windows)]
-        locators.push(Arc::new(WindowsRegistry::from(conda_locator.clone())));
+        LocatorKind::WindowsRegistry => Some(Arc::new(
+            pet_windows_registry::WindowsRegistry::from(conda_locator.clone()),
+        )),
         #[cfg(windows)]
-        locators.push(Arc::new(WinPython::new()));
-    }
-    // 4. Pyenv Python
-    locators.push(Arc::new(PyEnv::from(environment, conda_locator.clone())));
-
-    // 5. Pixi
-    locators.push(Arc::new(Pixi::new()));
-
-    // 6. Conda Python
-    locators.push(conda_locator);
-
-    // 7. Support for Virtual Envs
-    // The order of these matter.
-    // Basically PipEnv is a superset of VirtualEnvWrapper, which is a superset of Venv, which is a superset of VirtualEnv.
-    locators.push(Arc::new(Uv::from(environment)));
-    locators.push(poetry_locator);
-    locators.push(Arc::new(PipEnv::from(environment)));
-    // Hatch must run before VirtualEnvWrapper: a Hatch project can configure
-    // `dirs.env.virtual = "~/.virtualenvs"` (or any other directory that
-    // overlaps with `WORKON_HOME`), and we want Hatch to claim its envs
-    // first when the workspace marks them as Hatch-managed.
-    locators.push(Arc::new(Hatch::from(environment)));
-    locators.push(Arc::new(VirtualEnvWrapper::from(environment)));
-    locators.push(Arc::new(Venv::new()));
-    // VirtualEnv is the most generic, hence should be the last.
-    locators.push(Arc::new(VirtualEnv::new()));
-
-    // 8. Homebrew Python
-    if cfg!(unix) {
+        LocatorKind::WinPython => Some(Arc::new(pet_winpython::WinPython::from(environment))),
+        LocatorKind::PyEnv => Some(Arc::new(PyEnv::from(environment, conda_locator.clone()))),
+        LocatorKind::Pixi => Some(Arc::new(Pixi::new())),
+        LocatorKind::Tox => Some(Arc::new(Tox::new())),
+        LocatorKind::Nox => Some(Arc::new(Nox::new())),
+        LocatorKind::Conda => Some(conda_locator.clone()),
+        LocatorKind::Uv => Some(Arc::new(Uv::from(environment))),
+        LocatorKind::Poetry => Some(poetry_locator.clone()),
+        LocatorKind::PipEnv => Some(Arc::new(PipEnv::from(environment))),
+        LocatorKind::Hatch => Some(Arc::new(Hatch::from(environment))),
+        LocatorKind::VirtualEnvWrapper => Some(Arc::new(VirtualEnvWrapper::from(environment))),
+        LocatorKind::Venv => Some(Arc::new(Venv::new())),
+        LocatorKind::VirtualEnv => Some(Arc::new(VirtualEnv::new())),
         #[cfg(unix)]
-        use pet_homebrew::Homebrew;
-        #[cfg(unix)]
-        let homebrew_locator = Homebrew::from(environment);
-        #[cfg(unix)]
-        locators.push(Arc::new(homebrew_locator));
+        LocatorKind::Homebrew => Some(Arc::new(pet_homebrew::Homebrew::from(environment))),
+        LocatorKind::MacXCode => Some(Arc::new(MacXCode::new())),
+        LocatorKind::MacCommandLineTools => Some(Arc::new(MacCmdLineTools::from(environment))),
+        LocatorKind::MacPythonOrg => Some(Arc::new(MacPythonOrg::from(environment))),
+        LocatorKind::LinuxGlobal => Some(Arc::new(LinuxGlobalPython::from(environment))),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    }
+}
+
+/// Asks the locators to identify the environment.
+/// The first match determines the `kind`, the other matching locators of the `Managed` scope add their kind as a tag,
+/// along with the project, manager & name when not already known.
+/// Only the locators that can identify an environment of that `kind` are asked (see `can_overlap`).
+fn identify_using_locators(
+    env: &PythonEnv,
+    locators: &[Arc<dyn Locator>],
+) -> Option<PythonEnvironment> {
+    let mut locators = locators.iter();
+    let (mut identified, scope) = locators.by_ref().find_map(|loc| {
+        let locator_name = format!("{:?}", loc.get_kind());
+        let _span = info_span!("try_from_locator", locator = %locator_name).entered();
+        let identified = loc.try_from(env)?;
+        Some((identified, get_locator_scope(loc.as_ref())))
+    })?;
+    if scope == LocatorScope::Exclusive {
+        return Some(identified);
+    }
+    let kind = identified.kind;
+    for loc in locators.filter(|loc| {
+        get_locator_scope(loc.as_ref()) == LocatorScope::Managed && can_overlap(loc.as_ref(), kind)
+    }) {
+        let locator_name = format!("{:?}", loc.get_kind());
+        let _span = info_span!("try_from_locator", locator = %locator_name).entered();
+        if let Some(other) = loc.try_from(env) {
+            trace!(
+                "Env {:?} identified as {:?} is also identified as {:?}",
+                env.executable,
+                identified.kind,
+                other.kind
+            );
+            merge_environments(&mut identified, other);
+        }
     }
+    Some(identified)
+}
+
+fn get_locator_scope(locator: &dyn Locator) -> LocatorScope {
+    get_locator_order(locator.get_kind())
+        .map(|order| order.scope)
+        .unwrap_or(LocatorScope::Exclusive)
+}
+
+/// Whether the locator can identify an environment of the given kind as well, i.e. it supports that kind
+/// or both are kinds of virtual environments, the only environments several tools can share
+/// (e.g. a uv venv in a Poetry project), whereas a Conda environment is never a Poetry environment.
+fn can_overlap(locator: &dyn Locator, kind: Option<PythonEnvironmentKind>) -> bool {
+    let Some(kind) = kind else {
+        return true;
+    };
+    locator.supported_categories().iter().any(|category| {
+        *category == kind || (is_virtual_env_kind(*category) && is_virtual_env_kind(kind))
+    })
+}
+
+fn is_virtual_env_kind(kind: PythonEnvironmentKind) -> bool {
+    matches!(
+        kind,
+        PythonEnvironmentKind::Venv
+            | PythonEnvironmentKind::VirtualEnv
+            | PythonEnvironmentKind::VirtualEnvWrapper
+            | PythonEnvironmentKind::PyenvVirtualEnv
+            | PythonEnvironmentKind::Poetry
+            | PythonEnvironmentKind::Pipenv
+            | PythonEnvironmentKind::Hatch
+            | PythonEnvironmentKind::Uv
+            | PythonEnvironmentKind::UvWorkspace
+            | PythonEnvironmentKind::UvScript
+            | PythonEnvironmentKind::Tox
+            | PythonEnvironmentKind::Nox
+    )
+}
 
-    // 9. Global Mac Python
-    // 10. CommandLineTools Python & xcode
-    if std::env::consts::OS == "macos" {
-        locators.push(Arc::new(MacXCode::new()));
-        locators.push(Arc::new(MacCmdLineTools::new()));
-        locators.push(Arc::new(MacPythonOrg::new()));
+fn merge_environments(env: &mut PythonEnvironment, other: PythonEnvironment) {
+    if let Some(kind) = other.kind {
+        let tags = env.tags.get_or_insert_with(Vec::new);
+        if env.kind != Some(kind) && !tags.contains(&kind) {
+            tags.push(kind);
+        }
+        if tags.is_empty() {
+            env.tags = None;
+        }
     }
-    // 11. Global Linux Python
-    // All other Linux (not mac, & not windows)
-    // THIS MUST BE LAST
-    if std::env::consts::OS != "macos" && std::env::consts::OS != "windows" {
-        locators.push(Arc::new(LinuxGlobalPython::new()))
+    if env.project.is_none() {
+        env.project = other.project;
+    }
+    if env.manager.is_none() {
+        env.manager = other.manager;
+    }
+    if env.name.is_none() {
+        env.name = other.name;
+    }
+}
+
+/// Decorator for the environments reported by `Locator::find` of the locator `kind`,
+/// these are tagged with the other `Managed` locators that identify them (only those that `can_overlap`),
+/// as done for the environments identified using the locators (see `identify_using_locators`).
+pub struct TaggingReporter<'a> {
+    reporter: &'a dyn Reporter,
+    kind: LocatorKind,
+    locators: &'a [Arc<dyn Locator>],
+    file_system: Arc<dyn FileSystem>,
+}
+
+impl<'a> TaggingReporter<'a> {
+    pub fn new(
+        reporter: &'a dyn Reporter,
+        kind: LocatorKind,
+        locators: &'a [Arc<dyn Locator>],
+        file_system: Arc<dyn FileSystem>,
+    ) -> Self {
+        Self {
+            reporter,
+            kind,
+            locators,
+            file_system,
+        }
+    }
+}
+
+impl Reporter for TaggingReporter<'_> {
+    fn report_manager(&self, manager: &EnvManager) {
+        self.reporter.report_manager(manager);
+    }
+
+    fn report_environment(&self, env: &PythonEnvironment) {
+        let scope = get_locator_order(self.kind).map(|order| order.scope);
+        let Some(executable) = env.executable.clone() else {
+            self.reporter.report_environment(env);
+            return;
+        };
+        if scope.is_none() || scope == Some(LocatorScope::Exclusive) {
+            self.reporter.report_environment(env);
+            return;
+        }
+        let locators = self
+            .locators
+            .iter()
+            .filter(|loc| {
+                loc.get_kind() != self.kind
+                    && get_locator_scope(loc.as_ref()) == LocatorScope::Managed
+                    && can_overlap(loc.as_ref(), env.kind)
+            })
+            .collect::<Vec<_>>();
+        if locators.is_empty() {
+            self.reporter.report_environment(env);
+            return;
+        }
+        let python_env = PythonEnv::new_in(
+            self.file_system.clone(),
+            executable,
+            env.prefix.clone(),
+            env.version.clone(),
+        );
+        let mut env = env.clone();
+        for loc in locators {
+            if let Some(other) = loc.try_from(&python_env) {
+                merge_environments(&mut env, other);
+            }
+        }
+        self.reporter.report_environment(&env);
+    }
+
+    fn report_telemetry(&self, event: &TelemetryEvent) {
+        self.reporter.report_telemetry(event);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.reporter.is_cancelled()
     }
-    Arc::new(locators)
 }
 
 /// Identify the Python environment using the locators.
@@ -113,19 +453,24 @@ pub fn identify_python_environment_using_locators(
     locators: &[Arc<dyn Locator>],
     global_env_search_paths: &[PathBuf],
 ) -> Option<PythonEnvironment> {
+    try_identify_python_environment_using_locators(env, locators, global_env_search_paths).ok()
+}
+
+/// Same as `identify_python_environment_using_locators`, with the reason why Python could not be
+/// spawned when none of the locators know this environment.
+pub fn try_identify_python_environment_using_locators(
+    env: &PythonEnv,
+    locators: &[Arc<dyn Locator>],
+    global_env_search_paths: &[PathBuf],
+) -> Result<PythonEnvironment, SpawnFailure> {
     let executable = env.executable.clone();
     trace!(
         "Identifying Python environment using locators: {:?}",
         executable
     );
 
-    // Try each locator and record which one matches
-    for loc in locators.iter() {
-        let locator_name = format!("{:?}", loc.get_kind());
-        let _span = info_span!("try_from_locator", locator = %locator_name).entered();
-        if let Some(env) = loc.try_from(env) {
-            return Some(env);
-        }
+    if let Some(env) = identify_using_locators(env, locators) {
+        return Ok(env);
     }
 
     trace!(
@@ -138,41 +483,39 @@ pub fn identify_python_environment_using_locators(
     // We try to get the interpreter info, hoping that the real exe returned might be identifiable.
     let _resolve_span =
         info_span!("resolve_python_env", executable = %executable.display()).entered();
-    if let Some(resolved_env) = ResolvedPythonEnv::from(&executable) {
-        let env = resolved_env.to_python_env();
-        if let Some(env) = locators.iter().find_map(|loc| loc.try_from(&env)) {
-            trace!("Env ({:?}) in Path resolved as {:?}", executable, env.kind);
-            // TODO: Telemetry point.
-            // As we had to spawn earlier.
-            return Some(env);
-        } else {
-            // We have no idea what this is.
-            // We have check all of the resolvers.
-            // Telemetry point, failed to identify env here.
-            let mut fallback_kind = None;
-
-            // If one of the symlinks are in the PATH variable, then we can treat this as a GlobalPath kind.
-            let symlinks = [
-                resolved_env.symlinks.clone().unwrap_or_default(),
-                vec![resolved_env.executable.clone(), executable.clone()],
-            ]
-            .concat();
-            for symlink in symlinks {
-                if let Some(bin) = symlink.parent() {
-                    if global_env_search_paths.contains(&bin.to_path_buf()) {
-                        fallback_kind = Some(PythonEnvironmentKind::GlobalPaths);
-                        break;
-                    }
+    let resolved_env = ResolvedPythonEnv::resolve_in(env.file_system.as_ref(), &executable)?;
+    let env = resolved_env.to_python_env();
+    if let Some(env) = identify_using_locators(&env, locators) {
+        trace!("Env ({:?}) in Path resolved as {:?}", executable, env.kind);
+        // TODO: Telemetry point.
+        // As we had to spawn earlier.
+        Ok(env)
+    } else {
+        // We have no idea what this is.
+        // We have check all of the resolvers.
+        // Telemetry point, failed to identify env here.
+        let mut fallback_kind = None;
+
+        // If one of the symlinks are in the PATH variable, then we can treat this as a GlobalPath kind.
+        let symlinks = [
+            resolved_env.symlinks.clone().unwrap_or_default(),
+            vec![resolved_env.executable.clone(), executable.clone()],
+        ]
+        .concat();
+        for symlink in symlinks {
+            if let Some(bin) = symlink.parent() {
+                if global_env_search_paths.contains(&bin.to_path_buf()) {
+                    fallback_kind = Some(PythonEnvironmentKind::GlobalPaths);
+                    break;
                 }
             }
-            info!(
-                "Env ({:?}) in Path resolved as {:?} and reported as {:?}",
-                executable, resolved_env, fallback_kind
-            );
-            return Some(create_unknown_env(resolved_env, fallback_kind));
         }
+        info!(
+            "Env ({:?}) in Path resolved as {:?} and reported as {:?}",
+            executable, resolved_env, fallback_kind
+        );
+        Ok(create_unknown_env(resolved_env, fallback_kind))
     }
-    None
 }
 
 fn create_unknown_env(
@@ -191,14 +534,10 @@ fn create_unknown_env(
     symlinks.dedup();
 
     PythonEnvironmentBuilder::new(fallback_category)
+        .arch(Some(resolved_env.architecture()))
         .symlinks(Some(symlinks))
         .executable(Some(resolved_env.executable))
         .prefix(Some(resolved_env.prefix))
-        .arch(Some(if resolved_env.is64_bit {
-            Architecture::X64
-        } else {
-            Architecture::X86
-        }))
         .version(Some(resolved_env.version))
         .build()
 }
@@ -215,9 +554,9 @@ fn find_symlinks(executable: &PathBuf) -> Option<Vec<PathBuf>> {
 
     use pet_fs::path::resolve_symlink;
     use pet_python_utils::executable::find_executables;
-    use std::fs;
+    use std::fs::canonicalize;
 
-    let real_exe = resolve_symlink(executable).or(fs::canonicalize(executable).ok());
+    let real_exe = resolve_symlink(executable).or(canonicalize(executable).ok());
 
     let bin = executable.parent()?;
     // Make no assumptions that bin is always where exes are in linux
@@ -228,7 +567,7 @@ fn find_symlinks(executable: &PathBuf) -> Option<Vec<PathBuf>> {
 
     let mut symlinks = vec![];
     for exe in find_executables(bin) {
-        let symlink = resolve_symlink(&exe).or(fs::canonicalize(&exe).ok());
+        let symlink = resolve_symlink(&exe).or(canonicalize(&exe).ok());
         if symlink == real_exe {
             symlinks.push(exe);
         }
@@ -243,3 +582,286 @@ fn find_symlinks(_executable: &PathBuf) -> Option<Vec<PathBuf>> {
     // Lets wait and see if this is necessary.
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pet_core::manager::EnvManagerType;
+    use pet_fs::file_system::os_file_system;
+    use pet_reporter::collect;
+    use std::path::Path;
+
+    fn position(kind: LocatorKind) -> usize {
+        LOCATOR_ORDER
+            .iter()
+            .position(|order| order.kind == kind)
+            .unwrap()
+    }
+
+    #[test]
+    fn locator_order_lists_each_locator_once() {
+        let mut kinds: Vec<LocatorKind> = LOCATOR_ORDER.iter().map(|order| order.kind).collect();
+        kinds.sort();
+        kinds.dedup();
+        assert_eq!(kinds.len(), LOCATOR_ORDER.len());
+    }
+
+    #[test]
+    fn locator_order_rules() {
+        assert_eq!(LOCATOR_ORDER.last().unwrap().kind, LocatorKind::External);
+        assert_eq!(
+            LOCATOR_ORDER[LOCATOR_ORDER.len() - 2].kind,
+            LocatorKind::LinuxGlobal
+        );
+        assert!(position(LocatorKind::Pixi) < position(LocatorKind::Conda));
+        assert!(position(LocatorKind::Tox) < position(LocatorKind::Uv));
+        assert!(position(LocatorKind::Nox) < position(LocatorKind::Conda));
+        assert!(position(LocatorKind::Nox) < position(LocatorKind::Uv));
+        assert!(position(LocatorKind::PipEnv) < position(LocatorKind::VirtualEnvWrapper));
+        assert!(position(LocatorKind::Hatch) < position(LocatorKind::VirtualEnvWrapper));
+        assert!(position(LocatorKind::Venv) < position(LocatorKind::VirtualEnv));
+        // The generic locators come after all of the locators of the tools managing virtual environments.
+        let first_generic = LOCATOR_ORDER
+            .iter()
+            .position(|order| order.scope == LocatorScope::Generic)
+            .unwrap();
+        assert!(LOCATOR_ORDER
+            .iter()
+            .skip(first_generic)
+            .all(|order| order.scope != LocatorScope::Managed));
+    }
+
+    #[test]
+    fn locators_are_created_in_the_documented_order() {
+        let environment = pet_core::os_environment::EnvironmentApi::new();
+        let conda_locator = Arc::new(Conda::from(&environment));
+        let poetry_locator = Arc::new(Poetry::from(&environment));
+        let locators = create_locators(conda_locator, poetry_locator, &environment);
+
+        let expected: Vec<LocatorKind> = LOCATOR_ORDER
+            .iter()
+            .filter(|order| order.platform.is_current())
+            .map(|order| order.kind)
+            .collect();
+        let actual: Vec<LocatorKind> = locators.iter().map(|locator| locator.get_kind()).collect();
+        assert_eq!(actual, expected);
+    }
+
+    struct TestLocator {
+        kind: LocatorKind,
+        environment: PythonEnvironment,
+    }
+
+    impl Locator for TestLocator {
+        fn get_kind(&self) -> LocatorKind {
+            self.kind
+        }
+        fn supported_categories(&self) -> Vec<PythonEnvironmentKind> {
+            self.environment.kind.into_iter().collect()
+        }
+        fn try_from(&self, env: &PythonEnv) -> Option<PythonEnvironment> {
+            if self.environment.executable.as_ref() == Some(&env.executable) {
+                Some(self.environment.clone())
+            } else {
+                None
+            }
+        }
+        fn find(&self, _reporter: &dyn Reporter) {}
+    }
+
+    fn locator(
+        kind: LocatorKind,
+        env_kind: PythonEnvironmentKind,
+        executable: &Path,
+        project: Option<&str>,
+        manager: Option<EnvManager>,
+    ) -> Arc<dyn Locator> {
+        Arc::new(TestLocator {
+            kind,
+            environment: PythonEnvironment {
+                executable: Some(executable.to_path_buf()),
+                kind: Some(env_kind),
+                project: project.map(PathBuf::from),
+                manager,
+                ..Default::default()
+            },
+        })
+    }
+
+    #[test]
+    fn all_matching_managed_locators_are_merged() {
+        let executable = PathBuf::from("/project/.venv/bin/python");
+        let poetry = EnvManager::new(
+            PathBuf::from("/usr/bin/poetry"),
+            EnvManagerType::Poetry,
+            None,
+        );
+        let locators = vec![
+            locator(
+                LocatorKind::Uv,
+                PythonEnvironmentKind::Uv,
+                &executable,
+                None,
+                None,
+            ),
+            locator(
+                LocatorKind::Poetry,
+                PythonEnvironmentKind::Poetry,
+                &executable,
+                Some("/project"),
+                Some(poetry.clone()),
+            ),
+            locator(
+                LocatorKind::Venv,
+                PythonEnvironmentKind::Venv,
+                &executable,
+                None,
+                None,
+            ),
+        ];
+
+        let env = identify_python_environment_using_locators(
+            &PythonEnv::new(executable, None, None),
+            &locators,
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(env.kind, Some(PythonEnvironmentKind::Uv));
+        assert_eq!(env.tags, Some(vec![PythonEnvironmentKind::Poetry]));
+        assert_eq!(env.project, Some(PathBuf::from("/project")));
+        assert_eq!(env.manager, Some(poetry));
+    }
+
+    #[test]
+    fn exclusive_locators_are_not_merged() {
+        let executable = PathBuf::from("/usr/bin/python3");
+        let locators = vec![
+            locator(
+                LocatorKind::External,
+                PythonEnvironmentKind::External,
+                &executable,
+                None,
+                None,
+            ),
+            locator(
+                LocatorKind::Poetry,
+                PythonEnvironmentKind::Poetry,
+                &executable,
+                Some("/project"),
+                None,
+            ),
+        ];
+
+        let env = identify_python_environment_using_locators(
+            &PythonEnv::new(executable, None, None),
+            &locators,
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(env.kind, Some(PythonEnvironmentKind::External));
+        assert_eq!(env.tags, None);
+        assert_eq!(env.project, None);
+    }
+
+    #[test]
+    fn environments_found_by_managed_locators_are_tagged() {
+        let executable = PathBuf::from("/project/.venv/bin/python");
+        let locators = vec![
+            locator(
+                LocatorKind::Uv,
+                PythonEnvironmentKind::Uv,
+                &executable,
+                None,
+                None,
+            ),
+            locator(
+                LocatorKind::Poetry,
+                PythonEnvironmentKind::Poetry,
+                &executable,
+                Some("/project"),
+                None,
+            ),
+            locator(
+                LocatorKind::Venv,
+                PythonEnvironmentKind::Venv,
+                &executable,
+                None,
+                None,
+            ),
+        ];
+        let collect_reporter = collect::create_reporter();
+
+        TaggingReporter::new(
+            &collect_reporter,
+            LocatorKind::Poetry,
+            &locators,
+            os_file_system(),
+        )
+        .report_environment(&PythonEnvironment {
+            executable: Some(executable),
+            kind: Some(PythonEnvironmentKind::Poetry),
+            project: Some(PathBuf::from("/project")),
+            ..Default::default()
+        });
+
+        let environments = collect_reporter.environments.lock().unwrap();
+        assert_eq!(environments.len(), 1);
+        assert_eq!(environments[0].kind, Some(PythonEnvironmentKind::Poetry));
+        assert_eq!(environments[0].tags, Some(vec![PythonEnvironmentKind::Uv]));
+    }
+
+    #[test]
+    fn only_locators_of_overlapping_kinds_are_merged() {
+        let executable = PathBuf::from("/project/.venv/bin/python");
+        let locators = vec![
+            locator(
+                LocatorKind::Conda,
+                PythonEnvironmentKind::Conda,
+                &executable,
+                None,
+                None,
+            ),
+            locator(
+                LocatorKind::Poetry,
+                PythonEnvironmentKind::Poetry,
+                &executable,
+                Some("/project"),
+                None,
+            ),
+            locator(
+                LocatorKind::Uv,
+                PythonEnvironmentKind::Uv,
+                &executable,
+                None,
+                None,
+            ),
+        ];
+
+        let env = identify_python_environment_using_locators(
+            &PythonEnv::new(executable.clone(), None, None),
+            &locators,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(env.kind, Some(PythonEnvironmentKind::Conda));
+        assert_eq!(env.tags, None);
+        assert_eq!(env.project, None);
+
+        let collect_reporter = collect::create_reporter();
+        TaggingReporter::new(
+            &collect_reporter,
+            LocatorKind::Poetry,
+            &locators,
+            os_file_system(),
+        )
+        .report_environment(&PythonEnvironment {
+            executable: Some(executable),
+            kind: Some(PythonEnvironmentKind::Poetry),
+            ..Default::default()
+        });
+        let environments = collect_reporter.environments.lock().unwrap();
+        assert_eq!(environments[0].tags, Some(vec![PythonEnvironmentKind::Uv]));
+    }
+}
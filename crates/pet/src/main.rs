@@ -4,19 +4,32 @@
 use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
-use jsonrpc::start_jsonrpc_server;
-use pet::{find_and_report_envs_stdio, resolve_report_stdio, FindOptions};
+use jsonrpc::{start_jsonrpc_daemon, start_jsonrpc_server};
+use pet::capture::{capture_stdio, replay_stdio};
+use pet::trace::{finish_trace_file, set_trace_file, TraceFileFormat};
+use pet::{find_and_report_envs_stdio, resolve_report_stdio, which_report_stdio, FindOptions};
 use pet_core::python_environment::PythonEnvironmentKind;
+use pet_core::NetworkFilesystemPolicy;
+use pet_jsonrpc::{daemon, transport::Listener};
 
 mod find;
 mod jsonrpc;
 mod locators;
+mod pool;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+
+    /// Writes the spans (e.g. of each locator) to this file, to be viewed as a flamegraph.
+    #[arg(long, global = true, env = "PET_TRACE_FILE")]
+    trace_file: Option<PathBuf>,
+
+    /// Format of the trace file.
+    #[arg(long, global = true, value_enum, default_value_t = TraceFileFormat::Chrome)]
+    trace_file_format: TraceFileFormat,
 }
 
 #[derive(Subcommand, Debug)]
@@ -24,6 +37,7 @@ enum Commands {
     /// Finds the environments and reports them to the standard output.
     Find {
         /// List of files/folders to search for environments.
+        /// Scripts with inline metadata (PEP 723) are reported as the environment `uv run` runs them in.
         /// The current directory is automatically used as a workspace folder if none provided.
         #[arg(value_name = "SEARCH PATHS")]
         search_paths: Option<Vec<PathBuf>>,
@@ -44,6 +58,10 @@ enum Commands {
         #[arg(short, long)]
         report_missing: bool,
 
+        /// Levels of sub directories of the workspace directories searched for environments (defaults to 1).
+        #[arg(long, env = "PET_WORKSPACE_SEARCH_DEPTH")]
+        workspace_search_depth: Option<usize>,
+
         /// Exclusively search just the workspace directories.
         /// I.e. exclude all global environments.
         #[arg(short, long, conflicts_with = "kind")]
@@ -74,10 +92,33 @@ enum Commands {
         /// Use comma-separated values when setting via the environment variable.
         #[arg(long, env = "PET_ENVIRONMENT_DIRECTORIES", value_delimiter = ',')]
         environment_directories: Option<Vec<PathBuf>>,
+
+        /// Executables that locate additional environments (plugins), see the `pet-external` crate.
+        /// Use comma-separated values when setting via the environment variable.
+        #[arg(long, env = "PET_EXTERNAL_LOCATORS", value_delimiter = ',')]
+        external_locators: Option<Vec<PathBuf>>,
+
+        /// Search this directory instead of `/`, e.g. an unpacked container image.
+        /// Search paths are then paths within the image & Python is never spawned.
+        #[arg(long, value_name = "DIR")]
+        root: Option<PathBuf>,
+
+        /// Number of threads searching for environments (defaults to the number of CPUs, at least 4).
+        #[arg(long, env = "PET_DISCOVERY_THREADS")]
+        threads: Option<usize>,
+
+        /// What to do with workspace, environment & locator (e.g. ~/.pyenv/versions) directories on network (NFS, SMB, etc) or FUSE (e.g. sshfs) file systems.
+        #[arg(long, value_enum, env = "PET_NETWORK_FILESYSTEM_POLICY")]
+        network_filesystem_policy: Option<NetworkFilesystemPolicy>,
+
+        /// Search the sub directories of the workspace directories that are on other file systems (mounts).
+        #[arg(long)]
+        cross_mount_boundaries: bool,
     },
     /// Resolves & reports the details of the the environment to the standard output.
     Resolve {
-        /// Fully qualified path to the Python executable
+        /// Fully qualified path to the Python executable,
+        /// or to a script with inline metadata (PEP 723) to resolve the environment `uv run` runs it in.
         #[arg(value_name = "PYTHON EXE")]
         executable: PathBuf,
 
@@ -93,18 +134,95 @@ enum Commands {
         #[arg(short, long)]
         json: bool,
     },
+    /// Resolves & reports the Python environment a command (e.g. `python3`) would run.
+    /// Activated environments and `PATH` are searched in order, and pyenv, mise & asdf shims are expanded.
+    Which {
+        /// Name of the command to look up.
+        #[arg(value_name = "NAME", default_value = "python")]
+        name: String,
+
+        /// Directory the command would be run from (defaults to the current directory).
+        /// Shims such as pyenv use this to pick the interpreter.
+        #[arg(long)]
+        cwd: Option<PathBuf>,
+
+        /// Directory to cache the environment information after spawning Python.
+        #[arg(short, long, env = "PET_CACHE_DIRECTORY")]
+        cache_directory: Option<PathBuf>,
+
+        /// Whether to display verbose output (defaults to warnings).
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Output results as JSON.
+        #[arg(short, long)]
+        json: bool,
+    },
     /// Starts the JSON RPC Server.
     Server,
+    /// Starts the JSON RPC Server as a daemon, serving any number of clients over a socket.
+    /// Each client has its own configuration, refreshes are shared by clients with the same configuration.
+    /// Only the current user can connect, and clients must send the token of the daemon in `initialize`.
+    Daemon {
+        /// Path of the Unix domain socket to listen on (only accessible by the current user).
+        /// Defaults to `daemon.sock` in `$XDG_RUNTIME_DIR/pet` (else `<temp>/pet-<uid>`).
+        #[cfg(unix)]
+        #[arg(long, conflicts_with = "port")]
+        socket: Option<PathBuf>,
+
+        /// Name of the named pipe to listen on, defaults to `\\.\pipe\pet-daemon-<user>`.
+        #[cfg(windows)]
+        #[arg(long, conflicts_with = "port")]
+        pipe: Option<String>,
+
+        /// TCP port to listen on, on the loopback interface only (0 picks a free port).
+        /// The address is printed to the standard output once listening.
+        #[arg(long)]
+        port: Option<u16>,
+
+        /// File the token clients must send in `initialize` is written to (only readable by the current user).
+        /// Defaults to `daemon.token` next to the default socket (`%LOCALAPPDATA%\pet` on Windows).
+        #[arg(long)]
+        token_file: Option<PathBuf>,
+    },
+    /// Captures an anonymized snapshot of the layout of the Python environments into a tarball,
+    /// used to reproduce discovery issues on another machine with `--replay`.
+    Capture {
+        /// List of folders to capture as workspace folders.
+        /// The current directory is automatically used as a workspace folder if none provided.
+        #[arg(value_name = "SEARCH PATHS", conflicts_with = "replay")]
+        search_paths: Option<Vec<PathBuf>>,
+
+        /// Path of the tarball to create.
+        #[arg(short, long, default_value = "pet-capture.tar")]
+        output: PathBuf,
+
+        /// Runs discovery against a snapshot instead, and reports the environments found as JSON.
+        #[arg(long, value_name = "SNAPSHOT")]
+        replay: Option<PathBuf>,
+
+        /// Display verbose output (defaults to warnings).
+        #[arg(short, long)]
+        verbose: bool,
+    },
 }
 
 fn main() {
     let cli = Cli::parse();
 
+    if let Some(trace_file) = &cli.trace_file {
+        if let Err(err) = set_trace_file(Some(trace_file), cli.trace_file_format) {
+            eprintln!("Failed to create the trace file {trace_file:?}: {err}");
+            std::process::exit(1);
+        }
+    }
+
     match cli.command.unwrap_or(Commands::Find {
         list: true,
         verbose: false,
         report_missing: false,
         search_paths: None,
+        workspace_search_depth: None,
         workspace: false,
         cache_directory: None,
         kind: None,
@@ -113,12 +231,18 @@ fn main() {
         pipenv_executable: None,
         poetry_executable: None,
         environment_directories: None,
+        external_locators: None,
+        root: None,
+        threads: None,
+        network_filesystem_policy: None,
+        cross_mount_boundaries: false,
     }) {
         Commands::Find {
             list,
             verbose,
             report_missing,
             search_paths,
+            workspace_search_depth,
             workspace,
             cache_directory,
             kind,
@@ -127,6 +251,11 @@ fn main() {
             pipenv_executable,
             poetry_executable,
             environment_directories,
+            external_locators,
+            root,
+            threads,
+            network_filesystem_policy,
+            cross_mount_boundaries,
         } => {
             let mut workspace_only = workspace;
             if search_paths.clone().is_some()
@@ -145,6 +274,7 @@ fn main() {
                 verbose,
                 report_missing,
                 search_paths,
+                workspace_search_depth,
                 workspace_only,
                 cache_directory,
                 kind,
@@ -153,6 +283,11 @@ fn main() {
                 pipenv_executable,
                 poetry_executable,
                 environment_directories,
+                external_locators,
+                root_directory: root,
+                discovery_threads: threads,
+                network_filesystem_policy,
+                cross_mount_boundaries,
             });
         }
         Commands::Resolve {
@@ -161,6 +296,56 @@ fn main() {
             cache_directory,
             json,
         } => resolve_report_stdio(executable, verbose, cache_directory, json),
+        Commands::Which {
+            name,
+            cwd,
+            cache_directory,
+            verbose,
+            json,
+        } => which_report_stdio(name, cwd, verbose, cache_directory, json),
         Commands::Server => start_jsonrpc_server(),
+        Commands::Daemon {
+            #[cfg(unix)]
+            socket,
+            #[cfg(windows)]
+            pipe,
+            port,
+            token_file,
+        } => {
+            let listener = match port {
+                Some(port) => Listener::bind_tcp(port),
+                #[cfg(unix)]
+                None => socket
+                    .map_or_else(daemon::get_default_socket_path, Ok)
+                    .and_then(|socket| Listener::bind_unix(&socket)),
+                #[cfg(windows)]
+                None => Listener::bind_pipe(&pipe.unwrap_or_else(daemon::get_default_pipe_name)),
+            };
+            let token = daemon::generate_token();
+            let token_file = token_file.map_or_else(daemon::get_default_token_file, Ok);
+            let result = listener.and_then(|listener| {
+                let token_file = token_file?;
+                daemon::write_token_file(&token_file, &token)?;
+                Ok((listener, token_file))
+            });
+            match result {
+                Ok((listener, token_file)) => start_jsonrpc_daemon(listener, token, &token_file),
+                Err(err) => {
+                    eprintln!("Failed to start the daemon: {err}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Capture {
+            search_paths,
+            output,
+            replay,
+            verbose,
+        } => match replay {
+            Some(snapshot) => replay_stdio(snapshot, verbose),
+            None => capture_stdio(output, search_paths, verbose),
+        },
     }
+
+    finish_trace_file();
 }
@@ -0,0 +1,68 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Threads used to search for environments.
+//!
+//! All of the discovery work (locators, search paths, workspace folders and anything they
+//! parallelize with rayon) runs in a single work-stealing pool shared by all refreshes,
+//! instead of a thread per path, which results in hundreds of threads on some machines.
+
+use log::trace;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::{
+    sync::{Arc, Mutex},
+    thread::available_parallelism,
+};
+
+/// Minimum number of threads used by default, most of the work is waiting on I/O.
+const MIN_DEFAULT_THREADS: usize = 4;
+
+static POOL: Mutex<Option<(usize, Arc<ThreadPool>)>> = Mutex::new(None);
+
+/// Number of threads used when not configured, the number of CPUs (at least 4).
+pub fn default_discovery_threads() -> usize {
+    available_parallelism()
+        .map(|threads| threads.get())
+        .unwrap_or(1)
+        .max(MIN_DEFAULT_THREADS)
+}
+
+/// Returns the pool shared by all refreshes, with the number of threads provided (or the default).
+/// The pool is re-created when the number of threads changes,
+/// a refresh in progress keeps using the previous pool until it completes.
+pub fn get_discovery_pool(threads: Option<usize>) -> Arc<ThreadPool> {
+    let threads = threads
+        .filter(|threads| *threads > 0)
+        .unwrap_or_else(default_discovery_threads);
+    let mut pool = POOL.lock().expect("discovery pool mutex poisoned");
+    if let Some((size, pool)) = pool.as_ref() {
+        if *size == threads {
+            return pool.clone();
+        }
+    }
+    trace!("Creating discovery thread pool with {} threads", threads);
+    let new_pool = Arc::new(
+        ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|index| format!("pet-discovery-{index}"))
+            .build()
+            .expect("failed to create the discovery thread pool"),
+    );
+    *pool = Some((threads, new_pool.clone()));
+    new_pool
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pool_has_the_number_of_threads_configured() {
+        assert_eq!(get_discovery_pool(Some(3)).current_num_threads(), 3);
+        assert_eq!(get_discovery_pool(Some(2)).current_num_threads(), 2);
+        assert_eq!(
+            get_discovery_pool(Some(0)).current_num_threads(),
+            default_discovery_threads()
+        );
+    }
+}
@@ -1,35 +1,112 @@
 // Copyright (c) Microsoft Corporation.
 // Licensed under the MIT License.
 
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
 
 use log::{trace, warn};
 use pet_core::{
-    arch::Architecture,
     env::PythonEnv,
     os_environment::Environment,
     python_environment::{PythonEnvironment, PythonEnvironmentBuilder},
     Locator,
 };
 use pet_env_var_path::get_search_paths_from_env_variables;
-use pet_python_utils::{env::ResolvedPythonEnv, executable::find_executable};
+use pet_python_utils::{
+    env::{ResolvedPythonEnv, SpawnFailure},
+    executable::find_executable_in,
+};
+use pet_shims::{add_shim_symlinks, ShimResolver};
+use pet_uv::script::find_script_environment;
 
-use crate::locators::identify_python_environment_using_locators;
+use crate::lineage::resolve_base_environment_in;
+use crate::locators::try_identify_python_environment_using_locators;
 
 #[derive(Debug)]
 pub struct ResolvedEnvironment {
     pub discovered: PythonEnvironment,
     pub resolved: Option<PythonEnvironment>,
+    /// Why Python could not be spawned, when `resolved` is `None`.
+    pub failure: Option<SpawnFailure>,
 }
 
+/// Resolves the environment of the executable, spawning Python if need be.
+/// Fails when none of the locators know this environment & Python could not be spawned either.
 pub fn resolve_environment(
     executable: &PathBuf,
     locators: &Arc<Vec<Arc<dyn Locator>>>,
     os_environment: &dyn Environment,
-) -> Option<ResolvedEnvironment> {
+) -> Result<ResolvedEnvironment, SpawnFailure> {
+    resolve_environment_visiting(executable, locators, os_environment, &mut HashSet::new())
+}
+
+/// Same as `resolve_environment`, without following the shims & scripts already `visited`
+/// on the way to this executable (e.g. two scoop shims pointing to one another).
+fn resolve_environment_visiting(
+    executable: &PathBuf,
+    locators: &Arc<Vec<Arc<dyn Locator>>>,
+    os_environment: &dyn Environment,
+    visited: &mut HashSet<PathBuf>,
+) -> Result<ResolvedEnvironment, SpawnFailure> {
+    let follow = visited.insert(executable.clone());
+    if !follow {
+        warn!(
+            "Not following {:?} again, the shims or scripts leading to it form a cycle",
+            executable
+        );
+    }
+    // Shims (pyenv, pyenv-win, scoop) are resolved to the interpreter they would run
+    // and reported as a symlink of that environment.
+    if let Some(target) = follow
+        .then(|| ShimResolver::from(os_environment).resolve(executable, None))
+        .flatten()
+        .filter(|target| target != executable)
+    {
+        trace!("Resolved shim {:?} to {:?}", executable, target);
+        let mut result = resolve_environment_visiting(&target, locators, os_environment, visited)?;
+        add_shim_symlinks(&mut result.discovered, std::slice::from_ref(executable));
+        if let Some(resolved) = result.resolved.as_mut() {
+            add_shim_symlinks(resolved, std::slice::from_ref(executable));
+        }
+        return Ok(result);
+    }
+    // Scripts with inline metadata (PEP 723) are resolved to the environment uv runs them in.
+    if let Some(script_env) = follow
+        .then(|| find_script_environment(os_environment, executable))
+        .flatten()
+    {
+        let Some(script_executable) = script_env.executable.clone() else {
+            return Ok(ResolvedEnvironment {
+                discovered: script_env,
+                resolved: None,
+                failure: None,
+            });
+        };
+        trace!(
+            "Resolved script {:?} to {:?}",
+            executable,
+            script_executable
+        );
+        let mut result =
+            resolve_environment_visiting(&script_executable, locators, os_environment, visited)?;
+        link_to_script(&mut result.discovered, &script_env);
+        if let Some(resolved) = result.resolved.as_mut() {
+            link_to_script(resolved, &script_env);
+        }
+        return Ok(result);
+    }
     // First check if executable is actually a file or a path.
+    let file_system = os_environment.file_system();
     let mut executable = executable.to_owned();
-    if executable.is_dir() {
+    if file_system.is_dir(&executable) {
         trace!(
             "Looking to resolve Python executable in provided directory, {:?}, file = {:?}, sylink = {:?}, metadata = {:?}",
             executable,
@@ -37,7 +114,7 @@ pub fn resolve_environment(
             executable.is_symlink(),
             executable.metadata(),
         );
-        executable = match find_executable(&executable) {
+        executable = match find_executable_in(file_system.as_ref(), &executable) {
             Some(exe) => exe,
             None => {
                 warn!("Could not find Python executable in {:?}", executable);
@@ -50,78 +127,154 @@ pub fn resolve_environment(
         );
     }
     // First check if this is a known environment
-    let env = PythonEnv::new(executable.to_owned(), None, None);
+    let env = PythonEnv::new_in(file_system.clone(), executable.to_owned(), None, None);
     trace!(
         "In resolve_environment, looking for Python Env {:?} in {:?}",
         env,
         executable
     );
     let global_env_search_paths: Vec<PathBuf> = get_search_paths_from_env_variables(os_environment);
+    let mut env =
+        try_identify_python_environment_using_locators(&env, locators, &global_env_search_paths)
+            .inspect_err(|failure| warn!("Unknown Python Env {:?}: {}", executable, failure))?;
+    if env.base_environment.is_none() {
+        env.base_environment = resolve_base_environment_in(&file_system, &env, locators);
+    }
+    // Ok we got the environment.
+    // Now try to resolve this fully, by spawning python.
+    let Some(executable) = env.executable.clone() else {
+        warn!("Unknown Python Env {:?} resolved as {:?}", executable, env);
+        return Ok(ResolvedEnvironment {
+            discovered: env,
+            resolved: None,
+            failure: None,
+        });
+    };
+    match ResolvedPythonEnv::resolve_in(file_system.as_ref(), &executable) {
+        Ok(info) => {
+            trace!(
+                "In resolve_environment, Resolved Python Exe {:?} as {:?}",
+                executable,
+                info
+            );
+            let discovered = env.clone();
+            let mut symlinks = env.symlinks.clone().unwrap_or_default();
+            symlinks.push(info.executable.clone());
+            symlinks.append(&mut info.symlinks.clone().unwrap_or_default());
+            symlinks.sort();
+            symlinks.dedup();
 
-    if let Some(env) =
-        identify_python_environment_using_locators(&env, locators, &global_env_search_paths)
-    {
-        // Ok we got the environment.
-        // Now try to resolve this fully, by spawning python.
-        if let Some(ref executable) = env.executable {
-            if let Some(info) = ResolvedPythonEnv::from(executable) {
-                trace!(
-                    "In resolve_environment, Resolved Python Exe {:?} as {:?}",
-                    executable,
-                    info
-                );
-                let discovered = env.clone();
-                let mut symlinks = env.symlinks.clone().unwrap_or_default();
-                symlinks.push(info.executable.clone());
-                symlinks.append(&mut info.symlinks.clone().unwrap_or_default());
-                symlinks.sort();
-                symlinks.dedup();
-
-                let version = Some(info.version.clone());
-                let prefix = Some(info.prefix.clone());
-                let arch = Some(if info.is64_bit {
-                    Architecture::X64
-                } else {
-                    Architecture::X86
-                });
-
-                let resolved = PythonEnvironmentBuilder::new(env.kind)
-                    .arch(arch)
-                    .display_name(env.display_name)
-                    .executable(Some(info.executable.clone()))
-                    .manager(env.manager)
-                    .name(env.name)
-                    .prefix(prefix)
-                    .project(env.project)
-                    .symlinks(Some(symlinks))
-                    .version(version)
-                    .build();
-
-                info.add_to_cache(resolved.clone());
-
-                Some(ResolvedEnvironment {
-                    discovered,
-                    resolved: Some(resolved),
-                })
-            } else {
-                Some(ResolvedEnvironment {
-                    discovered: env,
-                    resolved: None,
-                })
+            let version = Some(info.version.clone());
+            let prefix = Some(info.prefix.clone());
+            let arch = Some(info.architecture());
+
+            let resolved = PythonEnvironmentBuilder::new(env.kind)
+                .tags(env.tags)
+                .external_kind(env.external_kind)
+                .arch(arch)
+                .display_name(env.display_name)
+                .executable(Some(info.executable.clone()))
+                .manager(env.manager)
+                .name(env.name)
+                .prefix(prefix)
+                .project(env.project)
+                .symlinks(Some(symlinks))
+                .version(version)
+                .base_environment(env.base_environment)
+                .build();
+
+            info.add_to_cache_in(file_system.as_ref(), resolved.clone());
+
+            Ok(ResolvedEnvironment {
+                discovered,
+                resolved: Some(resolved),
+                failure: None,
+            })
+        }
+        Err(failure) => {
+            // Surface the reason, unless the locator already knows what is wrong with this env.
+            if env.error.is_none() {
+                env.error = Some(failure.to_string());
             }
-        } else {
-            warn!("Unknown Python Env {:?} resolved as {:?}", executable, env);
-            Some(ResolvedEnvironment {
+            Ok(ResolvedEnvironment {
                 discovered: env,
                 resolved: None,
+                failure: Some(failure),
             })
         }
-    } else {
-        warn!("Unknown Python Env {:?}", executable);
-        None
     }
 }
 
+/// Reports the environment as that of the script, i.e. the environment uv runs the script in.
+fn link_to_script(env: &mut PythonEnvironment, script_env: &PythonEnvironment) {
+    env.kind = script_env.kind;
+    env.name = script_env.name.clone();
+    env.script = script_env.script.clone();
+}
+
+/// Maximum number of Python executables spawned at the same time when resolving many executables.
+pub const MAX_RESOLVE_CONCURRENCY: usize = 8;
+
+pub fn default_resolve_concurrency() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(MAX_RESOLVE_CONCURRENCY)
+}
+
+/// The concurrency requested (or the default), at most `MAX_RESOLVE_CONCURRENCY`.
+pub fn get_resolve_concurrency(concurrency: Option<usize>) -> usize {
+    concurrency
+        .unwrap_or_else(default_resolve_concurrency)
+        .clamp(1, MAX_RESOLVE_CONCURRENCY)
+}
+
+/// Groups the executables that resolve to the same environment, i.e. duplicates &
+/// executables in the same directory that are symlinks of each other (e.g. `bin/python` & `bin/python3`).
+/// The groups & their items are in the order in which the executables were first provided.
+pub fn group_executables(executables: &[PathBuf]) -> Vec<Vec<PathBuf>> {
+    let mut groups: Vec<Vec<PathBuf>> = vec![];
+    let mut keys: HashMap<(Option<PathBuf>, PathBuf), usize> = HashMap::new();
+    let mut seen: HashSet<&PathBuf> = HashSet::new();
+    for executable in executables {
+        if !seen.insert(executable) {
+            continue;
+        }
+        // Symlinks in different directories are not grouped, as the symlink of a virtual environment
+        // points to the base interpreter, yet is a different environment.
+        let target = fs::canonicalize(executable).unwrap_or(executable.clone());
+        let key = (executable.parent().map(Path::to_path_buf), target);
+        match keys.get(&key) {
+            Some(index) => groups[*index].push(executable.clone()),
+            None => {
+                keys.insert(key, groups.len());
+                groups.push(vec![executable.clone()]);
+            }
+        }
+    }
+    groups
+}
+
+/// Calls `f` for each of the items, with at most `concurrency` calls running at the same time.
+/// The calls run on the rayon pool of the caller (the global pool when not called from a pool),
+/// hence fewer run at the same time when the pool has fewer threads.
+pub fn for_each_with_concurrency<T, F>(items: &[T], concurrency: usize, f: F)
+where
+    T: Sync,
+    F: Fn(&T) + Sync,
+{
+    let next = AtomicUsize::new(0);
+    rayon::scope(|s| {
+        for _ in 0..concurrency.clamp(1, items.len().max(1)) {
+            s.spawn(|_| {
+                while let Some(item) = items.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    f(item);
+                }
+            });
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -168,6 +321,26 @@ mod tests {
         fn find(&self, _reporter: &dyn Reporter) {}
     }
 
+    /// A test locator that recognizes any executable as an environment of a plugin.
+    struct ExternalLocator;
+    impl Locator for ExternalLocator {
+        fn get_kind(&self) -> LocatorKind {
+            LocatorKind::External
+        }
+        fn supported_categories(&self) -> Vec<PythonEnvironmentKind> {
+            vec![PythonEnvironmentKind::External]
+        }
+        fn try_from(&self, env: &PythonEnv) -> Option<PythonEnvironment> {
+            Some(
+                PythonEnvironmentBuilder::new(Some(PythonEnvironmentKind::External))
+                    .external_kind(Some("CompanyBundle".to_string()))
+                    .executable(Some(env.executable.clone()))
+                    .build(),
+            )
+        }
+        fn find(&self, _reporter: &dyn Reporter) {}
+    }
+
     #[test]
     fn resolve_does_not_reject_non_standard_executable_names() {
         // Issue #375: DCC tools like mayapy.exe and hython.exe should not be
@@ -198,7 +371,7 @@ mod tests {
         // (resolved will be None because there's no real Python to spawn,
         // but the environment should be discovered).
         assert!(
-            result.is_some(),
+            result.is_ok(),
             "resolve_environment should not reject non-standard executable names like {:?}",
             exe_name
         );
@@ -234,8 +407,152 @@ mod tests {
         // locator chain was reached despite the non-standard name.
         let result = resolve_environment(&nonexistent, &locators, &env);
         assert!(
-            result.is_some(),
+            result.is_ok(),
             "non-standard executable name should reach the locator chain"
         );
     }
+
+    #[test]
+    fn resolve_reports_scoop_shim_as_symlink_of_target() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let shims = tmp.path().join("shims");
+        let app = tmp.path().join("apps").join("python").join("current");
+        std::fs::create_dir_all(&shims).unwrap();
+        std::fs::create_dir_all(&app).unwrap();
+        let target = app.join("python.exe");
+        let shim = shims.join("python.exe");
+        std::fs::write(&target, "fake").unwrap();
+        std::fs::write(&shim, "fake").unwrap();
+        std::fs::write(
+            shims.join("python.shim"),
+            format!("path = \"{}\"", target.display()),
+        )
+        .unwrap();
+
+        let locators: Arc<Vec<Arc<dyn Locator>>> =
+            Arc::new(vec![Arc::new(AcceptAllLocator) as Arc<dyn Locator>]);
+
+        let result = resolve_environment(&shim, &locators, &EmptyEnvironment).unwrap();
+
+        assert_eq!(result.discovered.executable, Some(target.clone()));
+        let symlinks = result.discovered.symlinks.unwrap();
+        assert!(symlinks.contains(&shim));
+        assert!(symlinks.contains(&target));
+    }
+
+    #[test]
+    fn resolve_does_not_follow_shims_pointing_to_one_another() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let first = tmp.path().join("first").join("shims");
+        let second = tmp.path().join("second").join("shims");
+        std::fs::create_dir_all(&first).unwrap();
+        std::fs::create_dir_all(&second).unwrap();
+        let first_shim = first.join("python.exe");
+        let second_shim = second.join("python.exe");
+        std::fs::write(&first_shim, "fake").unwrap();
+        std::fs::write(&second_shim, "fake").unwrap();
+        std::fs::write(
+            first.join("python.shim"),
+            format!("path = \"{}\"", second_shim.display()),
+        )
+        .unwrap();
+        std::fs::write(
+            second.join("python.shim"),
+            format!("path = \"{}\"", first_shim.display()),
+        )
+        .unwrap();
+
+        let locators: Arc<Vec<Arc<dyn Locator>>> =
+            Arc::new(vec![Arc::new(AcceptAllLocator) as Arc<dyn Locator>]);
+
+        let result = resolve_environment(&first_shim, &locators, &EmptyEnvironment).unwrap();
+
+        assert_eq!(result.discovered.executable, Some(first_shim.clone()));
+        let symlinks = result.discovered.symlinks.unwrap();
+        assert!(symlinks.contains(&second_shim));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolve_keeps_the_kind_reported_by_a_plugin() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let executable = tmp.path().join("python");
+        // Prints the interpreter details, as the script run by pet would.
+        std::fs::write(
+            &executable,
+            format!(
+                "#!/bin/sh\necho 093385e9-59f7-4a16-a604-14bf206256fe\n\
+                echo '{{\"version\": \"3.12.1\", \"sys_prefix\": \"{}\", \"executable\": \"{}\", \"is64_bit\": true}}'\n",
+                tmp.path().display(),
+                executable.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&executable, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let locators: Arc<Vec<Arc<dyn Locator>>> =
+            Arc::new(vec![Arc::new(ExternalLocator) as Arc<dyn Locator>]);
+
+        let result = resolve_environment(&executable, &locators, &EmptyEnvironment).unwrap();
+
+        let resolved = result.resolved.unwrap();
+        assert_eq!(resolved.kind, Some(PythonEnvironmentKind::External));
+        assert_eq!(resolved.external_kind, Some("CompanyBundle".to_string()));
+    }
+
+    #[test]
+    fn executables_are_grouped_by_symlink_set() {
+        let dir = tempfile::tempdir().unwrap();
+        let bin = dir.path().join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        let python = bin.join("python3.12");
+        fs::write(&python, "").unwrap();
+        let other = bin.join("other");
+        fs::write(&other, "").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&python, bin.join("python")).unwrap();
+        #[cfg(windows)]
+        fs::copy(&python, bin.join("python")).unwrap();
+
+        let groups = group_executables(&[
+            bin.join("python"),
+            other.clone(),
+            python.clone(),
+            bin.join("python"),
+        ]);
+
+        if cfg!(unix) {
+            assert_eq!(groups, vec![vec![bin.join("python"), python], vec![other]]);
+        } else {
+            assert_eq!(groups.len(), 3);
+        }
+    }
+
+    #[test]
+    fn resolve_concurrency_is_capped() {
+        assert_eq!(get_resolve_concurrency(Some(1000)), MAX_RESOLVE_CONCURRENCY);
+        assert_eq!(get_resolve_concurrency(Some(0)), 1);
+        assert_eq!(get_resolve_concurrency(Some(2)), 2);
+        assert!(get_resolve_concurrency(None) <= MAX_RESOLVE_CONCURRENCY);
+    }
+
+    #[test]
+    fn for_each_with_concurrency_is_bounded() {
+        let items: Vec<usize> = (0..20).collect();
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+        let total = AtomicUsize::new(0);
+
+        for_each_with_concurrency(&items, 3, |item| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            total.fetch_add(*item, Ordering::SeqCst);
+            running.fetch_sub(1, Ordering::SeqCst);
+        });
+
+        assert_eq!(total.load(Ordering::SeqCst), (0..20).sum::<usize>());
+        assert!(max_running.load(Ordering::SeqCst) <= 3);
+    }
 }
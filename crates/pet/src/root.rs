@@ -0,0 +1,113 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Searching an alternate root directory instead of `/`, e.g. an unpacked container image.
+//!
+//! The locators work with the paths of the host (`<root>/usr/bin/python3`),
+//! the environments are reported with the paths of the image (`/usr/bin/python3`).
+
+use pet_core::{
+    manager::EnvManager,
+    os_environment::{Environment, EnvironmentApi, RootedEnvironment},
+    python_environment::PythonEnvironment,
+    reporter::Reporter,
+    telemetry::TelemetryEvent,
+};
+use pet_fs::file_system::{os_file_system, FileSystem, RootedFileSystem};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Creates the environment searched for Python environments, the alternate root directory if provided.
+/// The file system of the alternate root directory maps the paths of the image to the host & disables spawning Python.
+pub fn create_environment(root_directory: Option<&Path>) -> Arc<dyn Environment> {
+    match root_directory {
+        Some(root) => Arc::new(RootedEnvironment::new(root.to_path_buf())),
+        None => Arc::new(EnvironmentApi::new()),
+    }
+}
+
+/// File system of the alternate root directory if provided, else the real file system.
+/// Used to map the paths of the image provided by the user (e.g. workspace folders) to the host.
+pub fn create_file_system(root_directory: Option<&Path>) -> Arc<dyn FileSystem> {
+    match root_directory {
+        Some(root) => Arc::new(RootedFileSystem::new(root)),
+        None => os_file_system(),
+    }
+}
+
+/// Wraps the reporter so that environments & managers are reported with the paths of the image,
+/// if the environment is that of an alternate root directory.
+pub fn create_reporter(
+    reporter: Arc<dyn Reporter>,
+    environment: &dyn Environment,
+) -> Arc<dyn Reporter> {
+    let file_system = environment.file_system();
+    if file_system.root_directory().is_some() {
+        Arc::new(ImagePathReporter {
+            reporter,
+            file_system,
+        })
+    } else {
+        reporter
+    }
+}
+
+struct ImagePathReporter {
+    reporter: Arc<dyn Reporter>,
+    file_system: Arc<dyn FileSystem>,
+}
+
+impl Reporter for ImagePathReporter {
+    fn report_manager(&self, manager: &EnvManager) {
+        self.reporter
+            .report_manager(&to_image_manager(self.file_system.as_ref(), manager));
+    }
+
+    fn report_environment(&self, env: &PythonEnvironment) {
+        self.reporter
+            .report_environment(&to_image_environment(self.file_system.as_ref(), env));
+    }
+
+    fn report_telemetry(&self, event: &TelemetryEvent) {
+        self.reporter.report_telemetry(event);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.reporter.is_cancelled()
+    }
+}
+
+fn to_image_manager(file_system: &dyn FileSystem, manager: &EnvManager) -> EnvManager {
+    EnvManager {
+        executable: file_system.to_image_path(&manager.executable),
+        ..manager.clone()
+    }
+}
+
+/// Maps the paths of an environment found in the alternate root directory of the file system to the paths of the image.
+/// Environments are returned as is when the file system has no alternate root directory.
+pub fn to_image_environment(
+    file_system: &dyn FileSystem,
+    env: &PythonEnvironment,
+) -> PythonEnvironment {
+    let to_image_path = |path: &PathBuf| file_system.to_image_path(path);
+    let map = |path: &Option<PathBuf>| path.as_ref().map(to_image_path);
+    PythonEnvironment {
+        executable: map(&env.executable),
+        prefix: map(&env.prefix),
+        project: map(&env.project),
+        base_environment: map(&env.base_environment),
+        script: map(&env.script),
+        symlinks: env
+            .symlinks
+            .as_ref()
+            .map(|symlinks| symlinks.iter().map(to_image_path).collect()),
+        manager: env
+            .manager
+            .as_ref()
+            .map(|manager| to_image_manager(file_system, manager)),
+        ..env.clone()
+    }
+}
@@ -0,0 +1,344 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Stream based discovery for async runtimes, enabled using the `async` feature.
+//!
+//! The search runs on a background thread (it is file system bound and uses blocking I/O),
+//! events are delivered through a bounded channel, i.e. when the consumer falls behind the
+//! search waits for the consumer to catch up.
+//! Dropping the stream cancels the search, i.e. no further locators or directories are searched
+//! (the work already under way is completed, e.g. a locator that has started searching).
+//!
+//! ```no_run
+//! # async fn list() {
+//! use futures_core::Stream;
+//! use pet::{finder::Finder, stream::DiscoveryEvent};
+//! use std::{future::poll_fn, pin::Pin};
+//!
+//! let mut stream = Finder::new().workspace(".").stream();
+//! while let Some(event) = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+//!     if let DiscoveryEvent::Environment(environment) = event {
+//!         println!("{:?}", environment.executable);
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{self, Receiver, SyncSender, TryRecvError},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+    thread,
+    time::{Duration, Instant},
+};
+
+use futures_core::Stream;
+use pet_core::{
+    manager::EnvManager, python_environment::PythonEnvironment, reporter::Reporter,
+    telemetry::refresh_progress::RefreshProgress, telemetry::TelemetryEvent,
+};
+
+use crate::finder::Finder;
+
+/// Number of events buffered before the search waits for the consumer.
+const DEFAULT_CAPACITY: usize = 64;
+
+static NEXT_STREAM_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+#[allow(clippy::large_enum_variant)]
+pub enum DiscoveryEvent {
+    Environment(PythonEnvironment),
+    Manager(EnvManager),
+    /// Progress of the search phases & locators.
+    Progress(RefreshProgress),
+    /// The search has completed, this is always the last event.
+    Done(Duration),
+}
+
+impl Finder {
+    /// Starts the search on a background thread and returns the events as a `Stream`.
+    pub fn stream(&self) -> DiscoveryStream {
+        self.stream_with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Same as `stream`, with the number of events buffered before the search waits for
+    /// the consumer.
+    pub fn stream_with_capacity(&self, capacity: usize) -> DiscoveryStream {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let shared = Arc::new(Shared::default());
+        let finder = self.clone();
+        let reporter = StreamReporter {
+            sender: sender.clone(),
+            shared: shared.clone(),
+            finder: finder.clone(),
+        };
+        let thread_shared = shared.clone();
+        thread::spawn(move || {
+            // Declared first so that it is dropped last, even if the search panics.
+            let _wake_on_exit = WakeOnDrop(thread_shared.clone());
+            let start = Instant::now();
+            let stream_id = NEXT_STREAM_ID.fetch_add(1, Ordering::Relaxed);
+            finder.find_and_report(Arc::new(reporter), Some(stream_id));
+            if sender.send(DiscoveryEvent::Done(start.elapsed())).is_ok() {
+                thread_shared.wake();
+            }
+        });
+        DiscoveryStream {
+            receiver,
+            shared,
+            done: false,
+        }
+    }
+}
+
+/// Events of a search started using `Finder::stream`.
+#[derive(Debug)]
+pub struct DiscoveryStream {
+    receiver: Receiver<DiscoveryEvent>,
+    shared: Arc<Shared>,
+    done: bool,
+}
+
+impl Stream for DiscoveryStream {
+    type Item = DiscoveryEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+        let event = match self.receiver.try_recv() {
+            Err(TryRecvError::Empty) => {
+                // Register before checking again, else an event sent in between is missed.
+                self.shared.register(cx.waker());
+                self.receiver.try_recv()
+            }
+            event => event,
+        };
+        match event {
+            Ok(event) => {
+                self.done = matches!(event, DiscoveryEvent::Done(_));
+                Poll::Ready(Some(event))
+            }
+            Err(TryRecvError::Empty) => Poll::Pending,
+            Err(TryRecvError::Disconnected) => {
+                self.done = true;
+                Poll::Ready(None)
+            }
+        }
+    }
+}
+
+impl Drop for DiscoveryStream {
+    fn drop(&mut self) {
+        self.shared.cancelled.store(true, Ordering::Release);
+    }
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    waker: Mutex<Option<Waker>>,
+    cancelled: AtomicBool,
+}
+
+impl Shared {
+    fn register(&self, waker: &Waker) {
+        let mut current = self.waker.lock().expect("waker mutex poisoned");
+        if !current.as_ref().is_some_and(|w| w.will_wake(waker)) {
+            *current = Some(waker.clone());
+        }
+    }
+
+    fn wake(&self) {
+        if let Some(waker) = self.waker.lock().expect("waker mutex poisoned").take() {
+            waker.wake();
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+}
+
+struct WakeOnDrop(Arc<Shared>);
+
+impl Drop for WakeOnDrop {
+    fn drop(&mut self) {
+        self.0.wake();
+    }
+}
+
+struct StreamReporter {
+    sender: SyncSender<DiscoveryEvent>,
+    shared: Arc<Shared>,
+    finder: Finder,
+}
+
+impl StreamReporter {
+    fn send(&self, event: DiscoveryEvent) {
+        if self.shared.is_cancelled() {
+            return;
+        }
+        // Blocks while the channel is full, slowing the search down to the pace of the consumer.
+        match self.sender.send(event) {
+            Ok(_) => self.shared.wake(),
+            Err(_) => self.shared.cancelled.store(true, Ordering::Release),
+        }
+    }
+}
+
+impl Reporter for StreamReporter {
+    fn report_manager(&self, manager: &EnvManager) {
+        self.send(DiscoveryEvent::Manager(manager.clone()));
+    }
+
+    fn report_environment(&self, env: &PythonEnvironment) {
+        if self.finder.is_requested_kind(env) {
+            self.send(DiscoveryEvent::Environment(env.clone()));
+        }
+    }
+
+    fn report_telemetry(&self, event: &TelemetryEvent) {
+        if let TelemetryEvent::RefreshProgress(progress) = event {
+            self.send(DiscoveryEvent::Progress(progress.clone()));
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.shared.is_cancelled()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pet_core::python_environment::PythonEnvironmentKind;
+    use std::{
+        fs,
+        task::Wake,
+        thread::Thread,
+        time::{Duration, Instant},
+    };
+    use tempfile::TempDir;
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Collects all of the events, parking the thread while the stream is pending.
+    fn collect(mut stream: DiscoveryStream) -> Vec<DiscoveryEvent> {
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let mut events = vec![];
+        loop {
+            match Pin::new(&mut stream).poll_next(&mut cx) {
+                Poll::Ready(Some(event)) => events.push(event),
+                Poll::Ready(None) => return events,
+                Poll::Pending => {
+                    assert!(Instant::now() < deadline, "stream did not complete");
+                    thread::park_timeout(Duration::from_millis(100));
+                }
+            }
+        }
+    }
+
+    fn create_reporter(capacity: usize, finder: Finder) -> (StreamReporter, DiscoveryStream) {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let shared = Arc::new(Shared::default());
+        let reporter = StreamReporter {
+            sender,
+            shared: shared.clone(),
+            finder,
+        };
+        let stream = DiscoveryStream {
+            receiver,
+            shared,
+            done: false,
+        };
+        (reporter, stream)
+    }
+
+    #[test]
+    fn reporter_sends_requested_environments_and_progress() {
+        let (reporter, stream) =
+            create_reporter(10, Finder::new().kinds([PythonEnvironmentKind::Venv]));
+        let venv = PythonEnvironment {
+            kind: Some(PythonEnvironmentKind::Venv),
+            ..Default::default()
+        };
+
+        reporter.report_environment(&PythonEnvironment {
+            kind: Some(PythonEnvironmentKind::Conda),
+            ..Default::default()
+        });
+        reporter.report_environment(&venv);
+        reporter.report_telemetry(&TelemetryEvent::SearchCompleted(Duration::from_secs(1)));
+        drop(reporter);
+
+        assert_eq!(collect(stream), vec![DiscoveryEvent::Environment(venv)]);
+    }
+
+    #[test]
+    fn dropping_the_stream_cancels_a_blocked_reporter() {
+        let (reporter, stream) = create_reporter(1, Finder::new());
+        let shared = stream.shared.clone();
+        reporter.report_environment(&PythonEnvironment::default());
+
+        // The channel is full, hence this blocks until the stream is dropped.
+        let blocked = thread::spawn(move || {
+            reporter.report_environment(&PythonEnvironment::default());
+            reporter
+        });
+        thread::sleep(Duration::from_millis(50));
+        drop(stream);
+        let reporter = blocked.join().unwrap();
+
+        assert!(shared.is_cancelled());
+        // Further events are dropped without blocking.
+        reporter.report_environment(&PythonEnvironment::default());
+    }
+
+    #[test]
+    fn stream_reports_workspace_environments_and_ends_with_done() {
+        let workspace = TempDir::new().unwrap();
+        let venv = workspace.path().join(".venv");
+        let bin = venv.join(if cfg!(windows) { "Scripts" } else { "bin" });
+        fs::create_dir_all(&bin).unwrap();
+        fs::write(venv.join("pyvenv.cfg"), "version = 3.12.1\n").unwrap();
+        fs::write(
+            bin.join(if cfg!(windows) {
+                "python.exe"
+            } else {
+                "python"
+            }),
+            "",
+        )
+        .unwrap();
+
+        let events = collect(
+            Finder::new()
+                .workspace(workspace.path())
+                .workspace_only(true)
+                .stream_with_capacity(1),
+        );
+
+        assert!(matches!(events.last(), Some(DiscoveryEvent::Done(_))));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            DiscoveryEvent::Environment(env) if env.prefix.as_ref().is_some_and(|p| p.ends_with(".venv"))
+        )));
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, DiscoveryEvent::Progress(_))));
+    }
+}
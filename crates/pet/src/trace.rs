@@ -0,0 +1,465 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Writes the `tracing` spans (e.g. `locators_phase`, `locator_find`, `resolve_python_env`) to a file,
+//! so that a slow refresh can be looked at as a flamegraph (e.g. in `chrome://tracing` or https://ui.perfetto.dev).
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::{
+    cell::Cell,
+    fmt::Debug,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Instant, SystemTime},
+};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Level, Subscriber,
+};
+use tracing_subscriber::{
+    filter::{filter_fn, LevelFilter},
+    layer::Context,
+    registry::LookupSpan,
+    Layer,
+};
+
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TraceFileFormat {
+    /// Chrome Trace Event format (JSON array of complete events).
+    #[default]
+    Chrome,
+    /// OpenTelemetry (OTLP) JSON, one `ExportTraceServiceRequest` per line.
+    Otlp,
+}
+
+static TRACE_SINK: TraceSink = TraceSink::new();
+static NEXT_SPAN_ID: AtomicU64 = AtomicU64::new(1);
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static THREAD_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Writes the spans to `path` (replacing the file), or stops writing them when `None`.
+/// The previous trace file (if any) is completed first, this is a noop if the spans are already written to `path`.
+pub fn set_trace_file(path: Option<&Path>, format: TraceFileFormat) -> io::Result<()> {
+    match path {
+        Some(path) if TRACE_SINK.is_writing_to(path, format) => {}
+        Some(path) => {
+            let file = File::create(path)?;
+            TRACE_SINK.start(Box::new(file), Some(path.to_path_buf()), format);
+        }
+        None => TRACE_SINK.stop(),
+    }
+    Ok(())
+}
+
+/// Completes the trace file, e.g. before the process exits.
+pub fn finish_trace_file() {
+    TRACE_SINK.stop();
+}
+
+/// Layer writing the spans to the trace file, spans are only recorded while there is a trace file.
+pub(crate) fn trace_file_layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    TraceFileLayer { sink: &TRACE_SINK }.with_filter(
+        filter_fn(|metadata| {
+            metadata.is_span() && *metadata.level() <= Level::INFO && TRACE_SINK.is_active()
+        })
+        .with_max_level_hint(LevelFilter::INFO),
+    )
+}
+
+struct TraceWriter {
+    path: Option<PathBuf>,
+    format: TraceFileFormat,
+    output: BufWriter<Box<dyn Write + Send>>,
+    events_written: usize,
+    started: Instant,
+}
+
+impl TraceWriter {
+    fn write_span(&mut self, span: &SpanRecord, name: &str, target: &str) -> io::Result<()> {
+        match self.format {
+            TraceFileFormat::Chrome => {
+                let event = json!({
+                    "name": name,
+                    "cat": target,
+                    "ph": "X",
+                    "ts": span.started.saturating_duration_since(self.started).as_micros() as u64,
+                    "dur": span.started.elapsed().as_micros() as u64,
+                    "pid": std::process::id(),
+                    "tid": span.thread_id,
+                    "args": Value::Object(span.fields.clone()),
+                });
+                let separator = if self.events_written == 0 {
+                    "[\n"
+                } else {
+                    ",\n"
+                };
+                write!(self.output, "{separator}{event}")?;
+            }
+            TraceFileFormat::Otlp => {
+                let end_time = span.started_at + span.started.elapsed();
+                let attributes = span
+                    .fields
+                    .iter()
+                    .map(|(key, value)| {
+                        json!({ "key": key, "value": { "stringValue": value_to_string(value) } })
+                    })
+                    .collect::<Vec<Value>>();
+                let mut otlp_span = json!({
+                    "traceId": format!("{:032x}", span.trace_id),
+                    "spanId": format!("{:016x}", span.span_id),
+                    "name": name,
+                    // SPAN_KIND_INTERNAL
+                    "kind": 1,
+                    "startTimeUnixNano": unix_nanos(span.started_at).to_string(),
+                    "endTimeUnixNano": unix_nanos(end_time).to_string(),
+                    "attributes": attributes,
+                });
+                if let Some(parent_span_id) = span.parent_span_id {
+                    otlp_span["parentSpanId"] = json!(format!("{parent_span_id:016x}"));
+                }
+                let request = json!({
+                    "resourceSpans": [{
+                        "resource": {
+                            "attributes": [
+                                { "key": "service.name", "value": { "stringValue": "pet" } },
+                                { "key": "service.version", "value": { "stringValue": env!("CARGO_PKG_VERSION") } }
+                            ]
+                        },
+                        "scopeSpans": [{
+                            "scope": { "name": target },
+                            "spans": [otlp_span]
+                        }]
+                    }]
+                });
+                writeln!(self.output, "{request}")?;
+            }
+        }
+        self.events_written += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        if self.format == TraceFileFormat::Chrome {
+            let closing = if self.events_written == 0 {
+                "[]\n"
+            } else {
+                "\n]\n"
+            };
+            self.output.write_all(closing.as_bytes())?;
+        }
+        self.output.flush()
+    }
+}
+
+struct TraceSink {
+    writer: Mutex<Option<TraceWriter>>,
+    active: AtomicBool,
+}
+
+impl TraceSink {
+    const fn new() -> Self {
+        TraceSink {
+            writer: Mutex::new(None),
+            active: AtomicBool::new(false),
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    fn is_writing_to(&self, path: &Path, format: TraceFileFormat) -> bool {
+        self.writer
+            .lock()
+            .expect("trace writer mutex poisoned")
+            .as_ref()
+            .is_some_and(|writer| writer.path.as_deref() == Some(path) && writer.format == format)
+    }
+
+    fn start(&self, output: Box<dyn Write + Send>, path: Option<PathBuf>, format: TraceFileFormat) {
+        let mut writer = self.writer.lock().expect("trace writer mutex poisoned");
+        if let Some(previous) = writer.take() {
+            if let Err(err) = previous.finish() {
+                log::warn!("Failed to complete the trace file: {err}");
+            }
+        }
+        *writer = Some(TraceWriter {
+            path,
+            format,
+            output: BufWriter::new(output),
+            events_written: 0,
+            started: Instant::now(),
+        });
+        self.active.store(true, Ordering::Relaxed);
+    }
+
+    fn stop(&self) {
+        self.active.store(false, Ordering::Relaxed);
+        let previous = self
+            .writer
+            .lock()
+            .expect("trace writer mutex poisoned")
+            .take();
+        if let Some(previous) = previous {
+            if let Err(err) = previous.finish() {
+                log::warn!("Failed to complete the trace file: {err}");
+            }
+        }
+    }
+
+    fn write_span(&self, span: &SpanRecord, name: &str, target: &str, flush: bool) {
+        let mut writer = self.writer.lock().expect("trace writer mutex poisoned");
+        if let Some(writer) = writer.as_mut() {
+            let mut result = writer.write_span(span, name, target);
+            if flush && result.is_ok() {
+                result = writer.output.flush();
+            }
+            if let Err(err) = result {
+                // Logging would be traced as well, hence only printed.
+                eprintln!("Failed to write to the trace file: {err}");
+            }
+        }
+    }
+}
+
+/// Kept in the extensions of each span recorded.
+struct SpanRecord {
+    started: Instant,
+    started_at: SystemTime,
+    thread_id: u64,
+    span_id: u64,
+    parent_span_id: Option<u64>,
+    trace_id: u128,
+    fields: Map<String, Value>,
+}
+
+struct TraceFileLayer {
+    sink: &'static TraceSink,
+}
+
+impl<S> Layer<S> for TraceFileLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let parent = span.parent().and_then(|parent| {
+            parent
+                .extensions()
+                .get::<SpanRecord>()
+                .map(|record| (record.span_id, record.trace_id))
+        });
+        let span_id = NEXT_SPAN_ID.fetch_add(1, Ordering::Relaxed);
+        let mut record = SpanRecord {
+            started: Instant::now(),
+            started_at: SystemTime::now(),
+            thread_id: current_thread_id(),
+            span_id,
+            parent_span_id: parent.map(|(span_id, _)| span_id),
+            trace_id: parent
+                .map(|(_, trace_id)| trace_id)
+                .unwrap_or_else(|| new_trace_id(span_id)),
+            fields: Map::new(),
+        };
+        attrs.record(&mut FieldVisitor(&mut record.fields));
+        span.extensions_mut().insert(record);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(record) = span.extensions_mut().get_mut::<SpanRecord>() {
+                values.record(&mut FieldVisitor(&mut record.fields));
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(record) = span.extensions_mut().remove::<SpanRecord>() else {
+            return;
+        };
+        // Flushed once the outermost span (e.g. a refresh) completes.
+        let flush = record.parent_span_id.is_none();
+        self.sink.write_span(
+            &record,
+            span.metadata().name(),
+            span.metadata().target(),
+            flush,
+        );
+    }
+}
+
+struct FieldVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0
+            .insert(field.name().to_string(), json!(format!("{value:?}")));
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn current_thread_id() -> u64 {
+    THREAD_ID.with(|id| {
+        if id.get() == 0 {
+            id.set(NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed));
+        }
+        id.get()
+    })
+}
+
+/// Unique across processes (trace ids of OTLP are expected to be globally unique).
+fn new_trace_id(span_id: u64) -> u128 {
+    ((unix_nanos(SystemTime::now()) as u128) << 64)
+        | ((std::process::id() as u128) << 32)
+        | (span_id as u128 & 0xffff_ffff)
+}
+
+fn unix_nanos(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tracing::info_span;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn trace_nested_spans(format: TraceFileFormat) -> String {
+        let sink: &'static TraceSink = Box::leak(Box::new(TraceSink::new()));
+        let buffer = SharedBuffer::default();
+        sink.start(Box::new(buffer.clone()), None, format);
+        let subscriber = tracing_subscriber::registry().with(TraceFileLayer { sink });
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _outer = info_span!("locators_phase").entered();
+            let _inner = info_span!("locator_find", locator = "Conda").entered();
+        });
+        sink.stop();
+
+        let output = buffer.0.lock().unwrap().clone();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn spans_are_written_as_chrome_trace_events() {
+        let output = trace_nested_spans(TraceFileFormat::Chrome);
+
+        let events: Vec<Value> = serde_json::from_str(&output).unwrap();
+        // Spans are written once they close, i.e. the inner span first.
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["name"], "locator_find");
+        assert_eq!(events[0]["ph"], "X");
+        assert_eq!(events[0]["args"]["locator"], "Conda");
+        assert_eq!(events[1]["name"], "locators_phase");
+        assert!(events[1]["ts"].as_u64().unwrap() <= events[0]["ts"].as_u64().unwrap());
+        assert!(events[1]["dur"].as_u64().unwrap() >= events[0]["dur"].as_u64().unwrap());
+    }
+
+    #[test]
+    fn spans_are_written_as_otlp_json_lines() {
+        let output = trace_nested_spans(TraceFileFormat::Otlp);
+
+        let spans = output
+            .lines()
+            .map(|line| {
+                let request: Value = serde_json::from_str(line).unwrap();
+                request["resourceSpans"][0]["scopeSpans"][0]["spans"][0].clone()
+            })
+            .collect::<Vec<Value>>();
+        assert_eq!(spans.len(), 2);
+        let (inner, outer) = (&spans[0], &spans[1]);
+        assert_eq!(inner["name"], "locator_find");
+        assert_eq!(inner["parentSpanId"], outer["spanId"]);
+        assert_eq!(inner["traceId"], outer["traceId"]);
+        assert!(outer.get("parentSpanId").is_none());
+        assert_eq!(
+            inner["attributes"][0],
+            json!({ "key": "locator", "value": { "stringValue": "Conda" } })
+        );
+    }
+
+    #[test]
+    fn empty_chrome_trace_is_valid_json() {
+        let sink: &'static TraceSink = Box::leak(Box::new(TraceSink::new()));
+        let buffer = SharedBuffer::default();
+        sink.start(Box::new(buffer.clone()), None, TraceFileFormat::Chrome);
+        sink.stop();
+
+        let output = buffer.0.lock().unwrap().clone();
+        let events: Vec<Value> = serde_json::from_slice(&output).unwrap();
+        assert!(events.is_empty());
+        assert!(!sink.is_active());
+    }
+
+    #[test]
+    fn setting_the_same_trace_file_again_keeps_the_spans_written() {
+        let sink: &'static TraceSink = Box::leak(Box::new(TraceSink::new()));
+        let path = PathBuf::from("trace.json");
+        sink.start(
+            Box::new(SharedBuffer::default()),
+            Some(path.clone()),
+            TraceFileFormat::Chrome,
+        );
+
+        assert!(sink.is_writing_to(&path, TraceFileFormat::Chrome));
+        assert!(!sink.is_writing_to(&path, TraceFileFormat::Otlp));
+        assert!(!sink.is_writing_to(Path::new("other.json"), TraceFileFormat::Chrome));
+    }
+}
@@ -0,0 +1,295 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use log::{trace, warn};
+use pet_core::{
+    env::PythonEnv, os_environment::Environment, python_environment::PythonEnvironment, Locator,
+};
+use pet_env_var_path::{
+    get_ordered_search_paths_from_path_variable, get_search_paths_from_env_variables,
+};
+use pet_python_utils::executable::new_silent_command;
+use pet_shims::{add_shim_symlinks, get_shim_kind, ShimKind, ShimResolver};
+
+use crate::locators::identify_python_environment_using_locators;
+
+#[derive(Debug)]
+pub struct WhichResult {
+    /// The file that would be run for the command, e.g. `~/.pyenv/shims/python3`.
+    pub command: PathBuf,
+    /// The shim manager that owns `command`, if it is a shim.
+    pub shim: Option<ShimKind>,
+    /// The real interpreter that would be run, after expanding shims.
+    /// `None` if the shim could not be expanded.
+    pub executable: Option<PathBuf>,
+    pub environment: Option<PythonEnvironment>,
+}
+
+/// Resolves the interpreter that `name` would run in `cwd` and identifies it using the locators.
+pub fn which_environment(
+    name: &str,
+    cwd: &Path,
+    locators: &Arc<Vec<Arc<dyn Locator>>>,
+    os_environment: &dyn Environment,
+) -> Option<WhichResult> {
+    let command = find_command(name, os_environment)?;
+    trace!("Command {:?} found at {:?}", name, command);
+
+    let shim = get_shim_kind(&command);
+    let executable = match shim {
+        Some(shim) => match ShimResolver::from(os_environment)
+            .resolve(&command, Some(cwd))
+            .or_else(|| expand_shim(shim, name, &command, cwd))
+        {
+            Some(executable) => executable,
+            None => {
+                warn!("Unable to expand {:?} shim {:?}", shim, command);
+                return Some(WhichResult {
+                    command,
+                    shim: Some(shim),
+                    executable: None,
+                    environment: None,
+                });
+            }
+        },
+        None => command.clone(),
+    };
+
+    let env = PythonEnv::new(executable.clone(), None, None);
+    let global_env_search_paths = get_search_paths_from_env_variables(os_environment);
+    let environment =
+        identify_python_environment_using_locators(&env, locators, &global_env_search_paths).map(
+            |mut environment| {
+                if shim.is_some() {
+                    add_shim_symlinks(&mut environment, std::slice::from_ref(&command));
+                }
+                environment
+            },
+        );
+
+    Some(WhichResult {
+        command,
+        shim,
+        executable: Some(executable),
+        environment,
+    })
+}
+
+/// Finds the file a shell would run for `name`.
+///
+/// An activated virtual environment (`VIRTUAL_ENV`) or conda environment (`CONDA_PREFIX`)
+/// takes precedence, followed by the directories of `PATH` in order.
+pub fn find_command(name: &str, os_environment: &dyn Environment) -> Option<PathBuf> {
+    let mut search_paths = vec![];
+    if let Some(venv) = os_environment.get_env_var("VIRTUAL_ENV".to_string()) {
+        let venv = PathBuf::from(venv);
+        search_paths.push(venv.join(if cfg!(windows) { "Scripts" } else { "bin" }));
+    }
+    if let Some(conda_prefix) = os_environment.get_env_var("CONDA_PREFIX".to_string()) {
+        let conda_prefix = PathBuf::from(conda_prefix);
+        if cfg!(windows) {
+            search_paths.push(conda_prefix.clone());
+        }
+        search_paths.push(conda_prefix.join("bin"));
+    }
+    search_paths.extend(get_ordered_search_paths_from_path_variable(os_environment));
+
+    let names = get_command_file_names(name);
+    search_paths
+        .iter()
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|file| is_executable_file(file))
+}
+
+/// Asks the shim manager which interpreter the shim would run in `cwd`.
+/// Only used for shims that cannot be resolved without spawning (e.g. mise & asdf).
+fn expand_shim(shim: ShimKind, name: &str, command: &Path, cwd: &Path) -> Option<PathBuf> {
+    if shim == ShimKind::Scoop {
+        return None;
+    }
+    // Prefer the tool installed alongside the shims (e.g. `~/.pyenv/bin/pyenv`), else use PATH.
+    let tool = command
+        .parent()
+        .and_then(|shims| shims.parent())
+        .map(|root| root.join("bin").join(shim.tool()))
+        .filter(|tool| tool.is_file())
+        .unwrap_or_else(|| PathBuf::from(shim.tool()));
+    let name = command
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or(name.to_string());
+
+    trace!("Expanding {:?} shim using {:?} which {}", shim, tool, name);
+    let output = new_silent_command(&tool)
+        .arg("which")
+        .arg(&name)
+        .current_dir(cwd)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        trace!(
+            "{:?} which {} failed: {}",
+            tool,
+            name,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        return None;
+    }
+    let executable = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if executable.is_empty() {
+        None
+    } else {
+        Some(PathBuf::from(executable))
+    }
+}
+
+#[cfg(windows)]
+fn get_command_file_names(name: &str) -> Vec<String> {
+    if Path::new(name).extension().is_some() {
+        vec![name.to_string()]
+    } else {
+        vec![
+            format!("{name}.exe"),
+            format!("{name}.bat"),
+            name.to_string(),
+        ]
+    }
+}
+
+#[cfg(unix)]
+fn get_command_file_names(name: &str) -> Vec<String> {
+    vec![name.to_string()]
+}
+
+#[cfg(windows)]
+fn is_executable_file(file: &Path) -> bool {
+    file.is_file()
+}
+
+#[cfg(unix)]
+fn is_executable_file(file: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    file.metadata()
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::HashMap, fs};
+    use tempfile::TempDir;
+
+    struct TestEnvironment {
+        vars: HashMap<String, String>,
+    }
+    impl Environment for TestEnvironment {
+        fn get_user_home(&self) -> Option<PathBuf> {
+            None
+        }
+        fn get_root(&self) -> Option<PathBuf> {
+            None
+        }
+        fn get_env_var(&self, key: String) -> Option<String> {
+            self.vars.get(&key).cloned()
+        }
+        fn get_know_global_search_locations(&self) -> Vec<PathBuf> {
+            vec![]
+        }
+    }
+
+    fn create_executable(dir: &Path, name: &str) -> PathBuf {
+        fs::create_dir_all(dir).unwrap();
+        let file = dir.join(if cfg!(windows) {
+            format!("{name}.exe")
+        } else {
+            name.to_string()
+        });
+        fs::write(&file, "").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&file, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        file
+    }
+
+    fn path_var(dirs: &[&Path]) -> String {
+        std::env::join_paths(dirs).unwrap().into_string().unwrap()
+    }
+
+    #[test]
+    fn find_command_uses_first_match_in_path_order() {
+        let tmp = TempDir::new().unwrap();
+        let first = tmp.path().join("first");
+        let second = tmp.path().join("second");
+        create_executable(&second, "python3");
+        let expected = create_executable(&first, "python3");
+
+        let environment = TestEnvironment {
+            vars: HashMap::from([("PATH".to_string(), path_var(&[&first, &second]))]),
+        };
+
+        let found = find_command("python3", &environment).unwrap();
+        assert_eq!(found.file_name(), expected.file_name());
+        assert!(found.starts_with(fs::canonicalize(&first).unwrap_or(first)));
+    }
+
+    #[test]
+    fn find_command_prefers_activated_virtual_env() {
+        let tmp = TempDir::new().unwrap();
+        let global = tmp.path().join("global");
+        let venv = tmp.path().join(".venv");
+        create_executable(&global, "python");
+        let expected = create_executable(
+            &venv.join(if cfg!(windows) { "Scripts" } else { "bin" }),
+            "python",
+        );
+
+        let environment = TestEnvironment {
+            vars: HashMap::from([
+                ("PATH".to_string(), path_var(&[&global])),
+                (
+                    "VIRTUAL_ENV".to_string(),
+                    venv.to_string_lossy().to_string(),
+                ),
+            ]),
+        };
+
+        assert_eq!(find_command("python", &environment), Some(expected));
+    }
+
+    #[test]
+    fn find_command_returns_none_when_not_on_path() {
+        let tmp = TempDir::new().unwrap();
+        let environment = TestEnvironment {
+            vars: HashMap::from([("PATH".to_string(), path_var(&[tmp.path()]))]),
+        };
+
+        assert_eq!(find_command("python", &environment), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn find_command_skips_non_executable_files() {
+        let tmp = TempDir::new().unwrap();
+        let first = tmp.path().join("first");
+        let second = tmp.path().join("second");
+        fs::create_dir_all(&first).unwrap();
+        fs::write(first.join("python"), "").unwrap();
+        create_executable(&second, "python");
+
+        let environment = TestEnvironment {
+            vars: HashMap::from([("PATH".to_string(), path_var(&[&first, &second]))]),
+        };
+
+        let found = find_command("python", &environment).unwrap();
+        assert!(found.starts_with(fs::canonicalize(&second).unwrap()));
+    }
+}
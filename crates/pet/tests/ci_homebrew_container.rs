@@ -26,6 +26,7 @@ fn verify_python_in_homebrew_contaner() {
     use pet_core::{
         os_environment::EnvironmentApi,
         python_environment::{PythonEnvironment, PythonEnvironmentKind},
+        python_version::PythonVersion,
     };
     use pet_poetry::Poetry;
     use pet_reporter::{cache::CacheReporter, collect};
@@ -74,7 +75,7 @@ fn verify_python_in_homebrew_contaner() {
     let python3_12 = PythonEnvironment {
         kind: Some(PythonEnvironmentKind::Homebrew),
         executable: Some(PathBuf::from("/home/linuxbrew/.linuxbrew/bin/python3.12")),
-        version: Some("3.12.8".to_string()), // This can change on CI, so we don't check it
+        version: Some("3.12.8".parse().unwrap()), // This can change on CI, so we don't check it
         symlinks: Some(vec![
             // For older versions of Python, we do not have a tonne of symlinks,
             // E.g. for 3.12.7 (which was the latest at some point, at a lot of symlinks)
@@ -90,7 +91,7 @@ fn verify_python_in_homebrew_contaner() {
     let python3_11 = PythonEnvironment {
         kind: Some(PythonEnvironmentKind::Homebrew),
         executable: Some(PathBuf::from("/home/linuxbrew/.linuxbrew/bin/python3.11")),
-        version: Some("3.11.11".to_string()), // This can change on CI, so we don't check it
+        version: Some("3.11.11".parse().unwrap()), // This can change on CI, so we don't check it
         symlinks: Some(vec![
             // For older versions of Python, we do not have a tonne of symlinks,
             // E.g. for 3.12.7 (which was the latest at some point, at a lot of symlinks)
@@ -113,11 +114,13 @@ fn verify_python_in_homebrew_contaner() {
         assert_eq!(python_env.executable, env.executable);
         assert_eq!(python_env.kind, env.kind);
         assert_eq!(python_env.manager, env.manager);
-        // Compare the first 4 parts (3.12)
-        assert_eq!(
-            python_env.version.clone().unwrap_or_default()[..4],
-            env.version.clone().unwrap_or_default()[..4]
-        );
+        // Compare the major & minor parts (3.12)
+        let major_minor = |env: &PythonEnvironment| {
+            env.version
+                .as_ref()
+                .map(|version| (version.major(), version.minor()))
+        };
+        assert_eq!(major_minor(python_env), major_minor(env));
 
         // We know the symlinks contain the full version, hence exclude the paths that contain the full version.
         let python_env_symlinks = python_env
@@ -126,11 +129,12 @@ fn verify_python_in_homebrew_contaner() {
             .unwrap_or_default()
             .into_iter()
             .filter(|p| {
-                !p.to_string_lossy()
-                    .contains(&env.version.clone().unwrap_or_default())
-                    && !p
-                        .to_string_lossy()
-                        .contains(&python_env.version.clone().unwrap_or_default())
+                let contains_version = |version: &Option<PythonVersion>| {
+                    version
+                        .as_ref()
+                        .is_some_and(|version| p.to_string_lossy().contains(version.as_str()))
+                };
+                !contains_version(&env.version) && !contains_version(&python_env.version)
             })
             .collect::<Vec<PathBuf>>();
         assert_eq!(
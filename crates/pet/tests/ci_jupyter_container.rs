@@ -57,7 +57,7 @@ fn verify_python_in_jupyter_contaner() {
         name: Some("base".to_string()),
         executable: Some(PathBuf::from("/opt/conda/bin/python")),
         prefix: Some(PathBuf::from("/opt/conda")),
-        version: Some("3.12.3".to_string()),
+        version: Some("3.12.3".parse().unwrap()),
         arch: Some(Architecture::X64),
         symlinks: Some(vec![
             PathBuf::from("/opt/conda/bin/python"),
@@ -76,7 +76,7 @@ fn verify_python_in_jupyter_contaner() {
         kind: Some(PythonEnvironmentKind::GlobalPaths),
         executable: Some(PathBuf::from("/usr/local/python/3.10.13/bin/python")),
         prefix: Some(PathBuf::from("/usr/local/python/3.10.13")),
-        version: Some("3.10.13.final.0".to_string()),
+        version: Some("3.10.13.final.0".parse().unwrap()),
         arch: Some(Architecture::X64),
         symlinks: Some(vec![
             PathBuf::from("/usr/local/python/3.10.13/bin/python"),
@@ -90,7 +90,7 @@ fn verify_python_in_jupyter_contaner() {
         kind: Some(PythonEnvironmentKind::LinuxGlobal),
         executable: Some(PathBuf::from("/usr/bin/python3")),
         prefix: Some(PathBuf::from("/usr")),
-        version: Some("3.8.10.final.0".to_string()),
+        version: Some("3.8.10.final.0".parse().unwrap()),
         arch: Some(Architecture::X64),
         symlinks: Some(vec![
             PathBuf::from("/usr/bin/python3"),
@@ -84,11 +84,19 @@ fn verify_ci_poetry_global() {
 
     poetry_envs
         .iter()
-        .find(|e| e.version.clone().unwrap_or_default().starts_with("3.12"))
+        .find(|e| {
+            e.version
+                .as_ref()
+                .is_some_and(|v| (v.major(), v.minor()) == (3, 12))
+        })
         .expect("Python 3.12 not found");
     poetry_envs
         .iter()
-        .find(|e| e.version.clone().unwrap_or_default().starts_with("3.11"))
+        .find(|e| {
+            e.version
+                .as_ref()
+                .is_some_and(|v| (v.major(), v.minor()) == (3, 11))
+        })
         .expect("Python 3.12 not found");
 }
 
@@ -158,9 +166,8 @@ fn verify_ci_poetry_project() {
     assert!(
         poetry_envs[0]
             .version
-            .clone()
-            .unwrap_or_default()
-            .starts_with("3.11"),
+            .as_ref()
+            .is_some_and(|v| (v.major(), v.minor()) == (3, 11)),
         "Python 3.11 not found"
     );
     assert_eq!(
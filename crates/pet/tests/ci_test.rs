@@ -340,7 +340,7 @@ fn verify_validity_of_interpreter_info(environment: PythonEnvironment) {
             environment.clone()
         );
     }
-    if let Some(version) = environment.clone().version {
+    if let Some(version) = environment.version.as_ref().map(ToString::to_string) {
         let expected_version = &interpreter_info.clone().sys_version;
         if !does_version_match(&version, expected_version) {
             // For pyenv environments, the version may come from the directory name
@@ -441,7 +441,10 @@ fn verify_we_can_get_same_env_info_using_find_with_exe(
     // Hence create the locators all over again.
     use pet::locators::create_locators;
     use pet_conda::Conda;
-    use pet_core::{os_environment::EnvironmentApi, Configuration};
+    use pet_core::{
+        os_environment::{Environment, EnvironmentApi},
+        Configuration,
+    };
     use std::{env, sync::Arc};
 
     let workspace_dir = PathBuf::from(env::var("GITHUB_WORKSPACE").unwrap_or_default());
@@ -469,6 +472,7 @@ fn verify_we_can_get_same_env_info_using_find_with_exe(
         &locators,
         &reporter,
         &global_env_search_paths,
+        &os_environment.file_system(),
     );
 
     let envs = collect_reporter.environments.lock().unwrap().clone();
@@ -506,9 +510,10 @@ fn compare_environments(actual: PythonEnvironment, expected: PythonEnvironment,
     //     );
     // }
 
-    if let (Some(version), Some(expected_version)) =
-        (expected.clone().version, actual.clone().version)
-    {
+    if let (Some(version), Some(expected_version)) = (
+        expected.version.as_ref().map(ToString::to_string),
+        actual.version.as_ref().map(ToString::to_string),
+    ) {
         if !does_version_match(&version, &expected_version) {
             // Pyenv directory name can differ from actual binary version at patch level.
             // See https://github.com/microsoft/python-environment-tools/issues/371
@@ -670,8 +675,8 @@ fn verify_we_can_get_same_env_info_using_resolve_with_exe(
         locator.configure(&config);
     }
 
-    let env = resolve_environment(executable, &locators, &os_environment).unwrap_or_else(|| {
-        panic!("Failed to resolve environment using `resolve` for {environment:?}")
+    let env = resolve_environment(executable, &locators, &os_environment).unwrap_or_else(|err| {
+        panic!("Failed to resolve environment using `resolve` for {environment:?}: {err}")
     });
     trace!(
         "For exe {:?} we got Environment = {:?}, To compare against {:?}",
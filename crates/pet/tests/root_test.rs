@@ -0,0 +1,78 @@
+// Copyright (c) Microsoft Corporation.
+// Licensed under the MIT License.
+
+//! Tests for `pet find --root`, searching an unpacked (fake) container image.
+//!
+//! The pet binary is spawned, so the command line options of `--root` are covered as well.
+
+#[cfg(unix)]
+#[test]
+fn find_in_alternate_root_directory() {
+    use serde_json::Value;
+    use std::{fs, os::unix::fs::symlink, process::Command};
+    use tempfile::TempDir;
+
+    let image = TempDir::new().unwrap();
+    let root = image.path();
+    let usr_bin = root.join("usr").join("bin");
+    fs::create_dir_all(&usr_bin).unwrap();
+    fs::create_dir_all(root.join("usr").join("lib").join("python3.12")).unwrap();
+    fs::write(usr_bin.join("python3.12"), "").unwrap();
+    // Absolute targets are resolved within the image, not on the host.
+    symlink("/usr/bin/python3.12", usr_bin.join("python3")).unwrap();
+
+    let venv = root.join("app").join(".venv");
+    fs::create_dir_all(venv.join("bin")).unwrap();
+    fs::write(
+        venv.join("pyvenv.cfg"),
+        "home = /usr/bin\nversion = 3.12.4\n",
+    )
+    .unwrap();
+    symlink("/usr/bin/python3.12", venv.join("bin").join("python")).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_pet"))
+        .arg("find")
+        .arg("--json")
+        .arg("--root")
+        .arg(root)
+        .arg("/app")
+        .output()
+        .expect("failed to run pet find");
+    assert!(
+        output.status.success(),
+        "pet find failed with stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let json: Value =
+        serde_json::from_slice(&output.stdout).expect("pet find stdout is not valid JSON");
+    let environments = json["environments"].as_array().unwrap();
+
+    let venv = environments
+        .iter()
+        .find(|env| env["executable"] == "/app/.venv/bin/python")
+        .unwrap_or_else(|| panic!("venv not found in {environments:?}"));
+    assert_eq!(venv["kind"], "Venv");
+    assert_eq!(venv["prefix"], "/app/.venv");
+    assert_eq!(venv["version"], "3.12.4");
+    // The symlink to `/usr/bin/python3.12` is resolved within the image, hence not broken.
+    assert!(venv["error"].is_null());
+
+    let global = environments
+        .iter()
+        .find(|env| env["kind"] == "LinuxGlobal")
+        .unwrap_or_else(|| panic!("global Python not found in {environments:?}"));
+    assert_eq!(global["prefix"], "/usr");
+    assert!(global["version"].as_str().unwrap().starts_with("3.12"));
+    let symlinks = global["symlinks"].as_array().unwrap();
+    assert!(symlinks.contains(&Value::from("/usr/bin/python3")));
+    assert!(symlinks.contains(&Value::from("/usr/bin/python3.12")));
+
+    // Nothing from the host, nor any path of the host.
+    let root = fs::canonicalize(root).unwrap();
+    for env in environments {
+        assert!(
+            !env.to_string().contains(root.to_str().unwrap()),
+            "path of the host reported in {env}"
+        );
+    }
+}